@@ -0,0 +1,127 @@
+//! A popup listing the hosting accounts and where their API tokens come from.
+
+use crate::{
+    forge::{token, Forge},
+    prelude::*,
+    t,
+};
+use ratatui::{text::Span, widgets::Clear};
+
+/// Where the token of an account comes from, if anywhere.
+pub enum Status {
+    Env(&'static str),
+    Keychain,
+    /// The forge's command line tool, such as `gh`.
+    Command(&'static str),
+    Missing,
+    /// The keychain could not be read.
+    Error(String),
+}
+
+pub struct Account {
+    pub forge: &'static str,
+    pub host: String,
+    pub token_url: String,
+    pub status: Status,
+}
+
+impl Account {
+    pub fn new(forge: &dyn Forge, host: String) -> Self {
+        let status = match token::get(forge, &host) {
+            Ok(Some(token)) => match token.source {
+                token::Source::Env(var) => Status::Env(var),
+                token::Source::Keychain => Status::Keychain,
+                token::Source::Command(program) => Status::Command(program),
+            },
+            Ok(None) => Status::Missing,
+            Err(err) => Status::Error(format!("{err:#}")),
+        };
+        Self {
+            forge: forge.name(),
+            token_url: forge.token_url(&host),
+            host,
+            status,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Accounts {
+    pub items: Vec<Account>,
+    pub state: ListState,
+}
+
+impl Accounts {
+    pub fn selected(&self) -> Option<&Account> {
+        self.state.selected().and_then(|i| self.items.get(i))
+    }
+
+    /// Replaces the accounts, keeping the selection on the same host.
+    pub fn reload(&mut self, items: Vec<Account>) {
+        let host = self.selected().map(|a| a.host.clone());
+        let idx = items.iter().position(|a| Some(&a.host) == host.as_ref());
+        self.items = items;
+        self.state
+            .select(idx.or((!self.items.is_empty()).then_some(0)));
+    }
+}
+
+pub struct AccountList<'a>(pub &'a mut Accounts);
+
+impl Widget for AccountList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let dim = Style::new().dim();
+        let items: Vec<ListItem> = self
+            .0
+            .items
+            .iter()
+            .map(|account| {
+                let status = match &account.status {
+                    Status::Env(var) => {
+                        Span::styled(t!("token from ${var}", var = var), GREEN.c300)
+                    }
+                    Status::Keychain => Span::styled(t!("token in keychain"), GREEN.c300),
+                    Status::Command(program) => {
+                        Span::styled(t!("token from {program}", program = program), GREEN.c300)
+                    }
+                    Status::Missing => Span::styled(t!("no token"), dim),
+                    Status::Error(err) => Span::styled(err.clone(), RED.c300),
+                };
+                ListItem::new(vec![
+                    Line::from(vec![
+                        Span::styled(account.host.clone(), Style::new().bold()),
+                        Span::raw("  "),
+                        Span::styled(account.forge, dim),
+                        Span::raw("  "),
+                        status,
+                    ]),
+                    Line::styled(format!("  {}", account.token_url), dim),
+                ])
+            })
+            .collect();
+        let title = match token::profile() {
+            Some(profile) => t!("Hosting accounts of profile {profile}", profile = profile),
+            None => t!("Hosting accounts").to_string(),
+        };
+        let list = List::new(items)
+            .block(
+                Block::new()
+                    .title(Line::raw(title).left_aligned())
+                    .title(
+                        Line::raw(t!("enter: set token  d: delete token  esc: close"))
+                            .right_aligned(),
+                    )
+                    .borders(Borders::ALL),
+            )
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c800))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        Clear.render(popup, buf);
+        StatefulWidget::render(list, popup, buf, &mut self.0.state);
+    }
+}
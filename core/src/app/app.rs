@@ -1,11 +1,132 @@
-use crate::{git, opts::Opts, prelude::*};
-use color_eyre::eyre::Context;
+use crate::{
+    askpass, cli, config::Config, crash, forge, fuzzy, git, logs, opts::Opts, paths, platform,
+    prelude::*, redact, t, theme,
+};
+use color_eyre::eyre::{eyre, Context};
+use ratatui::{backend::Backend, crossterm::event::KeyModifiers};
+use regex::Regex;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    time::Instant,
+};
 
-use super::branch;
+use super::{
+    accounts::{self, Accounts},
+    authors, branch, bulk,
+    checklist::{self, Checklist},
+    checks::CommitChecks,
+    clipboard, columns, commands, conflicts,
+    dashboard::Dashboard,
+    diff, dirs, divergence,
+    follow::{self, Follow},
+    gitconfig::{self, GitConfig},
+    help,
+    histories::Histories,
+    history::{self, FileHistory},
+    hooks::{self, Hooks},
+    hunks::{self, HunkView},
+    input::{self, Input},
+    jobs::{self, Jobs},
+    keymap::{self, Action, Chord, Keymap, Resolution},
+    leader,
+    lint::{self, Lint},
+    log,
+    marks::{self, Marks},
+    modal::{self, Modal, Outcome, Value},
+    mouse::{self, Click, Mouse},
+    nav::{self, Kept, Nav},
+    network::{self, Network},
+    notify::{self, Notifications},
+    output::Output,
+    palette,
+    pending::{self, Pending},
+    pickaxe,
+    query::Query,
+    range::{self, Range},
+    refs,
+    remotes::{self, Remotes},
+    rename,
+    reviews::{self, Reviews},
+    scroll,
+    settings::{self, Settings},
+    snapshot, status,
+    summary::Summary,
+    tags, taken,
+    template::{self, Template},
+    tips::Tips,
+    title,
+};
 
-const HEADER_STYLE: Style = Style::new().fg(SLATE.c100).bg(BLUE.c800);
-const NORMAL_ROW_BG: Color = SLATE.c950;
-const SELECTED_STYLE: Style = Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD);
+/// How often the event loop wakes up to check on background jobs and loading.
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often the counts of uncommitted changes and stashes are read again, to
+/// notice changes made outside grit.
+const STATUS_INTERVAL: Duration = Duration::from_secs(3);
+
+/// How long the selection has to stay put before the branch is read and its pull
+/// request looked up, so that holding `j` does not do so for every branch passed.
+const SELECTION_DEBOUNCE: Duration = Duration::from_millis(120);
+
+/// The longest time spent on input that queued up before drawing again, about a
+/// frame, so that a held key costs a frame per batch rather than per repeat.
+const INPUT_BATCH: Duration = Duration::from_millis(16);
+
+/// How long a note about the last checkout stays in the footer.
+const FOOTER_NOTE: Duration = Duration::from_secs(4);
+/// How many pull requests are looked up at once.
+const MAX_LOOKUPS: usize = 4;
+
+const MARK_STYLE: Style = Style::new().fg(AMBER.c400);
+const CONTRIBUTORS_STYLE: Style = Style::new().fg(BLUE.c200).add_modifier(Modifier::BOLD);
+const TRAILER_STYLE: Style = Style::new().fg(SLATE.c400);
+const HEAT_STYLE: Style = Style::new().fg(GREEN.c400).bg(SLATE.c900);
+const HINT_STYLE: Style = Style::new().fg(SLATE.c400).add_modifier(Modifier::ITALIC);
+/// How many panics are recovered from before giving up.
+const MAX_CRASHES: usize = 3;
+
+const GROUP_STYLE: Style = Style::new().fg(BLUE.c200).add_modifier(Modifier::BOLD);
+const TRACKING_STYLE: Style = Style::new().fg(AMBER.c200);
+const RANGE_STYLE: Style = Style::new().bg(BLUE.c900);
+/// The tree of a commit with no parent to diff against.
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+const PENDING_STYLE: Style = Style::new().fg(SLATE.c500);
+const WORKTREE_STYLE: Style = Style::new().fg(BLUE.c300);
+const NEW_STYLE: Style = Style::new().fg(GREEN.c200).add_modifier(Modifier::BOLD);
+const DIRTY_STYLE: Style = Style::new().fg(AMBER.c300);
+const OFFLINE_STYLE: Style = Style::new().fg(RED.c300).add_modifier(Modifier::BOLD);
+const STALE_STYLE: Style = Style::new().fg(SLATE.c500).add_modifier(Modifier::ITALIC);
+/// How many days after its last commit a branch is stale, unless configured.
+const STALE_DAYS: u32 = 90;
+/// With more branches than this, unless configured, only [`LOAD_FIRST`] are
+/// read at start.
+const LOAD_LIMIT: usize = 2000;
+const LOAD_FIRST: usize = 300;
+const VIEW_STYLE: Style = Style::new().fg(GREEN.c300);
+const SKELETON_STYLE: Style = Style::new().fg(SLATE.c700);
+const NOTE_STYLE: Style = Style::new().fg(AMBER.c200).add_modifier(Modifier::ITALIC);
+const CRUMB_STYLE: Style = Style::new()
+    .fg(BLUE.c200)
+    .add_modifier(Modifier::UNDERLINED);
+
+/// Git hooks by name, each with its arguments.
+type GitHooks = Vec<(&'static str, Vec<String>)>;
+
+/// What waits for a `pre-*` hook running as a job to pass.
+type AfterHook = Box<dyn FnOnce(&mut App) -> EResult<()>>;
+
+/// What waits for the command in the output pane to exit successfully.
+enum AfterOutput {
+    /// Work that writes commits once the git hooks passed, and its name.
+    Commits(String, jobs::Foreground),
+    /// The `post-checkout` hook, once git checked out the branch.
+    Checkout(String),
+}
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -16,9 +137,412 @@ pub enum Error {
     IO(#[from] io::Error),
 }
 
+/// What to go on with once the identity to commit with is fixed.
+#[derive(Clone, Copy, Debug)]
+enum Then {
+    Action(Action),
+    /// An operation on the selected commits.
+    Range(range::Op),
+}
+
+/// What to do with the value submitted by the open modal.
+enum Purpose {
+    PickSort,
+    PickView,
+    BranchName(NewBranch),
+    RenamePattern,
+    ApplyRenames(Vec<(String, String)>),
+    /// Local branches to delete, with the commit each was seen at.
+    DeleteBranches(Vec<(String, git2::Oid)>),
+    /// A local branch to delete, with the commit it was seen at, and whether
+    /// to force it although it is not merged.
+    DeleteBranch(String, git2::Oid, bool),
+    /// A remote-tracking branch whose branch on the server to delete, with
+    /// the commit it was seen at.
+    DeleteRemoteBranch(String, git2::Oid),
+    /// A local branch without an upstream, to push to the remote picked.
+    PushTo(String, Vec<String>),
+    /// A file of the work tree to put back the way it is at `HEAD`.
+    Discard(String),
+    /// The message to commit what is staged with.
+    Commit,
+    CommandLine,
+    /// Fetch refspecs for the named remote, one per line.
+    Refspecs(String),
+    Note(git2::Oid),
+    /// The message of a branch squashed down to one commit.
+    Squash(String, git::Squash),
+    /// The message of a new stash.
+    Stash,
+    /// A file to save the screen to.
+    Snapshot,
+    ExportBranches,
+    /// The title and description of a pull request, opened through the API.
+    PullRequest(NewPullRequest),
+    /// A number for the setting at this key of the config.
+    Setting(&'static str),
+    /// A value for this key of the repository's git config.
+    GitConfig(&'static str),
+    /// A value for `user.name` or `user.email` in the repository's git
+    /// config, to go on with the action after.
+    Identity {
+        key: &'static str,
+        then: Then,
+    },
+    /// A string to search the history of the selected branch for.
+    Pickaxe,
+    /// A file to show the history of, relative to the top of the work tree.
+    FileHistory,
+    /// The name of a branch to create at the commit of this ref.
+    BranchAtRef(String),
+    /// What to do about other branches having the name `name`.
+    NameTaken {
+        name: String,
+        naming: Naming,
+        choices: Vec<taken::Choice>,
+    },
+    /// Where the local branch stands in review, by index, the last to clear it.
+    BranchState(String),
+    /// The note kept on the local branch, empty to remove it.
+    BranchNote(String),
+    /// The new name of a local branch, renamed only if it still points at
+    /// `expected`.
+    RenameBranch {
+        old: String,
+        expected: Option<git2::Oid>,
+    },
+    /// The author to show the commits of, by index, `None` for all of them.
+    AuthorFilter(Vec<Option<String>>),
+    /// The commit under the cursor in each of the ways it can be copied.
+    CopyCommit(Vec<String>),
+    /// An API token for the host.
+    Token(String),
+    /// Removes the stored token of the host.
+    DeleteToken(String),
+    /// How many commits to deepen a shallow clone by, empty for all of them.
+    Deepen,
+    /// Rebases each child onto its parent, in order.
+    Restack(Vec<(String, String, git2::Oid)>),
+    /// Merges the branch into the checked out one.
+    Merge(String, git2::BranchType),
+    /// Rebases the checked out branch onto the branch.
+    Rebase(String, git2::BranchType),
+    /// The actions offered for the selected branch, by index.
+    BranchMenu(Vec<Action>),
+    /// The branch of the remote to point its `HEAD` at, by index.
+    RemoteHead {
+        remote: String,
+        branches: Vec<String>,
+    },
+    /// A range operation on these commits, oldest first.
+    Range(range::Op, Vec<git2::Oid>),
+    /// What ssh asks while fetching or pushing.
+    Askpass(askpass::Request),
+    /// The name of a remote to add, before what needed one is tried again.
+    RemoteName(Retry),
+    /// The URL of the remote of this name.
+    RemoteUrl(String, Retry),
+}
+
+/// What is tried again once a remote is added in its place.
+#[derive(Clone, Copy, Debug)]
+enum Retry {
+    Fetch,
+    Push,
+}
+
+/// Where a local branch is pushed to on a forge.
+struct Hosted {
+    forge: Arc<dyn forge::Forge>,
+    repo: forge::Repo,
+    remote: String,
+    /// The upstream, e.g. `origin/feature`.
+    upstream: String,
+    /// The name of the branch on the remote.
+    head: String,
+}
+
+/// Grey bars in the shape of commit lines, shown in place of the history of
+/// the selected branch until it is read.
+fn skeleton(width: u16) -> Vec<Line<'static>> {
+    const SUMMARIES: [usize; 6] = [34, 22, 41, 28, 37, 18];
+    SUMMARIES
+        .iter()
+        .map(|summary| {
+            let bars = ["░".repeat(19), "░".repeat(8), "░".repeat(*summary)].join("  ");
+            let bars: String = bars.chars().take(usize::from(width)).collect();
+            Line::styled(bars, SKELETON_STYLE)
+        })
+        .collect()
+}
+
+/// Summarizes where a pull request stands, e.g. `#12 approved · 2 unresolved`,
+/// and how old that is if it was fetched too long ago.
+fn review_line(review: &forge::Review, stale: Option<i64>) -> Line<'static> {
+    let (verdict, style) = match review.verdict {
+        forge::Verdict::Approved => (t!("approved"), Style::new().fg(GREEN.c300)),
+        forge::Verdict::ChangesRequested => (t!("changes requested"), Style::new().fg(RED.c300)),
+        forge::Verdict::Pending => (t!("awaiting review"), Style::new().fg(AMBER.c300)),
+    };
+    let sep = Span::styled(" · ", HINT_STYLE);
+    let mut spans = vec![
+        Span::styled(format!("#{} ", review.number), Style::new().bold()),
+        Span::styled(verdict, style),
+    ];
+    if review.draft {
+        spans.push(sep.clone());
+        spans.push(Span::styled(t!("draft"), Style::new().fg(SLATE.c400)));
+    }
+    if let Some(checks) = review.checks {
+        let (text, color) = match checks {
+            forge::Checks::Passed => (t!("checks passed"), GREEN.c300),
+            forge::Checks::Failed => (t!("checks failed"), RED.c300),
+            forge::Checks::Running => (t!("checks running"), AMBER.c300),
+        };
+        spans.push(sep.clone());
+        spans.push(Span::styled(text, Style::new().fg(color)));
+    }
+    if review.unresolved > 0 {
+        spans.push(sep.clone());
+        let text = t!("{count} unresolved", count = review.unresolved);
+        spans.push(Span::styled(text, Style::new().fg(AMBER.c300)));
+    }
+    match review.mergeable {
+        Some(false) => {
+            spans.push(sep.clone());
+            let text = t!("conflicts with base");
+            spans.push(Span::styled(text, Style::new().fg(RED.c300)));
+        }
+        Some(true) => {
+            spans.push(sep.clone());
+            spans.push(Span::styled(t!("mergeable"), Style::new().fg(GREEN.c300)));
+        }
+        None => {}
+    }
+    if let Some(fetched) = stale.and_then(|t| chrono::DateTime::from_timestamp(t, 0)) {
+        spans.push(sep.clone());
+        let time = fetched.with_timezone(&chrono::Local).format("%b %d %H:%M");
+        let text = t!("as of {time}", time = time);
+        spans.push(Span::styled(text, Style::new().fg(AMBER.c300).italic()));
+    }
+    spans.push(sep);
+    spans.push(Span::styled(review.url.clone(), HINT_STYLE));
+    Line::from(spans)
+}
+
+/// A pull request waiting for its title and description.
+struct NewPullRequest {
+    forge: Arc<dyn forge::Forge>,
+    repo: forge::Repo,
+    token: String,
+    head: String,
+    base: String,
+}
+
+/// What a branch name is asked for.
+enum Naming {
+    /// A branch from one of the list, named after the template.
+    New(NewBranch),
+    /// A branch at the commit of this ref.
+    At(String),
+    /// A new name for the local branch `old`, renamed only if it still
+    /// points at `expected`.
+    Rename {
+        old: String,
+        expected: Option<git2::Oid>,
+    },
+}
+
+/// A branch being created, filled in one template field at a time.
+struct NewBranch {
+    from: branch::Selection,
+    template: Template,
+    fields: Vec<String>,
+    values: Vec<(String, String)>,
+}
+
 pub struct App {
     repo: git::Repository,
+    config: Config,
+    hooks: Hooks,
     branch_list: branch::List,
+    keymap: Keymap,
+    chord: Chord,
+    modal: Option<(Modal, Purpose)>,
+    notifications: Notifications,
+    show_notifications: bool,
+    author_colors: bool,
+    /// The number of weeks shown in the activity column, if it is shown.
+    heat: Option<usize>,
+    /// Whether every branch is read, even past `branch.load_limit`.
+    load_all: bool,
+    show_authors: bool,
+    /// How the selected branch and its upstream went apart, while shown, and
+    /// how far it is scrolled.
+    divergence: Option<(git::Divergence, usize)>,
+    jobs: Jobs,
+    show_jobs: bool,
+    /// Quitting with jobs still running.
+    quit_prompt: Option<jobs::Quit>,
+    input: Box<dyn Input>,
+    /// Whether the log pane is open, and how many events it showed.
+    show_log: bool,
+    logged: u64,
+    marks: Marks,
+    show_marks: bool,
+    /// The saved view chosen last, shown while the filter and sort are still
+    /// its own.
+    view: Option<usize>,
+    /// Key bindings that keep others from being reached, found at load.
+    conflicts: Vec<String>,
+    show_conflicts: bool,
+    /// Set while waiting for the letter of a mark.
+    pending_mark: Option<marks::Pending>,
+    /// The output of a command run from the command line.
+    output: Option<Output>,
+    /// How diffs are shown, for the rest of the session.
+    diff_options: diff::Options,
+    remotes: Kept<Remotes>,
+    git_config: Kept<GitConfig>,
+    /// The outcome by remote of the last bulk operation spanning several.
+    bulk_report: Option<bulk::Report>,
+    /// What the bulk operations under way did so far, by job.
+    bulk: HashMap<jobs::JobId, (String, bulk::Groups)>,
+    /// Jobs that talk to a remote, any of which working shows that the
+    /// network is up.
+    remote_jobs: HashSet<jobs::JobId>,
+    /// Whether the identity to commit with was found fine this session.
+    identity_checked: bool,
+    settings: Kept<Settings>,
+    accounts: Kept<Accounts>,
+    forges: forge::Registry,
+    reviews: Reviews,
+    commit_checks: CommitChecks,
+    follow: Follow,
+    /// The followed branch whose new commits are shown, marked seen once
+    /// the selection leaves it.
+    follow_viewed: Option<String>,
+    /// The review state and note kept for each local branch.
+    checklist: Checklist,
+    /// Whether the hosts of the remotes can be reached.
+    network: Network,
+    dashboard: Option<Dashboard>,
+    /// The scroll position of the help popup.
+    help: Kept<usize>,
+    has_remotes: bool,
+    /// The remotes as of the last reload, for finding the forge of a branch
+    /// without asking git while drawing.
+    known_remotes: Vec<git::Remote>,
+    /// Only commits by this author, or with them as co-author, are shown in the
+    /// details pane, except while selecting commits.
+    author_filter: Option<String>,
+    /// Uncommitted changes and stashes, as of the last check.
+    status: git::Status,
+    /// The next status, while it is being read.
+    status_rx: Option<mpsc::Receiver<git::Result<git::Status>>>,
+    status_at: Instant,
+    /// Which branches are stale, while it is being read.
+    stale_rx: Option<mpsc::Receiver<git::Result<git::Stale>>>,
+    /// Which branches are stacked on which, while it is being read.
+    stacks_rx: Option<mpsc::Receiver<git::Result<HashMap<String, git::Link>>>>,
+    loading: Option<mpsc::Receiver<git::Result<git::Branch>>>,
+    /// The history of the selected branch while it is read, with that branch.
+    history: Option<(
+        String,
+        git::BranchType,
+        mpsc::Receiver<git::Result<git::History>>,
+    )>,
+    /// Bumped when the selection leaves the branch whose history is read, which
+    /// stops that read, queued or under way.
+    history_gen: Arc<AtomicU64>,
+    /// Tip commits from earlier runs, so that branches that did not move are
+    /// not read until they are selected.
+    tips: Tips,
+    /// Histories from earlier runs, so that branches that did not move are not
+    /// walked again.
+    histories: Histories,
+    pending_select: Option<String>,
+    /// What the last checkout did, whether it failed, and when, shown in the
+    /// footer for a while.
+    footer_note: Option<(String, bool, Instant)>,
+    details_scroll: usize,
+    /// The commits on the rows of the log in view, when it was last drawn.
+    log_in_view: Vec<git2::Oid>,
+    /// When the selection last moved, until what depends on it has caught up.
+    settle: Option<Instant>,
+    /// Snapshots of the remote refs taken before running fetch jobs, by job.
+    fetches: HashMap<jobs::JobId, BTreeMap<String, git2::Oid>>,
+    /// Where ssh asks for passphrases and about unknown hosts, if it can.
+    askpass: Option<askpass::Askpass>,
+    /// Deletes and pushes still running, shown on their branches meanwhile.
+    pending: Pending,
+    /// Changes to the repository by other programs, in watch mode.
+    watch: Option<git::Watch>,
+    /// The files whose changes were discarded this session, with the stash
+    /// each was kept in, the latest last.
+    discarded: Vec<(String, git2::Oid)>,
+    /// The commit cursor and range in the details pane, while selecting commits.
+    range: Option<Range>,
+    /// The screens drilled into, with where each was left.
+    nav: Nav,
+    /// Whether letters jump to the next branch starting with them.
+    jump: bool,
+    /// Whether typing edits the query of the branch list.
+    filtering: bool,
+    mouse: Mouse,
+    hits: mouse::Hits,
+    /// An interactive user command to hand the terminal to before the next frame.
+    suspend: Option<(String, std::process::Command)>,
+    /// Git work that signs commits, to run with the terminal handed over for
+    /// the passphrase, and its name.
+    foreground: Option<(String, jobs::Foreground)>,
+    /// What waits for the command in the output pane.
+    after_output: Option<AfterOutput>,
+    /// Commands whose pane was hidden while they ran, with what waits for
+    /// them, watched until they exit.
+    hidden_outputs: Vec<(Output, Option<AfterOutput>)>,
+    /// Jobs running the hooks of `[hooks]`, with what waits for a `pre-*` one
+    /// to pass.
+    hook_jobs: HashMap<jobs::JobId, Option<AfterHook>>,
+    /// Text to hand to an external editor once the loop comes around.
+    external: Option<(String, Purpose)>,
+    /// Where to save the screen once the next frame is drawn.
+    snapshot: Option<std::path::PathBuf>,
+    summary: Summary,
+    /// The checked out branch, as of the last reload.
+    head: Option<String>,
+    /// Whether the clone is shallow or partial, as of the last reload.
+    completeness: git::Completeness,
+    /// Whether history was rewritten with replace refs or grafts, as of the
+    /// last reload.
+    replacements: bool,
+    /// The branches checked out in other worktrees, with where, as of the last
+    /// reload.
+    worktrees: HashMap<String, PathBuf>,
+    /// A worktree to print the path of on exit, to change to.
+    exit_to: Option<PathBuf>,
+    /// The worktrees made to look around at a commit, removed on exit.
+    scratch: Vec<PathBuf>,
+    /// Jobs adding such worktrees, with where and for which commit.
+    scratch_jobs: HashMap<jobs::JobId, (PathBuf, String)>,
+    /// The directory browser to open another repository with, while shown.
+    dir_picker: Option<dirs::Picker>,
+    /// The repositories opened before in this session, most recent first.
+    recent: Vec<PathBuf>,
+    /// A repository to open once this one is closed.
+    switch_to: Option<PathBuf>,
+    /// The window title last set, so that it is only set again once it changes.
+    title: Option<String>,
+    /// Whether anything shown changed since the last frame.
+    redraw: bool,
+    last_frame: Instant,
+    /// The shortest time between two frames, from `view.max_fps`.
+    frame_interval: Duration,
+    /// The height of the screen the last frame was drawn on, which lists are
+    /// scrolled against when it changes.
+    height: u16,
+    /// Panics recovered from so far.
+    crashes: usize,
     exit: bool,
 }
 
@@ -29,200 +553,6333 @@ impl Widget for &mut App {
 }
 
 impl App {
-    pub fn new(opts: &Opts) -> EResult<Self> {
-        if let Some(dir) = &opts.dir {
-            std::env::set_current_dir(dir).wrap_err("change dir")?;
+    pub fn new(opts: &Opts, config: Config) -> EResult<Self> {
+        let repo = Self::open_repo(opts, &config)?;
+        Self::with_repo(opts, config, repo)
+    }
+
+    /// Opens the repository `opts` points at, for [`App::with_repo`].
+    pub fn open_repo(opts: &Opts, config: &Config) -> EResult<git::Repository> {
+        let repo = git::Repository::open(&opts.target())
+            .wrap_err("read repo")?
+            .with_max_commits(config.max_commits())
+            .with_replace(config.view.replace.unwrap_or(true));
+        Ok(repo)
+    }
+
+    pub fn with_repo(opts: &Opts, config: Config, repo: git::Repository) -> EResult<Self> {
+        let author_colors = config.view.author_colors;
+        let heat = config.view.heat.then(|| heat_weeks(&config));
+        let hooks = Hooks::new(config.hooks.clone(), repo.location());
+        crash::set_repo(repo.location().dir());
+        let forges = forge::Registry::new(&config.forge).wrap_err("read forge config")?;
+        let ttl = config.forge.cache_minutes.unwrap_or(10);
+        let reviews = Reviews::load(&repo.location().git_dir, ttl);
+        let commit_checks = CommitChecks::load(&repo.location().git_dir, ttl);
+        let checklist = Checklist::load(&repo.location().common_dir());
+        let follow = config
+            .view
+            .follow_seconds
+            .map_or(follow::DEFAULT_INTERVAL, |s| Duration::from_secs(s.max(1)));
+        // nothing reaches out to the remotes in safe mode
+        let follow = Follow::new((!config.safe).then_some(follow));
+        let summary = Summary::start(&repo);
+        let git_dir = &repo.location().git_dir;
+        let (tips, histories) = match opts.no_cache {
+            true => (
+                Tips::new(git_dir),
+                Histories::new(git_dir, repo.max_commits()),
+            ),
+            false => (
+                Tips::load(git_dir),
+                Histories::load(git_dir, repo.max_commits()),
+            ),
+        };
+        let branches = branch::List {
+            sort: config.view.sort.unwrap_or_default(),
+            grouped: config.view.group,
+            stacked: config.view.stacks,
+            filter: config.view.branches.into(),
+            ..Default::default()
+        };
+        if let Some(profile) = &config.profile {
+            forge::token::use_profile(profile);
         }
-        let repo = git::Repository::current().wrap_err("read repo")?;
-        let branches = branch::List::default();
+        let author_filter = config.view.author.clone();
+        let fps = config.view.max_fps.unwrap_or(60).max(1);
         let exit = false;
         let mut app = Self {
             repo,
+            config,
+            hooks,
             branch_list: branches,
+            keymap: Keymap::default(),
+            chord: Chord::default(),
+            modal: None,
+            notifications: Notifications::default(),
+            show_notifications: false,
+            author_colors,
+            heat,
+            load_all: false,
+            show_authors: false,
+            divergence: None,
+            jobs: Jobs::default(),
+            show_jobs: false,
+            quit_prompt: None,
+            input: Box::new(input::Crossterm),
+            show_log: false,
+            logged: 0,
+            marks: Marks::default(),
+            show_marks: false,
+            view: None,
+            conflicts: vec![],
+            show_conflicts: false,
+            pending_mark: None,
+            output: None,
+            diff_options: diff::Options::default(),
+            remotes: Kept::default(),
+            git_config: Kept::default(),
+            bulk_report: None,
+            bulk: HashMap::new(),
+            remote_jobs: HashSet::new(),
+            identity_checked: false,
+            settings: Kept::default(),
+            accounts: Kept::default(),
+            forges,
+            reviews,
+            commit_checks,
+            follow,
+            follow_viewed: None,
+            checklist,
+            network: Network::default(),
+            dashboard: None,
+            help: Kept::default(),
+            has_remotes: true,
+            known_remotes: vec![],
+            author_filter,
+            status: git::Status::default(),
+            status_rx: None,
+            status_at: Instant::now(),
+            stale_rx: None,
+            stacks_rx: None,
+            loading: None,
+            history: None,
+            history_gen: Arc::default(),
+            tips,
+            histories,
+            pending_select: opts.selection().map(ToOwned::to_owned),
+            footer_note: None,
+            details_scroll: 0,
+            log_in_view: vec![],
+            settle: None,
+            range: None,
+            nav: Nav::default(),
+            jump: false,
+            filtering: false,
+            fetches: HashMap::new(),
+            // without it ssh fails on what it would ask, as it did before
+            askpass: askpass::Askpass::start()
+                .inspect_err(|err| tracing::warn!("ssh prompts: {err:#}"))
+                .ok(),
+            pending: Pending::default(),
+            watch: None,
+            discarded: vec![],
+            mouse: Mouse::default(),
+            hits: mouse::Hits::default(),
+            suspend: None,
+            foreground: None,
+            after_output: None,
+            hidden_outputs: vec![],
+            hook_jobs: HashMap::new(),
+            external: None,
+            snapshot: None,
+            summary,
+            head: None,
+            completeness: git::Completeness::default(),
+            replacements: false,
+            worktrees: HashMap::new(),
+            exit_to: None,
+            scratch: Vec::new(),
+            scratch_jobs: HashMap::new(),
+            dir_picker: None,
+            recent: Vec::new(),
+            switch_to: None,
+            title: None,
+            redraw: true,
+            last_frame: Instant::now(),
+            frame_interval: Duration::from_secs(1) / fps,
+            height: 0,
+            crashes: 0,
             exit,
         };
+        for (name, keys) in &app.config.keys {
+            if let Err(err) = app.keymap.rebind(name, &keys.to_vec()) {
+                app.notifications.error(err);
+            }
+        }
+        if !app.config.views.is_empty() {
+            app.keymap.group("space w", t!("saved views"));
+        }
+        for (idx, view) in app.config.views.iter().enumerate() {
+            let seq = (idx < 9).then(|| format!("space w {}", idx + 1));
+            app.keymap.bind_view(seq.as_deref(), view.name.clone());
+        }
+        let handoffs = app.config.handoffs();
+        if !handoffs.is_empty() {
+            app.keymap.group("space o", t!("open in"));
+        }
+        app.config.commands.extend(handoffs);
+        for cmd in &app.config.commands {
+            let name = cmd.name.clone().unwrap_or_else(|| cmd.cmd.clone());
+            if !app.keymap.bind_command(&cmd.key, name) {
+                let err = t!("invalid key for command: {key}", key = cmd.key);
+                app.notifications.error(err);
+            }
+        }
+        app.conflicts = app.keymap.conflicts();
+        if !app.conflicts.is_empty() {
+            let count = app.conflicts.len();
+            app.notifications.error(t!(
+                "{count} key bindings conflict, see space v k",
+                count = count
+            ));
+            app.show_conflicts = true;
+        }
+        if app.config.view.watch {
+            match app.repo.watch() {
+                Ok(watch) => app.watch = Some(watch),
+                Err(err) => app.notifications.report(&err.into()),
+            }
+        }
+        app.first_run_hint();
+        app.prune_scratch();
         app.load_branches()?;
         Ok(app)
     }
 
-    pub fn run(&mut self, terminal: &mut crate::bootstrap::Tui) -> EResult<()> {
-        while !self.exit {
-            terminal.draw(|frame| self.render_frame(frame))?;
-            self.handle_events().wrap_err("handle events failed")?;
+    /// Draws and handles events until the user quits, or a scripted input runs
+    /// out and what it started is done. Each turn draws if
+    /// something changed and a frame is due, then waits in
+    /// [`App::handle_events`].
+    pub fn run<B: Backend>(&mut self, terminal: &mut crate::bootstrap::Tui<B>) -> EResult<()> {
+        while !(self.exit || (self.input.is_done() && self.is_idle())) {
+            let res = crash::catch(|| -> EResult<()> {
+                if self.frame_wait().is_some_and(|wait| wait.is_zero()) {
+                    if self.input.is_terminal() {
+                        self.update_title()?;
+                    }
+                    let frame = terminal.draw(|frame| self.render_frame(frame))?;
+                    if let Some(path) = self.snapshot.take() {
+                        match snapshot::save(frame.buffer, &path) {
+                            Ok(()) => self
+                                .notifications
+                                .success(t!("saved the screen to {path}", path = path.display())),
+                            Err(err) => self.notifications.report(&err),
+                        }
+                    }
+                    self.redraw = false;
+                    self.last_frame = Instant::now();
+                }
+                self.handle_events().wrap_err("handle events failed")?;
+                Ok(())
+            });
+            match res {
+                Ok(res) => res?,
+                Err(report) => self.recover(terminal, report)?,
+            }
+            if let Some((name, cmd)) = self.suspend.take() {
+                self.run_interactive(terminal, name, cmd)?;
+            }
+            if let Some((name, work)) = self.foreground.take() {
+                self.run_foreground(terminal, name, work)?;
+            }
+            if let Some((text, purpose)) = self.external.take() {
+                self.run_editor(terminal, text, purpose)?;
+            }
         }
+        // the histories read since the branches last loaded
+        self.histories.finish();
         Ok(())
     }
 
-    pub fn load_branches(&mut self) -> EResult<()> {
-        let filter = self.branch_list.filter.clone();
-        let branches: Vec<git::Branch> = self
-            .repo
-            .branches(filter.typ())
-            .wrap_err("get branches")?
-            .into_iter()
-            .collect();
-        self.branch_list = branch::List::build(branches, filter);
+    /// Reads events from `input` rather than the terminal.
+    pub fn with_input(mut self, input: impl Input + 'static) -> Self {
+        self.input = Box::new(input);
+        self
+    }
+
+    /// Whether nothing started is still under way: loading the branches or the
+    /// history of the selected one, waiting for the selection to settle, or a job.
+    fn is_idle(&self) -> bool {
+        self.loading.is_none()
+            && self.stale_rx.is_none()
+            && self.stacks_rx.is_none()
+            && self.history.is_none()
+            && self.settle.is_none()
+            && !self.jobs.is_busy()
+    }
+
+    /// Waits for the branches to load and draws them once, as startup does, for
+    /// `grit bench`.
+    pub fn draw_loaded<B: ratatui::backend::Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+    ) -> EResult<()> {
+        while self.loading.is_some() || self.stale_rx.is_some() || self.stacks_rx.is_some() {
+            if !self.poll_branches()? && !self.poll_stale_and_stacks() {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+        terminal.draw(|frame| self.render_frame(frame))?;
         Ok(())
     }
 
-    fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        let [header, main, footer] = Layout::vertical([
-            Constraint::Length(2),
-            Constraint::Fill(1),
-            Constraint::Length(1),
-        ])
-        .areas(area);
-        let [list, item] = Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(main);
-        self.render_header(header, buf);
-        self.render_branch_list(list, buf);
-        self.render_selected(item, buf);
-        App::render_footer(footer, buf);
+    /// What the session changed, for printing once the terminal is restored.
+    pub fn summary(&self) -> Vec<String> {
+        self.summary.lines(&self.repo)
     }
 
-    fn render_header(&self, area: Rect, buf: &mut Buffer) {
-        let sort = match self.branch_list.sort {
-            branch::Sort::NameAscending => "name asc",
-            branch::Sort::NameDescending => "name desc",
-            branch::Sort::DateAscending => "date asc",
-            branch::Sort::DateDescending => "date desc",
-        };
-        let header = format!("j/k/g/G: move [,]: sort ({sort})");
-        Paragraph::new(header)
-            .bold()
-            .left_aligned()
-            .render(area, buf);
+    /// The worktree the session ended by jumping to, to print for the shell.
+    pub fn exit_to(&self) -> Option<&Path> {
+        self.exit_to.as_deref()
     }
 
-    fn render_footer(area: Rect, buf: &mut Buffer) {
-        Paragraph::new("footer stuff").centered().render(area, buf);
+    /// Offers `recent`, the repositories opened before in this session, most
+    /// recent first, when opening another.
+    pub fn with_recent(mut self, recent: Vec<PathBuf>) -> Self {
+        self.recent = recent;
+        self
     }
 
-    fn render_branch_list(&mut self, area: Rect, buf: &mut Buffer) {
-        let block = Block::new()
-            .title(Line::raw("Branches").left_aligned())
-            .borders(Borders::TOP)
-            .border_set(symbols::border::EMPTY)
-            .border_style(HEADER_STYLE)
-            .bg(NORMAL_ROW_BG);
-        let items: Vec<ListItem> = self
-            .branch_list
-            .items
-            .iter()
-            .map(|item| ListItem::from(item))
-            .collect();
-        let list = List::new(items.into_iter())
-            .block(block)
-            .highlight_style(SELECTED_STYLE)
-            .highlight_symbol(">")
-            .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+    /// The work tree of the repository shown, or its git dir if it is bare.
+    pub fn dir(&self) -> PathBuf {
+        self.repo.location().dir().to_path_buf()
+    }
 
-        StatefulWidget::render(list, area, buf, &mut self.branch_list.state)
+    /// The name of the branch selected, if any.
+    pub fn selected_branch(&self) -> Option<String> {
+        self.branch_list.current().map(|b| b.name.clone())
     }
 
-    fn render_selected(&mut self, area: Rect, buf: &mut Buffer) {
-        let Some(branch) = self.branch_list.current() else {
-            return;
+    /// The repository picked to open next, which ended the session.
+    pub fn switch_to(&self) -> Option<&Path> {
+        self.switch_to.as_deref()
+    }
+
+    /// Sets the window title to the repository and checked out branch, and tells
+    /// the terminal where the repository is the first time round.
+    fn update_title(&mut self) -> EResult<()> {
+        let dir = self.repo.location().dir().to_path_buf();
+        let name = dir.file_name().map(|n| n.to_string_lossy().into_owned());
+        let name = redact::branch(name.as_deref().unwrap_or("?")).into_owned();
+        let text = match &self.head {
+            Some(head) => format!("grit: {name} ({})", redact::branch(head)),
+            None => format!("grit: {name}"),
         };
-        let _block = Block::new()
-            .title(Line::raw("Details").left_aligned())
-            .borders(Borders::TOP)
-            .border_set(symbols::border::EMPTY)
-            .border_style(HEADER_STYLE)
-            .bg(NORMAL_ROW_BG);
-        let commits = branch
-            .commits()
-            .iter()
-            .map(|c| {
-                let summary = c.summary.as_str();
-                let author = c.author.name.as_deref().unwrap_or("<none>");
-                let timestamp = &c.timestamp;
-                format!("{timestamp}: {author}: {summary}")
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-        Paragraph::new(commits).render(area, buf);
+        if self.title.as_ref() == Some(&text) {
+            return Ok(());
+        }
+        if self.title.is_none() && !redact::is_enabled() {
+            title::cwd(&dir)?;
+        }
+        title::set(&text)?;
+        self.title = Some(text);
+        Ok(())
     }
 
-    fn render_frame(&mut self, frame: &mut Frame) {
-        frame.render_widget(self, frame.size());
+    /// How long until the next frame may be drawn, if one is needed at all.
+    fn frame_wait(&self) -> Option<Duration> {
+        self.redraw.then(|| {
+            self.frame_interval
+                .saturating_sub(self.last_frame.elapsed())
+        })
     }
 
-    fn handle_events(&mut self) -> EResult<(), Error> {
-        match event::read()? {
-            Event::Key(key_event) => self
-                .handle_key(key_event)
-                .wrap_err("handle key failed")
-                .wrap_err_with(|| format!("{key_event:#?}"))?,
-            _ => {}
+    /// Picks up after a panic in the render or update path. Popups are the
+    /// likeliest culprits, so they are closed; a panic that keeps coming back
+    /// ends the session.
+    fn recover<B: Backend>(
+        &mut self,
+        terminal: &mut crate::bootstrap::Tui<B>,
+        report: Option<std::path::PathBuf>,
+    ) -> EResult<()> {
+        let report = report.map_or_else(|| "-".to_string(), |p| p.display().to_string());
+        self.crashes += 1;
+        if self.crashes > MAX_CRASHES {
+            color_eyre::eyre::bail!("grit kept crashing, see the report at {report}");
         }
+        self.modal = None;
+        self.show_jobs = false;
+        self.quit_prompt = None;
+        self.show_marks = false;
+        self.show_conflicts = false;
+        self.show_notifications = false;
+        self.show_authors = false;
+        self.divergence = None;
+        self.output = None;
+        self.remotes.hide();
+        self.git_config.hide();
+        self.bulk_report = None;
+        self.settings.hide();
+        self.accounts.hide();
+        self.dashboard = None;
+        self.help.hide();
+        self.chord = Chord::default();
+        self.redraw = true;
+        terminal.clear()?;
+        self.notifications.error(t!(
+            "Something went wrong. A crash report was written to {report}",
+            report = report
+        ));
         Ok(())
     }
 
-    fn handle_key(&mut self, key: KeyEvent) -> EResult<()> {
-        if key.kind != KeyEventKind::Press {
-            return Ok(());
+    /// Points new users at the help popup, once.
+    fn first_run_hint(&mut self) {
+        if !self.config.view.hints.unwrap_or(true) {
+            return;
         }
-        match key.code {
-            KeyCode::Char('q') => self.exit(),
-            KeyCode::Char('h') | KeyCode::Left => self.select_none()?,
-            KeyCode::Char('j') | KeyCode::Down => self.select_next()?,
-            KeyCode::Char('k') | KeyCode::Up => self.select_previous()?,
-            KeyCode::Char('g') | KeyCode::Home => self.select_first()?,
-            KeyCode::Char('G') | KeyCode::End => self.select_last()?,
-            KeyCode::Char('s') => self.cycle_sort()?,
-            KeyCode::Char('l') | KeyCode::Right | KeyCode::Enter => {
-                self.toggle_branch()?;
-            }
-            _ => {}
+        let Some(dir) = paths::state_dir() else {
+            return;
+        };
+        let marker = dir.join("onboarded");
+        if marker.exists() {
+            return;
         }
+        self.notifications.info(t!(
+            "Welcome to grit! Press ? to see all keys, or space for the menu."
+        ));
+        let _ = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&marker, ""));
+    }
+
+    /// Starts streaming branches into a fresh list. They are added as they arrive
+    /// by [`App::poll_branches`].
+    pub fn load_branches(&mut self) -> EResult<()> {
+        let filter = self.branch_list.filter.clone();
+        let (sort, grouped) = (self.branch_list.sort, self.branch_list.grouped);
+        let stacked = self.branch_list.stacked;
+        let stale_only = self.branch_list.stale_only;
+        let query = std::mem::take(&mut self.branch_list.query);
+        let sticky = self.branch_list.selection();
+        // in a repository with very many branches, the ones committed to last
+        // show at once and the rest are read when asked for
+        let count = self.repo.branch_count(filter.typ())?;
+        let limit = self.config.branch.load_limit.unwrap_or(LOAD_LIMIT);
+        let only = (!self.load_all && count > limit).then(|| {
+            // along with the branches that are worked on, whatever their tip
+            let mut keep: Vec<(String, git2::BranchType)> = self.branch_list.marked.clone();
+            keep.extend(sticky.as_ref().map(|s| (s.name.clone(), s.typ)));
+            let local = self.repo.head_branch().into_iter();
+            keep.extend(
+                local
+                    .chain(self.pending_select.clone())
+                    .map(|name| (name, git2::BranchType::Local)),
+            );
+            let keep = keep
+                .iter()
+                .map(|(name, typ)| git::refname::full(name, *typ))
+                .collect();
+            git::Recent {
+                typ: filter.typ(),
+                count: self.config.branch.load_first.unwrap_or(LOAD_FIRST),
+                keep,
+            }
+        });
+        // counted down as they arrive, as which are read is found out then
+        let unloaded = if only.is_some() { count } else { 0 };
+        // whether the branches left unread are stale is found out with them
+        let stale_of = only.clone();
+        // the activity column needs the history of every branch, the rest only
+        // that of the selected one, read once it is selected
+        let notes = self.repo.notes_tip()?;
+        let cached = self.histories.lookup(notes);
+        self.loading = Some(match self.heat {
+            Some(_) => self.repo.stream_branches_cached(filter.typ(), cached, only),
+            None => {
+                let known = self.tips.commits(notes);
+                self.repo
+                    .stream_branch_tips(filter.typ(), known, cached, only)
+            }
+        });
+        self.history = None;
+        // shown as they were until they are read again
+        let stale = std::mem::take(&mut self.branch_list.stale);
+        let stacks = std::mem::take(&mut self.branch_list.stacks);
+        self.branch_list = branch::List::build(vec![], filter);
+        (self.branch_list.stale, self.branch_list.stacks) = (stale, stacks);
+        self.branch_list.sort = sort;
+        self.branch_list.grouped = grouped;
+        self.branch_list.stale_only = stale_only;
+        self.branch_list.unloaded = unloaded;
+        let days = self.config.branch.stale_days.unwrap_or(STALE_DAYS);
+        let before = chrono::Utc::now().timestamp() - i64::from(days) * 86400;
+        self.stale_rx = Some(self.repo.stream_stale(before, stale_of));
+        self.branch_list.stacked = stacked;
+        self.read_stacks();
+        self.read_query_facts(&query)?;
+        self.branch_list.set_query(query);
+        self.branch_list.sticky = sticky;
+        self.branch_list.total = self.repo.branch_count(None)?;
+        self.head = self.repo.head_branch();
+        self.completeness = self.repo.completeness()?;
+        self.replacements = self.repo.has_replacements()?;
+        self.worktrees = self.repo.worktrees().unwrap_or_else(|err| {
+            self.notifications.report(&err.into());
+            HashMap::new()
+        });
+        let remotes = self.repo.remotes().unwrap_or_default();
+        self.has_remotes = !remotes.is_empty();
+        self.network
+            .watch(remotes.iter().filter_map(|r| r.url.as_deref()));
+        self.known_remotes = remotes;
+        self.refresh_status();
         Ok(())
     }
 
-    fn cycle_sort(&mut self) -> EResult<()> {
-        self.branch_list.sort = match self.branch_list.sort {
-            branch::Sort::NameAscending => branch::Sort::NameDescending,
-            branch::Sort::NameDescending => branch::Sort::DateAscending,
-            branch::Sort::DateAscending => branch::Sort::DateDescending,
-            branch::Sort::DateDescending => branch::Sort::NameAscending,
+    /// Starts reading which branches are stacked on which, while the list
+    /// shows it.
+    fn read_stacks(&mut self) {
+        match self.branch_list.stacked {
+            true => self.stacks_rx = Some(self.repo.stream_stacks()),
+            false => {
+                self.stacks_rx = None;
+                self.branch_list.stacks.clear();
+            }
+        }
+    }
+
+    /// Takes which branches are stale and which are stacked on which once
+    /// they are read, returning whether either was.
+    fn poll_stale_and_stacks(&mut self) -> bool {
+        let mut any = false;
+        if let Some(res) = self.stale_rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.stale_rx = None;
+            match res {
+                Ok(stale) => {
+                    self.branch_list.stale = stale;
+                    // what shows while only stale branches do may differ now
+                    if self.branch_list.stale_only {
+                        self.branch_list.set_stale_only(true);
+                    }
+                }
+                Err(err) => self.notifications.report(&err.into()),
+            }
+            any = true;
+        }
+        if let Some(res) = self.stacks_rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.stacks_rx = None;
+            match res {
+                Ok(stacks) => {
+                    self.branch_list.stacks = stacks;
+                    self.branch_list.sort();
+                }
+                Err(err) => self.notifications.report(&err.into()),
+            }
+            any = true;
+        }
+        any
+    }
+
+    /// Reads the counts of uncommitted changes and stashes, as after changing
+    /// them.
+    fn refresh_status(&mut self) {
+        self.status = self.repo.status().unwrap_or_default();
+        self.status_rx = None;
+        self.status_at = Instant::now();
+    }
+
+    /// Reads the counts again in the background every [`STATUS_INTERVAL`], and
+    /// picks up the result once it is in.
+    fn poll_status(&mut self) {
+        if let Some(rx) = &self.status_rx {
+            match rx.try_recv() {
+                Ok(status) => {
+                    let status = status.unwrap_or_default();
+                    self.redraw |= status != self.status;
+                    self.status = status;
+                    self.status_rx = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.status_rx = None,
+            }
+        } else if self.status_at.elapsed() >= STATUS_INTERVAL {
+            self.status_rx = Some(self.repo.stream_status());
+            self.status_at = Instant::now();
+        }
+    }
+
+    /// Reloads once a batch of changes by other programs is in, keeping the
+    /// selection. Nothing is reloaded while a load is under way already.
+    fn poll_watch(&mut self) {
+        if self.loading.is_some() || !self.watch.as_mut().is_some_and(git::Watch::poll) {
+            return;
+        }
+        self.refresh_status();
+        self.read_status_files(false);
+        if let Err(err) = self.load_branches() {
+            self.notifications.report(&err);
+        }
+        self.redraw = true;
+    }
+
+    /// Adds branches that have been loaded since the last call.
+    /// Adds the branches loaded so far, returning whether loading finished.
+    fn poll_branches(&mut self) -> EResult<bool> {
+        let Some(rx) = &self.loading else {
+            return Ok(false);
         };
-        self.branch_list.sort();
-        self.branch_list.state.select_first();
-        Ok(())
+        let mut batch = vec![];
+        let done = loop {
+            match rx.try_recv() {
+                Ok(branch) => batch.push(branch.wrap_err("get branches")?),
+                Err(mpsc::TryRecvError::Empty) => break false,
+                Err(mpsc::TryRecvError::Disconnected) => break true,
+            }
+        };
+        if !batch.is_empty() {
+            let unloaded = &mut self.branch_list.unloaded;
+            *unloaded = unloaded.saturating_sub(batch.len());
+            self.branch_list.extend(batch);
+        }
+        if done {
+            self.loading = None;
+            self.branch_list.settle();
+            let notes = self.repo.notes_tip()?;
+            self.tips.save(self.branch_list.all(), notes);
+            self.histories.update(self.branch_list.all(), notes);
+            self.histories.save();
+            self.note_arrivals();
+            if let Some(name) = self.pending_select.take() {
+                self.select_branch(&name)?;
+            }
+            self.load_current()?;
+            self.request_review();
+        }
+        Ok(done)
     }
 
-    fn select_none(&mut self) -> EResult<()> {
-        self.branch_list.state.select(None);
-        Ok(())
+    /// Selects the branch with the given name, or the closest fuzzy match.
+    pub fn select_branch(&mut self, name: &str) -> EResult<()> {
+        if self.branch_list.unmatched.iter().any(|b| b.name == name) {
+            // shown when asked for by name, even if the query hides it
+            self.branch_list.set_query(String::new());
+        }
+        let items = &self.branch_list.items;
+        let idx = items
+            .iter()
+            .position(|b| b.name == name)
+            .or_else(|| fuzzy::best(name, items.iter().map(|b| b.name.as_str())));
+        if let Some(idx) = idx {
+            self.branch_list.state.select(Some(idx));
+        }
+        self.load_selected()
     }
 
-    fn select_next(&mut self) -> EResult<()> {
-        self.branch_list.state.select_next();
-        Ok(())
+    fn selected_index(&self) -> usize {
+        let idx = self.branch_list.state.selected().unwrap_or(usize::MAX);
+        // select_last() selects usize::MAX until the next render clamps it.
+        idx.min(self.branch_list.items.len().saturating_sub(1))
     }
 
-    fn select_previous(&mut self) -> EResult<()> {
-        self.branch_list.state.select_previous();
-        Ok(())
+    /// Loads the commits of the selected branch if they have not been loaded yet.
+    fn load_selected(&mut self) -> EResult<()> {
+        self.selection_moved();
+        self.settle_selection()
     }
 
-    fn select_first(&mut self) -> EResult<()> {
-        self.branch_list.state.select_first();
+    /// Resets what belonged to the previous selection. The new one is read once
+    /// it stays put for [`SELECTION_DEBOUNCE`].
+    fn selection_moved(&mut self) {
+        self.details_scroll = 0;
+        let current = self.branch_list.current().map(|b| (&b.name, b.typ));
+        if self
+            .history
+            .as_ref()
+            .is_some_and(|(name, typ, _)| Some((name, *typ)) != current)
+        {
+            self.history_gen.fetch_add(1, Ordering::Relaxed);
+            self.history = None;
+        }
+        let current = current.map(|(name, _)| name);
+        if self
+            .range
+            .as_ref()
+            .is_some_and(|r| Some(&r.branch) != current)
+        {
+            self.range = None;
+            self.nav.reset();
+        }
+        self.settle = Some(Instant::now());
+        let current = self.branch_list.current().map(git::Branch::refname);
+        if self.follow_viewed != current {
+            if let Some(viewed) = self.follow_viewed.take() {
+                self.follow.seen(&viewed);
+            }
+            self.follow_viewed = current;
+        }
+    }
+
+    fn settle_selection(&mut self) -> EResult<()> {
+        self.settle = None;
+        self.load_current()?;
+        self.request_review();
         Ok(())
     }
 
-    fn select_last(&mut self) -> EResult<()> {
-        self.branch_list.state.select_last();
+    /// Starts reading the history of the selected branch, unless it has been
+    /// read or is being read already. [`App::poll_history`] takes it.
+    fn load_current(&mut self) -> EResult<()> {
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        if !branch.commits().is_empty() && !branch.partial {
+            return Ok(());
+        }
+        let reading = self
+            .history
+            .as_ref()
+            .is_some_and(|(name, typ, _)| *name == branch.name && *typ == branch.typ);
+        if !reading {
+            let (gen, started) = (
+                self.history_gen.clone(),
+                self.history_gen.load(Ordering::Relaxed),
+            );
+            let rx = branch.stream_history(move || gen.load(Ordering::Relaxed) != started);
+            self.history = Some((branch.name.clone(), branch.typ, rx));
+        }
         Ok(())
     }
 
-    fn toggle_branch(&mut self) -> EResult<()> {
-        if let Some(i) = self.branch_list.state.selected() {
-            let _branch = &self.branch_list.items[i];
+    /// Reads the history of the selected branch without waiting for the
+    /// worker, for what needs all of it at once.
+    fn load_current_now(&mut self) -> EResult<()> {
+        if self.branch_list.state.selected().is_none() {
+            return Ok(());
+        }
+        let idx = self.selected_index();
+        if let Some(branch) = self.branch_list.items.get_mut(idx) {
+            if branch.commits().is_empty() || branch.partial {
+                branch.load().wrap_err_with(|| format!("load {branch}"))?;
+            }
         }
         Ok(())
     }
 
-    fn exit(&mut self) {
-        self.exit = true;
+    /// Takes the history of the selected branch once it has been read,
+    /// returning whether it has.
+    fn poll_history(&mut self) -> bool {
+        let Some((name, typ, rx)) = &self.history else {
+            return false;
+        };
+        let history = match rx.try_recv() {
+            Ok(history) => history,
+            Err(mpsc::TryRecvError::Empty) => return false,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.history = None;
+                return false;
+            }
+        };
+        let list = &mut self.branch_list;
+        let branch = list
+            .items
+            .iter_mut()
+            .chain(&mut list.unmatched)
+            // read already by something that could not wait
+            .find(|b| b.name == *name && b.typ == *typ && b.partial);
+        match (branch, history) {
+            (Some(branch), Ok(history)) => {
+                branch.set_history(history);
+                if let Ok(notes) = self.repo.notes_tip() {
+                    self.histories.update([&*branch], notes);
+                }
+            }
+            // superseded by another selection
+            (_, Err(git::Error::Cancelled(_))) => {}
+            (Some(_), Err(err)) => {
+                let err = color_eyre::eyre::Report::new(err).wrap_err(format!("load {name}"));
+                self.notifications.report(&err);
+            }
+            (None, _) => {}
+        }
+        self.history = None;
+        true
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [header, main, footer] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        let [main, log_area] = match self.show_log {
+            true => Layout::vertical([Constraint::Fill(2), Constraint::Fill(1)]).areas(main),
+            false => [main, Rect::default()],
+        };
+        let [list, item] = Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(main);
+        self.render_header(header, buf);
+        match self.nav.current_mut() {
+            nav::View::History(history) => history::HistoryList(history).render(main, buf),
+            nav::View::Pickaxe(search) => pickaxe::SearchList(search).render(main, buf),
+            nav::View::Status(view) => status::StatusList(view).render(main, buf),
+            nav::View::Hunks(view) => hunks::HunkList(view).render(main, buf),
+            nav::View::Refs(view) => refs::RefList(view).render(main, buf),
+            nav::View::Tags(view) => tags::TagList(view).render(main, buf),
+            nav::View::Remotes(view) => remotes::RemoteTab(view).render(main, buf),
+            nav::View::Branches | nav::View::Commits(_) => {
+                self.render_branch_list(list, buf);
+                self.render_selected(item, buf);
+            }
+        }
+        if self.nav.current_mut().list_state().is_some() {
+            // nothing under the branch list's regions to click on
+            self.hits.list_header = Rect::default();
+            self.hits.list_rows = Rect::default();
+        }
+        if self.show_log {
+            log::LogPane.render(log_area, buf);
+            self.logged = logs::count();
+        }
+        self.render_footer(footer, buf);
+        self.render_leader_menu(main, buf);
+        notify::Toasts(&self.notifications).render(main, buf);
+        if self.show_notifications {
+            notify::History(&self.notifications).render(main, buf);
+        }
+        if self.show_authors {
+            let commits = self.branch_list.current().map_or(&[][..], |b| b.commits());
+            authors::Legend(commits).render(main, buf);
+        }
+        if let Some((div, scroll)) = &mut self.divergence {
+            divergence::Lanes { div, scroll }.render(main, buf);
+        }
+        if self.show_jobs {
+            jobs::JobList(&mut self.jobs).render(main, buf);
+        }
+        if self.show_conflicts {
+            conflicts::ConflictList(&self.conflicts).render(main, buf);
+        }
+        if self.show_marks {
+            marks::MarkList(&mut self.marks).render(main, buf);
+        }
+        if let Some(remotes) = self.remotes.shown_mut() {
+            remotes::RemoteList(remotes).render(main, buf);
+        }
+        if let Some(git_config) = self.git_config.shown_mut() {
+            gitconfig::GitConfigList(git_config).render(main, buf);
+        }
+        if let Some(report) = &self.bulk_report {
+            bulk::ReportView(report).render(main, buf);
+        }
+        if let Some(settings) = self.settings.shown_mut() {
+            let config = &self.config;
+            settings::SettingList { settings, config }.render(main, buf);
+        }
+        if let Some(accounts) = self.accounts.shown_mut() {
+            accounts::AccountList(accounts).render(main, buf);
+        }
+        if let Some(output) = &mut self.output {
+            output.render(main, buf);
+        }
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.render(main, buf);
+        }
+        if let Some(scroll) = self.help.shown_mut() {
+            let keymap = &self.keymap;
+            help::Help { keymap, scroll }.render(main, buf);
+        }
+        if let Some(picker) = &mut self.dir_picker {
+            picker.render(main, buf);
+        }
+        if let Some(quit) = self.quit_prompt {
+            let jobs = &self.jobs;
+            jobs::QuitPrompt { jobs, quit }.render(main, buf);
+        }
+        if let Some((modal, _)) = &mut self.modal {
+            modal.render(area, buf);
+        }
+    }
+
+    fn render_leader_menu(&self, area: Rect, buf: &mut Buffer) {
+        let prefix = self.chord.pending();
+        if prefix.first().is_some_and(|k| self.keymap.is_leader(*k)) {
+            let keymap = &self.keymap;
+            leader::Menu { keymap, prefix }.render(area, buf);
+        }
+    }
+
+    fn render_header(&mut self, area: Rect, buf: &mut Buffer) {
+        let [area, crumbs] = Layout::vertical([Constraint::Length(1); 2]).areas(area);
+        self.render_breadcrumb(crumbs, buf);
+        let sort = self.branch_list.sort.label();
+        let mut header = t!("j/k/gg/G: move s: sort ({sort})", sort = sort);
+        if self.loading.is_some() {
+            header = format!("{header}  {}", t!("loading…"));
+        }
+        let mut line = Line::raw(header);
+        if let Some(idx) = self.active_view() {
+            let name = &self.config.views[idx].name;
+            line.spans
+                .insert(0, Span::styled(format!("[{name}]  "), VIEW_STYLE));
+        }
+        let status = self.status;
+        let badges: Vec<String> = [
+            (status.modified, "~"),
+            (status.staged, "✚"),
+            (status.stashes, "⚑"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, sign)| format!("{sign}{count}"))
+        .collect();
+        if !badges.is_empty() {
+            let badges = format!("  ({})", badges.join(" "));
+            line.spans.push(Span::styled(badges, DIRTY_STYLE));
+        }
+        if self.replacements {
+            let history = match self.repo.replace() {
+                true => t!("replaced history"),
+                false => t!("original history"),
+            };
+            line.spans
+                .push(Span::styled(format!("  [{history}]"), HINT_STYLE));
+        }
+        if self.network.offline().is_some() {
+            line.spans.push(Span::styled(
+                format!("  [{}]", t!("offline")),
+                OFFLINE_STYLE,
+            ));
+        }
+        if self.config.safe {
+            line.spans.push(Span::styled(
+                format!("  [{}]", t!("safe mode")),
+                OFFLINE_STYLE,
+            ));
+        }
+        for (host, until) in forge::limit::blocked() {
+            let badge = t!(
+                "{host} rate limited until {time}",
+                host = host,
+                time = until.format("%H:%M")
+            );
+            line.spans
+                .push(Span::styled(format!("  [{badge}]"), DIRTY_STYLE));
+        }
+        if self.completeness.shallow {
+            line.spans
+                .push(Span::styled(format!("  [{}]", t!("shallow")), HINT_STYLE));
+        }
+        match self.completeness.partial.as_deref() {
+            Some("") => line
+                .spans
+                .push(Span::styled(format!("  [{}]", t!("partial")), HINT_STYLE)),
+            Some(filter) => line.spans.push(Span::styled(
+                format!("  [{}]", t!("partial: {filter}", filter = filter)),
+                HINT_STYLE,
+            )),
+            None => {}
+        }
+        Paragraph::new(line).bold().left_aligned().render(area, buf);
     }
+
+    /// Shows the screens drilled into, e.g. `branches ▸ main`, once there are
+    /// any, with the ones below the current one clickable. Until then it shows
+    /// the tabs.
+    fn render_breadcrumb(&mut self, area: Rect, buf: &mut Buffer) {
+        self.hits.crumbs.clear();
+        self.hits.tabs.clear();
+        if self.nav.is_root() {
+            let mut spans = vec![];
+            let mut x = area.x;
+            for (idx, label) in nav::tabs().into_iter().enumerate() {
+                let span = match idx == self.nav.tab() {
+                    true => Span::raw(format!("{} {label}", idx + 1)).bold(),
+                    false => Span::styled(format!("{} {label}", idx + 1), CRUMB_STYLE),
+                };
+                let width = span.width() as u16;
+                self.hits
+                    .tabs
+                    .push(Rect::new(x, area.y, width, 1).intersection(area));
+                x = x.saturating_add(width + 2);
+                spans.extend([span, Span::raw("  ")]);
+            }
+            Paragraph::new(Line::from(spans)).render(area, buf);
+            return;
+        }
+        let crumbs = self.nav.crumbs();
+        let last = crumbs.len() - 1;
+        let mut spans = vec![];
+        let mut x = area.x;
+        for (depth, crumb) in crumbs.into_iter().enumerate() {
+            if depth > 0 {
+                let sep = Span::styled(" ▸ ", TRAILER_STYLE);
+                x = x.saturating_add(sep.width() as u16);
+                spans.push(sep);
+            }
+            let span = match depth == last {
+                true => Span::raw(crumb).bold(),
+                false => Span::styled(crumb, CRUMB_STYLE),
+            };
+            let width = span.width() as u16;
+            let hit = Rect::new(x, area.y, width, 1).intersection(area);
+            self.hits.crumbs.push(hit);
+            x = x.saturating_add(width);
+            spans.push(span);
+        }
+        Paragraph::new(Line::from(spans)).render(area, buf);
+    }
+
+    fn render_footer(&self, area: Rect, buf: &mut Buffer) {
+        let pending = self.chord.pending();
+        let progress = self.jobs.progress();
+        if self.jobs.is_busy() {
+            let running = self.jobs.running();
+            let queued = self.jobs.queued();
+            let jobs = t!(
+                "jobs: {running} running, {queued} queued",
+                running = running,
+                queued = queued
+            );
+            let mut line = Line::raw(jobs);
+            if let Some(progress) = &progress {
+                line.push_span(Span::styled(format!("  {progress}"), theme::get().header));
+            }
+            Paragraph::new(line).left_aligned().render(area, buf);
+        }
+        if let Some(pending) = self.pending_mark {
+            let hint = match pending {
+                marks::Pending::Set => t!("mark: type a letter for the branch"),
+                marks::Pending::Jump => t!("jump to mark: type its letter"),
+            };
+            Paragraph::new(hint).centered().render(area, buf);
+        } else if self.filtering {
+            let mut query = Line::from(vec![
+                Span::raw(format!("/{}", self.branch_list.query)),
+                Span::raw("▏").slow_blink(),
+                Span::styled(t!("  enter: keep  esc: clear"), theme::get().header),
+            ]);
+            if let Some(err) = &self.branch_list.query_error {
+                query.push_span(Span::styled(format!("  {err}"), RED.c300));
+            }
+            Paragraph::new(query).render(area, buf);
+        } else if self.jump {
+            Paragraph::new(t!("jump: type the first letter of a branch  esc: done"))
+                .centered()
+                .render(area, buf);
+        } else if let Some((note, failed)) = self
+            .footer_note
+            .as_ref()
+            .filter(|_| pending.is_empty())
+            .map(|(note, failed, _)| (note, *failed))
+        {
+            let color = match failed {
+                true => RED.c300,
+                false => GREEN.c300,
+            };
+            Paragraph::new(Line::styled(note.as_str(), color))
+                .centered()
+                .render(area, buf);
+        } else if pending.is_empty() {
+            // the progress of a job takes the place of the hint
+            if progress.is_none() {
+                Paragraph::new(t!("q: quit  space: menu  ?: help"))
+                    .centered()
+                    .render(area, buf);
+            }
+        } else {
+            let keys = keymap::format_sequence(pending);
+            Paragraph::new(format!("{keys} …"))
+                .right_aligned()
+                .render(area, buf);
+        }
+    }
+
+    fn render_branch_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let stats = self.branch_list.stats();
+        let position = stats.position.unwrap_or_default();
+        let mut count = t!(
+            "{position}/{shown} branches",
+            position = position,
+            shown = stats.shown
+        );
+        if stats.marked > 0 {
+            count = format!("{count} {}", t!("({marked} marked)", marked = stats.marked));
+        }
+        if stats.hidden > 0 {
+            count = format!("{count} {}", t!("({hidden} hidden)", hidden = stats.hidden));
+        }
+        let unloaded = self.branch_list.unloaded;
+        if unloaded > 0 {
+            let more = match self.keymap.keys(Action::LoadAllBranches) {
+                Some(keys) => t!("({keys}: load {count} more)", keys = keys, count = unloaded),
+                None => t!("({count} more not loaded)", count = unloaded),
+            };
+            count = format!("{count} {more}");
+        }
+        let title = match (self.branch_list.stale_only, self.branch_list.query.as_str()) {
+            (false, "") => t!("Branches").to_string(),
+            (false, query) => t!("Branches matching {query}", query = query),
+            (true, "") => t!("Stale branches").to_string(),
+            (true, query) => t!("Stale branches matching {query}", query = query),
+        };
+        let block = Block::new()
+            .title(Line::raw(title).left_aligned())
+            .title(Line::raw(count).right_aligned())
+            .borders(Borders::TOP)
+            .border_set(symbols::border::EMPTY)
+            .border_style(theme::get().header)
+            .bg(theme::get().row_bg);
+        let now = chrono::Utc::now().timestamp();
+        let rows = self.branch_list.rows();
+        let columns = self.config.view.columns.as_deref().map(columns::resolve);
+        let cells: Vec<Option<Vec<Line>>> = rows
+            .iter()
+            .map(|row| {
+                let b = match *row {
+                    branch::Row::Header(..) => return None,
+                    branch::Row::Branch(idx) => &self.branch_list.items[idx],
+                };
+                let marker = if self.branch_list.is_marked(b) {
+                    "● "
+                } else {
+                    "  "
+                };
+                let mut line = branch::line(b);
+                let depth = match *row {
+                    branch::Row::Branch(idx) => self.branch_list.depth(idx),
+                    branch::Row::Header(..) => 0,
+                };
+                if depth > 0 {
+                    let indent = format!("{}└ ", "  ".repeat(depth - 1));
+                    line.spans.insert(0, Span::styled(indent, TRAILER_STYLE));
+                }
+                match self.branch_list.staleness(b) {
+                    Some(git::Staleness::Merged) => line
+                        .spans
+                        .push(Span::styled(format!(" {}", t!("merged")), STALE_STYLE)),
+                    Some(git::Staleness::Old) => line
+                        .spans
+                        .push(Span::styled(format!(" {}", t!("stale")), STALE_STYLE)),
+                    None => {}
+                }
+                if self.branch_list.link(b).is_some_and(|link| link.stale) {
+                    line.spans.push(Span::styled(
+                        format!(" ⟳ {}", t!("restack")),
+                        TRACKING_STYLE,
+                    ));
+                }
+                let pending = match b.typ {
+                    git2::BranchType::Local => self.pending.of(&b.name),
+                    git2::BranchType::Remote => None,
+                };
+                match pending {
+                    Some(pending::Op::Delete) => {
+                        line = line.patch_style(PENDING_STYLE.crossed_out());
+                        line.spans
+                            .push(Span::styled(t!(" deleting…"), PENDING_STYLE));
+                    }
+                    Some(pending::Op::Push) => {
+                        line.spans
+                            .push(Span::styled(t!(" ↑ pushing…"), PENDING_STYLE));
+                    }
+                    None => {}
+                }
+                line.spans.insert(0, Span::styled(marker, MARK_STYLE));
+                if let Some(weeks) = self.heat {
+                    let heat = format!("{} ", branch::heat(b, weeks, now));
+                    line.spans.insert(1, Span::styled(heat, HEAT_STYLE));
+                }
+                if let Some(path) = self.worktree_of(b) {
+                    let badge = format!(" ⌂ {}", redact::text(&path.display().to_string()));
+                    line.spans.push(Span::styled(badge, WORKTREE_STYLE));
+                }
+                if self.follow.is_followed(b) {
+                    let badge = match self.follow.new_count(b) {
+                        0 => format!(" ◉ {}", t!("following")),
+                        n => format!(" ◉ {}", t!("{count} new", count = n)),
+                    };
+                    line.spans.push(Span::styled(badge, NEW_STYLE));
+                }
+                let meta = match b.typ {
+                    git2::BranchType::Local => self.checklist.get(&b.name),
+                    git2::BranchType::Remote => None,
+                };
+                if let (None, Some(meta)) = (&columns, meta) {
+                    line.spans.push(Span::raw(" "));
+                    line.spans.extend(checklist::badge(meta).spans);
+                }
+                if let (None, Some(tracking)) = (&columns, branch::tracking(b)) {
+                    line.spans
+                        .push(Span::styled(format!(" {tracking}"), TRACKING_STYLE));
+                }
+                if let (
+                    None,
+                    Some(reviews::Lookup::Found {
+                        review: Some(review),
+                        ..
+                    }),
+                ) = (&columns, self.reviews.get(&b.name))
+                {
+                    line.spans.push(Span::raw(" "));
+                    line.spans.extend(columns::pr(review).spans);
+                }
+                if let Some(target) = &b.symbolic {
+                    let target = redact::branch(target);
+                    line.spans.push(match b.commits().is_empty() {
+                        true => Span::styled(
+                            format!(" → {target} ({})", t!("gone")),
+                            Style::new().fg(RED.c300),
+                        ),
+                        false => Span::styled(format!(" → {target}"), TRAILER_STYLE),
+                    });
+                }
+                let Some(columns) = &columns else {
+                    return Some(vec![line]);
+                };
+                let cells = columns.iter().map(|column| match column {
+                    columns::Column::Name => line.clone(),
+                    column => columns::cell(*column, b, self.reviews.get(&b.name), meta, now),
+                });
+                Some(cells.collect())
+            })
+            .collect();
+        let lines = match &columns {
+            // less the highlight symbol and the scrollbar
+            Some(columns) => columns::align(columns, cells, area.width.saturating_sub(2).into()),
+            None => cells
+                .into_iter()
+                .map(|c| c.and_then(|mut c| c.pop()))
+                .collect(),
+        };
+        let items: Vec<ListItem> = rows
+            .iter()
+            .zip(lines)
+            .map(|(row, line)| match (row, line) {
+                (branch::Row::Header(group, count), _) => {
+                    let header = format!("{} ({count})", group.label());
+                    ListItem::new(Line::styled(header, GROUP_STYLE))
+                }
+                (branch::Row::Branch(_), line) => ListItem::new(line.unwrap_or_default()),
+            })
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(theme::get().selected)
+            .highlight_symbol(">")
+            .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+        // the list state counts branches, the widget counts rows including headers
+        let selected = self.branch_list.state.selected().map(|_| {
+            let idx = self.selected_index();
+            rows.iter()
+                .position(|r| *r == branch::Row::Branch(idx))
+                .unwrap_or_default()
+        });
+        let mut view = ListState::default()
+            .with_offset(self.branch_list.state.offset())
+            .with_selected(selected);
+        StatefulWidget::render(list, area, buf, &mut view);
+        *self.branch_list.state.offset_mut() = view.offset();
+        let inner = Rect {
+            y: area.y + 1,
+            height: area.height.saturating_sub(1),
+            ..area
+        };
+        self.hits.list_header = Rect { height: 1, ..area };
+        self.hits.list_rows = inner;
+        self.hits.list_offset = view.offset();
+        self.hits.list_index = rows
+            .iter()
+            .map(|row| match row {
+                branch::Row::Branch(idx) => Some(*idx),
+                branch::Row::Header(..) => None,
+            })
+            .collect();
+        scroll::scrollbar(inner, buf, stats.shown, self.selected_index());
+        if self.loading.is_none() && self.branch_list.total <= 1 {
+            let rows = inner.height.saturating_sub(stats.shown as u16 + 1);
+            let empty = Rect {
+                y: inner.y + stats.shown as u16 + 1,
+                height: rows,
+                ..inner
+            };
+            let lines = if stats.shown == 0 {
+                vec![
+                    Line::raw(t!("No branches yet.")),
+                    Line::styled(
+                        t!("Make a first commit, then press n to branch off it."),
+                        HINT_STYLE,
+                    ),
+                ]
+            } else {
+                vec![
+                    Line::raw(t!("This is the only branch so far.")),
+                    Line::styled(t!("n: create a branch from it"), HINT_STYLE),
+                ]
+            };
+            Paragraph::new(lines).centered().render(empty, buf);
+        }
+    }
+
+    fn render_selected(&mut self, area: Rect, buf: &mut Buffer) {
+        let area = if self.has_remotes {
+            area
+        } else {
+            let [area, hint] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(2)]).areas(area);
+            let lines = vec![
+                Line::raw(t!("No remotes configured.")),
+                Line::styled(t!("f or space r p to add one"), HINT_STYLE),
+            ];
+            Paragraph::new(lines).centered().render(hint, buf);
+            area
+        };
+        let Some(branch) = self.branch_list.current() else {
+            return;
+        };
+        let _block = Block::new()
+            .title(Line::raw(t!("Details")).left_aligned())
+            .borders(Borders::TOP)
+            .border_set(symbols::border::EMPTY)
+            .border_style(theme::get().header)
+            .bg(theme::get().row_bg);
+        let review = match self.reviews.get(&branch.name) {
+            Some(reviews::Lookup::Found {
+                review: Some(review),
+                stale,
+            }) => Some(review_line(review, stale)),
+            Some(reviews::Lookup::Pending) => {
+                Some(Line::styled(t!("looking up the pull request…"), HINT_STYLE))
+            }
+            Some(reviews::Lookup::Failed(err)) => Some(Line::styled(
+                t!("pull request unavailable: {err}", err = err),
+                HINT_STYLE,
+            )),
+            Some(reviews::Lookup::Limited(until)) => Some(Line::styled(
+                t!(
+                    "pull request not looked up, rate limited until {time}",
+                    time = until.format("%H:%M")
+                ),
+                HINT_STYLE,
+            )),
+            Some(reviews::Lookup::Found { review: None, .. }) | None => None,
+        };
+        let meta = match branch.typ {
+            git2::BranchType::Local => self.checklist.get(&branch.name),
+            git2::BranchType::Remote => None,
+        };
+        let [header, status, triage, area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(review.is_some().into()),
+            Constraint::Length(meta.is_some().into()),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        if let Some(review) = review {
+            Paragraph::new(review).render(status, buf);
+        }
+        if let Some(meta) = meta {
+            Paragraph::new(checklist::line(meta)).render(triage, buf);
+        }
+        let contributors = branch
+            .contributors()
+            .iter()
+            .map(|(name, count)| format!("{} ({count})", redact::person(name)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let author = self
+            .author_filter
+            .as_deref()
+            .filter(|_| self.range.is_none());
+        let shown = |c: &&git::Commit| {
+            author.is_none_or(|author| {
+                c.author.name.as_deref() == Some(author) || c.co_authors().any(|a| a == author)
+            })
+        };
+        if let Some(range) = &self.range {
+            let text = t!(
+                "{count} selected  enter: show  y: copy  Y: copy as  w: worktree  v: visual  c: cherry-pick  r: revert  e: export  d: diffstat  f: fixup  a: autosquash  esc: done",
+                count = range.len()
+            );
+            Paragraph::new(text).style(HINT_STYLE).render(header, buf);
+        } else if let Some(author) = author {
+            let text = t!(
+                "{author}: {shown} of {total} commits  esc: show all",
+                author = redact::person(author),
+                shown = branch.commits().iter().filter(shown).count(),
+                total = branch.commits().len()
+            );
+            Paragraph::new(text)
+                .style(CONTRIBUTORS_STYLE)
+                .render(header, buf);
+        } else if !contributors.is_empty() {
+            let text = t!("authors: {authors}", authors = contributors);
+            Paragraph::new(text)
+                .style(CONTRIBUTORS_STYLE)
+                .render(header, buf);
+        }
+        let mut lines: Vec<Line> = vec![];
+        let mut cursor_line = None;
+        // the line each commit starts on
+        let mut starts = vec![];
+        // a column of check dots once any commit has them
+        let dots = branch
+            .commits()
+            .iter()
+            .any(|c| self.commit_checks.get(c.id).is_some());
+        for (idx, c) in branch
+            .commits()
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| shown(c))
+        {
+            let summary = redact::text(&c.summary);
+            let author = c.author.name.as_deref().unwrap_or(t!("<none>"));
+            let names = std::iter::once(author)
+                .chain(c.co_authors())
+                .map(redact::person)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let timestamp = &c.timestamp;
+            let line = format!("{timestamp}: {names}: {summary}");
+            let mut line = if self.author_colors {
+                Line::styled(line, authors::color(&c.author))
+            } else {
+                Line::raw(line)
+            };
+            match &c.replacement {
+                Some(git::Replacement::Object { short, .. }) => line.spans.push(Span::styled(
+                    format!("  ⇄ {}", t!("replaces {id}", id = short)),
+                    TRAILER_STYLE,
+                )),
+                Some(git::Replacement::Graft) => line.spans.push(Span::styled(
+                    format!("  ⇄ {}", t!("grafted")),
+                    TRAILER_STYLE,
+                )),
+                None => {}
+            }
+            if self.follow.is_new(branch, c.id) {
+                line = line.patch_style(NEW_STYLE);
+                line.spans
+                    .push(Span::styled(format!("  {}", t!("new")), NEW_STYLE));
+            }
+            if dots {
+                let dot = match self.commit_checks.get(c.id) {
+                    Some(Some(forge::Checks::Passed)) => {
+                        Span::styled("● ", Style::new().fg(GREEN.c300))
+                    }
+                    Some(Some(forge::Checks::Failed)) => {
+                        Span::styled("● ", Style::new().fg(RED.c300))
+                    }
+                    Some(Some(forge::Checks::Running)) => {
+                        Span::styled("● ", Style::new().fg(AMBER.c300))
+                    }
+                    Some(None) => Span::styled("· ", HINT_STYLE),
+                    None => Span::raw("  "),
+                };
+                line.spans.insert(0, dot);
+            }
+            starts.push((lines.len(), c.id));
+            if let Some(range) = &self.range {
+                if range.cursor == idx {
+                    cursor_line = Some(lines.len());
+                    line = line.patch_style(theme::get().selected);
+                } else if range.contains(idx) {
+                    line = line.patch_style(RANGE_STYLE);
+                }
+            }
+            lines.push(line);
+            for trailer in &c.trailers {
+                let text = format!("    {}: {}", trailer.key, redact::text(&trailer.value));
+                lines.push(Line::styled(text, TRAILER_STYLE));
+            }
+            for note in c.note.iter().flat_map(|n| n.lines()) {
+                lines.push(Line::styled(format!("    {note}"), NOTE_STYLE));
+            }
+        }
+        let unread = branch.partial || branch.commits().is_empty();
+        if unread && (self.history.is_some() || self.settle.is_some()) {
+            lines.extend(skeleton(area.width));
+        }
+        if branch.truncated {
+            lines.push(Line::styled(
+                t!("history truncated, the clone is shallow  space r d: deepen"),
+                HINT_STYLE,
+            ));
+        }
+        let len = lines.len();
+        if let Some(cursor) = cursor_line {
+            // keep the commit cursor in view
+            let height = usize::from(area.height).max(1);
+            self.details_scroll = self
+                .details_scroll
+                .clamp(cursor.saturating_sub(height - 1), cursor);
+        }
+        self.details_scroll = self.details_scroll.min(len.saturating_sub(1));
+        let shown = self.details_scroll..self.details_scroll + usize::from(area.height);
+        self.log_in_view = starts
+            .into_iter()
+            .filter(|(line, _)| shown.contains(line))
+            .map(|(_, id)| id)
+            .collect();
+        Paragraph::new(lines)
+            .scroll((self.details_scroll as u16, 0))
+            .render(area, buf);
+        scroll::scrollbar(area, buf, len, self.details_scroll);
+    }
+
+    fn render_frame(&mut self, frame: &mut Frame) {
+        self.height = frame.size().height;
+        frame.render_widget(self, frame.size());
+        theme::finish(frame.buffer_mut());
+    }
+
+    /// Collects what background work finished, then waits for input only as
+    /// long as the nearest deadline allows: the next frame, a key chord or a
+    /// notification expiring, the status interval, or the job poll interval
+    /// while anything runs. So loading, jobs and their progress update the
+    /// screen without a key being pressed.
+    ///
+    /// Input is read here, on the thread that owns the terminal, rather than
+    /// by a thread feeding a channel, which would keep reading keys while an
+    /// editor or an interactive command has the terminal.
+    fn handle_events(&mut self) -> EResult<(), Error> {
+        // draw what the settled selection has to show before waiting on input
+        if self
+            .settle
+            .is_some_and(|at| at.elapsed() >= SELECTION_DEBOUNCE)
+        {
+            self.redraw = true;
+            return Ok(self.settle_selection()?);
+        }
+        let jobs_done = self.poll_jobs();
+        // what quitting waited for is done
+        if self.quit_prompt == Some(jobs::Quit::Waiting) && !self.jobs.is_busy() {
+            self.exit = true;
+        }
+        self.poll_askpass();
+        self.poll_watch();
+        self.poll_status();
+        let reviewed = self.reviews.poll();
+        // the next ones in view, or those a rate limit held up
+        if reviewed || self.reviews.retry_due() {
+            self.request_review();
+        }
+        let followed = self.poll_follow();
+        let checked = self.commit_checks.poll();
+        self.commit_checks.retry_due();
+        self.request_checks();
+        let connected = self.poll_network();
+        let logged = self.show_log && logs::count() != self.logged;
+        let searched = match self.nav.current_mut() {
+            nav::View::Pickaxe(search) => search.poll() || search.is_running(),
+            nav::View::Refs(view) => view.poll(),
+            _ => false,
+        };
+        let mut finished = self.output.as_mut().is_some_and(Output::poll);
+        if finished {
+            let passed = self.output.as_ref().is_some_and(Output::succeeded);
+            let after = self.after_output.take();
+            self.after_output(after, passed);
+        }
+        finished |= self.poll_hidden_outputs();
+        if finished {
+            self.load_branches()?;
+        }
+        let loaded = self.poll_branches().unwrap_or_else(|err| {
+            self.loading = None;
+            self.notifications.report(&err);
+            true
+        });
+        let read = self.poll_history();
+        let marked = self.poll_stale_and_stacks();
+        if let Some(dashboard) = &mut self.dashboard {
+            dashboard.poll();
+        }
+        // one more frame once a command finishes, to show that it did
+        let running = finished
+            || jobs_done
+            || loaded
+            || read
+            || marked
+            || self.stale_rx.is_some()
+            || self.stacks_rx.is_some()
+            || reviewed
+            || checked
+            || followed
+            || connected
+            || logged
+            || searched
+            || self.reviews.is_loading()
+            || self.commit_checks.is_loading()
+            || self.network.is_checking()
+            || self.output.as_ref().is_some_and(Output::is_running)
+            || !self.hidden_outputs.is_empty()
+            || self.dashboard.as_ref().is_some_and(Dashboard::is_loading);
+        let busy =
+            self.jobs.is_busy() || self.loading.is_some() || self.history.is_some() || running;
+        // whatever is in progress may have moved on since the last frame
+        self.redraw |= busy;
+        let busy = busy.then_some(JOB_POLL_INTERVAL);
+        let settle = self
+            .settle
+            .map(|at| SELECTION_DEBOUNCE.saturating_sub(at.elapsed()));
+        let status = match self.status_rx {
+            Some(_) => JOB_POLL_INTERVAL,
+            None => STATUS_INTERVAL.saturating_sub(self.status_at.elapsed()),
+        };
+        let timeout = [
+            self.chord.timeout(),
+            self.notifications.timeout(),
+            busy,
+            Some(status),
+            settle,
+            // a load under way is waited on as it is
+            self.watch
+                .as_ref()
+                .filter(|_| self.loading.is_none())
+                .and_then(git::Watch::wait),
+            self.frame_wait(),
+            self.network.wait(),
+            self.reviews.retry_wait(),
+            self.commit_checks.retry_wait(),
+            self.follow.wait(),
+            self.footer_note
+                .as_ref()
+                .map(|(_, _, at)| FOOTER_NOTE.saturating_sub(at.elapsed())),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        if let Some(timeout) = timeout {
+            if !self.input.poll(timeout)? {
+                self.redraw |= self.notifications.expire();
+                if self
+                    .footer_note
+                    .as_ref()
+                    .is_some_and(|(_, _, at)| at.elapsed() >= FOOTER_NOTE)
+                {
+                    self.footer_note = None;
+                    self.redraw = true;
+                }
+                self.redraw |= self.chord.timeout().is_some_and(|t| t.is_zero());
+                if let Some(action) = self.chord.expire(&self.keymap) {
+                    self.perform(action);
+                }
+                return Ok(());
+            }
+        }
+        let event = self.input.read()?;
+        self.handle_event(event)?;
+        let deadline = Instant::now() + INPUT_BATCH;
+        while Instant::now() < deadline
+            && !self.exit
+            && self.suspend.is_none()
+            && self.external.is_none()
+            && self.snapshot.is_none()
+            && self.input.poll(Duration::ZERO)?
+        {
+            let event = self.input.read()?;
+            self.handle_event(event)?;
+        }
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) -> EResult<(), Error> {
+        self.redraw = true;
+        match event {
+            Event::Key(key_event) => self
+                .handle_key(key_event)
+                .wrap_err("handle key failed")
+                .wrap_err_with(|| format!("{key_event:#?}"))?,
+            Event::Paste(text) => {
+                if let Some((modal, _)) = &mut self.modal {
+                    modal.paste(&text);
+                } else if self.filtering {
+                    // the query is a single line
+                    let mut query = self.branch_list.query.clone();
+                    query.extend(text.chars().map(|c| if c.is_control() { ' ' } else { c }));
+                    self.filter_branches(query)?;
+                }
+            }
+            Event::Mouse(mouse_event) if !self.has_overlay() => {
+                if let Some(click) = self.mouse.handle(&self.hits, mouse_event) {
+                    if let Err(err) = self.handle_click(click) {
+                        self.notifications.report(&err);
+                    }
+                }
+            }
+            Event::Resize(_, height) => self.rescale_lists(height),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Keeps the selected row of every list about as far down it as it was
+    /// before the screen changed height, see [`scroll::rescale`].
+    fn rescale_lists(&mut self, height: u16) {
+        let from = self.height;
+        // the list state counts branches, the offset counts rows including headers
+        let idx = self.selected_index();
+        let row = self.hits.list_index.iter().position(|i| *i == Some(idx));
+        if let Some(row) = row.filter(|_| self.branch_list.state.selected().is_some()) {
+            let mut view = ListState::default()
+                .with_offset(self.branch_list.state.offset())
+                .with_selected(Some(row));
+            scroll::rescale(&mut view, from, height);
+            *self.branch_list.state.offset_mut() = view.offset();
+        }
+        let popups = [
+            self.modal
+                .as_mut()
+                .and_then(|(modal, _)| modal.list_state()),
+            Some(&mut self.jobs.state),
+            Some(&mut self.marks.state),
+            self.git_config.shown_mut().map(|c| &mut c.state),
+            self.settings.shown_mut().map(|s| &mut s.state),
+            self.accounts.shown_mut().map(|a| &mut a.state),
+        ];
+        for state in self.nav.list_states().chain(popups.into_iter().flatten()) {
+            scroll::rescale(state, from, height);
+        }
+    }
+
+    /// Whether a popup currently owns the input.
+    fn has_overlay(&self) -> bool {
+        self.modal.is_some()
+            || self.quit_prompt.is_some()
+            || self.show_jobs
+            || self.show_marks
+            || self.show_conflicts
+            || self.show_notifications
+            || self.show_authors
+            || self.divergence.is_some()
+            || self.output.is_some()
+            || self.remotes.is_shown()
+            || self.git_config.is_shown()
+            || self.bulk_report.is_some()
+            || self.dir_picker.is_some()
+            || self.settings.is_shown()
+            || self.accounts.is_shown()
+            || self.dashboard.is_some()
+            || self.help.is_shown()
+    }
+
+    fn handle_click(&mut self, click: Click) -> EResult<()> {
+        match click {
+            Click::ScrollUp => self.select_previous(),
+            Click::ScrollDown => self.select_next(),
+            Click::Single(mouse::Target::ListHeader) => self.toggle_sort_key(),
+            Click::Single(mouse::Target::Branch(idx)) => self.select_index(idx),
+            Click::Single(mouse::Target::Crumb(depth)) => {
+                self.back_to(depth);
+                Ok(())
+            }
+            Click::Single(mouse::Target::Tab(idx)) => self.switch_tab(idx),
+            Click::Double(mouse::Target::Branch(idx)) => {
+                self.select_index(idx)?;
+                self.toggle_branch()
+            }
+            Click::Right(mouse::Target::Branch(idx)) => {
+                self.select_index(idx)?;
+                self.branch_menu();
+                Ok(())
+            }
+            Click::Middle(mouse::Target::Branch(idx)) => {
+                let Some(branch) = self.branch_list.items.get(idx) else {
+                    return Ok(());
+                };
+                clipboard::copy(&branch.name)?;
+                let msg = t!("copied {name}", name = redact::branch(&branch.name));
+                self.notifications.info(msg);
+                Ok(())
+            }
+            Click::Double(
+                mouse::Target::ListHeader | mouse::Target::Crumb(_) | mouse::Target::Tab(_),
+            )
+            | Click::Middle(
+                mouse::Target::ListHeader | mouse::Target::Crumb(_) | mouse::Target::Tab(_),
+            )
+            | Click::Right(
+                mouse::Target::ListHeader | mouse::Target::Crumb(_) | mouse::Target::Tab(_),
+            ) => Ok(()),
+        }
+    }
+
+    fn select_index(&mut self, idx: usize) -> EResult<()> {
+        if idx < self.branch_list.items.len() {
+            self.branch_list.state.select(Some(idx));
+            self.selection_moved();
+        }
+        Ok(())
+    }
+
+    /// Switches between sorting by name and by date, flipping the direction when
+    /// the key is already the active one.
+    fn toggle_sort_key(&mut self) -> EResult<()> {
+        use branch::Sort;
+        let sort = match self.branch_list.sort {
+            Sort::DateDescending => Sort::DateAscending,
+            Sort::DateAscending => Sort::NameAscending,
+            Sort::NameAscending => Sort::NameDescending,
+            Sort::NameDescending | Sort::Divergence => Sort::DateDescending,
+        };
+        self.set_sort(sort)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> EResult<()> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(());
+        }
+        if let Some((modal, _)) = &mut self.modal {
+            match modal.handle_key(key) {
+                Outcome::Pending => {}
+                Outcome::Cancelled => self.modal = None,
+                Outcome::Submitted(value) => {
+                    if let Some((_, purpose)) = self.modal.take() {
+                        if let Err(err) = self.on_submit(purpose, value) {
+                            self.report(&err);
+                        }
+                    }
+                }
+            }
+            return Ok(());
+        }
+        if self.quit_prompt.is_some() {
+            match key.code {
+                KeyCode::Char('w') | KeyCode::Enter => {
+                    self.quit_prompt = Some(jobs::Quit::Waiting);
+                }
+                KeyCode::Char('c') => {
+                    self.jobs.cancel_all();
+                    self.quit_prompt = Some(jobs::Quit::Waiting);
+                }
+                KeyCode::Esc => {
+                    self.quit_prompt = None;
+                    self.switch_to = None;
+                    self.exit_to = None;
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(picker) = &mut self.dir_picker {
+            match picker.handle(key.code) {
+                dirs::Picked::Dir(dir) => self.switch_repo(dir),
+                dirs::Picked::Cancel => self.dir_picker = None,
+                dirs::Picked::Nothing => {}
+            }
+            return Ok(());
+        }
+        if let Some(output) = &mut self.output {
+            match key.code {
+                // a command goes on out of sight, as it may be halfway through
+                // writing something, unless it is a diff that is only read
+                KeyCode::Esc | KeyCode::Char('q')
+                    if output.is_running() && output.diff().is_none() =>
+                {
+                    if let Some(output) = self.output.take() {
+                        let title = redact::text(output.title()).into_owned();
+                        self.notifications
+                            .info(t!("{title} goes on in the background", title = title));
+                        self.hidden_outputs.push((output, self.after_output.take()));
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('s') => {
+                    output.kill();
+                    self.output = None;
+                    if let Some(AfterOutput::Commits(name, _)) = self.after_output.take() {
+                        self.notifications
+                            .info(t!("{name} cancelled with its hooks", name = name));
+                    }
+                }
+                KeyCode::Char('j') | KeyCode::Down => output.scroll_by(1),
+                KeyCode::Char('k') | KeyCode::Up => output.scroll_by(-1),
+                KeyCode::PageDown | KeyCode::Char(' ') => output.scroll_by(10),
+                KeyCode::PageUp => output.scroll_by(-10),
+                KeyCode::Char('g') | KeyCode::Home => output.scroll_to_top(),
+                KeyCode::Char('G') | KeyCode::End => output.follow(),
+                KeyCode::Char('x') if output.is_folded() => output.unfold(),
+                KeyCode::Char('o') => {
+                    if let Some(submodule) = output.submodule().cloned() {
+                        self.submodule_commits(&submodule);
+                    }
+                }
+                KeyCode::Char('w') if output.diff().is_some() => {
+                    self.diff_options.ignore_whitespace ^= true;
+                    self.rerun_diff();
+                }
+                KeyCode::Char('c') if output.diff().is_some() => {
+                    self.diff_options.context = self.diff_options.context.next();
+                    self.rerun_diff();
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(remotes) = self.remotes.shown_mut() {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.remotes.hide(),
+                KeyCode::Char('j') | KeyCode::Down => remotes.state.select_next(),
+                KeyCode::Char('k') | KeyCode::Up => remotes.state.select_previous(),
+                KeyCode::Char('e') => self.edit_refspecs(),
+                KeyCode::Char('p') => self.toggle_prune()?,
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(report) = &mut self.bulk_report {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.bulk_report = None,
+                KeyCode::Char('j') | KeyCode::Down => {
+                    report.scroll = report.scroll.saturating_add(1)
+                }
+                KeyCode::Char('k') | KeyCode::Up => report.scroll = report.scroll.saturating_sub(1),
+                KeyCode::Char('e') => {
+                    report.export(&mut self.notifications);
+                    self.notifications
+                        .success(t!("exported to the notifications and the log"));
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(git_config) = self.git_config.shown_mut() {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.git_config.hide(),
+                KeyCode::Char('j') | KeyCode::Down => git_config.state.select_next(),
+                KeyCode::Char('k') | KeyCode::Up => git_config.state.select_previous(),
+                KeyCode::Enter | KeyCode::Char('e') => {
+                    if let Some(key) = git_config.selected_key() {
+                        let value = git_config.effective(key).map(|e| e.value.clone());
+                        self.prompt_git_config(key, &value.unwrap_or_default(), None);
+                    }
+                }
+                KeyCode::Char('d') => {
+                    if let Some(key) = git_config.selected_key() {
+                        self.set_git_config(key, None)?;
+                    }
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(settings) = self.settings.shown_mut() {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.settings.hide(),
+                KeyCode::Char('j') | KeyCode::Down => settings.state.select_next(),
+                KeyCode::Char('k') | KeyCode::Up => settings.state.select_previous(),
+                KeyCode::Enter | KeyCode::Char(' ') => self.change_setting()?,
+                KeyCode::Char('d') => {
+                    if let Some(setting) = settings.selected() {
+                        self.set_setting(setting.key, None)?;
+                    }
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(accounts) = self.accounts.shown_mut() {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.accounts.hide(),
+                KeyCode::Char('j') | KeyCode::Down => accounts.state.select_next(),
+                KeyCode::Char('k') | KeyCode::Up => accounts.state.select_previous(),
+                KeyCode::Enter | KeyCode::Char('e') => self.edit_token(),
+                KeyCode::Char('d') => self.delete_token(),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.show_jobs {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('J') => self.show_jobs = false,
+                KeyCode::Char('j') | KeyCode::Down => self.jobs.state.select_next(),
+                KeyCode::Char('k') | KeyCode::Up => self.jobs.state.select_previous(),
+                KeyCode::Char('x') | KeyCode::Delete => self.jobs.cancel_selected(),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.show_conflicts {
+            if let KeyCode::Esc | KeyCode::Char('q') = key.code {
+                self.show_conflicts = false;
+            }
+            return Ok(());
+        }
+        if self.show_marks {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.show_marks = false,
+                KeyCode::Char('j') | KeyCode::Down => self.marks.state.select_next(),
+                KeyCode::Char('k') | KeyCode::Up => self.marks.state.select_previous(),
+                KeyCode::Char('d') | KeyCode::Delete => self.marks.remove_selected(),
+                KeyCode::Enter => {
+                    if let Some(mark) = self.marks.selected() {
+                        self.show_marks = false;
+                        self.jump_to_mark(mark)?;
+                    }
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(scroll) = self.help.shown_mut() {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') => self.help.hide(),
+                KeyCode::Char('j') | KeyCode::Down => *scroll += 1,
+                KeyCode::Char('k') | KeyCode::Up => *scroll = scroll.saturating_sub(1),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.dashboard.is_some() {
+            if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+                self.dashboard = None;
+            }
+            return Ok(());
+        }
+        if self.show_authors {
+            if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+                self.show_authors = false;
+            }
+            return Ok(());
+        }
+        if let Some((_, scroll)) = &mut self.divergence {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.divergence = None,
+                KeyCode::Char('j') | KeyCode::Down => *scroll += 1,
+                KeyCode::Char('k') | KeyCode::Up => *scroll = scroll.saturating_sub(1),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.show_notifications {
+            if matches!(
+                key.code,
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('N')
+            ) {
+                self.show_notifications = false;
+            }
+            return Ok(());
+        }
+        if self.nav.is_root() && self.nav.tab() > 0 {
+            let action = self.keymap.exact(&[key.into()]);
+            if let Some(action) = action.filter(|a| a.switches_tab()) {
+                return self.dispatch(action);
+            }
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Backspace => {
+                    return self.switch_tab(0);
+                }
+                _ => {}
+            }
+        }
+        if let Some(state) = self.nav.current_mut().list_state() {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Backspace => self.back(),
+                KeyCode::Char('j') | KeyCode::Down => state.select_next(),
+                KeyCode::Char('k') | KeyCode::Up => state.select_previous(),
+                KeyCode::Char('g') | KeyCode::Home => state.select_first(),
+                KeyCode::Char('G') | KeyCode::End => state.select_last(),
+                KeyCode::Enter => self.show_found_commit(),
+                KeyCode::Char('i') => self.read_status_files(true),
+                KeyCode::Char('s') => self.stage()?,
+                KeyCode::Char('d') => self.confirm_discard(),
+                KeyCode::Char('u') => self.restore_discarded()?,
+                KeyCode::Tab => self.read_hunks(true)?,
+                KeyCode::Char('b') => self.prompt_branch_at_ref(),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(range) = &mut self.range {
+            let count = self.branch_list.current().map_or(0, |b| b.commits().len());
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Backspace => self.back(),
+                KeyCode::Char('j') | KeyCode::Down => range.move_by(1, count),
+                KeyCode::Char('k') | KeyCode::Up => range.move_by(-1, count),
+                KeyCode::Char('g') | KeyCode::Home => range.cursor = 0,
+                KeyCode::Char('G') | KeyCode::End => range.move_by(isize::MAX, count),
+                KeyCode::Char('v') | KeyCode::Char('V') => range.toggle_visual(),
+                KeyCode::Enter => self.show_cursor_commit(),
+                KeyCode::Char('y') => self.copy_cursor_commit()?,
+                KeyCode::Char('Y') => self.pick_copy_format()?,
+                KeyCode::Char('w') => self.inspect_commit()?,
+                KeyCode::Char('c') => self.range_op(range::Op::CherryPick),
+                KeyCode::Char('r') => self.range_op(range::Op::Revert),
+                KeyCode::Char('e') => self.range_op(range::Op::Export),
+                KeyCode::Char('d') => self.range_op(range::Op::Diffstat),
+                KeyCode::Char('f') => self.range_op(range::Op::Fixup),
+                KeyCode::Char('a') => self.range_op(range::Op::Autosquash),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(pending) = self.pending_mark.take() {
+            // any key that cannot name a mark cancels
+            match key.code {
+                KeyCode::Char(c) if Marks::is_valid(c) => match pending {
+                    marks::Pending::Set => self.set_mark(c),
+                    marks::Pending::Jump => self.jump_to_mark(c)?,
+                },
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.filtering {
+            let mut query = self.branch_list.query.clone();
+            match key.code {
+                KeyCode::Char(c) if (key.modifiers - KeyModifiers::SHIFT).is_empty() => {
+                    query.push(c);
+                }
+                KeyCode::Backspace => {
+                    query.pop();
+                }
+                KeyCode::Enter => self.filtering = false,
+                KeyCode::Esc => {
+                    self.filtering = false;
+                    query.clear();
+                }
+                _ => {}
+            }
+            return self.filter_branches(query);
+        }
+        if self.jump {
+            match key.code {
+                KeyCode::Char(c) if (key.modifiers - KeyModifiers::SHIFT).is_empty() => {
+                    return self.jump_to(c);
+                }
+                KeyCode::Esc => {
+                    self.jump = false;
+                    return Ok(());
+                }
+                // anything else leaves jump mode and does what it always does
+                _ => self.jump = false,
+            }
+        }
+        if key.code == KeyCode::Esc && !self.branch_list.query.is_empty() {
+            self.chord.clear();
+            return self.filter_branches(String::new());
+        }
+        if key.code == KeyCode::Esc {
+            if self.chord.pending().is_empty() && self.author_filter.take().is_none() {
+                self.notifications.dismiss();
+            }
+            self.chord.clear();
+            return Ok(());
+        }
+        if let Resolution::Action(action) = self.chord.push(&self.keymap, key.into()) {
+            self.perform(action);
+        }
+        Ok(())
+    }
+
+    /// Dispatches an action, reporting failures as error toasts rather than
+    /// tearing down the UI.
+    fn perform(&mut self, action: Action) {
+        crash::record(format!("{action:?}"));
+        tracing::debug!(?action, "perform");
+        if let Err(err) = self.dispatch(action) {
+            self.notifications.report(&err);
+        }
+    }
+
+    fn dispatch(&mut self, action: Action) -> EResult<()> {
+        match action {
+            Action::Quit => self.exit(),
+            Action::SelectNone => self.select_none()?,
+            Action::SelectNext => self.select_next()?,
+            Action::SelectPrevious => self.select_previous()?,
+            Action::SelectFirst => self.select_first()?,
+            Action::SelectLast => self.select_last()?,
+            Action::CycleSort => self.cycle_sort()?,
+            Action::PickSort => self.pick_sort(),
+            Action::ToggleBranch => self.toggle_branch()?,
+            Action::BranchMenu => self.branch_menu(),
+            Action::BranchesTab => self.switch_tab(0)?,
+            Action::TagsTab => self.switch_tab(1)?,
+            Action::RemotesTab => self.switch_tab(2)?,
+            Action::NextTab => self.switch_tab((self.nav.tab() + 1) % 3)?,
+            Action::PreviousTab => self.switch_tab((self.nav.tab() + 2) % 3)?,
+            Action::Command(idx) => self.run_command(idx),
+            Action::PickView => self.pick_view(),
+            Action::ToggleStale => self.toggle_stale()?,
+            Action::DeleteStale => self.delete_stale(),
+            Action::LoadAllBranches => self.load_all_branches()?,
+            Action::SetBranchState => self.pick_branch_state(),
+            Action::EditBranchNote => self.prompt_branch_note(),
+            Action::View(idx) => self.apply_view(Some(idx))?,
+            Action::EditNote => self.edit_note()?,
+            Action::ShowRemotes => {
+                let items = self.repo.remotes()?;
+                self.remotes.show(Remotes::default).reload(items);
+            }
+            Action::ShowConflicts => self.show_conflicts = true,
+            Action::ShowGitConfig => {
+                let entries = self.repo.config_entries()?;
+                self.git_config.show(GitConfig::default).reload(entries);
+            }
+            Action::ShowSettings => {
+                self.settings.show(|| Settings::new(self.conflicts.clone()));
+            }
+            Action::ShowAccounts => {
+                let items = self.load_accounts()?;
+                self.accounts.show(Accounts::default).reload(items);
+            }
+            Action::CommandLine => {
+                let input = modal::Input::new(t!("Command, e.g. git log -3"));
+                self.modal = Some((Modal::Input(input), Purpose::CommandLine));
+            }
+            Action::ShowNotifications => self.show_notifications = true,
+            Action::ToggleAuthorColors => self.author_colors = !self.author_colors,
+            Action::ShowHelp => {
+                self.help.show(Default::default);
+            }
+            Action::ShowStats => self.dashboard = Some(Dashboard::new(self.repo.stats())),
+            Action::SquashBranch => self.squash_branch()?,
+            Action::CreatePullRequest => self.create_pull_request()?,
+            Action::OpenPullRequest => self.open_pull_request()?,
+            Action::Deepen => self.prompt_deepen("", None),
+            Action::SetRemoteHead => self.pick_remote_head()?,
+            Action::DeleteBranch => self.confirm_delete_branch()?,
+            Action::DeleteBranches => self.confirm_delete(),
+            Action::Push => self.push(),
+            Action::PushMarked => self.push_marked(),
+            Action::DiffWorktree => self.diff_worktree(),
+            Action::ShowStatus => self.show_status()?,
+            Action::ShowRefs => {
+                let refs = self.repo.stream_refs();
+                self.drill(nav::View::Refs(refs::RefsView::new(refs)));
+            }
+            Action::Commit => self.start_commit()?,
+            Action::Stash => {
+                let input = modal::Input::new(t!("Stash message (optional)"));
+                self.modal = Some((Modal::Input(input), Purpose::Stash));
+            }
+            Action::Snapshot => {
+                let name = chrono::Local::now().format("grit-%Y%m%d-%H%M%S.txt");
+                let input = modal::Input::new(t!("Save the screen to (.txt, .ans or .html)"))
+                    .with_text(name.to_string());
+                self.modal = Some((Modal::Input(input), Purpose::Snapshot));
+            }
+            Action::ExportBranches => {
+                let title = t!("Export the branches to (.csv or .md, empty to copy)");
+                let input = modal::Input::new(title).with_text("branches.md".to_string());
+                self.modal = Some((Modal::Input(input), Purpose::ExportBranches));
+            }
+            Action::JumpMode => {
+                self.chord.clear();
+                self.jump = true;
+            }
+            Action::FilterBranches => {
+                self.chord.clear();
+                self.filtering = true;
+            }
+            Action::SelectCommits => {
+                self.load_selected()?;
+                self.load_current_now()?;
+                let name = self
+                    .branch_list
+                    .current()
+                    .filter(|b| !b.commits().is_empty())
+                    .map(|b| b.name.clone());
+                if let Some(name) = name {
+                    self.drill(nav::View::Commits(name.clone()));
+                    self.range = Some(Range::new(name));
+                }
+            }
+            Action::ToggleReplace => {
+                let replace = !self.repo.replace();
+                self.repo = self.repo.clone().with_replace(replace);
+                self.load_branches()?;
+                if !self.replacements {
+                    self.notifications
+                        .info(t!("no history was replaced in this repository"));
+                }
+            }
+            Action::ToggleGroups => {
+                self.branch_list.grouped = !self.branch_list.grouped;
+                self.branch_list.sort();
+            }
+            Action::ToggleStacks => {
+                self.branch_list.stacked = !self.branch_list.stacked;
+                self.read_stacks();
+                self.branch_list.sort();
+            }
+            Action::Restack => self.confirm_restack(),
+            Action::MergeBranch => self.confirm_integrate(false)?,
+            Action::RebaseOnto => self.confirm_integrate(true)?,
+            Action::ToggleHeat => {
+                self.heat = match self.heat {
+                    Some(_) => None,
+                    None => Some(heat_weeks(&self.config)),
+                };
+                if self.heat.is_some() && self.branch_list.all().any(|b| b.partial) {
+                    self.load_branches()?;
+                }
+            }
+            Action::ShowDivergence => self.show_divergence()?,
+            Action::ShowAuthors => {
+                self.load_selected()?;
+                self.load_current_now()?;
+                self.show_authors = true;
+            }
+            Action::SetMark | Action::JumpToMark => {
+                self.chord.clear();
+                self.pending_mark = Some(match action {
+                    Action::SetMark => marks::Pending::Set,
+                    _ => marks::Pending::Jump,
+                });
+            }
+            Action::FilterAuthor => self.pick_author()?,
+            Action::Pickaxe => {
+                let input = modal::Input::new(t!("Find commits adding or removing"));
+                self.modal = Some((Modal::Input(input), Purpose::Pickaxe));
+            }
+            Action::JumpToWorktree => self.jump_to_worktree()?,
+            Action::ToggleFollow => self.toggle_follow(),
+            Action::OpenRepository => self.open_dir_picker(),
+            Action::FileHistory => {
+                let input =
+                    modal::Input::new(t!("History of file (from the top of the work tree)"));
+                self.modal = Some((Modal::Input(input), Purpose::FileHistory));
+            }
+            Action::ShowMarks => {
+                self.marks.open();
+                self.show_marks = true;
+            }
+            Action::ShowJobs => {
+                self.show_jobs = true;
+                self.jobs.state.select_first();
+            }
+            Action::ToggleLog => self.show_log = !self.show_log,
+            Action::FetchAll => self.fetch_all(),
+            Action::PruneRemotes => self.prune_remotes(),
+            Action::ScrollDetailsDown => self.details_scroll += 1,
+            Action::ScrollDetailsUp => self.details_scroll = self.details_scroll.saturating_sub(1),
+            Action::CreateBranch => self.create_branch()?,
+            Action::RenameBranch => self.prompt_rename_branch(),
+            Action::ToggleMark => {
+                self.branch_list.toggle_mark();
+                self.select_next()?;
+            }
+            Action::ClearMarks => self.branch_list.marked.clear(),
+            Action::BatchRename => self.prompt_rename_pattern(String::new(), None),
+        }
+        Ok(())
+    }
+
+    /// Asks what ssh asks, once nothing else is being asked.
+    fn poll_askpass(&mut self) {
+        if self.modal.is_some() {
+            return;
+        }
+        let Some(request) = self.askpass.as_ref().and_then(askpass::Askpass::next) else {
+            return;
+        };
+        let modal = match &request.prompt {
+            askpass::Prompt::HostKey { host, fingerprint } => {
+                let message = t!(
+                    "{host} is not in known_hosts yet. Its key is\n{fingerprint}\n\nTrust it and add it to known_hosts?",
+                    host = host,
+                    fingerprint = fingerprint
+                );
+                Modal::Confirm(modal::Confirm::new(t!("Unknown host"), message).strict())
+            }
+            askpass::Prompt::Secret(prompt) => Modal::Input(modal::Input::new(prompt).secret()),
+            askpass::Prompt::Text(prompt) => Modal::Input(modal::Input::new(prompt)),
+        };
+        self.modal = Some((modal, Purpose::Askpass(request)));
+        self.redraw = true;
+    }
+
+    /// Reports finished jobs, returning whether there were any.
+    fn poll_jobs(&mut self) -> bool {
+        let mut refresh = false;
+        let mut fetched = vec![];
+        let finished = self.jobs.poll();
+        let any = !finished.is_empty();
+        // a job may have pushed, or opened a pull request
+        if finished.iter().any(|f| matches!(f.result, Some(Ok(_)))) {
+            self.reviews.invalidate();
+            self.request_review();
+        }
+        for finished in finished {
+            if let Some(then) = self.hook_jobs.remove(&finished.id) {
+                self.hook_ran(finished, then);
+                continue;
+            }
+            if let Some((path, short)) = self.scratch_jobs.remove(&finished.id) {
+                self.scratch_added(finished, path, short);
+                continue;
+            }
+            // the branches show what is true again, whether it worked or not
+            if let Some((names, pending::Op::Delete)) = self.pending.finish(finished.id) {
+                self.forget_deleted(&names);
+            }
+            refresh |= finished.refresh;
+            if let Some(before) = self.fetches.remove(&finished.id) {
+                if matches!(finished.result, Some(Ok(_))) {
+                    fetched.push(before);
+                }
+            }
+            if let Some(Ok(_)) = finished.result {
+                self.summary.ran(&finished.name);
+            }
+            let remote = self.remote_jobs.remove(&finished.id);
+            let worked = matches!(finished.result, Some(Ok(_)));
+            if let Some((title, groups)) = self.bulk.remove(&finished.id) {
+                let report = bulk::Report::new(title, &groups);
+                if remote && worked && report.any_succeeded() {
+                    self.reached();
+                }
+                if let (true, Some(Ok(msg))) = (report.is_detailed(), &finished.result) {
+                    // what else the job said, which the report has no place for
+                    if !msg.trim().is_empty() {
+                        self.notifications.info(msg.trim().to_string());
+                    }
+                    self.bulk_report = Some(report);
+                    continue;
+                }
+            }
+            if remote && worked {
+                self.reached();
+            }
+            match finished.result {
+                Some(Ok(msg)) if msg.trim().is_empty() => {
+                    self.notifications.success(finished.name);
+                }
+                Some(Ok(msg)) => {
+                    let msg = format!("{}: {}", finished.name, msg.trim());
+                    self.notifications.success(msg);
+                }
+                Some(Err(err)) => {
+                    // the network may be down, which is better found out now
+                    if network::is_unreachable(&err) {
+                        self.network.check();
+                    }
+                    self.notifications.report(&err)
+                }
+                None => self
+                    .notifications
+                    .info(t!("{job} cancelled", job = finished.name)),
+            }
+        }
+        if refresh {
+            if let Err(err) = self.load_branches() {
+                self.notifications.report(&err);
+            }
+            return any;
+        }
+        for before in fetched {
+            if let Err(err) = self.refresh_tracking(before) {
+                self.notifications.report(&err);
+            }
+        }
+        any
+    }
+
+    fn fetch_all(&mut self) {
+        if !self.has_remotes {
+            return self.prompt_remote_name(Retry::Fetch, "origin", None);
+        }
+        let loc = self.repo.location();
+        let hooks = self.hooks.clone();
+        // remote branches come and go with a fetch, so a list showing them is
+        // reloaded in full; otherwise only the affected branches are updated
+        let reload = self.branch_list.filter.typ() != Some(git2::BranchType::Local);
+        let before = if reload {
+            None
+        } else {
+            self.repo.remote_refs().ok()
+        };
+        let reload = before.is_none();
+        let repo = self.repo.clone();
+        let title = t!("fetch all remotes").to_string();
+        let groups = bulk::Groups::default();
+        let filled = groups.clone();
+        let id = self.jobs.submit(title.clone(), reload, move |cancel| {
+            // left out as `git fetch --all` leaves them out
+            let remotes: Vec<String> = repo
+                .remotes()?
+                .into_iter()
+                .filter(|remote| !remote.skip_fetch_all)
+                .map(|remote| remote.name)
+                .collect();
+            // one remote at a time, to tell which of them failed and why
+            for remote in &remotes {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                // what it fetched is in the refreshed list, so only the
+                // progress is shown, not the summary
+                let res = git::command::remote_progress(
+                    &loc,
+                    &["fetch", "--prune", "--progress", remote],
+                    || cancel.is_cancelled(),
+                    |line| cancel.progress(line),
+                );
+                let error = match res {
+                    Ok(_) => None,
+                    Err(err) if remotes.len() == 1 => return Err(err.into()),
+                    Err(err) => Some(format!("{err:#}")),
+                };
+                let label = t!("fetch").to_string();
+                bulk::add(&filled, remote, bulk::Item { label, error });
+            }
+            let hook = hooks.run(hooks::Event::PostFetch, &hooks::Context::default())?;
+            Ok(hook.unwrap_or_default())
+        });
+        self.bulk.insert(id, (title, groups));
+        self.remote_jobs.insert(id);
+        if let Some(before) = before {
+            self.fetches.insert(id, before);
+        }
+    }
+
+    /// Recomputes the upstream status of the local branches whose upstream
+    /// changed since `before`, a snapshot of the remote refs taken before a fetch.
+    fn refresh_tracking(&mut self, before: BTreeMap<String, git2::Oid>) -> EResult<()> {
+        let after = self.repo.remote_refs()?;
+        let changed: BTreeSet<&String> = before
+            .keys()
+            .chain(after.keys())
+            .filter(|name| before.get(*name) != after.get(*name))
+            .collect();
+        if changed.is_empty() {
+            return Ok(());
+        }
+        let mut updated = 0;
+        // the branches the query hides too, for when it changes
+        let list = &mut self.branch_list;
+        for branch in list.items.iter_mut().chain(&mut list.unmatched) {
+            if branch.typ != git2::BranchType::Local {
+                continue;
+            }
+            let affected = match self.repo.upstream(&branch.name) {
+                Some(upstream) => changed.contains(&upstream),
+                // the upstream was pruned
+                None => branch.tracking != Some(git::Tracking::NoUpstream),
+            };
+            if affected {
+                branch.tracking = Some(self.repo.tracking(&branch.name)?);
+                updated += 1;
+            }
+        }
+        if updated > 0 {
+            self.branch_list.sort();
+            self.notifications.info(t!(
+                "updated the upstream status of {count} branches",
+                count = updated
+            ));
+        }
+        Ok(())
+    }
+
+    fn edit_note(&mut self) -> EResult<()> {
+        self.load_selected()?;
+        let Some(commit) = self.branch_list.current().and_then(|b| b.commits().first()) else {
+            return Ok(());
+        };
+        let mut id = commit.id.to_string();
+        id.truncate(7);
+        let title = t!("Note on {commit} (ctrl-s: save)", commit = id);
+        let editor = modal::Editor::new(title).with_text(commit.note.as_deref().unwrap_or(""));
+        self.edit(editor, Purpose::Note(commit.id));
+        Ok(())
+    }
+
+    /// The selected branch, if it is a local one, else why it is not.
+    fn selected_local(&mut self) -> Option<String> {
+        let branch = self.branch_list.current()?;
+        if branch.typ != git2::BranchType::Local {
+            self.notifications
+                .error(t!("only local branches have a review state"));
+            return None;
+        }
+        Some(branch.name.clone())
+    }
+
+    /// Asks where the selected local branch stands in review.
+    fn pick_branch_state(&mut self) {
+        let Some(name) = self.selected_local() else {
+            return;
+        };
+        let mut items: Vec<String> = checklist::State::ALL
+            .iter()
+            .map(|s| s.label().to_string())
+            .collect();
+        items.push(t!("clear").to_string());
+        let title = t!("Review state of {branch}", branch = redact::branch(&name));
+        let select = modal::Select::new(title, items);
+        self.modal = Some((Modal::Select(select), Purpose::BranchState(name)));
+    }
+
+    /// Asks for the note to keep on the selected local branch.
+    fn prompt_branch_note(&mut self) {
+        let Some(name) = self.selected_local() else {
+            return;
+        };
+        let note = self.checklist.get(&name).and_then(|m| m.note.clone());
+        let title = t!("Note on {branch}", branch = redact::branch(&name));
+        let input = modal::Input::new(title).with_text(note.unwrap_or_default());
+        self.modal = Some((Modal::Input(input), Purpose::BranchNote(name)));
+    }
+
+    /// Where the local branch `name` is hosted, or why it is not.
+    fn hosted(&self, name: &str) -> EResult<Result<Hosted, String>> {
+        let Some(upstream) = self.repo.upstream(name) else {
+            return Ok(Err(t!(
+                "{name} has no upstream, push it before opening a pull request",
+                name = name
+            )));
+        };
+        let remotes = self.repo.remotes()?;
+        let Some((remote, head)) = remotes.iter().find_map(|r| {
+            let head = upstream.strip_prefix(&format!("{}/", r.name))?;
+            Some((r, head.to_string()))
+        }) else {
+            return Ok(Err(t!(
+                "the remote of {upstream} is gone",
+                upstream = upstream
+            )));
+        };
+        let Some(repo) = remote.url.as_deref().and_then(forge::Repo::parse) else {
+            return Ok(Err(t!(
+                "{remote} is not hosted on a known forge",
+                remote = remote.name
+            )));
+        };
+        let Some(forge) = self.forges.for_host(&repo.host) else {
+            return Ok(Err(t!(
+                "no forge is known for {host}, add it to [forge.hosts]",
+                host = repo.host
+            )));
+        };
+        Ok(Ok(Hosted {
+            forge,
+            repo,
+            remote: remote.name.clone(),
+            upstream,
+            head,
+        }))
+    }
+
+    /// Opens a pull request for the upstream of the selected branch: through the
+    /// API when there is a token, otherwise by opening the forge's compare page.
+    fn create_pull_request(&mut self) -> EResult<()> {
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        let name = branch.name.clone();
+        if branch.typ != git2::BranchType::Local {
+            self.notifications
+                .info(t!("select a local branch to open a pull request"));
+            return Ok(());
+        }
+        let hosted = match self.hosted(&name)? {
+            Ok(hosted) => hosted,
+            Err(why) => {
+                self.notifications.info(why);
+                return Ok(());
+            }
+        };
+        let Some(pr) = self.draft_pull_request(&name, &hosted)? else {
+            return Ok(());
+        };
+        let base = pr.base.clone();
+        let forge = hosted.forge;
+        let token = match self.forge_api() {
+            true => forge::token::get(&*forge, &hosted.repo.host)?,
+            false => None,
+        };
+        let Some(token) = token else {
+            let url = forge.compare_url(&hosted.repo, &pr);
+            return self.open_url(&url);
+        };
+        let title = t!(
+            "New {kind} from {head} into {base} (ctrl-s: create)",
+            kind = forge.pull_request_name(),
+            head = hosted.head,
+            base = base
+        );
+        let text = format!("{}\n\n{}", pr.title, pr.body);
+        let editor = modal::Editor::new(title).with_text(&text);
+        let new = NewPullRequest {
+            forge,
+            repo: hosted.repo,
+            token: token.secret,
+            head: hosted.head,
+            base,
+        };
+        self.edit(editor, Purpose::PullRequest(new));
+        Ok(())
+    }
+
+    /// Drafts a pull request of the local branch `name` into the default
+    /// branch, unless there is no default branch or `name` is it, which the
+    /// user is told.
+    fn draft_pull_request(
+        &mut self,
+        name: &str,
+        hosted: &Hosted,
+    ) -> EResult<Option<forge::PullRequest>> {
+        let Some(default) = self.repo.default_branch()? else {
+            self.notifications
+                .info(t!("no default branch to open a pull request against"));
+            return Ok(None);
+        };
+        let base = default
+            .strip_prefix(&format!("{}/", hosted.remote))
+            .unwrap_or(&default)
+            .to_string();
+        if base == hosted.head {
+            self.notifications
+                .info(t!("{name} is the default branch", name = name));
+            return Ok(None);
+        }
+        let summaries = self.repo.summaries(&hosted.upstream, &default)?;
+        Ok(Some(forge::PullRequest::draft(
+            &hosted.head,
+            &base,
+            &summaries,
+        )))
+    }
+
+    /// Opens the pull request of the selected branch in the browser, or the
+    /// page that starts one if it has none, as far as is known.
+    fn open_pull_request(&mut self) -> EResult<()> {
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        let name = branch.name.clone();
+        if branch.typ != git2::BranchType::Local {
+            self.notifications
+                .info(t!("select a local branch to open its pull request"));
+            return Ok(());
+        }
+        if let Some(reviews::Lookup::Found {
+            review: Some(review),
+            ..
+        }) = self.reviews.get(&name)
+        {
+            let url = review.url.clone();
+            return self.open_url(&url);
+        }
+        let hosted = match self.hosted(&name)? {
+            Ok(hosted) => hosted,
+            Err(why) => {
+                self.notifications.info(why);
+                return Ok(());
+            }
+        };
+        let Some(pr) = self.draft_pull_request(&name, &hosted)? else {
+            return Ok(());
+        };
+        let url = hosted.forge.compare_url(&hosted.repo, &pr);
+        self.open_url(&url)
+    }
+
+    fn open_url(&mut self, url: &str) -> EResult<()> {
+        open::that_detached(url).wrap_err_with(|| format!("open {url}"))?;
+        self.notifications.info(t!("opened {url}", url = url));
+        Ok(())
+    }
+
+    /// Creates the pull request in a job, from text whose first line is the title
+    /// and the rest the description.
+    fn submit_pull_request(&mut self, new: NewPullRequest, text: &str) {
+        let (title, body) = text.trim().split_once('\n').unwrap_or((text.trim(), ""));
+        if title.trim().is_empty() {
+            self.notifications
+                .info(t!("pull request aborted, the title is empty"));
+            return;
+        }
+        let pr = forge::PullRequest {
+            title: title.trim().to_string(),
+            body: body.trim().to_string(),
+            head: new.head,
+            base: new.base,
+        };
+        let name = t!("create {kind}", kind = new.forge.pull_request_name());
+        self.jobs.submit(name, false, move |_| {
+            let url = new.forge.create_pull_request(&new.repo, &new.token, &pr)?;
+            Ok(t!("created {url}", url = url))
+        });
+    }
+
+    /// Writes the branches listed, in their order, to a CSV or Markdown file
+    /// as the extension of `path` tells, or copies them as Markdown if it is
+    /// empty.
+    fn export_branches(&mut self, path: &str) -> EResult<()> {
+        let rows: Vec<cli::BranchRow> = self
+            .branch_list
+            .rows()
+            .into_iter()
+            .filter_map(|row| match row {
+                branch::Row::Branch(idx) => Some(&self.branch_list.items[idx]),
+                branch::Row::Header(..) => None,
+            })
+            .map(cli::BranchRow::from)
+            .collect();
+        let table = cli::BranchRow::table(&rows);
+        if path.is_empty() {
+            clipboard::copy(&table.markdown()).wrap_err("copy to clipboard")?;
+            let msg = t!(
+                "copied {count} branches as a Markdown table",
+                count = rows.len()
+            );
+            self.notifications.success(msg);
+            return Ok(());
+        }
+        let text = match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("csv") => table.csv(),
+            _ => table.markdown(),
+        };
+        std::fs::write(path, text).wrap_err_with(|| format!("write {path}"))?;
+        let msg = t!(
+            "exported {count} branches to {path}",
+            count = rows.len(),
+            path = path
+        );
+        self.notifications.success(msg);
+        Ok(())
+    }
+
+    fn toggle_stale(&mut self) -> EResult<()> {
+        let stale_only = !self.branch_list.stale_only;
+        self.branch_list.set_stale_only(stale_only);
+        if stale_only && self.branch_list.items.is_empty() {
+            self.notifications.info(t!("no stale branches"));
+        }
+        self.load_selected()
+    }
+
+    /// Reads the branches left out past `branch.load_limit`, for the rest of
+    /// the session.
+    fn load_all_branches(&mut self) -> EResult<()> {
+        if self.branch_list.unloaded == 0 {
+            self.notifications
+                .info(t!("every branch is loaded already"));
+            return Ok(());
+        }
+        self.load_all = true;
+        self.load_branches()
+    }
+
+    /// Asks whether to delete every stale branch listed, and only those,
+    /// whatever is marked.
+    fn delete_stale(&mut self) {
+        let stale: Vec<_> = self
+            .branch_list
+            .items
+            .iter()
+            .filter(|b| self.branch_list.staleness(b).is_some())
+            .map(|b| (b.name.clone(), b.typ))
+            .collect();
+        if stale.is_empty() {
+            self.notifications.info(t!("no stale branches"));
+            return;
+        }
+        self.confirm_delete_of(&stale);
+    }
+
+    /// Asks whether to delete the marked local branches, or the selected one.
+    fn confirm_delete(&mut self) {
+        let targets: Vec<_> = self
+            .branch_list
+            .targets()
+            .into_iter()
+            .map(|b| (b.name.clone(), b.typ))
+            .collect();
+        self.confirm_delete_of(&targets);
+    }
+
+    /// Asks whether to delete the local branches among `targets`, by name and
+    /// type.
+    fn confirm_delete_of(&mut self, targets: &[(String, git2::BranchType)]) {
+        let branches: Vec<(String, git2::Oid)> = self
+            .branch_list
+            .items
+            .iter()
+            .filter(|b| {
+                targets
+                    .iter()
+                    .any(|(name, typ)| *name == b.name && *typ == b.typ)
+            })
+            .filter(|b| b.typ == git2::BranchType::Local && self.pending.of(&b.name).is_none())
+            .filter(|b| self.head.as_ref() != Some(&b.name))
+            .filter(|b| !self.config.branch.is_protected(&b.name))
+            .filter_map(|b| Some((b.name.clone(), b.tip()?)))
+            .collect();
+        if branches.is_empty() {
+            self.notifications.info(t!(
+                "nothing to delete, the checked out, protected and remote branches are kept"
+            ));
+            return;
+        }
+        let names: Vec<_> = branches
+            .iter()
+            .map(|(n, _)| match self.repo.is_merged(n).unwrap_or(false) {
+                true => redact::branch(n).to_string(),
+                false => t!("{branch} (not merged)", branch = redact::branch(n)).to_string(),
+            })
+            .collect();
+        let message = names.join("\n");
+        let title = t!("Delete {count} branches?", count = branches.len());
+        let confirm = modal::Confirm::new(title, message);
+        self.modal = Some((Modal::Confirm(confirm), Purpose::DeleteBranches(branches)));
+    }
+
+    /// Asks whether to delete the selected local branch, and to do so anyway if
+    /// it is not merged, as `git branch -D` would.
+    fn confirm_delete_branch(&mut self) -> EResult<()> {
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        if branch.typ == git2::BranchType::Remote {
+            self.confirm_delete_remote();
+            return Ok(());
+        }
+        let name = branch.name.clone();
+        let shown = redact::branch(&name);
+        let refusal = if self.head.as_ref() == Some(&name) {
+            Some(
+                t!(
+                    "{branch} is checked out, check out another branch first",
+                    branch = shown
+                )
+                .to_string(),
+            )
+        } else if let Some(path) = self.worktree_of(branch) {
+            Some(
+                t!(
+                    "{branch} is checked out in {path}",
+                    branch = shown,
+                    path = path.display()
+                )
+                .to_string(),
+            )
+        } else if self.config.branch.is_protected(&name) {
+            Some(t!("{branch} is protected", branch = shown).to_string())
+        } else {
+            None
+        };
+        if let Some(refusal) = refusal {
+            self.notifications.info(refusal);
+            return Ok(());
+        }
+        let Some(tip) = branch.tip() else {
+            return Ok(());
+        };
+        if self.pending.of(&name).is_some() {
+            return Ok(());
+        }
+        let merged = self.repo.is_merged(&name)?;
+        let confirm = match merged {
+            true => modal::Confirm::new(
+                t!("Delete {branch}?", branch = shown),
+                t!("Its commits are merged, nothing is lost."),
+            ),
+            false => modal::Confirm::new(
+                t!("Force delete {branch}?", branch = shown),
+                t!(
+                    "{branch} is not fully merged. Its commits will only be kept in the reflog, for a while.",
+                    branch = shown
+                ),
+            )
+            .strict(),
+        };
+        self.modal = Some((
+            Modal::Confirm(confirm),
+            Purpose::DeleteBranch(name, tip, !merged),
+        ));
+        Ok(())
+    }
+
+    /// Asks whether to delete the branch on the server that the selected
+    /// remote-tracking branch mirrors.
+    fn confirm_delete_remote(&mut self) {
+        let Some(branch) = self.branch_list.current() else {
+            return;
+        };
+        let name = branch.name.clone();
+        let shown = redact::branch(&name);
+        // protection is by the name of the branch, whichever remote it is on
+        let short = name.split_once('/').map_or(name.as_str(), |(_, n)| n);
+        let refusal = if branch.symbolic.is_some() {
+            Some(t!("{branch} only points at another branch", branch = shown).to_string())
+        } else if self.config.branch.is_protected(short) {
+            Some(t!("{branch} is protected", branch = shown).to_string())
+        } else {
+            None
+        };
+        if let Some(refusal) = refusal {
+            self.notifications.info(refusal);
+            return;
+        }
+        let Some(tip) = branch.tip() else {
+            return;
+        };
+        let confirm = modal::Confirm::new(
+            t!("Delete {branch} on the server?", branch = shown),
+            t!(
+                "The branch is deleted for everyone who fetches from the remote, unless it moved since the last fetch."
+            ),
+        )
+        .strict();
+        self.modal = Some((
+            Modal::Confirm(confirm),
+            Purpose::DeleteRemoteBranch(name, tip),
+        ));
+    }
+
+    /// Deletes the branch on the server that the remote-tracking branch `name`
+    /// mirrors, in the background.
+    fn delete_remote_branch(&mut self, name: String, tip: git2::Oid) {
+        let repo = self.repo.clone();
+        let job = t!("delete {branch}", branch = &name);
+        let id = self.jobs.submit(job, true, move |cancel| {
+            Ok(repo.delete_remote_branch(&name, tip, || cancel.is_cancelled())?)
+        });
+        self.remote_jobs.insert(id);
+    }
+
+    /// Drops the review state and note of those of `names` that are gone, once
+    /// a job deleting them finished, which may have failed partway.
+    fn forget_deleted(&mut self, names: &[String]) {
+        let gone: Vec<&str> = names
+            .iter()
+            .filter(|name| {
+                let refname = format!("refs/heads/{name}");
+                matches!(self.repo.ref_target(&refname), Ok(None))
+            })
+            .map(String::as_str)
+            .collect();
+        if let Err(err) = self.checklist.forget(&gone) {
+            self.notifications.report(&err);
+        }
+    }
+
+    /// Deletes `branches` in the background, showing them as deleted meanwhile.
+    /// Unless `force`, those not merged are kept.
+    fn delete_branches(&mut self, branches: Vec<(String, git2::Oid)>, force: bool) {
+        let repo = self.repo.clone();
+        let names: Vec<String> = branches.iter().map(|(n, _)| n.clone()).collect();
+        let name = match names.as_slice() {
+            [one] => t!("delete {branch}", branch = one),
+            _ => t!("delete {count} branches", count = names.len()),
+        };
+        let hooks = self.hooks.clone();
+        let id = self.jobs.submit(name, true, move |cancel| {
+            // each pre-delete hook before any branch goes, as any of them may
+            // veto the lot
+            let mut out = vec![];
+            for (name, tip) in &branches {
+                let ctx = hooks::Context {
+                    branch: Some(name.clone()),
+                    commit: Some(tip.to_string()),
+                    target: None,
+                };
+                out.extend(hooks.run(hooks::Event::PreDelete, &ctx)?);
+            }
+            for (name, tip) in &branches {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                repo.delete_branch(name, *tip, force)?;
+            }
+            Ok(out.join("\n"))
+        });
+        self.branch_list.marked.clear();
+        self.pending.add(id, names, pending::Op::Delete);
+    }
+
+    /// Pushes the selected local branch, asking which remote to push it to if
+    /// it has no upstream and there are several.
+    fn push(&mut self) {
+        let Some(branch) = self.branch_list.current() else {
+            return;
+        };
+        if branch.typ != git2::BranchType::Local {
+            self.notifications
+                .info(t!("only local branches can be pushed"));
+            return;
+        }
+        if !self.has_remotes {
+            return self.prompt_remote_name(Retry::Push, "origin", None);
+        }
+        let (name, upstream) = (branch.name.clone(), branch.upstream.is_some());
+        let remotes: Vec<String> = match upstream {
+            true => vec![],
+            false => self
+                .repo
+                .remotes()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| r.name)
+                .collect(),
+        };
+        match remotes.as_slice() {
+            [_, _, ..] => {
+                let title = t!("Push {branch} to", branch = redact::branch(&name));
+                let select = modal::Select::new(title, remotes.clone());
+                self.modal = Some((Modal::Select(select), Purpose::PushTo(name, remotes)));
+            }
+            [one] => {
+                let one = one.clone();
+                self.push_branch(name, Some(one));
+            }
+            [] => self.push_branch(name, None),
+        }
+    }
+
+    /// The names of the remotes, none if they cannot be read.
+    fn remote_names(&self) -> Vec<String> {
+        let remotes = self.repo.remotes().unwrap_or_default();
+        remotes.into_iter().map(|r| r.name).collect()
+    }
+
+    /// Pushes the marked local branches in the background, each to its
+    /// upstream, or else to `origin` or the only remote, reporting by remote
+    /// what went through.
+    fn push_marked(&mut self) {
+        let names: Vec<String> = self
+            .branch_list
+            .marked
+            .iter()
+            .filter(|(_, typ)| *typ == git2::BranchType::Local)
+            .map(|(name, _)| name.clone())
+            .collect();
+        if names.is_empty() {
+            self.notifications
+                .info(t!("mark local branches to push first"));
+            return;
+        }
+        if !self.has_remotes {
+            return self.prompt_remote_name(Retry::Push, "origin", None);
+        }
+        let remotes = self.remote_names();
+        let fallback = match remotes.as_slice() {
+            [one] => one.clone(),
+            _ => "origin".to_string(),
+        };
+        let repo = self.repo.clone();
+        let title = match names.as_slice() {
+            [one] => t!("push {branch}", branch = one),
+            _ => t!("push {count} branches", count = names.len()),
+        };
+        let groups = bulk::Groups::default();
+        let filled = groups.clone();
+        let branches = names.clone();
+        let id = self.jobs.submit(title.clone(), true, move |cancel| {
+            for name in &branches {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                let remote = repo
+                    .push_remote(name, &fallback)
+                    .unwrap_or_else(|_| fallback.clone());
+                let res = repo.push(
+                    name,
+                    Some(&fallback),
+                    || cancel.is_cancelled(),
+                    |line| cancel.progress(line),
+                );
+                let error = res.err().map(|err| format!("{err:#}"));
+                let label = t!("push {branch}", branch = name);
+                bulk::add(&filled, &remote, bulk::Item { label, error });
+            }
+            Ok(String::new())
+        });
+        self.bulk.insert(id, (title, groups));
+        self.remote_jobs.insert(id);
+        self.branch_list.marked.clear();
+        self.pending.add(id, names, pending::Op::Push);
+    }
+
+    /// Deletes the remote-tracking branches whose branch on the server is gone,
+    /// on every remote, in the background, reporting by remote what went.
+    fn prune_remotes(&mut self) {
+        if !self.has_remotes {
+            return;
+        }
+        let remotes = self.remote_names();
+        let repo = self.repo.clone();
+        let title = t!("prune all remotes").to_string();
+        let groups = bulk::Groups::default();
+        let filled = groups.clone();
+        let id = self.jobs.submit(title.clone(), true, move |cancel| {
+            let mut count = 0;
+            for remote in &remotes {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                let items = match repo.prune_remote(remote, || cancel.is_cancelled()) {
+                    Ok(pruned) if pruned.is_empty() => vec![bulk::Item {
+                        label: t!("nothing to prune").to_string(),
+                        error: None,
+                    }],
+                    Ok(pruned) => {
+                        count += pruned.len();
+                        pruned
+                            .into_iter()
+                            .map(|name| bulk::Item {
+                                label: t!("pruned {branch}", branch = name),
+                                error: None,
+                            })
+                            .collect()
+                    }
+                    Err(err) if remotes.len() == 1 => return Err(err.into()),
+                    Err(err) => vec![bulk::Item {
+                        label: t!("prune").to_string(),
+                        error: Some(format!("{err:#}")),
+                    }],
+                };
+                for item in items {
+                    bulk::add(&filled, remote, item);
+                }
+            }
+            // the report tells more, with several remotes
+            Ok(match remotes.len() {
+                1 => t!("{count} remote-tracking branches pruned", count = count),
+                _ => String::new(),
+            })
+        });
+        self.bulk.insert(id, (title, groups));
+        self.remote_jobs.insert(id);
+    }
+
+    /// Pushes the local branch `branch` in the background, to its upstream or
+    /// else to `remote`, showing it as pushed meanwhile.
+    fn push_branch(&mut self, branch: String, remote: Option<String>) {
+        let repo = self.repo.clone();
+        let name = t!("push {branch}", branch = branch);
+        let job = branch.clone();
+        let id = self.jobs.submit(name, true, move |cancel| {
+            Ok(repo.push(
+                &job,
+                remote.as_deref(),
+                || cancel.is_cancelled(),
+                |line| cancel.progress(line),
+            )?)
+        });
+        self.pending.add(id, vec![branch], pending::Op::Push);
+        self.remote_jobs.insert(id);
+    }
+
+    /// Collects a check of the network, telling when it went down or came
+    /// back, in which case the selected branch's pull request is looked up.
+    /// Returns whether one finished.
+    fn poll_network(&mut self) -> bool {
+        let checking = self.network.is_checking();
+        match self.network.poll() {
+            Some(true) => {
+                self.notifications.success(t!("back online"));
+                self.request_review();
+            }
+            Some(false) => {
+                let reason = self.network.offline().unwrap_or_default();
+                self.notifications.error(t!(
+                    "offline, showing what was cached: {reason}",
+                    reason = reason
+                ));
+            }
+            None => {}
+        }
+        checking && !self.network.is_checking()
+    }
+
+    /// Takes a remote that answered as the network being up, whatever the
+    /// check made of the hosts it could see.
+    fn reached(&mut self) {
+        if self.network.reached() {
+            self.notifications.success(t!("back online"));
+            self.request_review();
+        }
+    }
+
+    /// Offers to add a remote where there is none to fetch from or push to,
+    /// showing why the last name given was refused, if it was.
+    fn prompt_remote_name(&mut self, retry: Retry, name: &str, error: Option<String>) {
+        let title = t!("No remotes yet. Name of the remote to add");
+        let mut input = modal::Input::new(title).with_text(name.to_string());
+        if let Some(error) = error {
+            input.set_error(error);
+        }
+        self.modal = Some((Modal::Input(input), Purpose::RemoteName(retry)));
+    }
+
+    fn prompt_remote_url(&mut self, name: String, retry: Retry, url: &str, error: Option<String>) {
+        let title = t!("URL of {remote}", remote = name);
+        let mut input = modal::Input::new(title).with_text(url.to_string());
+        if let Some(error) = error {
+            input.set_error(error);
+        }
+        self.modal = Some((Modal::Input(input), Purpose::RemoteUrl(name, retry)));
+    }
+
+    /// Adds the remote, then does what needed it.
+    fn add_remote(&mut self, name: String, url: &str, retry: Retry) {
+        if let Err(err) = self.repo.add_remote(&name, url) {
+            return match err {
+                git::Error::InvalidRemoteName(_) | git::Error::RemoteExists(_) => {
+                    self.prompt_remote_name(retry, &name, Some(err.to_string()))
+                }
+                err => self.prompt_remote_url(name, retry, url, Some(format!("{err:#}"))),
+            };
+        }
+        self.has_remotes = true;
+        self.notifications.success(t!(
+            "added remote {remote} at {url}",
+            remote = name,
+            url = redact::text(url)
+        ));
+        match retry {
+            Retry::Fetch => self.fetch_all(),
+            Retry::Push => self.push(),
+        }
+    }
+
+    /// Whether the forges' APIs are called: built in, and not turned off in the
+    /// config or by `--safe`.
+    fn forge_api(&self) -> bool {
+        cfg!(feature = "forge") && self.config.forge.api.unwrap_or(true)
+    }
+
+    /// Looks up the pull requests of the selected branch and of the others in
+    /// view in the background, each once and [`MAX_LOOKUPS`] at a time. While
+    /// offline the cache is all there is, and they are looked up once back
+    /// online.
+    fn request_review(&mut self) {
+        if self.network.offline().is_some() || !self.forge_api() {
+            return;
+        }
+        let in_view = self
+            .hits
+            .list_index
+            .iter()
+            .skip(self.hits.list_offset)
+            .take(self.hits.list_rows.height.into())
+            .flatten()
+            .filter_map(|idx| self.branch_list.items.get(*idx));
+        let mut wanted: Vec<String> = vec![];
+        for branch in self.branch_list.current().into_iter().chain(in_view) {
+            let local = branch.typ == git2::BranchType::Local;
+            if local && self.reviews.wants(&branch.name) && !wanted.contains(&branch.name) {
+                wanted.push(branch.name.clone());
+            }
+        }
+        for name in wanted {
+            if self.reviews.in_flight() >= MAX_LOOKUPS {
+                break;
+            }
+            match self.hosted(&name).ok().and_then(Result::ok) {
+                Some(hosted) => self
+                    .reviews
+                    .request(name, hosted.forge, hosted.repo, hosted.head),
+                None => self.reviews.skip(name),
+            }
+        }
+    }
+
+    /// The forge the commits of `branch` were pushed to, if it is hosted on
+    /// one: that of its upstream, or of its remote. Only what was read with
+    /// the branches is used, as this runs on every turn.
+    fn commit_forge(&self, branch: &git::Branch) -> Option<(Arc<dyn forge::Forge>, forge::Repo)> {
+        let upstream = match branch.typ {
+            git2::BranchType::Local => branch.upstream.as_ref()?,
+            git2::BranchType::Remote => &branch.name,
+        };
+        // the longest remote name that fits, as they may contain slashes
+        let remote = self
+            .known_remotes
+            .iter()
+            .filter(|r| upstream.starts_with(&format!("{}/", r.name)))
+            .max_by_key(|r| r.name.len())?;
+        let repo = remote.url.as_deref().and_then(forge::Repo::parse)?;
+        let forge = self.forges.for_host(&repo.host)?;
+        Some((forge, repo))
+    }
+
+    /// Looks up the checks of the pushed commits in view in the log in the
+    /// background, each once and [`MAX_LOOKUPS`] at a time. Commits that are
+    /// only local have none to look up.
+    fn request_checks(&mut self) {
+        if self.network.offline().is_some() || !self.forge_api() {
+            return;
+        }
+        let Some(branch) = self.branch_list.current() else {
+            return;
+        };
+        let ahead = match branch.tracking {
+            Some(git::Tracking::Ahead(ahead) | git::Tracking::Diverged { ahead, .. }) => ahead,
+            _ => 0,
+        };
+        let unpushed: HashSet<git2::Oid> =
+            branch.commits().iter().take(ahead).map(|c| c.id).collect();
+        let wanted: Vec<git2::Oid> = self
+            .log_in_view
+            .iter()
+            .filter(|id| !unpushed.contains(id) && self.commit_checks.wants(**id))
+            .copied()
+            .collect();
+        if wanted.is_empty() || self.commit_checks.in_flight() >= MAX_LOOKUPS {
+            return;
+        }
+        let Some((forge, repo)) = self.commit_forge(branch) else {
+            for id in wanted {
+                self.commit_checks.skip(id);
+            }
+            return;
+        };
+        for id in wanted {
+            if self.commit_checks.in_flight() >= MAX_LOOKUPS {
+                break;
+            }
+            self.commit_checks.request(id, forge.clone(), repo.clone());
+        }
+    }
+
+    /// Squashes the selected branch into one commit, after editing the message.
+    fn squash_branch(&mut self) -> EResult<()> {
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        if branch.typ != git2::BranchType::Local {
+            self.notifications
+                .error(t!("only local branches can be squashed"));
+            return Ok(());
+        }
+        let name = branch.name.clone();
+        if self.config.branch.is_protected(&name) {
+            let branch = redact::branch(&name);
+            self.notifications
+                .error(t!("{branch} is protected", branch = branch));
+            return Ok(());
+        }
+        if !self.check_identity(Then::Action(Action::SquashBranch))? {
+            return Ok(());
+        }
+        let plan = self.repo.plan_squash(&name)?;
+        if plan.count < 2 {
+            let msg = t!(
+                "nothing to squash, {name} has {count} commits since {onto}",
+                name = name,
+                count = plan.count,
+                onto = plan.onto
+            );
+            self.notifications.info(msg);
+            return Ok(());
+        }
+        let title = t!(
+            "Squash {count} commits of {name} (ctrl-s: save)",
+            count = plan.count,
+            name = name
+        );
+        let editor = modal::Editor::new(title).with_text(&plan.message);
+        self.edit(editor, Purpose::Squash(name, plan));
+        Ok(())
+    }
+
+    /// Reloads the commits of the selected branch, e.g. after editing a note.
+    fn reload_selected(&mut self) -> EResult<()> {
+        let idx = self.selected_index();
+        if let Some(branch) = self.branch_list.items.get_mut(idx) {
+            branch.load().wrap_err_with(|| format!("load {branch}"))?;
+        }
+        Ok(())
+    }
+
+    fn edit_refspecs(&mut self) {
+        let Some(remote) = self.remotes.shown().and_then(Remotes::selected) else {
+            return;
+        };
+        let title = t!("Fetch refspecs of {remote}", remote = remote.name);
+        let editor = modal::Editor::new(title).with_text(&remote.fetch.join("\n"));
+        self.edit(editor, Purpose::Refspecs(remote.name.clone()));
+    }
+
+    fn toggle_prune(&mut self) -> EResult<()> {
+        let Some(remote) = self.remotes.shown().and_then(Remotes::selected) else {
+            return Ok(());
+        };
+        let prune = !remote.prune.unwrap_or(false);
+        let name = remote.name.clone();
+        self.repo.set_prune(&name, prune)?;
+        self.reload_remotes()
+    }
+
+    /// Flips the selected setting, moves it on to the next choice, or asks
+    /// for a number.
+    fn change_setting(&mut self) -> EResult<()> {
+        let Some(setting) = self.settings.shown().and_then(Settings::selected) else {
+            return Ok(());
+        };
+        let value = settings::get(&self.config, setting.key);
+        match (setting.kind, value) {
+            (settings::Kind::Bool, Some(settings::Value::Bool(on))) => {
+                self.set_setting(setting.key, Some(settings::Value::Bool(!on)))
+            }
+            (settings::Kind::Sort, Some(settings::Value::Sort(sort))) => {
+                let at = branch::Sort::ALL.iter().position(|s| *s == sort);
+                let next = branch::Sort::ALL[at.map_or(0, |at| at + 1) % branch::Sort::ALL.len()];
+                self.set_setting(setting.key, Some(settings::Value::Sort(next)))
+            }
+            (settings::Kind::Number, value) => {
+                let text = match value {
+                    Some(settings::Value::Number(n)) => n.to_string(),
+                    _ => String::new(),
+                };
+                self.prompt_setting(setting.key, &text, None);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Asks how many commits to deepen a shallow clone by.
+    fn prompt_deepen(&mut self, text: &str, error: Option<String>) {
+        if !self.completeness.shallow {
+            self.notifications.info(t!("the clone is not shallow"));
+            return;
+        }
+        let title = t!("Deepen the history by how many commits (empty: all)");
+        let mut input = modal::Input::new(title).with_text(text);
+        if let Some(error) = error {
+            input.set_error(error);
+        }
+        self.modal = Some((Modal::Input(input), Purpose::Deepen));
+    }
+
+    /// Fetches `by` more commits below where the shallow clone stops, or the
+    /// rest of the history for `None`.
+    fn deepen(&mut self, by: Option<usize>) {
+        let repo = self.repo.clone();
+        let name = match by {
+            Some(by) => t!("deepen the history by {count} commits", count = by),
+            None => t!("fetch all of the history").to_string(),
+        };
+        let hooks = self.hooks.clone();
+        let id = self.jobs.submit(name, true, move |cancel| {
+            let out = repo.deepen(by, || cancel.is_cancelled())?;
+            let hook = hooks.run(hooks::Event::PostFetch, &hooks::Context::default())?;
+            Ok([Some(out), hook]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join("\n"))
+        });
+        self.remote_jobs.insert(id);
+    }
+
+    /// Asks whether to rebase the branches stacked on the selected one onto
+    /// their parents, which they need once a parent has moved.
+    fn confirm_restack(&mut self) {
+        let Some(branch) = self.branch_list.current() else {
+            return;
+        };
+        if !self.branch_list.stacked {
+            self.notifications
+                .info(t!("turn on the stack view first  space v t"));
+            return;
+        }
+        let links = self.branch_list.stacked_on(&branch.name);
+        if links.is_empty() {
+            let msg = t!(
+                "no branches are stacked on {branch}",
+                branch = redact::branch(&branch.name)
+            );
+            self.notifications.info(msg);
+            return;
+        }
+        match self.check_identity(Then::Action(Action::Restack)) {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(err) => return self.report(&err),
+        }
+        let names: Vec<_> = links
+            .iter()
+            .map(|(_, child, _)| redact::branch(child).into_owned())
+            .collect();
+        let message = t!(
+            "Rebase {branches} onto the branches they are stacked on? Their commits get new ids.",
+            branches = names.join(", ")
+        );
+        let confirm = modal::Confirm::new(t!("Restack"), message);
+        self.modal = Some((Modal::Confirm(confirm), Purpose::Restack(links)));
+    }
+
+    /// Offers the actions that apply to the selected branch, each with its
+    /// keys, for those who would rather pick one than remember it.
+    fn branch_menu(&mut self) {
+        let Some(branch) = self.branch_list.current() else {
+            return;
+        };
+        let local = branch.typ == git2::BranchType::Local;
+        let head = local && self.head.as_ref() == Some(&branch.name);
+        let symbolic = branch.name.ends_with("/HEAD");
+        let mut actions = vec![];
+        if !head && !symbolic {
+            actions.push(Action::ToggleBranch);
+        }
+        actions.push(Action::DiffWorktree);
+        if branch.upstream.is_some() {
+            actions.push(Action::ShowDivergence);
+        }
+        if !head && !symbolic && self.head.is_some() {
+            actions.extend([Action::MergeBranch, Action::RebaseOnto]);
+        }
+        if local {
+            actions.extend([Action::RenameBranch, Action::SquashBranch]);
+            if branch.upstream.is_some() || self.has_remotes {
+                actions.extend([
+                    Action::Push,
+                    Action::CreatePullRequest,
+                    Action::OpenPullRequest,
+                ]);
+            }
+            if self.worktrees.contains_key(&branch.name) {
+                actions.push(Action::JumpToWorktree);
+            }
+            actions.extend([Action::SetBranchState, Action::EditBranchNote]);
+            if self.branch_list.stacked && !self.branch_list.stacked_on(&branch.name).is_empty() {
+                actions.push(Action::Restack);
+            }
+        }
+        if !symbolic {
+            actions.push(Action::ToggleFollow);
+        }
+        actions.extend([
+            Action::EditNote,
+            Action::FileHistory,
+            Action::Pickaxe,
+            Action::ToggleMark,
+        ]);
+        if !head && !symbolic {
+            actions.push(Action::DeleteBranch);
+        }
+        let width = actions
+            .iter()
+            .map(|a| self.keymap.describe(*a).chars().count())
+            .max()
+            .unwrap_or_default();
+        let items = actions
+            .iter()
+            .map(|a| {
+                let description = self.keymap.describe(*a);
+                match self.keymap.keys(*a) {
+                    Some(keys) => format!("{description:<width$}  {keys}"),
+                    None => description.to_string(),
+                }
+            })
+            .collect();
+        let select = modal::Select::new(redact::branch(&branch.name), items);
+        self.modal = Some((Modal::Select(select), Purpose::BranchMenu(actions)));
+    }
+
+    /// Asks whether to merge the selected branch into the checked out one, or
+    /// to rebase the checked out one onto it, once tried in memory. If that
+    /// conflicts, the conflicting files are shown instead.
+    fn confirm_integrate(&mut self, rebase: bool) -> EResult<()> {
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        let (name, typ) = (branch.name.clone(), branch.typ);
+        let head = self.repo.head_branch().ok_or(git::Error::Detached)?;
+        let (branch, head_shown) = (redact::branch(&name), redact::branch(&head));
+        if rebase && self.config.branch.is_protected(&head) {
+            self.notifications
+                .error(t!("{branch} is protected", branch = head_shown));
+            return Ok(());
+        }
+        let then = match rebase {
+            true => Action::RebaseOnto,
+            false => Action::MergeBranch,
+        };
+        if !self.check_identity(Then::Action(then))? {
+            return Ok(());
+        }
+        let plan = match rebase {
+            true => self.repo.plan_rebase(&name, typ)?,
+            false => self.repo.plan_merge(&name, typ)?,
+        };
+        let message = match plan {
+            git::Plan::UpToDate => {
+                let msg = match rebase {
+                    true => t!(
+                        "{head} is based on {branch} already",
+                        head = head_shown,
+                        branch = branch
+                    ),
+                    false => t!(
+                        "{head} has every commit of {branch} already",
+                        head = head_shown,
+                        branch = branch
+                    ),
+                };
+                self.notifications.info(msg);
+                return Ok(());
+            }
+            git::Plan::Conflicts(paths) => {
+                let title = match rebase {
+                    true => t!(
+                        "Rebasing {head} onto {branch}",
+                        head = head_shown,
+                        branch = branch
+                    ),
+                    false => t!(
+                        "Merging {branch} into {head}",
+                        head = head_shown,
+                        branch = branch
+                    ),
+                };
+                let mut lines = vec![
+                    t!("These files conflict, so nothing was changed:").to_string(),
+                    String::new(),
+                ];
+                lines.extend(paths.into_iter().map(|path| format!("  {path}")));
+                self.output = Some(Output::text(title, t!("conflicts"), lines));
+                return Ok(());
+            }
+            git::Plan::FastForward(count) => t!(
+                "Fast-forward {head} by {count} commits to {branch}?",
+                head = head_shown,
+                count = count,
+                branch = branch
+            ),
+            git::Plan::Clean(count) if rebase => t!(
+                "Replay {count} commits of {head} onto {branch}? They get new ids.",
+                count = count,
+                head = head_shown,
+                branch = branch
+            ),
+            git::Plan::Clean(count) => t!(
+                "Merge {count} commits of {branch} into {head} with a merge commit?",
+                count = count,
+                branch = branch,
+                head = head_shown
+            ),
+        };
+        let (title, purpose) = match rebase {
+            true => (t!("Rebase"), Purpose::Rebase(name, typ)),
+            false => (t!("Merge"), Purpose::Merge(name, typ)),
+        };
+        let confirm = modal::Confirm::new(title, message);
+        self.modal = Some((Modal::Confirm(confirm), purpose));
+        Ok(())
+    }
+
+    /// Asks which branch `HEAD` of a remote should point at, which is where
+    /// the default branch is read from. The remote is that of the selected
+    /// branch, or `origin`.
+    fn pick_remote_head(&mut self) -> EResult<()> {
+        let remotes = self.repo.remotes()?;
+        let selected = self
+            .branch_list
+            .current()
+            .filter(|b| b.typ == git2::BranchType::Remote);
+        let remote = match selected {
+            Some(branch) => remotes
+                .iter()
+                .filter(|r| branch.name.starts_with(&format!("{}/", r.name)))
+                .max_by_key(|r| r.name.len()),
+            None => remotes.iter().find(|r| r.name == "origin"),
+        };
+        let Some(remote) = remote.map(|r| r.name.clone()) else {
+            self.notifications
+                .info(t!("select a branch of the remote to set its HEAD"));
+            return Ok(());
+        };
+        let prefix = format!("refs/remotes/{remote}/");
+        let refs = self.repo.refs()?;
+        let head = format!("{prefix}HEAD");
+        let current = refs
+            .iter()
+            .find(|r| r.name == head)
+            .and_then(|r| r.symbolic.as_deref()?.strip_prefix(&prefix));
+        let branches: Vec<String> = refs
+            .iter()
+            .filter(|r| r.name != head)
+            .filter_map(|r| r.name.strip_prefix(&prefix))
+            .map(ToOwned::to_owned)
+            .collect();
+        if branches.is_empty() {
+            self.notifications
+                .info(t!("{remote} has no branches", remote = remote));
+            return Ok(());
+        }
+        let selected = branches
+            .iter()
+            .position(|b| Some(b.as_str()) == current)
+            .unwrap_or_default();
+        let items = branches
+            .iter()
+            .map(|b| redact::branch(b).into_owned())
+            .collect();
+        let title = t!("Point {remote}/HEAD at", remote = remote);
+        let select = modal::Select::new(title, items).with_selected(selected);
+        self.modal = Some((
+            Modal::Select(select),
+            Purpose::RemoteHead { remote, branches },
+        ));
+        Ok(())
+    }
+
+    fn prompt_setting(&mut self, key: &'static str, text: &str, error: Option<String>) {
+        let title = t!("{key} (empty: default)", key = key);
+        let mut input = modal::Input::new(title).with_text(text);
+        if let Some(error) = error {
+            input.set_error(error);
+        }
+        self.modal = Some((Modal::Input(input), Purpose::Setting(key)));
+    }
+
+    /// Changes the setting at `key`, or puts it back to its default, for the
+    /// rest of the session and in the config file.
+    /// A setting that cannot be brought into effect, such as watch mode where
+    /// the watcher does not start, is left the way it was.
+    fn set_setting(&mut self, key: &'static str, value: Option<settings::Value>) -> EResult<()> {
+        let before = self.config.clone();
+        settings::set(&mut self.config, key, value);
+        if let Err(err) = self.apply_setting(key) {
+            self.config = before;
+            return Err(err);
+        }
+        let Some(path) = self.config.path.clone() else {
+            self.notifications
+                .error(t!("no config file to save {key} to", key = key));
+            return Ok(());
+        };
+        settings::write(&path, key, value)
+    }
+
+    /// Brings what is shown in line with a setting just changed. Those not
+    /// handled here are read from the config each time they are used.
+    fn apply_setting(&mut self, key: &str) -> EResult<()> {
+        let view = &self.config.view;
+        match key {
+            "view.sort" => {
+                self.branch_list.sort = view.sort.unwrap_or_default();
+                self.branch_list.sort();
+            }
+            "view.stacks" => {
+                self.branch_list.stacked = view.stacks;
+                self.read_stacks();
+                self.branch_list.sort();
+            }
+            "view.group" => {
+                self.branch_list.grouped = view.group;
+                self.branch_list.sort();
+            }
+            "view.author_colors" => self.author_colors = view.author_colors,
+            "view.heat" | "view.heat_weeks" => {
+                self.heat = view.heat.then(|| heat_weeks(&self.config));
+                if self.heat.is_some() && self.branch_list.all().any(|b| b.partial) {
+                    self.load_branches()?;
+                }
+            }
+            "view.max_fps" => {
+                let fps = view.max_fps.unwrap_or(60).max(1);
+                self.frame_interval = Duration::from_secs(1) / fps;
+            }
+            "view.watch" => {
+                self.watch = match view.watch {
+                    true => Some(self.repo.watch()?),
+                    false => None,
+                };
+            }
+            "max_commits" => {
+                let max = self.config.max_commits();
+                self.repo = self.repo.clone().with_max_commits(max);
+                self.load_branches()?;
+            }
+            "view.replace" => {
+                let replace = view.replace.unwrap_or(true);
+                self.repo = self.repo.clone().with_replace(replace);
+                self.load_branches()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Whether commits made now get the right author: `user.name` and
+    /// `user.email` set, the address matching `commit.email_pattern`. Checked
+    /// until it passes once in a session. Where it fails, asks for the value
+    /// in the repository's config, going on with `then` after.
+    fn check_identity(&mut self, then: Then) -> EResult<bool> {
+        if self.identity_checked {
+            return Ok(true);
+        }
+        let pattern = self.email_pattern()?;
+        let entries = self.repo.config_entries()?;
+        let Some((key, problem)) = gitconfig::identity_problem(&entries, pattern.as_ref()) else {
+            self.identity_checked = true;
+            return Ok(true);
+        };
+        let value = gitconfig::effective(&entries, key).map_or("", |e| e.value.as_str());
+        self.prompt_identity(key, value, problem, then);
+        Ok(false)
+    }
+
+    fn email_pattern(&self) -> EResult<Option<Regex>> {
+        let pattern = self.config.commit.email_pattern.as_deref();
+        pattern
+            .map(Regex::new)
+            .transpose()
+            .wrap_err("commit.email_pattern")
+    }
+
+    fn prompt_identity(&mut self, key: &'static str, text: &str, problem: String, then: Then) {
+        let title = t!("{key} to commit with in this repository", key = key);
+        let mut input = modal::Input::new(title).with_text(text);
+        input.set_error(problem);
+        self.modal = Some((Modal::Input(input), Purpose::Identity { key, then }));
+    }
+
+    /// Asks for the value of `key` in the repository's git config.
+    fn prompt_git_config(&mut self, key: &'static str, text: &str, error: Option<String>) {
+        let title = t!("{key} in this repository (empty: unset)", key = key);
+        let mut input = modal::Input::new(title).with_text(text);
+        if let Some(error) = error {
+            input.set_error(error);
+        }
+        self.modal = Some((Modal::Input(input), Purpose::GitConfig(key)));
+    }
+
+    /// Sets `key` in the repository's git config, or unsets it there.
+    fn set_git_config(&mut self, key: &'static str, value: Option<String>) -> EResult<()> {
+        let message = match &value {
+            Some(value) => t!("set {key} to {value}", key = key, value = value),
+            None => t!("unset {key} in this repository", key = key),
+        };
+        self.repo.set_local_config(key, value)?;
+        let entries = self.repo.config_entries()?;
+        if let Some(git_config) = self.git_config.shown_mut() {
+            git_config.reload(entries);
+        }
+        self.notifications.success(message);
+        Ok(())
+    }
+
+    fn reload_remotes(&mut self) -> EResult<()> {
+        let items = self.repo.remotes()?;
+        if let Some(remotes) = self.remotes.shown_mut() {
+            remotes.reload(items);
+        }
+        Ok(())
+    }
+
+    /// The hosting accounts of the known forges and of the remotes.
+    fn load_accounts(&self) -> EResult<Vec<accounts::Account>> {
+        let remotes = self.repo.remotes()?;
+        let urls = remotes.iter().filter_map(|r| r.url.as_deref());
+        Ok(self
+            .forges
+            .accounts(urls)
+            .into_iter()
+            .map(|(forge, host)| accounts::Account::new(&*forge, host))
+            .collect())
+    }
+
+    fn reload_accounts(&mut self) -> EResult<()> {
+        let items = self.load_accounts()?;
+        if let Some(accounts) = self.accounts.shown_mut() {
+            accounts.reload(items);
+        }
+        Ok(())
+    }
+
+    fn edit_token(&mut self) {
+        let Some(account) = self.accounts.shown().and_then(Accounts::selected) else {
+            return;
+        };
+        let title = t!(
+            "{forge} token for {host}",
+            forge = account.forge,
+            host = account.host
+        );
+        let input = modal::Input::new(title).secret();
+        self.modal = Some((Modal::Input(input), Purpose::Token(account.host.clone())));
+    }
+
+    fn delete_token(&mut self) {
+        let Some(account) = self.accounts.shown().and_then(Accounts::selected) else {
+            return;
+        };
+        match account.status {
+            accounts::Status::Keychain => {
+                let message = t!(
+                    "Delete the token for {host} from the keychain?",
+                    host = account.host
+                );
+                let confirm = modal::Confirm::new(t!("Delete token"), message);
+                let purpose = Purpose::DeleteToken(account.host.clone());
+                self.modal = Some((Modal::Confirm(confirm), purpose));
+            }
+            accounts::Status::Env(var) => self.notifications.info(t!(
+                "the token comes from ${var}, unset it to use the keychain",
+                var = var
+            )),
+            accounts::Status::Command(program) => self.notifications.info(t!(
+                "the token comes from {program}, log out there to stop using it",
+                program = program
+            )),
+            accounts::Status::Missing | accounts::Status::Error(_) => {}
+        }
+    }
+
+    fn run_command_line(&mut self, text: &str) {
+        match palette::Command::parse(text) {
+            Ok(palette::Command::Git(args)) => {
+                let mut cmd = self.repo.location().command();
+                cmd.args(&args);
+                let title = format!("git {}", args.join(" "));
+                self.output = Some(Output::spawn(title, cmd));
+            }
+            Ok(palette::Command::History { path, follow }) => {
+                if let Err(err) = self.file_history(&path, follow) {
+                    self.notifications.report(&err);
+                }
+            }
+            Err(err) => {
+                let mut input = modal::Input::new(t!("Command, e.g. git log -3")).with_text(text);
+                input.set_error(err);
+                self.modal = Some((Modal::Input(input), Purpose::CommandLine));
+            }
+        }
+    }
+
+    /// Opens the history of `path`, relative to the top of the work tree, on the
+    /// selected branch.
+    fn file_history(&mut self, path: &str, follow: bool) -> EResult<()> {
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        let name = branch.name.clone();
+        let changes = self.repo.file_history(&name, branch.typ, path, follow)?;
+        let history = FileHistory::new(name, path.to_string(), changes);
+        self.drill(nav::View::History(history));
+        Ok(())
+    }
+
+    /// Opens the uncommitted changes of the work tree, without ignored files.
+    fn show_status(&mut self) -> EResult<()> {
+        let files = self.repo.status_files(false)?;
+        self.drill(nav::View::Status(status::StatusView::new(files, false)));
+        Ok(())
+    }
+
+    /// Asks for the name of a branch to create at the ref selected on the refs
+    /// screen, or the tag selected on the tags tab.
+    fn prompt_branch_at_ref(&mut self) {
+        let (name, from, commit) = match self.nav.current_mut() {
+            nav::View::Refs(view) => match view.selected() {
+                Some(r) => (r.name.clone(), r.name.clone(), r.commit.is_some()),
+                None => return,
+            },
+            nav::View::Tags(view) => match view.selected() {
+                Some(tag) => {
+                    let from = format!("refs/tags/{}", tag.name);
+                    (tag.name.clone(), from, tag.commit.is_some())
+                }
+                None => return,
+            },
+            _ => return,
+        };
+        if !commit {
+            let msg = t!("{name} does not point at a commit", name = name);
+            self.notifications.info(msg);
+            return;
+        }
+        let input = modal::Input::new(t!("New branch from {from}", from = from));
+        self.modal = Some((Modal::Input(input), Purpose::BranchAtRef(from)));
+    }
+
+    fn create_branch_at_ref(&mut self, from: &str, name: &str) -> EResult<()> {
+        let name = git::refname::normalize(name);
+        self.claim_name(name, Naming::At(from.to_string()))
+    }
+
+    /// Creates the branch `name` at the ref `from`, replacing a local one of
+    /// that name if `force`, and tracking `track` if given.
+    fn create_at(
+        &mut self,
+        from: &str,
+        name: String,
+        force: bool,
+        track: Option<String>,
+    ) -> EResult<()> {
+        let ctx = hooks::Context {
+            branch: Some(name.clone()),
+            commit: self.repo.commit_id(from),
+            target: Some(from.to_string()),
+        };
+        let from = from.to_string();
+        self.run_pre_hook(hooks::Event::PreCreate, vec![ctx.clone()], move |app| {
+            if let Err(err) = app.repo.create_branch_at(&name, &from, force) {
+                // let the user fix the name rather than starting over
+                let mut input = modal::Input::new(t!("New branch from {from}", from = from))
+                    .with_text(name.clone());
+                input.set_error(format!("{err:#}"));
+                app.modal = Some((Modal::Input(input), Purpose::BranchAtRef(from)));
+                return Ok(());
+            }
+            app.run_hook(hooks::Event::PostCreate, vec![ctx]);
+            app.track(&name, track);
+            app.notifications
+                .success(t!("created branch {name}", name = name));
+            if let nav::View::Refs(view) = app.nav.current_mut() {
+                view.reload(app.repo.stream_refs());
+            }
+            app.pending_select = Some(name);
+            app.load_branches()
+        })
+    }
+
+    /// Reads the files of the status screen again, if it is open, listing
+    /// ignored files the other way round if `toggle`.
+    fn read_status_files(&mut self, toggle: bool) {
+        let nav::View::Status(view) = self.nav.current_mut() else {
+            return;
+        };
+        let ignored = view.ignored != toggle;
+        match self.repo.status_files(ignored) {
+            Ok(files) => view.replace(files, ignored),
+            Err(err) => self.notifications.report(&err.into()),
+        }
+    }
+
+    /// Opens the hunks of the selected file of the status screen, the unstaged
+    /// ones unless all its changes are staged, or stages or unstages the
+    /// selected hunk of the hunk screen.
+    fn stage(&mut self) -> EResult<()> {
+        match self.nav.current_mut() {
+            nav::View::Status(view) => {
+                let Some(file) = view.selected() else {
+                    return Ok(());
+                };
+                if file.is_ignored() {
+                    let msg = t!(
+                        "{path} is ignored, there is nothing to stage",
+                        path = file.path
+                    );
+                    self.notifications.info(msg);
+                    return Ok(());
+                }
+                if file.is_conflicted() {
+                    let msg = t!(
+                        "{path} has conflicts, resolve them in the file or with git mergetool rather than hunk by hunk",
+                        path = file.path
+                    );
+                    self.notifications.error(msg);
+                    return Ok(());
+                }
+                let path = file.path.clone();
+                let staged = file.worktree == ' ' && !file.is_untracked();
+                let found = self.repo.hunks(&path, staged)?;
+                self.drill(nav::View::Hunks(HunkView::new(path, staged, found)));
+            }
+            nav::View::Hunks(view) => {
+                let Some((idx, hunk)) = view.selected() else {
+                    return Ok(());
+                };
+                let res = self
+                    .repo
+                    .apply_hunk(&view.path, view.staged, idx, &hunk.header);
+                // a hunk that changed meanwhile is shown as it is now instead
+                if let Err(err) = res {
+                    self.notifications.report(&err.into());
+                }
+                self.refresh_status();
+                self.read_hunks(false)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Opens the editor for the message to commit what is staged with, starting
+    /// from the commit template if there is one.
+    fn start_commit(&mut self) -> EResult<()> {
+        self.refresh_status();
+        if self.status.staged == 0 {
+            self.notifications.info(t!("nothing is staged to commit"));
+            return Ok(());
+        }
+        if !self.check_identity(Then::Action(Action::Commit))? {
+            return Ok(());
+        }
+        let template = self.repo.commit_template()?.unwrap_or_default();
+        let lint = Lint::new(&self.config.commit)?;
+        let editor = modal::Editor::new(t!("Commit message"))
+            .with_text(&template)
+            .at_start()
+            .with_lint(lint);
+        self.edit(editor, Purpose::Commit);
+        Ok(())
+    }
+
+    /// Commits what is staged with `message`, unless the lint is enforced and
+    /// finds something wrong, in which case the message is opened again.
+    fn commit(&mut self, message: String) -> EResult<()> {
+        if lint::is_blank(&message) {
+            self.notifications
+                .info(t!("commit aborted, the message is empty"));
+            return Ok(());
+        }
+        let lint = Lint::new(&self.config.commit)?;
+        let warnings = lint.check(&message);
+        if self.config.commit.enforce && !warnings.is_empty() {
+            self.notifications.error(t!(
+                "not committed, the message has {count} problems",
+                count = warnings.len()
+            ));
+            let editor = modal::Editor::new(t!("Commit message"))
+                .with_text(&message)
+                .with_lint(lint);
+            self.edit(editor, Purpose::Commit);
+            return Ok(());
+        }
+        // git runs the hooks, whose output is shown as it comes
+        if self.config.commit.hooks && !self.repo.signs_commits() {
+            let cmd = self.repo.commit_command(&message);
+            self.output = Some(Output::spawn(t!("commit").to_string(), cmd));
+            return Ok(());
+        }
+        let repo = self.repo.clone();
+        self.make_commits(t!("commit").to_string(), move || Ok(repo.commit(&message)?));
+        Ok(())
+    }
+
+    /// Asks whether to discard the changes to the selected file of the status
+    /// screen. Only `y` confirms.
+    fn confirm_discard(&mut self) {
+        let nav::View::Status(view) = self.nav.current_mut() else {
+            return;
+        };
+        let Some(file) = view.selected() else {
+            return;
+        };
+        if file.is_ignored() {
+            let msg = t!(
+                "{path} is ignored, there is nothing to discard",
+                path = file.path
+            );
+            self.notifications.info(msg);
+            return;
+        }
+        let (title, message) = match file.is_untracked() {
+            true => (
+                t!("Delete {path}?", path = file.path),
+                t!("It is untracked. It is stashed first, so u on this screen brings it back."),
+            ),
+            false => (
+                t!("Discard the changes to {path}?", path = file.path),
+                t!("Staged and unstaged changes both go. They are stashed first, so u on this screen brings them back."),
+            ),
+        };
+        let confirm = modal::Confirm::new(title, message).strict();
+        let purpose = Purpose::Discard(file.path.clone());
+        self.modal = Some((Modal::Confirm(confirm), purpose));
+    }
+
+    /// Brings back the changes discarded last, from their stash.
+    fn restore_discarded(&mut self) -> EResult<()> {
+        if !matches!(self.nav.current_mut(), nav::View::Status(_)) {
+            return Ok(());
+        }
+        let Some((path, id)) = self.discarded.pop() else {
+            self.notifications.info(t!("nothing was discarded"));
+            return Ok(());
+        };
+        if let Err(err) = self.repo.restore_discarded(id) {
+            // kept, to try again once whatever is in the way is gone
+            self.discarded.push((path, id));
+            return Err(err.into());
+        }
+        self.notifications
+            .success(t!("restored the changes to {path}", path = path));
+        self.refresh_status();
+        self.read_status_files(false);
+        Ok(())
+    }
+
+    /// Reads the hunks of the hunk screen again, if it is open, switching
+    /// between the unstaged and the staged ones if `switch`.
+    fn read_hunks(&mut self, switch: bool) -> EResult<()> {
+        let nav::View::Hunks(view) = self.nav.current_mut() else {
+            return Ok(());
+        };
+        let staged = view.staged != switch;
+        let found = self.repo.hunks(&view.path, staged)?;
+        view.replace(staged, found);
+        Ok(())
+    }
+
+    /// Starts searching the selected branch for commits that add or remove
+    /// `needle`, on a screen that fills in as they are found.
+    fn pickaxe(&mut self, needle: &str) {
+        let Some(branch) = self.branch_list.current() else {
+            return;
+        };
+        let rx = self.repo.pickaxe(&branch.name, branch.typ, needle);
+        let search = pickaxe::Search::new(branch.name.clone(), needle.to_string(), rx);
+        self.drill(nav::View::Pickaxe(search));
+    }
+
+    /// Shows the diff of the selected commit of a file's history or a search,
+    /// or of the selected file of the work tree.
+    fn show_found_commit(&mut self) {
+        let (args, title) = match self.nav.current_mut() {
+            nav::View::Status(view) => {
+                let Some(file) = view.selected() else {
+                    return;
+                };
+                let title = file.path.clone();
+                match view.diff_args() {
+                    Some(args) => self.show_diff(diff::Shown { title, args }),
+                    None => {
+                        let msg = t!("{path} is not tracked, there is no diff", path = title);
+                        self.notifications.info(msg);
+                    }
+                }
+                return;
+            }
+            nav::View::Hunks(_) => return,
+            nav::View::Refs(view) => {
+                let Some(commit) = view.selected().and_then(|r| r.commit.as_ref()) else {
+                    return;
+                };
+                let title = format!("{} {}", commit.short, commit.summary);
+                (vec![commit.id.to_string()], title)
+            }
+            nav::View::Tags(view) => {
+                let Some(tag) = view.selected().filter(|t| t.commit.is_some()) else {
+                    return;
+                };
+                // the tag itself, so that an annotated one shows its message
+                (vec![format!("refs/tags/{}", tag.name)], tag.name.clone())
+            }
+            nav::View::Remotes(_) => return,
+            nav::View::History(history) => {
+                let (Some(args), Some(change)) = (history.show_args(), history.selected()) else {
+                    return;
+                };
+                (args, format!("{} {}", change.commit.short, change.path))
+            }
+            nav::View::Pickaxe(search) => {
+                let (Some(args), Some(hit)) = (search.show_args(), search.selected()) else {
+                    return;
+                };
+                (args, format!("{} {}", hit.commit.short, hit.commit.summary))
+            }
+            nav::View::Branches | nav::View::Commits(_) => return,
+        };
+        let mut show = vec![
+            "show".to_string(),
+            "--summary".to_string(),
+            "--patch".to_string(),
+        ];
+        show.extend(args);
+        self.show_diff(diff::Shown { title, args: show });
+    }
+
+    /// Shows how the selected local branch and its upstream went apart.
+    fn show_divergence(&mut self) -> EResult<()> {
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        let name = redact::branch(&branch.name).into_owned();
+        if branch.typ != git2::BranchType::Local {
+            self.notifications
+                .info(t!("{name} is not a local branch", name = name));
+            return Ok(());
+        }
+        match self.repo.divergence(&branch.name)? {
+            Some(div) => self.divergence = Some((div, 0)),
+            None => self
+                .notifications
+                .info(t!("{name} has no upstream", name = name)),
+        }
+        Ok(())
+    }
+
+    /// Copies the commit under the cursor as `[view] copy` asks, its full id
+    /// unless set. A link falls back to the id where the forge is unknown.
+    fn copy_cursor_commit(&mut self) -> EResult<()> {
+        let Some(commit) = self.cursor_commit() else {
+            return Ok(());
+        };
+        let url = match self.config.view.copy {
+            clipboard::Format::Markdown => self.commit_url(&commit.id.to_string())?,
+            _ => None,
+        };
+        let text = self
+            .config
+            .view
+            .copy
+            .text(&commit, url.as_deref())
+            .unwrap_or_else(|| commit.id.to_string());
+        self.copy_text(&text)
+    }
+
+    /// Offers the ways to copy the commit under the cursor, each with what it
+    /// copies, starting at the one `y` uses.
+    fn pick_copy_format(&mut self) -> EResult<()> {
+        let Some(commit) = self.cursor_commit() else {
+            return Ok(());
+        };
+        let url = self.commit_url(&commit.id.to_string())?;
+        let width = clipboard::Format::ALL
+            .iter()
+            .map(|f| f.label().chars().count())
+            .max()
+            .unwrap_or_default()
+            + 2;
+        let (texts, items): (Vec<String>, Vec<String>) = clipboard::Format::ALL
+            .iter()
+            .filter_map(|format| {
+                let text = format.text(&commit, url.as_deref())?;
+                let first = text.lines().next().unwrap_or_default();
+                let preview = match format {
+                    clipboard::Format::Markdown => redact::url(first),
+                    _ => redact::text(first),
+                };
+                let item = format!("{:<width$}{preview}", format.label());
+                Some((text, item))
+            })
+            .unzip();
+        let current = clipboard::Format::ALL
+            .iter()
+            .position(|f| *f == self.config.view.copy)
+            .filter(|idx| *idx < texts.len())
+            .unwrap_or_default();
+        let select = modal::Select::new(t!("Copy commit as"), items).with_selected(current);
+        self.modal = Some((Modal::Select(select), Purpose::CopyCommit(texts)));
+        Ok(())
+    }
+
+    /// The commit under the cursor of the details pane.
+    fn cursor_commit(&self) -> Option<git::Commit> {
+        let (range, branch) = (self.range.as_ref()?, self.branch_list.current()?);
+        branch.commits().get(range.cursor).cloned()
+    }
+
+    /// The page of the commit `id` on the forge of the selected branch's
+    /// remote, or of `origin` or else any remote for a branch without one.
+    fn commit_url(&self, id: &str) -> EResult<Option<String>> {
+        let remotes = self.repo.remotes()?;
+        let remote = self.branch_list.current().and_then(|b| {
+            let upstream = match b.typ {
+                git2::BranchType::Local => self.repo.upstream(&b.name)?,
+                git2::BranchType::Remote => b.name.clone(),
+            };
+            remotes
+                .iter()
+                .find(|r| upstream.starts_with(&format!("{}/", r.name)))
+        });
+        let origin = remotes.iter().find(|r| r.name == "origin");
+        let url = remote
+            .into_iter()
+            .chain(origin)
+            .chain(&remotes)
+            .find_map(|r| {
+                let repo = forge::Repo::parse(r.url.as_deref()?)?;
+                let forge = self.forges.for_host(&repo.host)?;
+                Some(forge.commit_url(&repo, id))
+            });
+        Ok(url)
+    }
+
+    /// Puts `text` on the clipboard, telling what went there by its first line.
+    fn copy_text(&mut self, text: &str) -> EResult<()> {
+        clipboard::copy(text)?;
+        // a markdown link has a url in it, shown without any credentials
+        let first = text.lines().next().unwrap_or_default();
+        let first = redact::url(first);
+        self.notifications.info(t!("copied {name}", name = first));
+        Ok(())
+    }
+
+    /// Shows the commit under the cursor of the details pane on the whole
+    /// screen: its message in full, who wrote and who committed it, and its diff.
+    fn show_cursor_commit(&mut self) {
+        let (Some(range), Some(branch)) = (&self.range, self.branch_list.current()) else {
+            return;
+        };
+        let Some(commit) = branch.commits().get(range.cursor) else {
+            return;
+        };
+        let id = commit.id.to_string();
+        let title = format!("{} {}", commit.short, commit.summary);
+        let args = ["show", "--format=fuller", "--summary", "--patch", &id];
+        let args = args.into_iter().map(String::from).collect();
+        self.show_diff(diff::Shown { title, args });
+        self.output = self.output.take().map(Output::full_screen);
+    }
+
+    /// Shows a diff in the output pane with the options of the session.
+    fn show_diff(&mut self, shown: diff::Shown) {
+        let Some((sub, args)) = shown.args.split_first() else {
+            return;
+        };
+        let mut cmd = self.repo.location().command();
+        cmd.arg(sub)
+            .args(self.config.diff.args())
+            .args(self.diff_options.args())
+            .args(args);
+        let title = match self.diff_options.label() {
+            Some(label) => format!("{} [{label}]", shown.title),
+            None => shown.title.clone(),
+        };
+        let fold_after = self.config.diff.fold_lines.unwrap_or(1000);
+        self.output = Some(Output::spawn(title, cmd).with_diff(shown, fold_after));
+    }
+
+    /// Shows the commits a diff moved a submodule across, if it is checked out.
+    fn submodule_commits(&mut self, submodule: &diff::Submodule) {
+        let dir = self.repo.location().dir().join(&submodule.path);
+        if !dir.join(".git").exists() {
+            let path = &submodule.path;
+            self.notifications.info(t!(
+                "the submodule at {path} is not initialized",
+                path = path
+            ));
+            return;
+        }
+        let mut cmd = std::process::Command::new("git");
+        cmd.current_dir(&dir).args([
+            "log",
+            "--no-color",
+            "--format=%h %ad %an: %s",
+            "--date=short",
+            "--end-of-options",
+            &submodule.range(),
+        ]);
+        let (old, new) = (
+            submodule.short(&submodule.old),
+            submodule.short(&submodule.new),
+        );
+        let title = format!("{} {old}..{new}", submodule.path);
+        self.output = Some(Output::spawn(title, cmd));
+    }
+
+    /// Shows the open diff again after its options changed, where it was.
+    fn rerun_diff(&mut self) {
+        let Some(old) = self.output.take() else {
+            return;
+        };
+        old.kill();
+        if let Some(shown) = old.diff().cloned() {
+            self.show_diff(shown);
+            self.output = self.output.take().map(|new| new.keep_scroll(&old));
+        }
+    }
+
+    /// Shows what checking out the selected branch would change in the work tree,
+    /// uncommitted changes included.
+    fn diff_worktree(&mut self) {
+        let Some(branch) = self.branch_list.current() else {
+            return;
+        };
+        let rev = match branch.typ {
+            git2::BranchType::Local => format!("refs/heads/{}", branch.name),
+            git2::BranchType::Remote => format!("refs/remotes/{}", branch.name),
+        };
+        // reversed, so the diff reads from the work tree to the branch
+        let args = [
+            "diff",
+            "--no-color",
+            "-R",
+            "--stat",
+            "--summary",
+            "--patch",
+            &rev,
+            "--",
+        ];
+        let title = t!("work tree → {branch}", branch = branch.name);
+        let args = args.map(String::from).to_vec();
+        self.show_diff(diff::Shown { title, args });
+    }
+
+    /// Runs a user command against the selected branch, either as a job or, for
+    /// interactive commands, in the foreground once the current frame is done.
+    fn run_command(&mut self, idx: usize) {
+        let Some(user) = self.config.commands.get(idx).cloned() else {
+            return;
+        };
+        let mut values = vec![];
+        if let Some(branch) = self.branch_list.current() {
+            let rev = match branch.typ {
+                git2::BranchType::Local => format!("refs/heads/{}", branch.name),
+                git2::BranchType::Remote => format!("refs/remotes/{}", branch.name),
+            };
+            values.push(("branch".to_string(), branch.name.clone()));
+            let name = branch.name.clone();
+            let commit = self.repo.commit_id(&rev);
+            values.extend(commit.map(|c| ("commit".to_string(), c)));
+            let upstream = self.repo.upstream(&name);
+            values.extend(upstream.map(|u| ("upstream".to_string(), u)));
+        }
+        let script = commands::render(&user.cmd, &values);
+        let name = user.name.unwrap_or(script.clone());
+        let mut cmd = platform::current().shell(&script);
+        cmd.current_dir(self.repo.location().dir());
+        if user.interactive {
+            self.suspend = Some((name, cmd));
+            return;
+        }
+        self.jobs.submit(name.clone(), true, move |cancel| {
+            Ok(git::command::capture(cmd, &name, || cancel.is_cancelled())?)
+        });
+    }
+
+    /// Opens `editor`, or hands its text to git's editor if the config says so.
+    fn edit(&mut self, editor: modal::Editor, purpose: Purpose) {
+        match self.config.editor.external {
+            true => self.external = Some((editor.text(), purpose)),
+            false => self.modal = Some((Modal::Editor(editor), purpose)),
+        }
+    }
+
+    /// Edits `text` in git's editor and submits the result for `purpose`, as if
+    /// it came from the built-in editor. Quitting the editor with an error, as
+    /// `:cq` does in vim, cancels.
+    fn run_editor<B: Backend>(
+        &mut self,
+        terminal: &mut crate::bootstrap::Tui<B>,
+        text: String,
+        purpose: Purpose,
+    ) -> EResult<()> {
+        let edited = self.external_edit(terminal, &text);
+        self.redraw = true;
+        self.title = None;
+        match edited {
+            Ok(Some(text)) => {
+                if let Err(err) = self.on_submit(purpose, Value::Text(text)) {
+                    self.report(&err);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => self.notifications.report(&err),
+        }
+        Ok(())
+    }
+
+    /// Returns the edited text, or `None` if the editor failed. The text goes
+    /// through a file of its own in the temporary directory, created anew so
+    /// that no one else can have put it there, and removed after.
+    fn external_edit<B: Backend>(
+        &mut self,
+        terminal: &mut crate::bootstrap::Tui<B>,
+        text: &str,
+    ) -> EResult<Option<String>> {
+        let out = self
+            .repo
+            .location()
+            .command()
+            .args(["var", "GIT_EDITOR"])
+            .output()
+            .wrap_err("git var GIT_EDITOR")?;
+        let editor = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        if !out.status.success() || editor.is_empty() {
+            color_eyre::eyre::bail!("no editor is configured");
+        }
+        let mut file = tempfile::Builder::new()
+            .prefix("grit-")
+            .suffix(".txt")
+            .tempfile()
+            .wrap_err("create file to edit")?;
+        std::io::Write::write_all(&mut file, format!("{text}\n").as_bytes())
+            .wrap_err_with(|| format!("write {}", file.path().display()))?;
+        // closed, for editors that will not open a file held open elsewhere
+        let path = file.into_temp_path();
+        // the editor is run the way git runs it, so that it may carry arguments
+        let mut cmd = platform::current().editor(&editor, &path);
+        cmd.current_dir(self.repo.location().dir());
+        crate::bootstrap::suspend()?;
+        let status = cmd.status();
+        crate::bootstrap::resume(terminal)?;
+        match status.wrap_err_with(|| editor.clone())? {
+            status if status.success() => {
+                let text = std::fs::read_to_string(&path)
+                    .wrap_err_with(|| format!("read {}", path.display()))?;
+                Ok(Some(text.trim_end().to_string()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn run_interactive<B: Backend>(
+        &mut self,
+        terminal: &mut crate::bootstrap::Tui<B>,
+        name: String,
+        mut cmd: std::process::Command,
+    ) -> EResult<()> {
+        crate::bootstrap::suspend()?;
+        let status = cmd.status();
+        crate::bootstrap::resume(terminal)?;
+        self.redraw = true;
+        self.title = None;
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => self.notifications.error(t!(
+                "{name} exited with {status}",
+                name = name,
+                status = status
+            )),
+            Err(err) => self.notifications.error(format!("{name}: {err}")),
+        }
+        self.load_branches()
+    }
+
+    /// Runs `work` with the terminal suspended, so that gpg's pinentry or
+    /// ssh-keygen can ask for the passphrase of the signing key on it.
+    fn run_foreground<B: Backend>(
+        &mut self,
+        terminal: &mut crate::bootstrap::Tui<B>,
+        name: String,
+        work: jobs::Foreground,
+    ) -> EResult<()> {
+        crate::bootstrap::suspend()?;
+        println!("{}", t!("{name}: signing commits…", name = name));
+        // pinentry asks on the terminal gpg is told about
+        if std::env::var_os("GPG_TTY").is_none() {
+            if let Some(tty) = platform::current().tty() {
+                git::command::set_sign_env(vec![("GPG_TTY".to_string(), tty.into())]);
+            }
+        }
+        let res = work();
+        crate::bootstrap::resume(terminal)?;
+        self.redraw = true;
+        self.title = None;
+        match res {
+            Ok(msg) if msg.trim().is_empty() => self.notifications.success(name),
+            Ok(msg) => self
+                .notifications
+                .success(format!("{name}: {}", msg.trim())),
+            Err(err) => self.report(&err),
+        }
+        self.load_branches()
+    }
+
+    /// Runs `work`, which writes commits without git, once the installed ones
+    /// of the git `hooks` pass in the output pane, if `commit.hooks` is on.
+    fn make_hooked_commits(
+        &mut self,
+        name: String,
+        hooks: &[(&str, Vec<String>)],
+        work: impl FnOnce() -> EResult<String> + Send + 'static,
+    ) -> EResult<()> {
+        let cmd = match self.config.commit.hooks {
+            true => self.repo.hooks_command(hooks)?,
+            false => None,
+        };
+        let Some(cmd) = cmd else {
+            self.make_commits(name, work);
+            return Ok(());
+        };
+        let names: Vec<&str> = hooks.iter().map(|(hook, _)| *hook).collect();
+        let title = t!(
+            "hooks of {name}: {hooks}",
+            name = name,
+            hooks = names.join(", ")
+        );
+        self.output = Some(Output::spawn(title, cmd));
+        self.after_output = Some(AfterOutput::Commits(name, Box::new(work)));
+        Ok(())
+    }
+
+    /// The `pre` hooks and `commit-msg`, to run before grit writes a commit
+    /// with `message` itself, with the file `commit-msg` is given the message
+    /// in, to read it back from as the hook may rewrite it. None if
+    /// `commit.hooks` is off.
+    fn commit_hooks(
+        &self,
+        pre: &[&'static str],
+        message: &str,
+    ) -> EResult<(GitHooks, Option<PathBuf>)> {
+        if !self.config.commit.hooks {
+            return Ok((vec![], None));
+        }
+        let file = self.repo.message_file();
+        // ending in a newline, as git gives it, for hooks that append trailers
+        let message = format!("{}\n", message.trim_end());
+        std::fs::write(&file, message).wrap_err("write the commit message")?;
+        let mut hooks: Vec<_> = pre.iter().map(|hook| (*hook, vec![])).collect();
+        hooks.push(("commit-msg", vec![file.display().to_string()]));
+        Ok((hooks, Some(file)))
+    }
+
+    /// Goes on with `after`, what waits for the command that just exited, if
+    /// it `passed`.
+    fn after_output(&mut self, after: Option<AfterOutput>, passed: bool) {
+        match (after, passed) {
+            (None, _) | (Some(AfterOutput::Checkout(_)), false) => {}
+            (Some(AfterOutput::Commits(name, work)), true) => self.make_commits(name, work),
+            (Some(AfterOutput::Commits(name, _)), false) => self
+                .notifications
+                .error(t!("a hook failed, so {name} was not done", name = name)),
+            (Some(AfterOutput::Checkout(branch)), true) => {
+                let ctx = self.checkout_context(&branch);
+                self.run_hook(hooks::Event::PostCheckout, vec![ctx]);
+            }
+        }
+    }
+
+    /// Collects the output of the commands hidden while they ran, going on
+    /// with what waits for those that exited. Returns true if any did.
+    fn poll_hidden_outputs(&mut self) -> bool {
+        let mut exited = vec![];
+        for (idx, (output, _)) in self.hidden_outputs.iter_mut().enumerate() {
+            if output.poll() {
+                exited.push(idx);
+            }
+        }
+        for idx in exited.iter().rev() {
+            let (output, after) = self.hidden_outputs.remove(*idx);
+            let passed = output.succeeded();
+            if !matches!(after, Some(AfterOutput::Commits(..))) {
+                let title = redact::text(output.title()).into_owned();
+                match passed {
+                    true => self.notifications.success(title),
+                    false => self
+                        .notifications
+                        .error(t!("{title} failed", title = title)),
+                }
+            }
+            self.after_output(after, passed);
+        }
+        !exited.is_empty()
+    }
+
+    /// Runs git work that writes commits: in the foreground if they are
+    /// signed, otherwise as a job.
+    fn make_commits(
+        &mut self,
+        name: String,
+        work: impl FnOnce() -> EResult<String> + Send + 'static,
+    ) {
+        match self.repo.signs_commits() {
+            true => self.foreground = Some((name, Box::new(work))),
+            false => {
+                self.jobs.submit(name, true, move |_| work());
+            }
+        }
+    }
+
+    /// Runs the hook for `event` as a job, once for each of `ctxs`, reporting
+    /// its output.
+    fn run_hook(&mut self, event: hooks::Event, ctxs: Vec<hooks::Context>) {
+        self.submit_hook(event, ctxs, None);
+    }
+
+    /// Runs the `pre-*` hook for `event` as a job, once for each of `ctxs`,
+    /// and `then` once it passed for all of them. A failing one vetoes `then`.
+    /// Without a hook for `event`, `then` runs right away.
+    fn run_pre_hook(
+        &mut self,
+        event: hooks::Event,
+        ctxs: Vec<hooks::Context>,
+        then: impl FnOnce(&mut App) -> EResult<()> + 'static,
+    ) -> EResult<()> {
+        if !self.hooks.has(event) {
+            return then(self);
+        }
+        self.submit_hook(event, ctxs, Some(Box::new(then)));
+        Ok(())
+    }
+
+    fn submit_hook(
+        &mut self,
+        event: hooks::Event,
+        ctxs: Vec<hooks::Context>,
+        then: Option<AfterHook>,
+    ) {
+        if !self.hooks.has(event) {
+            return;
+        }
+        let hooks = self.hooks.clone();
+        let id = self.jobs.submit(event.key(), false, move |_| {
+            let mut out = vec![];
+            for ctx in &ctxs {
+                out.extend(hooks.run(event, ctx)?);
+            }
+            Ok(out.join("\n"))
+        });
+        self.hook_jobs.insert(id, then);
+    }
+
+    /// Reports how the hook job `finished` went, going on with `then` if it
+    /// passed.
+    fn hook_ran(&mut self, finished: jobs::Finished, then: Option<AfterHook>) {
+        match finished.result {
+            Some(Ok(out)) => {
+                if !out.trim().is_empty() {
+                    let out = format!("{}: {}", finished.name, out.trim());
+                    self.notifications.info(out);
+                }
+                if let Some(then) = then {
+                    if let Err(err) = then(self) {
+                        self.report(&err);
+                    }
+                }
+            }
+            Some(Err(err)) => self.notifications.report(&err),
+            None => self
+                .notifications
+                .info(t!("{job} cancelled", job = finished.name)),
+        }
+    }
+
+    /// Where the current screen is, for coming back to it.
+    fn position(&self) -> nav::Position {
+        nav::Position {
+            selection: self.branch_list.selection(),
+            offset: self.branch_list.state.offset(),
+            details_scroll: self.details_scroll,
+            range: self.range.clone(),
+        }
+    }
+
+    /// Shows tab `tab`: the branches, or the tags or remotes read afresh.
+    fn switch_tab(&mut self, tab: usize) -> EResult<()> {
+        if self.nav.is_root() && self.nav.tab() == tab {
+            return Ok(());
+        }
+        let view = match tab {
+            1 => nav::View::Tags(tags::TagsView::new(self.repo.tags()?)),
+            2 => nav::View::Remotes(Remotes::new(self.repo.remotes()?)),
+            _ => nav::View::Branches,
+        };
+        // the commits of a branch are a screen on top, closed with the rest
+        self.range = None;
+        self.nav.switch_tab(view);
+        Ok(())
+    }
+
+    /// Opens `view` on top of the current screen.
+    fn drill(&mut self, view: nav::View) {
+        let left = self.position();
+        self.nav.push(view, left);
+    }
+
+    /// Closes the current screen and puts the one below back where it was left.
+    fn back(&mut self) {
+        if let Some(position) = self.nav.pop() {
+            self.restore_position(position);
+        }
+        // hunks may have been staged on the screen just closed
+        self.read_status_files(false);
+    }
+
+    /// Closes the screens above `depth` and puts that one back where it was left.
+    fn back_to(&mut self, depth: usize) {
+        if let Some(position) = self.nav.pop_to(depth) {
+            self.restore_position(position);
+        }
+    }
+
+    fn restore_position(&mut self, position: nav::Position) {
+        self.branch_list.restore(position.selection);
+        *self.branch_list.state.offset_mut() = position.offset;
+        self.details_scroll = position.details_scroll;
+        self.range = position.range;
+    }
+
+    /// Starts `op` on the selected commits, asking first for anything that
+    /// changes the checked out branch.
+    fn range_op(&mut self, op: range::Op) {
+        if self.range.is_some() && !matches!(op, range::Op::Export | range::Op::Diffstat) {
+            match self.check_identity(Then::Range(op)) {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(err) => return self.report(&err),
+            }
+        }
+        let (Some(range), Some(branch)) = (&self.range, self.branch_list.current()) else {
+            return;
+        };
+        let ids: Vec<git2::Oid> = range
+            .commits(branch.commits())
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        let (Some(oldest), Some(newest)) = (ids.first(), ids.last()) else {
+            return;
+        };
+        let count = ids.len();
+        let rewrites = matches!(op, range::Op::Fixup | range::Op::Autosquash);
+        if rewrites && self.head.as_ref() != Some(&range.branch) {
+            let msg = t!(
+                "{op} works on the checked out branch, check out {branch} first",
+                op = op.label(),
+                branch = redact::branch(&range.branch)
+            );
+            self.notifications.info(msg);
+            return;
+        }
+        if matches!(op, range::Op::Autosquash) && self.config.branch.is_protected(&range.branch) {
+            let branch = redact::branch(&range.branch);
+            self.notifications
+                .error(t!("{branch} is protected", branch = branch));
+            return;
+        }
+        match op {
+            range::Op::Fixup => {
+                if self.status.staged == 0 {
+                    self.notifications
+                        .info(t!("nothing is staged to fix the commit up with"));
+                    return;
+                }
+                let Some(target) = branch.commits().get(range.cursor) else {
+                    return;
+                };
+                let ids = vec![target.id];
+                self.run_range_op(op, ids, String::new());
+            }
+            range::Op::Autosquash => {
+                let message = t!(
+                    "Rebase {branch} from below the selected commits, folding fixups into their targets? Its commits get new ids.",
+                    branch = redact::branch(&range.branch)
+                );
+                let confirm = modal::Confirm::new(op.label(), message);
+                self.modal = Some((Modal::Confirm(confirm), Purpose::Range(op, ids)));
+            }
+            range::Op::Diffstat => {
+                let base = self
+                    .repo
+                    .commit_id(&format!("{oldest}^"))
+                    .unwrap_or_else(|| EMPTY_TREE.to_string());
+                let args = ["diff", "--stat", "--summary", &base, &newest.to_string()];
+                let title = t!("diffstat of {count} commits", count = count);
+                let args = args.map(String::from).to_vec();
+                self.show_diff(diff::Shown { title, args });
+            }
+            range::Op::Export => {
+                let title = t!("Export {count} patches to directory", count = count);
+                let input = modal::Input::new(title).with_text("patches");
+                self.modal = Some((Modal::Input(input), Purpose::Range(op, ids)));
+            }
+            range::Op::CherryPick | range::Op::Revert => {
+                let message = t!(
+                    "{op} {count} commits on the checked out branch?",
+                    op = op.label(),
+                    count = count
+                );
+                let confirm = modal::Confirm::new(op.label(), message);
+                self.modal = Some((Modal::Confirm(confirm), Purpose::Range(op, ids)));
+            }
+        }
+    }
+
+    /// Runs a range operation as a job, or in the foreground if it writes
+    /// commits that are signed.
+    fn run_range_op(&mut self, op: range::Op, ids: Vec<git2::Oid>, dir: String) {
+        let repo = self.repo.clone();
+        let name = format!("{} ({})", op.label(), ids.len());
+        let hooks = self.config.commit.hooks;
+        self.back();
+        let work = move || -> EResult<String> {
+            match op {
+                range::Op::CherryPick => Ok(repo.cherry_pick(&ids, hooks)?),
+                range::Op::Revert => {
+                    let newest_first: Vec<_> = ids.iter().rev().copied().collect();
+                    Ok(repo.revert(&newest_first, hooks)?)
+                }
+                range::Op::Export => Ok(repo.format_patch(&ids, &dir)?),
+                range::Op::Fixup => Ok(repo.fixup(ids[0])?),
+                range::Op::Autosquash => {
+                    // onto the parent of the oldest, which is from the root if
+                    // it has none
+                    let base = repo.commit_id(&format!("{}^", ids[0]));
+                    let base = base.and_then(|id| git2::Oid::from_str(&id).ok());
+                    Ok(repo.autosquash(base)?)
+                }
+                range::Op::Diffstat => Ok(String::new()),
+            }
+        };
+        match op {
+            range::Op::CherryPick
+            | range::Op::Revert
+            | range::Op::Fixup
+            | range::Op::Autosquash => self.make_commits(name, work),
+            range::Op::Export | range::Op::Diffstat => {
+                self.jobs.submit(name, false, move |_| work());
+            }
+        }
+    }
+
+    fn on_submit(&mut self, purpose: Purpose, value: Value) -> EResult<()> {
+        match (purpose, value) {
+            (Purpose::AuthorFilter(mut authors), Value::Index(idx)) if idx < authors.len() => {
+                self.author_filter = authors.swap_remove(idx);
+                self.details_scroll = 0;
+            }
+            (Purpose::Pickaxe, Value::Text(needle)) if !needle.is_empty() => {
+                self.pickaxe(&needle);
+            }
+            (Purpose::FileHistory, Value::Text(path)) if !path.trim().is_empty() => {
+                self.file_history(path.trim(), true)?;
+            }
+            (Purpose::BranchAtRef(from), Value::Text(name)) => {
+                self.create_branch_at_ref(&from, name.trim())?;
+            }
+            (Purpose::RenameBranch { old, expected }, Value::Text(new)) => {
+                self.rename_branch(old, expected, new.trim())?;
+            }
+            (
+                Purpose::NameTaken {
+                    name,
+                    naming,
+                    mut choices,
+                },
+                Value::Index(idx),
+            ) if idx < choices.len() => {
+                self.resolve_taken(name, naming, choices.swap_remove(idx))?;
+            }
+            (Purpose::PickSort, Value::Index(idx)) => {
+                if let Some(sort) = branch::Sort::ALL.get(idx) {
+                    self.set_sort(*sort)?;
+                }
+            }
+            (Purpose::PickView, Value::Index(idx)) => self.apply_view(idx.checked_sub(1))?,
+            (Purpose::CopyCommit(texts), Value::Index(idx)) => {
+                if let Some(text) = texts.get(idx) {
+                    self.copy_text(text)?;
+                }
+            }
+            (Purpose::BranchName(new), Value::Text(text)) => self.next_branch_field(new, text)?,
+            (Purpose::RenamePattern, Value::Text(text)) => self.preview_renames(&text)?,
+            (Purpose::ApplyRenames(renames), Value::Confirmed) => self.apply_renames(renames)?,
+            (Purpose::DeleteBranches(branches), Value::Confirmed) => {
+                self.delete_branches(branches, true)
+            }
+            (Purpose::DeleteBranch(name, tip, force), Value::Confirmed) => {
+                self.delete_branches(vec![(name, tip)], force)
+            }
+            (Purpose::DeleteRemoteBranch(name, tip), Value::Confirmed) => {
+                self.delete_remote_branch(name, tip)
+            }
+            (Purpose::PushTo(branch, remotes), Value::Index(idx)) => {
+                if let Some(remote) = remotes.into_iter().nth(idx) {
+                    self.push_branch(branch, Some(remote));
+                }
+            }
+            (Purpose::Commit, Value::Text(text)) => self.commit(text)?,
+            (Purpose::Restack(links), Value::Confirmed) => {
+                let repo = self.repo.clone();
+                let name = t!("restack {count} branches", count = links.len());
+                self.make_commits(name, move || Ok(repo.restack(&links)?));
+            }
+            (Purpose::BranchMenu(actions), Value::Index(idx)) => {
+                if let Some(action) = actions.get(idx) {
+                    self.dispatch(*action)?;
+                }
+            }
+            (Purpose::Merge(name, typ), Value::Confirmed) => {
+                let repo = self.repo.clone();
+                let job = t!("merge {branch}", branch = redact::branch(&name));
+                let message = git::Repository::merge_message(&name, typ);
+                // only a merge commit runs hooks, as with git merge
+                let (hooks, file) = match self.repo.plan_merge(&name, typ)? {
+                    git::Plan::Clean(_) => self.commit_hooks(&["pre-merge-commit"], &message)?,
+                    _ => (vec![], None),
+                };
+                self.make_hooked_commits(job, &hooks, move || {
+                    let message = read_message(file, message)?;
+                    integrated(repo.merge(&name, typ, &message)?)
+                })?;
+            }
+            (Purpose::Rebase(name, typ), Value::Confirmed) => {
+                let repo = self.repo.clone();
+                let job = t!("rebase onto {branch}", branch = redact::branch(&name));
+                self.make_commits(job, move || integrated(repo.rebase(&name, typ)?));
+            }
+            (Purpose::RemoteHead { remote, branches }, Value::Index(idx)) => {
+                if let Some(branch) = branches.get(idx) {
+                    self.repo.set_remote_head(&remote, branch)?;
+                    self.notifications.success(t!(
+                        "{remote}/HEAD points at {remote}/{branch}",
+                        remote = remote,
+                        branch = branch
+                    ));
+                    if let nav::View::Refs(view) = self.nav.current_mut() {
+                        view.reload(self.repo.stream_refs());
+                    }
+                    self.load_branches()?;
+                }
+            }
+            (Purpose::Deepen, Value::Text(text)) => match text.trim() {
+                "" => self.deepen(None),
+                text => match text.parse() {
+                    Ok(n) if n > 0 => self.deepen(Some(n)),
+                    _ => self.prompt_deepen(text, Some(t!("not a number").into())),
+                },
+            },
+            (Purpose::Setting(key), Value::Text(text)) => match text.trim() {
+                "" => self.set_setting(key, None)?,
+                text => match text.parse::<usize>() {
+                    Ok(n) if i64::try_from(n).is_ok() => {
+                        self.set_setting(key, Some(settings::Value::Number(n)))?
+                    }
+                    Ok(_) => self.prompt_setting(key, text, Some(t!("too large").into())),
+                    Err(_) => self.prompt_setting(key, text, Some(t!("not a number").into())),
+                },
+            },
+            (Purpose::GitConfig(key), Value::Text(text)) => match text.trim() {
+                "" => self.set_git_config(key, None)?,
+                value => match gitconfig::validate(key, value) {
+                    Ok(()) => self.set_git_config(key, Some(value.to_string()))?,
+                    Err(err) => self.prompt_git_config(key, value, Some(err)),
+                },
+            },
+            (Purpose::Identity { key, then }, Value::Text(text)) => {
+                let pattern = self.email_pattern()?;
+                let value = text.trim();
+                match gitconfig::check_identity(key, value, pattern.as_ref()) {
+                    Ok(()) => {
+                        self.repo.set_local_config(key, Some(value.to_string()))?;
+                        self.notifications.success(t!(
+                            "set {key} to {value}",
+                            key = key,
+                            value = value
+                        ));
+                        match then {
+                            Then::Action(action) => self.dispatch(action)?,
+                            Then::Range(op) => self.range_op(op),
+                        }
+                    }
+                    Err(problem) => self.prompt_identity(key, value, problem, then),
+                }
+            }
+            (Purpose::Discard(path), Value::Confirmed) => {
+                let id = self.repo.discard(&path)?;
+                self.notifications.success(t!(
+                    "discarded the changes to {path}, u brings them back",
+                    path = path
+                ));
+                self.discarded.push((path, id));
+                self.refresh_status();
+                self.read_status_files(false);
+            }
+            (Purpose::CommandLine, Value::Text(text)) => self.run_command_line(&text),
+            (Purpose::Range(op, ids), Value::Text(dir)) => self.run_range_op(op, ids, dir),
+            (Purpose::Range(op, ids), Value::Confirmed) => {
+                self.run_range_op(op, ids, String::new())
+            }
+            (Purpose::PullRequest(new), Value::Text(text)) => self.submit_pull_request(new, &text),
+            (Purpose::Token(host), Value::Text(text)) => {
+                if text.trim().is_empty() {
+                    return Ok(());
+                }
+                forge::token::store(&host, text.trim())?;
+                self.reviews.invalidate();
+                self.notifications
+                    .success(t!("stored the token for {host}", host = host));
+                self.reload_accounts()?;
+            }
+            (Purpose::Askpass(request), Value::Text(text)) => request.answer(&text),
+            (Purpose::Askpass(request), Value::Confirmed) => request.answer("yes"),
+            (Purpose::DeleteToken(host), Value::Confirmed) => {
+                forge::token::delete(&host)?;
+                self.reviews.invalidate();
+                self.notifications
+                    .success(t!("deleted the token for {host}", host = host));
+                self.reload_accounts()?;
+            }
+            (Purpose::RemoteName(retry), Value::Text(name)) => {
+                let name = name.trim();
+                match git2::Remote::is_valid_name(name) {
+                    true => self.prompt_remote_url(name.to_string(), retry, "", None),
+                    false => {
+                        let err = git::Error::InvalidRemoteName(name.to_string());
+                        self.prompt_remote_name(retry, name, Some(err.to_string()))
+                    }
+                }
+            }
+            (Purpose::RemoteUrl(name, retry), Value::Text(url)) => {
+                self.add_remote(name, url.trim(), retry)
+            }
+            (Purpose::ExportBranches, Value::Text(text)) => self.export_branches(text.trim())?,
+            (Purpose::Snapshot, Value::Text(text)) if !text.trim().is_empty() => {
+                self.snapshot = Some(text.trim().into());
+            }
+            (Purpose::Stash, Value::Text(text)) => {
+                let out = self.repo.stash(text.trim(), self.config.stash.untracked)?;
+                self.notifications.success(out.trim());
+                self.refresh_status();
+            }
+            (Purpose::Squash(name, plan), Value::Text(text)) => {
+                if text.trim().is_empty() {
+                    self.notifications
+                        .info(t!("squash aborted, the message is empty"));
+                    return Ok(());
+                }
+                let done = t!(
+                    "squashed {count} commits of {name}",
+                    count = plan.count,
+                    name = name
+                );
+                let (hooks, file) = self.commit_hooks(&["pre-commit"], &text)?;
+                let repo = self.repo.clone();
+                self.make_hooked_commits(done, &hooks, move || {
+                    let text = read_message(file, text)?;
+                    repo.squash(&name, &plan, &text)?;
+                    Ok(String::new())
+                })?;
+            }
+            (Purpose::BranchState(name), Value::Index(idx)) => {
+                let state = checklist::State::ALL.get(idx).copied();
+                self.checklist.set_state(&name, state)?;
+            }
+            (Purpose::BranchNote(name), Value::Text(text)) => {
+                let note = Some(text.trim().to_string()).filter(|t| !t.is_empty());
+                self.checklist.set_note(&name, note)?;
+            }
+            (Purpose::Note(id), Value::Text(text)) => {
+                self.repo.set_note(id, &text)?;
+                self.reload_selected()?;
+            }
+            (Purpose::Refspecs(name), Value::Text(text)) => {
+                let specs = text
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect();
+                self.repo.set_fetch_refspecs(&name, specs)?;
+                self.notifications
+                    .success(t!("updated refspecs of {remote}", remote = name));
+                self.reload_remotes()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn prompt_rename_pattern(&mut self, text: String, error: Option<String>) {
+        let mut input = modal::Input::new(t!(
+            "Rename pattern: prefix:<text>, suffix:<text> or s/<regex>/<new>/"
+        ))
+        .with_text(text);
+        if let Some(error) = error {
+            input.set_error(error);
+        }
+        self.modal = Some((Modal::Input(input), Purpose::RenamePattern));
+    }
+
+    fn preview_renames(&mut self, text: &str) -> EResult<()> {
+        let pattern = match rename::Pattern::parse(text) {
+            Ok(pattern) => pattern,
+            Err(err) => {
+                self.prompt_rename_pattern(text.to_string(), Some(err));
+                return Ok(());
+            }
+        };
+        let names: Vec<String> = self
+            .branch_list
+            .targets()
+            .into_iter()
+            .filter(|b| b.typ == git2::BranchType::Local)
+            .filter(|b| !self.config.branch.is_protected(&b.name))
+            .map(|b| b.name.clone())
+            .collect();
+        let existing = self.repo.local_names()?;
+        let renames = rename::plan(&pattern, &names, &existing);
+        if renames.is_empty() {
+            let err = t!("the pattern does not change any local branch name");
+            self.prompt_rename_pattern(text.to_string(), Some(err.to_string()));
+            return Ok(());
+        }
+        let preview = rename::preview(&renames);
+        if renames.iter().any(|r| r.problem.is_some()) {
+            let title = t!("Cannot rename");
+            let confirm = modal::Confirm::new(title, preview);
+            self.modal = Some((Modal::Confirm(confirm), Purpose::RenamePattern));
+            return Ok(());
+        }
+        let renames = renames.into_iter().map(|r| (r.old, r.new)).collect();
+        let title = t!("Rename branches?");
+        let confirm = modal::Confirm::new(title, preview);
+        self.modal = Some((Modal::Confirm(confirm), Purpose::ApplyRenames(renames)));
+        Ok(())
+    }
+
+    /// Asks for a new name for the selected local branch.
+    fn prompt_rename_branch(&mut self) {
+        let Some(branch) = self.branch_list.current() else {
+            return;
+        };
+        if branch.typ != git2::BranchType::Local {
+            let name = redact::branch(&branch.name).into_owned();
+            self.notifications
+                .info(t!("{name} is not a local branch", name = name));
+            return;
+        }
+        if self.config.branch.is_protected(&branch.name) {
+            let branch = redact::branch(&branch.name).into_owned();
+            self.notifications
+                .error(t!("{branch} is protected", branch = branch));
+            return;
+        }
+        let old = branch.name.clone();
+        let expected = branch.tip();
+        let input = modal::Input::new(t!("Rename {name}", name = old)).with_text(old.clone());
+        self.modal = Some((Modal::Input(input), Purpose::RenameBranch { old, expected }));
+    }
+
+    fn rename_branch(
+        &mut self,
+        old: String,
+        expected: Option<git2::Oid>,
+        new: &str,
+    ) -> EResult<()> {
+        let new = git::refname::normalize(new);
+        if new == old {
+            return Ok(());
+        }
+        self.claim_name(new, Naming::Rename { old, expected })
+    }
+
+    /// Renames `old` to `new`, replacing a local branch of that name if
+    /// `force`, and tracking `track` if given.
+    fn rename_to(
+        &mut self,
+        old: String,
+        expected: Option<git2::Oid>,
+        new: String,
+        force: bool,
+        track: Option<String>,
+    ) -> EResult<()> {
+        let ctx = hooks::Context {
+            branch: Some(old.clone()),
+            commit: self.repo.commit_id(&format!("refs/heads/{old}")),
+            target: Some(new.clone()),
+        };
+        self.run_pre_hook(hooks::Event::PreRename, vec![ctx.clone()], move |app| {
+            if let Err(err) = app.repo.rename_branch(&old, &new, expected, force) {
+                // let the user fix the name rather than starting over
+                let mut input = modal::Input::new(t!("Rename {name}", name = old)).with_text(new);
+                input.set_error(format!("{err:#}"));
+                app.modal = Some((Modal::Input(input), Purpose::RenameBranch { old, expected }));
+                return Ok(());
+            }
+            let ctx = hooks::Context {
+                branch: Some(new.clone()),
+                ..ctx
+            };
+            app.run_hook(hooks::Event::PostRename, vec![ctx]);
+            app.checklist.rename(&old, &new)?;
+            app.track(&new, track);
+            app.summary.renamed(&old, &new);
+            app.notifications
+                .success(t!("renamed {old} to {new}", old = old, new = new));
+            app.pending_select = Some(new);
+            app.load_branches()
+        })
+    }
+
+    /// Renames each of `renames`, an old name and a new one, or none of them:
+    /// a `pre-rename` hook that fails stops the batch before any ref is
+    /// touched, and the renames done are undone if one fails.
+    fn apply_renames(&mut self, renames: Vec<(String, String)>) -> EResult<()> {
+        self.branch_list.marked.clear();
+        let ctx = |old: &String, new: &String, commit: Option<String>| hooks::Context {
+            branch: Some(old.clone()),
+            commit,
+            target: Some(new.clone()),
+        };
+        let mut commits = HashMap::new();
+        let mut ctxs = vec![];
+        for (old, new) in &renames {
+            let commit = self.repo.commit_id(&format!("refs/heads/{old}"));
+            ctxs.push(ctx(old, new, commit.clone()));
+            commits.insert(old.clone(), commit);
+        }
+        self.run_pre_hook(hooks::Event::PreRename, ctxs, move |app| {
+            app.rename_all(renames, commits)
+        })
+    }
+
+    /// Renames each of `renames`, whose `pre-rename` hooks passed, with the
+    /// commits they were at for the `post-rename` ones.
+    fn rename_all(
+        &mut self,
+        renames: Vec<(String, String)>,
+        mut commits: HashMap<String, Option<String>>,
+    ) -> EResult<()> {
+        let steps = rename::order(&renames, &self.repo.local_names()?);
+        for (at, step) in steps.iter().enumerate() {
+            // a branch is only checked against the tip seen on its first step
+            let expected = self
+                .branch_list
+                .items
+                .iter()
+                .find(|b| b.typ == git2::BranchType::Local && b.name == step.from)
+                .filter(|_| commits.contains_key(&step.from))
+                .and_then(git::Branch::tip);
+            if let Err(err) = self
+                .repo
+                .rename_branch(&step.from, &step.to, expected, false)
+            {
+                self.report(&err.into());
+                for undo in steps[..at].iter().rev() {
+                    if let Err(err) = self.repo.rename_branch(&undo.to, &undo.from, None, false) {
+                        self.report(&err.into());
+                    }
+                }
+                return self.load_branches();
+            }
+        }
+        let mut ctxs = vec![];
+        for (old, new) in &renames {
+            ctxs.push(hooks::Context {
+                branch: Some(new.clone()),
+                commit: commits.remove(old).flatten(),
+                target: Some(new.clone()),
+            });
+            if let Err(err) = self.checklist.rename(old, new) {
+                self.notifications.report(&err);
+            }
+            self.summary.renamed(old, new);
+        }
+        self.run_hook(hooks::Event::PostRename, ctxs);
+        self.notifications
+            .success(t!("renamed {count} branches", count = renames.len()));
+        self.load_branches()
+    }
+
+    /// Starts creating a branch from the selected one, using the configured name
+    /// template if there is one.
+    fn create_branch(&mut self) -> EResult<()> {
+        let Some(from) = self.branch_list.selection() else {
+            return Ok(());
+        };
+        let template = self.config.branch.template.as_deref().unwrap_or("{name}");
+        let template = Template::parse(template);
+        let new = NewBranch {
+            from,
+            fields: template.fields(),
+            template,
+            values: vec![],
+        };
+        self.prompt_branch_field(new, None)
+    }
+
+    fn prompt_branch_field(&mut self, new: NewBranch, error: Option<String>) -> EResult<()> {
+        let field = new.fields.get(new.values.len()).cloned();
+        let (title, text) = match field.as_deref() {
+            Some("name") | None => (t!("New branch from {from}", from = new.from.name), None),
+            Some("user") => (
+                t!("Branch {field}", field = "user"),
+                self.repo.user_name()?.map(|u| template::slugify(&u)),
+            ),
+            Some(field) => (t!("Branch {field}", field = field), None),
+        };
+        let mut input = modal::Input::new(title);
+        if let Some(text) = text {
+            input = input.with_text(text);
+        }
+        if let Some(error) = error {
+            input.set_error(error);
+        }
+        self.modal = Some((Modal::Input(input), Purpose::BranchName(new)));
+        Ok(())
+    }
+
+    fn next_branch_field(&mut self, mut new: NewBranch, text: String) -> EResult<()> {
+        if let Some(field) = new.fields.get(new.values.len()).cloned() {
+            new.values.push((field, text));
+        }
+        if new.values.len() < new.fields.len() {
+            return self.prompt_branch_field(new, None);
+        }
+        let name = git::refname::normalize(&new.template.render(&new.values));
+        if let Err(err) = git::refname::validate(&name) {
+            new.values.pop();
+            return self.prompt_branch_field(new, Some(format!("{name}: {err}")));
+        }
+        self.claim_name(name, Naming::New(new))
+    }
+
+    /// Creates the branch `name` that `new` was filled in for, replacing a
+    /// local one of that name if `force`, and tracking `track` if given.
+    fn create_new(
+        &mut self,
+        mut new: NewBranch,
+        name: String,
+        force: bool,
+        track: Option<String>,
+    ) -> EResult<()> {
+        let Some(from) = self
+            .branch_list
+            .items
+            .iter()
+            .find(|b| b.name == new.from.name && b.typ == new.from.typ)
+            .cloned()
+        else {
+            return Ok(());
+        };
+        let rev = match from.typ {
+            git2::BranchType::Local => format!("refs/heads/{}", from.name),
+            git2::BranchType::Remote => format!("refs/remotes/{}", from.name),
+        };
+        let ctx = hooks::Context {
+            branch: Some(name.clone()),
+            commit: self.repo.commit_id(&rev),
+            target: Some(from.name.clone()),
+        };
+        self.run_pre_hook(hooks::Event::PreCreate, vec![ctx.clone()], move |app| {
+            if let Err(err) = app.repo.create_branch(&name, &from, force) {
+                // let the user fix the last field rather than starting over
+                new.values.pop();
+                return app.prompt_branch_field(new, Some(format!("{err:#}")));
+            }
+            app.run_hook(hooks::Event::PostCreate, vec![ctx]);
+            app.track(&name, track);
+            app.notifications
+                .success(t!("created branch {name}", name = name));
+            app.pending_select = Some(name);
+            app.load_branches()
+        })
+    }
+
+    /// Goes on naming a branch `name` if no other branch has the name, and
+    /// otherwise asks what to do about the one that has it.
+    fn claim_name(&mut self, name: String, naming: Naming) -> EResult<()> {
+        let holders = self.repo.branches_named(&name)?;
+        if holders.is_empty() {
+            return self.name_branch(naming, name, false, None);
+        }
+        // replacing drops the commits of the local branch, so only one that
+        // is merged may be, as deleting it without forcing would
+        let replaceable = !self.config.branch.is_protected(&name)
+            && self.head.as_deref() != Some(&name)
+            && self.repo.is_merged(&name).unwrap_or(false);
+        let free = taken::suggest(&name, |candidate| {
+            self.repo
+                .branches_named(candidate)
+                .map_or(true, |holders| !holders.is_empty())
+        });
+        let Some(choices) = taken::choices(&holders, replaceable, free) else {
+            return Ok(());
+        };
+        let held: Vec<&str> = holders.iter().map(|(branch, _)| branch.as_str()).collect();
+        let title = t!(
+            "{name} is taken by {branches}",
+            name = name,
+            branches = held.join(", ")
+        );
+        let items = choices.iter().map(|choice| choice.label(&name)).collect();
+        let select = modal::Select::new(title, items);
+        self.modal = Some((
+            Modal::Select(select),
+            Purpose::NameTaken {
+                name,
+                naming,
+                choices,
+            },
+        ));
+        Ok(())
+    }
+
+    /// Creates or renames the branch as `naming` says.
+    fn name_branch(
+        &mut self,
+        naming: Naming,
+        name: String,
+        force: bool,
+        track: Option<String>,
+    ) -> EResult<()> {
+        match naming {
+            Naming::New(new) => self.create_new(new, name, force, track),
+            Naming::At(from) => self.create_at(&from, name, force, track),
+            Naming::Rename { old, expected } => self.rename_to(old, expected, name, force, track),
+        }
+    }
+
+    fn resolve_taken(
+        &mut self,
+        name: String,
+        naming: Naming,
+        choice: taken::Choice,
+    ) -> EResult<()> {
+        match choice {
+            taken::Choice::Overwrite => self.name_branch(naming, name, true, None),
+            taken::Choice::Track(upstream) => self.name_branch(naming, name, false, Some(upstream)),
+            taken::Choice::Anyway => self.name_branch(naming, name, false, None),
+            taken::Choice::Jump(branch) => self.select_branch(&branch),
+            taken::Choice::Rename(free) => {
+                let (title, purpose) = match naming {
+                    Naming::New(mut new) => {
+                        new.values.pop();
+                        let error = t!("{name} is taken, {free} is not", name = name, free = free);
+                        return self.prompt_branch_field(new, Some(error));
+                    }
+                    Naming::At(from) => (
+                        t!("New branch from {from}", from = from),
+                        Purpose::BranchAtRef(from),
+                    ),
+                    Naming::Rename { old, expected } => (
+                        t!("Rename {name}", name = old),
+                        Purpose::RenameBranch { old, expected },
+                    ),
+                };
+                let input = modal::Input::new(title).with_text(free);
+                self.modal = Some((Modal::Input(input), purpose));
+                Ok(())
+            }
+        }
+    }
+
+    /// Makes the local branch `name` track `upstream`, if given.
+    fn track(&mut self, name: &str, upstream: Option<String>) {
+        let Some(upstream) = upstream else {
+            return;
+        };
+        if let Err(err) = self.repo.set_upstream(name, &upstream) {
+            self.report(&err.into());
+        }
+    }
+
+    /// Asks whose commits to show, among the authors of the selected branch.
+    fn pick_author(&mut self) -> EResult<()> {
+        self.load_selected()?;
+        self.load_current_now()?;
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        let mut authors = vec![None];
+        let mut items = vec![t!("all authors").to_string()];
+        for (name, count) in branch.contributors() {
+            authors.push(Some(name.to_string()));
+            items.push(format!("{} ({count})", redact::person(name)));
+        }
+        let current = authors
+            .iter()
+            .position(|a| *a == self.author_filter)
+            .unwrap_or_default();
+        let select = modal::Select::new(t!("Show commits by"), items).with_selected(current);
+        self.modal = Some((Modal::Select(select), Purpose::AuthorFilter(authors)));
+        Ok(())
+    }
+
+    fn pick_sort(&mut self) {
+        let items = branch::Sort::ALL.map(|s| s.label().to_string()).to_vec();
+        let current = branch::Sort::ALL
+            .iter()
+            .position(|s| *s == self.branch_list.sort)
+            .unwrap_or_default();
+        let select = modal::Select::new(t!("Sort by"), items).with_selected(current);
+        self.modal = Some((Modal::Select(select), Purpose::PickSort));
+    }
+
+    /// Offers the saved views, after one that shows every branch.
+    fn pick_view(&mut self) {
+        if self.config.views.is_empty() {
+            let msg = t!("no saved views, add [[views]] tables to the config");
+            self.notifications.info(msg);
+            return;
+        }
+        let mut items = vec![t!("all branches").to_string()];
+        items.extend(
+            self.config
+                .views
+                .iter()
+                .enumerate()
+                .map(|(idx, view)| match idx < 9 {
+                    true => format!("{}  {}", idx + 1, view.name),
+                    false => format!("   {}", view.name),
+                }),
+        );
+        let current = self.active_view().map_or(0, |idx| idx + 1);
+        let select = modal::Select::new(t!("Views"), items).with_selected(current);
+        self.modal = Some((Modal::Select(select), Purpose::PickView));
+    }
+
+    /// Filters and sorts the branch list as the saved view `idx` does, or
+    /// shows every branch if it is `None`.
+    fn apply_view(&mut self, idx: Option<usize>) -> EResult<()> {
+        let Some(idx) = idx else {
+            self.view = None;
+            return self.filter_branches(String::new());
+        };
+        let Some(view) = self.config.views.get(idx).cloned() else {
+            return Ok(());
+        };
+        self.view = Some(idx);
+        self.filter_branches(view.filter)?;
+        if let Some(sort) = view.sort {
+            self.branch_list.sort = sort;
+            self.branch_list.sort();
+            self.load_selected()?;
+        }
+        Ok(())
+    }
+
+    /// The saved view chosen last, unless the filter or sort changed since.
+    fn active_view(&self) -> Option<usize> {
+        let idx = self.view?;
+        let view = self.config.views.get(idx)?;
+        let sorted = view.sort.is_none_or(|sort| sort == self.branch_list.sort);
+        (sorted && view.filter == self.branch_list.query).then_some(idx)
+    }
+
+    fn set_sort(&mut self, sort: branch::Sort) -> EResult<()> {
+        self.notifications
+            .info(t!("sorted by {sort}", sort = sort.label()));
+        self.branch_list.sort = sort;
+        self.branch_list.sort();
+        self.load_selected()
+    }
+
+    fn cycle_sort(&mut self) -> EResult<()> {
+        let sort = match self.branch_list.sort {
+            branch::Sort::NameAscending => branch::Sort::NameDescending,
+            branch::Sort::NameDescending => branch::Sort::DateAscending,
+            branch::Sort::DateAscending => branch::Sort::DateDescending,
+            branch::Sort::DateDescending => branch::Sort::Divergence,
+            branch::Sort::Divergence => branch::Sort::NameAscending,
+        };
+        self.set_sort(sort)
+    }
+
+    fn select_none(&mut self) -> EResult<()> {
+        self.branch_list.state.select(None);
+        Ok(())
+    }
+
+    fn set_mark(&mut self, mark: char) {
+        let Some(sel) = self.branch_list.selection() else {
+            return;
+        };
+        self.marks.set(mark, &sel);
+        let msg = t!(
+            "marked {branch} as {mark}",
+            branch = redact::branch(&sel.name),
+            mark = mark
+        );
+        self.notifications.info(msg);
+    }
+
+    fn jump_to_mark(&mut self, mark: char) -> EResult<()> {
+        let Some((name, typ)) = self.marks.get(mark) else {
+            self.notifications.info(t!("no mark {mark}", mark = mark));
+            return Ok(());
+        };
+        let idx = self
+            .branch_list
+            .items
+            .iter()
+            .position(|b| b.name == *name && b.typ == *typ);
+        match idx {
+            Some(idx) => self.select_index(idx),
+            None => {
+                let msg = t!("{branch} is not in the list", branch = redact::branch(name));
+                self.notifications.info(msg);
+                Ok(())
+            }
+        }
+    }
+
+    /// Narrows the branch list to `query`, loading the branch selected then.
+    fn filter_branches(&mut self, query: String) -> EResult<()> {
+        if query != self.branch_list.query {
+            self.read_query_facts(&query)?;
+            self.branch_list.set_query(query);
+            self.load_selected()?;
+        }
+        Ok(())
+    }
+
+    /// Reads what the terms of `query` are checked against: who the user is,
+    /// unless known already, and which branches are merged, which a merge or
+    /// fetch since the last time may have changed.
+    fn read_query_facts(&mut self, query: &str) -> EResult<()> {
+        let Ok(parsed) = Query::parse(query) else {
+            return Ok(());
+        };
+        let list = &mut self.branch_list;
+        if parsed.needs_me() && list.me.is_none() {
+            list.me = self.repo.user_name()?;
+        }
+        if parsed.needs_merged() {
+            list.merged = self.repo.merged_branches()?;
+        }
+        Ok(())
+    }
+
+    /// Selects the next branch starting with `letter`, so that typing it again
+    /// goes through them all.
+    fn jump_to(&mut self, letter: char) -> EResult<()> {
+        match self.branch_list.next_starting_with(letter) {
+            Some(idx) => self.select_index(idx),
+            None => {
+                self.notifications
+                    .info(t!("no branch starts with {letter}", letter = letter));
+                Ok(())
+            }
+        }
+    }
+
+    fn select_next(&mut self) -> EResult<()> {
+        self.branch_list.state.select_next();
+        self.selection_moved();
+        Ok(())
+    }
+
+    fn select_previous(&mut self) -> EResult<()> {
+        self.branch_list.state.select_previous();
+        self.selection_moved();
+        Ok(())
+    }
+
+    fn select_first(&mut self) -> EResult<()> {
+        self.branch_list.state.select_first();
+        self.selection_moved();
+        Ok(())
+    }
+
+    fn select_last(&mut self) -> EResult<()> {
+        self.branch_list.state.select_last();
+        self.selection_moved();
+        Ok(())
+    }
+
+    /// The context of the `post-checkout` hook for the local branch `name`.
+    fn checkout_context(&self, name: &str) -> hooks::Context {
+        hooks::Context {
+            branch: Some(name.to_string()),
+            commit: self.repo.commit_id(&format!("refs/heads/{name}")),
+            target: None,
+        }
+    }
+
+    /// Checks out the selected branch, or for a remote one the local branch
+    /// tracking it, created if there is none yet.
+    fn toggle_branch(&mut self) -> EResult<()> {
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        let (name, typ) = (branch.name.clone(), branch.typ);
+        if name.ends_with("/HEAD") {
+            self.notifications
+                .info(t!("{name} only points at another branch", name = name));
+            return Ok(());
+        }
+        if typ == git2::BranchType::Local && self.head.as_ref() == Some(&name) {
+            self.footer_note = Some((
+                t!(
+                    "{name} is checked out already",
+                    name = redact::branch(&name)
+                )
+                .to_string(),
+                false,
+                Instant::now(),
+            ));
+            return Ok(());
+        }
+        // git runs the hook, whose output is shown as it comes, and the list
+        // is reloaded once it is done
+        if self.config.commit.hooks && self.repo.has_hook("post-checkout") {
+            let (local, cmd) = self.repo.switch_command(&name, typ)?;
+            let title = t!("post-checkout of {name}", name = redact::branch(&local));
+            self.output = Some(Output::spawn(title.to_string(), cmd));
+            self.after_output = Some(AfterOutput::Checkout(local.clone()));
+            self.pending_select = Some(local);
+            return Ok(());
+        }
+        match self.repo.switch_to(&name, typ) {
+            Ok((local, created)) => {
+                let ctx = self.checkout_context(&local);
+                self.run_hook(hooks::Event::PostCheckout, vec![ctx]);
+                let note = match created {
+                    false => t!("checked out {name}", name = redact::branch(&local)),
+                    true => t!(
+                        "checked out {local} tracking {name}",
+                        local = redact::branch(&local),
+                        name = redact::branch(&name)
+                    ),
+                };
+                self.footer_note = Some((note.to_string(), false, Instant::now()));
+                self.pending_select = Some(local);
+                self.load_branches()
+            }
+            Err(err) => {
+                let note = match err.root() {
+                    git::Error::DirtyWorktree(_) => {
+                        t!(
+                            "could not check out {name}: uncommitted changes",
+                            name = redact::branch(&name)
+                        )
+                    }
+                    _ => t!("could not check out {name}", name = redact::branch(&name)),
+                };
+                self.footer_note = Some((note.to_string(), true, Instant::now()));
+                self.report(&err.into());
+                Ok(())
+            }
+        }
+    }
+
+    /// Quits, unless jobs are still running, which it asks about first rather
+    /// than cutting a fetch or push off midway.
+    fn exit(&mut self) {
+        if self.jobs.is_busy() {
+            self.quit_prompt = Some(jobs::Quit::Asking);
+            return;
+        }
+        self.exit = true;
+    }
+
+    /// Shows `err`, reloading the branches if it came from one having moved
+    /// since they were loaded, so that what is shown is current again.
+    fn report(&mut self, err: &color_eyre::Report) {
+        self.notifications.report(err);
+        let root = err.downcast_ref::<git::Error>().map(git::Error::root);
+        if matches!(root, Some(git::Error::Moved(_))) {
+            if let Err(err) = self.load_branches() {
+                self.notifications.report(&err);
+            }
+        }
+    }
+
+    /// The other worktree `branch` is checked out in, if any.
+    fn worktree_of(&self, branch: &git::Branch) -> Option<&PathBuf> {
+        match branch.typ {
+            git2::BranchType::Local => self.worktrees.get(&branch.name),
+            git2::BranchType::Remote => None,
+        }
+    }
+
+    /// Browses the directories around this repository for another to open,
+    /// with the ones opened before in this session at the top.
+    fn open_dir_picker(&mut self) {
+        let dir = self.dir();
+        let start = dir.parent().unwrap_or(&dir);
+        let current = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+        let recent = self
+            .recent
+            .iter()
+            .filter(|path| **path != current)
+            .cloned()
+            .collect();
+        self.dir_picker = Some(dirs::Picker::new(start, recent));
+    }
+
+    /// Closes this repository to open the one at or above `dir`, if there is
+    /// one, leaving the browser open otherwise.
+    fn switch_repo(&mut self, dir: PathBuf) {
+        if git2::Repository::discover(&dir).is_err() {
+            self.notifications
+                .info(t!("{dir} is not in a git repository", dir = dir.display()));
+            return;
+        }
+        tracing::info!(?dir, "switching repository");
+        self.dir_picker = None;
+        self.switch_to = Some(dir);
+        self.exit();
+    }
+
+    /// Checks the commit under the cursor out in a worktree of its own under
+    /// the temp dir, to look around or run tests at it without touching this
+    /// one, until grit exits. Inside tmux it opens in a new window, and
+    /// otherwise its path is copied.
+    fn inspect_commit(&mut self) -> EResult<()> {
+        let Some(commit) = self.cursor_commit() else {
+            return Ok(());
+        };
+        let tmp = std::env::temp_dir();
+        let pid = std::process::id();
+        let taken: Vec<&PathBuf> = self.scratch_jobs.values().map(|(path, _)| path).collect();
+        let path = (1..)
+            .map(|n| tmp.join(format!("grit-{}-{pid}-{n}", commit.short)))
+            .find(|path| !path.exists() && !taken.contains(&path))
+            .unwrap_or_default();
+        let (repo, id) = (self.repo.clone(), commit.id);
+        let added = path.clone();
+        let name = t!("check out {commit}", commit = commit.short);
+        let job = self.jobs.submit(name, false, move |_| {
+            repo.add_detached_worktree(&added, id)?;
+            Ok(String::new())
+        });
+        self.scratch_jobs.insert(job, (path, commit.short.clone()));
+        Ok(())
+    }
+
+    /// Opens the worktree the job `finished` added at `path` for the commit
+    /// `short`, or takes away what a failed one left.
+    fn scratch_added(&mut self, finished: jobs::Finished, path: PathBuf, short: String) {
+        match finished.result {
+            Some(Ok(_)) => {}
+            Some(Err(err)) => {
+                self.notifications.report(&err);
+                return;
+            }
+            None => {
+                if path.exists() {
+                    let _ = self.repo.remove_worktree(&path);
+                }
+                self.notifications
+                    .info(t!("{job} cancelled", job = finished.name));
+                return;
+            }
+        }
+        self.scratch.push(path.clone());
+        let shown = path.display().to_string();
+        let opened = match in_tmux() {
+            true => tmux_window(&path),
+            false => clipboard::copy(&shown).map_err(Into::into),
+        };
+        if let Err(err) = opened {
+            self.notifications.report(&err);
+        }
+        self.notifications.success(t!(
+            "{commit} is checked out at {path} until grit exits",
+            commit = short,
+            path = shown
+        ));
+    }
+
+    /// Removes the worktrees made to look around at commits by grit processes
+    /// no longer running, which a crash kept from removing them. Those with
+    /// changes are kept, and told about.
+    fn prune_scratch(&mut self) {
+        let Ok(paths) = self.repo.worktree_paths() else {
+            return;
+        };
+        let tmp = std::env::temp_dir();
+        let tmp = tmp.canonicalize().unwrap_or(tmp);
+        for path in paths {
+            let Some(pid) = scratch_pid(&path, &tmp) else {
+                continue;
+            };
+            if pid == std::process::id() || platform::current().is_running(pid) {
+                continue;
+            }
+            if let Err(err) = self.repo.remove_worktree(&path) {
+                tracing::warn!("remove worktree {}: {err:#}", path.display());
+                self.notifications.info(t!(
+                    "kept {path}, a worktree left behind with changes in it",
+                    path = path.display()
+                ));
+            }
+        }
+    }
+
+    /// Removes the worktrees made to look around at commits, keeping those
+    /// with changes, which the next start tells about.
+    fn remove_scratch(&mut self) {
+        for path in self.scratch.drain(..) {
+            if let Err(err) = self.repo.remove_worktree(&path) {
+                tracing::warn!("keep worktree {}: {err:#}", path.display());
+            }
+        }
+    }
+
+    /// Follows the selected remote branch, or the upstream of the selected
+    /// local one, or stops following it.
+    fn toggle_follow(&mut self) {
+        let Some(branch) = self.branch_list.current() else {
+            return;
+        };
+        let followed = match (branch.typ, &branch.upstream) {
+            (git2::BranchType::Remote, _) => branch.name.clone(),
+            (git2::BranchType::Local, Some(upstream)) => upstream.clone(),
+            (git2::BranchType::Local, None) => {
+                let msg = t!(
+                    "{branch} has no upstream to follow",
+                    branch = redact::branch(&branch.name)
+                );
+                self.notifications.info(msg);
+                return;
+            }
+        };
+        let name = redact::branch(&followed).into_owned();
+        let refname = git::refname::full(&followed, git2::BranchType::Remote);
+        let tip = self.repo.ref_target(&refname).ok().flatten();
+        if self.follow.toggle(&followed, tip) {
+            self.follow_viewed = self.branch_list.current().map(git::Branch::refname);
+            let msg = match self.follow.fetches() {
+                true => t!("following {branch}", branch = name),
+                false => t!(
+                    "following {branch}, without fetching it in safe mode",
+                    branch = name
+                ),
+            };
+            self.notifications.info(msg);
+        } else {
+            self.notifications
+                .info(t!("stopped following {branch}", branch = name));
+        }
+    }
+
+    /// The remote and the branch on it each followed branch is fetched as.
+    fn follow_targets(&self) -> Vec<(String, String)> {
+        let remotes = self.remote_names();
+        let mut targets = vec![];
+        for name in self.follow.names() {
+            // the longest remote name that fits, as they may contain slashes
+            let target = remotes
+                .iter()
+                .filter_map(|remote| {
+                    let rest = name.strip_prefix(remote.as_str())?.strip_prefix('/')?;
+                    Some((remote.clone(), rest.to_string()))
+                })
+                .max_by_key(|(remote, _)| remote.len());
+            if let Some(target) = target.filter(|t| !targets.contains(t)) {
+                targets.push(target);
+            }
+        }
+        targets
+    }
+
+    /// Fetches the followed branches once it is time to, and reloads once a
+    /// fetch brought anything, returning whether a fetch finished.
+    fn poll_follow(&mut self) -> bool {
+        let done = match self.follow.poll() {
+            Some(Ok(())) => {
+                if !self.follow.is_failing() {
+                    self.reached();
+                    self.run_hook(hooks::Event::PostFetch, vec![hooks::Context::default()]);
+                }
+                let repo = &self.repo;
+                let moved = self
+                    .follow
+                    .has_moved(|refname| repo.ref_target(refname).ok().flatten());
+                if moved && self.loading.is_none() {
+                    if let Err(err) = self.load_branches() {
+                        self.notifications.report(&err);
+                    }
+                }
+                true
+            }
+            Some(Err(err)) => {
+                self.notifications.report(&err);
+                true
+            }
+            None => false,
+        };
+        if self.follow.is_due() {
+            let targets = self.follow_targets();
+            self.follow.fetch(self.repo.location(), targets);
+        }
+        done
+    }
+
+    /// Marks what arrived on the followed branches since they were last
+    /// loaded as new, and tells about it.
+    fn note_arrivals(&mut self) {
+        let repo = &self.repo;
+        let moved = self
+            .follow
+            .moved(|refname| repo.ref_target(refname).ok().flatten());
+        for (refname, old, new) in moved {
+            let ids = match self.repo.commits_since(old, new) {
+                Ok(ids) => ids,
+                Err(err) => {
+                    self.notifications.report(&err.into());
+                    continue;
+                }
+            };
+            let count = ids.len();
+            if let Some(name) = self.follow.arrived(&refname, ids) {
+                let msg = t!(
+                    "{count} new on {branch}",
+                    count = count,
+                    branch = redact::branch(name)
+                );
+                self.notifications.info(msg);
+            }
+        }
+    }
+
+    /// Goes to the worktree the selected branch is checked out in: a new tmux
+    /// window there inside tmux, or else quitting to print its path.
+    fn jump_to_worktree(&mut self) -> EResult<()> {
+        let Some(branch) = self.branch_list.current() else {
+            return Ok(());
+        };
+        let Some(path) = self.worktree_of(branch).cloned() else {
+            let name = redact::branch(&branch.name);
+            let msg = t!(
+                "{branch} is not checked out in another worktree",
+                branch = name
+            );
+            self.notifications.info(msg);
+            return Ok(());
+        };
+        if !in_tmux() {
+            self.exit_to = Some(path);
+            self.exit();
+            return Ok(());
+        }
+        tmux_window(&path)
+    }
+}
+
+// whether grit quits, fails or panics, the worktrees made to look around go
+impl Drop for App {
+    fn drop(&mut self) {
+        self.remove_scratch();
+    }
+}
+
+/// The process that made `path`, if it is a worktree grit made in `tmp` to
+/// look around at a commit: `grit-<commit>-<pid>-<n>`.
+fn scratch_pid(path: &Path, tmp: &Path) -> Option<u32> {
+    let parent = path.parent()?;
+    let parent = parent.canonicalize().unwrap_or(parent.to_path_buf());
+    if parent != tmp {
+        return None;
+    }
+    let name = path.file_name()?.to_str()?.strip_prefix("grit-")?;
+    let mut parts = name.rsplitn(3, '-');
+    let (_, pid) = (parts.next()?, parts.next()?);
+    parts.next()?;
+    pid.parse().ok()
+}
+
+fn in_tmux() -> bool {
+    std::env::var_os("TMUX").is_some_and(|tmux| !tmux.is_empty())
+}
+
+/// Opens a new tmux window in `dir`.
+fn tmux_window(dir: &Path) -> EResult<()> {
+    let status = std::process::Command::new("tmux")
+        .args(["new-window", "-c"])
+        .arg(dir)
+        .status()
+        .wrap_err("run tmux")?;
+    if !status.success() {
+        color_eyre::eyre::bail!("tmux new-window exited with {status}");
+    }
+    Ok(())
+}
+
+/// The result of a merge or rebase job, which fails if it would have
+/// conflicted after all, e.g. because a branch moved since it was confirmed.
+/// The message of a commit: `message`, or what `commit-msg` left in `file`
+/// if the hooks ran.
+fn read_message(file: Option<PathBuf>, message: String) -> EResult<String> {
+    match file {
+        Some(file) => std::fs::read_to_string(file).wrap_err("read the commit message back"),
+        None => Ok(message),
+    }
+}
+
+fn integrated(plan: git::Plan) -> EResult<String> {
+    match plan {
+        git::Plan::Conflicts(paths) => Err(eyre!(t!(
+            "conflicts in {paths}, nothing was changed",
+            paths = paths.join(", ")
+        ))),
+        _ => Ok(String::new()),
+    }
+}
+
+fn heat_weeks(config: &Config) -> usize {
+    config.view.heat_weeks.unwrap_or(12).clamp(1, 52)
 }
@@ -0,0 +1,83 @@
+//! Stable per-author colors and a legend for them.
+
+use crate::{git, prelude::*, redact, t};
+use ratatui::{
+    style::palette::tailwind::{CYAN, EMERALD, FUCHSIA, LIME, ORANGE, PINK, SKY, VIOLET, YELLOW},
+    text::Span,
+    widgets::Clear,
+};
+
+const COLORS: [Color; 9] = [
+    SKY.c400,
+    EMERALD.c400,
+    ORANGE.c400,
+    FUCHSIA.c400,
+    YELLOW.c300,
+    CYAN.c300,
+    PINK.c400,
+    LIME.c400,
+    VIOLET.c400,
+];
+
+/// A color derived from a hash of the author's email, so that an author keeps
+/// the same color across branches and sessions.
+pub fn color(author: &git::Author) -> Color {
+    let key = author
+        .email
+        .as_deref()
+        .or(author.name.as_deref())
+        .unwrap_or_default()
+        .to_lowercase();
+    // FNV-1a, since std's hasher is not stable across releases
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    });
+    COLORS[(hash % COLORS.len() as u64) as usize]
+}
+
+/// A popup mapping colors to the authors of the given commits.
+pub struct Legend<'a>(pub &'a [git::Commit]);
+
+impl Widget for Legend<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut authors: Vec<&git::Author> = vec![];
+        for commit in self.0 {
+            if !authors.iter().any(|a| a.email == commit.author.email) {
+                authors.push(&commit.author);
+            }
+        }
+        let lines: Vec<Line> = if authors.is_empty() {
+            vec![Line::raw(t!("no commits loaded"))]
+        } else {
+            authors
+                .iter()
+                .map(|a| {
+                    let name = redact::person(a.name.as_deref().unwrap_or("?"));
+                    let email = redact::email(a.email.as_deref().unwrap_or_default());
+                    Line::from(vec![
+                        Span::styled("██ ", color(a)),
+                        Span::raw(format!("{name} <{email}>")),
+                    ])
+                })
+                .collect()
+        };
+        let width = lines.iter().map(Line::width).max().unwrap_or_default() as u16 + 4;
+        let width = width.max(20).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c900))
+            .block(
+                Block::new()
+                    .title(Line::raw(t!("Authors")).left_aligned())
+                    .borders(Borders::ALL),
+            )
+            .render(popup, buf);
+    }
+}
@@ -1,10 +1,10 @@
+use super::query::{Facts, Query};
 use crate::git;
-use crate::prelude::*;
+use crate::{config::BranchKind, prelude::*, redact, t, theme};
 use git2::BranchType;
-use ratatui::widgets::{ListItem, ListState};
-
-const LOCAL_BRANCH_COLOR: Color = SLATE.c200;
-const REMOTE_BRANCH_COLOR: Color = RED.c200;
+use ratatui::widgets::ListState;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Default)]
 pub struct List {
@@ -12,15 +12,88 @@ pub struct List {
     pub state: ListState,
     pub sort: Sort,
     pub filter: Filter,
+    /// How many branches the repository has, regardless of the filter.
+    pub total: usize,
+    /// A selection to restore once its branch shows up while the list is loading.
+    pub sticky: Option<Selection>,
+    /// Branches marked for bulk actions.
+    pub marked: Vec<(String, BranchType)>,
+    /// Buckets branches by their upstream status, under a header per group.
+    pub grouped: bool,
+    /// Narrows the list to the branches matching it, as [`Query`] parses it.
+    pub query: String,
+    /// The query as last parsed without error.
+    pub parsed: Query,
+    /// Why the query does not parse, if it does not.
+    pub query_error: Option<String>,
+    /// The configured user name, once a query asked for `author:me`.
+    pub me: Option<String>,
+    /// The merged local branches, once a query asked.
+    pub merged: HashSet<String>,
+    /// The loaded branches the query hides, put back when it changes.
+    pub unmatched: Vec<git::Branch>,
+    /// Lists stacked branches under the ones they are built on, instead of
+    /// grouping.
+    pub stacked: bool,
+    /// The links of the stacked local branches, by name.
+    pub stacks: HashMap<String, git::Link>,
+    /// How deep each branch is in its stack, by index, while stacked.
+    pub depths: Vec<usize>,
+    /// What tells the stale branches apart.
+    pub stale: git::Stale,
+    /// Narrows the list to the stale branches.
+    pub stale_only: bool,
+    /// How many branches were left unread, past `branch.load_limit`.
+    pub unloaded: usize,
+}
+
+/// The sections of a grouped list, in the order they are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Group {
+    UpToDate,
+    Ahead,
+    Behind,
+    Diverged,
+    NoUpstream,
+    Remote,
+}
+
+/// A row of the list widget: a group header with its branch count, or a branch
+/// by index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Row {
+    Header(Group, usize),
+    Branch(usize),
+}
+
+/// Counts shown alongside the list.
+pub struct Stats {
+    /// The 1-based position of the cursor.
+    pub position: Option<usize>,
+    pub shown: usize,
+    pub marked: usize,
+    /// Branches excluded by the branch type filter or the query.
+    pub hidden: usize,
 }
 
-#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Deserialize)]
+#[serde(try_from = "String")]
 pub enum Sort {
     NameAscending,
     NameDescending,
     DateAscending,
     #[default]
     DateDescending,
+    /// Farthest from the upstream first, counting commits on either side.
+    Divergence,
+}
+
+/// Identifies the selected branch so it can be found again after the list is rebuilt.
+#[derive(Clone, Debug)]
+pub struct Selection {
+    pub name: String,
+    pub typ: BranchType,
+    pub index: usize,
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -28,7 +101,9 @@ pub struct Filter(Option<git2::BranchType>);
 
 impl List {
     pub fn current(&self) -> Option<&git::Branch> {
-        self.state.selected().and_then(|i| self.items.get(i))
+        let idx = self.state.selected()?;
+        // select_last() selects usize::MAX until the next render clamps it.
+        self.items.get(idx.min(self.items.len().checked_sub(1)?))
     }
     pub fn build(branches: Vec<git::Branch>, filter: Filter) -> Self {
         let sort = Sort::default();
@@ -39,42 +114,511 @@ impl List {
             state,
             sort,
             filter,
+            total: 0,
+            sticky: None,
+            marked: vec![],
+            grouped: false,
+            query: String::new(),
+            parsed: Query::default(),
+            query_error: None,
+            me: None,
+            merged: HashSet::new(),
+            unmatched: vec![],
+            stacked: false,
+            stacks: HashMap::new(),
+            depths: vec![],
+            stale: git::Stale::default(),
+            stale_only: false,
+            unloaded: 0,
         };
         list.sort();
         list.state.select_first();
         list
     }
 
+    pub fn stats(&self) -> Stats {
+        let shown = self.items.len();
+        let position = self
+            .state
+            .selected()
+            .filter(|_| shown > 0)
+            .map(|i| i.min(shown - 1) + 1);
+        Stats {
+            position,
+            shown,
+            marked: self.marked.len(),
+            hidden: self.total.saturating_sub(shown + self.unloaded),
+        }
+    }
+
+    pub fn selection(&self) -> Option<Selection> {
+        let index = self.state.selected()?;
+        let index = index.min(self.items.len().checked_sub(1)?);
+        let branch = &self.items[index];
+        Some(Selection {
+            name: branch.name.clone(),
+            typ: branch.typ,
+            index,
+        })
+    }
+
+    /// Every loaded branch, including those the query hides.
+    pub fn all(&self) -> impl Iterator<Item = &git::Branch> {
+        self.items.iter().chain(&self.unmatched)
+    }
+
+    /// Whether a local branch is stale, and why.
+    pub fn staleness(&self, branch: &git::Branch) -> Option<git::Staleness> {
+        branch.staleness(&self.stale)
+    }
+
+    /// Shows only the stale branches, or every branch again.
+    pub fn set_stale_only(&mut self, stale_only: bool) {
+        self.stale_only = stale_only;
+        self.set_query(self.query.clone());
+    }
+
+    fn matches(&self, branch: &git::Branch) -> bool {
+        if self.stale_only && self.staleness(branch).is_none() {
+            return false;
+        }
+        let facts = Facts {
+            me: self.me.as_deref(),
+            merged: &self.merged,
+            now: chrono::Utc::now().timestamp(),
+        };
+        self.parsed.is_empty() || self.parsed.matches(branch, &facts)
+    }
+
+    /// Narrows the list to the branches matching `query`, keeping the selected
+    /// branch selected if it still matches. A query that does not parse keeps
+    /// the list as the last one that did.
+    pub fn set_query(&mut self, query: String) {
+        let sel = self.selection();
+        match Query::parse(&query) {
+            Ok(parsed) => {
+                self.query_error = (parsed.needs_me() && self.me.is_none())
+                    .then(|| t!("author:me and owner:me need user.name to be set").to_string());
+                self.parsed = parsed;
+            }
+            Err(err) => self.query_error = Some(err),
+        }
+        self.query = query;
+        let mut all = std::mem::take(&mut self.items);
+        all.append(&mut self.unmatched);
+        (self.items, self.unmatched) = all.into_iter().partition(|b| self.matches(b));
+        self.order();
+        self.restore(sel);
+    }
+
+    pub fn is_marked(&self, branch: &git::Branch) -> bool {
+        self.marked
+            .iter()
+            .any(|(name, typ)| *name == branch.name && *typ == branch.typ)
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let Some(branch) = self.current() else {
+            return;
+        };
+        let key = (branch.name.clone(), branch.typ);
+        match self.marked.iter().position(|k| *k == key) {
+            Some(pos) => {
+                self.marked.remove(pos);
+            }
+            None => self.marked.push(key),
+        }
+    }
+
+    /// The marked branches, or the selected one if nothing is marked.
+    pub fn targets(&self) -> Vec<&git::Branch> {
+        if self.marked.is_empty() {
+            return self.current().into_iter().collect();
+        }
+        self.items.iter().filter(|b| self.is_marked(b)).collect()
+    }
+
+    fn position(&self, sel: &Selection) -> Option<usize> {
+        self.items
+            .iter()
+            .position(|b| b.name == sel.name && b.typ == sel.typ)
+    }
+
+    /// Selects the branch identified by `sel`, falling back to the nearest index if
+    /// it no longer exists.
+    pub fn restore(&mut self, sel: Option<Selection>) {
+        let idx = match &sel {
+            Some(sel) => self
+                .position(sel)
+                .unwrap_or(sel.index.min(self.items.len().saturating_sub(1))),
+            None => 0,
+        };
+        self.state.select((!self.items.is_empty()).then_some(idx));
+    }
+
+    /// Adds branches to the list, keeping the selected branch selected. If a sticky
+    /// selection is waiting for one of the new branches, that branch is selected.
+    pub fn extend(&mut self, branches: impl IntoIterator<Item = git::Branch>) {
+        let mut sel = self.selection();
+        for branch in branches {
+            match self.matches(&branch) {
+                true => self.items.push(branch),
+                false => self.unmatched.push(branch),
+            }
+        }
+        self.sort();
+        if let Some(sticky) = self.sticky.take() {
+            if self.position(&sticky).is_some() {
+                sel = Some(sticky);
+            } else {
+                self.sticky = Some(sticky);
+            }
+        }
+        self.restore(sel);
+    }
+
+    /// Called once loading has finished. A sticky selection whose branch never
+    /// showed up falls back to the nearest index.
+    pub fn settle(&mut self) {
+        if let Some(sticky) = self.sticky.take() {
+            self.restore(Some(sticky));
+        }
+    }
+
+    /// The rows to show, with group headers when grouped.
+    pub fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![];
+        let mut current = None;
+        for (idx, branch) in self.items.iter().enumerate() {
+            let group = Group::of(branch);
+            if self.grouped && !self.stacked && current != Some(group) {
+                let count = self.items[idx..]
+                    .iter()
+                    .take_while(|b| Group::of(b) == group)
+                    .count();
+                rows.push(Row::Header(group, count));
+                current = Some(group);
+            }
+            rows.push(Row::Branch(idx));
+        }
+        rows
+    }
+
+    /// The next branch after the selected one whose name starts with `letter`,
+    /// ignoring case and the remote of remote branches, wrapping around.
+    pub fn next_starting_with(&self, letter: char) -> Option<usize> {
+        let start = self.state.selected().map_or(0, |i| i + 1);
+        let len = self.items.len();
+        (0..len).map(|i| (start + i) % len).find(|&i| {
+            let branch = &self.items[i];
+            let name = match branch.typ {
+                BranchType::Remote => branch
+                    .name
+                    .split_once('/')
+                    .map_or(&*branch.name, |(_, n)| n),
+                BranchType::Local => &branch.name,
+            };
+            name.chars()
+                .next()
+                .is_some_and(|c| c.to_lowercase().eq(letter.to_lowercase()))
+        })
+    }
+
+    /// Re-sorts the list, keeping the selected branch selected.
     pub fn sort(&mut self) {
-        match self.sort {
-            Sort::NameAscending => self.items.sort_by(|b1, b2| b1.name.cmp(&b2.name)),
-            Sort::NameDescending => self.items.sort_by(|b1, b2| b2.name.cmp(&b1.name)),
-            Sort::DateAscending => self.items.sort_by(|b1, b2| {
+        let sel = self.selection();
+        self.order();
+        if sel.is_some() {
+            self.restore(sel);
+        }
+    }
+
+    fn order(&mut self) {
+        self.sort.apply(&mut self.items);
+        self.depths.clear();
+        if self.stacked {
+            self.stack();
+        } else if self.grouped {
+            // stable, so the sort still applies within each group
+            self.items.sort_by_key(Group::of);
+        }
+    }
+
+    /// Moves stacked branches right under the ones they are built on, in the
+    /// order of the sort among siblings.
+    fn stack(&mut self) {
+        let len = self.items.len();
+        let mut children = vec![vec![]; len];
+        let mut roots = vec![];
+        for (idx, branch) in self.items.iter().enumerate() {
+            let parent = self.link(branch).and_then(|link| {
+                self.items
+                    .iter()
+                    .position(|p| p.typ == BranchType::Local && p.name == link.parent)
+            });
+            match parent {
+                Some(parent) => children[parent].push(idx),
+                None => roots.push(idx),
+            }
+        }
+        let mut order = Vec::with_capacity(len);
+        let mut pending: Vec<(usize, usize)> = roots.into_iter().rev().map(|i| (i, 0)).collect();
+        while let Some((idx, depth)) = pending.pop() {
+            order.push(idx);
+            self.depths.push(depth);
+            pending.extend(children[idx].iter().rev().map(|&i| (i, depth + 1)));
+        }
+        let mut slots: Vec<Option<git::Branch>> = std::mem::take(&mut self.items)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.items = order.into_iter().filter_map(|i| slots[i].take()).collect();
+    }
+
+    /// Where a local branch sits in its stack, if it is stacked.
+    pub fn link(&self, branch: &git::Branch) -> Option<&git::Link> {
+        self.stacks
+            .get(&branch.name)
+            .filter(|_| branch.typ == BranchType::Local)
+    }
+
+    /// How deep the branch at `idx` is in its stack, 0 at the bottom.
+    pub fn depth(&self, idx: usize) -> usize {
+        self.depths.get(idx).copied().unwrap_or_default()
+    }
+
+    /// The branches stacked on `name`, and on those in turn, each after the
+    /// one it is built on, with that one and the tip it had when it was.
+    pub fn stacked_on(&self, name: &str) -> Vec<(String, String, git2::Oid)> {
+        let mut found = vec![];
+        let mut parents = vec![name.to_string()];
+        while let Some(parent) = parents.pop() {
+            let mut children: Vec<(&String, git2::Oid)> = self
+                .stacks
+                .iter()
+                .filter(|(_, link)| link.parent == parent)
+                .map(|(child, link)| (child, link.base))
+                .collect();
+            children.sort();
+            for (child, base) in children {
+                // a stack never loops, but this must not hang if it did
+                if child == name || found.iter().any(|(_, c, _)| c == child) {
+                    continue;
+                }
+                found.push((parent.clone(), child.clone(), base));
+                parents.push(child.clone());
+            }
+        }
+        found
+    }
+}
+
+impl Group {
+    pub fn of(branch: &git::Branch) -> Group {
+        match branch.tracking {
+            _ if branch.typ == BranchType::Remote => Group::Remote,
+            Some(git::Tracking::UpToDate) => Group::UpToDate,
+            Some(git::Tracking::Ahead(_)) => Group::Ahead,
+            Some(git::Tracking::Behind(_)) => Group::Behind,
+            Some(git::Tracking::Diverged { .. }) => Group::Diverged,
+            Some(git::Tracking::NoUpstream) | None => Group::NoUpstream,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Group::UpToDate => t!("tracking & up to date"),
+            Group::Ahead => t!("ahead"),
+            Group::Behind => t!("behind"),
+            Group::Diverged => t!("diverged"),
+            Group::NoUpstream => t!("no upstream"),
+            Group::Remote => t!("remote"),
+        }
+    }
+}
+
+impl TryFrom<String> for Sort {
+    type Error = String;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s).ok_or_else(|| {
+            format!("unknown sort {s:?}, expected name, name-desc, date-asc, date or divergence")
+        })
+    }
+}
+
+impl Sort {
+    pub const ALL: [Sort; 5] = [
+        Sort::NameAscending,
+        Sort::NameDescending,
+        Sort::DateAscending,
+        Sort::DateDescending,
+        Sort::Divergence,
+    ];
+
+    /// Parses the names used in the config and `--sort`: `name`, `name-desc`,
+    /// `date-asc`, `date` or `divergence`. The bare names mean the natural
+    /// order of each.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "name" | "name-asc" => Some(Sort::NameAscending),
+            "name-desc" => Some(Sort::NameDescending),
+            "date-asc" => Some(Sort::DateAscending),
+            "date" | "date-desc" => Some(Sort::DateDescending),
+            "divergence" => Some(Sort::Divergence),
+            _ => None,
+        }
+    }
+
+    /// The name [`Sort::parse`] reads back.
+    pub fn key(self) -> &'static str {
+        match self {
+            Sort::NameAscending => "name",
+            Sort::NameDescending => "name-desc",
+            Sort::DateAscending => "date-asc",
+            Sort::DateDescending => "date",
+            Sort::Divergence => "divergence",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Sort::NameAscending => t!("name asc"),
+            Sort::NameDescending => t!("name desc"),
+            Sort::DateAscending => t!("date asc"),
+            Sort::DateDescending => t!("date desc"),
+            Sort::Divergence => t!("divergence"),
+        }
+    }
+
+    pub fn apply(self, items: &mut [git::Branch]) {
+        match self {
+            Sort::NameAscending => items.sort_by(|b1, b2| b1.name.cmp(&b2.name)),
+            Sort::NameDescending => items.sort_by(|b1, b2| b2.name.cmp(&b1.name)),
+            Sort::DateAscending => items.sort_by(|b1, b2| {
                 let b1 = b1.commits.first().as_ref().map(|c| c.timestamp.epoch());
                 let b2 = b2.commits.first().as_ref().map(|c| c.timestamp.epoch());
                 b1.cmp(&b2)
             }),
-            Sort::DateDescending => self.items.sort_by(|b1, b2| {
+            Sort::DateDescending => items.sort_by(|b1, b2| {
                 let i1 = b1.commits.first().as_ref().map(|c| c.timestamp.epoch());
                 let i2 = b2.commits.first().as_ref().map(|c| c.timestamp.epoch());
                 i2.cmp(&i1)
             }),
+            Sort::Divergence => {
+                // the newest first among branches as far from their upstreams
+                Sort::DateDescending.apply(items);
+                items.sort_by_key(|b| {
+                    let (ahead, behind) = divergence(b);
+                    std::cmp::Reverse((ahead + behind, behind))
+                });
+            }
         };
     }
 }
 
-impl From<&git::Branch> for ListItem<'_> {
-    fn from(value: &git::Branch) -> Self {
-        let name = value.name.to_string();
-        let line = match value.typ {
-            BranchType::Local => {
-                Line::styled(name, LOCAL_BRANCH_COLOR).add_modifier(Modifier::BOLD)
-            }
-            BranchType::Remote => {
-                Line::styled(name, REMOTE_BRANCH_COLOR).add_modifier(Modifier::DIM)
-            }
-        };
-        ListItem::new(line)
+/// How many commits a local branch is ahead and behind its upstream, zero for
+/// either if it has none.
+fn divergence(branch: &git::Branch) -> (usize, usize) {
+    match branch.tracking {
+        Some(git::Tracking::Ahead(n)) => (n, 0),
+        Some(git::Tracking::Behind(n)) => (0, n),
+        Some(git::Tracking::Diverged { ahead, behind }) => (ahead, behind),
+        _ => (0, 0),
+    }
+}
+
+const HEAT_LEVELS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// A sparkline of the number of loaded commits per week, oldest week first,
+/// ending with the week up to `now`.
+pub fn heat(branch: &git::Branch, weeks: usize, now: i64) -> String {
+    const WEEK: i64 = 7 * 24 * 60 * 60;
+    let mut counts = vec![0usize; weeks];
+    for commit in branch.commits() {
+        let age = (now - commit.timestamp.epoch()).max(0) / WEEK;
+        if let Some(count) = usize::try_from(age)
+            .ok()
+            .and_then(|age| counts.get_mut(age))
+        {
+            *count += 1;
+        }
+    }
+    let max = counts.iter().copied().max().unwrap_or_default().max(1);
+    counts
+        .iter()
+        .rev()
+        .map(|&n| {
+            // any activity at all shows at least the lowest bar
+            let level = (n * (HEAT_LEVELS.len() - 1)).div_ceil(max);
+            HEAT_LEVELS[level]
+        })
+        .collect()
+}
+
+/// How far a local branch is ahead and behind its upstream, e.g. `↑2 ↓1`.
+pub fn tracking(branch: &git::Branch) -> Option<String> {
+    match branch.tracking? {
+        git::Tracking::Ahead(n) => Some(format!("↑{n}")),
+        git::Tracking::Behind(n) => Some(format!("↓{n}")),
+        git::Tracking::Diverged { ahead, behind } => Some(format!("↑{ahead} ↓{behind}")),
+        git::Tracking::UpToDate | git::Tracking::NoUpstream => None,
+    }
+}
+
+/// Prefixes that say what a branch is for rather than whose it is.
+const KIND_PREFIXES: &[&str] = &[
+    "feature", "feat", "fix", "bugfix", "hotfix", "release", "chore", "docs", "refactor", "test",
+];
+
+/// Whose a branch is, as far as can be told.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Owner<'a> {
+    /// The first part of the name, under the `user/topic` convention.
+    Prefix(&'a str),
+    /// The author of the tip, for a name without such a part.
+    Author(&'a str),
+}
+
+impl Owner<'_> {
+    pub fn name(&self) -> &str {
+        match self {
+            Owner::Prefix(name) | Owner::Author(name) => name,
+        }
+    }
+}
+
+/// Whose a branch is: the first part of its name, leaving out the remote,
+/// unless it names a kind of change such as `fix/`, else the author of its
+/// tip. This is mostly of use for remote branches that others push to.
+pub fn owner(branch: &git::Branch) -> Option<Owner<'_>> {
+    let name = match branch.typ {
+        BranchType::Local => branch.name.as_str(),
+        BranchType::Remote => branch.name.split_once('/').map_or("", |(_, n)| n),
+    };
+    let prefix = name
+        .split_once('/')
+        .map(|(prefix, _)| prefix)
+        .filter(|p| !p.is_empty() && !KIND_PREFIXES.contains(&p.to_lowercase().as_str()));
+    match prefix {
+        Some(prefix) => Some(Owner::Prefix(prefix)),
+        None => branch
+            .commits()
+            .first()
+            .and_then(|c| c.author.name.as_deref())
+            .map(Owner::Author),
+    }
+}
+
+/// The list row for a branch.
+pub fn line(value: &git::Branch) -> Line<'static> {
+    let name = redact::branch(&value.name).into_owned();
+    match value.typ {
+        BranchType::Local => {
+            Line::styled(name, theme::get().local_branch).add_modifier(Modifier::BOLD)
+        }
+        BranchType::Remote => {
+            Line::styled(name, theme::get().remote_branch).add_modifier(Modifier::DIM)
+        }
     }
 }
 
@@ -84,9 +628,19 @@ impl Default for Filter {
     }
 }
 
+impl From<BranchKind> for Filter {
+    fn from(kind: BranchKind) -> Self {
+        Self(match kind {
+            BranchKind::All => None,
+            BranchKind::Local => Some(BranchType::Local),
+            BranchKind::Remote => Some(BranchType::Remote),
+        })
+    }
+}
+
 impl Filter {
     pub fn typ(&self) -> Option<BranchType> {
-        self.0.clone()
+        self.0
     }
 
     #[allow(unused)]
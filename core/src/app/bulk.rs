@@ -0,0 +1,179 @@
+//! What a bulk operation spanning several remotes did on each of them, such as
+//! fetching every remote or pushing the marked branches: a popup grouping what
+//! succeeded and what failed, and why, by remote, rather than a single toast.
+
+use super::notify::{Level, Notifications};
+use crate::{prelude::*, redact, t};
+use ratatui::{
+    text::Span,
+    widgets::{Clear, Wrap},
+};
+use std::sync::{Arc, Mutex};
+
+/// One thing done on a remote, such as pushing a branch to it.
+pub struct Item {
+    pub label: String,
+    pub error: Option<String>,
+}
+
+pub struct Group {
+    pub remote: String,
+    pub items: Vec<Item>,
+}
+
+impl Group {
+    fn failed(&self) -> usize {
+        self.items.iter().filter(|i| i.error.is_some()).count()
+    }
+}
+
+/// What a job filled in as it went, remote by remote.
+pub type Groups = Arc<Mutex<Vec<Group>>>;
+
+/// Records `item` under `remote`, after what was done there before.
+pub fn add(groups: &Groups, remote: &str, item: Item) {
+    let Ok(mut groups) = groups.lock() else {
+        return;
+    };
+    match groups.iter_mut().find(|g| g.remote == remote) {
+        Some(group) => group.items.push(item),
+        None => groups.push(Group {
+            remote: remote.to_string(),
+            items: vec![item],
+        }),
+    }
+}
+
+pub struct Report {
+    /// The operation, e.g. `fetch all remotes`.
+    pub title: String,
+    pub groups: Vec<Group>,
+    pub scroll: u16,
+}
+
+impl Report {
+    /// The report of the job that filled `groups`, once it finished.
+    pub fn new(title: String, groups: &Groups) -> Self {
+        let groups = groups
+            .lock()
+            .map(|mut groups| std::mem::take(&mut *groups))
+            .unwrap_or_default();
+        Self {
+            title,
+            groups,
+            scroll: 0,
+        }
+    }
+
+    /// Whether it has more to tell than a toast: outcomes on several remotes,
+    /// or anything that failed.
+    pub fn is_detailed(&self) -> bool {
+        self.groups.len() > 1 || self.counts().1 > 0
+    }
+
+    /// Whether anything in it worked.
+    pub fn any_succeeded(&self) -> bool {
+        self.counts().0 > 0
+    }
+
+    fn counts(&self) -> (usize, usize) {
+        let all: usize = self.groups.iter().map(|g| g.items.len()).sum();
+        let failed: usize = self.groups.iter().map(Group::failed).sum();
+        (all - failed, failed)
+    }
+
+    /// The report in a line, for the title of the popup.
+    pub fn summary(&self) -> String {
+        let (succeeded, failed) = self.counts();
+        t!(
+            "{what}: {succeeded} succeeded, {failed} failed",
+            what = self.title,
+            succeeded = succeeded,
+            failed = failed
+        )
+    }
+
+    /// Adds every outcome to the history of `notifications`, without showing
+    /// them, and to the log file.
+    pub fn export(&self, notifications: &mut Notifications) {
+        for group in &self.groups {
+            for item in &group.items {
+                let remote = &group.remote;
+                match &item.error {
+                    Some(err) => {
+                        tracing::warn!(remote, "{}: {}: {err}", self.title, item.label);
+                        let msg = format!("{remote}: {}: {err}", item.label);
+                        notifications.record(Level::Error, msg);
+                    }
+                    None => {
+                        tracing::info!(remote, "{}: {}", self.title, item.label);
+                        let msg = format!("{remote}: {}", item.label);
+                        notifications.record(Level::Success, msg);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct ReportView<'a>(pub &'a Report);
+
+impl Widget for ReportView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let ok = Style::new().fg(GREEN.c300);
+        let err = Style::new().fg(RED.c300);
+        let dim = Style::new().fg(SLATE.c400);
+        let mut lines = vec![];
+        for group in &self.0.groups {
+            let failed = group.failed();
+            let mut header = vec![Span::styled(
+                redact::text(&group.remote).into_owned(),
+                Style::new().bold(),
+            )];
+            header.push(Span::styled(
+                format!("  ✓ {}", group.items.len() - failed),
+                ok,
+            ));
+            if failed > 0 {
+                header.push(Span::styled(format!("  ✗ {failed}"), err));
+            }
+            lines.push(Line::from(header));
+            for item in &group.items {
+                let label = redact::text(&item.label).into_owned();
+                let Some(reason) = &item.error else {
+                    lines.push(Line::from(vec![Span::styled("  ✓ ", ok), Span::raw(label)]));
+                    continue;
+                };
+                lines.push(Line::from(vec![
+                    Span::styled("  ✗ ", err),
+                    Span::raw(label),
+                ]));
+                // git explains over several lines
+                for line in redact::text(reason)
+                    .lines()
+                    .filter(|l| !l.trim().is_empty())
+                {
+                    lines.push(Line::styled(format!("    {line}"), dim));
+                }
+            }
+            lines.push(Line::default());
+        }
+        let block = Block::new()
+            .title(Line::raw(self.0.summary()).left_aligned())
+            .title(Line::raw(t!("e: export to notifications and log  esc: close")).right_aligned())
+            .borders(Borders::ALL)
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c800));
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((self.0.scroll, 0))
+            .render(popup, buf);
+    }
+}
@@ -0,0 +1,154 @@
+//! Where local branches stand in review, as kept by hand to triage many of
+//! them: todo, ready or blocked, and a note. Kept in `.git/grit/branches.json`,
+//! which the work trees share and which is never pushed.
+
+use crate::{prelude::*, redact, t};
+use color_eyre::eyre::WrapErr;
+use ratatui::text::Span;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum State {
+    Todo,
+    Ready,
+    Blocked,
+}
+
+impl State {
+    pub const ALL: [State; 3] = [State::Todo, State::Ready, State::Blocked];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            State::Todo => t!("todo"),
+            State::Ready => t!("ready"),
+            State::Blocked => t!("blocked"),
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            State::Todo => SLATE.c300,
+            State::Ready => GREEN.c300,
+            State::Blocked => RED.c300,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Meta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<State>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl Meta {
+    fn is_empty(&self) -> bool {
+        self.state.is_none() && self.note.is_none()
+    }
+}
+
+/// The state of a branch in a word, then `✎` if it has a note.
+pub fn badge(meta: &Meta) -> Line<'static> {
+    let mut spans = vec![];
+    if let Some(state) = meta.state {
+        spans.push(Span::styled(state.label(), Style::new().fg(state.color())));
+    }
+    if meta.note.is_some() {
+        let gap = if spans.is_empty() { "" } else { " " };
+        spans.push(Span::styled(format!("{gap}✎"), Style::new().fg(SLATE.c400)));
+    }
+    Line::from(spans)
+}
+
+/// The state and the whole note of a branch, for the details pane.
+pub fn line(meta: &Meta) -> Line<'static> {
+    let mut line = badge(meta);
+    if let Some(note) = &meta.note {
+        // the pencil goes, the note itself says there is one
+        line.spans.pop();
+        if !line.spans.is_empty() {
+            line.spans.push(Span::raw("  "));
+        }
+        let note = redact::text(note).into_owned();
+        line.spans
+            .push(Span::styled(note, Style::new().fg(SLATE.c300)));
+    }
+    line
+}
+
+/// The state and note of each local branch that has either.
+pub struct Checklist {
+    branches: BTreeMap<String, Meta>,
+    path: PathBuf,
+}
+
+impl Checklist {
+    /// The checklist of the repository whose main git dir is `common_dir`. One
+    /// that cannot be read starts out empty.
+    pub fn load(common_dir: &Path) -> Self {
+        let path = common_dir.join("grit").join("branches.json");
+        let branches = match std::fs::read(&path) {
+            Ok(json) => serde_json::from_slice(&json).unwrap_or_else(|err| {
+                tracing::warn!("read {}: {err}", path.display());
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        Self { branches, path }
+    }
+
+    pub fn get(&self, branch: &str) -> Option<&Meta> {
+        self.branches.get(branch)
+    }
+
+    pub fn set_state(&mut self, branch: &str, state: Option<State>) -> EResult<()> {
+        self.update(branch, |meta| meta.state = state)
+    }
+
+    pub fn set_note(&mut self, branch: &str, note: Option<String>) -> EResult<()> {
+        self.update(branch, |meta| meta.note = note)
+    }
+
+    /// Moves what `old` had to `new`, once the branch is renamed.
+    pub fn rename(&mut self, old: &str, new: &str) -> EResult<()> {
+        let Some(meta) = self.branches.remove(old) else {
+            return Ok(());
+        };
+        self.branches.insert(new.to_string(), meta);
+        self.save()
+    }
+
+    /// Drops what the branches `names` had, once they are deleted.
+    pub fn forget(&mut self, names: &[&str]) -> EResult<()> {
+        let before = self.branches.len();
+        self.branches
+            .retain(|name, _| !names.contains(&name.as_str()));
+        match self.branches.len() == before {
+            true => Ok(()),
+            false => self.save(),
+        }
+    }
+
+    fn update(&mut self, branch: &str, change: impl FnOnce(&mut Meta)) -> EResult<()> {
+        let meta = self.branches.entry(branch.to_string()).or_default();
+        change(meta);
+        if meta.is_empty() {
+            self.branches.remove(branch);
+        }
+        self.save()
+    }
+
+    fn save(&self) -> EResult<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).wrap_err_with(|| format!("create {}", dir.display()))?;
+        }
+        let json = serde_json::to_vec_pretty(&self.branches)?;
+        std::fs::write(&self.path, json).wrap_err_with(|| format!("write {}", self.path.display()))
+    }
+}
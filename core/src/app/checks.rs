@@ -0,0 +1,211 @@
+//! How the CI checks of single commits went, fetched in the background for the
+//! commits in view in the log, so that the one that broke a branch stands out.
+//!
+//! What was fetched is kept per repository in the cache directory. A finished
+//! outcome does not change, so only commits whose checks were still running,
+//! or had none yet, are looked up again once the entry expires.
+
+use crate::{
+    forge::{self, limit, Checks, Forge},
+    paths,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc},
+};
+
+/// Entries older than this are dropped from the cache file.
+const MAX_AGE: i64 = 30 * 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    checks: Option<Checks>,
+    /// Unix seconds.
+    fetched: i64,
+}
+
+/// A finished lookup: the outcome, or on failure until when the forge is rate
+/// limited, if that is why.
+type Done = (git2::Oid, Result<Option<Checks>, Option<DateTime<Local>>>);
+
+/// Check outcomes by commit.
+pub struct CommitChecks {
+    entries: HashMap<git2::Oid, Entry>,
+    /// Commits looked up or skipped this session.
+    done: HashSet<git2::Oid>,
+    pending: HashSet<git2::Oid>,
+    /// Until when the forge is rate limited, once a lookup ran into it.
+    limited: Option<DateTime<Local>>,
+    path: Option<PathBuf>,
+    /// How long an entry without a finished outcome is trusted, in seconds.
+    ttl: i64,
+    tx: mpsc::Sender<Done>,
+    rx: mpsc::Receiver<Done>,
+}
+
+impl CommitChecks {
+    /// Checks cached for the repository at `git_dir`, if there are any.
+    pub fn load(git_dir: &Path, ttl_minutes: u64) -> Self {
+        let path = paths::repo_cache("checks", git_dir);
+        let now = chrono::Utc::now().timestamp();
+        let entries = path
+            .as_ref()
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|json| serde_json::from_slice::<HashMap<String, Entry>>(&json).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, entry)| now - entry.fetched < MAX_AGE)
+            .filter_map(|(id, entry)| Some((git2::Oid::from_str(&id).ok()?, entry)))
+            .collect();
+        let (tx, rx) = mpsc::channel();
+        Self {
+            entries,
+            done: HashSet::new(),
+            pending: HashSet::new(),
+            limited: None,
+            path,
+            ttl: i64::try_from(ttl_minutes.saturating_mul(60)).unwrap_or(i64::MAX),
+            tx,
+            rx,
+        }
+    }
+
+    /// How the checks of `id` went, `Some(None)` while they are looked up.
+    pub fn get(&self, id: git2::Oid) -> Option<Option<Checks>> {
+        match self.entries.get(&id).and_then(|entry| entry.checks) {
+            Some(checks) => Some(Some(checks)),
+            None if self.pending.contains(&id) => Some(None),
+            None => None,
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// How many lookups are under way.
+    pub fn in_flight(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Forgets the rate limit once it is over, returning whether it was.
+    pub fn retry_due(&mut self) -> bool {
+        if self.limited.is_some_and(|until| until <= Local::now()) {
+            self.limited = None;
+            return true;
+        }
+        false
+    }
+
+    /// How long until the rate limit that stopped a lookup is over.
+    pub fn retry_wait(&self) -> Option<std::time::Duration> {
+        let until = self.limited?;
+        Some((until - Local::now()).to_std().unwrap_or_default())
+    }
+
+    /// Whether `id` should be looked up: unless the cache has a finished
+    /// outcome or a fresh entry, and not while rate limited. Checks still
+    /// running are looked up again once their entry is stale; a commit with
+    /// no entry, having no forge or failing, is looked up once per session.
+    pub fn wants(&self, id: git2::Oid) -> bool {
+        if self.limited.is_some() || self.pending.contains(&id) {
+            return false;
+        }
+        let now = chrono::Utc::now().timestamp();
+        match self.entries.get(&id) {
+            Some(e) => match e.checks {
+                Some(Checks::Passed | Checks::Failed) => false,
+                _ => now - e.fetched > self.ttl,
+            },
+            None => !self.done.contains(&id),
+        }
+    }
+
+    /// Remembers that `id` has no forge to look it up on.
+    pub fn skip(&mut self, id: git2::Oid) {
+        self.done.insert(id);
+    }
+
+    /// Looks up the checks of the commit `id` on a thread of its own, which
+    /// reads the token too, unless the forge is rate limited, which needs no
+    /// thread to find out.
+    pub fn request(&mut self, id: git2::Oid, forge: Arc<dyn Forge>, repo: forge::Repo) {
+        self.done.insert(id);
+        let blocked = limit::blocked();
+        if let Some((_, until)) = blocked.into_iter().find(|(host, _)| *host == repo.host) {
+            self.done.remove(&id);
+            self.limited = Some(until);
+            return;
+        }
+        self.pending.insert(id);
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            // without a token it shows no dot, like a commit the forge does
+            // not know
+            let res = forge::token::cached(&*forge, &repo.host)
+                .and_then(|token| {
+                    token.ok_or_else(|| color_eyre::eyre::eyre!("no token for {}", repo.host))
+                })
+                .and_then(|token| forge.commit_checks(&repo, &token, &id.to_string()))
+                .map_err(|err| {
+                    tracing::debug!("checks of {id}: {err:#}");
+                    err.downcast_ref::<limit::Limited>()
+                        .map(|limited| limited.until)
+                });
+            let _ = tx.send((id, res));
+        });
+    }
+
+    /// Collects finished lookups, returning whether there were any.
+    pub fn poll(&mut self) -> bool {
+        let mut any = false;
+        while let Ok((id, res)) = self.rx.try_recv() {
+            self.pending.remove(&id);
+            match res {
+                Ok(checks) => {
+                    let fetched = chrono::Utc::now().timestamp();
+                    self.entries.insert(id, Entry { checks, fetched });
+                }
+                // looked up again once the limit is over
+                Err(Some(until)) => {
+                    self.done.remove(&id);
+                    self.limited = Some(until);
+                }
+                // a commit the forge does not know, or a failure, shows no dot,
+                // and what was known is tried again a while later
+                Err(None) => {
+                    if let Some(entry) = self.entries.get_mut(&id) {
+                        entry.fetched = chrono::Utc::now().timestamp();
+                    }
+                }
+            }
+            any = true;
+        }
+        if any {
+            self.save();
+        }
+        any
+    }
+
+    /// Writes the cache file. Failing to is not worth bothering anyone about.
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let entries: HashMap<String, &Entry> = self
+            .entries
+            .iter()
+            .map(|(id, entry)| (id.to_string(), entry))
+            .collect();
+        let Ok(json) = serde_json::to_vec(&entries) else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = std::fs::write(path, json);
+    }
+}
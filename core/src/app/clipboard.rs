@@ -0,0 +1,93 @@
+//! Copies text to the system clipboard through the terminal using OSC 52, which
+//! works over ssh and without any platform clipboard libraries.
+
+use crate::{git, prelude::*, t};
+use serde::Deserialize;
+use std::io::Write;
+
+/// How a commit is put on the clipboard.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// The full id.
+    #[default]
+    Sha,
+    /// The abbreviated id and the summary, as `git log --oneline` shows it.
+    Oneline,
+    /// The whole message.
+    Message,
+    /// A markdown link to the commit on the forge, titled like `Oneline`.
+    Markdown,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [
+        Format::Sha,
+        Format::Oneline,
+        Format::Message,
+        Format::Markdown,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Format::Sha => t!("id"),
+            Format::Oneline => t!("id and summary"),
+            Format::Message => t!("message"),
+            Format::Markdown => t!("markdown link"),
+        }
+    }
+
+    /// The text of `commit`, given its page on the forge. A link needs the
+    /// page.
+    pub fn text(self, commit: &git::Commit, url: Option<&str>) -> Option<String> {
+        match self {
+            Format::Sha => Some(commit.id.to_string()),
+            Format::Oneline => Some(format!("{} {}", commit.short, commit.summary)),
+            Format::Message => Some(commit.message.trim_end().to_string()),
+            Format::Markdown => url.map(|url| {
+                let summary = escape_markdown(&commit.summary);
+                format!("[`{}` {summary}]({url})", commit.short)
+            }),
+        }
+    }
+}
+
+/// `text` with the characters Markdown reads as markup escaped, such as a `]`
+/// that would end the text of a link early.
+fn escape_markdown(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '[' | ']' | '(' | ')' | '`' | '*' | '_') {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut out = stdout();
+    write!(out, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    out.flush()
+}
+
+fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut res = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                res.push(TABLE[(n >> (18 - i * 6)) as usize & 63] as char);
+            } else {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
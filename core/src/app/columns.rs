@@ -0,0 +1,231 @@
+//! The columns of the branch list, when the config picks them: cells lined up
+//! under each other, each column as wide as its widest cell up to a limit,
+//! with the summary taking whatever room is left.
+
+use super::{branch, checklist, reviews};
+use crate::{forge, git, prelude::*, redact};
+use ratatui::text::Span;
+use serde::Deserialize;
+use unicode_width::UnicodeWidthChar;
+
+const DIM_STYLE: Style = Style::new().fg(SLATE.c400);
+const TRACKING_STYLE: Style = Style::new().fg(AMBER.c200);
+/// Between two columns.
+const GAP: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    /// The name with its marks and badges.
+    Name,
+    /// How long ago the tip was committed.
+    Age,
+    /// The author of the tip.
+    Author,
+    /// Whose the branch is, by the `user/` prefix of its name or else the
+    /// author of its tip.
+    Owner,
+    /// How far a local branch is ahead and behind its upstream.
+    Tracking,
+    Upstream,
+    /// The summary of the tip.
+    Summary,
+    /// The pull request and its review state, once looked up.
+    Pr,
+    /// The review state kept by hand, and whether there is a note.
+    Checklist,
+}
+
+impl Column {
+    /// The widest the column gets.
+    fn max_width(self) -> usize {
+        match self {
+            Column::Name => 40,
+            Column::Age => 4,
+            Column::Author => 20,
+            Column::Owner => 16,
+            Column::Tracking => 12,
+            Column::Upstream => 30,
+            Column::Summary => usize::MAX,
+            Column::Pr => 10,
+            Column::Checklist => 9,
+        }
+    }
+}
+
+/// The columns in the order given, each where it first appears, with the name
+/// first if it was left out.
+pub fn resolve(columns: &[Column]) -> Vec<Column> {
+    let mut res: Vec<Column> = vec![];
+    for column in columns {
+        if !res.contains(column) {
+            res.push(*column);
+        }
+    }
+    if !res.contains(&Column::Name) {
+        res.insert(0, Column::Name);
+    }
+    res
+}
+
+/// The cell of `column` for a branch. The name is built by the list itself.
+pub fn cell(
+    column: Column,
+    branch: &git::Branch,
+    review: Option<reviews::Lookup>,
+    meta: Option<&checklist::Meta>,
+    now: i64,
+) -> Line<'static> {
+    let tip = branch.commits().first();
+    match column {
+        Column::Name => Line::default(),
+        Column::Age => tip.map_or_else(Line::default, |c| {
+            Line::styled(age(now - c.timestamp.epoch()), DIM_STYLE)
+        }),
+        Column::Author => tip
+            .and_then(|c| c.author.name.as_deref())
+            .map_or_else(Line::default, |name| {
+                Line::styled(redact::person(name).into_owned(), DIM_STYLE)
+            }),
+        Column::Owner => branch::owner(branch).map_or_else(Line::default, |owner| {
+            Line::styled(redact::person(owner.name()).into_owned(), DIM_STYLE)
+        }),
+        Column::Tracking => {
+            branch::tracking(branch).map_or_else(Line::default, |t| Line::styled(t, TRACKING_STYLE))
+        }
+        Column::Upstream => branch.upstream.as_deref().map_or_else(Line::default, |u| {
+            Line::styled(redact::branch(u).into_owned(), DIM_STYLE)
+        }),
+        Column::Summary => tip.map_or_else(Line::default, |c| {
+            Line::raw(redact::text(&c.summary).into_owned())
+        }),
+        Column::Pr => match review {
+            Some(reviews::Lookup::Found {
+                review: Some(review),
+                ..
+            }) => pr(review),
+            _ => Line::default(),
+        },
+        Column::Checklist => meta.map_or_else(Line::default, checklist::badge),
+    }
+}
+
+/// The pull request of a branch in a few characters: its number and the
+/// verdict of the review, or `◌` for a draft, then a dot colored by how the
+/// checks went, if there are any.
+pub fn pr(review: &forge::Review) -> Line<'static> {
+    let (sign, color) = match review.verdict {
+        _ if review.draft => ("◌", SLATE.c400),
+        forge::Verdict::Approved => ("✓", GREEN.c300),
+        forge::Verdict::ChangesRequested => ("✗", RED.c300),
+        forge::Verdict::Pending => ("…", AMBER.c300),
+    };
+    let mut spans = vec![Span::styled(format!("#{} {sign}", review.number), color)];
+    if let Some(checks) = review.checks {
+        let color = match checks {
+            forge::Checks::Passed => GREEN.c300,
+            forge::Checks::Failed => RED.c300,
+            forge::Checks::Running => AMBER.c300,
+        };
+        spans.push(Span::styled(" ●", color));
+    }
+    Line::from(spans)
+}
+
+/// A short age such as `5m`, `3h`, `12d`, `4mo` or `2y`.
+pub(super) fn age(secs: i64) -> String {
+    let (minutes, hours, days) = (secs / 60, secs / 3600, secs / 86400);
+    match () {
+        _ if hours < 1 => format!("{}m", minutes.max(0)),
+        _ if days < 2 => format!("{hours}h"),
+        _ if days < 60 => format!("{days}d"),
+        _ if days < 730 => format!("{}mo", days / 30),
+        _ => format!("{}y", days / 365),
+    }
+}
+
+/// Lines up the cells of each row within `width`: every column but the last
+/// is padded to its width, and cells too wide for it are cut with `…`. Rows
+/// without cells, such as group headers, are left alone.
+pub fn align(
+    columns: &[Column],
+    rows: Vec<Option<Vec<Line<'static>>>>,
+    width: usize,
+) -> Vec<Option<Line<'static>>> {
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(idx, column)| {
+            let widest = rows
+                .iter()
+                .flatten()
+                .map(|cells| cells.get(idx).map_or(0, Line::width))
+                .max()
+                .unwrap_or_default();
+            widest.min(column.max_width())
+        })
+        .collect();
+    if let Some(idx) = columns.iter().position(|c| *c == Column::Summary) {
+        let others: usize = widths
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != idx)
+            .map(|(_, w)| w + GAP)
+            .sum();
+        widths[idx] = width.saturating_sub(others).min(widths[idx]);
+    }
+    rows.into_iter()
+        .map(|cells| {
+            let cells = cells?;
+            let mut spans = vec![];
+            let last = cells.len().saturating_sub(1);
+            for (idx, (cell, width)) in cells.into_iter().zip(&widths).enumerate() {
+                if idx > 0 {
+                    spans.push(Span::raw(" ".repeat(GAP)));
+                }
+                let cell = fit(cell, *width);
+                let pad = width.saturating_sub(cell.width());
+                // the style of the whole cell goes under that of its spans
+                let style = cell.style;
+                spans.extend(
+                    cell.spans
+                        .into_iter()
+                        .map(|span| Span::styled(span.content, style.patch(span.style))),
+                );
+                if idx < last && pad > 0 {
+                    spans.push(Span::raw(" ".repeat(pad)));
+                }
+            }
+            Some(Line::from(spans))
+        })
+        .collect()
+}
+
+/// Cuts a line to `width` columns, ending it with `…` if anything was cut.
+fn fit(line: Line<'static>, width: usize) -> Line<'static> {
+    if line.width() <= width {
+        return line;
+    }
+    let mut room = width.saturating_sub(1);
+    let mut spans = vec![];
+    for span in line.spans {
+        let mut text = String::new();
+        for c in span.content.chars() {
+            let w = c.width().unwrap_or_default();
+            if w > room {
+                room = 0;
+                break;
+            }
+            room -= w;
+            text.push(c);
+        }
+        spans.push(Span::styled(text, span.style));
+        if room == 0 {
+            break;
+        }
+    }
+    if width > 0 {
+        spans.push(Span::raw("…"));
+    }
+    Line::from(spans).style(line.style)
+}
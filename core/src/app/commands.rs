@@ -0,0 +1,22 @@
+//! User commands bound to keys in the config.
+
+use super::template::Template;
+
+/// Fills in the placeholders of a user command, quoting each value for the shell.
+pub fn render(cmd: &str, values: &[(String, String)]) -> String {
+    let quoted: Vec<(String, String)> = values
+        .iter()
+        .map(|(name, val)| (name.clone(), quote(val)))
+        .collect();
+    Template::parse(cmd).render(&quoted)
+}
+
+fn quote(s: &str) -> String {
+    if !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '/' | '.'))
+    {
+        return s.to_string();
+    }
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
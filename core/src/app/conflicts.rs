@@ -0,0 +1,40 @@
+//! Key bindings that keep others from being reached, found when the keymap is
+//! loaded and listed in a popup of their own, so that a user command bound over
+//! a built-in does not quietly take it away.
+
+use crate::{prelude::*, t};
+use ratatui::widgets::{Clear, Wrap};
+
+pub struct ConflictList<'a>(pub &'a [String]);
+
+impl Widget for ConflictList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let mut lines: Vec<Line> = self
+            .0
+            .iter()
+            .map(|c| Line::styled(format!("• {c}"), AMBER.c300))
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::styled(
+                t!("no key bindings conflict"),
+                Style::new().dim(),
+            ));
+        }
+        let block = Block::new()
+            .title(Line::raw(t!("Keymap conflicts")).left_aligned())
+            .title(Line::raw(t!("esc: close")).right_aligned())
+            .borders(Borders::ALL)
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c800));
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(popup, buf);
+    }
+}
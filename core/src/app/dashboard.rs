@@ -0,0 +1,178 @@
+//! A dashboard of repository statistics, computed in the background.
+
+use crate::{
+    git::{
+        self,
+        stats::{self, Stats},
+    },
+    prelude::*,
+    redact, t,
+};
+use ratatui::{
+    layout::Direction,
+    text::Span,
+    widgets::{Bar, BarChart, BarGroup, Clear, Sparkline},
+};
+use std::sync::mpsc;
+
+const TOP: usize = 8;
+
+pub struct Dashboard {
+    rx: Option<mpsc::Receiver<git::Result<Stats>>>,
+    stats: Option<EResult<Stats, String>>,
+}
+
+impl Dashboard {
+    pub fn new(rx: mpsc::Receiver<git::Result<Stats>>) -> Self {
+        Self {
+            rx: Some(rx),
+            stats: None,
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    pub fn poll(&mut self) {
+        let Some(rx) = &self.rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(res) => {
+                self.stats = Some(res.map_err(|err| format!("{err:#}")));
+                self.rx = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.stats = Some(Err(t!("statistics were not computed").to_string()));
+                self.rx = None;
+            }
+        }
+    }
+}
+
+impl Widget for &Dashboard {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 20,
+            y: area.y + area.height / 20,
+            width: area.width - area.width / 10,
+            height: area.height - area.height / 10,
+        };
+        let block = Block::new()
+            .title(Line::raw(t!("Statistics")).left_aligned())
+            .title(Line::raw(t!("esc: close")).right_aligned())
+            .borders(Borders::ALL)
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c900));
+        let inner = block.inner(popup);
+        Clear.render(popup, buf);
+        block.render(popup, buf);
+        match &self.stats {
+            None => Paragraph::new(t!("computing…")).render(inner, buf),
+            Some(Err(err)) => Paragraph::new(err.as_str())
+                .style(Style::new().fg(RED.c300))
+                .render(inner, buf),
+            Some(Ok(stats)) => render_stats(stats, inner, buf),
+        }
+    }
+}
+
+fn render_stats(stats: &Stats, area: Rect, buf: &mut Buffer) {
+    let [top, volume, authors] = Layout::vertical([
+        Constraint::Length(TOP as u16 + 2),
+        Constraint::Length(6),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+    let [counts, divergence] =
+        Layout::horizontal([Constraint::Length(32), Constraint::Fill(1)]).areas(top);
+
+    let label = Style::new().fg(SLATE.c400);
+    let count_lines = vec![
+        Line::from(vec![
+            Span::styled(format!("{:<10}", t!("local")), label),
+            Span::raw(t!(
+                "{total} ({stale} stale)",
+                total = stats.local,
+                stale = stats.stale_local
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled(format!("{:<10}", t!("remote")), label),
+            Span::raw(t!(
+                "{total} ({stale} stale)",
+                total = stats.remote,
+                stale = stats.stale_remote
+            )),
+        ]),
+        Line::styled(
+            t!(
+                "stale: no commits in {days} days",
+                days = stats::STALE_AFTER_DAYS
+            ),
+            label,
+        ),
+    ];
+    Paragraph::new(count_lines)
+        .block(section(t!("Branches")))
+        .render(counts, buf);
+
+    let title = match &stats.default_branch {
+        Some(base) => t!(
+            "Divergence from {base} (ahead/behind)",
+            base = redact::branch(base)
+        ),
+        None => t!("Divergence: no default branch found").to_string(),
+    };
+    let lines: Vec<Line> = stats
+        .divergence
+        .iter()
+        .take(TOP)
+        .map(|(name, ahead, behind)| {
+            Line::from(vec![
+                Span::styled(format!("{:>5}↑ {:>5}↓  ", ahead, behind), label),
+                Span::raw(redact::branch(name)),
+            ])
+        })
+        .collect();
+    Paragraph::new(lines)
+        .block(section(&title))
+        .render(divergence, buf);
+
+    let weeks = stats.weekly.len();
+    Sparkline::default()
+        .data(&stats.weekly)
+        .style(Style::new().fg(GREEN.c400))
+        .block(section(&t!(
+            "Commits per week, last {weeks} weeks",
+            weeks = weeks
+        )))
+        .render(volume, buf);
+
+    let bars: Vec<Bar> = stats
+        .authors
+        .iter()
+        .take(TOP)
+        .map(|(name, count)| {
+            Bar::default()
+                .value(*count)
+                .label(Line::raw(redact::person(name)))
+                .style(Style::new().fg(BLUE.c400))
+                .value_style(Style::new().fg(SLATE.c950).bg(BLUE.c400))
+        })
+        .collect();
+    BarChart::default()
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .data(BarGroup::default().bars(&bars))
+        .block(section(t!("Top authors")))
+        .render(authors, buf);
+}
+
+fn section(title: &str) -> Block<'_> {
+    Block::new()
+        .title(Line::styled(title, Style::new().bold()))
+        .borders(Borders::TOP)
+}
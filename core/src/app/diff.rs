@@ -0,0 +1,158 @@
+//! How diffs are shown: whitespace and context, changed from the output pane
+//! while a diff is open and kept for the rest of the session.
+
+use crate::{prelude::*, t};
+
+/// How many lines of context surround each change.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Context {
+    None,
+    #[default]
+    Three,
+    Ten,
+    /// The whole file.
+    Full,
+}
+
+impl Context {
+    pub fn next(self) -> Self {
+        match self {
+            Context::None => Context::Three,
+            Context::Three => Context::Ten,
+            Context::Ten => Context::Full,
+            Context::Full => Context::None,
+        }
+    }
+
+    fn lines(self) -> usize {
+        match self {
+            Context::None => 0,
+            Context::Three => 3,
+            Context::Ten => 10,
+            // more than any file worth reading in a diff has
+            Context::Full => 1_000_000,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Options {
+    pub ignore_whitespace: bool,
+    pub context: Context,
+}
+
+impl Options {
+    /// The options to give `git diff` and `git show`.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec![format!("--unified={}", self.context.lines())];
+        if self.ignore_whitespace {
+            args.push("--ignore-all-space".to_string());
+        }
+        args
+    }
+
+    /// What differs from git's defaults, for the title of the pane.
+    pub fn label(&self) -> Option<String> {
+        let mut parts = vec![];
+        if self.ignore_whitespace {
+            parts.push(t!("ignoring whitespace").to_string());
+        }
+        match self.context {
+            Context::Three => {}
+            Context::Full => parts.push(t!("whole files").to_string()),
+            Context::None | Context::Ten => {
+                let lines = self.context.lines();
+                parts.push(t!("{lines} lines of context", lines = lines));
+            }
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+/// A diff open in the output pane, kept to run again when the options change.
+#[derive(Clone, Debug)]
+pub struct Shown {
+    pub title: String,
+    /// The git subcommand and its arguments, without the diff options.
+    pub args: Vec<String>,
+}
+
+/// The style of a line of `git show` or `git diff` output, colored as git
+/// colors it in a terminal.
+pub fn style(line: &str) -> Style {
+    match line.as_bytes().first() {
+        _ if line.starts_with("+++ ") || line.starts_with("--- ") => Style::new().bold(),
+        Some(b'+') => Style::new().fg(GREEN.c300),
+        Some(b'-') => Style::new().fg(RED.c300),
+        _ if line.starts_with("@@") => Style::new().fg(BLUE.c300),
+        _ if line.starts_with("commit ") => Style::new().fg(AMBER.c200),
+        _ if line.starts_with("diff ") => Style::new().bold(),
+        _ => Style::new(),
+    }
+}
+
+/// Whether a line of a file's diff header gives it tree mode 160000, the mode
+/// of a submodule, on either side.
+pub fn is_gitlink_header(line: &str) -> bool {
+    const GITLINK: &str = "160000";
+    if let Some(index) = line.strip_prefix("index ") {
+        return index.split(' ').nth(1) == Some(GITLINK);
+    }
+    [
+        "new file mode ",
+        "deleted file mode ",
+        "old mode ",
+        "new mode ",
+    ]
+    .iter()
+    .any(|prefix| line.strip_prefix(prefix) == Some(GITLINK))
+}
+
+/// Whether `id` is a full object id, as git writes one for a submodule.
+fn is_oid(id: &str) -> bool {
+    matches!(id.len(), 40 | 64) && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// A change to the commit a submodule is at, from the two lines git shows for
+/// it.
+#[derive(Clone, Debug)]
+pub struct Submodule {
+    pub path: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl Submodule {
+    /// The change from the two lines, for a file git gave tree mode 160000.
+    /// Anything but an object id on either side is left as text.
+    pub fn parse(path: &str, removed: &str, added: &str) -> Option<Self> {
+        let old = removed.strip_prefix("-Subproject commit ")?.trim();
+        let new = added.strip_prefix("+Subproject commit ")?.trim();
+        if !is_oid(old.trim_end_matches("-dirty")) || !is_oid(new.trim_end_matches("-dirty")) {
+            return None;
+        }
+        Some(Self {
+            path: path.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        })
+    }
+
+    /// A commit of the change, abbreviated, keeping the `-dirty` git adds for
+    /// uncommitted changes in the submodule.
+    pub fn short(&self, id: &str) -> String {
+        let (sha, dirty) = match id.strip_suffix("-dirty") {
+            Some(sha) => (sha, "-dirty"),
+            None => (id, ""),
+        };
+        let sha: String = sha.chars().take(7).collect();
+        format!("{sha}{dirty}")
+    }
+
+    /// The range of the submodule's commits between the two.
+    pub fn range(&self) -> String {
+        let old = self.old.trim_end_matches("-dirty");
+        let new = self.new.trim_end_matches("-dirty");
+        format!("{old}..{new}")
+    }
+}
@@ -0,0 +1,156 @@
+//! A browser of directories to pick a repository with: the ones opened
+//! before this session first, then the way up, then the subdirectories of
+//! the one browsed, with repositories marked.
+
+use crate::{prelude::*, t};
+use ratatui::widgets::Clear;
+use std::path::{Path, PathBuf};
+
+const REPO_STYLE: Style = Style::new().fg(GREEN.c300);
+const RECENT_STYLE: Style = Style::new().fg(BLUE.c300);
+
+pub enum Picked {
+    Dir(PathBuf),
+    Cancel,
+    Nothing,
+}
+
+enum Entry {
+    /// A repository opened before in this session.
+    Recent(PathBuf),
+    Up,
+    /// A subdirectory of the one browsed, and whether it is a repository.
+    Dir(String, bool),
+}
+
+pub struct Picker {
+    dir: PathBuf,
+    recent: Vec<PathBuf>,
+    entries: Vec<Entry>,
+    state: ListState,
+}
+
+impl Picker {
+    /// Browses `dir`, or the nearest directory above it that is there.
+    pub fn new(dir: &Path, recent: Vec<PathBuf>) -> Self {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let dir = dir
+            .ancestors()
+            .find(|dir| dir.is_dir())
+            .map_or(dir.clone(), Path::to_path_buf);
+        let mut picker = Self {
+            dir,
+            recent,
+            entries: Vec::new(),
+            state: ListState::default(),
+        };
+        picker.read();
+        picker
+    }
+
+    fn read(&mut self) {
+        let mut dirs: Vec<(String, bool)> = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                (name, is_repo(&entry.path()))
+            })
+            .filter(|(name, _)| !name.starts_with('.'))
+            .collect();
+        dirs.sort();
+        self.entries = self.recent.iter().cloned().map(Entry::Recent).collect();
+        self.entries.push(Entry::Up);
+        self.entries
+            .extend(dirs.into_iter().map(|(name, repo)| Entry::Dir(name, repo)));
+        self.state.select(Some(self.recent.len()));
+    }
+
+    fn enter(&mut self, dir: PathBuf) {
+        self.dir = dir;
+        self.read();
+    }
+
+    pub fn handle(&mut self, code: KeyCode) -> Picked {
+        let idx = self.state.selected().unwrap_or_default();
+        let last = self.entries.len().saturating_sub(1);
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') => return Picked::Cancel,
+            KeyCode::Char('j') | KeyCode::Down => self.state.select(Some((idx + 1).min(last))),
+            KeyCode::Char('k') | KeyCode::Up => self.state.select(Some(idx.saturating_sub(1))),
+            KeyCode::Char('g') | KeyCode::Home => self.state.select(Some(0)),
+            KeyCode::Char('G') | KeyCode::End => self.state.select(Some(last)),
+            KeyCode::Char('h') | KeyCode::Left | KeyCode::Backspace => {
+                if let Some(parent) = self.dir.parent() {
+                    self.enter(parent.to_path_buf());
+                }
+            }
+            KeyCode::Char('.') => return Picked::Dir(self.dir.clone()),
+            KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => {
+                let open = code == KeyCode::Enter;
+                match self.entries.get(idx) {
+                    Some(Entry::Recent(path)) => return Picked::Dir(path.clone()),
+                    Some(Entry::Up) => {
+                        if let Some(parent) = self.dir.parent() {
+                            self.enter(parent.to_path_buf());
+                        }
+                    }
+                    Some(Entry::Dir(name, repo)) => {
+                        let path = self.dir.join(name);
+                        match open && *repo {
+                            true => return Picked::Dir(path),
+                            false => self.enter(path),
+                        }
+                    }
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+        Picked::Nothing
+    }
+}
+
+impl Widget for &mut Picker {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Recent(path) => {
+                    ListItem::new(Line::styled(path.display().to_string(), RECENT_STYLE))
+                }
+                Entry::Up => ListItem::new(".."),
+                Entry::Dir(name, repo) => {
+                    let mut line = Line::raw(format!("{name}/"));
+                    if *repo {
+                        line.spans.push(Span::styled("  git", REPO_STYLE));
+                    }
+                    ListItem::new(line)
+                }
+            })
+            .collect();
+        let block = Block::new()
+            .title(Line::raw(self.dir.display().to_string()).left_aligned())
+            .title(
+                Line::raw(t!(
+                    "enter: open repository or go in  h: up  .: open this one  esc: back"
+                ))
+                .right_aligned(),
+            )
+            .borders(Borders::ALL);
+        let list = List::new(items)
+            .block(block)
+            .highlight_symbol("> ")
+            .highlight_style(Style::new().bg(SLATE.c700));
+        Clear.render(area, buf);
+        StatefulWidget::render(list, area, buf, &mut self.state);
+    }
+}
+
+/// Whether `dir` is a work tree with a `.git` in it, or a bare repository.
+fn is_repo(dir: &Path) -> bool {
+    dir.join(".git").exists() || (dir.join("HEAD").is_file() && dir.join("objects").is_dir())
+}
@@ -0,0 +1,119 @@
+//! A popup with the commits a branch and its upstream do not share, in two
+//! lanes down to the commit they do, to see what a rebase, a merge or a force
+//! push would do before choosing one.
+
+use crate::{git, prelude::*, redact, t};
+use ratatui::{text::Span, widgets::Clear};
+
+const LOCAL: Color = GREEN.c300;
+const REMOTE: Color = AMBER.c300;
+
+pub struct Lanes<'a> {
+    pub div: &'a git::Divergence,
+    pub scroll: &'a mut usize,
+}
+
+impl Widget for Lanes<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let div = self.div;
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let dim = Style::new().dim();
+        let (branch, upstream) = (redact::branch(&div.branch), redact::branch(&div.upstream));
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled(
+                    t!(
+                        "● {branch} only: {count}",
+                        branch = branch,
+                        count = div.ahead
+                    ),
+                    LOCAL,
+                ),
+                Span::raw("   "),
+                Span::styled(
+                    t!(
+                        "● {upstream} only: {count}",
+                        upstream = upstream,
+                        count = div.behind
+                    ),
+                    REMOTE,
+                ),
+            ]),
+            Line::raw(""),
+        ];
+        // both sides interleaved by date, each in its lane
+        let mut commits: Vec<(&git::Commit, bool)> = div
+            .local
+            .iter()
+            .map(|c| (c, true))
+            .chain(div.remote.iter().map(|c| (c, false)))
+            .collect();
+        commits.sort_by_key(|(c, _)| std::cmp::Reverse(c.timestamp.epoch()));
+        for (commit, local) in commits {
+            let lanes = match local {
+                true => vec![Span::styled("● ", LOCAL), Span::styled("│ ", REMOTE)],
+                false => vec![Span::styled("│ ", LOCAL), Span::styled("● ", REMOTE)],
+            };
+            lines.push(row(lanes, commit));
+        }
+        let more = [
+            (div.ahead.saturating_sub(div.local.len()), &branch),
+            (div.behind.saturating_sub(div.remote.len()), &upstream),
+        ];
+        for (count, name) in more.into_iter().filter(|(count, _)| *count > 0) {
+            lines.push(Line::styled(
+                t!(
+                    "┆ ┆ … {count} older commits of {name}",
+                    count = count,
+                    name = name
+                ),
+                dim,
+            ));
+        }
+        match &div.base {
+            Some(base) => {
+                lines.push(row(vec![Span::raw("●─╯ ")], base));
+                lines.push(Line::styled(t!("    merge base"), dim));
+            }
+            None => lines.push(Line::styled(t!("no history in common"), dim)),
+        }
+        let title = t!(
+            "{branch} and {upstream}",
+            branch = branch,
+            upstream = upstream
+        );
+        let block = Block::new()
+            .title(Line::raw(title).left_aligned())
+            .title(Line::raw(t!("j/k: scroll  esc: close")).right_aligned())
+            .borders(Borders::ALL);
+        let inner = block.inner(popup);
+        let max = lines.len().saturating_sub(inner.height as usize);
+        *self.scroll = (*self.scroll).min(max);
+        let count = lines.len();
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c900))
+            .block(block)
+            .scroll((*self.scroll as u16, 0))
+            .render(popup, buf);
+        super::scroll::scrollbar(inner, buf, count, *self.scroll);
+    }
+}
+
+fn row<'a>(mut spans: Vec<Span<'a>>, commit: &git::Commit) -> Line<'a> {
+    let author = commit.author.name.as_deref().unwrap_or("?");
+    spans.extend([
+        Span::styled(format!("{} ", commit.short), Style::new().dim()),
+        Span::raw(redact::text(&commit.summary).into_owned()),
+        Span::styled(
+            format!("  {}, {}", redact::person(author), commit.timestamp),
+            Style::new().dim(),
+        ),
+    ]);
+    Line::from(spans)
+}
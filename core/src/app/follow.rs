@@ -0,0 +1,202 @@
+//! Branches followed like `tail -f`: while grit runs, the remote branch of each
+//! is fetched every so often, and the commits that arrive on it are marked as
+//! new until the branch has been looked at. Only remote branches are followed,
+//! as that is where the commits arrive: following a local branch follows its
+//! upstream, whose commits are the ones shown.
+
+use crate::{git, prelude::*};
+use color_eyre::eyre::WrapErr;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::mpsc,
+    time::Instant,
+};
+
+/// How often followed branches are fetched unless configured otherwise.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Followed {
+    /// The short name, e.g. `origin/main`, for telling about it and fetching
+    /// it.
+    name: String,
+    /// The tip when it was last looked at.
+    tip: Option<git2::Oid>,
+    /// Commits that arrived since it was last looked at.
+    new: HashSet<git2::Oid>,
+}
+
+/// The followed remote branches, by full ref name.
+pub struct Follow {
+    branches: HashMap<String, Followed>,
+    /// How often to fetch, or `None` not to, as with `--safe`.
+    interval: Option<Duration>,
+    fetched: Option<Instant>,
+    /// The fetch under way, which sends whether it worked.
+    fetching: Option<mpsc::Receiver<EResult<()>>>,
+    /// Whether the last fetch failed, so that a failure is told about once
+    /// rather than on every try.
+    failing: bool,
+}
+
+impl Follow {
+    pub fn new(interval: Option<Duration>) -> Self {
+        Self {
+            branches: HashMap::new(),
+            interval,
+            fetched: None,
+            fetching: None,
+            failing: false,
+        }
+    }
+
+    /// Whether followed branches are fetched, rather than only watched for
+    /// what other fetches bring.
+    pub fn fetches(&self) -> bool {
+        self.interval.is_some()
+    }
+
+    /// Follows the remote branch `name`, e.g. `origin/main`, or stops
+    /// following it, returning whether it is now. `tip` is where it points.
+    pub fn toggle(&mut self, name: &str, tip: Option<git2::Oid>) -> bool {
+        let refname = git::refname::full(name, git2::BranchType::Remote);
+        if self.branches.remove(&refname).is_some() {
+            return false;
+        }
+        let followed = Followed {
+            name: name.to_string(),
+            tip,
+            new: HashSet::new(),
+        };
+        self.branches.insert(refname, followed);
+        // fetched right away rather than an interval from now
+        self.fetched = None;
+        true
+    }
+
+    pub fn is_followed(&self, branch: &git::Branch) -> bool {
+        self.branches.contains_key(&branch.refname())
+    }
+
+    /// The short names of the followed branches.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.branches.values().map(|f| f.name.as_str())
+    }
+
+    /// How many commits arrived on `branch` since it was last looked at.
+    pub fn new_count(&self, branch: &git::Branch) -> usize {
+        self.branches
+            .get(&branch.refname())
+            .map_or(0, |f| f.new.len())
+    }
+
+    /// Whether `id` arrived on `branch` since it was last looked at.
+    pub fn is_new(&self, branch: &git::Branch, id: git2::Oid) -> bool {
+        self.branches
+            .get(&branch.refname())
+            .is_some_and(|f| f.new.contains(&id))
+    }
+
+    /// Forgets what was new on `branch`, once it was looked at.
+    pub fn seen(&mut self, refname: &str) {
+        if let Some(followed) = self.branches.get_mut(refname) {
+            followed.new.clear();
+        }
+    }
+
+    /// Whether the tip of any followed branch moved since it was last looked
+    /// at, by [`Follow::moved`].
+    pub fn has_moved(&self, tip_of: impl Fn(&str) -> Option<git2::Oid>) -> bool {
+        self.branches.iter().any(|(refname, followed)| {
+            let tip = tip_of(refname);
+            tip.is_some() && tip != followed.tip
+        })
+    }
+
+    /// The followed branches whose tip moved since this was last asked, with
+    /// the old tip and the new one. The new tip is kept.
+    pub fn moved(
+        &mut self,
+        tip_of: impl Fn(&str) -> Option<git2::Oid>,
+    ) -> Vec<(String, git2::Oid, git2::Oid)> {
+        let mut moved = vec![];
+        for (refname, followed) in &mut self.branches {
+            let tip = tip_of(refname);
+            if let (Some(old), Some(new)) = (followed.tip, tip) {
+                if old != new {
+                    moved.push((refname.clone(), old, new));
+                }
+            }
+            followed.tip = tip.or(followed.tip);
+        }
+        moved
+    }
+
+    /// Marks `ids` as new on the branch `refname`, returning its short name.
+    pub fn arrived(&mut self, refname: &str, ids: Vec<git2::Oid>) -> Option<&str> {
+        let followed = self.branches.get_mut(refname)?;
+        followed.new.extend(ids);
+        Some(&followed.name)
+    }
+
+    /// Whether it is time to fetch the followed branches again.
+    pub fn is_due(&self) -> bool {
+        let Some(interval) = self.interval else {
+            return false;
+        };
+        !self.branches.is_empty()
+            && self.fetching.is_none()
+            && self.fetched.is_none_or(|at| at.elapsed() >= interval)
+    }
+
+    /// How long until the next fetch, if anything is followed.
+    pub fn wait(&self) -> Option<Duration> {
+        let interval = self.interval?;
+        if self.branches.is_empty() || self.fetching.is_some() {
+            return None;
+        }
+        let since = self.fetched.map_or(interval, |at| at.elapsed());
+        Some(interval.saturating_sub(since))
+    }
+
+    /// Fetches each of `targets`, a remote and a branch on it, on a thread of
+    /// its own.
+    pub fn fetch(&mut self, loc: git::command::Location, targets: Vec<(String, String)>) {
+        self.fetched = Some(Instant::now());
+        if targets.is_empty() {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let res = targets.iter().try_for_each(|(remote, branch)| {
+                git::command::remote(&loc, &["fetch", remote, branch], || false)
+                    .map(drop)
+                    .wrap_err_with(|| format!("fetch {remote} {branch}"))
+            });
+            let _ = tx.send(res);
+        });
+        self.fetching = Some(rx);
+    }
+
+    /// Whether the last fetch failed.
+    pub fn is_failing(&self) -> bool {
+        self.failing
+    }
+
+    /// Takes the fetch once it is done: whether it worked, and if not, the
+    /// error, unless the one before failed too.
+    pub fn poll(&mut self) -> Option<EResult<()>> {
+        let res = self.fetching.as_ref()?.try_recv().ok()?;
+        self.fetching = None;
+        match res {
+            Ok(()) => {
+                self.failing = false;
+                Some(Ok(()))
+            }
+            Err(_) if self.failing => Some(Ok(())),
+            Err(err) => {
+                self.failing = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
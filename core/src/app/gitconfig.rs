@@ -0,0 +1,208 @@
+//! A popup of the git config in effect: first the keys grit's workflows lean
+//! on, which can be set in the repository config from here, then every value
+//! with the file it comes from, struck through where another overrides it.
+//! Values that may hold credentials, such as `http.<url>.extraHeader`, are
+//! masked, as are the user and password of URLs.
+
+use crate::{git, prelude::*, redact, t};
+use ratatui::{text::Span, widgets::Clear};
+use regex::Regex;
+
+/// The keys that can be set here.
+pub const KEYS: [&str; 4] = ["user.name", "user.email", "pull.rebase", "push.default"];
+
+/// Why `value` will not do for the key `name`, if it will not.
+pub fn validate(name: &str, value: &str) -> Result<(), String> {
+    let one_of = |choices: &[&str]| match choices.contains(&value) {
+        true => Ok(()),
+        false => Err(t!(
+            "expected one of {choices}",
+            choices = choices.join(", ")
+        )),
+    };
+    match name {
+        "user.name" if value.contains(['<', '>', '\n']) => {
+            Err(t!("a name cannot have < or > in it").to_string())
+        }
+        "user.email" => {
+            let (local, domain) = value.split_once('@').unwrap_or_default();
+            match local.is_empty() || domain.is_empty() || value.contains(['<', '>', ' ']) {
+                true => Err(t!("not an email address").to_string()),
+                false => Ok(()),
+            }
+        }
+        "pull.rebase" => one_of(&["true", "false", "merges", "interactive"]),
+        "push.default" => one_of(&["simple", "current", "upstream", "matching", "nothing"]),
+        _ => Ok(()),
+    }
+}
+
+/// The entry in effect for `name`, if it is set anywhere.
+pub fn effective<'a>(entries: &'a [git::ConfigEntry], name: &str) -> Option<&'a git::ConfigEntry> {
+    entries
+        .iter()
+        .rev()
+        .find(|e| e.name == name && !e.overridden)
+}
+
+/// Why `value` will not do for `user.name` or `user.email` of commits, given
+/// the `pattern` the address has to match, if it will not.
+pub fn check_identity(key: &str, value: &str, pattern: Option<&Regex>) -> Result<(), String> {
+    if value.trim().is_empty() {
+        return Err(t!("{key} is not set", key = key));
+    }
+    validate(key, value)?;
+    match pattern {
+        Some(pattern) if key == "user.email" && !pattern.is_match(value) => Err(t!(
+            "{email} is not the address expected here, which matches {pattern}",
+            email = value,
+            pattern = pattern
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// What is wrong with the identity commits would be made with, as the key to
+/// fix and why, if anything is. A key set in the environment, as
+/// `GIT_AUTHOR_NAME` or `GIT_AUTHOR_EMAIL`, is left to whoever set it.
+pub fn identity_problem(
+    entries: &[git::ConfigEntry],
+    pattern: Option<&Regex>,
+) -> Option<(&'static str, String)> {
+    [
+        ("user.name", "GIT_AUTHOR_NAME"),
+        ("user.email", "GIT_AUTHOR_EMAIL"),
+    ]
+    .into_iter()
+    .filter(|(_, var)| std::env::var_os(var).is_none())
+    .find_map(|(key, _)| {
+        let value = effective(entries, key).map_or("", |e| e.value.as_str());
+        check_identity(key, value, pattern)
+            .err()
+            .map(|err| (key, err))
+    })
+}
+
+/// Whether the value of `name` may be a secret, such as a token in a header.
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let (section, key) = (
+        name.split('.').next().unwrap_or_default(),
+        name.rsplit('.').next().unwrap_or_default(),
+    );
+    matches!(
+        (section, key),
+        ("http", "extraheader") | ("credential", _) | ("url", "insteadof")
+    )
+}
+
+/// The value of `entry` as it can be shown.
+fn shown(entry: &git::ConfigEntry) -> String {
+    match is_secret(&entry.name) {
+        true => "********".to_string(),
+        false => redact::url(&entry.value).into_owned(),
+    }
+}
+
+fn level(level: git::ConfigLevel) -> &'static str {
+    match level {
+        git::ConfigLevel::ProgramData => "programdata",
+        git::ConfigLevel::System => "system",
+        git::ConfigLevel::Xdg => "xdg",
+        git::ConfigLevel::Global => "global",
+        git::ConfigLevel::Local => "local",
+        git::ConfigLevel::Worktree => "worktree",
+        git::ConfigLevel::App => "command",
+    }
+}
+
+#[derive(Default)]
+pub struct GitConfig {
+    pub entries: Vec<git::ConfigEntry>,
+    pub state: ListState,
+}
+
+impl GitConfig {
+    /// Replaces the values, keeping the selection on the same row.
+    pub fn reload(&mut self, entries: Vec<git::ConfigEntry>) {
+        self.entries = entries;
+        let last = KEYS.len() + self.entries.len() - 1;
+        let idx = self.state.selected().unwrap_or_default().min(last);
+        self.state.select(Some(idx));
+    }
+
+    /// The key of the selected row, if it is one that can be set here.
+    pub fn selected_key(&self) -> Option<&'static str> {
+        self.state.selected().and_then(|i| KEYS.get(i)).copied()
+    }
+
+    /// The value in effect for `name`, if it is set anywhere.
+    pub fn effective(&self, name: &str) -> Option<&git::ConfigEntry> {
+        effective(&self.entries, name)
+    }
+}
+
+pub struct GitConfigList<'a>(pub &'a mut GitConfig);
+
+impl Widget for GitConfigList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let dim = Style::new().dim();
+        let width = self
+            .0
+            .entries
+            .iter()
+            .map(|e| e.name.chars().count())
+            .chain(KEYS.iter().map(|k| k.len()))
+            .max()
+            .unwrap_or_default()
+            .min(popup.width as usize / 2);
+        let mut items: Vec<ListItem> = KEYS
+            .iter()
+            .map(|key| {
+                let mut spans = vec![Span::styled(format!("{key:width$}  "), Style::new().bold())];
+                match self.0.effective(key) {
+                    Some(entry) => {
+                        spans.push(Span::raw(shown(entry)));
+                        spans.push(Span::styled(format!("  {}", level(entry.level)), dim));
+                    }
+                    None => spans.push(Span::styled(t!("unset"), dim)),
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        items.extend(self.0.entries.iter().map(|entry| {
+            let style = match entry.overridden {
+                true => dim.add_modifier(Modifier::CROSSED_OUT),
+                false => Style::new(),
+            };
+            ListItem::new(Line::from(vec![
+                // a subsection may be a URL too, as in url.<base>.insteadOf
+                Span::styled(format!("{:width$}  ", redact::url(&entry.name)), style),
+                Span::styled(shown(entry), style),
+                Span::styled(format!("  {}", level(entry.level)), dim),
+            ]))
+        }));
+        let list = List::new(items)
+            .block(
+                Block::new()
+                    .title(Line::raw(t!("Git config")).left_aligned())
+                    .title(
+                        Line::raw(t!(
+                            "enter: set in this repository  d: unset here  esc: close"
+                        ))
+                        .right_aligned(),
+                    )
+                    .borders(Borders::ALL),
+            )
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c800))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        Clear.render(popup, buf);
+        StatefulWidget::render(list, popup, buf, &mut self.0.state);
+    }
+}
@@ -0,0 +1,53 @@
+//! A popup listing every key binding, with the name each action goes by in
+//! the `[keys]` table of the config.
+
+use super::keymap::{self, Keymap};
+use crate::{prelude::*, t};
+use ratatui::{text::Span, widgets::Clear};
+
+const KEY_STYLE: Style = Style::new().fg(BLUE.c300).add_modifier(Modifier::BOLD);
+
+pub struct Help<'a> {
+    pub keymap: &'a Keymap,
+    pub scroll: &'a mut usize,
+}
+
+impl Widget for Help<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let lines: Vec<Line> = self
+            .keymap
+            .bindings()
+            .map(|(keys, action)| {
+                let mut spans = vec![
+                    Span::styled(format!("{:>12}", keymap::format_sequence(keys)), KEY_STYLE),
+                    Span::raw("  "),
+                    Span::raw(self.keymap.describe(action).to_string()),
+                ];
+                if let Some(name) = action.name() {
+                    spans.push(Span::styled(format!("  {name}"), Style::new().dim()));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        let block = Block::new()
+            .title(Line::raw(t!("Keys")).left_aligned())
+            .title(Line::raw(t!("j/k: scroll  esc: close")).right_aligned())
+            .borders(Borders::ALL);
+        let inner = block.inner(popup);
+        let max = lines.len().saturating_sub(inner.height as usize);
+        *self.scroll = (*self.scroll).min(max);
+        Clear.render(popup, buf);
+        Paragraph::new(lines.clone())
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c900))
+            .block(block)
+            .scroll((*self.scroll as u16, 0))
+            .render(popup, buf);
+        super::scroll::scrollbar(inner, buf, lines.len(), *self.scroll);
+    }
+}
@@ -0,0 +1,246 @@
+//! The histories of branches, kept across runs so that a branch whose tip did
+//! not move is not walked again. Entries are keyed by full ref name and hold
+//! the tip they were read at, so a branch that moved simply misses and is read
+//! again. Commits that branches share are stored once. As the commits carry
+//! their notes, the whole cache is for one tip of the notes ref.
+
+use crate::{git, paths};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread::JoinHandle,
+};
+
+/// Entries not seen for this long are dropped from the cache file.
+const MAX_AGE: i64 = 30 * 24 * 60 * 60;
+/// How long an entry found unchanged goes without writing down that it was seen.
+const SEEN_RESOLUTION: i64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct File {
+    /// How many commits each history was read up to. Histories read up to
+    /// another count are of no use.
+    max_commits: usize,
+    /// The commit the notes ref was at when the commits were read.
+    #[serde(default)]
+    notes: Option<String>,
+    /// Entries by full ref name.
+    refs: HashMap<String, Entry>,
+    /// The commits of the entries, by id.
+    commits: HashMap<String, Commit>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Entry {
+    /// The commits, newest first, by id. The first is the tip.
+    commits: Vec<String>,
+    truncated: bool,
+    /// When the ref last pointed at the tip, in unix seconds.
+    seen: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Commit {
+    short: String,
+    summary: String,
+    message: String,
+    author: Option<String>,
+    email: Option<String>,
+    /// The commit time, in unix seconds.
+    time: i64,
+    note: Option<String>,
+}
+
+pub struct Histories {
+    /// Shared with the branch loader and the writer of the cache file, and
+    /// copied on change only while they still have it.
+    file: Arc<File>,
+    path: Option<PathBuf>,
+    /// Whether entries changed since the cache file was written.
+    dirty: bool,
+    /// The write of the cache file under way.
+    writing: Option<JoinHandle<()>>,
+}
+
+impl Histories {
+    /// An empty cache, written to the cache file of the repository at
+    /// `git_dir` once it is saved.
+    pub fn new(git_dir: &Path, max_commits: usize) -> Self {
+        let file = File {
+            max_commits,
+            ..Default::default()
+        };
+        let path = paths::repo_cache("history", git_dir);
+        Self {
+            file: Arc::new(file),
+            path,
+            dirty: false,
+            writing: None,
+        }
+    }
+
+    /// Reads the cache file of the repository at `git_dir`, if there is one
+    /// for histories of up to `max_commits`.
+    pub fn load(git_dir: &Path, max_commits: usize) -> Self {
+        let mut histories = Self::new(git_dir, max_commits);
+        let now = chrono::Utc::now().timestamp();
+        let file = histories
+            .path
+            .as_ref()
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|json| serde_json::from_slice::<File>(&json).ok())
+            .filter(|file| file.max_commits == max_commits);
+        if let Some(mut file) = file {
+            file.refs.retain(|_, entry| now - entry.seen < MAX_AGE);
+            histories.file = Arc::new(file);
+        }
+        histories
+    }
+
+    /// Looks up the cached histories for
+    /// [`git::Repository::stream_branch_tips`], which builds those of the
+    /// branches it reads as it goes. Nothing is cached while the notes ref is
+    /// at `notes` rather than where it was when the commits were read.
+    pub fn lookup(&self, notes: Option<git::Oid>) -> git::Cached {
+        if self.file.notes != notes.map(|id| id.to_string()) {
+            return Box::new(|_, _| None);
+        }
+        let file = Arc::clone(&self.file);
+        Box::new(move |refname, tip| {
+            let entry = file.refs.get(refname)?;
+            if *entry.commits.first()? != tip.to_string() {
+                return None;
+            }
+            let commits = entry
+                .commits
+                .iter()
+                .map(|id| commit(&file, id))
+                .collect::<Option<Vec<_>>>()?;
+            Some(git::History::new(commits, entry.truncated))
+        })
+    }
+
+    /// Remembers the histories of those of `branches` that were read in full,
+    /// with the notes ref at `notes`, until the next [`Histories::save`].
+    pub fn update<'a>(
+        &mut self,
+        branches: impl IntoIterator<Item = &'a git::Branch>,
+        notes: Option<git::Oid>,
+    ) {
+        let now = chrono::Utc::now().timestamp();
+        let notes = notes.map(|id| id.to_string());
+        let file = Arc::make_mut(&mut self.file);
+        if file.notes != notes {
+            // every commit may have another note now
+            file.refs.clear();
+            file.commits.clear();
+            file.notes = notes;
+            self.dirty = true;
+        }
+        for branch in branches {
+            // a symbolic ref has the history of the branch it points at
+            if branch.partial || branch.commits().is_empty() || branch.symbolic.is_some() {
+                continue;
+            }
+            for commit in branch.commits() {
+                let id = commit.id.to_string();
+                file.commits.entry(id).or_insert_with(|| Commit {
+                    short: commit.short.clone(),
+                    summary: commit.summary.clone(),
+                    message: commit.message.clone(),
+                    author: commit.author.name.clone(),
+                    email: commit.author.email.clone(),
+                    time: commit.timestamp.epoch(),
+                    note: commit.note.clone(),
+                });
+            }
+            let ids: Vec<String> = branch.commits().iter().map(|c| c.id.to_string()).collect();
+            let refname = branch.refname();
+            if let Some(entry) = file.refs.get_mut(&refname) {
+                if entry.commits == ids && entry.truncated == branch.truncated {
+                    // not written again for every run that finds it unchanged
+                    if now - entry.seen > SEEN_RESOLUTION {
+                        entry.seen = now;
+                        self.dirty = true;
+                    }
+                    continue;
+                }
+            }
+            let entry = Entry {
+                commits: ids,
+                truncated: branch.truncated,
+                seen: now,
+            };
+            file.refs.insert(refname, entry);
+            self.dirty = true;
+        }
+    }
+
+    /// Writes the cache file on a thread of its own if anything changed,
+    /// dropping the commits no entry has anymore. Failing to is not worth
+    /// bothering anyone about.
+    pub fn save(&mut self) {
+        // tried again once the write before is done
+        if !self.dirty || self.writing.as_ref().is_some_and(|w| !w.is_finished()) {
+            return;
+        }
+        self.dirty = false;
+        let file = Arc::make_mut(&mut self.file);
+        let used: HashSet<&String> = file.refs.values().flat_map(|e| &e.commits).collect();
+        let unused: Vec<String> = file
+            .commits
+            .keys()
+            .filter(|id| !used.contains(id))
+            .cloned()
+            .collect();
+        for id in unused {
+            file.commits.remove(&id);
+        }
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+        let file = Arc::clone(&self.file);
+        self.writing = Some(std::thread::spawn(move || {
+            let Ok(json) = serde_json::to_vec(&*file) else {
+                return;
+            };
+            if let Some(dir) = path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            let _ = std::fs::write(path, json);
+        }));
+    }
+
+    /// Saves what is left to and waits for the cache file to be written, as
+    /// before quitting.
+    pub fn finish(&mut self) {
+        if let Some(writing) = self.writing.take() {
+            let _ = writing.join();
+        }
+        self.save();
+        if let Some(writing) = self.writing.take() {
+            let _ = writing.join();
+        }
+    }
+}
+
+/// The cached commit `id`.
+fn commit(file: &File, id: &str) -> Option<git::Commit> {
+    let commit = file.commits.get(id)?;
+    let author = git::Author {
+        name: commit.author.clone(),
+        email: commit.email.clone(),
+    };
+    git::Commit::cached(
+        git::Oid::from_str(id).ok()?,
+        commit.short.clone(),
+        commit.summary.clone(),
+        commit.message.clone(),
+        author,
+        commit.time,
+        commit.note.clone(),
+    )
+    .ok()
+}
@@ -0,0 +1,86 @@
+//! The commits of a branch that changed a file, as a screen of its own, each
+//! of which opens the diff of the file in that commit.
+
+use crate::{git, prelude::*, redact, t};
+use ratatui::text::Span;
+
+pub struct FileHistory {
+    pub branch: String,
+    pub path: String,
+    pub changes: Vec<git::FileChange>,
+    pub state: ListState,
+}
+
+impl FileHistory {
+    pub fn new(branch: String, path: String, changes: Vec<git::FileChange>) -> Self {
+        let state = ListState::default().with_selected((!changes.is_empty()).then_some(0));
+        Self {
+            branch,
+            path,
+            changes,
+            state,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&git::FileChange> {
+        self.state.selected().and_then(|i| self.changes.get(i))
+    }
+
+    /// The arguments to `git show` that show the selected change to the file,
+    /// across the rename if the commit renamed it.
+    pub fn show_args(&self) -> Option<Vec<String>> {
+        let change = self.selected()?;
+        let mut args = vec![
+            change.commit.id.to_string(),
+            "--".to_string(),
+            change.path.clone(),
+        ];
+        args.extend(change.renamed_from.clone());
+        Some(args)
+    }
+}
+
+pub struct HistoryList<'a>(pub &'a mut FileHistory);
+
+impl Widget for HistoryList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let history = self.0;
+        let dim = Style::new().dim();
+        let mut items: Vec<ListItem> = history
+            .changes
+            .iter()
+            .map(|change| {
+                let c = &change.commit;
+                let author = c.author.name.as_deref().unwrap_or(t!("<none>"));
+                let mut lines = vec![Line::from(vec![
+                    Span::styled(format!("{} ", c.short), dim),
+                    Span::raw(format!("{}: ", c.timestamp)),
+                    Span::raw(format!("{}: ", redact::person(author))),
+                    Span::raw(redact::text(&c.summary).into_owned()),
+                ])];
+                if let Some(old) = &change.renamed_from {
+                    let rename = t!("renamed from {old} to {new}", old = old, new = change.path);
+                    lines.push(Line::styled(format!("        {rename}"), AMBER.c200));
+                }
+                ListItem::new(lines)
+            })
+            .collect();
+        let title = t!(
+            "History of {path} on {branch}",
+            path = history.path,
+            branch = redact::branch(&history.branch)
+        );
+        let block = Block::new()
+            .title(Line::raw(title).left_aligned())
+            .title(Line::raw(t!("enter: diff  esc: back")).right_aligned())
+            .borders(Borders::TOP);
+        if items.is_empty() {
+            let empty = t!("no commits changed {path}", path = history.path);
+            items.push(ListItem::new(Line::styled(empty, dim)));
+        }
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        StatefulWidget::render(list, area, buf, &mut history.state);
+    }
+}
@@ -0,0 +1,103 @@
+//! User-defined shell hooks that run around grit actions.
+
+use crate::{git::command::Location, platform, t};
+use color_eyre::{eyre::Context as _, Report};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Event {
+    PreCreate,
+    PostCreate,
+    PreRename,
+    PostRename,
+    PreDelete,
+    PostCheckout,
+    PostFetch,
+}
+
+impl Event {
+    /// The key of the hook in the `[hooks]` config table.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Event::PreCreate => "pre-create",
+            Event::PostCreate => "post-create",
+            Event::PreRename => "pre-rename",
+            Event::PostRename => "post-rename",
+            Event::PreDelete => "pre-delete",
+            Event::PostCheckout => "post-checkout",
+            Event::PostFetch => "post-fetch",
+        }
+    }
+
+    fn is_pre(&self) -> bool {
+        matches!(self, Event::PreCreate | Event::PreRename | Event::PreDelete)
+    }
+}
+
+/// The context passed to a hook as `GRIT_*` environment variables.
+#[derive(Default, Clone)]
+pub struct Context {
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+    /// The new name of a renamed branch, or the branch a new one starts from.
+    pub target: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Hooks {
+    commands: BTreeMap<String, String>,
+    loc: Location,
+}
+
+impl Hooks {
+    pub fn new(commands: BTreeMap<String, String>, loc: Location) -> Self {
+        Self { commands, loc }
+    }
+
+    /// Whether a hook is configured for `event`.
+    pub fn has(&self, event: Event) -> bool {
+        self.commands.contains_key(event.key())
+    }
+
+    /// Runs the hook for `event`, if one is configured, and returns its output.
+    /// A non-zero exit is an error, which aborts the action for `pre-*` hooks.
+    pub fn run(&self, event: Event, ctx: &Context) -> Result<Option<String>, Report> {
+        let Some(script) = self.commands.get(event.key()) else {
+            return Ok(None);
+        };
+        let mut cmd = platform::current().shell(script);
+        cmd.current_dir(self.loc.dir())
+            .env("GRIT_HOOK", event.key())
+            .env("GIT_DIR", &self.loc.git_dir)
+            // the address of the prompts is for the git grit runs only
+            .env_remove(crate::askpass::ENV);
+        let vars = [
+            ("GRIT_BRANCH", &ctx.branch),
+            ("GRIT_COMMIT", &ctx.commit),
+            ("GRIT_TARGET", &ctx.target),
+        ];
+        for (var, val) in vars {
+            if let Some(val) = val {
+                cmd.env(var, val);
+            }
+        }
+        let output = cmd
+            .output()
+            .wrap_err_with(|| format!("run {} hook", event.key()))?;
+        let text = [output.stdout, output.stderr]
+            .map(|b| String::from_utf8_lossy(&b).trim().to_string())
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !output.status.success() {
+            let action = if event.is_pre() {
+                t!("aborted")
+            } else {
+                t!("failed")
+            };
+            color_eyre::eyre::bail!("{} hook {action}: {text}", event.key());
+        }
+        Ok(Some(text).filter(|t| !t.is_empty()))
+    }
+}
@@ -0,0 +1,91 @@
+//! The hunks of the changes to one file, as a screen of its own drilled into
+//! from the work tree status, to stage or unstage one at a time the way
+//! `git add -p` does.
+
+use crate::{git, prelude::*, t};
+use ratatui::text::Span;
+
+pub struct HunkView {
+    pub path: String,
+    /// Whether the staged hunks are listed, rather than the unstaged ones.
+    pub staged: bool,
+    pub hunks: Vec<git::Hunk>,
+    pub state: ListState,
+}
+
+impl HunkView {
+    pub fn new(path: String, staged: bool, hunks: Vec<git::Hunk>) -> Self {
+        let state = ListState::default().with_selected((!hunks.is_empty()).then_some(0));
+        Self {
+            path,
+            staged,
+            hunks,
+            state,
+        }
+    }
+
+    /// Lists `hunks` instead, keeping the cursor where it was, so that the
+    /// hunk after one just staged is the one selected.
+    pub fn replace(&mut self, staged: bool, hunks: Vec<git::Hunk>) {
+        let at = self.state.selected().unwrap_or(0);
+        let at = (!hunks.is_empty()).then(|| at.min(hunks.len() - 1));
+        self.staged = staged;
+        self.hunks = hunks;
+        self.state.select(at);
+    }
+
+    pub fn selected(&self) -> Option<(usize, &git::Hunk)> {
+        let idx = self.state.selected()?;
+        Some((idx, self.hunks.get(idx)?))
+    }
+}
+
+pub struct HunkList<'a>(pub &'a mut HunkView);
+
+impl Widget for HunkList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let view = self.0;
+        let dim = Style::new().dim();
+        let mut items: Vec<ListItem> = view
+            .hunks
+            .iter()
+            .map(|hunk| {
+                let mut lines = vec![Line::styled(hunk.header.clone(), BLUE.c300)];
+                lines.extend(hunk.lines.iter().map(|(origin, text)| {
+                    let style = match origin {
+                        '+' => Style::new().fg(GREEN.c300),
+                        '-' => Style::new().fg(RED.c300),
+                        _ => Style::new(),
+                    };
+                    Line::from(Span::styled(format!("{origin}{text}"), style))
+                }));
+                ListItem::new(lines)
+            })
+            .collect();
+        if items.is_empty() {
+            let empty = match view.staged {
+                true => t!("nothing staged in {path}", path = view.path),
+                false => t!("nothing to stage in {path}", path = view.path),
+            };
+            items.push(ListItem::new(Line::styled(empty, dim)));
+        }
+        let (title, hints) = match view.staged {
+            true => (
+                t!("Staged changes to {path}", path = view.path),
+                t!("s: unstage hunk  tab: unstaged  esc: back"),
+            ),
+            false => (
+                t!("Unstaged changes to {path}", path = view.path),
+                t!("s: stage hunk  tab: staged  esc: back"),
+            ),
+        };
+        let block = Block::new()
+            .title(Line::raw(title).left_aligned())
+            .title(Line::raw(hints).right_aligned())
+            .borders(Borders::TOP);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        StatefulWidget::render(list, area, buf, &mut view.state);
+    }
+}
@@ -0,0 +1,105 @@
+//! Where the events of the app come from: the terminal, or a [`Script`] of
+//! them, which drives the app without one, as the integration tests do.
+
+use super::keymap::{self, Key};
+use crate::prelude::*;
+use ratatui::crossterm::event::KeyModifiers;
+use std::collections::VecDeque;
+
+pub trait Input {
+    /// Waits up to `timeout` for an event, returning whether one is ready.
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+
+    fn read(&mut self) -> io::Result<Event>;
+
+    /// Whether no more events are coming, which ends the session once what
+    /// they started is done.
+    fn is_done(&self) -> bool {
+        false
+    }
+
+    /// Whether the events come from the terminal the app draws on, which the
+    /// window title is set on.
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+/// The terminal grit runs in.
+pub struct Crossterm;
+
+impl Input for Crossterm {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        event::read()
+    }
+
+    fn is_terminal(&self) -> bool {
+        true
+    }
+}
+
+/// Events given up front, handed out as fast as the app takes them.
+#[derive(Default)]
+pub struct Script(VecDeque<Event>);
+
+impl Script {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Presses the keys of a sequence as the keymap writes them, e.g. `j j`,
+    /// `space v s` or `ctrl-e`.
+    ///
+    /// # Panics
+    ///
+    /// If a key cannot be parsed, which is a mistake in the script.
+    pub fn keys(mut self, seq: &str) -> Self {
+        let keys = keymap::parse_sequence(seq).unwrap_or_else(|| panic!("bad keys: {seq}"));
+        self.0.extend(keys.into_iter().map(press));
+        self
+    }
+
+    /// Types `text` a character at a time, e.g. into a prompt.
+    pub fn text(mut self, text: &str) -> Self {
+        let keys = text.chars().map(|c| Key {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::NONE,
+        });
+        self.0.extend(keys.map(press));
+        self
+    }
+
+    pub fn event(mut self, event: Event) -> Self {
+        self.0.push_back(event);
+        self
+    }
+}
+
+fn press(key: Key) -> Event {
+    Event::Key(KeyEvent::new(key.code, key.modifiers))
+}
+
+impl Input for Script {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        if self.0.is_empty() {
+            // what the app waits for happens in the background meanwhile
+            std::thread::sleep(timeout.min(Duration::from_millis(5)));
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        self.0
+            .pop_front()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "the script ran out"))
+    }
+
+    fn is_done(&self) -> bool {
+        self.0.is_empty()
+    }
+}
@@ -0,0 +1,391 @@
+//! Background work queue. Jobs run one at a time on a worker thread so that two
+//! mutations never race on the same repository.
+
+use crate::{prelude::*, redact, t};
+use ratatui::{text::Span, widgets::Clear};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::Instant,
+};
+
+pub type JobId = u64;
+
+/// The result of a finished job: a message for the notification area.
+pub type JobResult = EResult<String>;
+
+type Task = Box<dyn FnOnce(&Cancel) -> JobResult + Send>;
+
+/// Work that writes signed commits, run while the terminal is handed over
+/// rather than as a job.
+pub type Foreground = Box<dyn FnOnce() -> JobResult + Send>;
+
+/// Handed to running jobs: a cooperative cancellation flag, and where to
+/// report how far along they are.
+#[derive(Clone, Default)]
+pub struct Cancel {
+    cancelled: Arc<AtomicBool>,
+    progress: Arc<Mutex<Option<String>>>,
+}
+
+impl Cancel {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Reports progress, shown in the footer while the job runs.
+    pub fn progress(&self, text: &str) {
+        if let Ok(mut progress) = self.progress.lock() {
+            *progress = Some(text.to_string());
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    Queued,
+    Running,
+    Cancelling,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl Status {
+    fn label(&self) -> &'static str {
+        match self {
+            Status::Queued => t!("queued"),
+            Status::Running => t!("running"),
+            Status::Cancelling => t!("cancelling"),
+            Status::Done => t!("done"),
+            Status::Failed => t!("failed"),
+            Status::Cancelled => t!("cancelled"),
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        matches!(self, Status::Queued | Status::Running | Status::Cancelling)
+    }
+}
+
+pub struct Job {
+    pub id: JobId,
+    pub name: String,
+    pub status: Status,
+    /// Whether the branch list should be reloaded once the job finishes.
+    pub refresh: bool,
+    started: Option<Instant>,
+    elapsed: Option<Duration>,
+}
+
+/// A job that finished since the last poll.
+pub struct Finished {
+    pub id: JobId,
+    pub name: String,
+    pub refresh: bool,
+    pub result: Option<JobResult>,
+}
+
+pub struct Jobs {
+    jobs: Vec<Job>,
+    queue: VecDeque<(JobId, Task)>,
+    running: Option<(JobId, Cancel)>,
+    next_id: JobId,
+    tx: mpsc::Sender<(JobId, JobResult)>,
+    rx: mpsc::Receiver<(JobId, JobResult)>,
+    pub state: ListState,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            jobs: vec![],
+            queue: VecDeque::new(),
+            running: None,
+            next_id: 1,
+            tx,
+            rx,
+            state: ListState::default(),
+        }
+    }
+}
+
+impl Jobs {
+    /// Queues a job. It starts as soon as every job queued before it has finished.
+    pub fn submit<F>(&mut self, name: impl Into<String>, refresh: bool, task: F) -> JobId
+    where
+        F: FnOnce(&Cancel) -> JobResult + Send + 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        let name = name.into();
+        tracing::debug!(job = id, name, "queued");
+        self.jobs.push(Job {
+            id,
+            name,
+            status: Status::Queued,
+            refresh,
+            started: None,
+            elapsed: None,
+        });
+        self.queue.push_back((id, Box::new(task)));
+        self.start_next();
+        id
+    }
+
+    fn start_next(&mut self) {
+        if self.running.is_some() {
+            return;
+        }
+        let Some((id, task)) = self.queue.pop_front() else {
+            return;
+        };
+        let cancel = Cancel::default();
+        if let Some(job) = self.job_mut(id) {
+            job.status = Status::Running;
+            job.started = Some(Instant::now());
+        }
+        let tx = self.tx.clone();
+        let token = cancel.clone();
+        std::thread::spawn(move || {
+            let _span = tracing::info_span!("job", id).entered();
+            let res = task(&token);
+            match &res {
+                Ok(_) => tracing::info!("done"),
+                Err(err) => tracing::warn!("failed: {err:#}"),
+            }
+            let _ = tx.send((id, res));
+        });
+        self.running = Some((id, cancel));
+    }
+
+    /// Collects finished jobs and starts the next queued one.
+    pub fn poll(&mut self) -> Vec<Finished> {
+        let mut finished = vec![];
+        while let Ok((id, result)) = self.rx.try_recv() {
+            self.running = None;
+            if let Some(job) = self.job_mut(id) {
+                let cancelled = job.status == Status::Cancelling;
+                job.status = match (&result, cancelled) {
+                    (_, true) => Status::Cancelled,
+                    (Ok(_), false) => Status::Done,
+                    (Err(_), false) => Status::Failed,
+                };
+                job.elapsed = job.started.map(|s| s.elapsed());
+                finished.push(Finished {
+                    id,
+                    name: job.name.clone(),
+                    refresh: job.refresh,
+                    result: (!cancelled).then_some(result),
+                });
+            }
+            self.start_next();
+        }
+        finished
+    }
+
+    /// Cancels a queued job outright, or asks a running one to stop.
+    pub fn cancel(&mut self, id: JobId) {
+        if let Some(pos) = self.queue.iter().position(|(qid, _)| *qid == id) {
+            self.queue.remove(pos);
+            if let Some(job) = self.job_mut(id) {
+                job.status = Status::Cancelled;
+            }
+            return;
+        }
+        if let Some((rid, cancel)) = &self.running {
+            if *rid == id {
+                cancel.cancel();
+                if let Some(job) = self.job_mut(id) {
+                    job.status = Status::Cancelling;
+                }
+            }
+        }
+    }
+
+    /// Cancels every job that has not finished.
+    pub fn cancel_all(&mut self) {
+        let ids: Vec<JobId> = self
+            .jobs
+            .iter()
+            .filter(|j| j.status.is_active())
+            .map(|j| j.id)
+            .collect();
+        for id in ids {
+            self.cancel(id);
+        }
+    }
+
+    pub fn cancel_selected(&mut self) {
+        let id = self
+            .state
+            .selected()
+            .and_then(|i| self.jobs.iter().rev().nth(i))
+            .map(|j| j.id);
+        if let Some(id) = id {
+            self.cancel(id);
+        }
+    }
+
+    pub fn running(&self) -> usize {
+        usize::from(self.running.is_some())
+    }
+
+    /// The progress the running job last reported.
+    pub fn progress(&self) -> Option<String> {
+        let (_, cancel) = self.running.as_ref()?;
+        cancel.progress.lock().ok()?.clone()
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.jobs.iter().any(|j| j.status.is_active())
+    }
+
+    fn job_mut(&mut self, id: JobId) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|j| j.id == id)
+    }
+}
+
+/// A popup listing all jobs of the session, newest first.
+pub struct JobList<'a>(pub &'a mut Jobs);
+
+impl Widget for JobList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let items: Vec<ListItem> = self
+            .0
+            .jobs
+            .iter()
+            .rev()
+            .map(|job| {
+                let style = match job.status {
+                    Status::Failed => Style::new().fg(RED.c300),
+                    Status::Done => Style::new().fg(GREEN.c300),
+                    Status::Running | Status::Cancelling => Style::new().fg(BLUE.c300),
+                    _ => Style::new().dim(),
+                };
+                let elapsed = job
+                    .elapsed
+                    .or_else(|| job.started.map(|s| s.elapsed()))
+                    .map(|d| format!(" {:.1}s", d.as_secs_f32()))
+                    .unwrap_or_default();
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<11}", job.status.label()), style),
+                    Span::raw(redact::text(&job.name).into_owned()),
+                    Span::styled(elapsed, Style::new().dim()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::new()
+                    .title(Line::raw(t!("Jobs")).left_aligned())
+                    .title(Line::raw(t!("x: cancel  esc: close")).right_aligned())
+                    .borders(Borders::ALL),
+            )
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c800))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        Clear.render(popup, buf);
+        StatefulWidget::render(list, popup, buf, &mut self.0.state);
+    }
+}
+
+/// How far quitting got while jobs were still running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quit {
+    /// Asking whether to wait for them or cancel them.
+    Asking,
+    /// Quitting once they are done, or cancelled.
+    Waiting,
+}
+
+/// The jobs that have not finished, when quitting would cut them off midway.
+pub struct QuitPrompt<'a> {
+    pub jobs: &'a Jobs,
+    pub quit: Quit,
+}
+
+impl Widget for QuitPrompt<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let jobs: Vec<&Job> = self
+            .jobs
+            .jobs
+            .iter()
+            .filter(|j| j.status.is_active())
+            .collect();
+        let mut lines: Vec<Line> = jobs
+            .iter()
+            .map(|job| {
+                let style = match job.status {
+                    Status::Queued => Style::new().dim(),
+                    _ => Style::new().fg(BLUE.c300),
+                };
+                let elapsed = job
+                    .started
+                    .map(|s| format!(" {:.1}s", s.elapsed().as_secs_f32()))
+                    .unwrap_or_default();
+                Line::from(vec![
+                    Span::styled(format!("{:<11}", job.status.label()), style),
+                    Span::raw(redact::text(&job.name).into_owned()),
+                    Span::styled(elapsed, Style::new().dim()),
+                ])
+            })
+            .collect();
+        // git reports over several lines, of which the last is the latest
+        let progress = self.jobs.progress();
+        let latest = progress
+            .as_deref()
+            .and_then(|p| p.lines().rev().find(|l| !l.trim().is_empty()));
+        if let Some(latest) = latest {
+            lines.push(Line::default());
+            lines.push(Line::styled(
+                redact::text(latest).into_owned(),
+                Style::new().fg(SLATE.c300),
+            ));
+        }
+        let (title, hint) = match self.quit {
+            Quit::Asking => (
+                t!("Jobs still running"),
+                t!("w: wait and quit  c: cancel them and quit  esc: stay"),
+            ),
+            Quit::Waiting => (
+                t!("Quitting once the jobs are done"),
+                t!("c: cancel them  esc: stay"),
+            ),
+        };
+        let width = (hint.chars().count() as u16 + 4)
+            .max(area.width / 2)
+            .min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let block = Block::new()
+            .title(Line::raw(title).left_aligned())
+            .title_bottom(Line::raw(hint).right_aligned())
+            .borders(Borders::ALL)
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c800));
+        Clear.render(popup, buf);
+        Paragraph::new(lines).block(block).render(popup, buf);
+    }
+}
@@ -0,0 +1,750 @@
+use crate::{prelude::*, t};
+use ratatui::crossterm::event::KeyModifiers;
+use std::{fmt::Display, time::Instant};
+
+/// How long a partially typed key sequence waits for its next key.
+pub const CHORD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    SelectNone,
+    SelectNext,
+    SelectPrevious,
+    SelectFirst,
+    SelectLast,
+    CycleSort,
+    PickSort,
+    ToggleBranch,
+    BranchMenu,
+    ShowNotifications,
+    ShowJobs,
+    ToggleLog,
+    FetchAll,
+    PruneRemotes,
+    Deepen,
+    SetRemoteHead,
+    ScrollDetailsDown,
+    ScrollDetailsUp,
+    CreateBranch,
+    ToggleMark,
+    ClearMarks,
+    BatchRename,
+    RenameBranch,
+    CommandLine,
+    ShowRemotes,
+    ShowAccounts,
+    EditNote,
+    ToggleAuthorColors,
+    ToggleHeat,
+    ToggleGroups,
+    ToggleStacks,
+    Restack,
+    ToggleReplace,
+    SelectCommits,
+    SquashBranch,
+    MergeBranch,
+    RebaseOnto,
+    CreatePullRequest,
+    OpenPullRequest,
+    DeleteBranch,
+    DeleteBranches,
+    Push,
+    PushMarked,
+    DiffWorktree,
+    Stash,
+    ShowStats,
+    ShowHelp,
+    ShowAuthors,
+    ShowDivergence,
+    Snapshot,
+    ExportBranches,
+    JumpMode,
+    FilterBranches,
+    SetMark,
+    JumpToMark,
+    ShowMarks,
+    FilterAuthor,
+    FileHistory,
+    ShowStatus,
+    ShowRefs,
+    ShowSettings,
+    ShowGitConfig,
+    ShowConflicts,
+    Commit,
+    JumpToWorktree,
+    OpenRepository,
+    Pickaxe,
+    PickView,
+    ToggleStale,
+    DeleteStale,
+    LoadAllBranches,
+    SetBranchState,
+    EditBranchNote,
+    ToggleFollow,
+    BranchesTab,
+    TagsTab,
+    RemotesTab,
+    NextTab,
+    PreviousTab,
+    /// A saved view from the config, by index.
+    View(usize),
+    /// A user command from the config, by index.
+    Command(usize),
+}
+
+impl Action {
+    /// Whether the action switches tabs, which it does from any of them.
+    pub fn switches_tab(self) -> bool {
+        matches!(
+            self,
+            Action::BranchesTab
+                | Action::TagsTab
+                | Action::RemotesTab
+                | Action::NextTab
+                | Action::PreviousTab
+        )
+    }
+
+    /// The name of the action in the `[keys]` table of the config, e.g.
+    /// `fetch-all`. User commands have none.
+    pub fn name(self) -> Option<String> {
+        if let Action::Command(_) | Action::View(_) = self {
+            return None;
+        }
+        let mut name = String::new();
+        for c in format!("{self:?}").chars() {
+            if c.is_uppercase() && !name.is_empty() {
+                name.push('-');
+            }
+            name.push(c.to_ascii_lowercase());
+        }
+        Some(name)
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => t!("quit"),
+            Action::SelectNone => t!("clear selection"),
+            Action::SelectNext => t!("next branch"),
+            Action::SelectPrevious => t!("previous branch"),
+            Action::SelectFirst => t!("first branch"),
+            Action::SelectLast => t!("last branch"),
+            Action::CycleSort => t!("cycle sort"),
+            Action::PickSort => t!("sort by…"),
+            Action::ToggleBranch => t!("check out branch"),
+            Action::BranchMenu => t!("actions on this branch…"),
+            Action::ShowNotifications => t!("notifications"),
+            Action::ShowJobs => t!("jobs"),
+            Action::ToggleLog => t!("log pane"),
+            Action::FetchAll => t!("fetch all remotes"),
+            Action::PruneRemotes => t!("prune gone remote branches"),
+            Action::Deepen => t!("deepen shallow history…"),
+            Action::SetRemoteHead => t!("set remote HEAD…"),
+            Action::ScrollDetailsDown => t!("scroll details down"),
+            Action::ScrollDetailsUp => t!("scroll details up"),
+            Action::CreateBranch => t!("create branch"),
+            Action::ToggleMark => t!("mark branch"),
+            Action::ClearMarks => t!("clear marks"),
+            Action::BatchRename => t!("rename marked branches"),
+            Action::RenameBranch => t!("rename branch"),
+            Action::CommandLine => t!("run command…"),
+            Action::ShowRemotes => t!("remotes"),
+            Action::ShowAccounts => t!("hosting accounts"),
+            Action::ToggleAuthorColors => t!("color commits by author"),
+            Action::ShowStats => t!("statistics"),
+            Action::ShowHelp => t!("help"),
+            Action::ToggleHeat => t!("activity column"),
+            Action::ToggleGroups => t!("group by upstream status"),
+            Action::ToggleStacks => t!("stack branches"),
+            Action::Restack => t!("restack branches on this one…"),
+            Action::ToggleReplace => t!("show replaced or original history"),
+            Action::SelectCommits => t!("select commits"),
+            Action::SquashBranch => t!("squash into one commit"),
+            Action::MergeBranch => t!("merge into the checked out branch…"),
+            Action::RebaseOnto => t!("rebase the checked out branch onto this one…"),
+            Action::CreatePullRequest => t!("create pull request"),
+            Action::OpenPullRequest => t!("open pull request in browser"),
+            Action::DeleteBranch => t!("delete branch…"),
+            Action::DeleteBranches => t!("delete branches…"),
+            Action::Push => t!("push branch"),
+            Action::PushMarked => t!("push marked branches"),
+            Action::DiffWorktree => t!("diff work tree against branch"),
+            Action::Stash => t!("stash changes"),
+            Action::ShowAuthors => t!("author colors"),
+            Action::ShowDivergence => t!("divergence from upstream"),
+            Action::Snapshot => t!("save screen to a file…"),
+            Action::ExportBranches => t!("export the branch list…"),
+            Action::JumpMode => t!("jump to branches by letter"),
+            Action::FilterBranches => t!("filter branches…"),
+            Action::SetMark => t!("set mark…"),
+            Action::JumpToMark => t!("jump to mark…"),
+            Action::ShowMarks => t!("marks"),
+            Action::FilterAuthor => t!("show commits by author…"),
+            Action::FileHistory => t!("history of a file…"),
+            Action::ShowStatus => t!("work tree status"),
+            Action::ShowRefs => t!("browse refs"),
+            Action::ShowSettings => t!("settings"),
+            Action::ShowGitConfig => t!("git config"),
+            Action::ShowConflicts => t!("keymap conflicts"),
+            Action::Commit => t!("commit staged changes…"),
+            Action::JumpToWorktree => t!("go to the branch's worktree"),
+            Action::OpenRepository => t!("open another repository"),
+            Action::Pickaxe => t!("find commits adding or removing a string…"),
+            Action::EditNote => t!("edit note on tip commit"),
+            Action::PickView => t!("saved views…"),
+            Action::ToggleStale => t!("show only stale branches"),
+            Action::DeleteStale => t!("delete stale branches…"),
+            Action::LoadAllBranches => t!("load every branch"),
+            Action::SetBranchState => t!("set review state…"),
+            Action::EditBranchNote => t!("edit branch note…"),
+            Action::ToggleFollow => t!("follow branch, fetching it and marking new commits"),
+            Action::BranchesTab => t!("branches tab"),
+            Action::TagsTab => t!("tags tab"),
+            Action::RemotesTab => t!("remotes tab"),
+            Action::NextTab => t!("next tab"),
+            Action::PreviousTab => t!("previous tab"),
+            Action::View(_) => t!("saved view"),
+            Action::Command(_) => t!("user command"),
+        }
+    }
+}
+
+/// A single key press, ignoring its kind and state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers;
+        // shift is already part of the char for letters and symbols, and of
+        // backtab
+        if let KeyCode::Char(_) | KeyCode::BackTab = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+impl Key {
+    /// Parses keys such as `j`, `G`, `enter`, `space` or `ctrl-s`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        while let Some((prefix, tail)) = rest.split_once('-').filter(|(_, t)| !t.is_empty()) {
+            modifiers |= match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" | "c" => KeyModifiers::CONTROL,
+                "alt" | "a" | "m" => KeyModifiers::ALT,
+                "shift" | "s" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+            rest = tail;
+        }
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "enter" | "cr" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" | "bs" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+        Some(Self { code, modifiers })
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::BackTab => write!(f, "backtab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::Up => write!(f, "up"),
+            KeyCode::Down => write!(f, "down"),
+            KeyCode::Left => write!(f, "left"),
+            KeyCode::Right => write!(f, "right"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Parses a whitespace separated key sequence such as `g g` or `space b d`.
+pub fn parse_sequence(s: &str) -> Option<Vec<Key>> {
+    s.split_whitespace().map(Key::parse).collect()
+}
+
+pub fn format_sequence(keys: &[Key]) -> String {
+    keys.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Maps key sequences to actions.
+pub struct Keymap {
+    leader: Key,
+    bindings: Vec<(Vec<Key>, Action)>,
+    groups: Vec<(Vec<Key>, &'static str)>,
+    /// The names of user commands, indexed as in [`Action::Command`].
+    commands: Vec<String>,
+    /// The names of saved views, indexed as in [`Action::View`].
+    views: Vec<String>,
+    /// Bindings that user commands or `[keys]` took the keys of, built-in or
+    /// not.
+    replaced: Vec<(Vec<Key>, Action)>,
+}
+
+/// What the next key of a sequence leads to.
+pub enum Next {
+    Group(&'static str),
+    Action(Action),
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self {
+            leader: Key {
+                code: KeyCode::Char(' '),
+                modifiers: KeyModifiers::NONE,
+            },
+            bindings: vec![],
+            groups: vec![],
+            commands: vec![],
+            views: vec![],
+            replaced: vec![],
+        };
+        for (seq, label) in [
+            ("space b", t!("branch")),
+            ("space r", t!("remote")),
+            ("space v", t!("view")),
+            ("space g", t!("git")),
+        ] {
+            keymap.group(seq, label);
+        }
+        for (seq, action) in [
+            ("q", Action::Quit),
+            ("h", Action::SelectNone),
+            ("left", Action::SelectNone),
+            ("j", Action::SelectNext),
+            ("down", Action::SelectNext),
+            ("k", Action::SelectPrevious),
+            ("up", Action::SelectPrevious),
+            ("g g", Action::SelectFirst),
+            ("home", Action::SelectFirst),
+            ("G", Action::SelectLast),
+            ("end", Action::SelectLast),
+            ("s", Action::CycleSort),
+            ("S", Action::PickSort),
+            ("l", Action::ToggleBranch),
+            ("right", Action::ToggleBranch),
+            ("enter", Action::BranchMenu),
+            ("N", Action::ShowNotifications),
+            ("J", Action::ShowJobs),
+            ("n", Action::CreateBranch),
+            ("space b n", Action::CreateBranch),
+            ("d", Action::DeleteBranch),
+            ("x", Action::ToggleMark),
+            ("X", Action::ClearMarks),
+            ("space b x", Action::ToggleMark),
+            ("space b X", Action::ClearMarks),
+            ("space b R", Action::BatchRename),
+            ("r", Action::RenameBranch),
+            ("space b m", Action::RenameBranch),
+            ("space b s", Action::SquashBranch),
+            ("M", Action::MergeBranch),
+            ("R", Action::RebaseOnto),
+            ("space g m", Action::MergeBranch),
+            ("space g b", Action::RebaseOnto),
+            ("space b p", Action::CreatePullRequest),
+            ("o", Action::OpenPullRequest),
+            ("space b O", Action::OpenPullRequest),
+            ("space b w", Action::JumpToWorktree),
+            ("space b d", Action::DeleteBranches),
+            ("p", Action::Push),
+            ("space r p", Action::Push),
+            ("space r P", Action::PushMarked),
+            ("ctrl-e", Action::ScrollDetailsDown),
+            ("ctrl-y", Action::ScrollDetailsUp),
+            ("space q", Action::Quit),
+            ("?", Action::ShowHelp),
+            ("space ?", Action::ShowHelp),
+            ("space r f", Action::FetchAll),
+            ("space r x", Action::PruneRemotes),
+            ("space r d", Action::Deepen),
+            ("space r h", Action::SetRemoteHead),
+            ("space r r", Action::ShowRemotes),
+            ("space r a", Action::ShowAccounts),
+            (":", Action::CommandLine),
+            ("space g n", Action::EditNote),
+            ("v", Action::SelectCommits),
+            ("tab", Action::NextTab),
+            ("backtab", Action::PreviousTab),
+            ("1", Action::BranchesTab),
+            ("2", Action::TagsTab),
+            ("3", Action::RemotesTab),
+            ("space v 1", Action::BranchesTab),
+            ("space v 2", Action::TagsTab),
+            ("space v 3", Action::RemotesTab),
+            ("space g v", Action::SelectCommits),
+            ("space g d", Action::DiffWorktree),
+            ("ctrl-s", Action::Stash),
+            ("ctrl-o", Action::OpenRepository),
+            ("space g s", Action::Stash),
+            ("space g :", Action::CommandLine),
+            ("space b o", Action::ToggleBranch),
+            ("space b a", Action::BranchMenu),
+            ("space v s", Action::CycleSort),
+            ("space v S", Action::PickSort),
+            ("space v g", Action::SelectFirst),
+            ("space v G", Action::SelectLast),
+            ("space v n", Action::ShowNotifications),
+            ("space v j", Action::ShowJobs),
+            ("`", Action::ToggleLog),
+            ("space v l", Action::ToggleLog),
+            ("space v c", Action::ToggleAuthorColors),
+            ("space v a", Action::ShowAuthors),
+            ("space v h", Action::ToggleHeat),
+            ("space v u", Action::ToggleGroups),
+            ("space v t", Action::ToggleStacks),
+            ("space b r", Action::Restack),
+            ("space b u", Action::ShowDivergence),
+            ("space v r", Action::ToggleReplace),
+            ("space v d", Action::ShowStats),
+            ("space v e", Action::Snapshot),
+            ("space v x", Action::ExportBranches),
+            ("f", Action::FetchAll),
+            ("space v f", Action::JumpMode),
+            ("/", Action::FilterBranches),
+            ("m", Action::SetMark),
+            ("'", Action::JumpToMark),
+            ("space v m", Action::ShowMarks),
+            ("space v o", Action::ShowSettings),
+            ("space v k", Action::ShowConflicts),
+            ("a", Action::FilterAuthor),
+            ("space g a", Action::FilterAuthor),
+            ("space g h", Action::FileHistory),
+            ("space g w", Action::ShowStatus),
+            ("space g r", Action::ShowRefs),
+            ("space g c", Action::Commit),
+            ("space g C", Action::ShowGitConfig),
+            ("space g p", Action::Pickaxe),
+            ("V", Action::PickView),
+            ("z", Action::ToggleStale),
+            ("space v z", Action::ToggleStale),
+            ("D", Action::DeleteStale),
+            ("space b D", Action::DeleteStale),
+            ("space b L", Action::LoadAllBranches),
+            ("space b t", Action::SetBranchState),
+            ("space b e", Action::EditBranchNote),
+            ("space b f", Action::ToggleFollow),
+            ("space v w", Action::PickView),
+        ] {
+            keymap.bind(seq, action);
+        }
+        keymap
+    }
+}
+
+impl Keymap {
+    /// Binds a key sequence, replacing any previous binding of the same sequence.
+    pub fn bind(&mut self, seq: &str, action: Action) {
+        let Some(keys) = parse_sequence(seq) else {
+            return;
+        };
+        self.bindings.retain(|(k, _)| k != &keys);
+        self.bindings.push((keys, action));
+    }
+
+    /// Binds the built-in action called `name` to `seqs` instead of its default
+    /// keys, failing with a message fit to show if the name or a sequence is
+    /// unknown.
+    pub fn rebind(&mut self, name: &str, seqs: &[String]) -> Result<(), String> {
+        let Some(action) = self
+            .bindings
+            .iter()
+            .map(|(_, a)| *a)
+            .find(|a| a.name().as_deref() == Some(name))
+        else {
+            return Err(t!("unknown action {name} in [keys]", name = name));
+        };
+        let mut parsed = vec![];
+        for seq in seqs {
+            match parse_sequence(seq) {
+                Some(keys) => parsed.push(keys),
+                None => return Err(t!("invalid key {key} for {name}", key = seq, name = name)),
+            }
+        }
+        self.bindings.retain(|(_, a)| *a != action);
+        for keys in parsed {
+            if let Some(other) = self.exact(&keys) {
+                self.replaced.push((keys.clone(), other));
+            }
+            self.bindings.retain(|(k, _)| k != &keys);
+            self.bindings.push((keys, action));
+        }
+        Ok(())
+    }
+
+    /// Binds the next saved view to `seq`, or to no keys if it is `None`. Keys
+    /// that `[keys]` gave to an action are taken over and listed as a conflict.
+    pub fn bind_view(&mut self, seq: Option<&str>, name: String) {
+        if let Some(keys) = seq.and_then(parse_sequence) {
+            if let Some(action) = self.exact(&keys) {
+                self.replaced.push((keys.clone(), action));
+            }
+            self.bindings.retain(|(k, _)| k != &keys);
+            self.bindings.push((keys, Action::View(self.views.len())));
+        }
+        self.views.push(name);
+    }
+
+    /// Binds a user command, returning false if the sequence can't be parsed.
+    pub fn bind_command(&mut self, seq: &str, name: String) -> bool {
+        let Some(keys) = parse_sequence(seq) else {
+            return false;
+        };
+        if let Some(action) = self.exact(&keys) {
+            self.replaced.push((keys, action));
+        }
+        self.bind(seq, Action::Command(self.commands.len()));
+        self.commands.push(name);
+        true
+    }
+
+    /// Every binding, in the order they were bound.
+    pub fn bindings(&self) -> impl Iterator<Item = (&[Key], Action)> {
+        self.bindings
+            .iter()
+            .map(|(keys, action)| (keys.as_slice(), *action))
+    }
+
+    /// Bindings that keep others from being reached, each with the one that
+    /// wins: those whose keys a user command or a saved view took, and those whose keys start a
+    /// longer sequence, which only run after a pause, or never after the
+    /// leader. An action left without keys is pointed out.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut res = vec![];
+        for (keys, lost) in &self.replaced {
+            let winner = self.exact(keys).map_or("?", |a| self.describe(a));
+            let mut line = t!(
+                "{keys}: {winner} replaces {lost}",
+                keys = format_sequence(keys),
+                winner = winner,
+                lost = self.describe(*lost)
+            );
+            if !self.is_bound(*lost) {
+                line.push_str(t!(", which has no other keys"));
+            }
+            res.push(line);
+        }
+        for (keys, action) in &self.bindings {
+            let Some((longer, winner)) = self
+                .bindings
+                .iter()
+                .find(|(k, _)| k.len() > keys.len() && k.starts_with(keys))
+            else {
+                continue;
+            };
+            let (seq, lost) = (format_sequence(keys), self.describe(*action));
+            let (longer, winner) = (format_sequence(longer), self.describe(*winner));
+            // sequences after the leader wait for as long as the menu is open
+            let mut line = match self.is_leader(keys[0]) {
+                true => t!(
+                    "{keys}: {action} never runs, {longer} ({winner}) starts the same way",
+                    keys = seq,
+                    action = lost,
+                    longer = longer,
+                    winner = winner
+                ),
+                false => t!(
+                    "{keys}: {action} only runs after a pause, {longer} ({winner}) starts the same way",
+                    keys = seq,
+                    action = lost,
+                    longer = longer,
+                    winner = winner
+                ),
+            };
+            let elsewhere = self.bindings.iter().any(|(k, a)| a == action && k != keys);
+            if self.is_leader(keys[0]) && !elsewhere {
+                line.push_str(t!(", which has no other keys"));
+            }
+            res.push(line);
+        }
+        res
+    }
+
+    /// Whether any keys run `action`.
+    fn is_bound(&self, action: Action) -> bool {
+        self.bindings.iter().any(|(_, a)| *a == action)
+    }
+
+    /// The shortest keys that run `action`, as shown to the user.
+    pub fn keys(&self, action: Action) -> Option<String> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .min_by_key(|(keys, _)| keys.len())
+            .map(|(keys, _)| format_sequence(keys))
+    }
+
+    /// What an action does, as shown in the leader menu.
+    pub fn describe(&self, action: Action) -> &str {
+        match action {
+            Action::Command(idx) => self.commands.get(idx).map_or("?", String::as_str),
+            Action::View(idx) => self.views.get(idx).map_or("?", String::as_str),
+            action => action.description(),
+        }
+    }
+
+    /// Labels the group of bindings that start with `seq` in the leader menu.
+    pub fn group(&mut self, seq: &str, label: &'static str) {
+        if let Some(keys) = parse_sequence(seq) {
+            self.groups.retain(|(k, _)| k != &keys);
+            self.groups.push((keys, label));
+        }
+    }
+
+    pub fn is_leader(&self, key: Key) -> bool {
+        key == self.leader
+    }
+
+    pub fn group_label(&self, keys: &[Key]) -> Option<&'static str> {
+        self.groups
+            .iter()
+            .find(|(k, _)| k == keys)
+            .map(|(_, label)| *label)
+    }
+
+    /// Lists the keys that can follow `prefix`, and what each of them leads to.
+    pub fn next(&self, prefix: &[Key]) -> Vec<(Key, Next)> {
+        let mut res: Vec<(Key, Next)> = vec![];
+        for (keys, action) in &self.bindings {
+            if keys.len() <= prefix.len() || !keys.starts_with(prefix) {
+                continue;
+            }
+            let key = keys[prefix.len()];
+            if res.iter().any(|(k, _)| *k == key) {
+                continue;
+            }
+            let next = if keys.len() == prefix.len() + 1 {
+                Next::Action(*action)
+            } else {
+                let group = &keys[..=prefix.len()];
+                Next::Group(self.group_label(group).unwrap_or("…"))
+            };
+            res.push((key, next));
+        }
+        res
+    }
+
+    pub fn exact(&self, keys: &[Key]) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(k, _)| k == keys)
+            .map(|(_, action)| *action)
+    }
+
+    fn is_prefix(&self, keys: &[Key]) -> bool {
+        self.bindings
+            .iter()
+            .any(|(k, _)| k.len() > keys.len() && k.starts_with(keys))
+    }
+}
+
+pub enum Resolution {
+    Action(Action),
+    Pending,
+    Unbound,
+}
+
+/// Accumulates key presses until they resolve to an action.
+#[derive(Default)]
+pub struct Chord {
+    pending: Vec<Key>,
+    deadline: Option<Instant>,
+}
+
+impl Chord {
+    pub fn pending(&self) -> &[Key] {
+        &self.pending
+    }
+
+    /// How long until the pending sequence times out, if there is one.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn push(&mut self, keymap: &Keymap, key: Key) -> Resolution {
+        self.pending.push(key);
+        if keymap.is_prefix(&self.pending) {
+            // leader sequences wait for as long as the menu is open
+            self.deadline = if keymap.is_leader(self.pending[0]) {
+                None
+            } else {
+                Some(Instant::now() + CHORD_TIMEOUT)
+            };
+            return Resolution::Pending;
+        }
+        let res = match keymap.exact(&self.pending) {
+            Some(action) => Resolution::Action(action),
+            None => Resolution::Unbound,
+        };
+        self.clear();
+        res
+    }
+
+    /// Resolves a timed out sequence to the action bound to exactly those keys, if any.
+    pub fn expire(&mut self, keymap: &Keymap) -> Option<Action> {
+        if self.timeout().is_some_and(|t| t.is_zero()) {
+            let action = keymap.exact(&self.pending);
+            self.clear();
+            return action;
+        }
+        None
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.deadline = None;
+    }
+}
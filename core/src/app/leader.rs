@@ -0,0 +1,56 @@
+use super::keymap::{Key, Keymap, Next};
+use crate::{prelude::*, t};
+use ratatui::{text::Span, widgets::Clear};
+
+const MENU_STYLE: Style = Style::new().fg(SLATE.c100).bg(SLATE.c900);
+const KEY_STYLE: Style = Style::new().fg(BLUE.c300).add_modifier(Modifier::BOLD);
+const GROUP_STYLE: Style = Style::new().fg(RED.c200);
+
+/// A which-key style popup listing what the keys after the pending prefix do.
+pub struct Menu<'a> {
+    pub keymap: &'a Keymap,
+    pub prefix: &'a [Key],
+}
+
+impl Widget for Menu<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let next = self.keymap.next(self.prefix);
+        if next.is_empty() {
+            return;
+        }
+        let lines: Vec<Line> = next
+            .iter()
+            .map(|(key, next)| {
+                let (desc, style) = match next {
+                    Next::Group(label) => (format!("+{label}"), GROUP_STYLE),
+                    Next::Action(action) => (self.keymap.describe(*action).to_string(), MENU_STYLE),
+                };
+                Line::from(vec![
+                    Span::styled(format!("{key:>6}"), KEY_STYLE),
+                    Span::raw("  "),
+                    Span::styled(desc, style),
+                ])
+            })
+            .collect();
+        let title = self.keymap.group_label(self.prefix).unwrap_or(t!("leader"));
+        let width = lines.iter().map(Line::width).max().unwrap_or_default() as u16 + 4;
+        let width = width.max(title.len() as u16 + 4).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.right().saturating_sub(width),
+            y: area.bottom().saturating_sub(height),
+            width,
+            height,
+        };
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .style(MENU_STYLE)
+            .block(
+                Block::new()
+                    .title(Line::raw(title).left_aligned())
+                    .borders(Borders::ALL)
+                    .border_style(MENU_STYLE),
+            )
+            .render(popup, buf);
+    }
+}
@@ -0,0 +1,95 @@
+//! Checks on the shape of commit messages, as the `[commit]` config asks for:
+//! line lengths in the manner of the 50/72 rule and a pattern for the subject.
+
+use crate::{config::CommitConfig, prelude::*, t};
+use color_eyre::eyre::WrapErr;
+use regex::Regex;
+
+/// The most warnings shown at once, so that a long body does not bury the
+/// message being edited.
+pub const MAX_SHOWN: usize = 3;
+
+#[derive(Clone, Debug, Default)]
+pub struct Lint {
+    subject_max: Option<usize>,
+    body_max: Option<usize>,
+    pattern: Option<Regex>,
+}
+
+impl Lint {
+    pub fn new(config: &CommitConfig) -> EResult<Self> {
+        let pattern = config
+            .subject_pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .wrap_err("commit.subject_pattern")?;
+        Ok(Self {
+            subject_max: config.subject_max,
+            body_max: config.body_max,
+            pattern,
+        })
+    }
+
+    /// What is wrong with `message`, comment lines left out as git leaves them
+    /// out.
+    pub fn check(&self, message: &str) -> Vec<String> {
+        let lines: Vec<&str> = strip_comments(message).collect();
+        let Some(subject) = lines.first() else {
+            return vec![];
+        };
+        let mut warnings = vec![];
+        let len = subject.chars().count();
+        if let Some(max) = self.subject_max.filter(|max| len > *max) {
+            warnings.push(t!(
+                "the subject is {len} characters, over {max}",
+                len = len,
+                max = max
+            ));
+        }
+        if let Some(pattern) = self.pattern.as_ref().filter(|p| !p.is_match(subject)) {
+            warnings.push(t!(
+                "the subject does not match {pattern}",
+                pattern = pattern
+            ));
+        }
+        if let Some(max) = self.body_max {
+            if lines.get(1).is_some_and(|line| !line.trim().is_empty()) {
+                warnings.push(t!("the subject is not followed by a blank line").to_string());
+            }
+            for (idx, line) in lines.iter().enumerate().skip(1) {
+                let len = line.chars().count();
+                if len > max {
+                    let line = idx + 1;
+                    warnings.push(t!(
+                        "line {line} is {len} characters, over {max}",
+                        line = line,
+                        len = len,
+                        max = max
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+}
+
+/// Whether `message` has nothing in it but comments and blank lines, which git
+/// refuses to commit.
+pub fn is_blank(message: &str) -> bool {
+    strip_comments(message).next().is_none()
+}
+
+/// The lines of `message` that are not comments, without the blank ones
+/// around them.
+fn strip_comments(message: &str) -> impl Iterator<Item = &str> {
+    let lines: Vec<&str> = message.lines().filter(|l| !l.starts_with('#')).collect();
+    let skip = lines.iter().take_while(|l| l.trim().is_empty()).count();
+    let end = lines.len()
+        - lines
+            .iter()
+            .rev()
+            .take_while(|l| l.trim().is_empty())
+            .count();
+    lines.into_iter().take(end.max(skip)).skip(skip)
+}
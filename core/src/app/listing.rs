@@ -0,0 +1,68 @@
+//! The list behind the screens of named things, such as the tags, the remotes
+//! and every ref: the items with the selection, which stays on the same name
+//! when they are read again.
+
+use crate::git;
+use ratatui::widgets::ListState;
+
+/// What an item of a [`Listing`] is told apart by.
+pub trait Named {
+    fn name(&self) -> &str;
+}
+
+impl Named for git::Tag {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Named for git::Remote {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Named for git::Ref {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+pub struct Listing<T> {
+    pub items: Vec<T>,
+    pub state: ListState,
+}
+
+impl<T> Default for Listing<T> {
+    fn default() -> Self {
+        Self {
+            items: vec![],
+            state: ListState::default(),
+        }
+    }
+}
+
+impl<T: Named> Listing<T> {
+    /// A list of `items` with the first one selected.
+    pub fn new(items: Vec<T>) -> Self {
+        let mut listing = Self::default();
+        listing.reload(items);
+        listing
+    }
+
+    pub fn selected(&self) -> Option<&T> {
+        self.state.selected().and_then(|i| self.items.get(i))
+    }
+
+    /// Replaces the items, keeping the selection on the one of the same name,
+    /// or on the first if it is gone.
+    pub fn reload(&mut self, items: Vec<T>) {
+        let name = self.selected().map(|item| item.name().to_string());
+        let idx = items
+            .iter()
+            .position(|item| Some(item.name()) == name.as_deref());
+        self.items = items;
+        self.state
+            .select(idx.or((!self.items.is_empty()).then_some(0)));
+    }
+}
@@ -0,0 +1,52 @@
+//! The log pane: the latest events traced, under the rest of the screen while
+//! it is open, for finding out what went wrong without leaving the app.
+
+use crate::{logs, prelude::*, redact, t};
+use ratatui::{text::Span, widgets::Clear};
+use tracing::Level;
+
+pub struct LogPane;
+
+impl Widget for LogPane {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new()
+            .title(Line::raw(t!("Log")).left_aligned())
+            .title(Line::raw(t!("`: hide")).right_aligned())
+            .borders(Borders::TOP);
+        let inner = block.inner(area);
+        Clear.render(area, buf);
+        block.render(area, buf);
+        let recent = logs::recent();
+        if recent.is_empty() {
+            let hint = t!("nothing logged yet, -v logs more");
+            Paragraph::new(Line::styled(hint, Style::new().dim())).render(inner, buf);
+            return;
+        }
+        let skip = recent.len().saturating_sub(usize::from(inner.height));
+        let lines: Vec<Line> = recent[skip..].iter().map(line).collect();
+        Paragraph::new(lines).render(inner, buf);
+    }
+}
+
+fn line(entry: &logs::Entry) -> Line<'static> {
+    let color = match entry.level {
+        Level::ERROR => RED.c300,
+        Level::WARN => AMBER.c300,
+        Level::INFO => GREEN.c300,
+        Level::DEBUG => BLUE.c300,
+        Level::TRACE => SLATE.c400,
+    };
+    let dim = Style::new().dim();
+    let mut spans = vec![
+        Span::styled(format!("{} ", entry.time.format("%H:%M:%S")), dim),
+        Span::styled(format!("{:5} ", entry.level), color),
+        Span::styled(format!("{} ", entry.target), dim),
+    ];
+    if !entry.spans.is_empty() {
+        // span fields hold branch names and paths as much as the message
+        let joined = entry.spans.join(":");
+        spans.push(Span::styled(format!("{}: ", redact::text(&joined)), dim));
+    }
+    spans.push(Span::raw(redact::text(&entry.message).into_owned()));
+    Line::from(spans)
+}
@@ -0,0 +1,110 @@
+//! Vim-style marks: a letter remembered for a branch, to jump back to it later
+//! in the session. Not to be confused with marking branches for bulk actions.
+
+use super::branch::Selection;
+use crate::{prelude::*, redact, t};
+use git2::BranchType;
+use ratatui::{text::Span, widgets::Clear};
+use std::collections::BTreeMap;
+
+/// What the next letter typed is for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pending {
+    Set,
+    Jump,
+}
+
+#[derive(Default)]
+pub struct Marks {
+    /// The branch of each mark, by name and type.
+    pub items: BTreeMap<char, (String, BranchType)>,
+    /// The selected row of the popup.
+    pub state: ListState,
+}
+
+impl Marks {
+    /// Whether `c` can name a mark.
+    pub fn is_valid(c: char) -> bool {
+        c.is_ascii_alphanumeric()
+    }
+
+    pub fn set(&mut self, mark: char, sel: &Selection) {
+        self.items.insert(mark, (sel.name.clone(), sel.typ));
+    }
+
+    pub fn get(&self, mark: char) -> Option<&(String, BranchType)> {
+        self.items.get(&mark)
+    }
+
+    /// The mark on the selected row of the popup.
+    pub fn selected(&self) -> Option<char> {
+        let idx = self.state.selected()?;
+        self.items.keys().nth(idx).copied()
+    }
+
+    pub fn remove_selected(&mut self) {
+        if let Some(mark) = self.selected() {
+            self.items.remove(&mark);
+        }
+        if self.items.is_empty() {
+            self.state.select(None);
+        } else if self.state.selected().is_some_and(|i| i >= self.items.len()) {
+            self.state.select(Some(self.items.len() - 1));
+        }
+    }
+
+    /// Selects the first row if nothing is, for opening the popup.
+    pub fn open(&mut self) {
+        if self.state.selected().is_none() && !self.items.is_empty() {
+            self.state.select(Some(0));
+        }
+    }
+}
+
+pub struct MarkList<'a>(pub &'a mut Marks);
+
+impl Widget for MarkList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let dim = Style::new().dim();
+        let mut items: Vec<ListItem> = self
+            .0
+            .items
+            .iter()
+            .map(|(mark, (name, typ))| {
+                let typ = match typ {
+                    BranchType::Local => t!("local"),
+                    BranchType::Remote => t!("remote"),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{mark}  "), Style::new().bold()),
+                    Span::raw(redact::branch(name).into_owned()),
+                    Span::raw("  "),
+                    Span::styled(typ, dim),
+                ]))
+            })
+            .collect();
+        if items.is_empty() {
+            items.push(ListItem::new(Line::styled(
+                t!("no marks yet, set one with m and a letter"),
+                dim,
+            )));
+        }
+        let list = List::new(items)
+            .block(
+                Block::new()
+                    .title(Line::raw(t!("Marks")).left_aligned())
+                    .title(Line::raw(t!("enter: jump  d: delete  esc: close")).right_aligned())
+                    .borders(Borders::ALL),
+            )
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c800))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        Clear.render(popup, buf);
+        StatefulWidget::render(list, popup, buf, &mut self.0.state);
+    }
+}
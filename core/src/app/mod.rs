@@ -1,4 +1,64 @@
+mod accounts;
+#[allow(clippy::module_inception)]
 mod app;
+mod authors;
 mod branch;
+mod bulk;
+mod checklist;
+mod checks;
+mod clipboard;
+mod columns;
+mod commands;
+mod conflicts;
+mod dashboard;
+mod diff;
+mod dirs;
+mod divergence;
+mod follow;
+mod gitconfig;
+mod help;
+mod histories;
+mod history;
+mod hooks;
+mod hunks;
+pub mod input;
+pub mod jobs;
+mod keymap;
+mod leader;
+mod lint;
+mod listing;
+mod log;
+mod marks;
+pub mod modal;
+mod mouse;
+mod nav;
+mod network;
+pub mod notify;
+mod output;
+mod palette;
+mod pending;
+mod pickaxe;
+pub mod picker;
+mod query;
+mod range;
+pub mod recent;
+mod refs;
+mod remotes;
+mod rename;
+mod reviews;
+mod scroll;
+mod settings;
+mod snapshot;
+mod status;
+mod summary;
+mod tags;
+mod taken;
+mod template;
+mod tips;
+pub mod title;
+pub mod triage;
 
 pub use app::App;
+pub use branch::Sort;
+pub use clipboard::Format as CopyFormat;
+pub use columns::Column;
@@ -0,0 +1,50 @@
+use super::{Outcome, Value};
+use crate::{prelude::*, t};
+
+/// Asks a yes/no question.
+pub struct Confirm {
+    title: String,
+    message: String,
+    /// Whether only `y` confirms, not `enter`.
+    strict: bool,
+}
+
+impl Confirm {
+    pub fn new(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            strict: false,
+        }
+    }
+
+    /// Makes `y` the only key that confirms, for what is hard to take back, so
+    /// that an `enter` meant for something else does not.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Outcome::Submitted(Value::Confirmed),
+            KeyCode::Enter if !self.strict => Outcome::Submitted(Value::Confirmed),
+            KeyCode::Char('n') | KeyCode::Char('N') => Outcome::Cancelled,
+            _ => Outcome::Pending,
+        }
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let lines = || self.message.lines().map(|l| l.chars().count() as u16);
+        let width = (lines().max().unwrap_or(0) + 2).clamp(30, 72);
+        let rows: u16 = lines().map(|n| n / (width - 2) + 1).sum();
+        let height = rows + 3;
+        let inner = super::frame(area, &self.title, width, height, buf);
+        let [body, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        Paragraph::new(self.message.as_str())
+            .wrap(ratatui::widgets::Wrap { trim: true })
+            .render(body, buf);
+        super::hint(t!("y: yes  n: no"), status, buf);
+    }
+}
@@ -0,0 +1,357 @@
+use super::{Outcome, Value};
+use crate::{app::lint::Lint, prelude::*, t};
+use ratatui::crossterm::event::KeyModifiers;
+
+/// A multi-line text editor. `enter` inserts a newline and `ctrl-s` submits, as
+/// does `ctrl-enter` in terminals that report it.
+///
+/// Long lines wrap at word boundaries, and the keys follow readline: `alt-b` and
+/// `alt-f` move by word, `ctrl-k`, `ctrl-u`, `ctrl-w` and `alt-d` kill text, and
+/// `ctrl-y` yanks the last kill back.
+pub struct Editor {
+    title: String,
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+    /// The first visual row shown.
+    scroll: usize,
+    /// The size of the text area as of the last render, for wrapping and paging.
+    width: usize,
+    height: usize,
+    killed: String,
+    /// Whether the last key killed text, so that the next kill adds to it.
+    killing: bool,
+    /// Checks the text is shown with, for commit messages.
+    lint: Option<Lint>,
+}
+
+/// A visual row: a line, or a part of a wrapped one, from `start` to `end` in chars.
+#[derive(Clone, Copy)]
+struct Row {
+    line: usize,
+    start: usize,
+    end: usize,
+    /// Whether the line goes on in the next row.
+    wrapped: bool,
+}
+
+impl Editor {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            lines: vec![String::new()],
+            row: 0,
+            col: 0,
+            scroll: 0,
+            width: 70,
+            height: 6,
+            killed: String::new(),
+            killing: false,
+            lint: None,
+        }
+    }
+
+    /// Puts the cursor at the start of the text, for text that is filled in
+    /// from the top, as commit templates are.
+    pub fn at_start(mut self) -> Self {
+        (self.row, self.col) = (0, 0);
+        self
+    }
+
+    /// Shows what `lint` finds wrong with the text under it as it is edited.
+    pub fn with_lint(mut self, lint: Lint) -> Self {
+        self.lint = Some(lint);
+        self
+    }
+
+    /// What the lint finds wrong with the text, if there is one.
+    pub fn warnings(&self) -> Vec<String> {
+        self.lint
+            .as_ref()
+            .map(|lint| lint.check(&self.text()))
+            .unwrap_or_default()
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.lines = text.lines().map(ToOwned::to_owned).collect();
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.row = self.lines.len() - 1;
+        self.col = self.lines[self.row].chars().count();
+        self
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte(&self, row: usize, col: usize) -> usize {
+        let line = &self.lines[row];
+        line.char_indices()
+            .nth(col)
+            .map(|(b, _)| b)
+            .unwrap_or(line.len())
+    }
+
+    /// Inserts pasted text at the cursor, splitting it into lines.
+    pub fn paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n");
+        let mut pasted = text.split(['\n', '\r']);
+        let at = self.byte(self.row, self.col);
+        let line = &mut self.lines[self.row];
+        let rest = line.split_off(at);
+        let first = pasted.next().unwrap_or_default();
+        line.push_str(first);
+        self.col += first.chars().count();
+        for next in pasted {
+            self.row += 1;
+            self.lines.insert(self.row, next.to_string());
+            self.col = next.chars().count();
+        }
+        self.lines[self.row].push_str(&rest);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let killing = std::mem::take(&mut self.killing);
+        match key.code {
+            KeyCode::Char('s') | KeyCode::Enter if ctrl => {
+                return Outcome::Submitted(Value::Text(self.text()))
+            }
+            KeyCode::Enter => self.paste("\n"),
+            KeyCode::Backspace if self.col == 0 && self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Delete if self.at_line_end() && self.row + 1 < self.lines.len() => {
+                let next = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&next);
+            }
+            KeyCode::Left if self.col == 0 && self.row > 0 => {
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+            }
+            KeyCode::Right if self.at_line_end() && self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = 0;
+            }
+            KeyCode::Up => self.move_rows(-1),
+            KeyCode::Down => self.move_rows(1),
+            KeyCode::PageUp => self.move_rows(-(self.height as isize)),
+            KeyCode::PageDown => self.move_rows(self.height as isize),
+            KeyCode::Home if ctrl => (self.row, self.col) = (0, 0),
+            KeyCode::End if ctrl => self.end_of_text(),
+            KeyCode::Char('<') if alt => (self.row, self.col) = (0, 0),
+            KeyCode::Char('>') if alt => self.end_of_text(),
+            KeyCode::Char('b') if alt => self.col = self.word_start(),
+            KeyCode::Left if ctrl || alt => self.col = self.word_start(),
+            KeyCode::Char('f') if alt => self.col = self.word_end(),
+            KeyCode::Right if ctrl || alt => self.col = self.word_end(),
+            KeyCode::Char('k') if ctrl => {
+                let end = match self.at_line_end() && self.row + 1 < self.lines.len() {
+                    true => None,
+                    false => Some(self.line_len(self.row)),
+                };
+                self.kill(self.col, end, killing, false);
+            }
+            KeyCode::Char('u') if ctrl => self.kill(0, Some(self.col), killing, true),
+            KeyCode::Char('w') if ctrl => {
+                self.kill(self.word_start(), Some(self.col), killing, true)
+            }
+            KeyCode::Char('d') if alt => self.kill(self.col, Some(self.word_end()), killing, false),
+            KeyCode::Char('y') if ctrl => {
+                let killed = self.killed.clone();
+                self.paste(&killed);
+            }
+            _ => {
+                super::edit_line(&mut self.lines[self.row], &mut self.col, key);
+            }
+        }
+        Outcome::Pending
+    }
+
+    fn at_line_end(&self) -> bool {
+        self.col >= self.line_len(self.row)
+    }
+
+    fn end_of_text(&mut self) {
+        self.row = self.lines.len() - 1;
+        self.col = self.line_len(self.row);
+    }
+
+    /// Removes the chars of the current line from `start` to `end`, or the line
+    /// break after it if `end` is `None`, into the kill buffer. Kills in a row
+    /// add up, before or after what was killed so far depending on the direction.
+    fn kill(&mut self, start: usize, end: Option<usize>, append: bool, backward: bool) {
+        let text = match end {
+            Some(end) => {
+                let (from, to) = (self.byte(self.row, start), self.byte(self.row, end));
+                let text = self.lines[self.row][from..to].to_string();
+                self.lines[self.row].replace_range(from..to, "");
+                self.col = start;
+                text
+            }
+            None => {
+                let next = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&next);
+                "\n".to_string()
+            }
+        };
+        match (append, backward) {
+            (false, _) => self.killed = text,
+            (true, false) => self.killed.push_str(&text),
+            (true, true) => self.killed.insert_str(0, &text),
+        }
+        self.killing = true;
+    }
+
+    /// Where the word before the cursor starts, or the start of the line.
+    fn word_start(&self) -> usize {
+        let chars: Vec<char> = self.lines[self.row].chars().collect();
+        let mut col = self.col.min(chars.len());
+        while col > 0 && !chars[col - 1].is_alphanumeric() {
+            col -= 1;
+        }
+        while col > 0 && chars[col - 1].is_alphanumeric() {
+            col -= 1;
+        }
+        col
+    }
+
+    /// Where the word at or after the cursor ends, or the end of the line.
+    fn word_end(&self) -> usize {
+        let chars: Vec<char> = self.lines[self.row].chars().collect();
+        let mut col = self.col;
+        while col < chars.len() && !chars[col].is_alphanumeric() {
+            col += 1;
+        }
+        while col < chars.len() && chars[col].is_alphanumeric() {
+            col += 1;
+        }
+        col
+    }
+
+    /// Splits the lines into rows of at most `width` chars, breaking after the
+    /// last space that fits, or mid-word if there is none.
+    fn rows(&self, width: usize) -> Vec<Row> {
+        let width = width.max(1);
+        let mut rows = vec![];
+        for (line, text) in self.lines.iter().enumerate() {
+            let chars: Vec<char> = text.chars().collect();
+            let mut start = 0;
+            while chars.len() - start > width {
+                let end = (start + 1..start + width)
+                    .rev()
+                    .find(|&i| chars[i - 1] == ' ')
+                    .unwrap_or(start + width);
+                rows.push(Row {
+                    line,
+                    start,
+                    end,
+                    wrapped: true,
+                });
+                start = end;
+            }
+            rows.push(Row {
+                line,
+                start,
+                end: chars.len(),
+                wrapped: false,
+            });
+        }
+        rows
+    }
+
+    /// The row the cursor is on: the one it falls within, or that it ends.
+    fn cursor_row(&self, rows: &[Row]) -> usize {
+        rows.iter()
+            .position(|r| {
+                r.line == self.row && r.start <= self.col && (self.col < r.end || !r.wrapped)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Moves the cursor by `by` rows as shown, keeping its column where it can.
+    fn move_rows(&mut self, by: isize) {
+        let rows = self.rows(self.width);
+        let current = self.cursor_row(&rows);
+        let offset = self.col - rows[current].start;
+        let target = current.saturating_add_signed(by).min(rows.len() - 1);
+        let row = rows[target];
+        // a wrapped row ends where the next starts, which is not on it
+        let last = if row.wrapped { row.end - 1 } else { row.end };
+        self.row = row.line;
+        self.col = (row.start + offset).min(last);
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let warnings = self.warnings();
+        let shown = warnings.len().min(crate::app::lint::MAX_SHOWN) as u16;
+        // not clamp: on a short terminal the room left is below the least height
+        let height = (self.lines.len() as u16 + 1 + shown)
+            .min(area.height.saturating_sub(4))
+            .max(6 + shown);
+        let inner = super::frame(area, &self.title, 72, height, buf);
+        let [body, warned, status] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(shown),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        let mut warned_lines: Vec<Line> = warnings
+            .iter()
+            .take(shown as usize)
+            .map(|w| Line::styled(format!("⚠ {w}"), super::WARNING_STYLE))
+            .collect();
+        if warnings.len() > shown as usize {
+            if let Some(last) = warned_lines.last_mut() {
+                let more = warnings.len() - shown as usize;
+                last.spans
+                    .push(Span::raw(t!(" (+{more} more)", more = more)));
+            }
+        }
+        Paragraph::new(warned_lines).render(warned, buf);
+        // one column is kept free for the cursor at the end of a full row
+        self.width = (body.width as usize).saturating_sub(1).max(1);
+        self.height = (body.height as usize).max(1);
+        let rows = self.rows(self.width);
+        let current = self.cursor_row(&rows);
+        if current < self.scroll {
+            self.scroll = current;
+        } else if current >= self.scroll + self.height {
+            self.scroll = current + 1 - self.height;
+        }
+        let lines: Vec<Line> = rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(self.height)
+            .map(|(i, row)| {
+                let text: String = self.lines[row.line]
+                    .chars()
+                    .skip(row.start)
+                    .take(row.end - row.start)
+                    .collect();
+                if i == current {
+                    super::cursor_line(&text, self.col - row.start)
+                } else {
+                    Line::raw(text)
+                }
+            })
+            .collect();
+        Paragraph::new(lines).render(body, buf);
+        let hint = match crate::bootstrap::enhanced_keys() {
+            true => t!("ctrl-enter: save  esc: cancel"),
+            false => t!("ctrl-s: save  esc: cancel"),
+        };
+        super::hint(hint, status, buf);
+    }
+}
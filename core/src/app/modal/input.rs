@@ -0,0 +1,78 @@
+use super::{Outcome, Value};
+use crate::{prelude::*, t};
+
+/// A single-line text prompt.
+pub struct Input {
+    title: String,
+    text: String,
+    cursor: usize,
+    error: Option<String>,
+    /// Masks the text, for tokens and passwords.
+    secret: bool,
+}
+
+impl Input {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            text: String::new(),
+            cursor: 0,
+            error: None,
+            secret: false,
+        }
+    }
+
+    pub fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self.cursor = self.text.chars().count();
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Shows a validation error under the input until the text changes.
+    pub fn set_error(&mut self, error: impl Into<String>) {
+        self.error = Some(error.into());
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        if key.code == KeyCode::Enter {
+            return Outcome::Submitted(Value::Text(self.text.clone()));
+        }
+        if super::edit_line(&mut self.text, &mut self.cursor, key) {
+            self.error = None;
+        }
+        Outcome::Pending
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        super::paste_line(&mut self.text, &mut self.cursor, text);
+        self.error = None;
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let chars = self.text.chars().count().max(self.title.chars().count());
+        let width = (chars as u16 + 2).clamp(40, 80);
+        let inner = super::frame(area, &self.title, width, 2, buf);
+        let [line, status] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(inner);
+        let text = match self.secret {
+            true => "•".repeat(self.text.chars().count()),
+            false => self.text.clone(),
+        };
+        Paragraph::new(super::cursor_line(&text, self.cursor)).render(line, buf);
+        match &self.error {
+            Some(err) => Paragraph::new(err.as_str())
+                .style(super::ERROR_STYLE)
+                .render(status, buf),
+            None => super::hint(t!("enter: ok  esc: cancel"), status, buf),
+        }
+    }
+}
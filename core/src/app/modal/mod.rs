@@ -0,0 +1,210 @@
+//! Overlay widgets that capture input until they are submitted or dismissed.
+//!
+//! Every modal shares the same frame and key conventions: `esc` cancels, and
+//! `enter` submits (`ctrl-s` in the multi-line editor, where `enter` inserts a
+//! newline).
+
+mod confirm;
+mod editor;
+mod input;
+mod select;
+
+pub use confirm::Confirm;
+pub use editor::Editor;
+pub use input::Input;
+pub use select::Select;
+
+use crate::{prelude::*, redact};
+use ratatui::widgets::Clear;
+
+const MODAL_STYLE: Style = Style::new().fg(SLATE.c100).bg(SLATE.c900);
+const BORDER_STYLE: Style = Style::new().fg(BLUE.c300);
+const HINT_STYLE: Style = Style::new().fg(SLATE.c400);
+const ERROR_STYLE: Style = Style::new().fg(RED.c300);
+const WARNING_STYLE: Style = Style::new().fg(AMBER.c300);
+const CURSOR_STYLE: Style = Style::new().add_modifier(Modifier::REVERSED);
+
+pub enum Modal {
+    Input(Input),
+    Editor(Editor),
+    Select(Select),
+    Confirm(Confirm),
+}
+
+/// The result of feeding a key to a modal.
+pub enum Outcome {
+    Pending,
+    Cancelled,
+    Submitted(Value),
+}
+
+pub enum Value {
+    Text(String),
+    Index(usize),
+    Confirmed,
+}
+
+impl Modal {
+    /// The list of the modal, if it has one.
+    pub fn list_state(&mut self) -> Option<&mut ListState> {
+        match self {
+            Modal::Select(select) => Some(&mut select.state),
+            _ => None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        if key.code == KeyCode::Esc {
+            return Outcome::Cancelled;
+        }
+        match self {
+            Modal::Input(m) => m.handle_key(key),
+            Modal::Editor(m) => m.handle_key(key),
+            Modal::Select(m) => m.handle_key(key),
+            Modal::Confirm(m) => m.handle_key(key),
+        }
+    }
+
+    /// Inserts pasted text at the cursor of a text modal.
+    pub fn paste(&mut self, text: &str) {
+        match self {
+            Modal::Input(m) => m.paste(text),
+            Modal::Editor(m) => m.paste(text),
+            Modal::Select(_) | Modal::Confirm(_) => {}
+        }
+    }
+}
+
+impl Widget for &mut Modal {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        match self {
+            Modal::Input(m) => m.render(area, buf),
+            Modal::Editor(m) => m.render(area, buf),
+            Modal::Select(m) => m.render(area, buf),
+            Modal::Confirm(m) => m.render(area, buf),
+        }
+    }
+}
+
+/// Clears a centered popup of the given size and draws the shared modal frame
+/// around it, returning the inner area.
+fn frame(area: Rect, title: &str, width: u16, height: u16, buf: &mut Buffer) -> Rect {
+    let width = (width + 2).min(area.width);
+    let height = (height + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 3,
+        width,
+        height,
+    };
+    let block = Block::new()
+        .title(Line::raw(format!(" {} ", redact::text(title))).left_aligned())
+        .borders(Borders::ALL)
+        .border_style(BORDER_STYLE)
+        .style(MODAL_STYLE);
+    let inner = block.inner(popup);
+    Clear.render(popup, buf);
+    block.render(popup, buf);
+    inner
+}
+
+fn hint(text: &str, area: Rect, buf: &mut Buffer) {
+    Paragraph::new(text)
+        .style(HINT_STYLE)
+        .right_aligned()
+        .render(area, buf);
+}
+
+/// Single-line text editing shared by the input and editor widgets. `cursor` is a
+/// char index into `text`.
+fn edit_line(text: &mut String, cursor: &mut usize, key: KeyEvent) -> bool {
+    use ratatui::crossterm::event::KeyModifiers;
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let len = text.chars().count();
+    let byte = |text: &str, idx: usize| {
+        text.char_indices()
+            .nth(idx)
+            .map(|(b, _)| b)
+            .unwrap_or(text.len())
+    };
+    match key.code {
+        KeyCode::Char('a') if ctrl => *cursor = 0,
+        KeyCode::Char('e') if ctrl => *cursor = len,
+        KeyCode::Char('u') if ctrl => {
+            let end = byte(text, *cursor);
+            text.replace_range(..end, "");
+            *cursor = 0;
+        }
+        KeyCode::Char('k') if ctrl => {
+            let start = byte(text, *cursor);
+            text.truncate(start);
+        }
+        KeyCode::Char('w') if ctrl => {
+            let chars: Vec<char> = text.chars().collect();
+            let mut start = *cursor;
+            while start > 0 && chars[start - 1].is_whitespace() {
+                start -= 1;
+            }
+            while start > 0 && !chars[start - 1].is_whitespace() {
+                start -= 1;
+            }
+            let (from, to) = (byte(text, start), byte(text, *cursor));
+            text.replace_range(from..to, "");
+            *cursor = start;
+        }
+        KeyCode::Char(c) if !ctrl => {
+            let at = byte(text, *cursor);
+            text.insert(at, c);
+            *cursor += 1;
+        }
+        KeyCode::Backspace if *cursor > 0 => {
+            let at = byte(text, *cursor - 1);
+            text.remove(at);
+            *cursor -= 1;
+        }
+        KeyCode::Delete if *cursor < len => {
+            let at = byte(text, *cursor);
+            text.remove(at);
+        }
+        KeyCode::Left => *cursor = cursor.saturating_sub(1),
+        KeyCode::Right => *cursor = (*cursor + 1).min(len),
+        KeyCode::Home => *cursor = 0,
+        KeyCode::End => *cursor = len,
+        _ => return false,
+    }
+    true
+}
+
+/// Inserts `pasted` into a single line at char index `cursor`, leaving out a
+/// trailing newline and joining any other lines with spaces.
+fn paste_line(text: &mut String, cursor: &mut usize, pasted: &str) {
+    let pasted = pasted
+        .trim_end_matches(['\r', '\n'])
+        .lines()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let at = text
+        .char_indices()
+        .nth(*cursor)
+        .map(|(b, _)| b)
+        .unwrap_or(text.len());
+    text.insert_str(at, &pasted);
+    *cursor += pasted.chars().count();
+}
+
+/// Renders `text` with a block cursor at char index `cursor`.
+fn cursor_line(text: &str, cursor: usize) -> Line<'static> {
+    use ratatui::text::Span;
+    let before: String = text.chars().take(cursor).collect();
+    let at: String = text
+        .chars()
+        .nth(cursor)
+        .map(String::from)
+        .unwrap_or(" ".into());
+    let after: String = text.chars().skip(cursor + 1).collect();
+    Line::from(vec![
+        Span::raw(before),
+        Span::styled(at, CURSOR_STYLE),
+        Span::raw(after),
+    ])
+}
@@ -0,0 +1,64 @@
+use super::{Outcome, Value};
+use crate::{prelude::*, t};
+
+/// Picks one item from a list.
+pub struct Select {
+    title: String,
+    items: Vec<String>,
+    pub(super) state: ListState,
+}
+
+impl Select {
+    pub fn new(title: impl Into<String>, items: Vec<String>) -> Self {
+        let mut state = ListState::default();
+        state.select_first();
+        Self {
+            title: title.into(),
+            items,
+            state,
+        }
+    }
+
+    pub fn with_selected(mut self, idx: usize) -> Self {
+        self.state.select(Some(idx));
+        self
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.state.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => self.state.select_previous(),
+            KeyCode::Char('g') | KeyCode::Home => self.state.select_first(),
+            KeyCode::Char('G') | KeyCode::End => self.state.select_last(),
+            KeyCode::Enter => {
+                if let Some(idx) = self.state.selected() {
+                    let idx = idx.min(self.items.len().saturating_sub(1));
+                    if idx < self.items.len() {
+                        return Outcome::Submitted(Value::Index(idx));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Outcome::Pending
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let width = self
+            .items
+            .iter()
+            .map(|i| i.chars().count() as u16 + 2)
+            .max()
+            .unwrap_or_default()
+            .clamp(30, 80);
+        let height = (self.items.len() as u16).min(20) + 1;
+        let inner = super::frame(area, &self.title, width, height, buf);
+        let [body, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        let list = List::new(self.items.iter().map(|i| ListItem::new(i.as_str())))
+            .highlight_style(super::CURSOR_STYLE)
+            .highlight_symbol(">");
+        StatefulWidget::render(list, body, buf, &mut self.state);
+        super::hint(t!("enter: select  esc: cancel"), status, buf);
+    }
+}
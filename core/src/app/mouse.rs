@@ -0,0 +1,100 @@
+use crate::prelude::*;
+use ratatui::crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use std::time::Instant;
+
+/// Two clicks on the same spot within this window count as a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Screen regions recorded during the last render, used to map clicks back to
+/// what was drawn there.
+#[derive(Default)]
+pub struct Hits {
+    /// The title row of the branch list.
+    pub list_header: Rect,
+    /// The rows of the branch list, and the index of the first visible row.
+    pub list_rows: Rect,
+    pub list_offset: usize,
+    /// The branch shown on each row of the list, `None` for group headers.
+    pub list_index: Vec<Option<usize>>,
+    /// The parts of the breadcrumb, by depth.
+    pub crumbs: Vec<Rect>,
+    /// The tabs, shown in place of the breadcrumb on the screen at the bottom.
+    pub tabs: Vec<Rect>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Target {
+    ListHeader,
+    Branch(usize),
+    /// The screen at this depth in the breadcrumb.
+    Crumb(usize),
+    Tab(usize),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Click {
+    Single(Target),
+    Double(Target),
+    Middle(Target),
+    Right(Target),
+    ScrollUp,
+    ScrollDown,
+}
+
+impl Hits {
+    fn target(&self, col: u16, row: u16) -> Option<Target> {
+        let pos = ratatui::layout::Position { x: col, y: row };
+        if let Some(depth) = self.crumbs.iter().position(|r| r.contains(pos)) {
+            return Some(Target::Crumb(depth));
+        }
+        if let Some(idx) = self.tabs.iter().position(|r| r.contains(pos)) {
+            return Some(Target::Tab(idx));
+        }
+        if self.list_header.contains(pos) {
+            return Some(Target::ListHeader);
+        }
+        if self.list_rows.contains(pos) {
+            let row = self.list_offset + (row - self.list_rows.y) as usize;
+            return self
+                .list_index
+                .get(row)
+                .copied()
+                .flatten()
+                .map(Target::Branch);
+        }
+        None
+    }
+}
+
+/// Turns raw mouse events into clicks on known targets.
+#[derive(Default)]
+pub struct Mouse {
+    last_click: Option<(Instant, u16, u16)>,
+}
+
+impl Mouse {
+    pub fn handle(&mut self, hits: &Hits, event: MouseEvent) -> Option<Click> {
+        let (col, row) = (event.column, event.row);
+        match event.kind {
+            MouseEventKind::ScrollUp => Some(Click::ScrollUp),
+            MouseEventKind::ScrollDown => Some(Click::ScrollDown),
+            MouseEventKind::Down(MouseButton::Middle) => hits.target(col, row).map(Click::Middle),
+            MouseEventKind::Down(MouseButton::Right) => hits.target(col, row).map(Click::Right),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let target = hits.target(col, row)?;
+                let now = Instant::now();
+                let double = self
+                    .last_click
+                    .is_some_and(|(at, c, r)| now - at <= DOUBLE_CLICK && (c, r) == (col, row));
+                if double {
+                    self.last_click = None;
+                    Some(Click::Double(target))
+                } else {
+                    self.last_click = Some((now, col, row));
+                    Some(Click::Single(target))
+                }
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! The screens drilled into from the branch list, as a stack. Each screen keeps
+//! where it was left when another was opened on top of it, so that going back
+//! with `esc` puts it back exactly as it was, scroll positions included. The
+//! header shows the stack as a breadcrumb, each part of which can be clicked to
+//! go back to that screen.
+//!
+//! The screen at the bottom is one of the tabs: the branches, the tags or the
+//! remotes.
+
+use super::{
+    branch, history::FileHistory, hunks::HunkView, pickaxe::Search, range::Range, refs::RefsView,
+    remotes::Remotes, status::StatusView, tags::TagsView,
+};
+use crate::{redact, t};
+use ratatui::widgets::ListState;
+
+/// A screen, from the branch list down.
+pub enum View {
+    Branches,
+    /// The commits of the named branch, with a cursor over them.
+    Commits(String),
+    History(FileHistory),
+    /// The commits that add or remove a string.
+    Pickaxe(Search),
+    /// The uncommitted changes of the work tree.
+    Status(StatusView),
+    /// The hunks of one file of the work tree, to stage or unstage.
+    Hunks(HunkView),
+    /// Every ref of the repository.
+    Refs(RefsView),
+    /// The tab of the tags.
+    Tags(TagsView),
+    /// The tab of the remotes.
+    Remotes(Remotes),
+}
+
+impl View {
+    /// The list of a screen that replaces the branch list, if it is one.
+    pub fn list_state(&mut self) -> Option<&mut ListState> {
+        match self {
+            View::Branches | View::Commits(_) => None,
+            View::History(history) => Some(&mut history.state),
+            View::Pickaxe(search) => Some(&mut search.state),
+            View::Status(status) => Some(&mut status.state),
+            View::Hunks(hunks) => Some(&mut hunks.state),
+            View::Refs(refs) => Some(&mut refs.list.state),
+            View::Tags(tags) => Some(&mut tags.state),
+            View::Remotes(remotes) => Some(&mut remotes.state),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            View::Branches => t!("branches").to_string(),
+            View::Commits(branch) => redact::branch(branch).to_string(),
+            View::History(history) => history.path.clone(),
+            View::Pickaxe(search) => format!("-S {}", search.needle),
+            View::Status(_) => t!("status").to_string(),
+            View::Hunks(hunks) => hunks.path.clone(),
+            View::Refs(_) => t!("refs").to_string(),
+            View::Tags(_) => t!("tags").to_string(),
+            View::Remotes(_) => t!("remotes").to_string(),
+        }
+    }
+
+    /// Which tab the screen is, if it is one.
+    pub fn tab(&self) -> Option<usize> {
+        match self {
+            View::Branches => Some(0),
+            View::Tags(_) => Some(1),
+            View::Remotes(_) => Some(2),
+            _ => None,
+        }
+    }
+}
+
+/// The labels of the tabs, in order.
+pub fn tabs() -> [&'static str; 3] {
+    [t!("branches"), t!("tags"), t!("remotes")]
+}
+
+/// Where a screen was left.
+#[derive(Clone, Debug, Default)]
+pub struct Position {
+    pub selection: Option<branch::Selection>,
+    /// The first row of the branch list shown.
+    pub offset: usize,
+    pub details_scroll: usize,
+    pub range: Option<Range>,
+}
+
+pub struct Nav {
+    /// The screens open, the branch list first, with where each was left. The
+    /// position of the top one is only filled in once another covers it.
+    stack: Vec<(View, Position)>,
+}
+
+impl Default for Nav {
+    fn default() -> Self {
+        Self {
+            stack: vec![(View::Branches, Position::default())],
+        }
+    }
+}
+
+impl Nav {
+    pub fn current_mut(&mut self) -> &mut View {
+        &mut self
+            .stack
+            .last_mut()
+            .expect("the branch list is never popped")
+            .0
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.stack.len() == 1
+    }
+
+    /// Opens `view`, remembering where the current screen was left.
+    pub fn push(&mut self, view: View, left: Position) {
+        if let Some((_, position)) = self.stack.last_mut() {
+            *position = left;
+        }
+        self.stack.push((view, Position::default()));
+    }
+
+    /// Closes the current screen, returning where the one below was left, or
+    /// `None` on the branch list.
+    pub fn pop(&mut self) -> Option<Position> {
+        self.pop_to(self.stack.len().checked_sub(2)?)
+    }
+
+    /// Closes the screens above `depth`, the branch list being 0, returning
+    /// where the one at `depth` was left, or `None` if it is the current one.
+    pub fn pop_to(&mut self, depth: usize) -> Option<Position> {
+        if depth + 1 >= self.stack.len() {
+            return None;
+        }
+        self.stack.truncate(depth + 1);
+        self.stack.last().map(|(_, position)| position.clone())
+    }
+
+    /// The tab at the bottom of the stack.
+    pub fn tab(&self) -> usize {
+        self.stack[0].0.tab().unwrap_or_default()
+    }
+
+    /// Makes `view` the only screen, as the tab at the bottom of the stack.
+    pub fn switch_tab(&mut self, view: View) {
+        self.stack = vec![(view, Position::default())];
+    }
+
+    /// Goes back to the branch list without restoring anything, for when what
+    /// the screens showed is gone.
+    pub fn reset(&mut self) {
+        self.stack.truncate(1);
+    }
+
+    /// The lists of the screens open that have one.
+    pub fn list_states(&mut self) -> impl Iterator<Item = &mut ListState> {
+        self.stack
+            .iter_mut()
+            .filter_map(|(view, _)| view.list_state())
+    }
+
+    /// The label of each screen open, the branch list first.
+    pub fn crumbs(&self) -> Vec<String> {
+        self.stack.iter().map(|(view, _)| view.label()).collect()
+    }
+}
+
+/// The state of a popup, kept for the session so that opening it again finds
+/// it the way it was left rather than rebuilt.
+pub struct Kept<T> {
+    state: Option<T>,
+    shown: bool,
+}
+
+impl<T> Default for Kept<T> {
+    fn default() -> Self {
+        Self {
+            state: None,
+            shown: false,
+        }
+    }
+}
+
+impl<T> Kept<T> {
+    pub fn is_shown(&self) -> bool {
+        self.shown
+    }
+
+    /// The state, while the popup is shown.
+    pub fn shown(&self) -> Option<&T> {
+        self.state.as_ref().filter(|_| self.shown)
+    }
+
+    pub fn shown_mut(&mut self) -> Option<&mut T> {
+        self.state.as_mut().filter(|_| self.shown)
+    }
+
+    /// Shows the popup, returning its state from last time, or from `init` the
+    /// first time.
+    pub fn show(&mut self, init: impl FnOnce() -> T) -> &mut T {
+        self.shown = true;
+        self.state.get_or_insert_with(init)
+    }
+
+    pub fn hide(&mut self) {
+        self.shown = false;
+    }
+}
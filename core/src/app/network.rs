@@ -0,0 +1,188 @@
+//! Whether the hosts of the remotes can be reached, found by resolving them
+//! and connecting to them on a thread of its own. While none can, the app is
+//! offline: pull requests are not looked up and what the cache has is shown
+//! instead. It is only a hint, as ssh aliases, proxies and `insteadOf` can
+//! make git reach what the check cannot, so git is never kept from trying,
+//! and any fetch or push that works counts as being online.
+
+use crate::git;
+use color_eyre::Report;
+use std::{
+    net::{TcpStream, ToSocketAddrs},
+    path::Path,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long a host gets to accept a connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// How often the hosts are checked again while offline.
+const OFFLINE_RECHECK: Duration = Duration::from_secs(30);
+/// How often the hosts are checked again while online.
+const ONLINE_RECHECK: Duration = Duration::from_secs(300);
+
+#[derive(Default)]
+pub struct Network {
+    hosts: Vec<(String, u16)>,
+    /// Why none of the hosts could be reached, while offline.
+    offline: Option<String>,
+    /// When the last check finished.
+    checked: Option<Instant>,
+    rx: Option<mpsc::Receiver<Result<(), String>>>,
+}
+
+impl Network {
+    /// Checks the hosts of `urls` from now on, at once if they changed.
+    pub fn watch<'a>(&mut self, urls: impl IntoIterator<Item = &'a str>) {
+        let mut hosts: Vec<_> = urls.into_iter().filter_map(host).collect();
+        hosts.sort();
+        hosts.dedup();
+        if hosts != self.hosts {
+            self.hosts = hosts;
+            self.offline = None;
+            self.checked = None;
+            self.check();
+        }
+    }
+
+    /// Starts a check, unless one is under way or there is nothing to reach.
+    pub fn check(&mut self) {
+        if self.rx.is_some() || self.hosts.is_empty() {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let hosts = self.hosts.clone();
+        thread::spawn(move || {
+            let _ = tx.send(reach(&hosts));
+        });
+        self.rx = Some(rx);
+    }
+
+    pub fn is_checking(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    /// How long until the next check is due, starting it once it is.
+    pub fn wait(&mut self) -> Option<Duration> {
+        let interval = match self.offline {
+            Some(_) => OFFLINE_RECHECK,
+            None => ONLINE_RECHECK,
+        };
+        let left = interval.saturating_sub(self.checked?.elapsed());
+        if left.is_zero() {
+            self.check();
+            return None;
+        }
+        Some(left)
+    }
+
+    /// Collects a finished check. Returns whether the app is online now, if
+    /// that changed.
+    pub fn poll(&mut self) -> Option<bool> {
+        let res = match self.rx.as_ref()?.try_recv() {
+            Ok(res) => res,
+            Err(mpsc::TryRecvError::Empty) => return None,
+            // the check died without an answer, so nothing changed
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.rx = None;
+                return None;
+            }
+        };
+        self.rx = None;
+        self.checked = Some(Instant::now());
+        let was_offline = self.offline.is_some();
+        match &res {
+            Ok(()) => tracing::debug!(hosts = ?self.hosts, "reachable"),
+            Err(reason) => tracing::warn!(hosts = ?self.hosts, reason, "unreachable"),
+        }
+        self.offline = res.err();
+        (was_offline != self.offline.is_some()).then_some(self.offline.is_none())
+    }
+
+    /// Takes a remote operation that worked as being online, returning
+    /// whether the app was offline until now.
+    pub fn reached(&mut self) -> bool {
+        self.checked = Some(Instant::now());
+        self.offline.take().is_some()
+    }
+
+    /// Why the hosts cannot be reached, while offline.
+    pub fn offline(&self) -> Option<&str> {
+        self.offline.as_deref()
+    }
+}
+
+/// Connects to each host in turn until one answers, failing with why the
+/// last one did not if none does.
+fn reach(hosts: &[(String, u16)]) -> Result<(), String> {
+    let mut last = String::new();
+    for (host, port) in hosts {
+        let addrs = match (host.as_str(), *port).to_socket_addrs() {
+            Ok(addrs) => addrs,
+            Err(err) => {
+                last = format!("{host}: {err}");
+                continue;
+            }
+        };
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(_) => return Ok(()),
+                Err(err) => last = format!("{host}: {err}"),
+            }
+        }
+    }
+    Err(last)
+}
+
+/// The host and port a remote URL connects to, or `None` for a local one.
+fn host(url: &str) -> Option<(String, u16)> {
+    if let Some((scheme, rest)) = url.split_once("://") {
+        let port = match scheme {
+            "https" => 443,
+            "http" => 80,
+            "ssh" | "git+ssh" | "ssh+git" => 22,
+            "git" => 9418,
+            _ => return None,
+        };
+        let authority = rest.split('/').next()?;
+        let authority = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let (host, port) = authority
+            .rsplit_once(':')
+            .and_then(|(host, port)| Some((host, port.parse().ok()?)))
+            .unwrap_or((authority, port));
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        return (!host.is_empty()).then(|| (host.to_string(), port));
+    }
+    // an scp-like address such as git@host:repo, unless it is a local path
+    let (before, _) = url.split_once(':')?;
+    if before.contains('/') || Path::new(url).exists() {
+        return None;
+    }
+    let host = before.rsplit_once('@').map_or(before, |(_, host)| host);
+    (!host.is_empty()).then(|| (host.to_string(), 22))
+}
+
+/// Whether `err` came of a host not being reachable.
+pub fn is_unreachable(err: &Report) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<git::Error>()
+            .is_some_and(|err| matches!(err.root(), git::Error::Network(_)))
+            || is_transport(cause)
+    })
+}
+
+#[cfg(feature = "forge")]
+fn is_transport(cause: &(dyn std::error::Error + 'static)) -> bool {
+    cause
+        .downcast_ref::<ureq::Error>()
+        .is_some_and(|err| matches!(err, ureq::Error::Transport(_)))
+}
+
+#[cfg(not(feature = "forge"))]
+fn is_transport(_: &(dyn std::error::Error + 'static)) -> bool {
+    false
+}
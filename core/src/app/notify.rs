@@ -0,0 +1,213 @@
+use crate::{git, prelude::*, redact, t};
+use chrono::{DateTime, Local};
+use color_eyre::Report;
+use ratatui::{text::Span, widgets::Clear};
+use std::time::Instant;
+
+/// How long info and success toasts stay on screen.
+const TOAST_TTL: Duration = Duration::from_secs(4);
+/// Errors linger longer since they usually need reading.
+const ERROR_TTL: Duration = Duration::from_secs(10);
+const MAX_TOASTS: usize = 3;
+const MAX_HISTORY: usize = 200;
+
+const TOAST_STYLE: Style = Style::new().fg(SLATE.c100).bg(SLATE.c800);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Success,
+    Error,
+}
+
+impl Level {
+    fn style(self) -> Style {
+        match self {
+            Level::Info => Style::new().fg(BLUE.c300),
+            Level::Success => Style::new().fg(GREEN.c300),
+            Level::Error => Style::new().fg(RED.c300),
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Level::Info => "•",
+            Level::Success => "✓",
+            Level::Error => "✗",
+        }
+    }
+
+    fn ttl(self) -> Duration {
+        match self {
+            Level::Error => ERROR_TTL,
+            _ => TOAST_TTL,
+        }
+    }
+}
+
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    pub time: DateTime<Local>,
+    expires: Instant,
+}
+
+/// Toasts currently on screen plus the history of everything that was shown.
+#[derive(Default)]
+pub struct Notifications {
+    history: Vec<Notification>,
+    visible: usize,
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: Level, message: impl Into<String>) {
+        self.add(level, message);
+        self.visible = (self.visible + 1).min(MAX_TOASTS);
+    }
+
+    /// Adds a notification to the history without showing it as a toast. The
+    /// toasts on screen go, as they are no longer the latest.
+    pub fn record(&mut self, level: Level, message: impl Into<String>) {
+        self.visible = 0;
+        self.add(level, message);
+    }
+
+    fn add(&mut self, level: Level, message: impl Into<String>) {
+        self.history.push(Notification {
+            level,
+            message: message.into(),
+            time: Local::now(),
+            expires: Instant::now() + level.ttl(),
+        });
+        if self.history.len() > MAX_HISTORY {
+            self.history.remove(0);
+        }
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Level::Info, message);
+    }
+
+    pub fn success(&mut self, message: impl Into<String>) {
+        self.push(Level::Success, message);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Level::Error, message);
+    }
+
+    /// Shows `err`, with advice on how to recover for the kinds of git errors
+    /// that have a known fix.
+    pub fn report(&mut self, err: &Report) {
+        let hint = match err.downcast_ref::<git::Error>().map(git::Error::root) {
+            Some(git::Error::DirtyWorktree(_)) => {
+                Some(t!("commit or stash your local changes first"))
+            }
+            Some(git::Error::AuthFailed(_)) => Some(t!("check your credentials or ssh agent")),
+            Some(git::Error::HostKey(_)) => {
+                Some(t!("the host key of the remote is unknown or has changed"))
+            }
+            Some(git::Error::Network(_)) => Some(t!("check your connection to the remote")),
+            Some(git::Error::Moved(_)) => Some(t!("the repository changed, refreshing")),
+            _ => None,
+        };
+        tracing::warn!("{err:#}");
+        match hint {
+            Some(hint) => self.error(format!("{err:#} ({hint})")),
+            None => self.error(format!("{err:#}")),
+        }
+    }
+
+    pub fn history(&self) -> &[Notification] {
+        &self.history
+    }
+
+    fn toasts(&self) -> &[Notification] {
+        let start = self.history.len().saturating_sub(self.visible);
+        &self.history[start..]
+    }
+
+    /// How long until the next toast should be dismissed.
+    pub fn timeout(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.toasts()
+            .iter()
+            .map(|n| n.expires.saturating_duration_since(now))
+            .min()
+    }
+
+    /// Dismisses expired toasts, returning whether anything changed.
+    pub fn expire(&mut self) -> bool {
+        let now = Instant::now();
+        let before = self.visible;
+        while self.visible > 0 && self.toasts()[0].expires <= now {
+            self.visible -= 1;
+        }
+        before != self.visible
+    }
+
+    pub fn dismiss(&mut self) {
+        self.visible = 0;
+    }
+}
+
+/// Stacks the visible toasts in the top right corner of the area.
+pub struct Toasts<'a>(pub &'a Notifications);
+
+impl Widget for Toasts<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = (area.width / 2).max(30).min(area.width);
+        for (y, toast) in (area.y..area.bottom()).zip(self.0.toasts().iter().rev()) {
+            let rect = Rect {
+                x: area.right().saturating_sub(width),
+                y,
+                width,
+                height: 1,
+            };
+            Clear.render(rect, buf);
+            Paragraph::new(line(toast))
+                .style(TOAST_STYLE)
+                .render(rect, buf);
+        }
+    }
+}
+
+/// A popup listing past notifications, newest first.
+pub struct History<'a>(pub &'a Notifications);
+
+impl Widget for History<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let lines: Vec<Line> = if self.0.history.is_empty() {
+            vec![Line::raw(t!("no notifications yet"))]
+        } else {
+            self.0.history.iter().rev().map(line).collect()
+        };
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .style(TOAST_STYLE)
+            .block(
+                Block::new()
+                    .title(Line::raw(t!("Notifications")).left_aligned())
+                    .title(Line::raw(t!("esc: close")).right_aligned())
+                    .borders(Borders::ALL),
+            )
+            .render(popup, buf);
+    }
+}
+
+fn line(n: &Notification) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("{} ", n.level.symbol()), n.level.style()),
+        Span::styled(
+            format!("{} ", n.time.format("%H:%M:%S")),
+            Style::new().dim(),
+        ),
+        Span::raw(redact::text(&n.message).into_owned()),
+    ])
+}
@@ -0,0 +1,389 @@
+//! A pane that streams the output of a running command.
+
+use super::diff;
+use crate::{prelude::*, redact, t};
+use ratatui::widgets::Clear;
+use std::{
+    io::{BufRead, BufReader, Read},
+    process::{Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+/// The most lines taken in per poll, so that a flood of output cannot hold up
+/// the UI.
+const MAX_LINES_PER_POLL: usize = 5000;
+
+enum Msg {
+    Line(String),
+    Exit(std::io::Result<ExitStatus>),
+}
+
+/// A row of the pane: a line of output, or the rest of a file's diff, folded
+/// away because it is too long to read anyway.
+enum Row {
+    Line(usize),
+    Folded(usize),
+    /// The two lines of a change to the commit of a submodule.
+    Submodule(diff::Submodule),
+}
+
+pub struct Output {
+    title: String,
+    lines: Vec<String>,
+    rows: Vec<Row>,
+    rx: Option<mpsc::Receiver<Msg>>,
+    kill: Arc<AtomicBool>,
+    status: Option<String>,
+    /// Whether the command exited successfully.
+    succeeded: bool,
+    /// The first visible line, or None to follow the end of the output.
+    scroll: Option<usize>,
+    height: usize,
+    /// The diff shown, if it is one.
+    diff: Option<diff::Shown>,
+    /// How many lines of a file's diff are shown before the rest is folded,
+    /// unless everything is shown.
+    fold_after: Option<usize>,
+    /// How many lines of the current file's diff have come in.
+    file_lines: usize,
+    /// The path of the current file of the diff.
+    file_path: Option<String>,
+    /// Whether the current file of the diff is a submodule, tree mode 160000
+    /// on either side.
+    gitlink: bool,
+    folded: bool,
+    /// Whether the pane covers the whole screen rather than floating over it.
+    full: bool,
+}
+
+impl Output {
+    pub fn spawn(title: String, mut cmd: Command) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let kill = Arc::new(AtomicBool::new(false));
+        let child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        match child {
+            Ok(mut child) => {
+                let readers = [
+                    forward(child.stdout.take(), tx.clone()),
+                    forward(child.stderr.take(), tx.clone()),
+                ];
+                let killed = kill.clone();
+                thread::spawn(move || {
+                    let status = loop {
+                        match child.try_wait() {
+                            Ok(Some(status)) => break Ok(status),
+                            Ok(None) if killed.load(Ordering::Relaxed) => {
+                                let _ = child.kill();
+                                break child.wait();
+                            }
+                            Ok(None) => thread::sleep(Duration::from_millis(50)),
+                            Err(err) => break Err(err),
+                        }
+                    };
+                    for reader in readers {
+                        let _ = reader.join();
+                    }
+                    let _ = tx.send(Msg::Exit(status));
+                });
+            }
+            Err(err) => {
+                let _ = tx.send(Msg::Exit(Err(err)));
+            }
+        }
+        Self {
+            title,
+            lines: vec![],
+            rows: vec![],
+            rx: Some(rx),
+            kill,
+            status: None,
+            succeeded: false,
+            scroll: None,
+            height: 0,
+            diff: None,
+            fold_after: None,
+            file_lines: 0,
+            file_path: None,
+            gitlink: false,
+            folded: false,
+            full: false,
+        }
+    }
+
+    /// A pane of `lines` known up front, rather than the output of a command.
+    pub fn text(title: String, status: impl Into<String>, lines: Vec<String>) -> Self {
+        let mut output = Self {
+            title,
+            lines: vec![],
+            rows: vec![],
+            rx: None,
+            kill: Arc::default(),
+            status: Some(status.into()),
+            succeeded: true,
+            scroll: Some(0),
+            height: 0,
+            diff: None,
+            fold_after: None,
+            file_lines: 0,
+            file_path: None,
+            gitlink: false,
+            folded: false,
+            full: false,
+        };
+        for line in lines {
+            output.push(line);
+        }
+        output
+    }
+
+    /// Marks the output as `diff`, whose options can be changed while shown,
+    /// folding away what comes after the first `fold_after` lines of a file.
+    pub fn with_diff(mut self, diff: diff::Shown, fold_after: usize) -> Self {
+        self.diff = Some(diff);
+        self.fold_after = Some(fold_after);
+        self
+    }
+
+    /// Covers the whole screen, for output read rather than glanced at.
+    pub fn full_screen(mut self) -> Self {
+        self.full = true;
+        self
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn is_folded(&self) -> bool {
+        self.folded
+    }
+
+    /// Shows the lines folded away, and any still to come.
+    pub fn unfold(&mut self) {
+        self.fold_after = None;
+        self.folded = false;
+        self.rows.clear();
+        for idx in 0..self.lines.len() {
+            self.add_row(idx);
+        }
+    }
+
+    /// The first submodule change from the top of the pane down.
+    pub fn submodule(&self) -> Option<&diff::Submodule> {
+        let max = self.rows.len().saturating_sub(self.height);
+        let pos = self.scroll.unwrap_or(max).min(max);
+        self.rows[pos..].iter().find_map(|row| match row {
+            Row::Submodule(submodule) => Some(submodule),
+            Row::Line(_) | Row::Folded(_) => None,
+        })
+    }
+
+    fn push(&mut self, line: String) {
+        self.lines.push(line);
+        self.add_row(self.lines.len() - 1);
+    }
+
+    fn add_row(&mut self, idx: usize) {
+        let line = &self.lines[idx];
+        if line.starts_with("diff --git ") {
+            self.file_lines = 0;
+            self.file_path = None;
+            self.gitlink = false;
+        }
+        // a reversed diff, as the work tree one is, names the new side a/
+        let new_path = line.strip_prefix("+++ b/");
+        if let Some(path) = new_path.or_else(|| line.strip_prefix("+++ a/")) {
+            self.file_path = Some(path.to_string());
+        }
+        if diff::is_gitlink_header(line) {
+            self.gitlink = true;
+        }
+        self.file_lines += 1;
+        match self.fold_after {
+            Some(max) if self.file_lines > max => match self.rows.last_mut() {
+                Some(Row::Folded(count)) => *count += 1,
+                _ => {
+                    self.rows.push(Row::Folded(1));
+                    self.folded = true;
+                }
+            },
+            _ => {
+                let submodule = match (self.diff.is_some(), self.rows.last(), &self.file_path) {
+                    (true, Some(Row::Line(prev)), Some(path)) if self.gitlink => {
+                        diff::Submodule::parse(path, &self.lines[*prev], line)
+                    }
+                    _ => None,
+                };
+                match submodule {
+                    // the line before is the other half of the change
+                    Some(submodule) => {
+                        self.rows.pop();
+                        self.rows.push(Row::Submodule(submodule));
+                    }
+                    None => self.rows.push(Row::Line(idx)),
+                }
+            }
+        }
+    }
+
+    pub fn diff(&self) -> Option<&diff::Shown> {
+        self.diff.as_ref()
+    }
+
+    /// Scrolls to where `other` is scrolled, and covers as much of the screen,
+    /// for output that replaces it.
+    pub fn keep_scroll(mut self, other: &Output) -> Self {
+        self.scroll = other.scroll;
+        self.full = other.full;
+        self
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.succeeded
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    /// Collects new output. Returns true once the command has just exited.
+    pub fn poll(&mut self) -> bool {
+        for _ in 0..MAX_LINES_PER_POLL {
+            let Some(Ok(msg)) = self.rx.as_ref().map(mpsc::Receiver::try_recv) else {
+                break;
+            };
+            match msg {
+                Msg::Line(line) => self.push(line),
+                Msg::Exit(status) => {
+                    self.succeeded = status.as_ref().is_ok_and(ExitStatus::success);
+                    self.status = Some(match status {
+                        Ok(status) if status.success() => t!("done").to_string(),
+                        Ok(status) => t!("exited with {status}", status = status),
+                        Err(err) => err.to_string(),
+                    });
+                    self.rx = None;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    pub fn kill(&self) {
+        self.kill.store(true, Ordering::Relaxed);
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.rows.len().saturating_sub(self.height);
+        let pos = self.scroll.unwrap_or(max).saturating_add_signed(delta);
+        self.scroll = (pos < max).then_some(pos);
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll = Some(0);
+    }
+
+    pub fn follow(&mut self) {
+        self.scroll = None;
+    }
+}
+
+fn forward(
+    pipe: Option<impl Read + Send + 'static>,
+    tx: mpsc::Sender<Msg>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let Some(pipe) = pipe else {
+            return;
+        };
+        for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+            if tx.send(Msg::Line(line)).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+impl Widget for &mut Output {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = match self.full {
+            true => area,
+            false => Rect {
+                x: area.x + area.width / 10,
+                y: area.y + area.height / 10,
+                width: area.width - area.width / 5,
+                height: area.height - area.height / 5,
+            },
+        };
+        let status = match &self.status {
+            Some(status) => status.clone(),
+            None => t!("running…").to_string(),
+        };
+        let hint = match self.diff {
+            Some(_) if self.rows.iter().any(|row| matches!(row, Row::Submodule(_))) => {
+                t!("o: submodule commits  w: whitespace  c: context  esc: close")
+            }
+            Some(_) if self.folded => {
+                t!("x: unfold  w: whitespace  c: context  j/k: scroll  esc: close")
+            }
+            Some(_) => t!("w: whitespace  c: context  j/k: scroll  esc: close"),
+            None if self.rx.is_some() => t!("j/k: scroll  s: stop  esc: hide"),
+            None => t!("j/k: scroll  esc: close"),
+        };
+        let block = Block::new()
+            .title(Line::raw(format!("{} ({status})", redact::text(&self.title))).left_aligned())
+            .title(Line::raw(hint).right_aligned())
+            .borders(Borders::ALL);
+        let inner = block.inner(popup);
+        self.height = inner.height as usize;
+        let max = self.rows.len().saturating_sub(self.height);
+        let pos = self.scroll.unwrap_or(max).min(max);
+        let placeholder = Style::new().fg(AMBER.c200);
+        let lines: Vec<Line> = self.rows[pos..]
+            .iter()
+            .take(self.height)
+            .map(|row| match row {
+                Row::Line(idx) => {
+                    let line = &self.lines[*idx];
+                    let binary = line.starts_with("Binary files ") && line.ends_with(" differ");
+                    match (&self.diff, binary) {
+                        (Some(_), true) => Line::styled(t!("binary file changed"), placeholder),
+                        (Some(_), false) => {
+                            Line::styled(redact::text(line).into_owned(), diff::style(line))
+                        }
+                        (None, _) => Line::raw(redact::text(line).into_owned()),
+                    }
+                }
+                Row::Submodule(submodule) => Line::styled(
+                    t!(
+                        "Subproject commit {old} → {new}",
+                        old = submodule.short(&submodule.old),
+                        new = submodule.short(&submodule.new)
+                    ),
+                    placeholder,
+                ),
+                Row::Folded(count) => Line::styled(
+                    t!(
+                        "… {count} more lines of this file, too many to show — press x to load",
+                        count = count
+                    ),
+                    placeholder,
+                ),
+            })
+            .collect();
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c900))
+            .block(block)
+            .render(popup, buf);
+        super::scroll::scrollbar(inner, buf, self.rows.len(), pos);
+    }
+}
@@ -0,0 +1,67 @@
+//! Commands typed after `:`.
+
+use crate::t;
+
+pub enum Command {
+    /// Runs git with the given arguments.
+    Git(Vec<String>),
+    /// Shows the commits of the selected branch that changed a file, following
+    /// renames unless `--no-follow` is given.
+    History { path: String, follow: bool },
+}
+
+impl Command {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut words = split(s)?.into_iter();
+        match words.next().as_deref() {
+            Some("git") => Ok(Command::Git(words.collect())),
+            Some("history") => {
+                let mut follow = true;
+                let mut path = None;
+                for word in words {
+                    match word.as_str() {
+                        "--no-follow" => follow = false,
+                        "--follow" => follow = true,
+                        _ if path.is_none() => path = Some(word),
+                        _ => return Err(t!("history takes one path").to_string()),
+                    }
+                }
+                let path = path.ok_or_else(|| t!("history needs a path").to_string())?;
+                Ok(Command::History { path, follow })
+            }
+            Some(other) => Err(t!("unknown command: {cmd}", cmd = other)),
+            None => Err(t!("no command given").to_string()),
+        }
+    }
+}
+
+/// Splits a command line into words, honouring single and double quotes and
+/// backslash escapes.
+fn split(s: &str) -> Result<Vec<String>, String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(next) = chars.next() {
+                    word.get_or_insert_with(String::new).push(next);
+                }
+            }
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(t!("unterminated quote").to_string());
+    }
+    words.extend(word);
+    Ok(words)
+}
@@ -0,0 +1,38 @@
+//! Mutations running in the background, shown on their branches as soon as
+//! they are queued rather than once they are done, and forgotten again if they
+//! fail, so that the list goes back to what is true.
+
+use super::jobs::JobId;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Delete,
+    Push,
+}
+
+#[derive(Default)]
+pub struct Pending {
+    /// The local branches each job is changing, and how.
+    jobs: HashMap<JobId, (Vec<String>, Op)>,
+}
+
+impl Pending {
+    pub fn add(&mut self, job: JobId, branches: Vec<String>, op: Op) {
+        self.jobs.insert(job, (branches, op));
+    }
+
+    /// What is being done to the local branch `name`, if anything.
+    pub fn of(&self, name: &str) -> Option<Op> {
+        self.jobs
+            .values()
+            .find(|(branches, _)| branches.iter().any(|b| b == name))
+            .map(|(_, op)| *op)
+    }
+
+    /// Forgets what `job` was doing, now that it finished one way or another,
+    /// returning the branches it was changing and how.
+    pub fn finish(&mut self, job: JobId) -> Option<(Vec<String>, Op)> {
+        self.jobs.remove(&job)
+    }
+}
@@ -0,0 +1,144 @@
+//! The commits of a branch that add or remove a string, as a screen of its own
+//! that fills in while the history is searched.
+
+use crate::{
+    git::{self, pickaxe::Progress},
+    prelude::*,
+    redact, t,
+};
+use ratatui::text::Span;
+use std::sync::mpsc::{self, TryRecvError};
+
+pub struct Search {
+    pub branch: String,
+    pub needle: String,
+    pub hits: Vec<git::pickaxe::Hit>,
+    pub state: ListState,
+    /// How many commits have been looked at.
+    walked: usize,
+    rx: Option<mpsc::Receiver<git::Result<Progress>>>,
+    error: Option<String>,
+}
+
+impl Search {
+    pub fn new(branch: String, needle: String, rx: mpsc::Receiver<git::Result<Progress>>) -> Self {
+        Self {
+            branch,
+            needle,
+            hits: vec![],
+            state: ListState::default(),
+            walked: 0,
+            rx: Some(rx),
+            error: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    /// Takes in what the search found since the last call, returning whether
+    /// there was anything.
+    pub fn poll(&mut self) -> bool {
+        let Some(rx) = &self.rx else {
+            return false;
+        };
+        let mut changed = false;
+        loop {
+            match rx.try_recv() {
+                Ok(Ok(Progress::Found(hit))) => {
+                    self.hits.push(*hit);
+                    if self.state.selected().is_none() {
+                        self.state.select(Some(0));
+                    }
+                }
+                Ok(Ok(Progress::Walked(walked))) => self.walked = walked,
+                Ok(Err(err)) => self.error = Some(err.to_string()),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.rx = None;
+                    changed = true;
+                    break;
+                }
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    pub fn selected(&self) -> Option<&git::pickaxe::Hit> {
+        self.state.selected().and_then(|i| self.hits.get(i))
+    }
+
+    /// The arguments to `git show` that show the selected commit, limited to
+    /// the files that added or removed the string.
+    pub fn show_args(&self) -> Option<Vec<String>> {
+        let hit = self.selected()?;
+        let mut args = vec![
+            format!("-S{}", self.needle),
+            hit.commit.id.to_string(),
+            "--".to_string(),
+        ];
+        args.extend(hit.paths.iter().cloned());
+        Some(args)
+    }
+}
+
+pub struct SearchList<'a>(pub &'a mut Search);
+
+impl Widget for SearchList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let search = self.0;
+        let dim = Style::new().dim();
+        let mut items: Vec<ListItem> = search
+            .hits
+            .iter()
+            .map(|hit| {
+                let c = &hit.commit;
+                let author = c.author.name.as_deref().unwrap_or(t!("<none>"));
+                let paths = hit
+                    .paths
+                    .iter()
+                    .map(|p| redact::text(p))
+                    .collect::<Vec<_>>();
+                ListItem::new(vec![
+                    Line::from(vec![
+                        Span::styled(format!("{} ", c.short), dim),
+                        Span::raw(format!("{}: ", c.timestamp)),
+                        Span::raw(format!("{}: ", redact::person(author))),
+                        Span::raw(redact::text(&c.summary).into_owned()),
+                    ]),
+                    Line::styled(format!("        {}", paths.join(", ")), AMBER.c200),
+                ])
+            })
+            .collect();
+        if let Some(err) = &search.error {
+            items.push(ListItem::new(Line::styled(err.clone(), RED.c300)));
+        } else if items.is_empty() && !search.is_running() {
+            let empty = t!("no commits add or remove {needle}", needle = search.needle);
+            items.push(ListItem::new(Line::styled(empty, dim)));
+        }
+        let title = t!(
+            "Commits adding or removing {needle} on {branch}",
+            needle = format!("\"{}\"", search.needle),
+            branch = redact::branch(&search.branch)
+        );
+        let status = match search.is_running() {
+            true => t!(
+                "searching… {walked} commits, {found} found",
+                walked = search.walked,
+                found = search.hits.len()
+            ),
+            false => t!("{found} found", found = search.hits.len()),
+        };
+        let block = Block::new()
+            .title(Line::raw(title).left_aligned())
+            .title(Line::raw(t!("enter: diff  esc: back")).right_aligned())
+            .title_bottom(Line::styled(status, dim))
+            .borders(Borders::TOP | Borders::BOTTOM);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        StatefulWidget::render(list, area, buf, &mut search.state);
+    }
+}
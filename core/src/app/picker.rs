@@ -0,0 +1,38 @@
+//! A minimal standalone TUI for picking one item, used by headless subcommands.
+
+use super::modal::{self, Modal, Outcome, Value};
+use crate::{bootstrap, opts::Opts, prelude::*, theme};
+
+/// Shows `items` in a full screen picker and returns the index of the chosen
+/// item, or `None` if the user cancelled.
+pub fn pick(opts: &Opts, title: &str, items: Vec<String>) -> EResult<Option<usize>> {
+    let mut terminal = bootstrap::init(opts)?;
+    let res = run(&mut terminal, title, items);
+    bootstrap::restore()?;
+    res
+}
+
+fn run(terminal: &mut bootstrap::Tui, title: &str, items: Vec<String>) -> EResult<Option<usize>> {
+    let mut modal = Modal::Select(modal::Select::new(title, items));
+    loop {
+        terminal.draw(|frame| {
+            frame.render_widget(&mut modal, frame.size());
+            theme::finish(frame.buffer_mut());
+        })?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if key.code == KeyCode::Char('q') {
+            return Ok(None);
+        }
+        match modal.handle_key(key) {
+            Outcome::Pending => {}
+            Outcome::Cancelled => return Ok(None),
+            Outcome::Submitted(Value::Index(idx)) => return Ok(Some(idx)),
+            Outcome::Submitted(_) => return Ok(None),
+        }
+    }
+}
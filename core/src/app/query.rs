@@ -0,0 +1,189 @@
+//! The query of the branch filter: words fuzzy-matched against branch names,
+//! and terms such as `author:me`, `owner:ada`, `ahead>0`, `age<30d` or
+//! `merged:false` checked against what is known of each branch. A branch has
+//! to match all of them.
+
+use super::branch::{self, Owner};
+use crate::{cli, fuzzy, git, t};
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    words: Vec<String>,
+    terms: Vec<Term>,
+}
+
+#[derive(Clone, Debug)]
+enum Term {
+    /// The author of the tip, by part of the name or email, or `me`.
+    Author(String),
+    /// Whose the branch is, as [`branch::owner`] tells, by part of the name,
+    /// or `me`.
+    Owner(String),
+    Ahead(Op, usize),
+    Behind(Op, usize),
+    /// How long ago the tip was committed, in seconds.
+    Age(Op, i64),
+    Merged(bool),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Op {
+    fn holds<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            Op::Less => left < right,
+            Op::LessOrEqual => left <= right,
+            Op::Equal => left == right,
+            Op::GreaterOrEqual => left >= right,
+            Op::Greater => left > right,
+        }
+    }
+}
+
+/// What a query is checked against besides the branch itself.
+pub struct Facts<'a> {
+    /// The configured user name, for `author:me` and `owner:me`.
+    pub me: Option<&'a str>,
+    /// The local branches merged into their upstream or `HEAD`, if a term
+    /// asks.
+    pub merged: &'a HashSet<String>,
+    pub now: i64,
+}
+
+impl Query {
+    /// Parses `text`, failing with a message fit to show next to it.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut query = Query::default();
+        for token in text.split_whitespace() {
+            let Some(at) = token.find([':', '<', '>', '=']) else {
+                query.words.push(token.to_string());
+                continue;
+            };
+            let (key, rest) = token.split_at(at);
+            let (op, value) = match rest {
+                _ if rest.starts_with("<=") => (Op::LessOrEqual, &rest[2..]),
+                _ if rest.starts_with(">=") => (Op::GreaterOrEqual, &rest[2..]),
+                _ if rest.starts_with('<') => (Op::Less, &rest[1..]),
+                _ if rest.starts_with('>') => (Op::Greater, &rest[1..]),
+                _ => (Op::Equal, &rest[1..]),
+            };
+            let count = || {
+                value
+                    .parse()
+                    .map_err(|_| t!("expected a number in {token}", token = token))
+            };
+            let term = match (key, op) {
+                ("author", Op::Equal) if !value.is_empty() => Term::Author(value.to_lowercase()),
+                ("owner", Op::Equal) if !value.is_empty() => Term::Owner(value.to_lowercase()),
+                ("ahead", _) => Term::Ahead(op, count()?),
+                ("behind", _) => Term::Behind(op, count()?),
+                ("age", _) => Term::Age(op, cli::parse_age(value)?),
+                ("merged", Op::Equal) => match value {
+                    "true" | "yes" => Term::Merged(true),
+                    "false" | "no" => Term::Merged(false),
+                    _ => return Err(t!("expected true or false in {token}", token = token)),
+                },
+                ("author" | "owner" | "merged", _) => {
+                    return Err(t!("expected {key}:<value>", key = key));
+                }
+                _ => {
+                    let msg = t!(
+                        "unknown filter {key}, expected author, owner, ahead, behind, age or merged",
+                        key = key
+                    );
+                    return Err(msg);
+                }
+            };
+            query.terms.push(term);
+        }
+        Ok(query)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.terms.is_empty()
+    }
+
+    /// Whether a term needs to know which branches are merged.
+    pub fn needs_merged(&self) -> bool {
+        self.terms.iter().any(|t| matches!(t, Term::Merged(_)))
+    }
+
+    /// Whether a term is about `author:me` or `owner:me`.
+    pub fn needs_me(&self) -> bool {
+        self.terms
+            .iter()
+            .any(|t| matches!(t, Term::Author(name) | Term::Owner(name) if name == "me"))
+    }
+
+    pub fn matches(&self, branch: &git::Branch, facts: &Facts) -> bool {
+        let words = self
+            .words
+            .iter()
+            .all(|word| fuzzy::score(word, &branch.name).is_some());
+        words && self.terms.iter().all(|term| term.matches(branch, facts))
+    }
+}
+
+impl Term {
+    fn matches(&self, branch: &git::Branch, facts: &Facts) -> bool {
+        let tip = branch.commits().first();
+        match self {
+            Term::Author(author) => tip.is_some_and(|c| {
+                let name = c.author.name.as_deref().unwrap_or_default();
+                let email = c.author.email.as_deref().unwrap_or_default();
+                match (author.as_str(), facts.me) {
+                    ("me", Some(me)) => name == me,
+                    // no one is me without a user name
+                    ("me", None) => false,
+                    _ => [name, email]
+                        .iter()
+                        .any(|s| s.to_lowercase().contains(author.as_str())),
+                }
+            }),
+            Term::Owner(owner) => match (branch::owner(branch), owner.as_str(), facts.me) {
+                // no one is me without a user name
+                (_, "me", None) => false,
+                (Some(Owner::Author(name)), "me", Some(me)) => name == me,
+                // a prefix is usually the first name or the whole name run together
+                (Some(Owner::Prefix(prefix)), "me", Some(me)) => {
+                    let prefix = prefix.to_lowercase();
+                    let me = me.to_lowercase();
+                    me.split_whitespace().next() == Some(prefix.as_str())
+                        || me.split_whitespace().collect::<String>() == prefix
+                }
+                (Some(found), _, _) => found.name().to_lowercase().contains(owner.as_str()),
+                (None, _, _) => false,
+            },
+            Term::Ahead(op, count) => divergence(branch).is_some_and(|(a, _)| op.holds(a, *count)),
+            Term::Behind(op, count) => divergence(branch).is_some_and(|(_, b)| op.holds(b, *count)),
+            Term::Age(op, age) => {
+                tip.is_some_and(|c| op.holds(facts.now - c.timestamp.epoch(), *age))
+            }
+            // only known of local branches
+            Term::Merged(merged) => {
+                branch.typ == git2::BranchType::Local
+                    && facts.merged.contains(&branch.name) == *merged
+            }
+        }
+    }
+}
+
+/// How many commits a branch is ahead of and behind its upstream, if it has
+/// one.
+fn divergence(branch: &git::Branch) -> Option<(usize, usize)> {
+    match branch.tracking? {
+        git::Tracking::UpToDate => Some((0, 0)),
+        git::Tracking::Ahead(ahead) => Some((ahead, 0)),
+        git::Tracking::Behind(behind) => Some((0, behind)),
+        git::Tracking::Diverged { ahead, behind } => Some((ahead, behind)),
+        git::Tracking::NoUpstream => None,
+    }
+}
@@ -0,0 +1,90 @@
+//! Selecting a contiguous range of commits in the details pane, in the manner of
+//! vim's visual mode.
+
+use crate::{git, t};
+
+/// A cursor over the commits of a branch, newest first, and the other end of
+/// the range once visual mode is on.
+#[derive(Clone, Debug)]
+pub struct Range {
+    pub branch: String,
+    pub cursor: usize,
+    pub anchor: Option<usize>,
+}
+
+/// What can be done with a range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    CherryPick,
+    Revert,
+    Export,
+    Diffstat,
+    /// Commits what is staged as a fixup of the commit under the cursor.
+    Fixup,
+    /// Folds the fixups above the range into their targets.
+    Autosquash,
+}
+
+impl Op {
+    pub fn label(self) -> &'static str {
+        match self {
+            Op::CherryPick => t!("cherry-pick"),
+            Op::Revert => t!("revert"),
+            Op::Export => t!("export patches"),
+            Op::Diffstat => t!("diffstat"),
+            Op::Fixup => t!("fixup"),
+            Op::Autosquash => t!("autosquash"),
+        }
+    }
+}
+
+impl Range {
+    pub fn new(branch: String) -> Self {
+        Self {
+            branch,
+            cursor: 0,
+            anchor: None,
+        }
+    }
+
+    /// The first and last selected index.
+    pub fn bounds(&self) -> (usize, usize) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        (anchor.min(self.cursor), anchor.max(self.cursor))
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        let (first, last) = self.bounds();
+        (first..=last).contains(&idx)
+    }
+
+    pub fn len(&self) -> usize {
+        let (first, last) = self.bounds();
+        last - first + 1
+    }
+
+    /// Moves the cursor by `delta` commits, staying within `count` commits.
+    pub fn move_by(&mut self, delta: isize, count: usize) {
+        let max = count.saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(max);
+    }
+
+    /// Starts visual mode at the cursor, or leaves it.
+    pub fn toggle_visual(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+    }
+
+    /// The selected commits, oldest first, the order in which they are applied.
+    pub fn commits<'a>(&self, commits: &'a [git::Commit]) -> Vec<&'a git::Commit> {
+        let (first, last) = self.bounds();
+        commits
+            .get(first..=last.min(commits.len().saturating_sub(1)))
+            .unwrap_or_default()
+            .iter()
+            .rev()
+            .collect()
+    }
+}
@@ -0,0 +1,69 @@
+//! The repositories grit opened lately, kept in the state dir: each with the
+//! branch selected when it was left and when it was opened, newest first.
+
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// How many repositories are remembered.
+const MAX: usize = 20;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Repo {
+    pub path: PathBuf,
+    /// The branch selected when grit left it.
+    pub branch: Option<String>,
+    /// When it was opened, in unix seconds.
+    pub opened: i64,
+}
+
+fn file() -> Option<PathBuf> {
+    paths::state_dir().map(|dir| dir.join("recent.json"))
+}
+
+/// The repositories opened lately that are still there, newest first.
+pub fn load() -> Vec<Repo> {
+    let Some(json) = file().and_then(|path| std::fs::read(path).ok()) else {
+        return Vec::new();
+    };
+    let mut repos: Vec<Repo> = serde_json::from_slice(&json).unwrap_or_default();
+    repos.retain(|repo| repo.path.is_dir());
+    repos
+}
+
+/// The branch selected when the repository at `path` was left, if it was
+/// opened lately.
+pub fn branch(path: &Path) -> Option<String> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    load()
+        .into_iter()
+        .find(|repo| repo.path == path)
+        .and_then(|repo| repo.branch)
+}
+
+/// Puts the repository at `path` first, with `branch` selected in it.
+/// Failing to is not worth bothering anyone about.
+pub fn record(path: &Path, branch: Option<String>, opened: i64) {
+    let Some(file) = file() else {
+        return;
+    };
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mut repos = load();
+    repos.retain(|repo| repo.path != path);
+    repos.insert(
+        0,
+        Repo {
+            path,
+            branch,
+            opened,
+        },
+    );
+    repos.truncate(MAX);
+    let Ok(json) = serde_json::to_vec_pretty(&repos) else {
+        return;
+    };
+    if let Some(dir) = file.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(file, json);
+}
@@ -0,0 +1,145 @@
+//! Every ref of the repository, as a screen of its own, for what the branch
+//! list leaves out: notes, replace refs, the `pull/*` refs of forges and the
+//! namespaces of other tools. Below the list is the commit the selected ref
+//! peels to, from which a branch can be created.
+
+use super::listing::Listing;
+use crate::{git, prelude::*, redact, t};
+use ratatui::{text::Span, widgets::Wrap};
+use std::sync::mpsc;
+
+pub struct RefsView {
+    pub list: Listing<git::Ref>,
+    rx: Option<mpsc::Receiver<git::Result<Vec<git::Ref>>>>,
+    error: Option<String>,
+}
+
+impl RefsView {
+    /// A view filled in once `rx`, from [`git::Repository::stream_refs`],
+    /// delivers.
+    pub fn new(rx: mpsc::Receiver<git::Result<Vec<git::Ref>>>) -> Self {
+        Self {
+            list: Listing::default(),
+            rx: Some(rx),
+            error: None,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&git::Ref> {
+        self.list.selected()
+    }
+
+    /// Reads the refs again, keeping the selection on the same ref once they
+    /// are in.
+    pub fn reload(&mut self, rx: mpsc::Receiver<git::Result<Vec<git::Ref>>>) {
+        self.rx = Some(rx);
+    }
+
+    /// Takes the refs once they are read, returning whether anything changed.
+    pub fn poll(&mut self) -> bool {
+        let Some(rx) = &self.rx else {
+            return false;
+        };
+        let res = match rx.try_recv() {
+            Ok(res) => res.map_err(|err| format!("{err:#}")),
+            Err(mpsc::TryRecvError::Empty) => return false,
+            Err(mpsc::TryRecvError::Disconnected) => Err(t!("refs were not read").to_string()),
+        };
+        self.rx = None;
+        match res {
+            Ok(refs) => {
+                self.list.reload(refs);
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err),
+        }
+        true
+    }
+}
+
+/// `name` with all but its namespace, e.g. `refs/heads/`, redacted.
+fn redact_ref(name: &str) -> String {
+    let mut parts = name.splitn(3, '/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some("refs"), Some(kind), Some(rest)) => format!("refs/{kind}/{}", redact::branch(rest)),
+        _ => redact::branch(name).into_owned(),
+    }
+}
+
+pub struct RefList<'a>(pub &'a mut RefsView);
+
+impl Widget for RefList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let view = self.0;
+        let [list, preview] = Layout::vertical([Constraint::Fill(1); 2]).areas(area);
+        let dim = Style::new().dim();
+        let mut items: Vec<ListItem> = view
+            .list
+            .items
+            .iter()
+            .map(|r| {
+                let mut spans = vec![
+                    Span::styled(format!("{:7} ", r.short), dim),
+                    Span::raw(redact_ref(&r.name)),
+                ];
+                if let Some(target) = &r.symbolic {
+                    spans.push(Span::styled(format!(" → {}", redact_ref(target)), dim));
+                }
+                match r.kind {
+                    Some(git2::ObjectType::Commit) | None => {}
+                    Some(kind) => spans.push(Span::styled(format!("  ({kind})"), AMBER.c200)),
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let block = Block::new()
+            .title(Line::raw(t!("Refs")).left_aligned())
+            .title(Line::raw(t!("enter: show  b: branch from ref  esc: back")).right_aligned())
+            .borders(Borders::TOP);
+        if items.is_empty() {
+            let empty = match (&view.error, &view.rx) {
+                (Some(err), _) => Line::styled(err.clone(), RED.c300),
+                (None, Some(_)) => Line::styled(t!("reading refs…"), dim),
+                (None, None) => Line::styled(t!("no refs"), dim),
+            };
+            items.push(ListItem::new(empty));
+        }
+        let widget = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        StatefulWidget::render(widget, list, buf, &mut view.list.state);
+        let Some(selected) = view.selected() else {
+            return;
+        };
+        let block = Block::new()
+            .title(Line::raw(t!("Peeled")).left_aligned())
+            .borders(Borders::TOP);
+        let lines = match &selected.commit {
+            Some(c) => {
+                let author = c.author.name.as_deref().unwrap_or(t!("<none>"));
+                let mut lines = vec![
+                    Line::styled(c.id.to_string(), dim),
+                    Line::raw(format!("{}: {}", c.timestamp, redact::person(author))),
+                    Line::raw(""),
+                ];
+                lines.extend(
+                    redact::text(&c.message)
+                        .lines()
+                        .map(|line| Line::raw(line.to_string())),
+                );
+                lines
+            }
+            None => vec![Line::styled(
+                t!(
+                    "{name} does not point at a commit",
+                    name = redact_ref(&selected.name)
+                ),
+                dim,
+            )],
+        };
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(preview, buf);
+    }
+}
@@ -0,0 +1,140 @@
+//! The remotes: a popup listing them with their fetch refspecs and prune
+//! setting, and a tab of their own next to the branches.
+
+use super::listing::Listing;
+use crate::{git, prelude::*, redact, t};
+use ratatui::{text::Span, widgets::Clear};
+
+pub type Remotes = Listing<git::Remote>;
+
+pub struct RemoteList<'a>(pub &'a mut Remotes);
+
+impl Widget for RemoteList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let dim = Style::new().dim();
+        let items: Vec<ListItem> = self
+            .0
+            .items
+            .iter()
+            .map(|remote| {
+                let prune = match remote.prune {
+                    Some(true) => t!("prune: on"),
+                    Some(false) => t!("prune: off"),
+                    None => t!("prune: default"),
+                };
+                let mut lines = vec![Line::from(vec![
+                    Span::styled(remote.name.clone(), Style::new().bold()),
+                    Span::raw("  "),
+                    Span::styled(shown_url(remote), dim),
+                    Span::raw("  "),
+                    Span::styled(prune, dim),
+                ])];
+                if remote.fetch.is_empty() {
+                    lines.push(Line::styled(
+                        format!("  {}", t!("no fetch refspecs")),
+                        RED.c300,
+                    ));
+                }
+                for spec in &remote.fetch {
+                    lines.push(Line::raw(format!("  {spec}")));
+                }
+                ListItem::new(lines)
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::new()
+                    .title(Line::raw(t!("Remotes")).left_aligned())
+                    .title(
+                        Line::raw(t!("e: edit refspecs  p: toggle prune  esc: close"))
+                            .right_aligned(),
+                    )
+                    .borders(Borders::ALL),
+            )
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c800))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        Clear.render(popup, buf);
+        StatefulWidget::render(list, popup, buf, &mut self.0.state);
+    }
+}
+
+/// The remotes as a tab next to the branches: each with its URL, and below
+/// the list the refspecs and branches of the selected one.
+pub struct RemoteTab<'a>(pub &'a mut Remotes);
+
+impl Widget for RemoteTab<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [list, preview] = Layout::vertical([Constraint::Fill(1); 2]).areas(area);
+        let dim = Style::new().dim();
+        let width = self
+            .0
+            .items
+            .iter()
+            .map(|r| r.name.chars().count())
+            .max()
+            .unwrap_or_default();
+        let mut items: Vec<ListItem> = self
+            .0
+            .items
+            .iter()
+            .map(|remote| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{:width$}  ", remote.name)),
+                    Span::styled(shown_url(remote), dim),
+                ]))
+            })
+            .collect();
+        let count = t!("{count} remotes", count = self.0.items.len());
+        let block = Block::new()
+            .title(Line::raw(count).left_aligned())
+            .title(Line::raw(t!("esc: back")).right_aligned())
+            .borders(Borders::TOP);
+        if items.is_empty() {
+            items.push(ListItem::new(Line::styled(t!("no remotes"), dim)));
+        }
+        let widget = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        StatefulWidget::render(widget, list, buf, &mut self.0.state);
+        let Some(remote) = self.0.selected() else {
+            return;
+        };
+        let mut lines = vec![Line::raw(shown_url(remote))];
+        lines.extend(
+            remote
+                .fetch
+                .iter()
+                .map(|spec| Line::styled(spec.clone(), dim)),
+        );
+        lines.push(Line::raw(""));
+        lines.push(Line::raw(t!(
+            "{count} branches",
+            count = remote.branches.len()
+        )));
+        lines.extend(
+            remote
+                .branches
+                .iter()
+                .map(|name| Line::raw(format!("  {}", redact::branch(name)))),
+        );
+        Paragraph::new(lines)
+            .block(
+                Block::new()
+                    .title(Line::raw(remote.name.clone()).left_aligned())
+                    .borders(Borders::TOP),
+            )
+            .render(preview, buf);
+    }
+}
+
+/// The URL of `remote` without the credentials it may carry, redacted in demo
+/// mode.
+fn shown_url(remote: &git::Remote) -> String {
+    redact::url(remote.url.as_deref().unwrap_or_default()).into_owned()
+}
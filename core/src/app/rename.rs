@@ -0,0 +1,171 @@
+//! Planning of bulk branch renames.
+
+use crate::{git::refname, t};
+use regex::Regex;
+use std::collections::HashSet;
+
+pub enum Pattern {
+    Prefix(String),
+    Suffix(String),
+    Replace(Regex, String),
+}
+
+impl Pattern {
+    /// Parses `prefix:<text>`, `suffix:<text>` or `s/<regex>/<replacement>/`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if let Some(prefix) = s.strip_prefix("prefix:") {
+            return Ok(Self::Prefix(prefix.to_string()));
+        }
+        if let Some(suffix) = s.strip_prefix("suffix:") {
+            return Ok(Self::Suffix(suffix.to_string()));
+        }
+        if let Some(rest) = s.strip_prefix("s/") {
+            let rest = rest.strip_suffix('/').unwrap_or(rest);
+            let Some((re, rep)) = rest.split_once('/') else {
+                return Err(t!("expected s/<regex>/<replacement>/").to_string());
+            };
+            let re = Regex::new(re).map_err(|err| err.to_string())?;
+            return Ok(Self::Replace(re, rep.to_string()));
+        }
+        Err(t!("expected prefix:<text>, suffix:<text> or s/<regex>/<replacement>/").to_string())
+    }
+
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            Pattern::Prefix(prefix) => format!("{prefix}{name}"),
+            Pattern::Suffix(suffix) => format!("{name}{suffix}"),
+            Pattern::Replace(re, rep) => re.replace(name, rep.as_str()).into_owned(),
+        }
+    }
+}
+
+pub struct Rename {
+    pub old: String,
+    pub new: String,
+    pub problem: Option<String>,
+}
+
+/// Works out the new name of each branch, flagging invalid names and collisions
+/// with the local branches `existing` or with each other, including a name
+/// that another would have to be a directory of, as `feat` and `feat/x`.
+/// Unchanged names are left out.
+pub fn plan(pattern: &Pattern, names: &[String], existing: &[String]) -> Vec<Rename> {
+    let mut renames: Vec<Rename> = names
+        .iter()
+        .map(|old| {
+            let new = pattern.apply(old);
+            Rename {
+                old: old.clone(),
+                new,
+                problem: None,
+            }
+        })
+        .filter(|r| r.old != r.new)
+        .collect();
+    let moved: HashSet<&str> = renames.iter().map(|r| r.old.as_str()).collect();
+    // the names there are once every rename is done
+    let kept: Vec<&str> = existing
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !moved.contains(name))
+        .collect();
+    let news: Vec<String> = renames.iter().map(|r| r.new.clone()).collect();
+    for rename in &mut renames {
+        let new = &rename.new;
+        let nested = kept
+            .iter()
+            .copied()
+            .chain(news.iter().map(String::as_str))
+            .find(|other| other != new && conflicts(other, new));
+        rename.problem = if let Err(err) = refname::validate(new) {
+            Some(err.to_string())
+        } else if kept.contains(&new.as_str()) {
+            Some(t!("already exists").to_string())
+        } else if news.iter().filter(|n| *n == new).count() > 1 {
+            Some(t!("duplicate name").to_string())
+        } else {
+            nested.map(|other| t!("conflicts with {name}", name = other).to_string())
+        };
+    }
+    renames
+}
+
+/// One branch rename of those [`order`] works out, to the new name or a
+/// temporary one.
+pub struct Step {
+    pub from: String,
+    pub to: String,
+}
+
+/// The steps that carry out `renames`, each of an old name and a new one
+/// [`plan`] found no problem with, among the local branches `existing`. A
+/// rename goes after those that free its new name, as `b` → `c` before `a`
+/// → `b`, and a branch goes through a temporary name where they wait on each
+/// other, as in a swap or `feat` → `feat/x`.
+pub fn order(renames: &[(String, String)], existing: &[String]) -> Vec<Step> {
+    let mut current: Vec<String> = existing.to_vec();
+    // the name each branch has now, its new name, and its name before
+    let mut pending: Vec<(String, String, String)> = renames
+        .iter()
+        .map(|(old, new)| (old.clone(), new.clone(), old.clone()))
+        .collect();
+    let mut steps = vec![];
+    let mut temporary = 0;
+    while !pending.is_empty() {
+        let free = pending
+            .iter()
+            .position(|(_, to, _)| !current.iter().any(|name| conflicts(name, to)));
+        let (idx, to, done) = match free {
+            Some(idx) => (idx, pending[idx].1.clone(), true),
+            None => {
+                // each branch gets out of the way once at most
+                let Some(idx) = pending.iter().position(|(from, _, old)| from == old) else {
+                    break;
+                };
+                let to = loop {
+                    temporary += 1;
+                    let to = format!("grit-rename-{temporary}");
+                    if !current.iter().any(|name| conflicts(name, &to)) {
+                        break to;
+                    }
+                };
+                (idx, to, false)
+            }
+        };
+        let from = pending[idx].0.clone();
+        current.retain(|name| *name != from);
+        current.push(to.clone());
+        if done {
+            pending.remove(idx);
+        } else {
+            pending[idx].0 = to.clone();
+        }
+        steps.push(Step { from, to });
+    }
+    // what could not be ordered goes as is, to fail on its own
+    steps.extend(pending.into_iter().map(|(from, to, _)| Step { from, to }));
+    steps
+}
+
+/// Whether refs `a` and `b` cannot both exist: the same name, or one a
+/// directory of the other.
+fn conflicts(a: &str, b: &str) -> bool {
+    let nested = |outer: &str, inner: &str| {
+        inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.starts_with('/'))
+    };
+    a == b || nested(a, b) || nested(b, a)
+}
+
+/// A preview of the plan, one `old → new` line per branch.
+pub fn preview(renames: &[Rename]) -> String {
+    renames
+        .iter()
+        .map(|r| match &r.problem {
+            Some(problem) => format!("{} → {}  ✗ {problem}", r.old, r.new),
+            None => format!("{} → {}", r.old, r.new),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
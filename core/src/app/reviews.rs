@@ -0,0 +1,259 @@
+//! The review state of the pull requests of branches, fetched in the background.
+//!
+//! What was fetched is kept per repository in the cache directory, so a restart
+//! does not hit the APIs again until it expires, and a forge that cannot be
+//! reached still shows what was last known along with its age.
+
+use crate::{
+    forge::{self, limit, Forge, Review},
+    paths,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc},
+};
+
+/// Entries older than this are dropped from the cache file.
+const MAX_AGE: i64 = 30 * 24 * 60 * 60;
+
+pub enum Lookup<'a> {
+    Pending,
+    Found {
+        /// The open pull request, if the branch has one.
+        review: Option<&'a Review>,
+        /// When it was fetched, if that is too long ago to trust.
+        stale: Option<i64>,
+    },
+    Failed(&'a str),
+    /// Not looked up because the forge is rate limited until then.
+    Limited(DateTime<Local>),
+}
+
+/// Why a lookup failed.
+enum Failure {
+    Limited(DateTime<Local>),
+    Error(String),
+    /// There is no token to look it up with.
+    NoToken,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    review: Option<Review>,
+    /// Unix seconds.
+    fetched: i64,
+    /// Set once something happened that may have changed the pull request.
+    #[serde(skip)]
+    outdated: bool,
+}
+
+/// Review states by local branch name.
+pub struct Reviews {
+    entries: HashMap<String, Entry>,
+    /// Branches looked up or skipped this session.
+    done: HashSet<String>,
+    pending: HashSet<String>,
+    failed: HashMap<String, String>,
+    /// Branches to look up again once the rate limit of their forge is over.
+    limited: HashMap<String, DateTime<Local>>,
+    path: Option<PathBuf>,
+    /// How long an entry is trusted, in seconds.
+    ttl: i64,
+    tx: mpsc::Sender<(String, Result<Option<Review>, Failure>)>,
+    rx: mpsc::Receiver<(String, Result<Option<Review>, Failure>)>,
+}
+
+impl Reviews {
+    /// Reviews cached for the repository at `git_dir`, if there are any.
+    pub fn load(git_dir: &Path, ttl_minutes: u64) -> Self {
+        let path = paths::repo_cache("forge", git_dir);
+        let now = chrono::Utc::now().timestamp();
+        let entries = path
+            .as_ref()
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|json| serde_json::from_slice::<HashMap<String, Entry>>(&json).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, entry)| now - entry.fetched < MAX_AGE)
+            .collect();
+        let (tx, rx) = mpsc::channel();
+        Self {
+            entries,
+            done: HashSet::new(),
+            pending: HashSet::new(),
+            failed: HashMap::new(),
+            limited: HashMap::new(),
+            path,
+            ttl: i64::try_from(ttl_minutes.saturating_mul(60)).unwrap_or(i64::MAX),
+            tx,
+            rx,
+        }
+    }
+
+    pub fn get(&self, branch: &str) -> Option<Lookup<'_>> {
+        let failed = self.failed.get(branch);
+        let limited = self.limited.get(branch);
+        match self.entries.get(branch) {
+            Some(entry) => {
+                let now = chrono::Utc::now().timestamp();
+                let expired = now - entry.fetched > self.ttl;
+                let missed = failed.is_some() || limited.is_some();
+                Some(Lookup::Found {
+                    review: entry.review.as_ref(),
+                    stale: (expired || missed).then_some(entry.fetched),
+                })
+            }
+            None if self.pending.contains(branch) => Some(Lookup::Pending),
+            None => match limited {
+                Some(until) => Some(Lookup::Limited(*until)),
+                None => failed.map(|err| Lookup::Failed(err)),
+            },
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// How many lookups are under way.
+    pub fn in_flight(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Forgets that the branches whose forge is no longer rate limited were
+    /// looked up, so that they are again, returning whether there were any.
+    pub fn retry_due(&mut self) -> bool {
+        let now = Local::now();
+        let due: Vec<String> = self
+            .limited
+            .iter()
+            .filter(|(_, until)| **until <= now)
+            .map(|(branch, _)| branch.clone())
+            .collect();
+        for branch in &due {
+            self.limited.remove(branch);
+            self.done.remove(branch);
+        }
+        !due.is_empty()
+    }
+
+    /// How long until the rate limit that stopped a lookup is over.
+    pub fn retry_wait(&self) -> Option<std::time::Duration> {
+        let until = self.limited.values().min()?;
+        Some((*until - Local::now()).to_std().unwrap_or_default())
+    }
+
+    /// Whether `branch` should be looked up: once per session, unless the cache
+    /// has a fresh entry, and again once a rate limit that stopped it is over.
+    pub fn wants(&self, branch: &str) -> bool {
+        if let Some(until) = self.limited.get(branch) {
+            return *until <= Local::now();
+        }
+        if self.done.contains(branch) {
+            return false;
+        }
+        let now = chrono::Utc::now().timestamp();
+        self.entries
+            .get(branch)
+            .is_none_or(|e| e.outdated || now - e.fetched > self.ttl)
+    }
+
+    /// Remembers that `branch` has no pull request to look up.
+    pub fn skip(&mut self, branch: String) {
+        self.done.insert(branch);
+    }
+
+    /// Looks up the pull request from `head` on a thread of its own, which
+    /// reads the token too, unless the forge is rate limited, which needs no
+    /// thread to find out.
+    pub fn request(
+        &mut self,
+        branch: String,
+        forge: Arc<dyn Forge>,
+        repo: forge::Repo,
+        head: String,
+    ) {
+        self.done.insert(branch.clone());
+        self.limited.remove(&branch);
+        let blocked = limit::blocked();
+        if let Some((_, until)) = blocked.into_iter().find(|(host, _)| *host == repo.host) {
+            self.limited.insert(branch, until);
+            return;
+        }
+        self.pending.insert(branch.clone());
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            let res = match forge::token::cached(&*forge, &repo.host) {
+                Ok(Some(token)) => forge.review(&repo, &token, &head).map_err(|err| {
+                    match err.downcast_ref::<limit::Limited>() {
+                        Some(limited) => Failure::Limited(limited.until),
+                        None => Failure::Error(format!("{err:#}")),
+                    }
+                }),
+                Ok(None) => Err(Failure::NoToken),
+                Err(err) => Err(Failure::Error(format!("{err:#}"))),
+            };
+            let _ = tx.send((branch, res));
+        });
+    }
+
+    /// Collects finished lookups, returning whether there were any.
+    pub fn poll(&mut self) -> bool {
+        let mut any = false;
+        while let Ok((branch, res)) = self.rx.try_recv() {
+            self.pending.remove(&branch);
+            match res {
+                Ok(review) => {
+                    self.failed.remove(&branch);
+                    let fetched = chrono::Utc::now().timestamp();
+                    let entry = Entry {
+                        review,
+                        fetched,
+                        outdated: false,
+                    };
+                    self.entries.insert(branch, entry);
+                }
+                Err(Failure::Limited(until)) => {
+                    self.limited.insert(branch, until);
+                }
+                Err(Failure::Error(err)) => {
+                    self.failed.insert(branch, err);
+                }
+                // nothing to tell, as without a token nothing is looked up
+                Err(Failure::NoToken) => {}
+            }
+            any = true;
+        }
+        if any {
+            self.save();
+        }
+        any
+    }
+
+    /// Looks branches up again, keeping what is known until then.
+    pub fn invalidate(&mut self) {
+        self.done.clear();
+        self.failed.clear();
+        self.limited.clear();
+        for entry in self.entries.values_mut() {
+            entry.outdated = true;
+        }
+    }
+
+    /// Writes the cache file. Failing to is not worth bothering anyone about.
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let Ok(json) = serde_json::to_vec(&self.entries) else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = std::fs::write(path, json);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::prelude::*;
+use ratatui::widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState};
+
+/// Draws a vertical scrollbar along the right edge of `area` when the content
+/// does not fit.
+pub fn scrollbar(area: Rect, buf: &mut Buffer, content_len: usize, position: usize) {
+    if content_len <= area.height as usize {
+        return;
+    }
+    let mut state = ScrollbarState::new(content_len)
+        .position(position)
+        .viewport_content_length(area.height as usize);
+    Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .render(area, buf, &mut state);
+}
+
+/// Moves the first row of a list shown on a screen `from` rows tall so that,
+/// on one `to` rows tall, its selected row is about as far down as it was,
+/// rather than wherever the list widget leaves it, e.g. at the very bottom
+/// once the screen got shorter.
+pub fn rescale(state: &mut ListState, from: u16, to: u16) {
+    let Some(selected) = state.selected() else {
+        return;
+    };
+    // not in view, which the widget sorts out
+    let Some(row) = selected.checked_sub(state.offset()) else {
+        return;
+    };
+    if from == 0 || from == to || row >= usize::from(from) {
+        return;
+    }
+    let (from, to) = (usize::from(from), usize::from(to));
+    let row = ((row * to + from / 2) / from).min(to.saturating_sub(1));
+    *state.offset_mut() = selected.saturating_sub(row);
+}
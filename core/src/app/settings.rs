@@ -0,0 +1,266 @@
+//! The config options that can be changed while grit runs, as a popup. Each
+//! change takes effect at once and is written back to the config file, which
+//! keeps its comments and layout. The keymap is only looked over, for bindings
+//! that keep others from being reached.
+
+use crate::{app::Sort, config::Config, git, prelude::*, t};
+use color_eyre::eyre::{eyre, WrapErr};
+use ratatui::widgets::Clear;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Bool,
+    Number,
+    Sort,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Value {
+    Bool(bool),
+    Number(usize),
+    Sort(Sort),
+}
+
+pub struct Setting {
+    /// Where it is in the config file, e.g. `view.heat`.
+    pub key: &'static str,
+    pub kind: Kind,
+    /// What it is unset, if anything.
+    pub default: Option<Value>,
+}
+
+pub const SETTINGS: [Setting; 18] = [
+    setting(
+        "view.sort",
+        Kind::Sort,
+        Some(Value::Sort(Sort::DateDescending)),
+    ),
+    setting("view.group", Kind::Bool, Some(Value::Bool(false))),
+    setting("view.stacks", Kind::Bool, Some(Value::Bool(false))),
+    setting("view.author_colors", Kind::Bool, Some(Value::Bool(false))),
+    setting("view.heat", Kind::Bool, Some(Value::Bool(false))),
+    setting("view.heat_weeks", Kind::Number, Some(Value::Number(12))),
+    setting("view.hints", Kind::Bool, Some(Value::Bool(true))),
+    setting("view.watch", Kind::Bool, Some(Value::Bool(false))),
+    setting("view.max_fps", Kind::Number, Some(Value::Number(60))),
+    setting("view.replace", Kind::Bool, Some(Value::Bool(true))),
+    setting(
+        "max_commits",
+        Kind::Number,
+        Some(Value::Number(git::DEFAULT_MAX_COMMITS)),
+    ),
+    setting("stash.untracked", Kind::Bool, Some(Value::Bool(false))),
+    setting("editor.external", Kind::Bool, Some(Value::Bool(false))),
+    setting("diff.renames", Kind::Bool, Some(Value::Bool(true))),
+    setting("diff.fold_lines", Kind::Number, Some(Value::Number(1000))),
+    setting("commit.subject_max", Kind::Number, None),
+    setting("commit.body_max", Kind::Number, None),
+    setting("commit.enforce", Kind::Bool, Some(Value::Bool(false))),
+];
+
+const fn setting(key: &'static str, kind: Kind, default: Option<Value>) -> Setting {
+    Setting { key, kind, default }
+}
+
+/// The value of the setting at `key` in `config`, `None` for one unset without
+/// a default.
+pub fn get(config: &Config, key: &str) -> Option<Value> {
+    let view = &config.view;
+    let value = match key {
+        "view.sort" => Value::Sort(view.sort.unwrap_or_default()),
+        "view.group" => Value::Bool(view.group),
+        "view.stacks" => Value::Bool(view.stacks),
+        "view.author_colors" => Value::Bool(view.author_colors),
+        "view.heat" => Value::Bool(view.heat),
+        "view.heat_weeks" => Value::Number(view.heat_weeks.unwrap_or(12)),
+        "view.hints" => Value::Bool(view.hints.unwrap_or(true)),
+        "view.watch" => Value::Bool(view.watch),
+        "view.max_fps" => Value::Number(view.max_fps.unwrap_or(60) as usize),
+        "view.replace" => Value::Bool(view.replace.unwrap_or(true)),
+        "max_commits" => Value::Number(config.max_commits()),
+        "stash.untracked" => Value::Bool(config.stash.untracked),
+        "editor.external" => Value::Bool(config.editor.external),
+        "diff.renames" => Value::Bool(config.diff.renames.unwrap_or(true)),
+        "diff.fold_lines" => Value::Number(config.diff.fold_lines.unwrap_or(1000)),
+        "commit.subject_max" => Value::Number(config.commit.subject_max?),
+        "commit.body_max" => Value::Number(config.commit.body_max?),
+        "commit.enforce" => Value::Bool(config.commit.enforce),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Sets the setting at `key` in `config`, or puts it back to its default.
+pub fn set(config: &mut Config, key: &str, value: Option<Value>) {
+    let flag = |value| match value {
+        Some(Value::Bool(b)) => Some(b),
+        _ => None,
+    };
+    let number = |value| match value {
+        Some(Value::Number(n)) => Some(n),
+        _ => None,
+    };
+    let view = &mut config.view;
+    match key {
+        "view.sort" => {
+            view.sort = match value {
+                Some(Value::Sort(sort)) => Some(sort),
+                _ => None,
+            }
+        }
+        "view.group" => view.group = flag(value).unwrap_or_default(),
+        "view.stacks" => view.stacks = flag(value).unwrap_or_default(),
+        "view.author_colors" => view.author_colors = flag(value).unwrap_or_default(),
+        "view.heat" => view.heat = flag(value).unwrap_or_default(),
+        "view.heat_weeks" => view.heat_weeks = number(value),
+        "view.hints" => view.hints = flag(value),
+        "view.watch" => view.watch = flag(value).unwrap_or_default(),
+        "view.max_fps" => view.max_fps = number(value).map(|n| n.min(u32::MAX as usize) as u32),
+        "view.replace" => view.replace = flag(value),
+        "max_commits" => config.max_commits = number(value),
+        "stash.untracked" => config.stash.untracked = flag(value).unwrap_or_default(),
+        "editor.external" => config.editor.external = flag(value).unwrap_or_default(),
+        "diff.renames" => config.diff.renames = flag(value),
+        "diff.fold_lines" => config.diff.fold_lines = number(value),
+        "commit.subject_max" => config.commit.subject_max = number(value),
+        "commit.body_max" => config.commit.body_max = number(value),
+        "commit.enforce" => config.commit.enforce = flag(value).unwrap_or_default(),
+        _ => {}
+    }
+}
+
+/// Writes the setting at `key` to the config file at `path`, removing it for
+/// `None`. The rest of the file is left the way it was.
+pub fn write(path: &Path, key: &str, value: Option<Value>) -> EResult<()> {
+    let item = match value {
+        Some(Value::Bool(b)) => Some(toml_edit::value(b)),
+        Some(Value::Number(n)) => {
+            let n = i64::try_from(n).map_err(|_| eyre!("{n} is too large for {key}"))?;
+            Some(toml_edit::value(n))
+        }
+        Some(Value::Sort(sort)) => Some(toml_edit::value(sort.key())),
+        None => None,
+    };
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(err).wrap_err_with(|| format!("read config {}", path.display()));
+        }
+    };
+    let mut doc: toml_edit::DocumentMut = contents
+        .parse()
+        .wrap_err_with(|| format!("parse config {}", path.display()))?;
+    let (tables, name) = match key.rsplit_once('.') {
+        Some((tables, name)) => (Some(tables), name),
+        None => (None, key),
+    };
+    let mut table = doc.as_table_mut();
+    for part in tables.into_iter().flat_map(|t| t.split('.')) {
+        table = table
+            .entry(part)
+            .or_insert_with(toml_edit::table)
+            .as_table_mut()
+            .ok_or_else(|| eyre!("{part} in {} is not a table", path.display()))?;
+    }
+    match item {
+        Some(item) => table[name] = item,
+        None => {
+            table.remove(name);
+        }
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).wrap_err_with(|| format!("create {}", dir.display()))?;
+    }
+    std::fs::write(path, doc.to_string())
+        .wrap_err_with(|| format!("write config {}", path.display()))
+}
+
+fn show(value: Option<Value>) -> String {
+    match value {
+        Some(Value::Bool(true)) => t!("on").to_string(),
+        Some(Value::Bool(false)) => t!("off").to_string(),
+        Some(Value::Number(n)) => n.to_string(),
+        Some(Value::Sort(sort)) => sort.label().to_string(),
+        None => t!("unset").to_string(),
+    }
+}
+
+pub struct Settings {
+    pub state: ListState,
+    /// The keymap bindings that keep others from being reached.
+    pub conflicts: Vec<String>,
+}
+
+impl Settings {
+    pub fn new(conflicts: Vec<String>) -> Self {
+        Self {
+            state: ListState::default().with_selected(Some(0)),
+            conflicts,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&'static Setting> {
+        self.state.selected().and_then(|i| SETTINGS.get(i))
+    }
+}
+
+pub struct SettingList<'a> {
+    pub settings: &'a mut Settings,
+    pub config: &'a Config,
+}
+
+impl Widget for SettingList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let dim = Style::new().dim();
+        let width = SETTINGS.iter().map(|s| s.key.len()).max().unwrap_or(0);
+        let items: Vec<ListItem> = SETTINGS
+            .iter()
+            .map(|setting| {
+                let value = get(self.config, setting.key);
+                let style = match value == setting.default {
+                    true => dim,
+                    false => Style::new().bold(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{:width$}  ", setting.key)),
+                    Span::styled(show(value), style),
+                ]))
+            })
+            .collect();
+        let mut conflicts = vec![Line::styled(t!("Keymap conflicts"), Style::new().bold())];
+        if self.settings.conflicts.is_empty() {
+            conflicts.push(Line::styled(t!("none"), dim));
+        }
+        conflicts.extend(
+            self.settings
+                .conflicts
+                .iter()
+                .map(|c| Line::styled(c.clone(), AMBER.c300)),
+        );
+        let block = Block::new()
+            .title(Line::raw(t!("Settings")).left_aligned())
+            .title(Line::raw(t!("enter: change  d: default  esc: close")).right_aligned())
+            .borders(Borders::ALL)
+            .style(Style::new().fg(SLATE.c100).bg(SLATE.c800));
+        Clear.render(popup, buf);
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+        let height = (conflicts.len() as u16 + 1).min(inner.height / 2);
+        let [list, keymap] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(height)]).areas(inner);
+        let list_widget =
+            List::new(items).highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        StatefulWidget::render(list_widget, list, buf, &mut self.settings.state);
+        Paragraph::new(conflicts)
+            .block(Block::new().borders(Borders::TOP))
+            .render(keymap, buf);
+    }
+}
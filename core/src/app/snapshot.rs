@@ -0,0 +1,223 @@
+//! Writes what is on screen to a file, for sharing a view of the branches in a
+//! chat or a ticket. The extension picks the format: `.html`, `.ans` for ANSI
+//! escapes that `cat` can show, or plain text otherwise.
+
+use crate::prelude::*;
+use color_eyre::eyre::Context;
+use ratatui::{
+    buffer::{Buffer, Cell},
+    crossterm::{
+        style::{Attribute, SetAttribute, SetBackgroundColor, SetForegroundColor},
+        Command,
+    },
+};
+use std::{fmt::Write, path::Path};
+use unicode_width::UnicodeWidthStr;
+
+/// The colors of the page, for cells that use the terminal's.
+const HTML_FG: (u8, u8, u8) = (0xd0, 0xd0, 0xd0);
+const HTML_BG: (u8, u8, u8) = (0x1c, 0x1c, 0x1c);
+
+/// The xterm defaults for the 16 named colors.
+const PALETTE: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x00, 0x00),
+    (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00),
+    (0x00, 0x00, 0xee),
+    (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00),
+    (0x00, 0xff, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff),
+    (0xff, 0x00, 0xff),
+    (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+pub fn save(buf: &Buffer, path: &Path) -> EResult<()> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let content = match ext.to_ascii_lowercase().as_str() {
+        "html" | "htm" => html(buf),
+        "ans" | "ansi" => ansi(buf),
+        _ => text(buf),
+    };
+    std::fs::write(path, content).wrap_err_with(|| format!("write {}", path.display()))
+}
+
+/// The cells of each row, leaving out those covered by a wide character
+/// before them.
+fn rows(buf: &Buffer) -> impl Iterator<Item = Vec<&Cell>> {
+    buf.content()
+        .chunks(buf.area.width.max(1) as usize)
+        .map(|row| {
+            let mut covered = 0;
+            row.iter()
+                .filter(|cell| {
+                    if covered > 0 {
+                        covered -= 1;
+                        return false;
+                    }
+                    covered = cell.symbol().width().saturating_sub(1);
+                    !cell.skip
+                })
+                .collect()
+        })
+}
+
+fn text(buf: &Buffer) -> String {
+    let mut out = String::new();
+    for row in rows(buf) {
+        let line: String = row.iter().map(|cell| cell.symbol()).collect();
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn ansi(buf: &Buffer) -> String {
+    let mut out = String::new();
+    for row in rows(buf) {
+        let mut last = None;
+        for cell in row {
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if last != Some(style) {
+                let _ = SetAttribute(Attribute::Reset).write_ansi(&mut out);
+                let _ = SetForegroundColor(cell.fg.into()).write_ansi(&mut out);
+                let _ = SetBackgroundColor(cell.bg.into()).write_ansi(&mut out);
+                for (modifier, attr) in ATTRIBUTES {
+                    if cell.modifier.contains(modifier) {
+                        let _ = SetAttribute(attr).write_ansi(&mut out);
+                    }
+                }
+                last = Some(style);
+            }
+            out.push_str(cell.symbol());
+        }
+        let _ = SetAttribute(Attribute::Reset).write_ansi(&mut out);
+        out.push('\n');
+    }
+    out
+}
+
+const ATTRIBUTES: [(Modifier, Attribute); 6] = [
+    (Modifier::BOLD, Attribute::Bold),
+    (Modifier::DIM, Attribute::Dim),
+    (Modifier::ITALIC, Attribute::Italic),
+    (Modifier::UNDERLINED, Attribute::Underlined),
+    (Modifier::REVERSED, Attribute::Reverse),
+    (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+];
+
+fn html(buf: &Buffer) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<meta charset=\"utf-8\">\n<title>grit</title>\n\
+         <pre style=\"color: {}; background: {}; font-family: monospace; padding: 1em\">\n",
+        hex(HTML_FG),
+        hex(HTML_BG)
+    );
+    for row in rows(buf) {
+        let mut cells = row.into_iter().peekable();
+        while let Some(first) = cells.next() {
+            let mut run = String::new();
+            escape(&mut run, first.symbol());
+            while let Some(cell) = cells.next_if(|c| same_style(c, first)) {
+                escape(&mut run, cell.symbol());
+            }
+            let _ = write!(out, "<span style=\"{}\">{run}</span>", css(first));
+        }
+        out.push('\n');
+    }
+    out.push_str("</pre>\n");
+    out
+}
+
+fn same_style(a: &Cell, b: &Cell) -> bool {
+    (a.fg, a.bg, a.modifier) == (b.fg, b.bg, b.modifier)
+}
+
+fn css(cell: &Cell) -> String {
+    let mut fg = rgb(cell.fg).unwrap_or(HTML_FG);
+    let mut bg = rgb(cell.bg).unwrap_or(HTML_BG);
+    if cell.modifier.contains(Modifier::REVERSED) {
+        std::mem::swap(&mut fg, &mut bg);
+    }
+    let mut css = format!("color: {}; background: {}", hex(fg), hex(bg));
+    if cell.modifier.contains(Modifier::BOLD) {
+        css.push_str("; font-weight: bold");
+    }
+    if cell.modifier.contains(Modifier::DIM) {
+        css.push_str("; opacity: 0.6");
+    }
+    if cell.modifier.contains(Modifier::ITALIC) {
+        css.push_str("; font-style: italic");
+    }
+    match (
+        cell.modifier.contains(Modifier::UNDERLINED),
+        cell.modifier.contains(Modifier::CROSSED_OUT),
+    ) {
+        (true, true) => css.push_str("; text-decoration: underline line-through"),
+        (true, false) => css.push_str("; text-decoration: underline"),
+        (false, true) => css.push_str("; text-decoration: line-through"),
+        (false, false) => {}
+    }
+    css
+}
+
+fn escape(out: &mut String, s: &str) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// The color as the xterm palette has it, or `None` for the terminal's own.
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    let named = |i: usize| Some(PALETTE[i]);
+    match color {
+        Color::Reset => None,
+        Color::Black => named(0),
+        Color::Red => named(1),
+        Color::Green => named(2),
+        Color::Yellow => named(3),
+        Color::Blue => named(4),
+        Color::Magenta => named(5),
+        Color::Cyan => named(6),
+        Color::Gray => named(7),
+        Color::DarkGray => named(8),
+        Color::LightRed => named(9),
+        Color::LightGreen => named(10),
+        Color::LightYellow => named(11),
+        Color::LightBlue => named(12),
+        Color::LightMagenta => named(13),
+        Color::LightCyan => named(14),
+        Color::White => named(15),
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(i @ 0..=15) => named(i as usize),
+        Color::Indexed(i @ 16..=231) => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let i = i - 16;
+            Some((level(i / 36), level(i / 6 % 6), level(i % 6)))
+        }
+        Color::Indexed(i) => {
+            let grey = 8 + (i - 232) * 10;
+            Some((grey, grey, grey))
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! The uncommitted changes of the work tree, file by file, as a screen of its
+//! own. Ignored files are left out unless asked for, as with `git status`.
+
+use crate::{git, prelude::*, t};
+use ratatui::text::Span;
+
+pub struct StatusView {
+    pub files: Vec<git::FileStatus>,
+    /// Whether ignored files are listed.
+    pub ignored: bool,
+    pub state: ListState,
+}
+
+impl StatusView {
+    pub fn new(files: Vec<git::FileStatus>, ignored: bool) -> Self {
+        let state = ListState::default().with_selected((!files.is_empty()).then_some(0));
+        Self {
+            files,
+            ignored,
+            state,
+        }
+    }
+
+    /// Lists `files` instead, keeping the selected file selected if it still
+    /// has changes.
+    pub fn replace(&mut self, files: Vec<git::FileStatus>, ignored: bool) {
+        let selected = self.selected().map(|f| f.path.clone());
+        let at = selected
+            .and_then(|path| files.iter().position(|f| f.path == path))
+            .or_else(|| (!files.is_empty()).then_some(0));
+        self.files = files;
+        self.ignored = ignored;
+        self.state.select(at);
+    }
+
+    pub fn selected(&self) -> Option<&git::FileStatus> {
+        self.state.selected().and_then(|i| self.files.get(i))
+    }
+
+    /// The arguments to `git diff` that show the changes to the selected file
+    /// since the last commit, none for a file git does not track.
+    pub fn diff_args(&self) -> Option<Vec<String>> {
+        let file = self.selected()?;
+        if file.is_untracked() || file.is_ignored() {
+            return None;
+        }
+        let args = ["diff", "HEAD", "--", &file.path];
+        Some(args.map(String::from).to_vec())
+    }
+}
+
+pub struct StatusList<'a>(pub &'a mut StatusView);
+
+impl Widget for StatusList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let view = self.0;
+        let dim = Style::new().dim();
+        let mut items: Vec<ListItem> = view
+            .files
+            .iter()
+            .map(|file| {
+                let style = match (file.index, file.worktree) {
+                    ('!', _) => dim,
+                    ('?', _) => Style::new().fg(RED.c300),
+                    ('U', _) => Style::new().fg(RED.c500).bold(),
+                    (_, ' ') => Style::new().fg(GREEN.c300),
+                    _ => Style::new().fg(AMBER.c200),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{}{} ", file.index, file.worktree), style),
+                    Span::styled(file.path.clone(), style),
+                ]))
+            })
+            .collect();
+        if items.is_empty() {
+            items.push(ListItem::new(Line::styled(t!("nothing to commit"), dim)));
+        }
+        let hints = match view.ignored {
+            true => t!("enter: diff  s: stage hunks  d: discard  i: hide ignored  esc: back"),
+            false => t!("enter: diff  s: stage hunks  d: discard  i: show ignored  esc: back"),
+        };
+        let block = Block::new()
+            .title(Line::raw(t!("Work tree status")).left_aligned())
+            .title(Line::raw(hints).right_aligned())
+            .borders(Borders::TOP);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        StatefulWidget::render(list, area, buf, &mut view.state);
+    }
+}
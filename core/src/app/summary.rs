@@ -0,0 +1,107 @@
+//! What a session did to the repository, printed once grit exits so that it
+//! stays in the scrollback.
+//!
+//! Branches are compared against how they were at startup rather than tracked
+//! action by action, so that user commands that create, delete or check out
+//! branches are accounted for as well.
+
+use crate::{git, redact, t};
+use git2::BranchType;
+use std::collections::BTreeSet;
+
+pub struct Summary {
+    /// The local branches when the session started.
+    branches: BTreeSet<String>,
+    head: Option<String>,
+    /// Branches renamed this session, from their name at startup.
+    renamed: Vec<(String, String)>,
+    /// Jobs that succeeded, by name, with how often.
+    jobs: Vec<(String, usize)>,
+}
+
+impl Summary {
+    pub fn start(repo: &git::Repository) -> Self {
+        Self {
+            branches: local_branches(repo),
+            head: repo.head_branch(),
+            renamed: vec![],
+            jobs: vec![],
+        }
+    }
+
+    pub fn renamed(&mut self, old: &str, new: &str) {
+        match self.renamed.iter_mut().find(|(_, to)| to == old) {
+            Some((_, to)) => *to = new.to_string(),
+            None => self.renamed.push((old.to_string(), new.to_string())),
+        }
+    }
+
+    pub fn ran(&mut self, job: &str) {
+        match self.jobs.iter_mut().find(|(name, _)| name == job) {
+            Some((_, count)) => *count += 1,
+            None => self.jobs.push((job.to_string(), 1)),
+        }
+    }
+
+    /// One line per kind of change, or none if nothing happened.
+    pub fn lines(&self, repo: &git::Repository) -> Vec<String> {
+        let now = local_branches(repo);
+        let renamed: Vec<_> = self
+            .renamed
+            .iter()
+            .filter(|(old, new)| self.branches.contains(old) && now.contains(new))
+            .collect();
+        let created = now
+            .difference(&self.branches)
+            .filter(|name| renamed.iter().all(|(_, new)| new != *name))
+            .map(|name| redact::branch(name))
+            .collect::<Vec<_>>();
+        let deleted = self
+            .branches
+            .difference(&now)
+            .filter(|name| renamed.iter().all(|(old, _)| old != *name))
+            .map(|name| redact::branch(name))
+            .collect::<Vec<_>>();
+        let mut lines = vec![];
+        if !created.is_empty() {
+            lines.push(t!("created {names}", names = created.join(", ")));
+        }
+        if !deleted.is_empty() {
+            lines.push(t!("deleted {names}", names = deleted.join(", ")));
+        }
+        for (old, new) in renamed {
+            lines.push(t!(
+                "renamed {old} to {new}",
+                old = redact::branch(old),
+                new = redact::branch(new)
+            ));
+        }
+        let head = repo.head_branch();
+        if let (Some(head), true) = (&head, head != self.head) {
+            lines.push(match &self.head {
+                Some(was) => t!(
+                    "checked out {name} (was {was})",
+                    name = redact::branch(head),
+                    was = redact::branch(was)
+                ),
+                None => t!("checked out {name}", name = redact::branch(head)),
+            });
+        }
+        for (job, count) in &self.jobs {
+            let job = redact::text(job);
+            lines.push(match count {
+                1 => t!("ran {job}", job = job),
+                n => t!("ran {job} ({count} times)", job = job, count = n),
+            });
+        }
+        lines.into_iter().map(|l| format!("grit: {l}")).collect()
+    }
+}
+
+fn local_branches(repo: &git::Repository) -> BTreeSet<String> {
+    repo.branches(Some(BranchType::Local))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|b| b.name)
+        .collect()
+}
@@ -0,0 +1,101 @@
+//! The tags of the repository, as a tab of their own next to the branches:
+//! each with the date and summary of the commit it is on, and below the list
+//! the message of the selected tag and its commit.
+
+use super::listing::Listing;
+use crate::{git, prelude::*, redact, t};
+use ratatui::{text::Span, widgets::Wrap};
+
+pub type TagsView = Listing<git::Tag>;
+
+pub struct TagList<'a>(pub &'a mut TagsView);
+
+impl Widget for TagList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let view = self.0;
+        let [list, preview] = Layout::vertical([Constraint::Fill(1); 2]).areas(area);
+        let dim = Style::new().dim();
+        let width = view
+            .items
+            .iter()
+            .map(|tag| redact::branch(&tag.name).chars().count())
+            .max()
+            .unwrap_or_default()
+            .min(30);
+        let mut items: Vec<ListItem> = view
+            .items
+            .iter()
+            .map(|tag| {
+                let mut spans = vec![Span::raw(format!("{:width$}  ", redact::branch(&tag.name)))];
+                if let Some(c) = &tag.commit {
+                    spans.push(Span::styled(format!("{}  ", c.timestamp), dim));
+                    spans.push(Span::raw(redact::text(&c.summary).into_owned()));
+                }
+                if tag.message.is_some() {
+                    spans.push(Span::styled(format!("  ({})", t!("annotated")), AMBER.c200));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let count = t!("{count} tags", count = view.items.len());
+        let block = Block::new()
+            .title(Line::raw(count).left_aligned())
+            .title(Line::raw(t!("enter: show  b: branch from tag  esc: back")).right_aligned())
+            .borders(Borders::TOP);
+        if items.is_empty() {
+            items.push(ListItem::new(Line::styled(t!("no tags"), dim)));
+        }
+        let widget = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        StatefulWidget::render(widget, list, buf, &mut view.state);
+        let Some(selected) = view.selected() else {
+            return;
+        };
+        let block = Block::new()
+            .title(Line::raw(redact::branch(&selected.name).into_owned()).left_aligned())
+            .borders(Borders::TOP);
+        let mut lines = vec![];
+        if let Some(message) = &selected.message {
+            let tagger = selected.tagger.as_ref().and_then(|a| a.name.as_deref());
+            if let Some(tagger) = tagger {
+                let line = t!("tagged by {name}", name = redact::person(tagger));
+                lines.push(Line::styled(line, dim));
+            }
+            lines.extend(
+                redact::text(message)
+                    .lines()
+                    .map(|line| Line::raw(line.to_string())),
+            );
+            lines.push(Line::raw(""));
+        }
+        match &selected.commit {
+            Some(c) => {
+                let author = c.author.name.as_deref().unwrap_or(t!("<none>"));
+                lines.push(Line::styled(c.id.to_string(), dim));
+                lines.push(Line::raw(format!(
+                    "{}: {}",
+                    c.timestamp,
+                    redact::person(author)
+                )));
+                lines.push(Line::raw(""));
+                lines.extend(
+                    redact::text(&c.message)
+                        .lines()
+                        .map(|line| Line::raw(line.to_string())),
+                );
+            }
+            None => lines.push(Line::styled(
+                t!(
+                    "{name} does not point at a commit",
+                    name = redact::branch(&selected.name)
+                ),
+                dim,
+            )),
+        }
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(preview, buf);
+    }
+}
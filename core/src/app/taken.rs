@@ -0,0 +1,68 @@
+//! What to do when a branch is to be created or renamed to a name that a
+//! local branch, or a branch of a remote, has already: replace the local
+//! one, track the remote one, pick another name, or go to the branch that
+//! has it.
+
+use crate::t;
+use git2::BranchType;
+
+#[derive(Clone, Debug)]
+pub enum Choice {
+    /// Replaces the local branch of the name.
+    Overwrite,
+    /// Goes ahead, making the branch track this remote branch.
+    Track(String),
+    /// Goes ahead, leaving the remote branches of the name alone.
+    Anyway,
+    /// Asks for another name, starting from this free one.
+    Rename(String),
+    /// Selects this branch, which has the name, instead.
+    Jump(String),
+}
+
+impl Choice {
+    pub fn label(&self, name: &str) -> String {
+        match self {
+            Choice::Overwrite => t!("replace the local {name}", name = name),
+            Choice::Track(upstream) => t!("go ahead, tracking {upstream}", upstream = upstream),
+            Choice::Anyway => t!("go ahead, without tracking").to_string(),
+            Choice::Rename(free) => t!("pick another name, such as {free}", free = free),
+            Choice::Jump(branch) => t!("go to {branch}", branch = branch),
+        }
+    }
+}
+
+/// The ways out for a name that the branches `holders` have, local or of
+/// remotes, or `None` if none has it. The local branch is only offered to
+/// replace if `replaceable`, and `free` is a name to pick instead.
+pub fn choices(
+    holders: &[(String, BranchType)],
+    replaceable: bool,
+    free: String,
+) -> Option<Vec<Choice>> {
+    if holders.is_empty() {
+        return None;
+    }
+    let local = holders.iter().any(|(_, typ)| *typ == BranchType::Local);
+    let mut choices = vec![];
+    match local {
+        true if replaceable => choices.push(Choice::Overwrite),
+        true => {}
+        false => {
+            let remote = holders.iter().map(|(name, _)| Choice::Track(name.clone()));
+            choices.extend(remote);
+            choices.push(Choice::Anyway);
+        }
+    }
+    choices.push(Choice::Rename(free));
+    choices.extend(holders.iter().map(|(name, _)| Choice::Jump(name.clone())));
+    Some(choices)
+}
+
+/// The first of `name-2`, `name-3` and so on that `taken` says is free.
+pub fn suggest(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    (2..)
+        .map(|n| format!("{name}-{n}"))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_default()
+}
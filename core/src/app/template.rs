@@ -0,0 +1,75 @@
+//! Branch name templates such as `{user}/{type}/{slug}`.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Field(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(s: &str) -> Self {
+        let mut parts = vec![];
+        let mut rest = s;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            parts.push(Part::Field(rest[start + 1..start + len].trim().to_string()));
+            rest = &rest[start + len + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        Self { parts }
+    }
+
+    /// The distinct placeholder names in the order they appear.
+    pub fn fields(&self) -> Vec<String> {
+        let mut fields: Vec<String> = vec![];
+        for part in &self.parts {
+            if let Part::Field(name) = part {
+                if !fields.contains(name) {
+                    fields.push(name.clone());
+                }
+            }
+        }
+        fields
+    }
+
+    /// Fills in the placeholders. The `slug` placeholder is slugified so that free
+    /// text such as a ticket title makes for a sensible ref name; the result should
+    /// still be run through [`refname::normalize`](crate::git::refname::normalize).
+    pub fn render(&self, values: &[(String, String)]) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Field(name) => values
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(n, v)| if n == "slug" { slugify(v) } else { v.clone() })
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+pub fn slugify(s: &str) -> String {
+    let mut slug = String::with_capacity(s.len());
+    for c in s.trim().chars() {
+        if c.is_alphanumeric() || matches!(c, '.' | '_' | '/') {
+            slug.extend(c.to_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
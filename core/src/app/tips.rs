@@ -0,0 +1,136 @@
+//! The tip commits of branches, kept across runs so that the list shows up
+//! without walking the history of every branch first. Entries are keyed by
+//! commit id, so a branch that moved simply misses and is read again. The
+//! commits are kept whole, trailers and note too, so the whole cache is for one
+//! tip of the notes ref.
+
+use crate::{git, paths};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Entries not seen for this long are dropped from the cache file.
+const MAX_AGE: i64 = 30 * 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Default)]
+struct File {
+    /// The commit the notes ref was at when the tips were read.
+    notes: Option<String>,
+    /// Entries by commit id.
+    entries: HashMap<String, Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    short: String,
+    summary: String,
+    message: String,
+    author: Option<String>,
+    email: Option<String>,
+    /// The commit time, in unix seconds.
+    time: i64,
+    note: Option<String>,
+    /// When a branch last pointed at the commit, in unix seconds.
+    seen: i64,
+}
+
+pub struct Tips {
+    file: File,
+    path: Option<PathBuf>,
+}
+
+impl Tips {
+    /// An empty cache, written to the cache file of the repository at
+    /// `git_dir` once it is saved.
+    pub fn new(git_dir: &Path) -> Self {
+        let path = paths::repo_cache("tips", git_dir);
+        Self {
+            file: File::default(),
+            path,
+        }
+    }
+
+    pub fn load(git_dir: &Path) -> Self {
+        let path = paths::repo_cache("tips", git_dir);
+        let now = chrono::Utc::now().timestamp();
+        let mut file = path
+            .as_ref()
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|json| serde_json::from_slice::<File>(&json).ok())
+            .unwrap_or_default();
+        file.entries.retain(|_, entry| now - entry.seen < MAX_AGE);
+        Self { file, path }
+    }
+
+    /// The cached commits, for [`git::Repository::stream_branch_tips`]. None
+    /// while the notes ref is at `notes` rather than where it was when the
+    /// tips were read.
+    pub fn commits(&self, notes: Option<git::Oid>) -> HashMap<git::Oid, git::Commit> {
+        if self.file.notes != notes.map(|id| id.to_string()) {
+            return HashMap::new();
+        }
+        self.file
+            .entries
+            .iter()
+            .filter_map(|(id, entry)| {
+                let id = git::Oid::from_str(id).ok()?;
+                let author = git::Author {
+                    name: entry.author.clone(),
+                    email: entry.email.clone(),
+                };
+                let commit = git::Commit::cached(
+                    id,
+                    entry.short.clone(),
+                    entry.summary.clone(),
+                    entry.message.clone(),
+                    author,
+                    entry.time,
+                    entry.note.clone(),
+                );
+                Some((id, commit.ok()?))
+            })
+            .collect()
+    }
+
+    /// Remembers the tips of `branches`, read with the notes ref at `notes`,
+    /// and writes the cache file. Failing to is not worth bothering anyone
+    /// about.
+    pub fn save<'a>(
+        &mut self,
+        branches: impl IntoIterator<Item = &'a git::Branch>,
+        notes: Option<git::Oid>,
+    ) {
+        let now = chrono::Utc::now().timestamp();
+        let notes = notes.map(|id| id.to_string());
+        if self.file.notes != notes {
+            // every tip may have another note now
+            self.file.entries.clear();
+            self.file.notes = notes;
+        }
+        for tip in branches.into_iter().filter_map(|b| b.commits().first()) {
+            let entry = Entry {
+                short: tip.short.clone(),
+                summary: tip.summary.clone(),
+                message: tip.message.clone(),
+                author: tip.author.name.clone(),
+                email: tip.author.email.clone(),
+                time: tip.timestamp.epoch(),
+                note: tip.note.clone(),
+                seen: now,
+            };
+            self.file.entries.insert(tip.id.to_string(), entry);
+        }
+        let Some(path) = &self.path else {
+            return;
+        };
+        let Ok(json) = serde_json::to_vec(&self.file) else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = std::fs::write(path, json);
+    }
+}
@@ -0,0 +1,44 @@
+//! The window title and working directory that the terminal shows for grit, so
+//! that tabs tell which repository and branch it is on. The title grit found is
+//! saved on the terminal's title stack by [`push`] and put back by [`pop`].
+
+use crate::prelude::*;
+use std::{io::Write, path::Path};
+
+/// Saves the current title, in terminals that keep a stack of them.
+pub fn push() -> io::Result<()> {
+    write_seq("\x1b[22;0t")
+}
+
+pub fn pop() -> io::Result<()> {
+    write_seq("\x1b[23;0t")
+}
+
+pub fn set(title: &str) -> io::Result<()> {
+    // control characters would end the sequence early
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    write_seq(&format!("\x1b]0;{title}\x07"))
+}
+
+/// Tells the terminal the working directory with OSC 7, for new tabs to open in.
+pub fn cwd(dir: &Path) -> io::Result<()> {
+    let Ok(mut url) = url::Url::from_file_path(dir) else {
+        return Ok(());
+    };
+    let _ = url.set_host(hostname().as_deref());
+    write_seq(&format!("\x1b]7;{url}\x07"))
+}
+
+fn hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+fn write_seq(seq: &str) -> io::Result<()> {
+    let mut out = stdout();
+    out.write_all(seq.as_bytes())?;
+    out.flush()
+}
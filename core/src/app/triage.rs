@@ -0,0 +1,248 @@
+//! What grit shows when it cannot open the repository it was pointed at: why,
+//! in plain words, and a way out, by running `git init` there, opening one
+//! of the repositories opened lately or picking another directory, rather than
+//! an error on a half restored terminal. Started outside any repository, this
+//! is where grit starts.
+
+use super::{
+    columns,
+    dirs::{Picked, Picker},
+    recent,
+};
+use crate::{git, opts::Opts, prelude::*, t, theme};
+use color_eyre::eyre::{eyre, WrapErr};
+use ratatui::{backend::Backend, layout::Flex, text::Span, widgets::Wrap};
+use std::path::{Path, PathBuf};
+
+const KEY_STYLE: Style = Style::new().fg(BLUE.c300).add_modifier(Modifier::BOLD);
+
+/// Why the repository could not be opened.
+enum Problem {
+    /// There is no repository at or above the directory.
+    NotARepo(PathBuf),
+    /// The repository is there but may not be read.
+    Denied(PathBuf),
+    /// The repository is there but could not be read, e.g. as it is corrupt.
+    Unreadable(PathBuf),
+}
+
+impl Problem {
+    fn of(err: &color_eyre::Report, opts: &Opts) -> Self {
+        let dir = opts
+            .git_dir
+            .clone()
+            .or_else(|| opts.dir.clone())
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+        let denied = |err: &(dyn std::error::Error + 'static)| {
+            err.downcast_ref::<io::Error>()
+                .is_some_and(|err| err.kind() == io::ErrorKind::PermissionDenied)
+                || err
+                    .downcast_ref::<git2::Error>()
+                    .is_some_and(|err| err.message().contains("ermission denied"))
+        };
+        match err.downcast_ref::<git::Error>() {
+            Some(git::Error::NotARepo(path)) => Problem::NotARepo(path.clone()),
+            _ if err.chain().any(denied) => Problem::Denied(dir),
+            _ => Problem::Unreadable(dir),
+        }
+    }
+
+    fn dir(&self) -> &Path {
+        match self {
+            Problem::NotARepo(dir) | Problem::Denied(dir) | Problem::Unreadable(dir) => dir,
+        }
+    }
+
+    fn title(&self) -> String {
+        let dir = self.dir().display().to_string();
+        match self {
+            Problem::NotARepo(_) => t!("{dir} is not in a git repository", dir = dir),
+            Problem::Denied(_) => t!("grit may not read the repository at {dir}", dir = dir),
+            Problem::Unreadable(_) => t!("the repository at {dir} could not be read", dir = dir),
+        }
+    }
+
+    fn advice(&self) -> &'static str {
+        match self {
+            Problem::NotARepo(_) => t!(
+                "Neither it nor any directory above it has a .git directory. Start a repository there, or pick one elsewhere."
+            ),
+            Problem::Denied(_) => t!(
+                "Check who owns it and the permissions of its .git directory, or pick another repository."
+            ),
+            Problem::Unreadable(_) => t!(
+                "It may be damaged. `git fsck` in it tells more. Pick another repository meanwhile."
+            ),
+        }
+    }
+}
+
+/// How many of the repositories opened lately are offered.
+const RECENT: usize = 10;
+
+/// Explains `err`, which opening the repository `opts` points at failed
+/// with, until the user picks a directory to try instead, which is returned,
+/// or gives up.
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    opts: &Opts,
+    err: &color_eyre::Report,
+) -> EResult<Option<PathBuf>> {
+    tracing::warn!("{err:#}");
+    let problem = Problem::of(err, opts);
+    let mut recent = recent::load();
+    recent.truncate(RECENT);
+    let mut selected = 0;
+    let mut picker: Option<Picker> = None;
+    let mut notice: Option<String> = None;
+    loop {
+        terminal.draw(|frame| {
+            match &mut picker {
+                Some(picker) => frame.render_widget(picker, frame.size()),
+                None => frame.render_widget(
+                    Explanation {
+                        problem: &problem,
+                        err,
+                        notice: notice.as_deref(),
+                        recent: &recent,
+                        selected,
+                    },
+                    frame.size(),
+                ),
+            }
+            theme::finish(frame.buffer_mut());
+        })?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if let Some(open) = &mut picker {
+            match open.handle(key.code) {
+                Picked::Dir(dir) => return Ok(Some(dir)),
+                Picked::Cancel => picker = None,
+                Picked::Nothing => {}
+            }
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+            KeyCode::Char('j') | KeyCode::Down => {
+                selected = (selected + 1).min(recent.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Enter if !recent.is_empty() => {
+                return Ok(Some(recent[selected].path.clone()));
+            }
+            KeyCode::Char('o') | KeyCode::Enter => {
+                let paths = recent.iter().map(|repo| repo.path.clone()).collect();
+                picker = Some(Picker::new(problem.dir(), paths));
+            }
+            KeyCode::Char('i') if matches!(problem, Problem::NotARepo(_)) => {
+                match git_init(problem.dir()) {
+                    Ok(()) => return Ok(Some(problem.dir().to_path_buf())),
+                    Err(err) => notice = Some(format!("{err:#}")),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Runs `git init` in `dir`.
+fn git_init(dir: &Path) -> EResult<()> {
+    let out = std::process::Command::new("git")
+        .arg("init")
+        .current_dir(dir)
+        .output()
+        .wrap_err("run git init")?;
+    if !out.status.success() {
+        let err = String::from_utf8_lossy(&out.stderr);
+        return Err(eyre!("git init failed: {}", err.trim()));
+    }
+    Ok(())
+}
+
+struct Explanation<'a> {
+    problem: &'a Problem,
+    err: &'a color_eyre::Report,
+    notice: Option<&'a str>,
+    recent: &'a [recent::Repo],
+    selected: usize,
+}
+
+impl Widget for Explanation<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let dim = Style::new().fg(SLATE.c400);
+        let key = |key: &'static str, what: &'static str| {
+            Line::from(vec![
+                Span::styled(format!("{key:>5}"), KEY_STYLE),
+                Span::raw("  "),
+                Span::raw(what),
+            ])
+        };
+        let mut lines = vec![
+            Line::styled(self.problem.title(), Style::new().bold()),
+            Line::default(),
+            Line::raw(self.problem.advice()),
+            Line::default(),
+            Line::styled(format!("{:#}", self.err), dim),
+            Line::default(),
+        ];
+        if !self.recent.is_empty() {
+            lines.push(Line::styled(t!("Opened lately"), Style::new().bold()));
+            let now = chrono::Utc::now().timestamp();
+            for (idx, repo) in self.recent.iter().enumerate() {
+                let mut line = Line::from(vec![
+                    Span::raw(match idx == self.selected {
+                        true => "> ",
+                        false => "  ",
+                    }),
+                    Span::raw(repo.path.display().to_string()),
+                ]);
+                if let Some(branch) = &repo.branch {
+                    line.spans.push(Span::styled(
+                        format!("  {branch}"),
+                        theme::get().local_branch,
+                    ));
+                }
+                let age = columns::age(now - repo.opened);
+                line.spans.push(Span::styled(format!("  {age}"), dim));
+                if idx == self.selected {
+                    line = line.patch_style(theme::get().selected);
+                }
+                lines.push(line);
+            }
+            lines.push(Line::default());
+            lines.push(key("enter", t!("open the selected one")));
+        }
+        if matches!(self.problem, Problem::NotARepo(_)) {
+            lines.push(key("i", t!("run git init there")));
+        }
+        lines.push(key("o", t!("open another directory")));
+        lines.push(key("q", t!("quit")));
+        if let Some(notice) = self.notice {
+            lines.push(Line::default());
+            lines.push(Line::styled(notice.to_string(), Style::new().fg(RED.c300)));
+        }
+        let [area] = Layout::horizontal([Constraint::Max(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        // as many rows as the lines take wrapped, inside the border and padding
+        let width = area.width.saturating_sub(4).max(1) as usize;
+        let rows: usize = lines.iter().map(|l| l.width().max(1).div_ceil(width)).sum();
+        let [area] = Layout::vertical([Constraint::Max(rows as u16 + 4)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = Block::new()
+            .title(Line::raw(t!("Cannot open the repository")).left_aligned())
+            .borders(Borders::ALL)
+            .padding(ratatui::widgets::Padding::uniform(1));
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,176 @@
+//! Answers what ssh asks while grit fetches or pushes: the passphrase of an
+//! encrypted key, or whether to trust a host it has not seen before. git runs
+//! there without a terminal to ask on, so ssh is pointed at the grit executable
+//! as its `SSH_ASKPASS` program. That grit hands the prompt to the one running
+//! the TUI over a local connection and prints the answer it gets back, which
+//! for a host key is `yes`, and ssh then adds the key to `known_hosts`.
+
+use crate::{git, prelude::*};
+use color_eyre::eyre::{bail, WrapErr};
+use std::{
+    io::{Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    process::ExitCode,
+    sync::mpsc,
+};
+
+/// Set for the askpass program to where the TUI listens and the token it
+/// expects, so that nothing else on the machine gets to ask.
+pub const ENV: &str = "GRIT_ASKPASS";
+
+/// What ssh asks.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Prompt {
+    /// Whether to trust a host whose key is not in `known_hosts` yet.
+    HostKey { host: String, fingerprint: String },
+    /// A passphrase or password, typed masked.
+    Secret(String),
+    /// Anything else, e.g. a user name.
+    Text(String),
+}
+
+impl Prompt {
+    fn parse(prompt: &str) -> Self {
+        if prompt.contains("continue connecting") {
+            let host = prompt
+                .split_once("host '")
+                .and_then(|(_, rest)| rest.split_once('\''))
+                .map_or("", |(host, _)| host);
+            let fingerprint = prompt
+                .lines()
+                .find_map(|line| line.split_once(" key fingerprint is "))
+                .map(|(kind, fp)| format!("{kind} {}", fp.trim_end_matches('.')))
+                .unwrap_or_default();
+            return Prompt::HostKey {
+                host: host.to_string(),
+                fingerprint,
+            };
+        }
+        let prompt = prompt.trim().to_string();
+        let lower = prompt.to_lowercase();
+        match ["passphrase", "password", "pin for"]
+            .iter()
+            .any(|s| lower.contains(s))
+        {
+            true => Prompt::Secret(prompt),
+            false => Prompt::Text(prompt),
+        }
+    }
+}
+
+/// A question from ssh waiting for its answer. Dropped unanswered, ssh is told
+/// no.
+pub struct Request {
+    pub prompt: Prompt,
+    stream: TcpStream,
+}
+
+impl Request {
+    pub fn answer(mut self, answer: &str) {
+        // if this fails ssh is gone, and with it the need for an answer
+        let _ = write!(self.stream, "+{answer}");
+    }
+}
+
+/// Where the prompts of ssh arrive in the TUI.
+pub struct Askpass {
+    rx: mpsc::Receiver<Request>,
+}
+
+impl Askpass {
+    /// Listens for prompts, and has the git commands that talk to remotes ask
+    /// them here.
+    pub fn start() -> EResult<Self> {
+        let exe = std::env::current_exe().wrap_err("find the grit executable")?;
+        let listener =
+            TcpListener::bind(("127.0.0.1", 0)).wrap_err("listen for the prompts of ssh")?;
+        let addr = listener
+            .local_addr()
+            .wrap_err("listen for the prompts of ssh")?;
+        let token = token();
+        git::command::set_remote_env(vec![
+            ("SSH_ASKPASS".into(), exe.into()),
+            // ask here even though ssh has a terminal, the one the TUI draws on
+            ("SSH_ASKPASS_REQUIRE".into(), "force".into()),
+            (ENV.into(), format!("{addr} {token}").into()),
+        ]);
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let Some(request) = receive(stream, &token) else {
+                    continue;
+                };
+                if tx.send(request).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self { rx })
+    }
+
+    /// The next prompt, if one came in.
+    pub fn next(&self) -> Option<Request> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// Reads a prompt, the token on the first line and the prompt after it.
+fn receive(mut stream: TcpStream, token: &str) -> Option<Request> {
+    stream.set_read_timeout(Some(Duration::from_secs(1))).ok()?;
+    let mut text = String::new();
+    stream.read_to_string(&mut text).ok()?;
+    let (sent, prompt) = text.split_once('\n')?;
+    if sent != token {
+        return None;
+    }
+    stream.set_read_timeout(None).ok()?;
+    Some(Request {
+        prompt: Prompt::parse(prompt),
+        stream,
+    })
+}
+
+/// Something hard to guess, from the random keys std seeds its hash maps with.
+fn token() -> String {
+    use std::hash::{BuildHasher, Hasher};
+    (0..2)
+        .map(|_| {
+            let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+            hasher.write_u32(std::process::id());
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+/// Runs grit as the askpass program of ssh: passes `prompt` on to the TUI
+/// named by `spec`, the value of [`ENV`], and prints the answer. Fails quietly
+/// when the question was dismissed, which ssh takes as no and reports itself.
+pub fn main(spec: &str, prompt: &str) -> ExitCode {
+    match ask(spec, prompt) {
+        Ok(Some(answer)) => {
+            println!("{answer}");
+            ExitCode::SUCCESS
+        }
+        Ok(None) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("grit: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn ask(spec: &str, prompt: &str) -> EResult<Option<String>> {
+    let Some((addr, token)) = spec.split_once(' ') else {
+        bail!("{ENV} is not an address and a token");
+    };
+    let mut stream = TcpStream::connect(addr).wrap_err_with(|| format!("connect to {addr}"))?;
+    write!(stream, "{token}\n{prompt}").wrap_err("send the prompt")?;
+    stream
+        .shutdown(Shutdown::Write)
+        .wrap_err("send the prompt")?;
+    let mut reply = String::new();
+    stream
+        .read_to_string(&mut reply)
+        .wrap_err("read the answer")?;
+    Ok(reply.strip_prefix('+').map(ToOwned::to_owned))
+}
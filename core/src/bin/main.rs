@@ -1,16 +1,209 @@
 use clap::Parser;
+use color_eyre::eyre::WrapErr;
+use grit::cli::Exit;
+use std::{io::IsTerminal, process::ExitCode};
 
-fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+fn main() -> ExitCode {
+    // run by ssh for a passphrase or about a host key while grit fetches
+    if let Ok(spec) = std::env::var(grit::askpass::ENV) {
+        let prompt = std::env::args().nth(1).unwrap_or_default();
+        return grit::askpass::main(&spec, &prompt);
+    }
+    let opts = match grit::opts::Opts::try_parse() {
+        Ok(opts) => opts,
+        Err(err) => {
+            let _ = err.print();
+            // --help and --version are reported as errors too
+            let exit = if err.use_stderr() {
+                Exit::Usage
+            } else {
+                Exit::Success
+            };
+            return exit.into();
+        }
+    };
+    match run(&opts) {
+        Ok(exit) => exit.into(),
+        // e.g. `grit | head`, where the reader has all it wants
+        Err(err)
+            if err
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|err| err.kind() == std::io::ErrorKind::BrokenPipe) =>
+        {
+            Exit::Success.into()
+        }
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            exit_of(&err).into()
+        }
+    }
+}
+
+/// Marks an error in the config, for [`exit_of`] to tell apart.
+#[derive(Debug)]
+struct ConfigError;
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid config")
+    }
+}
+
+/// The exit status for a run that failed with `err`: git's unless it was the
+/// config, or reading or writing something other than the repository.
+fn exit_of(err: &color_eyre::Report) -> Exit {
+    if err.downcast_ref::<ConfigError>().is_some() {
+        return Exit::Config;
+    }
+    let git = err.chain().any(|cause| cause.is::<grit::git::Error>());
+    let io = err.chain().any(|cause| cause.is::<std::io::Error>());
+    match io && !git {
+        true => Exit::Io,
+        false => Exit::Git,
+    }
+}
+
+/// The config `opts` point at, failing as [`ConfigError`].
+fn resolve(opts: &grit::opts::Opts) -> Result<grit::config::Config, color_eyre::Report> {
+    grit::config::Config::resolve(opts).wrap_err(ConfigError)
+}
+
+fn run(opts: &grit::opts::Opts) -> Result<Exit, color_eyre::Report> {
+    grit::bootstrap::init_logging(opts)?;
+    if let Some(command) = &opts.command {
+        let config = init_locale(opts)?;
+        let mut out = std::io::stdout().lock();
+        return match command {
+            grit::opts::Command::List => {
+                grit::cli::list(opts, &config, &mut out)?;
+                Ok(Exit::Success)
+            }
+            grit::opts::Command::Stale(args) => {
+                grit::cli::stale(opts, &config, args, &mut out)?;
+                Ok(Exit::Success)
+            }
+            grit::opts::Command::Delete(args) => {
+                grit::cli::delete(opts, &config, args, &mut out)?;
+                Ok(Exit::Success)
+            }
+            grit::opts::Command::Report(args) => {
+                grit::cli::report(opts, &config, args, &mut out)?;
+                Ok(Exit::Success)
+            }
+            grit::opts::Command::Prune(args) => {
+                grit::cli::prune(opts, args, &mut out)?;
+                Ok(Exit::Success)
+            }
+            grit::opts::Command::Checkout(args) => grit::cli::checkout(opts, args, &mut out),
+            grit::opts::Command::Bench(args) => {
+                grit::cli::bench(opts, &config, args, &mut out)?;
+                Ok(Exit::Success)
+            }
+        };
+    }
+    if !std::io::stdout().is_terminal() {
+        headless(opts)?;
+        return Ok(Exit::Success);
+    }
     grit::bootstrap::install_hooks()?;
-    let _opts = grit::opts::Opts::parse();
-    tui()?;
-    Ok(())
+    if opts.demo {
+        grit::redact::enable();
+    }
+    tui(opts)
 }
 
-fn tui() -> Result<(), color_eyre::Report> {
-    let opts = grit::opts::Opts::parse();
-    let mut terminal = grit::bootstrap::init(&opts)?;
-    grit::app::App::new(&opts)?.run(&mut terminal)?;
+fn init_locale(opts: &grit::opts::Opts) -> Result<grit::config::Config, color_eyre::Report> {
+    let config = resolve(opts)?;
+    grit::i18n::init(grit::i18n::Locale::detect(config.locale.as_deref()));
+    Ok(config)
+}
+
+fn headless(opts: &grit::opts::Opts) -> Result<(), color_eyre::Report> {
+    let config = init_locale(opts)?;
+    grit::cli::list(opts, &config, &mut std::io::stdout().lock())
+}
+
+fn tui(opts: &grit::opts::Opts) -> Result<Exit, color_eyre::Report> {
+    let config = init_locale(opts)?;
+    let theme = grit::theme::Theme::resolve(config.theme.as_deref(), &config.colors)
+        .wrap_err(ConfigError)?;
+    grit::theme::init(theme);
+    let mut terminal = grit::bootstrap::init(opts)?;
+    let res = session(&mut terminal, opts, config);
     grit::bootstrap::restore()?;
-    Ok(())
+    let (summary, exit_to, exit) = res?;
+    if !opts.quiet {
+        for line in summary {
+            println!("{line}");
+        }
+    }
+    match (exit_to, &opts.exit_file) {
+        // emptied when there is none, so that an old one is not gone to
+        (path, Some(file)) => {
+            let path = path.map(|p| format!("{}\n", p.display()));
+            std::fs::write(file, path.unwrap_or_default())
+                .wrap_err_with(|| format!("write {}", file.display()))?;
+        }
+        // last and on its own
+        (Some(path), None) => println!("{}", path.display()),
+        (None, None) => {}
+    }
+    Ok(exit)
+}
+
+/// Runs the app on the repository `opts` points at, and then on each one
+/// picked to open from it, remembering each as opened lately. Returns what
+/// they changed, the worktree to print on exit, and whether the user gave up
+/// on opening one.
+fn session(
+    terminal: &mut grit::bootstrap::Tui,
+    opts: &grit::opts::Opts,
+    mut config: grit::config::Config,
+) -> Result<(Vec<String>, Option<std::path::PathBuf>, Exit), color_eyre::Report> {
+    let mut opts = opts.clone();
+    let mut summary = Vec::new();
+    loop {
+        let Some(app) = open(terminal, &mut opts, config)? else {
+            return Ok((summary, None, Exit::Aborted));
+        };
+        let opened = chrono::Utc::now().timestamp();
+        let recent = grit::app::recent::load();
+        let mut app = app.with_recent(recent.into_iter().map(|repo| repo.path).collect());
+        app.run(terminal)?;
+        summary.extend(app.summary());
+        grit::app::recent::record(&app.dir(), app.selected_branch(), opened);
+        let Some(next) = app.switch_to().map(ToOwned::to_owned) else {
+            let exit_to = app.exit_to().map(ToOwned::to_owned);
+            return Ok((summary, exit_to, Exit::Success));
+        };
+        retarget(&mut opts, next);
+        config = resolve(&opts)?;
+    }
+}
+
+/// Points `opts` at the repository in `dir`, at the branch last selected
+/// there.
+fn retarget(opts: &mut grit::opts::Opts, dir: std::path::PathBuf) {
+    let branch = grit::app::recent::branch(&dir);
+    opts.retarget(dir, branch);
+}
+
+/// Opens the repository `opts` points at. While that fails, says why and
+/// offers to fix it or to pick another, until the user gives up.
+fn open(
+    terminal: &mut grit::bootstrap::Tui,
+    opts: &mut grit::opts::Opts,
+    mut config: grit::config::Config,
+) -> Result<Option<grit::app::App>, color_eyre::Report> {
+    loop {
+        let err = match grit::app::App::open_repo(opts, &config) {
+            Ok(repo) => return grit::app::App::with_repo(opts, config, repo).map(Some),
+            Err(err) => err,
+        };
+        let Some(dir) = grit::app::triage::run(terminal, opts, &err)? else {
+            return Ok(None);
+        };
+        retarget(opts, dir);
+        config = resolve(opts)?;
+    }
 }
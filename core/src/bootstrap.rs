@@ -1,43 +1,204 @@
 use super::prelude::*;
-use crate::opts;
+use crate::{app::title, logs, opts, paths, platform};
 
-use color_eyre::config::HookBuilder;
-use color_eyre::eyre;
+use color_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, WrapErr},
+};
+use ratatui::backend::Backend;
+use ratatui::crossterm::cursor::Show;
 use ratatui::crossterm::event::DisableMouseCapture;
 use ratatui::crossterm::event::EnableMouseCapture;
+use ratatui::crossterm::event::{DisableBracketedPaste, EnableBracketedPaste};
+use ratatui::crossterm::event::{
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use ratatui::crossterm::terminal::supports_keyboard_enhancement;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    OnceLock,
+};
 
-pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+/// The terminal the app draws on, which tests swap for a `TestBackend`.
+pub type Tui<B = CrosstermBackend<Stdout>> = Terminal<B>;
+
+/// Whether grit has the terminal in raw mode on the alternate screen, so that it
+/// is restored exactly once however grit exits.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether an interactive command has the terminal, see [`suspend`].
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the terminal speaks the kitty keyboard protocol, asked once.
+static ENHANCED_KEYS: OnceLock<bool> = OnceLock::new();
+
+/// Writes what grit traces to `--log-file` or [`paths::log_file`], at the
+/// levels `$GRIT_LOG` asks for, e.g. `debug` or `grit_core=trace`, or else
+/// `-v` does. Nothing is written to a file without either, or `--log-file`.
+/// The log pane of the app gets the same events, or those at info at least.
+pub fn init_logging(opts: &opts::Opts) -> EResult<()> {
+    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+    let filter = match std::env::var("GRIT_LOG").ok().filter(|f| !f.is_empty()) {
+        Some(filter) => Some(filter),
+        None => match opts.verbose {
+            0 => opts.log_file.as_ref().map(|_| "info".to_string()),
+            1 => Some("info".to_string()),
+            2 => Some("debug".to_string()),
+            _ => Some("trace".to_string()),
+        },
+    };
+    let parse = |filter: &str| EnvFilter::try_new(filter).wrap_err("GRIT_LOG");
+    let pane = logs::layer().with_filter(parse(filter.as_deref().unwrap_or("info"))?);
+    let path = opts.log_file.clone().or_else(paths::log_file);
+    let file = match (filter, path) {
+        (Some(filter), Some(path)) => {
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir)
+                    .wrap_err_with(|| format!("create {}", dir.display()))?;
+            }
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .wrap_err_with(|| format!("open log {}", path.display()))?;
+            let layer = tracing_subscriber::fmt::layer()
+                .with_writer(std::sync::Mutex::new(file))
+                .with_ansi(false)
+                .with_filter(parse(&filter)?);
+            Some(layer)
+        }
+        _ => None,
+    };
+    tracing_subscriber::registry()
+        .with(pane)
+        .with(file)
+        .try_init()
+        .map_err(|err| eyre!("start logging: {err}"))
+}
 
 pub fn init(_opts: &opts::Opts) -> io::Result<Tui> {
+    if platform::current().signals {
+        handle_signals();
+    }
+    enter()?;
+    Terminal::new(CrosstermBackend::new(stdout()))
+}
+
+/// Takes the terminal back after [`suspend`], e.g. once an interactive command exits.
+pub fn resume<B: Backend>(terminal: &mut Tui<B>) -> io::Result<()> {
+    enter()?;
+    SUSPENDED.store(false, Ordering::SeqCst);
+    terminal.clear()
+}
+
+fn enter() -> io::Result<()> {
     execute!(stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    let paste = execute!(stdout(), EnableBracketedPaste);
+    if !platform::current().paste_optional {
+        paste?;
+    }
+    title::push()?;
     enable_raw_mode()?;
-    Terminal::new(CrosstermBackend::new(stdout()))
+    enhance_keys()?;
+    ACTIVE.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Hands the terminal to an interactive command until [`resume`].
+pub fn suspend() -> io::Result<()> {
+    SUSPENDED.store(true, Ordering::SeqCst);
+    restore()
 }
 
 pub fn restore() -> io::Result<()> {
-    execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    if enhanced_keys() {
+        execute!(stdout(), PopKeyboardEnhancementFlags)?;
+    }
+    execute!(
+        stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        Show
+    )?;
+    title::pop()?;
     disable_raw_mode()?;
     Ok(())
 }
 
+/// Whether keys such as `ctrl-enter` and `shift-enter` can be told apart from
+/// plain `enter`, which takes the kitty keyboard protocol.
+pub fn enhanced_keys() -> bool {
+    ENHANCED_KEYS.get().copied().unwrap_or(false)
+}
+
+/// Switches to the kitty keyboard protocol where the terminal has it. Others are
+/// left as they are, and bindings that need it simply never fire.
+fn enhance_keys() -> io::Result<()> {
+    let supported =
+        || platform::current().keyboard_query && supports_keyboard_enhancement().unwrap_or(false);
+    if *ENHANCED_KEYS.get_or_init(supported) {
+        let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+            | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS;
+        execute!(stdout(), PushKeyboardEnhancementFlags(flags))?;
+    }
+    Ok(())
+}
+
+/// Restores the terminal before dying of SIGTERM, SIGINT or SIGHUP. An interrupt
+/// while an interactive command has the terminal is that command's to handle.
+#[cfg(unix)]
+fn handle_signals() {
+    use signal_hook::{
+        consts::{SIGHUP, SIGINT, SIGTERM},
+        iterator::Signals,
+        low_level::emulate_default_handler,
+    };
+    static INSTALLED: AtomicBool = AtomicBool::new(false);
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let Ok(mut signals) = Signals::new([SIGTERM, SIGINT, SIGHUP]) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            if signal == SIGINT && SUSPENDED.load(Ordering::SeqCst) {
+                continue;
+            }
+            let _ = restore();
+            let _ = emulate_default_handler(signal);
+        }
+    });
+}
+
+/// Only unix has the signals, see [`platform::Platform::signals`].
+#[cfg(not(unix))]
+fn handle_signals() {}
+
 pub fn install_hooks() -> color_eyre::Result<()> {
     let (panic_hook, eyre_hook) = HookBuilder::default().into_hooks();
 
     // convert from color_eyre hook into std panic hook
     let panic_hook = panic_hook.into_panic_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
-        crate::bootstrap::restore().unwrap();
+        let report = crate::crash::write(panic_info);
+        if crate::crash::is_catching() {
+            // the app carries on and tells the user about the report itself
+            return;
+        }
+        let _ = crate::bootstrap::restore();
         panic_hook(panic_info);
+        if let Some(path) = report {
+            eprintln!("A crash report was written to {}", path.display());
+        }
     }));
 
-    // convert form a color_eyre EyreHook into an eyre ErrorHook
-    let eyre_hook = eyre_hook.into_eyre_hook();
-    eyre::set_hook(Box::new(
-        move |error: &(dyn std::error::Error + 'static)| {
-            crate::bootstrap::restore().unwrap();
-            eyre_hook(error)
-        },
-    ))?;
+    // errors are shown in the app, and the terminal is restored once it exits
+    eyre_hook.install()?;
 
     Ok(())
 }
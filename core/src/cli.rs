@@ -0,0 +1,757 @@
+//! Plain, JSON, CSV and Markdown output for when grit is not driving a
+//! terminal.
+
+use crate::{
+    config::Config,
+    fuzzy,
+    git::{self, prune},
+    opts::{BenchArgs, CheckoutArgs, DeleteArgs, Opts, PruneArgs, ReportArgs, StaleArgs},
+    prelude::*,
+};
+use color_eyre::eyre::Context;
+use git2::BranchType;
+use ratatui::backend::TestBackend;
+use serde::Serialize;
+use std::{
+    io::{IsTerminal, Write},
+    path::Path,
+    time::Instant,
+};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Plain,
+    Json,
+    Csv,
+    #[value(name = "md", alias = "markdown")]
+    Markdown,
+}
+
+/// Rows of text under a header, for the CSV and Markdown formats.
+pub struct Table {
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// The table as CSV, with fields quoted where they have to be. A field a
+    /// spreadsheet would take for a formula, such as a commit summary starting
+    /// with `=`, gets a `'` in front so that it stays text.
+    pub fn csv(&self) -> String {
+        let field = |s: &str| {
+            let s = match s.starts_with(['=', '+', '-', '@']) {
+                true => format!("'{s}"),
+                false => s.to_string(),
+            };
+            match s.contains([',', '"', '\n', '\r']) {
+                true => format!("\"{}\"", s.replace('"', "\"\"")),
+                false => s,
+            }
+        };
+        let mut res = String::new();
+        let headers: Vec<String> = self.headers.iter().map(|h| h.to_string()).collect();
+        for row in std::iter::once(&headers).chain(&self.rows) {
+            let row: Vec<String> = row.iter().map(|s| field(s)).collect();
+            res.push_str(&row.join(","));
+            res.push_str("\r\n");
+        }
+        res
+    }
+
+    /// The table as a Markdown table, with pipes and line breaks escaped.
+    pub fn markdown(&self) -> String {
+        // backslashes first, so that the one escaping a pipe is not escaped
+        let cell = |s: &str| {
+            s.replace('\\', "\\\\")
+                .replace('|', "\\|")
+                .replace(['\r', '\n'], " ")
+        };
+        let mut res = format!("| {} |\n", self.headers.join(" | "));
+        res.push_str(&format!("|{}\n", " --- |".repeat(self.headers.len())));
+        for row in &self.rows {
+            let row: Vec<String> = row.iter().map(|s| cell(s)).collect();
+            res.push_str(&format!("| {} |\n", row.join(" | ")));
+        }
+        res
+    }
+
+    fn write(&self, format: Format, out: &mut impl Write) -> EResult<()> {
+        match format {
+            Format::Csv => write!(out, "{}", self.csv())?,
+            _ => write!(out, "{}", self.markdown())?,
+        }
+        Ok(())
+    }
+}
+
+/// The exit status of grit, for shell wrappers to branch on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    /// Done, or a selection was made.
+    Success = 0,
+    /// The user cancelled, or nothing could be selected.
+    Aborted = 1,
+    /// Git or the repository failed.
+    Git = 2,
+    /// The command line was invalid.
+    Usage = 3,
+    /// The config file, or a setting from the environment, was invalid.
+    Config = 4,
+    /// Reading or writing a file or the terminal failed.
+    Io = 5,
+}
+
+impl From<Exit> for std::process::ExitCode {
+    fn from(exit: Exit) -> Self {
+        Self::from(exit as u8)
+    }
+}
+
+#[derive(Serialize)]
+pub struct BranchRow {
+    pub name: String,
+    pub kind: &'static str,
+    pub date: Option<String>,
+    pub author: Option<String>,
+    pub summary: Option<String>,
+}
+
+impl From<&git::Branch> for BranchRow {
+    fn from(branch: &git::Branch) -> Self {
+        let tip = branch.commits().first();
+        Self {
+            name: branch.name.clone(),
+            kind: match branch.typ {
+                BranchType::Local => "local",
+                BranchType::Remote => "remote",
+            },
+            date: tip.map(|c| c.timestamp.to_string()),
+            author: tip.and_then(|c| c.author.name.clone()),
+            summary: tip.map(|c| c.summary.clone()),
+        }
+    }
+}
+
+impl BranchRow {
+    pub fn table(rows: &[BranchRow]) -> Table {
+        let cell = |s: &Option<String>| s.clone().unwrap_or_default();
+        Table {
+            headers: vec!["name", "kind", "date", "author", "summary"],
+            rows: rows
+                .iter()
+                .map(|row| {
+                    vec![
+                        row.name.clone(),
+                        row.kind.to_string(),
+                        cell(&row.date),
+                        cell(&row.author),
+                        cell(&row.summary),
+                    ]
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Loads the branches of the repository the same way the TUI does, sorted by date.
+pub fn branches(opts: &Opts, config: &Config) -> EResult<Vec<git::Branch>> {
+    let repo = git::Repository::open(&opts.target())
+        .wrap_err("read repo")?
+        .with_max_commits(config.max_commits());
+    let mut branches = repo
+        .stream_branches(Some(BranchType::Local))
+        .into_iter()
+        .collect::<git::Result<Vec<_>>>()?;
+    config.view.sort.unwrap_or_default().apply(&mut branches);
+    Ok(branches)
+}
+
+pub fn list(opts: &Opts, config: &Config, out: &mut impl Write) -> EResult<()> {
+    let rows: Vec<BranchRow> = branches(opts, config)?
+        .iter()
+        .map(BranchRow::from)
+        .collect();
+    write_branches(opts, &rows, out)
+}
+
+/// Lists the local branches whose last commit is older than `args.days` days,
+/// oldest first.
+pub fn stale(opts: &Opts, config: &Config, args: &StaleArgs, out: &mut impl Write) -> EResult<()> {
+    let cutoff = chrono::Utc::now().timestamp() - i64::from(args.days) * 24 * 60 * 60;
+    let mut branches: Vec<git::Branch> = branches(opts, config)?
+        .into_iter()
+        .filter(|b| {
+            b.commits()
+                .first()
+                .is_some_and(|c| c.timestamp.epoch() < cutoff)
+        })
+        .collect();
+    branches.sort_by_key(|b| b.commits().first().map(|c| c.timestamp.epoch()));
+    let rows: Vec<BranchRow> = branches.iter().map(BranchRow::from).collect();
+    write_branches(opts, &rows, out)
+}
+
+#[derive(Serialize)]
+struct ReportAuthor {
+    author: String,
+    email: Option<String>,
+    branches: Vec<ReportBranch>,
+}
+
+#[derive(Serialize)]
+struct ReportBranch {
+    name: String,
+    reason: &'static str,
+    /// Days since the last commit.
+    days: i64,
+    summary: Option<String>,
+}
+
+/// Prints the stale local branches, as the TUI flags them, grouped by the
+/// author of their last commit: the authors with the most first, and their
+/// oldest branches first.
+pub fn report(
+    opts: &Opts,
+    config: &Config,
+    args: &ReportArgs,
+    out: &mut impl Write,
+) -> EResult<()> {
+    let repo = git::Repository::open(&opts.target()).wrap_err("read repo")?;
+    let now = chrono::Utc::now().timestamp();
+    let stale = repo.stale(now - args.stale)?;
+    let mut authors: Vec<ReportAuthor> = vec![];
+    for branch in branches(opts, config)? {
+        let Some(staleness) = branch.staleness(&stale) else {
+            continue;
+        };
+        let tip = branch.commits().first();
+        let name = tip
+            .and_then(|c| c.author.name.clone())
+            .unwrap_or("?".into());
+        let email = tip.and_then(|c| c.author.email.clone());
+        let row = ReportBranch {
+            name: branch.name.clone(),
+            reason: staleness.label(),
+            days: tip.map_or(0, |c| (now - c.timestamp.epoch()) / 86400),
+            summary: tip.map(|c| c.summary.clone()),
+        };
+        match authors
+            .iter_mut()
+            .find(|a| a.author == name && a.email == email)
+        {
+            Some(author) => author.branches.push(row),
+            None => authors.push(ReportAuthor {
+                author: name,
+                email,
+                branches: vec![row],
+            }),
+        }
+    }
+    for author in &mut authors {
+        author.branches.sort_by_key(|b| std::cmp::Reverse(b.days));
+    }
+    authors.sort_by_key(|a| std::cmp::Reverse(a.branches.len()));
+    match opts.format {
+        Format::Plain => {
+            let count: usize = authors.iter().map(|a| a.branches.len()).sum();
+            let days = args.stale / 86400;
+            writeln!(
+                out,
+                "{count} stale branches: merged into the default branch, or without commits for {days} days"
+            )?;
+            for author in &authors {
+                let email = author
+                    .email
+                    .as_deref()
+                    .map(|e| format!(" <{e}>"))
+                    .unwrap_or_default();
+                writeln!(
+                    out,
+                    "\n{}{email}: {} branches",
+                    author.author,
+                    author.branches.len()
+                )?;
+                let width = author.branches.iter().map(|b| b.name.len()).max();
+                for branch in &author.branches {
+                    writeln!(
+                        out,
+                        "  {:width$}  {}, {} days  {}",
+                        branch.name,
+                        branch.reason,
+                        branch.days,
+                        branch.summary.as_deref().unwrap_or_default(),
+                        width = width.unwrap_or_default()
+                    )?;
+                }
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, &authors).wrap_err("write json")?;
+            writeln!(out)?;
+        }
+        format => Table {
+            headers: vec!["author", "email", "name", "reason", "days", "summary"],
+            rows: authors
+                .iter()
+                .flat_map(|a| {
+                    a.branches.iter().map(|b| {
+                        vec![
+                            a.author.clone(),
+                            a.email.clone().unwrap_or_default(),
+                            b.name.clone(),
+                            b.reason.to_string(),
+                            b.days.to_string(),
+                            b.summary.clone().unwrap_or_default(),
+                        ]
+                    })
+                })
+                .collect(),
+        }
+        .write(format, out)?,
+    }
+    Ok(())
+}
+
+fn write_branches(opts: &Opts, rows: &[BranchRow], out: &mut impl Write) -> EResult<()> {
+    match opts.format {
+        Format::Plain => {
+            for row in rows {
+                let date = row.date.as_deref().unwrap_or("-");
+                let author = row.author.as_deref().unwrap_or("-");
+                let summary = row.summary.as_deref().unwrap_or_default();
+                writeln!(out, "{}\t{date}\t{author}\t{summary}", row.name)?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, &rows).wrap_err("write json")?;
+            writeln!(out)?;
+        }
+        format => BranchRow::table(rows).write(format, out)?,
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct DeleteRow<'a> {
+    name: &'a str,
+    deleted: bool,
+    error: Option<String>,
+}
+
+/// Deletes the local branches named in `args`, each only if it is merged
+/// unless `--force`, and never the one checked out or a protected one.
+pub fn delete(
+    opts: &Opts,
+    config: &Config,
+    args: &DeleteArgs,
+    out: &mut impl Write,
+) -> EResult<()> {
+    let repo = git::Repository::open(&opts.target()).wrap_err("read repo")?;
+    let mut rows = vec![];
+    for name in &args.names {
+        let tip = repo
+            .commit_id(&format!("refs/heads/{name}"))
+            .and_then(|id| git2::Oid::from_str(&id).ok());
+        let res = match tip {
+            // even with --force, as the app refuses them
+            _ if config.branch.is_protected(name) => {
+                Err(git::Error::Other(format!("{name} is protected")))
+            }
+            Some(tip) => repo.delete_branch(name, tip, args.force),
+            None => Err(git::Error::BranchNotFound(name.clone())),
+        };
+        rows.push(DeleteRow {
+            name,
+            deleted: res.is_ok(),
+            error: res.err().map(|err| format!("{err:#}")),
+        });
+    }
+    match opts.format {
+        Format::Plain => {
+            for row in &rows {
+                match &row.error {
+                    Some(err) => writeln!(out, "{}\tfailed: {err}", row.name)?,
+                    None => writeln!(out, "{}\tdeleted", row.name)?,
+                }
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, &rows).wrap_err("write json")?;
+            writeln!(out)?;
+        }
+        format => Table {
+            headers: vec!["name", "deleted", "error"],
+            rows: rows
+                .iter()
+                .map(|row| {
+                    let error = row.error.clone().unwrap_or_default();
+                    vec![row.name.to_string(), row.deleted.to_string(), error]
+                })
+                .collect(),
+        }
+        .write(format, out)?,
+    }
+    if let Some(row) = rows.iter().find(|r| r.error.is_some()) {
+        color_eyre::eyre::bail!("could not delete {}", row.name);
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct PruneRow<'a> {
+    #[serde(flatten)]
+    candidate: &'a prune::Candidate,
+    deleted: bool,
+    error: Option<String>,
+}
+
+/// Deletes the branches matching `args`, or with `--dry-run` only lists them.
+pub fn prune(opts: &Opts, args: &PruneArgs, out: &mut impl Write) -> EResult<()> {
+    let repo = git::Repository::open(&opts.target()).wrap_err("read repo")?;
+    let criteria = prune::Criteria {
+        merged: args.merged,
+        gone: args.gone,
+        older_than: args.older_than,
+    };
+    let candidates = repo.prune_candidates(criteria)?;
+    let mut rows = vec![];
+    for candidate in &candidates {
+        let res = if args.dry_run {
+            Ok(())
+        } else {
+            // the criteria pick branches whether merged or not
+            repo.delete_branch(&candidate.name, candidate.tip, true)
+        };
+        rows.push(PruneRow {
+            candidate,
+            deleted: !args.dry_run && res.is_ok(),
+            error: res.err().map(|err| format!("{err:#}")),
+        });
+    }
+    match opts.format {
+        Format::Plain => {
+            for row in &rows {
+                let status = match (&row.error, args.dry_run) {
+                    (Some(err), _) => format!("failed: {err}"),
+                    (None, true) => "would delete".to_string(),
+                    (None, false) => "deleted".to_string(),
+                };
+                let reasons: Vec<&str> = row.candidate.reasons.iter().map(|r| r.label()).collect();
+                writeln!(
+                    out,
+                    "{}\t{status}\t{}",
+                    row.candidate.name,
+                    reasons.join(",")
+                )?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, &rows).wrap_err("write json")?;
+            writeln!(out)?;
+        }
+        format => Table {
+            headers: vec!["name", "reasons", "deleted", "error"],
+            rows: rows
+                .iter()
+                .map(|row| {
+                    let reasons: Vec<&str> =
+                        row.candidate.reasons.iter().map(|r| r.label()).collect();
+                    vec![
+                        row.candidate.name.clone(),
+                        reasons.join(","),
+                        row.deleted.to_string(),
+                        row.error.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+        }
+        .write(format, out)?,
+    }
+    if let Some(row) = rows.iter().find(|r| r.error.is_some()) {
+        color_eyre::eyre::bail!("could not delete {}", row.candidate.name);
+    }
+    Ok(())
+}
+
+/// Checks out the branch matching `args.pattern`. Remote branches only count
+/// when there is no local branch of the same name. When more than one branch
+/// matches, and stdout is a terminal, the user picks one.
+pub fn checkout(opts: &Opts, args: &CheckoutArgs, out: &mut impl Write) -> EResult<Exit> {
+    let repo = git::Repository::open(&opts.target()).wrap_err("read repo")?;
+    let mut branches = repo.branches(None)?;
+    let locals: Vec<String> = branches
+        .iter()
+        .filter(|b| b.typ == BranchType::Local)
+        .map(|b| b.name.clone())
+        .collect();
+    branches.retain(|b| {
+        b.typ == BranchType::Local
+            || !b
+                .name
+                .split_once('/')
+                .is_some_and(|(_, name)| locals.iter().any(|l| l == name))
+    });
+    let names: Vec<&str> = branches.iter().map(|b| b.name.as_str()).collect();
+    let matches = match names.iter().position(|n| *n == args.pattern) {
+        Some(exact) => vec![exact],
+        None => fuzzy::rank(&args.pattern, names.iter().copied()),
+    };
+    let idx = match matches.as_slice() {
+        [] => {
+            eprintln!("no branch matches {}", args.pattern);
+            return Ok(Exit::Aborted);
+        }
+        [only] => *only,
+        _ if !std::io::stdout().is_terminal() => {
+            let names: Vec<&str> = matches.iter().map(|&i| names[i]).collect();
+            eprintln!(
+                "{} matches several branches: {}",
+                args.pattern,
+                names.join(", ")
+            );
+            return Ok(Exit::Aborted);
+        }
+        _ => {
+            let items = matches.iter().map(|&i| names[i].to_string()).collect();
+            let title = format!("checkout {}", args.pattern);
+            match crate::app::picker::pick(opts, &title, items)? {
+                Some(choice) => matches[choice],
+                None => return Ok(Exit::Aborted),
+            }
+        }
+    };
+    let branch = &branches[idx];
+    if branch.typ == BranchType::Local {
+        if let Some(path) = repo.worktrees()?.remove(&branch.name) {
+            return jump_to_worktree(&branch.name, &path, out);
+        }
+    }
+    let output = match repo.checkout(&branch.name, branch.typ) {
+        Err(err) if matches!(err.root(), git::Error::DirtyWorktree(_)) => {
+            if !args.stash {
+                eprintln!("{err}");
+                eprintln!("commit or stash your changes, or rerun with --stash");
+                return Ok(Exit::Aborted);
+            }
+            // git makes no stash when it finds nothing it can stash, and
+            // popping then would apply an older one
+            let before = repo.latest_stash()?;
+            repo.stash("grit checkout", true)?;
+            let stashed = repo.latest_stash()? != before;
+            let output = repo.checkout(&branch.name, branch.typ).inspect_err(|_| {
+                // put the changes back where they were
+                if stashed {
+                    let _ = repo.stash_pop();
+                }
+            })?;
+            match stashed {
+                true => output + &repo.stash_pop()?,
+                false => output,
+            }
+        }
+        res => res?,
+    };
+    write!(out, "{output}")?;
+    Ok(Exit::Success)
+}
+
+/// Points at the worktree `name` is checked out in, which git would refuse to
+/// check it out again: inside tmux a new window opens there, otherwise its path
+/// is printed, for `cd "$(grit checkout ...)"`.
+fn jump_to_worktree(name: &str, path: &Path, out: &mut impl Write) -> EResult<Exit> {
+    eprintln!(
+        "{name} is checked out in the worktree at {}",
+        path.display()
+    );
+    let in_tmux = std::env::var_os("TMUX").is_some_and(|tmux| !tmux.is_empty());
+    if in_tmux && std::io::stdout().is_terminal() {
+        let status = std::process::Command::new("tmux")
+            .args(["new-window", "-c"])
+            .arg(path)
+            .status()
+            .wrap_err("run tmux")?;
+        if status.success() {
+            eprintln!("opened a tmux window there");
+            return Ok(Exit::Success);
+        }
+    }
+    writeln!(out, "{}", path.display())?;
+    Ok(Exit::Success)
+}
+
+#[derive(Serialize)]
+struct BenchRow {
+    max_commits: usize,
+    branches: usize,
+    commits: usize,
+    open_ms: f64,
+    refs_ms: f64,
+    load_ms: f64,
+    first_frame_ms: f64,
+}
+
+/// Times opening the repository, listing its refs, loading the commits of
+/// every branch and getting the TUI to its first full frame, once for each of
+/// `args.at` commits per branch.
+pub fn bench(opts: &Opts, config: &Config, args: &BenchArgs, out: &mut impl Write) -> EResult<()> {
+    let levels = match args.at.as_slice() {
+        [] => vec![config.max_commits()],
+        at => at.to_vec(),
+    };
+    // every run starts from empty caches and state of its own, so that runs
+    // measure the same work and those of the user are neither read nor
+    // written. set before any thread is started that could read them
+    let scratch = tempfile::Builder::new()
+        .prefix("grit-bench-")
+        .tempdir()
+        .wrap_err("create scratch dir")?;
+    std::env::set_var("XDG_CACHE_HOME", scratch.path().join("cache"));
+    std::env::set_var("XDG_STATE_HOME", scratch.path().join("state"));
+    let rows = bench_levels(opts, config, args, levels, scratch.path())?;
+    match opts.format {
+        Format::Plain => {
+            writeln!(
+                out,
+                "max commits\tbranches\tcommits\topen\trefs\tload\tfirst frame"
+            )?;
+            for row in &rows {
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{:.1}ms\t{:.1}ms\t{:.1}ms\t{:.1}ms",
+                    row.max_commits,
+                    row.branches,
+                    row.commits,
+                    row.open_ms,
+                    row.refs_ms,
+                    row.load_ms,
+                    row.first_frame_ms
+                )?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, &rows).wrap_err("write json")?;
+            writeln!(out)?;
+        }
+        format => Table {
+            headers: vec![
+                "max_commits",
+                "branches",
+                "commits",
+                "open_ms",
+                "refs_ms",
+                "load_ms",
+                "first_frame_ms",
+            ],
+            rows: rows
+                .iter()
+                .map(|row| {
+                    vec![
+                        row.max_commits.to_string(),
+                        row.branches.to_string(),
+                        row.commits.to_string(),
+                        format!("{:.1}", row.open_ms),
+                        format!("{:.1}", row.refs_ms),
+                        format!("{:.1}", row.load_ms),
+                        format!("{:.1}", row.first_frame_ms),
+                    ]
+                })
+                .collect(),
+        }
+        .write(format, out)?,
+    }
+    Ok(())
+}
+
+/// The median of `args.runs` runs at each of `levels` commits per branch, the
+/// caches and state in `scratch` cleared before each.
+fn bench_levels(
+    opts: &Opts,
+    config: &Config,
+    args: &BenchArgs,
+    levels: Vec<usize>,
+    scratch: &Path,
+) -> EResult<Vec<BenchRow>> {
+    let mut rows = vec![];
+    for max in levels {
+        let mut config = config.clone();
+        config.max_commits = Some(max);
+        // the welcome hint would mark the first run as done
+        config.view.hints = Some(false);
+        // what is measured is the repository, not the network
+        config.forge.api = Some(false);
+        config.view.watch = false;
+        let mut runs = vec![];
+        for _ in 0..args.runs {
+            for dir in ["cache", "state"] {
+                let _ = std::fs::remove_dir_all(scratch.join(dir));
+            }
+            runs.push(bench_once(opts, &config)?);
+        }
+        let median = |f: fn(&BenchRow) -> f64| {
+            let mut values: Vec<f64> = runs.iter().map(f).collect();
+            values.sort_by(f64::total_cmp);
+            values[values.len() / 2]
+        };
+        rows.push(BenchRow {
+            open_ms: median(|r| r.open_ms),
+            refs_ms: median(|r| r.refs_ms),
+            load_ms: median(|r| r.load_ms),
+            first_frame_ms: median(|r| r.first_frame_ms),
+            ..runs.swap_remove(0)
+        });
+    }
+    Ok(rows)
+}
+
+fn bench_once(opts: &Opts, config: &Config) -> EResult<BenchRow> {
+    let ms = |start: Instant| start.elapsed().as_secs_f64() * 1000.0;
+    let start = Instant::now();
+    let repo = git::Repository::open(&opts.target())
+        .wrap_err("read repo")?
+        .with_max_commits(config.max_commits());
+    let open_ms = ms(start);
+    let start = Instant::now();
+    let branches = repo.branches(None)?.len();
+    let refs_ms = ms(start);
+    let start = Instant::now();
+    let commits = repo
+        .stream_branches(None)
+        .into_iter()
+        .map(|branch| branch.map(|b| b.commits().len()))
+        .sum::<git::Result<usize>>()?;
+    let load_ms = ms(start);
+    let start = Instant::now();
+    let mut terminal = Terminal::new(TestBackend::new(120, 40))?;
+    crate::app::App::new(opts, config.clone())?.draw_loaded(&mut terminal)?;
+    let first_frame_ms = ms(start);
+    Ok(BenchRow {
+        max_commits: config.max_commits(),
+        branches,
+        commits,
+        open_ms,
+        refs_ms,
+        load_ms,
+        first_frame_ms,
+    })
+}
+
+/// Parses an age such as `36h`, `90d`, `12w`, `6m` or `1y` into seconds.
+pub fn parse_age(s: &str) -> Result<i64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let num: i64 = num
+        .parse()
+        .map_err(|_| format!("expected a number followed by h, d, w, m or y: {s}"))?;
+    let hours = match unit {
+        "h" => 1,
+        "d" | "" => 24,
+        "w" => 24 * 7,
+        "m" => 24 * 30,
+        "y" => 24 * 365,
+        _ => return Err(format!("unknown unit {unit:?}, expected h, d, w, m or y")),
+    };
+    num.checked_mul(hours * 60 * 60)
+        .ok_or_else(|| format!("expected a number followed by h, d, w, m or y: {s}"))
+}
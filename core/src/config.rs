@@ -0,0 +1,517 @@
+use color_eyre::eyre::{eyre, Context};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    app::{Column, CopyFormat, Sort},
+    opts::Opts,
+    paths, platform,
+    prelude::*,
+    t,
+};
+
+/// User configuration read from `config.toml` in [`paths::config_dir`].
+///
+/// Use [`Config::resolve`] to layer `GRIT_*` environment variables and command
+/// line flags on top of the file.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct Config {
+    pub locale: Option<String>,
+    /// The color theme: `default`, for dark terminals, `light`, or
+    /// `high-contrast`, which keeps to the terminal's 16 colors.
+    pub theme: Option<String>,
+    /// Colors that replace those of the theme.
+    pub colors: ColorsConfig,
+    /// Keys for built-in actions in place of their defaults, by action name,
+    /// e.g. `fetch-all = "F"` or `select-next = ["t", "down"]`.
+    pub keys: BTreeMap<String, Keys>,
+    /// How many commits are loaded per branch. Defaults to 100.
+    pub max_commits: Option<usize>,
+    pub branch: BranchConfig,
+    pub view: ViewConfig,
+    pub stash: StashConfig,
+    pub forge: ForgeConfig,
+    pub editor: EditorConfig,
+    pub diff: DiffConfig,
+    pub commit: CommitConfig,
+    /// Shell commands keyed by event: `pre-create`, `post-create`,
+    /// `pre-rename`, `post-rename`, `pre-delete`, `post-checkout` or
+    /// `post-fetch`. They run as jobs, and a failing `pre-*` one stops the
+    /// action.
+    pub hooks: BTreeMap<String, String>,
+    /// Shell commands bound to keys, from `[[commands]]` tables.
+    pub commands: Vec<UserCommand>,
+    /// Named filters and sorts of the branch list, from `[[views]]` tables.
+    /// The first nine are on `space w 1` to `space w 9`, leaving the number
+    /// keys to the tabs.
+    pub views: Vec<SavedView>,
+    /// Other git tools to hand the terminal to, from `[[handoff]]` tables.
+    /// Unset, tig, lazygit and gitui are offered if they are installed; an
+    /// empty list offers none.
+    pub handoff: Option<Vec<Handoff>>,
+    /// Overrides for the repositories whose paths match, from `[profiles.<name>]`
+    /// tables. The first matching profile in name order applies.
+    pub profiles: BTreeMap<String, Profile>,
+    /// The name of the profile that applies, if any.
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// The file this was read from, or would be, which settings changed in
+    /// grit are written back to.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// Whether integrations were turned off with `--safe`.
+    #[serde(skip)]
+    pub safe: bool,
+}
+
+/// Colors such as `blue`, `#1e40af` or `17`. Unset ones are the theme's.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct ColorsConfig {
+    pub header_fg: Option<String>,
+    pub header_bg: Option<String>,
+    pub selected_bg: Option<String>,
+    pub row_bg: Option<String>,
+    pub local_branch: Option<String>,
+    pub remote_branch: Option<String>,
+    /// How the selected row stands out: `color`, the theme's, `reverse` for
+    /// reverse video, or `bold` alone.
+    pub selection: Option<String>,
+    /// How many colors the terminal shows: `truecolor`, `256` or `16`.
+    /// Detected from `COLORTERM` and terminfo when unset.
+    pub depth: Option<String>,
+}
+
+/// One key sequence or several.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Keys {
+    pub fn to_vec(&self) -> Vec<String> {
+        match self {
+            Keys::One(seq) => vec![seq.clone()],
+            Keys::Many(seqs) => seqs.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct UserCommand {
+    /// A key sequence such as `P` or `space g p`.
+    pub key: String,
+    /// The shell command. `{branch}`, `{commit}` and `{upstream}` are replaced
+    /// with the selected branch.
+    pub cmd: String,
+    /// Shown in the leader menu instead of the command itself.
+    pub name: Option<String>,
+    /// Hands the terminal over to the command rather than capturing its output.
+    #[serde(default)]
+    pub interactive: bool,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SavedView {
+    /// Shown in the header while the view is active, e.g. `needs push`.
+    pub name: String,
+    /// A filter such as `author:me age>90d`, as typed after `/`.
+    #[serde(default)]
+    pub filter: String,
+    /// The sort, or the current one if unset.
+    pub sort: Option<Sort>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Handoff {
+    pub key: String,
+    /// The command, with the same placeholders as user commands.
+    pub cmd: String,
+    pub name: Option<String>,
+}
+
+/// Settings for a kind of repository, such as those of work or of open source
+/// projects, that override the rest of the config.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct Profile {
+    /// Where the repositories are, e.g. `~/work/*`. `*` matches within a
+    /// directory name and `**` across them; a pattern matching a parent of the
+    /// repository matches it too.
+    pub paths: Vec<String>,
+    pub theme: Option<String>,
+    /// Replaces `branch.protected`.
+    pub protected: Option<Vec<String>>,
+    /// Replaces `view.branches`.
+    pub branches: Option<BranchKind>,
+    /// Replaces `view.author`.
+    pub author: Option<String>,
+    /// Replaces `view.columns`.
+    pub columns: Option<Vec<Column>>,
+    /// Replaces `commit.email_pattern`, e.g. to commit with the work address
+    /// in work repositories.
+    pub email_pattern: Option<String>,
+}
+
+/// The tools offered when `handoff` is unset, if found on `$PATH`.
+const HANDOFF_DEFAULTS: [(&str, &str, &str); 3] = [
+    ("tig", "space o t", "tig {branch}"),
+    ("lazygit", "space o l", "lazygit branch"),
+    ("gitui", "space o g", "gitui"),
+];
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct StashConfig {
+    /// Stashes untracked files too.
+    pub untracked: bool,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct EditorConfig {
+    /// Edits messages and notes in the editor git uses, from `git var
+    /// GIT_EDITOR`, rather than in grit.
+    pub external: bool,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct DiffConfig {
+    /// Pairs up deleted and added files as renames. Defaults to true; turning
+    /// it off speeds up diffs that touch many files.
+    pub renames: Option<bool>,
+    /// Also looks for files copied from others changed alongside them.
+    /// Defaults to true, and only applies with renames.
+    pub copies: Option<bool>,
+    /// How similar, in percent, two files must be to count as a rename or a
+    /// copy. Defaults to 50, as in git.
+    pub similarity: Option<u8>,
+    /// How many lines of a file's diff are shown before the rest is folded
+    /// away, to be loaded with `x`. Defaults to 1000.
+    pub fold_lines: Option<usize>,
+}
+
+/// Checks on commit messages written in grit, shown as warnings while the
+/// message is edited. None are made unless set.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct CommitConfig {
+    /// The longest the first line may be, e.g. 50.
+    pub subject_max: Option<usize>,
+    /// The longest the other lines may be, e.g. 72. Also asks for a blank line
+    /// after the subject.
+    pub body_max: Option<usize>,
+    /// A regex the subject has to match, e.g. for conventional commits
+    /// `^(feat|fix|docs|chore)(\(.+\))?: `.
+    pub subject_pattern: Option<String>,
+    /// Refuses to commit while there are warnings, rather than only showing them.
+    pub enforce: bool,
+    /// Runs the repository's git hooks where git would and shows their
+    /// output: in a pane for `pre-commit` and `commit-msg` of commits, for
+    /// `post-checkout` of checking out a branch, and for the hooks grit runs
+    /// before writing squashes (`pre-commit`, `commit-msg`) and merge commits
+    /// (`pre-merge-commit`, `commit-msg`) itself. Cherry-picks and reverts
+    /// commit with `git commit` to run them. A failing hook stops the commit.
+    /// Plain commits and fixups are made by git, which runs their hooks even
+    /// when this is off, without showing their output as it comes.
+    pub hooks: bool,
+    /// A regex `user.email` has to match before the first commit or merge of
+    /// a session, e.g. `@example\.com$`.
+    pub email_pattern: Option<String>,
+}
+
+impl DiffConfig {
+    /// The options to give `git diff` and `git show`.
+    pub fn args(&self) -> Vec<String> {
+        if !self.renames.unwrap_or(true) {
+            return vec!["--no-renames".to_string()];
+        }
+        let similarity = self.similarity.unwrap_or(50).min(100);
+        let mut args = vec![format!("--find-renames={similarity}%")];
+        if self.copies.unwrap_or(true) {
+            args.push(format!("--find-copies={similarity}%"));
+        }
+        args
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct ForgeConfig {
+    /// Self-hosted instances, mapping a host to a forge, e.g.
+    /// `"git.example.com" = "gitea"`.
+    pub hosts: BTreeMap<String, String>,
+    /// How long fetched pull request data, and the checks of commits that had
+    /// not finished, are trusted before they are fetched again, in minutes.
+    /// Defaults to 10.
+    pub cache_minutes: Option<u64>,
+    /// Calls the forges' APIs to look up and open pull requests. Defaults to
+    /// true; without, pull requests are opened in the browser.
+    pub api: Option<bool>,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct BranchConfig {
+    /// A template for new branch names, e.g. `{user}/{type}/{slug}`. Each
+    /// placeholder is prompted for when creating a branch.
+    pub template: Option<String>,
+    /// Branches that are not deleted, renamed or squashed, as patterns such as
+    /// `main` or `release/*`.
+    pub protected: Vec<String>,
+    /// How many days after its last commit a local branch is stale. Defaults
+    /// to 90.
+    pub stale_days: Option<u32>,
+    /// With more branches than this, 2000 unless set, only the `load_first`
+    /// committed to last are read at start, and the rest once asked for.
+    pub load_limit: Option<usize>,
+    /// How many branches are read at start past `load_limit`. Defaults to 300.
+    pub load_first: Option<usize>,
+}
+
+impl BranchConfig {
+    pub fn is_protected(&self, name: &str) -> bool {
+        self.protected.iter().any(|pattern| glob(pattern, name))
+    }
+}
+
+/// Which branches the list starts out showing.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BranchKind {
+    All,
+    #[default]
+    Local,
+    Remote,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct ViewConfig {
+    /// Shows tips such as the first-run pointer to `?`. Defaults to true.
+    pub hints: Option<bool>,
+    /// The initial sort of the branch list, e.g. `name` or `date`.
+    pub sort: Option<Sort>,
+    /// Colors commits by author in the details pane.
+    pub author_colors: bool,
+    /// Shows a column of weekly commit activity in the branch list.
+    pub heat: bool,
+    /// How many weeks the activity column covers. Defaults to 12.
+    pub heat_weeks: Option<usize>,
+    /// Groups local branches by their upstream status.
+    pub group: bool,
+    /// Lists branches stacked on others under them, indented, instead of
+    /// grouping.
+    pub stacks: bool,
+    /// The most frames drawn per second. Defaults to 60.
+    pub max_fps: Option<u32>,
+    /// Reloads when other programs change the repository.
+    pub watch: bool,
+    /// How often followed branches are fetched, in seconds. Defaults to 60.
+    pub follow_seconds: Option<u64>,
+    /// Which branches are listed at first: `local`, the default, `remote` or
+    /// `all`.
+    pub branches: BranchKind,
+    /// Shows only the commits of this author at first, by name.
+    pub author: Option<String>,
+    /// The columns of the branch list, in order: `name`, `age`, `author`,
+    /// `owner`, `tracking`, `upstream`, `summary`, `pr` and `checklist`. Unset,
+    /// the name is followed by the tracking alone, unaligned.
+    pub columns: Option<Vec<Column>>,
+    /// Shows history through replace refs and grafts, as git does. Defaults
+    /// to true.
+    pub replace: Option<bool>,
+    /// What `y` copies of the commit under the cursor: `sha`, the default,
+    /// `oneline`, `message` or `markdown`. `Y` asks.
+    pub copy: CopyFormat,
+}
+
+impl Config {
+    /// Loads the config file and applies overrides, from lowest to highest
+    /// precedence: the file (`--config`, `$GRIT_CONFIG` or the default path),
+    /// `GRIT_*` environment variables, then command line flags.
+    pub fn resolve(opts: &Opts) -> EResult<Self> {
+        let explicit =
+            opts.config.is_some() || std::env::var_os("GRIT_CONFIG").is_some_and(|p| !p.is_empty());
+        let path = paths::config_file(opts.config.as_deref());
+        let mut config = match &path {
+            // a file asked for has to be there, the default one need not be
+            Some(path) if explicit || path.exists() => Self::load_from(path)?,
+            _ => Self::default(),
+        };
+        config.path = path;
+        let dir = opts.work_tree.as_ref().or(opts.dir.as_ref()).cloned();
+        if let Some(dir) = dir.or_else(|| std::env::current_dir().ok()) {
+            config.apply_profile(&dir);
+        }
+        config.apply_env(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))?;
+        config.apply_opts(opts);
+        tracing::debug!(path = ?config.path, profile = ?config.profile, "config");
+        Ok(config)
+    }
+
+    /// Applies the first profile with a path matching `dir` or one of its
+    /// parents.
+    fn apply_profile(&mut self, dir: &Path) {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let matches = |pattern: &String| {
+            let pattern = match (pattern.strip_prefix("~/"), &home) {
+                (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+                _ => pattern.clone(),
+            };
+            let pattern = pattern.trim_end_matches('/');
+            dir.ancestors()
+                .any(|path| glob(pattern, &path.to_string_lossy()))
+        };
+        let Some((name, profile)) = self
+            .profiles
+            .iter()
+            .find(|(_, profile)| profile.paths.iter().any(matches))
+        else {
+            return;
+        };
+        let profile = profile.clone();
+        self.profile = Some(name.clone());
+        if let Some(theme) = profile.theme {
+            self.theme = Some(theme);
+        }
+        if let Some(protected) = profile.protected {
+            self.branch.protected = protected;
+        }
+        if let Some(branches) = profile.branches {
+            self.view.branches = branches;
+        }
+        if let Some(author) = profile.author {
+            self.view.author = Some(author);
+        }
+        if let Some(columns) = profile.columns {
+            self.view.columns = Some(columns);
+        }
+        if let Some(pattern) = profile.email_pattern {
+            self.commit.email_pattern = Some(pattern);
+        }
+    }
+
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> EResult<()> {
+        if let Some(locale) = var("GRIT_LANG") {
+            self.locale = Some(locale);
+        }
+        if let Some(theme) = var("GRIT_THEME") {
+            self.theme = Some(theme);
+        }
+        if let Some(sort) = var("GRIT_SORT") {
+            let sort = Sort::try_from(sort).map_err(|err| eyre!("GRIT_SORT: {err}"))?;
+            self.view.sort = Some(sort);
+        }
+        if let Some(max) = var("GRIT_MAX_COMMITS") {
+            let max = max.parse().wrap_err("GRIT_MAX_COMMITS")?;
+            self.max_commits = Some(max);
+        }
+        Ok(())
+    }
+
+    fn apply_opts(&mut self, opts: &Opts) {
+        if let Some(locale) = &opts.locale {
+            self.locale = Some(locale.clone());
+        }
+        if let Some(theme) = &opts.theme {
+            self.theme = Some(theme.clone());
+        }
+        if let Some(sort) = opts.sort {
+            self.view.sort = Some(sort);
+        }
+        if let Some(max) = opts.max_commits {
+            self.max_commits = Some(max);
+        }
+        self.view.watch |= opts.watch;
+        if opts.safe {
+            self.make_safe();
+        }
+    }
+
+    /// Turns off whatever reaches beyond the repository, and the colors, for
+    /// `--safe`. The rest of the config is kept.
+    fn make_safe(&mut self) {
+        self.safe = true;
+        self.theme = None;
+        self.colors = ColorsConfig::default();
+        self.view.watch = false;
+        self.forge.api = Some(false);
+        self.hooks.clear();
+        self.commands.clear();
+        self.handoff = Some(Vec::new());
+    }
+
+    pub fn max_commits(&self) -> usize {
+        self.max_commits.unwrap_or(crate::git::DEFAULT_MAX_COMMITS)
+    }
+
+    /// The handoff tools, as interactive user commands.
+    pub fn handoffs(&self) -> Vec<UserCommand> {
+        let handoffs = match &self.handoff {
+            Some(handoffs) => handoffs.clone(),
+            None => HANDOFF_DEFAULTS
+                .iter()
+                .filter(|(program, _, _)| platform::current().on_path(program))
+                .map(|(program, key, cmd)| Handoff {
+                    key: key.to_string(),
+                    cmd: cmd.to_string(),
+                    name: Some(program.to_string()),
+                })
+                .collect(),
+        };
+        handoffs
+            .into_iter()
+            .map(|handoff| {
+                let name = handoff.name.as_deref().unwrap_or(&handoff.cmd);
+                UserCommand {
+                    name: Some(t!("open in {tool}", tool = name)),
+                    key: handoff.key,
+                    cmd: handoff.cmd,
+                    interactive: true,
+                }
+            })
+            .collect()
+    }
+
+    pub fn load() -> EResult<Self> {
+        match paths::config_file(None) {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> EResult<Self> {
+        let contents = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("read config {}", path.display()))?;
+        toml::from_str(&contents).wrap_err_with(|| format!("parse config {}", path.display()))
+    }
+}
+
+/// Whether `text` matches `pattern`, in which `*` stands for any characters
+/// but `/`, `**` for any at all, and `?` for one.
+fn glob(pattern: &str, text: &str) -> bool {
+    fn go(pattern: &[char], text: &[char]) -> bool {
+        match pattern {
+            [] => text.is_empty(),
+            ['*', '*', rest @ ..] => (0..=text.len()).any(|i| go(rest, &text[i..])),
+            ['*', rest @ ..] => {
+                let within = text.iter().position(|c| *c == '/').unwrap_or(text.len());
+                (0..=within).any(|i| go(rest, &text[i..]))
+            }
+            ['?', rest @ ..] => text.first().is_some_and(|c| *c != '/') && go(rest, &text[1..]),
+            [c, rest @ ..] => text.first() == Some(c) && go(rest, &text[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    go(&pattern, &text)
+}
@@ -0,0 +1,86 @@
+//! Crash reports, so that a panic leaves behind more than a torn down terminal.
+//!
+//! The panic hook installed by [`bootstrap`](crate::bootstrap) writes a report
+//! with the backtrace, the repository and the last actions taken. Panics inside
+//! [`catch`] are left for the caller to recover from instead of ending the process.
+
+use crate::paths;
+use std::{
+    backtrace::Backtrace,
+    cell::Cell,
+    collections::VecDeque,
+    fmt::Write,
+    panic::{AssertUnwindSafe, PanicHookInfo},
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+};
+
+/// How many of the latest actions a report lists.
+const TRAIL_LEN: usize = 20;
+
+static TRAIL: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static REPO: OnceLock<PathBuf> = OnceLock::new();
+static LAST_REPORT: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+thread_local! {
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Remembers an action for the next report.
+pub fn record(action: impl Into<String>) {
+    if let Ok(mut trail) = TRAIL.lock() {
+        if trail.len() == TRAIL_LEN {
+            trail.pop_front();
+        }
+        trail.push_back(action.into());
+    }
+}
+
+/// Sets the repository named in reports.
+pub fn set_repo(path: impl Into<PathBuf>) {
+    let _ = REPO.set(path.into());
+}
+
+/// Runs `f`, turning a panic into an error carrying the path of its report.
+pub fn catch<T>(f: impl FnOnce() -> T) -> Result<T, Option<PathBuf>> {
+    let outer = CATCHING.replace(true);
+    let res = std::panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(outer);
+    res.map_err(|_| LAST_REPORT.lock().ok().and_then(|mut path| path.take()))
+}
+
+/// Whether a panic on this thread will be recovered from by [`catch`].
+pub fn is_catching() -> bool {
+    CATCHING.get()
+}
+
+/// Writes a report for a panic, returning where it went.
+pub fn write(info: &PanicHookInfo<'_>) -> Option<PathBuf> {
+    let now = chrono::Local::now();
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "grit {} crashed at {now}",
+        env!("CARGO_PKG_VERSION")
+    );
+    if let Some(repo) = REPO.get() {
+        let _ = writeln!(report, "repo: {}", repo.display());
+    }
+    let _ = writeln!(report, "\n{info}\n\nlast actions:");
+    if let Ok(trail) = TRAIL.lock() {
+        for action in trail.iter() {
+            let _ = writeln!(report, "  {action}");
+        }
+    }
+    let _ = writeln!(report, "\nbacktrace:\n{}", Backtrace::force_capture());
+
+    let dir = paths::state_dir().unwrap_or_else(std::env::temp_dir);
+    let path = dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+    std::fs::create_dir_all(&dir).ok()?;
+    std::fs::write(&path, report).ok()?;
+    tracing::error!(report = %path.display(), "crashed");
+    if let Ok(mut last) = LAST_REPORT.lock() {
+        *last = Some(path.clone());
+    }
+    Some(path)
+}
@@ -0,0 +1,155 @@
+use super::{http, Checks, Forge, PullRequest, Repo, Review, Verdict};
+use crate::prelude::*;
+use color_eyre::eyre::eyre;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+/// Gitea and its Forgejo fork, which share an API.
+pub struct Gitea;
+
+impl Gitea {
+    fn pulls(repo: &Repo) -> String {
+        let Repo { host, owner, name } = repo;
+        format!("https://{host}/api/v1/repos/{owner}/{name}/pulls")
+    }
+
+    /// The combined status of the commit `sha`.
+    fn status(&self, repo: &Repo, headers: &http::Headers, sha: &str) -> EResult<Option<Checks>> {
+        let Repo { host, owner, name } = repo;
+        let url = format!("https://{host}/api/v1/repos/{owner}/{name}/commits/{sha}/status");
+        let status = http::get(self.budget(), &url, headers)?;
+        // a commit without statuses reads as pending too
+        Ok(match status["total_count"].as_u64() {
+            Some(0) => None,
+            _ => status["state"].as_str().and_then(Checks::parse),
+        })
+    }
+}
+
+impl Forge for Gitea {
+    fn id(&self) -> &'static str {
+        "gitea"
+    }
+
+    fn name(&self) -> &'static str {
+        "Gitea"
+    }
+
+    fn default_host(&self) -> Option<&'static str> {
+        Some("codeberg.org")
+    }
+
+    fn token_vars(&self) -> &'static [&'static str] {
+        &["GITEA_TOKEN"]
+    }
+
+    fn token_url(&self, host: &str) -> String {
+        format!("https://{host}/user/settings/applications")
+    }
+
+    fn compare_url(&self, repo: &Repo, pr: &PullRequest) -> String {
+        let Repo { host, owner, name } = repo;
+        let base = format!(
+            "https://{host}/{owner}/{name}/compare/{}...{}",
+            pr.base, pr.head
+        );
+        let params = [("title", pr.title.as_str()), ("body", pr.body.as_str())];
+        url::Url::parse_with_params(&base, params).map_or(base, String::from)
+    }
+
+    fn commit_url(&self, repo: &Repo, id: &str) -> String {
+        let Repo { host, owner, name } = repo;
+        format!("https://{host}/{owner}/{name}/commit/{id}")
+    }
+
+    fn create_pull_request(&self, repo: &Repo, token: &str, pr: &PullRequest) -> EResult<String> {
+        let body = json!({
+            "title": pr.title,
+            "body": pr.body,
+            "head": pr.head,
+            "base": pr.base,
+        });
+        let headers = [("Authorization", format!("token {token}"))];
+        let json = http::post(self.budget(), &Self::pulls(repo), &headers, body)?;
+        json["html_url"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| eyre!("the response has no html_url"))
+    }
+
+    fn review(&self, repo: &Repo, token: &str, head: &str) -> EResult<Option<Review>> {
+        let headers = [("Authorization", format!("token {token}"))];
+        let base = Self::pulls(repo);
+        let list = http::get(
+            self.budget(),
+            &format!("{base}?state=open&limit=50"),
+            &headers,
+        )?;
+        let Some(pr) = list.as_array().and_then(|prs| {
+            // one from a fork with a branch of the same name is another's
+            let full = format!("{}/{}", repo.owner, repo.name);
+            prs.iter().find(|pr| {
+                let from = pr["head"]["repo"]["full_name"].as_str().unwrap_or_default();
+                pr["head"]["ref"] == head && from.eq_ignore_ascii_case(&full)
+            })
+        }) else {
+            return Ok(None);
+        };
+        let number = pr["number"].as_u64().unwrap_or_default();
+        let checks = match pr["head"]["sha"].as_str() {
+            Some(sha) => self.status(repo, &headers, sha)?,
+            None => None,
+        };
+        let reviews = http::get(self.budget(), &format!("{base}/{number}/reviews"), &headers)?;
+        let reviews = reviews.as_array().cloned().unwrap_or_default();
+        // the latest verdict of each reviewer counts
+        let mut verdicts = BTreeMap::new();
+        let mut unresolved = 0;
+        for review in &reviews {
+            if review["dismissed"] == true {
+                continue;
+            }
+            let verdict = match review["state"].as_str() {
+                Some("APPROVED") => Verdict::Approved,
+                Some("REQUEST_CHANGES") => Verdict::ChangesRequested,
+                _ => Verdict::Pending,
+            };
+            if verdict != Verdict::Pending {
+                let user = review["user"]["login"].as_str().unwrap_or_default();
+                verdicts.insert(user.to_string(), verdict);
+            }
+            if review["comments_count"].as_u64().unwrap_or_default() > 0 {
+                let id = review["id"].as_u64().unwrap_or_default();
+                let comments = http::get(
+                    self.budget(),
+                    &format!("{base}/{number}/reviews/{id}/comments"),
+                    &headers,
+                )?;
+                unresolved += comments
+                    .as_array()
+                    .map_or(0, |c| c.iter().filter(|c| c["resolver"].is_null()).count());
+            }
+        }
+        let verdict = if verdicts.values().any(|v| *v == Verdict::ChangesRequested) {
+            Verdict::ChangesRequested
+        } else if verdicts.is_empty() {
+            Verdict::Pending
+        } else {
+            Verdict::Approved
+        };
+        Ok(Some(Review {
+            number,
+            url: pr["html_url"].as_str().unwrap_or_default().to_string(),
+            verdict,
+            unresolved,
+            mergeable: pr["mergeable"].as_bool(),
+            draft: pr["draft"].as_bool().unwrap_or_default(),
+            checks,
+        }))
+    }
+
+    fn commit_checks(&self, repo: &Repo, token: &str, id: &str) -> EResult<Option<Checks>> {
+        let headers = [("Authorization", format!("token {token}"))];
+        self.status(repo, &headers, id)
+    }
+}
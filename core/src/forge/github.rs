@@ -0,0 +1,204 @@
+use super::{http, limit::Budget, Checks, Forge, PullRequest, Repo, Review, Verdict};
+use crate::prelude::*;
+use color_eyre::eyre::{bail, eyre};
+use serde_json::json;
+
+pub struct GitHub;
+
+/// Finds the open pull request of a branch with its review threads, which only
+/// the GraphQL API tells apart as resolved or not.
+const REVIEW_QUERY: &str = "
+query($owner: String!, $name: String!, $head: String!) {
+  repository(owner: $owner, name: $name) {
+    pullRequests(headRefName: $head, states: OPEN, first: 20) {
+      nodes {
+        headRepositoryOwner { login }
+        headRepository { name }
+        number
+        url
+        isDraft
+        reviewDecision
+        mergeable
+        commits(last: 1) { nodes { commit { statusCheckRollup { state } } } }
+        reviewThreads(first: 100) { nodes { isResolved } }
+      }
+    }
+  }
+}";
+
+/// The checks of a commit, statuses and check runs taken together.
+const CHECKS_QUERY: &str = "
+query($owner: String!, $name: String!, $oid: GitObjectID!) {
+  repository(owner: $owner, name: $name) {
+    object(oid: $oid) { ... on Commit { statusCheckRollup { state } } }
+  }
+}";
+
+impl GitHub {
+    /// GitHub Enterprise serves the API from the same host.
+    fn api(host: &str) -> String {
+        match host {
+            "github.com" => "https://api.github.com".to_string(),
+            host => format!("https://{host}/api/v3"),
+        }
+    }
+
+    fn graphql(host: &str) -> String {
+        match host {
+            "github.com" => "https://api.github.com/graphql".to_string(),
+            host => format!("https://{host}/api/graphql"),
+        }
+    }
+
+    fn headers(token: &str) -> [(&'static str, String); 2] {
+        [
+            ("Authorization", format!("Bearer {token}")),
+            ("Accept", "application/vnd.github+json".to_string()),
+        ]
+    }
+}
+
+impl Forge for GitHub {
+    fn id(&self) -> &'static str {
+        "github"
+    }
+
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn default_host(&self) -> Option<&'static str> {
+        Some("github.com")
+    }
+
+    fn token_vars(&self) -> &'static [&'static str] {
+        &["GITHUB_TOKEN", "GH_TOKEN"]
+    }
+
+    fn token_url(&self, host: &str) -> String {
+        format!("https://{host}/settings/tokens")
+    }
+
+    fn token_command(&self, host: &str) -> Option<(&'static str, Vec<String>)> {
+        let args = ["auth", "token", "--hostname", host];
+        Some(("gh", args.map(String::from).to_vec()))
+    }
+
+    /// Well within the secondary limit on bursts of requests.
+    fn budget(&self) -> Budget {
+        Budget {
+            burst: 20,
+            per_minute: 60,
+        }
+    }
+
+    fn compare_url(&self, repo: &Repo, pr: &PullRequest) -> String {
+        let Repo { host, owner, name } = repo;
+        let base = format!(
+            "https://{host}/{owner}/{name}/compare/{}...{}",
+            pr.base, pr.head
+        );
+        let params = [
+            ("expand", "1"),
+            ("title", pr.title.as_str()),
+            ("body", pr.body.as_str()),
+        ];
+        url::Url::parse_with_params(&base, params).map_or(base, String::from)
+    }
+
+    fn commit_url(&self, repo: &Repo, id: &str) -> String {
+        let Repo { host, owner, name } = repo;
+        format!("https://{host}/{owner}/{name}/commit/{id}")
+    }
+
+    fn create_pull_request(&self, repo: &Repo, token: &str, pr: &PullRequest) -> EResult<String> {
+        let Repo { host, owner, name } = repo;
+        let url = format!("{}/repos/{owner}/{name}/pulls", Self::api(host));
+        let body = json!({
+            "title": pr.title,
+            "body": pr.body,
+            "head": pr.head,
+            "base": pr.base,
+        });
+        let json = http::post(self.budget(), &url, &Self::headers(token), body)?;
+        json["html_url"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| eyre!("the response has no html_url"))
+    }
+
+    fn review(&self, repo: &Repo, token: &str, head: &str) -> EResult<Option<Review>> {
+        let body = json!({
+            "query": REVIEW_QUERY,
+            "variables": { "owner": repo.owner, "name": repo.name, "head": head },
+        });
+        let json = http::post(
+            self.budget(),
+            &Self::graphql(&repo.host),
+            &Self::headers(token),
+            body,
+        )?;
+        if let Some(err) = json["errors"][0]["message"].as_str() {
+            bail!("{err}");
+        }
+        // one from a fork with a branch of the same name is another's
+        let nodes = json["data"]["repository"]["pullRequests"]["nodes"].as_array();
+        let Some(pr) = nodes.and_then(|nodes| {
+            nodes.iter().find(|pr| {
+                let owner = pr["headRepositoryOwner"]["login"]
+                    .as_str()
+                    .unwrap_or_default();
+                owner.eq_ignore_ascii_case(&repo.owner)
+                    && pr["headRepository"]["name"] == repo.name.as_str()
+            })
+        }) else {
+            return Ok(None);
+        };
+        let verdict = match pr["reviewDecision"].as_str() {
+            Some("APPROVED") => Verdict::Approved,
+            Some("CHANGES_REQUESTED") => Verdict::ChangesRequested,
+            _ => Verdict::Pending,
+        };
+        let threads = pr["reviewThreads"]["nodes"].as_array();
+        let unresolved = threads.map_or(0, |threads| {
+            threads
+                .iter()
+                .filter(|t| t["isResolved"].as_bool() == Some(false))
+                .count()
+        });
+        let mergeable = match pr["mergeable"].as_str() {
+            Some("MERGEABLE") => Some(true),
+            Some("CONFLICTING") => Some(false),
+            _ => None,
+        };
+        Ok(Some(Review {
+            number: pr["number"].as_u64().unwrap_or_default(),
+            url: pr["url"].as_str().unwrap_or_default().to_string(),
+            verdict,
+            unresolved,
+            mergeable,
+            draft: pr["isDraft"].as_bool().unwrap_or_default(),
+            checks: pr["commits"]["nodes"][0]["commit"]["statusCheckRollup"]["state"]
+                .as_str()
+                .and_then(Checks::parse),
+        }))
+    }
+
+    fn commit_checks(&self, repo: &Repo, token: &str, id: &str) -> EResult<Option<Checks>> {
+        let body = json!({
+            "query": CHECKS_QUERY,
+            "variables": { "owner": repo.owner, "name": repo.name, "oid": id },
+        });
+        let json = http::post(
+            self.budget(),
+            &Self::graphql(&repo.host),
+            &Self::headers(token),
+            body,
+        )?;
+        if let Some(err) = json["errors"][0]["message"].as_str() {
+            bail!("{err}");
+        }
+        let rollup = &json["data"]["repository"]["object"]["statusCheckRollup"];
+        Ok(rollup["state"].as_str().and_then(Checks::parse))
+    }
+}
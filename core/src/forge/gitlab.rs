@@ -0,0 +1,152 @@
+use super::{http, limit::Budget, Checks, Forge, PullRequest, Repo, Review, Verdict};
+use crate::{prelude::*, t};
+use color_eyre::eyre::eyre;
+use serde_json::json;
+
+pub struct GitLab;
+
+impl GitLab {
+    /// The merge requests endpoint of a project, which is addressed by its path
+    /// with the slashes encoded.
+    fn merge_requests(repo: &Repo) -> String {
+        let Repo { host, owner, name } = repo;
+        let project = format!("{owner}/{name}").replace('/', "%2F");
+        format!("https://{host}/api/v4/projects/{project}/merge_requests")
+    }
+
+    fn commit(repo: &Repo, id: &str) -> String {
+        let Repo { host, owner, name } = repo;
+        let project = format!("{owner}/{name}").replace('/', "%2F");
+        format!("https://{host}/api/v4/projects/{project}/repository/commits/{id}")
+    }
+}
+
+impl Forge for GitLab {
+    fn id(&self) -> &'static str {
+        "gitlab"
+    }
+
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
+
+    fn default_host(&self) -> Option<&'static str> {
+        Some("gitlab.com")
+    }
+
+    fn token_vars(&self) -> &'static [&'static str] {
+        &["GITLAB_TOKEN"]
+    }
+
+    fn token_url(&self, host: &str) -> String {
+        format!("https://{host}/-/user_settings/personal_access_tokens")
+    }
+
+    fn budget(&self) -> Budget {
+        Budget {
+            burst: 30,
+            per_minute: 120,
+        }
+    }
+
+    fn pull_request_name(&self) -> &'static str {
+        t!("merge request")
+    }
+
+    fn compare_url(&self, repo: &Repo, pr: &PullRequest) -> String {
+        let Repo { host, owner, name } = repo;
+        let base = format!("https://{host}/{owner}/{name}/-/merge_requests/new");
+        let params = [
+            ("merge_request[source_branch]", pr.head.as_str()),
+            ("merge_request[target_branch]", pr.base.as_str()),
+            ("merge_request[title]", pr.title.as_str()),
+            ("merge_request[description]", pr.body.as_str()),
+        ];
+        url::Url::parse_with_params(&base, params).map_or(base, String::from)
+    }
+
+    fn commit_url(&self, repo: &Repo, id: &str) -> String {
+        let Repo { host, owner, name } = repo;
+        format!("https://{host}/{owner}/{name}/-/commit/{id}")
+    }
+
+    fn create_pull_request(&self, repo: &Repo, token: &str, pr: &PullRequest) -> EResult<String> {
+        let body = json!({
+            "title": pr.title,
+            "description": pr.body,
+            "source_branch": pr.head,
+            "target_branch": pr.base,
+        });
+        let headers = [("PRIVATE-TOKEN", token.to_string())];
+        let json = http::post(self.budget(), &Self::merge_requests(repo), &headers, body)?;
+        json["web_url"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| eyre!("the response has no web_url"))
+    }
+
+    fn review(&self, repo: &Repo, token: &str, head: &str) -> EResult<Option<Review>> {
+        let headers = [("PRIVATE-TOKEN", token.to_string())];
+        let base = Self::merge_requests(repo);
+        let params = [("state", "opened"), ("source_branch", head)];
+        let list = url::Url::parse_with_params(&base, params)?;
+        let list = http::get(self.budget(), list.as_str(), &headers)?;
+        // one from a fork with a branch of the same name is another's
+        let Some(mr) = list.as_array().and_then(|list| {
+            list.iter()
+                .find(|mr| mr["source_project_id"] == mr["project_id"])
+        }) else {
+            return Ok(None);
+        };
+        let iid = mr["iid"].as_u64().unwrap_or_default();
+        // the list leaves out the pipeline
+        let mr = http::get(self.budget(), &format!("{base}/{iid}"), &headers)?;
+        let approvals = http::get(self.budget(), &format!("{base}/{iid}/approvals"), &headers)?;
+        let discussions = http::get(
+            self.budget(),
+            &format!("{base}/{iid}/discussions?per_page=100"),
+            &headers,
+        )?;
+        let verdict = if mr["detailed_merge_status"] == "requested_changes" {
+            Verdict::ChangesRequested
+        } else if approvals["approved_by"]
+            .as_array()
+            .is_some_and(|a| !a.is_empty())
+        {
+            Verdict::Approved
+        } else {
+            Verdict::Pending
+        };
+        let unresolved = discussions.as_array().map_or(0, |discussions| {
+            discussions
+                .iter()
+                .filter(|d| {
+                    d["notes"].as_array().is_some_and(|notes| {
+                        notes
+                            .iter()
+                            .any(|n| n["resolvable"] == true && n["resolved"] == false)
+                    })
+                })
+                .count()
+        });
+        Ok(Some(Review {
+            number: iid,
+            url: mr["web_url"].as_str().unwrap_or_default().to_string(),
+            verdict,
+            unresolved,
+            mergeable: mr["has_conflicts"].as_bool().map(|conflicts| !conflicts),
+            draft: mr["draft"].as_bool().unwrap_or_default(),
+            checks: mr["head_pipeline"]["status"]
+                .as_str()
+                .and_then(Checks::parse),
+        }))
+    }
+
+    fn commit_checks(&self, repo: &Repo, token: &str, id: &str) -> EResult<Option<Checks>> {
+        let headers = [("PRIVATE-TOKEN", token.to_string())];
+        let commit = http::get(self.budget(), &Self::commit(repo, id), &headers)?;
+        Ok(commit["last_pipeline"]["status"]
+            .as_str()
+            .and_then(Checks::parse))
+    }
+}
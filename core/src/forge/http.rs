@@ -0,0 +1,106 @@
+//! Blocking JSON requests to forge APIs, made off the UI thread, within the
+//! budget of the forge, see [`limit`]. Built without the `forge` feature, every
+//! request fails.
+
+use super::limit::{self, Budget};
+use crate::prelude::*;
+use color_eyre::eyre::{bail, Context};
+use serde_json::Value;
+#[cfg(feature = "forge")]
+use {
+    super::limit::Limited,
+    chrono::{DateTime, Local, TimeDelta},
+};
+
+pub type Headers = [(&'static str, String)];
+
+/// How long to back off when a forge says it is rate limited but not until when.
+#[cfg(feature = "forge")]
+const BACKOFF: TimeDelta = TimeDelta::seconds(60);
+
+pub fn get(budget: Budget, url: &str, headers: &Headers) -> EResult<Value> {
+    let host = acquire(budget, url)?;
+    tracing::debug!(url, "GET");
+    call(&host, "GET", url, headers, None)
+}
+
+pub fn post(budget: Budget, url: &str, headers: &Headers, body: Value) -> EResult<Value> {
+    let host = acquire(budget, url)?;
+    tracing::debug!(url, "POST");
+    call(&host, "POST", url, headers, Some(body))
+}
+
+/// Takes the call out of the budget of the host of `url`, which is returned.
+fn acquire(budget: Budget, url: &str) -> EResult<String> {
+    let parsed = url::Url::parse(url).wrap_err_with(|| format!("parse {url}"))?;
+    let host = parsed.host_str().unwrap_or_default().to_string();
+    limit::acquire(&host, budget).inspect_err(|limited| tracing::debug!("{limited}"))?;
+    Ok(host)
+}
+
+#[cfg(not(feature = "forge"))]
+fn call(_: &str, _: &str, url: &str, _: &Headers, _: Option<Value>) -> EResult<Value> {
+    bail!("grit was built without the forge feature, so {url} is not called")
+}
+
+#[cfg(feature = "forge")]
+fn call(
+    host: &str,
+    method: &str,
+    url: &str,
+    headers: &Headers,
+    body: Option<Value>,
+) -> EResult<Value> {
+    let mut req =
+        ureq::request(method, url).set("User-Agent", concat!("grit/", env!("CARGO_PKG_VERSION")));
+    for (name, value) in headers {
+        req = req.set(name, value);
+    }
+    let res = match body {
+        Some(body) => req.send_json(body),
+        None => req.call(),
+    };
+    match res {
+        Ok(resp) => resp.into_json().wrap_err("read the response"),
+        Err(ureq::Error::Status(code, resp)) => {
+            if let Some(until) = rate_limited(code, &resp) {
+                tracing::warn!(url, code, %until, "rate limited");
+                limit::back_off(host, until);
+                let host = host.to_string();
+                return Err(Limited { host, until }.into());
+            }
+            let text = resp.into_string().unwrap_or_default();
+            let message = serde_json::from_str::<Value>(&text)
+                .ok()
+                .and_then(|json| json["message"].as_str().map(String::from))
+                .unwrap_or(text);
+            bail!("{url} returned {code}: {}", message.trim());
+        }
+        // transport errors name the url already
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Until when the forge refuses calls, if `code` says it is rate limited.
+/// GitHub answers 403 for that as for a missing permission, which only its
+/// headers tell apart.
+#[cfg(feature = "forge")]
+fn rate_limited(code: u16, resp: &ureq::Response) -> Option<DateTime<Local>> {
+    let header = |names: &[&str]| names.iter().find_map(|name| resp.header(name));
+    let retry = header(&["retry-after"]).and_then(|secs| secs.trim().parse::<i64>().ok());
+    let spent = header(&["x-ratelimit-remaining", "ratelimit-remaining"]) == Some("0");
+    if code != 429 && !(code == 403 && (spent || retry.is_some())) {
+        return None;
+    }
+    let now = Local::now();
+    if let Some(secs) = retry {
+        return Some(now + TimeDelta::seconds(secs));
+    }
+    // GitHub and GitLab give the Unix time the budget is reset at
+    let reset = header(&["x-ratelimit-reset", "ratelimit-reset"])
+        .and_then(|epoch| epoch.trim().parse::<i64>().ok())
+        .and_then(|epoch| DateTime::from_timestamp(epoch, 0))
+        .map(|reset| reset.with_timezone(&Local))
+        .filter(|reset| *reset > now);
+    Some(reset.unwrap_or(now + BACKOFF))
+}
@@ -0,0 +1,108 @@
+//! A token bucket per host shared by every forge API call, so that scrolling
+//! through many branches spends the budget of a forge rather than getting
+//! refused by it. When a forge says it is rate limited anyway, calls to its
+//! host stop until the time it gives.
+
+use chrono::{DateTime, Local, TimeDelta};
+use std::{collections::BTreeMap, fmt, sync::Mutex, time::Instant};
+
+/// How many calls a forge takes in a burst, and how fast that refills.
+#[derive(Clone, Copy, Debug)]
+pub struct Budget {
+    pub burst: u32,
+    pub per_minute: u32,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self {
+            burst: 10,
+            per_minute: 30,
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+    /// Until when the forge refuses calls, as it said.
+    blocked: Option<DateTime<Local>>,
+}
+
+static BUCKETS: Mutex<BTreeMap<String, Bucket>> = Mutex::new(BTreeMap::new());
+
+/// A call not made, or refused by the forge, because of its rate limit.
+#[derive(Debug)]
+pub struct Limited {
+    pub host: String,
+    pub until: DateTime<Local>,
+}
+
+impl fmt::Display for Limited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let until = self.until.format("%H:%M");
+        write!(f, "{} is rate limited until {until}", self.host)
+    }
+}
+
+impl std::error::Error for Limited {}
+
+/// Takes a call out of the budget of `host`, failing if it is spent or the
+/// forge asked to back off.
+pub fn acquire(host: &str, budget: Budget) -> Result<(), Limited> {
+    let Ok(mut buckets) = BUCKETS.lock() else {
+        return Ok(());
+    };
+    let burst = f64::from(budget.burst.max(1));
+    let bucket = buckets.entry(host.to_string()).or_insert_with(|| Bucket {
+        tokens: burst,
+        refilled: Instant::now(),
+        blocked: None,
+    });
+    let now = Local::now();
+    if let Some(until) = bucket.blocked {
+        if until > now {
+            let host = host.to_string();
+            return Err(Limited { host, until });
+        }
+        bucket.blocked = None;
+    }
+    let rate = f64::from(budget.per_minute.max(1)) / 60.0;
+    let elapsed = bucket.refilled.elapsed().as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+    bucket.refilled = Instant::now();
+    if bucket.tokens < 1.0 {
+        let wait = ((1.0 - bucket.tokens) / rate).ceil() as i64;
+        let host = host.to_string();
+        let until = now + TimeDelta::seconds(wait);
+        return Err(Limited { host, until });
+    }
+    bucket.tokens -= 1.0;
+    Ok(())
+}
+
+/// Stops calls to `host` until `until`, as its forge asked.
+pub fn back_off(host: &str, until: DateTime<Local>) {
+    let Ok(mut buckets) = BUCKETS.lock() else {
+        return;
+    };
+    let bucket = buckets.entry(host.to_string()).or_insert_with(|| Bucket {
+        tokens: 0.0,
+        refilled: Instant::now(),
+        blocked: None,
+    });
+    bucket.tokens = 0.0;
+    bucket.blocked = Some(bucket.blocked.map_or(until, |b| b.max(until)));
+}
+
+/// The hosts whose forges asked to back off, with until when.
+pub fn blocked() -> Vec<(String, DateTime<Local>)> {
+    let Ok(buckets) = BUCKETS.lock() else {
+        return vec![];
+    };
+    let now = Local::now();
+    buckets
+        .iter()
+        .filter_map(|(host, bucket)| Some((host.clone(), bucket.blocked.filter(|u| *u > now)?)))
+        .collect()
+}
@@ -0,0 +1,259 @@
+//! Code hosting providers such as GitHub, GitLab and Gitea.
+//!
+//! Each provider implements [`Forge`] and is registered with a [`Registry`],
+//! which picks the provider serving a remote's host. Tokens are shared by all
+//! providers and live in the OS keychain, see [`token`].
+
+mod gitea;
+mod github;
+mod gitlab;
+mod http;
+pub mod limit;
+pub mod token;
+
+use crate::{config::ForgeConfig, prelude::*, t};
+use color_eyre::eyre::bail;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+/// A code hosting provider.
+pub trait Forge: Send + Sync {
+    /// The identifier used in the config, e.g. `github`.
+    fn id(&self) -> &'static str;
+
+    /// The name shown to users.
+    fn name(&self) -> &'static str;
+
+    /// The host of the public instance, if there is one.
+    fn default_host(&self) -> Option<&'static str>;
+
+    /// Environment variables that take precedence over a stored token.
+    fn token_vars(&self) -> &'static [&'static str];
+
+    /// The page where a token for `host` can be created.
+    fn token_url(&self, host: &str) -> String;
+
+    /// The forge's own command line tool, asked for its token when neither the
+    /// environment nor the keychain has one: the program and its arguments.
+    fn token_command(&self, _host: &str) -> Option<(&'static str, Vec<String>)> {
+        None
+    }
+
+    /// How many API calls the forge takes before it starts refusing them.
+    fn budget(&self) -> limit::Budget {
+        limit::Budget::default()
+    }
+
+    /// What the forge calls a pull request.
+    fn pull_request_name(&self) -> &'static str {
+        t!("pull request")
+    }
+
+    /// The page that starts a pull request, filled in as far as the forge allows.
+    fn compare_url(&self, repo: &Repo, pr: &PullRequest) -> String;
+
+    /// The page of the commit `id`.
+    fn commit_url(&self, repo: &Repo, id: &str) -> String;
+
+    /// Opens a pull request, returning its web page. This blocks on the network.
+    fn create_pull_request(&self, repo: &Repo, token: &str, pr: &PullRequest) -> EResult<String>;
+
+    /// The review state of the open pull request from `head`, if there is one.
+    /// This blocks on the network.
+    fn review(&self, repo: &Repo, token: &str, head: &str) -> EResult<Option<Review>>;
+
+    /// How the checks of the commit `id` went, if it has any. This blocks on
+    /// the network.
+    fn commit_checks(&self, repo: &Repo, token: &str, id: &str) -> EResult<Option<Checks>>;
+}
+
+/// A pull request to open, merging `head` into `base`.
+#[derive(Clone, Debug)]
+pub struct PullRequest {
+    pub title: String,
+    pub body: String,
+    pub head: String,
+    pub base: String,
+}
+
+impl PullRequest {
+    /// Drafts the title and description from the commits of the branch, oldest
+    /// first: a single commit lends its summary, otherwise the branch name is
+    /// used. The description lists the commits.
+    pub fn draft(head: &str, base: &str, summaries: &[String]) -> Self {
+        let title = match summaries {
+            [summary] => summary.clone(),
+            _ => {
+                let name = head
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or(head)
+                    .replace(['-', '_'], " ");
+                let mut chars = name.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+        };
+        let body = summaries
+            .iter()
+            .map(|s| format!("- {s}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Self {
+            title,
+            body,
+            head: head.to_string(),
+            base: base.to_string(),
+        }
+    }
+}
+
+/// Where the open pull request of a branch stands.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Review {
+    pub number: u64,
+    pub url: String,
+    pub verdict: Verdict,
+    /// Review threads that are not resolved yet.
+    pub unresolved: usize,
+    /// Whether it merges cleanly into its base, if the forge knows yet.
+    pub mergeable: Option<bool>,
+    /// Whether it is a draft, not ready for review yet.
+    #[serde(default)]
+    pub draft: bool,
+    /// How the checks of its head commit went, if it has any.
+    #[serde(default)]
+    pub checks: Option<Checks>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Verdict {
+    Approved,
+    ChangesRequested,
+    Pending,
+}
+
+/// How the CI checks of a commit went, all of them taken together.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Checks {
+    Passed,
+    Failed,
+    Running,
+}
+
+impl Checks {
+    /// Reads the combined state most forges report, e.g. `success` or
+    /// `PENDING`. States that say nothing about the outcome read as `None`.
+    fn parse(state: &str) -> Option<Self> {
+        match state.to_ascii_lowercase().as_str() {
+            "success" => Some(Checks::Passed),
+            "failure" | "failed" | "error" | "canceled" | "cancelled" => Some(Checks::Failed),
+            "pending"
+            | "expected"
+            | "running"
+            | "created"
+            | "preparing"
+            | "waiting_for_resource"
+            | "scheduled" => Some(Checks::Running),
+            _ => None,
+        }
+    }
+}
+
+/// A repository hosted on a forge, parsed from a remote URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Repo {
+    pub host: String,
+    pub owner: String,
+    pub name: String,
+}
+
+impl Repo {
+    /// Parses URLs such as `git@github.com:owner/name.git`.
+    pub fn parse(url: &str) -> Option<Self> {
+        let url = git_url_parse::GitUrl::parse(url).ok()?;
+        Some(Self {
+            host: url.host?,
+            owner: url.owner?,
+            name: url.name,
+        })
+    }
+}
+
+/// The known forges, and which hosts they serve.
+pub struct Registry {
+    forges: Vec<Arc<dyn Forge>>,
+    /// Hosts of self-hosted instances, mapped to a forge id.
+    hosts: BTreeMap<String, String>,
+}
+
+impl Registry {
+    /// The built-in forges, plus the hosts from the config.
+    pub fn new(config: &ForgeConfig) -> EResult<Self> {
+        let mut registry = Self {
+            forges: vec![],
+            hosts: BTreeMap::new(),
+        };
+        registry.register(Arc::new(github::GitHub));
+        registry.register(Arc::new(gitlab::GitLab));
+        registry.register(Arc::new(gitea::Gitea));
+        for (host, id) in &config.hosts {
+            if registry.get(id).is_none() {
+                let known = registry.forges.iter().map(|f| f.id());
+                let known = known.collect::<Vec<_>>().join(", ");
+                bail!("unknown forge {id} for {host}, expected one of {known}");
+            }
+            registry.hosts.insert(host.clone(), id.clone());
+        }
+        Ok(registry)
+    }
+
+    pub fn register(&mut self, forge: Arc<dyn Forge>) {
+        self.forges.push(forge);
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<dyn Forge>> {
+        self.forges.iter().find(|f| f.id() == id).cloned()
+    }
+
+    /// The forge serving `host`.
+    pub fn for_host(&self, host: &str) -> Option<Arc<dyn Forge>> {
+        match self.hosts.get(host) {
+            Some(id) => self.get(id),
+            None => self
+                .forges
+                .iter()
+                .find(|f| f.default_host() == Some(host))
+                .cloned(),
+        }
+    }
+
+    /// Every host a token can be configured for, with its forge: the public
+    /// instances, the configured ones and those of `remotes`.
+    pub fn accounts<'a>(
+        &self,
+        remotes: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<(Arc<dyn Forge>, String)> {
+        let mut hosts: BTreeSet<String> = self.hosts.keys().cloned().collect();
+        hosts.extend(
+            self.forges
+                .iter()
+                .filter_map(|f| f.default_host().map(String::from)),
+        );
+        hosts.extend(
+            remotes
+                .into_iter()
+                .filter_map(Repo::parse)
+                .map(|repo| repo.host),
+        );
+        hosts
+            .into_iter()
+            .filter_map(|host| Some((self.for_host(&host)?, host)))
+            .collect()
+    }
+}
@@ -0,0 +1,166 @@
+//! API tokens, stored per host in the OS keychain.
+//!
+//! A token in one of the forge's environment variables, such as `GITHUB_TOKEN`,
+//! wins over the stored one, so CI and one-off sessions need no setup. Without
+//! either, the forge's own tool is asked, such as `gh auth token`, so that
+//! being logged in there is enough.
+//!
+//! Under a config profile, tokens are stored for that profile alone, and the
+//! ones stored without a profile are used for hosts it has none for.
+
+use super::Forge;
+use crate::prelude::*;
+use color_eyre::eyre::Context;
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+/// The keychain service the tokens are stored under.
+const SERVICE: &str = "grit";
+
+/// The config profile in use, which tokens are stored for.
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// A token the forge's command line tool had, and the program.
+type Answer = Option<(&'static str, String)>;
+
+/// What the forges' tools answered, by host, so each is run once per session.
+static COMMANDS: Mutex<Option<HashMap<String, Answer>>> = Mutex::new(None);
+
+/// The tokens the background lookups found, by host, so that the keychain is
+/// asked once per host rather than once per lookup.
+static FOUND: Mutex<Option<HashMap<String, Option<String>>>> = Mutex::new(None);
+
+/// Stores tokens for the profile `name` from now on.
+pub fn use_profile(name: &str) {
+    let _ = PROFILE.set(name.to_string());
+}
+
+/// The config profile in use, if any.
+pub fn profile() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
+
+/// Where a token came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    Env(&'static str),
+    Keychain,
+    /// The forge's command line tool, such as `gh`.
+    Command(&'static str),
+}
+
+pub struct Token {
+    pub secret: String,
+    pub source: Source,
+}
+
+/// The token for `host`, from the environment, else the keychain, else the
+/// forge's command line tool.
+pub fn get(forge: &dyn Forge, host: &str) -> EResult<Option<Token>> {
+    for var in forge.token_vars() {
+        if let Some(secret) = std::env::var(var).ok().filter(|s| !s.is_empty()) {
+            return Ok(Some(Token {
+                secret,
+                source: Source::Env(var),
+            }));
+        }
+    }
+    let mut entries = vec![entry(host)?];
+    if PROFILE.get().is_some() {
+        entries.push(keyring::Entry::new(SERVICE, host).wrap_err("open the keychain")?);
+    }
+    for entry in entries {
+        match entry.get_password() {
+            Ok(secret) => {
+                return Ok(Some(Token {
+                    secret,
+                    source: Source::Keychain,
+                }))
+            }
+            Err(keyring::Error::NoEntry) => {}
+            Err(err) => return Err(err).wrap_err_with(|| format!("read the token for {host}")),
+        }
+    }
+    Ok(command(forge, host).map(|(program, secret)| Token {
+        secret,
+        source: Source::Command(program),
+    }))
+}
+
+/// The secret of the token for `host`, as [`get`] finds it, asked once per
+/// host until it is stored or deleted. Meant for the threads that look things
+/// up on the forge, as the keychain may take a while to answer.
+pub fn cached(forge: &dyn Forge, host: &str) -> EResult<Option<String>> {
+    let mut found = FOUND.lock().unwrap_or_else(|e| e.into_inner());
+    let found = found.get_or_insert_with(HashMap::new);
+    if let Some(secret) = found.get(host) {
+        return Ok(secret.clone());
+    }
+    let secret = get(forge, host)?.map(|token| token.secret);
+    found.insert(host.to_string(), secret.clone());
+    Ok(secret)
+}
+
+/// Forgets what [`cached`] found for `host`.
+fn forget(host: &str) {
+    let mut found = FOUND.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(found) = found.as_mut() {
+        found.remove(host);
+    }
+}
+
+/// The token the forge's command line tool has for `host`, if it is installed
+/// and logged in there.
+fn command(forge: &dyn Forge, host: &str) -> Answer {
+    let mut commands = COMMANDS.lock().unwrap_or_else(|e| e.into_inner());
+    let commands = commands.get_or_insert_with(HashMap::new);
+    if let Some(answer) = commands.get(host) {
+        return answer.clone();
+    }
+    let answer = forge.token_command(host).and_then(|(program, args)| {
+        let out = std::process::Command::new(program)
+            .args(args)
+            .stdin(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()
+            .filter(|out| out.status.success())?;
+        let secret = String::from_utf8(out.stdout).ok()?.trim().to_string();
+        (!secret.is_empty()).then_some((program, secret))
+    });
+    tracing::debug!(
+        host,
+        found = answer.is_some(),
+        "asked the forge's tool for a token"
+    );
+    commands.insert(host.to_string(), answer.clone());
+    answer
+}
+
+/// Stores the token for `host` in the keychain.
+pub fn store(host: &str, secret: &str) -> EResult<()> {
+    forget(host);
+    entry(host)?
+        .set_password(secret)
+        .wrap_err_with(|| format!("store the token for {host}"))
+}
+
+/// Removes the token for `host` from the keychain, if there is one.
+pub fn delete(host: &str) -> EResult<()> {
+    forget(host);
+    match entry(host)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(err).wrap_err_with(|| format!("delete the token for {host}")),
+    }
+}
+
+/// The keychain entry of the token for `host`, under the profile in use.
+fn entry(host: &str) -> EResult<keyring::Entry> {
+    let user = match PROFILE.get() {
+        Some(profile) => format!("{profile}/{host}"),
+        None => host.to_string(),
+    };
+    keyring::Entry::new(SERVICE, &user).wrap_err("open the keychain")
+}
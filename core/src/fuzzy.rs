@@ -0,0 +1,46 @@
+//! A small subsequence matcher used to pick branches from partial names.
+
+/// Scores how well `pattern` matches `candidate`, or `None` if the characters of
+/// `pattern` do not appear in order in `candidate`. Matching is case-insensitive.
+/// Consecutive matches and matches at word boundaries score higher, and shorter
+/// candidates win ties.
+pub fn score(pattern: &str, candidate: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut idx = 0;
+    for pc in pattern.chars().map(|c| c.to_ascii_lowercase()) {
+        let pos = (idx..chars.len()).find(|&i| chars[i].to_ascii_lowercase() == pc)?;
+        score += 1;
+        if prev.is_some_and(|p| p + 1 == pos) {
+            score += 5;
+        }
+        if pos == 0 || matches!(chars[pos - 1], '/' | '-' | '_' | '.') {
+            score += 3;
+        }
+        prev = Some(pos);
+        idx = pos + 1;
+    }
+    Some(score * 100 - chars.len() as i64)
+}
+
+/// Returns the index of the best match for `pattern` among `candidates`.
+pub fn best<'a>(pattern: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<usize> {
+    candidates
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, c)| score(pattern, c).map(|s| (i, s)))
+        .max_by_key(|(i, s)| (*s, std::cmp::Reverse(*i)))
+        .map(|(i, _)| i)
+}
+
+/// Returns the indices of the candidates that match `pattern`, best first.
+pub fn rank<'a>(pattern: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<usize> {
+    let mut scored: Vec<(usize, i64)> = candidates
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, c)| score(pattern, c).map(|s| (i, s)))
+        .collect();
+    scored.sort_by_key(|(i, s)| (std::cmp::Reverse(*s), *i));
+    scored.into_iter().map(|(i, _)| i).collect()
+}
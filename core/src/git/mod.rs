@@ -1,5 +0,0 @@
-mod branch;
-mod repo;
-
-pub use branch::Branch;
-pub use repo::Repository;
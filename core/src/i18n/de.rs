@@ -0,0 +1,575 @@
+pub fn lookup(key: &str) -> Option<&'static str> {
+    let val = match key {
+        "j/k/gg/G: move s: sort ({sort})" => "j/k/gg/G: bewegen s: sortieren ({sort})",
+        "name asc" => "Name aufst.",
+        "name desc" => "Name abst.",
+        "date asc" => "Datum aufst.",
+        "date desc" => "Datum abst.",
+        "q: quit  space: menu  ?: help" => "q: beenden  Leertaste: Menü  ?: Hilfe",
+        "quit" => "beenden",
+        "clear selection" => "Auswahl aufheben",
+        "next branch" => "nächster Branch",
+        "previous branch" => "vorheriger Branch",
+        "first branch" => "erster Branch",
+        "last branch" => "letzter Branch",
+        "cycle sort" => "Sortierung wechseln",
+        "branch" => "Branch",
+        "remote" => "Remote",
+        "view" => "Ansicht",
+        "git" => "Git",
+        "leader" => "Menü",
+        "sort by…" => "sortieren nach…",
+        "Sort by" => "Sortieren nach",
+        "enter: ok  esc: cancel" => "Enter: OK  Esc: abbrechen",
+        "ctrl-s: save  esc: cancel" => "Strg-s: speichern  Esc: abbrechen",
+        "enter: select  esc: cancel" => "Enter: auswählen  Esc: abbrechen",
+        "y: yes  n: no" => "y: ja  n: nein",
+        "notifications" => "Benachrichtigungen",
+        "Notifications" => "Benachrichtigungen",
+        "no notifications yet" => "noch keine Benachrichtigungen",
+        "esc: close" => "Esc: schließen",
+        "sorted by {sort}" => "sortiert nach {sort}",
+        "jobs" => "Aufgaben",
+        "Jobs" => "Aufgaben",
+        "fetch all remotes" => "alle Remotes abrufen",
+        "queued" => "wartend",
+        "running" => "läuft",
+        "cancelling" => "bricht ab",
+        "done" => "fertig",
+        "failed" => "fehlgeschlagen",
+        "cancelled" => "abgebrochen",
+        "x: cancel  esc: close" => "x: abbrechen  Esc: schließen",
+        "jobs: {running} running, {queued} queued" => {
+            "Aufgaben: {running} laufend, {queued} wartend"
+        }
+        "{job} cancelled" => "{job} abgebrochen",
+        "loading…" => "lädt…",
+        "{position}/{shown} branches" => "{position}/{shown} Branches",
+        "({hidden} hidden)" => "({hidden} ausgeblendet)",
+        "scroll details down" => "Details nach unten",
+        "scroll details up" => "Details nach oben",
+        "Branches" => "Branches",
+        "Details" => "Details",
+        "<none>" => "<keiner>",
+        "copied {name}" => "{name} kopiert",
+        "create branch" => "Branch erstellen",
+        "New branch from {from}" => "Neuer Branch von {from}",
+        "Branch {field}" => "Branch {field}",
+        "created branch {name}" => "Branch {name} erstellt",
+        "expected s/<regex>/<replacement>/" => "erwartet s/<regex>/<ersetzung>/",
+        "expected prefix:<text>, suffix:<text> or s/<regex>/<replacement>/" => {
+            "erwartet prefix:<text>, suffix:<text> oder s/<regex>/<ersetzung>/"
+        }
+        "already exists" => "existiert bereits",
+        "duplicate name" => "doppelter Name",
+        "mark branch" => "Branch markieren",
+        "clear marks" => "Markierungen aufheben",
+        "rename marked branches" => "markierte Branches umbenennen",
+        "Rename pattern: prefix:<text>, suffix:<text> or s/<regex>/<new>/" => {
+            "Umbenennungsmuster: prefix:<text>, suffix:<text> oder s/<regex>/<neu>/"
+        }
+        "the pattern does not change any local branch name" => {
+            "das Muster ändert keinen lokalen Branch-Namen"
+        }
+        "Cannot rename" => "Umbenennen nicht möglich",
+        "Rename branches?" => "Branches umbenennen?",
+        "renamed {count} branches" => "{count} Branches umbenannt",
+        "({marked} marked)" => "({marked} markiert)",
+        "aborted" => "abgebrochen",
+        "user command" => "Benutzerbefehl",
+        "invalid key for command: {key}" => "ungültige Taste für Befehl: {key}",
+        "{name} exited with {status}" => "{name} beendet mit {status}",
+        "run command…" => "Befehl ausführen…",
+        "Command, e.g. git log -3" => "Befehl, z. B. git log -3",
+        "exited with {status}" => "beendet mit {status}",
+        "running…" => "läuft…",
+        "j/k: scroll  esc: close" => "j/k: scrollen  esc: schließen",
+        "unknown command: {cmd}" => "unbekannter Befehl: {cmd}",
+        "no command given" => "kein Befehl angegeben",
+        "unterminated quote" => "nicht geschlossenes Anführungszeichen",
+        "remotes" => "Remotes",
+        "prune: on" => "prune: an",
+        "prune: off" => "prune: aus",
+        "prune: default" => "prune: Standard",
+        "no fetch refspecs" => "keine Fetch-Refspecs",
+        "Remotes" => "Remotes",
+        "e: edit refspecs  p: toggle prune  esc: close" => {
+            "e: Refspecs bearbeiten  p: prune umschalten  esc: schließen"
+        }
+        "updated refspecs of {remote}" => "Refspecs von {remote} aktualisiert",
+        "Fetch refspecs of {remote}" => "Fetch-Refspecs von {remote}",
+        "edit note on tip commit" => "Notiz am obersten Commit bearbeiten",
+        "Note on {commit} (ctrl-s: save)" => "Notiz zu {commit} (ctrl-s: speichern)",
+        "authors: {authors}" => "Autoren: {authors}",
+        "color commits by author" => "Commits nach Autor färben",
+        "author colors" => "Autorenfarben",
+        "no commits loaded" => "keine Commits geladen",
+        "Authors" => "Autoren",
+        "activity column" => "Aktivitätsspalte",
+        "statistics" => "Statistik",
+        "statistics were not computed" => "Statistik wurde nicht berechnet",
+        "Statistics" => "Statistik",
+        "computing…" => "wird berechnet…",
+        "local" => "lokal",
+        "{total} ({stale} stale)" => "{total} ({stale} veraltet)",
+        "stale: no commits in {days} days" => "veraltet: keine Commits seit {days} Tagen",
+        "Divergence from {base} (ahead/behind)" => "Abweichung von {base} (voraus/zurück)",
+        "Divergence: no default branch found" => "Abweichung: kein Standard-Branch gefunden",
+        "Commits per week, last {weeks} weeks" => "Commits pro Woche, letzte {weeks} Wochen",
+        "Top authors" => "Top-Autoren",
+        "help" => "Hilfe",
+        "Keys" => "Tasten",
+        "Welcome to grit! Press ? to see all keys, or space for the menu." => {
+            "Willkommen bei grit! Drücke ? für alle Tasten oder Leertaste für das Menü."
+        }
+        "No branches yet." => "Noch keine Branches.",
+        "Make a first commit, then press n to branch off it." => {
+            "Erstelle einen ersten Commit und drücke dann n, um davon abzuzweigen."
+        }
+        "This is the only branch so far." => "Dies ist bisher der einzige Branch.",
+        "n: create a branch from it" => "n: davon einen Branch erstellen",
+        "No remotes configured." => "Keine Remotes konfiguriert.",
+        "commit or stash your local changes first" => {
+            "Lokale Änderungen zuerst committen oder stashen"
+        }
+        "check your credentials or ssh agent" => "Zugangsdaten oder SSH-Agent prüfen",
+        "check your connection to the remote" => "Verbindung zum Remote prüfen",
+        "Something went wrong. A crash report was written to {report}" => {
+            "Etwas ist schiefgelaufen. Ein Absturzbericht wurde unter {report} gespeichert"
+        }
+        "tracking & up to date" => "verfolgt & aktuell",
+        "ahead" => "voraus",
+        "behind" => "zurück",
+        "diverged" => "auseinandergelaufen",
+        "no upstream" => "kein Upstream",
+        "group by upstream status" => "nach Upstream-Status gruppieren",
+        "cherry-pick" => "Cherry-Pick",
+        "revert" => "Revert",
+        "export patches" => "Patches exportieren",
+        "diffstat" => "Diffstat",
+        "{count} selected  enter: show  y: copy  Y: copy as  w: worktree  v: visual  c: cherry-pick  r: revert  e: export  d: diffstat  f: fixup  a: autosquash  esc: done" => "{count} ausgewählt  enter: anzeigen  y: kopieren  Y: kopieren als  w: Worktree  v: visuell  c: cherry-pick  r: revert  e: exportieren  d: diffstat  f: fixup  a: autosquash  esc: fertig",
+        "diffstat of {count} commits" => "Diffstat von {count} Commits",
+        "Export {count} patches to directory" => "{count} Patches in Verzeichnis exportieren",
+        "{op} {count} commits on the checked out branch?" => "{op}: {count} Commits auf dem ausgecheckten Branch?",
+        "select commits" => "Commits auswählen",
+        "only local branches can be squashed" => "nur lokale Branches können zusammengefasst werden",
+        "nothing to squash, {name} has {count} commits since {onto}" => "nichts zusammenzufassen, {name} hat {count} Commits seit {onto}",
+        "Squash {count} commits of {name} (ctrl-s: save)" => "{count} Commits von {name} zusammenfassen (Strg-s: speichern)",
+        "squash aborted, the message is empty" => "Zusammenfassen abgebrochen, die Nachricht ist leer",
+        "squashed {count} commits of {name}" => "{count} Commits von {name} zusammengefasst",
+        "squash into one commit" => "zu einem Commit zusammenfassen",
+        "work tree → {branch}" => "Arbeitsverzeichnis → {branch}",
+        "diff work tree against branch" => "Arbeitsverzeichnis mit Branch vergleichen",
+        "Stash message (optional)" => "Stash-Nachricht (optional)",
+        "stash changes" => "Änderungen stashen",
+        "updated the upstream status of {count} branches" => "Upstream-Status von {count} Branches aktualisiert",
+        "hosting accounts" => "Hosting-Konten",
+        "token from ${var}" => "Token aus ${var}",
+        "token in keychain" => "Token im Schlüsselbund",
+        "no token" => "kein Token",
+        "Hosting accounts" => "Hosting-Konten",
+        "enter: set token  d: delete token  esc: close" => "enter: Token setzen  d: Token löschen  esc: schließen",
+        "{forge} token for {host}" => "{forge}-Token für {host}",
+        "Delete the token for {host} from the keychain?" => "Token für {host} aus dem Schlüsselbund löschen?",
+        "Delete token" => "Token löschen",
+        "the token comes from ${var}, unset it to use the keychain" => "das Token stammt aus ${var}, entferne die Variable, um den Schlüsselbund zu nutzen",
+        "stored the token for {host}" => "Token für {host} gespeichert",
+        "deleted the token for {host}" => "Token für {host} gelöscht",
+        "pull request" => "Pull-Request",
+        "merge request" => "Merge-Request",
+        "create pull request" => "Pull-Request erstellen",
+        "{name} has no upstream, push it before opening a pull request" => "{name} hat keinen Upstream, pushe den Branch, bevor du einen Pull-Request öffnest",
+        "{remote} is not hosted on a known forge" => "{remote} liegt auf keiner bekannten Forge",
+        "no forge is known for {host}, add it to [forge.hosts]" => "für {host} ist keine Forge bekannt, trage den Host unter [forge.hosts] ein",
+        "no default branch to open a pull request against" => "kein Standard-Branch als Ziel für einen Pull-Request",
+        "{name} is the default branch" => "{name} ist der Standard-Branch",
+        "opened {url}" => "{url} geöffnet",
+        "New {kind} from {head} into {base} (ctrl-s: create)" => "Neuer {kind} von {head} nach {base} (ctrl-s: erstellen)",
+        "pull request aborted, the title is empty" => "Pull-Request abgebrochen, der Titel ist leer",
+        "create {kind}" => "{kind} erstellen",
+        "created {url}" => "{url} erstellt",
+        "select a local branch to open a pull request" => "wähle einen lokalen Branch, um einen Pull-Request zu öffnen",
+        "the remote of {upstream} is gone" => "das Remote von {upstream} existiert nicht mehr",
+        "looking up the pull request…" => "suche den Pull-Request…",
+        "pull request unavailable: {err}" => "Pull-Request nicht verfügbar: {err}",
+        "approved" => "genehmigt",
+        "changes requested" => "Änderungen angefordert",
+        "awaiting review" => "wartet auf Review",
+        "{count} unresolved" => "{count} ungelöst",
+        "conflicts with base" => "Konflikte mit der Basis",
+        "mergeable" => "mergebar",
+        "as of {time}" => "Stand {time}",
+        "saved the screen to {path}" => "Bildschirm nach {path} gespeichert",
+        "Save the screen to (.txt, .ans or .html)" => "Bildschirm speichern unter (.txt, .ans oder .html)",
+        "save screen to a file…" => "Bildschirm in Datei speichern…",
+        "created {names}" => "{names} erstellt",
+        "deleted {names}" => "{names} gelöscht",
+        "renamed {old} to {new}" => "{old} in {new} umbenannt",
+        "checked out {name} (was {was})" => "{name} ausgecheckt (vorher {was})",
+        "checked out {name}" => "{name} ausgecheckt",
+        "ran {job}" => "{job} ausgeführt",
+        "ran {job} ({count} times)" => "{job} ausgeführt ({count}-mal)",
+        "ctrl-enter: save  esc: cancel" => "Strg-Enter: speichern  Esc: abbrechen",
+        "branches" => "Branches",
+        "jump to branches by letter" => "zu Branches per Buchstabe springen",
+        "no branch starts with {letter}" => "kein Branch beginnt mit {letter}",
+        "jump: type the first letter of a branch  esc: done" => "Springen: Anfangsbuchstaben eines Branches tippen  Esc: fertig",
+        "set mark…" => "Markierung setzen…",
+        "jump to mark…" => "zu Markierung springen…",
+        "marks" => "Markierungen",
+        "Marks" => "Markierungen",
+        "enter: jump  d: delete  esc: close" => "Enter: springen  d: löschen  Esc: schließen",
+        "no marks yet, set one with m and a letter" => "noch keine Markierungen, m und ein Buchstabe setzt eine",
+        "marked {branch} as {mark}" => "{branch} als {mark} markiert",
+        "no mark {mark}" => "keine Markierung {mark}",
+        "{branch} is not in the list" => "{branch} ist nicht in der Liste",
+        "mark: type a letter for the branch" => "Markieren: Buchstaben für den Branch tippen",
+        "jump to mark: type its letter" => "zu Markierung springen: ihren Buchstaben tippen",
+        "open in {tool}" => "in {tool} öffnen",
+        "open in" => "öffnen in",
+        "show commits by author…" => "Commits nach Autor anzeigen…",
+        "all authors" => "alle Autoren",
+        "Show commits by" => "Commits anzeigen von",
+        "{author}: {shown} of {total} commits  esc: show all" => "{author}: {shown} von {total} Commits  Esc: alle anzeigen",
+        "history takes one path" => "history nimmt einen Pfad",
+        "history needs a path" => "history braucht einen Pfad",
+        "renamed from {old} to {new}" => "umbenannt von {old} in {new}",
+        "no commits changed {path}" => "keine Commits ändern {path}",
+        "history of a file…" => "Verlauf einer Datei…",
+        "History of file (from the top of the work tree)" => "Verlauf der Datei (vom Anfang des Arbeitsverzeichnisses)",
+        "History of {path} on {branch}" => "Verlauf von {path} auf {branch}",
+        "enter: diff  esc: back" => "Enter: Diff  Esc: zurück",
+        "find commits adding or removing a string…" => "Commits finden, die eine Zeichenkette hinzufügen oder entfernen…",
+        "Find commits adding or removing" => "Commits finden, die hinzufügen oder entfernen",
+        "no commits add or remove {needle}" => "keine Commits fügen {needle} hinzu oder entfernen es",
+        "searching… {walked} commits, {found} found" => "suche… {walked} Commits, {found} gefunden",
+        "{found} found" => "{found} gefunden",
+        "Commits adding or removing {needle} on {branch}" => "Commits, die {needle} auf {branch} hinzufügen oder entfernen",
+        "ignoring whitespace" => "Leerraum ignoriert",
+        "whole files" => "ganze Dateien",
+        "{lines} lines of context" => "{lines} Zeilen Kontext",
+        "w: whitespace  c: context  j/k: scroll  esc: close" => "w: Leerraum  c: Kontext  j/k: scrollen  esc: schließen",
+        "x: unfold  w: whitespace  c: context  j/k: scroll  esc: close" => "x: aufklappen  w: Leerraum  c: Kontext  j/k: scrollen  esc: schließen",
+        "binary file changed" => "Binärdatei geändert",
+        "… {count} more lines of this file, too many to show — press x to load" => "… {count} weitere Zeilen dieser Datei, zu viele zum Anzeigen — x lädt sie",
+        "o: submodule commits  w: whitespace  c: context  esc: close" => "o: Submodul-Commits  w: Leerraum  c: Kontext  esc: schließen",
+        "Subproject commit {old} → {new}" => "Subprojekt-Commit {old} → {new}",
+        "the submodule at {path} is not initialized" => "das Submodul in {path} ist nicht initialisiert",
+        "go to the branch's worktree" => "zum Arbeitsverzeichnis des Branches wechseln",
+        "{branch} is not checked out in another worktree" => "{branch} ist in keinem anderen Arbeitsverzeichnis ausgecheckt",
+        "the repository changed, refreshing" => "das Repository hat sich geändert, wird neu geladen",
+        "delete branches…" => "Branches löschen…",
+        "push branch" => "Branch pushen",
+        "nothing to delete, the checked out, protected and remote branches are kept" => "nichts zu löschen, der ausgecheckte Branch, geschützte und entfernte Branches bleiben",
+        "Delete {count} branches?" => "{count} Branches löschen?",
+        "delete {branch}" => "{branch} löschen",
+        "delete {count} branches" => "{count} Branches löschen",
+        "only local branches can be pushed" => "nur lokale Branches können gepusht werden",
+        "push {branch}" => "{branch} pushen",
+        " deleting…" => " wird gelöscht…",
+        " ↑ pushing…" => " ↑ wird gepusht…",
+        "work tree status" => "Status des Arbeitsverzeichnisses",
+        "status" => "Status",
+        "{path} is not tracked, there is no diff" => "{path} ist nicht versioniert, es gibt keinen Diff",
+        "nothing to commit" => "nichts zu committen",
+        "enter: diff  s: stage hunks  d: discard  i: hide ignored  esc: back" => "Enter: Diff  s: Hunks stagen  d: verwerfen  i: ignorierte ausblenden  Esc: zurück",
+        "enter: diff  s: stage hunks  d: discard  i: show ignored  esc: back" => "Enter: Diff  s: Hunks stagen  d: verwerfen  i: ignorierte zeigen  Esc: zurück",
+        "Work tree status" => "Status des Arbeitsverzeichnisses",
+        "{path} is ignored, there is nothing to stage" => "{path} wird ignoriert, es gibt nichts zu stagen",
+        "nothing staged in {path}" => "nichts gestagt in {path}",
+        "nothing to stage in {path}" => "nichts zu stagen in {path}",
+        "Staged changes to {path}" => "Gestagte Änderungen an {path}",
+        "Unstaged changes to {path}" => "Nicht gestagte Änderungen an {path}",
+        "s: unstage hunk  tab: unstaged  esc: back" => "s: Hunk unstagen  Tab: nicht gestagt  Esc: zurück",
+        "s: stage hunk  tab: staged  esc: back" => "s: Hunk stagen  Tab: gestagt  Esc: zurück",
+        "discarded the changes to {path}, u brings them back" => "Änderungen an {path} verworfen, u holt sie zurück",
+        "{path} is ignored, there is nothing to discard" => "{path} wird ignoriert, es gibt nichts zu verwerfen",
+        "Delete {path}?" => "{path} löschen?",
+        "It is untracked. It is stashed first, so u on this screen brings it back." => "Die Datei ist nicht versioniert. Sie wird zuerst gestasht, u auf dieser Ansicht holt sie zurück.",
+        "Discard the changes to {path}?" => "Änderungen an {path} verwerfen?",
+        "Staged and unstaged changes both go. They are stashed first, so u on this screen brings them back." => "Gestagte und nicht gestagte Änderungen gehen verloren. Sie werden zuerst gestasht, u auf dieser Ansicht holt sie zurück.",
+        "nothing was discarded" => "nichts wurde verworfen",
+        "restored the changes to {path}" => "Änderungen an {path} wiederhergestellt",
+        "fixup" => "Fixup",
+        "autosquash" => "Autosquash",
+        "{op} works on the checked out branch, check out {branch} first" => "{op} geht nur auf dem ausgecheckten Branch, erst {branch} auschecken",
+        "nothing is staged to fix the commit up with" => "nichts gestagt, um den Commit zu korrigieren",
+        "Rebase {branch} from below the selected commits, folding fixups into their targets? Its commits get new ids." => "{branch} ab unterhalb der ausgewählten Commits rebasen und Fixups in ihre Ziele falten? Die Commits bekommen neue IDs.",
+        "{name}: signing commits…" => "{name}: Commits werden signiert…",
+        "the subject is {len} characters, over {max}" => "der Betreff hat {len} Zeichen, mehr als {max}",
+        "the subject does not match {pattern}" => "der Betreff passt nicht zu {pattern}",
+        "the subject is not followed by a blank line" => "nach dem Betreff fehlt eine Leerzeile",
+        "line {line} is {len} characters, over {max}" => "Zeile {line} hat {len} Zeichen, mehr als {max}",
+        " (+{more} more)" => " (+{more} weitere)",
+        "commit staged changes…" => "gestagte Änderungen committen…",
+        "nothing is staged to commit" => "nichts gestagt zum Committen",
+        "Commit message" => "Commit-Nachricht",
+        "commit aborted, the message is empty" => "Commit abgebrochen, die Nachricht ist leer",
+        "not committed, the message has {count} problems" => "nicht committet, die Nachricht hat {count} Probleme",
+        "commit" => "Commit",
+        "settings" => "Einstellungen",
+        "on" => "an",
+        "off" => "aus",
+        "unset" => "nicht gesetzt",
+        "Keymap conflicts" => "Konflikte der Tastenbelegung",
+        "none" => "keine",
+        "Settings" => "Einstellungen",
+        "enter: change  d: default  esc: close" => "Enter: ändern  d: Standard  Esc: schließen",
+        "not a number" => "keine Zahl",
+        "{key} (empty: default)" => "{key} (leer: Standard)",
+        "no config file to save {key} to" => "keine Konfigurationsdatei, um {key} zu speichern",
+        "{keys}: {winner} replaces {lost}" => "{keys}: {winner} ersetzt {lost}",
+        ", which has no other keys" => ", das keine anderen Tasten hat",
+        "{keys}: {action} never runs, {longer} ({winner}) starts the same way" => "{keys}: {action} wird nie ausgeführt, {longer} ({winner}) beginnt genauso",
+        "{keys}: {action} only runs after a pause, {longer} ({winner}) starts the same way" => "{keys}: {action} wird erst nach einer Pause ausgeführt, {longer} ({winner}) beginnt genauso",
+        "keymap conflicts" => "Konflikte der Tastenbelegung",
+        "no key bindings conflict" => "keine Konflikte in der Tastenbelegung",
+        "{count} key bindings conflict, see space v k" => "{count} Tastenbelegungen stehen im Konflikt, siehe space v k",
+        "{branch} is protected" => "{branch} ist geschützt",
+        "Hosting accounts of profile {profile}" => "Hosting-Konten des Profils {profile}",
+        "{name} only points at another branch" => "{name} verweist nur auf einen anderen Branch",
+        "{name} is checked out already" => "{name} ist bereits ausgecheckt",
+        "checked out {local} tracking {name}" => "{local} ausgecheckt, folgt {name}",
+        "could not check out {name}: uncommitted changes" => "{name} konnte nicht ausgecheckt werden: nicht committete Änderungen",
+        "could not check out {name}" => "{name} konnte nicht ausgecheckt werden",
+        "check out branch" => "Branch auschecken",
+        "the host key of the remote is unknown or has changed" => "der Host-Schlüssel des Remotes ist unbekannt oder hat sich geändert",
+        "{host} is not in known_hosts yet. Its key is\\n{fingerprint}\\n\\nTrust it and add it to known_hosts?" => "{host} ist noch nicht in known_hosts. Sein Schlüssel ist\\n{fingerprint}\\n\\nVertrauen und zu known_hosts hinzufügen?",
+        "Unknown host" => "Unbekannter Host",
+        "delete branch…" => "Branch löschen…",
+        "{branch} (not merged)" => "{branch} (nicht gemergt)",
+        "{branch} is checked out, check out another branch first" => "{branch} ist ausgecheckt, zuerst einen anderen Branch auschecken",
+        "{branch} is checked out in {path}" => "{branch} ist in {path} ausgecheckt",
+        "Delete {branch}?" => "{branch} löschen?",
+        "Its commits are merged, nothing is lost." => "Die Commits sind gemergt, nichts geht verloren.",
+        "Force delete {branch}?" => "{branch} trotzdem löschen?",
+        "{branch} is not fully merged. Its commits will only be kept in the reflog, for a while." => "{branch} ist nicht vollständig gemergt. Die Commits bleiben nur eine Weile im Reflog erhalten.",
+        "shallow" => "flach",
+        "partial" => "partiell",
+        "partial: {filter}" => "partiell: {filter}",
+        "history truncated, the clone is shallow  space r d: deepen" => "Historie abgeschnitten, der Klon ist flach  space r d: vertiefen",
+        "the clone is not shallow" => "der Klon ist nicht flach",
+        "Deepen the history by how many commits (empty: all)" => "Historie um wie viele Commits vertiefen (leer: alle)",
+        "deepen the history by {count} commits" => "Historie um {count} Commits vertiefen",
+        "fetch all of the history" => "gesamte Historie holen",
+        "deepen shallow history…" => "flache Historie vertiefen…",
+        "replaced history" => "ersetzte Historie",
+        "original history" => "ursprüngliche Historie",
+        "replaces {id}" => "ersetzt {id}",
+        "grafted" => "gepfropft",
+        "no history was replaced in this repository" => "in diesem Repository wurde keine Historie ersetzt",
+        "show replaced or original history" => "ersetzte oder ursprüngliche Historie zeigen",
+        "  enter: keep  esc: clear" => "  Enter: behalten  Esc: leeren",
+        "Branches matching {query}" => "Branches passend zu {query}",
+        "filter branches…" => "Branches filtern…",
+        "Refs" => "Refs",
+        "refs" => "Refs",
+        "enter: show  b: branch from ref  esc: back" => "Enter: anzeigen  b: Branch aus Ref  Esc: zurück",
+        "no refs" => "keine Refs",
+        "Peeled" => "Commit",
+        "{name} does not point at a commit" => "{name} zeigt nicht auf einen Commit",
+        "browse refs" => "Refs durchsuchen",
+        "divergence" => "Abweichung",
+        "{remote}/HEAD points at {remote}/{branch}" => "{remote}/HEAD zeigt auf {remote}/{branch}",
+        "select a branch of the remote to set its HEAD" => "Branch des Remotes auswählen, um seinen HEAD zu setzen",
+        "{remote} has no branches" => "{remote} hat keine Branches",
+        "Point {remote}/HEAD at" => "{remote}/HEAD zeigen auf",
+        "gone" => "weg",
+        "set remote HEAD…" => "Remote-HEAD setzen…",
+        "restack" => "neu stapeln",
+        "turn on the stack view first  space v t" => "zuerst die Stapelansicht einschalten  space v t",
+        "no branches are stacked on {branch}" => "auf {branch} sind keine Branches gestapelt",
+        "Rebase {branches} onto the branches they are stacked on? Their commits get new ids." => "{branches} auf die Branches rebasen, auf denen sie gestapelt sind? Ihre Commits erhalten neue IDs.",
+        "Restack" => "Neu stapeln",
+        "restack {count} branches" => "{count} Branches neu stapeln",
+        "stack branches" => "Branches stapeln",
+        "restack branches on this one…" => "Branches auf diesem neu stapeln…",
+        "divergence from upstream" => "Abweichung vom Upstream",
+        "● {branch} only: {count}" => "● nur {branch}: {count}",
+        "● {upstream} only: {count}" => "● nur {upstream}: {count}",
+        "┆ ┆ … {count} older commits of {name}" => "┆ ┆ … {count} ältere Commits von {name}",
+        "    merge base" => "    Merge-Basis",
+        "no history in common" => "keine gemeinsame Historie",
+        "{branch} and {upstream}" => "{branch} und {upstream}",
+        "{name} is not a local branch" => "{name} ist kein lokaler Branch",
+        "{name} has no upstream" => "{name} hat keinen Upstream",
+        "rename branch" => "Branch umbenennen",
+        "Rename {name}" => "{name} umbenennen",
+        "expected a number in {token}" => "Zahl erwartet in {token}",
+        "expected true or false in {token}" => "true oder false erwartet in {token}",
+        "expected {key}:<value>" => "{key}:<Wert> erwartet",
+        "unknown filter {key}, expected author, owner, ahead, behind, age or merged" => "unbekannter Filter {key}, erwartet: author, owner, ahead, behind, age oder merged",
+        "unknown action {name} in [keys]" => "unbekannte Aktion {name} in [keys]",
+        "invalid key {key} for {name}" => "ungültige Taste {key} für {name}",
+        "saved views…" => "gespeicherte Ansichten…",
+        "saved view" => "gespeicherte Ansicht",
+        "no saved views, add [[views]] tables to the config" => "keine gespeicherten Ansichten, füge [[views]]-Tabellen zur Konfiguration hinzu",
+        "all branches" => "alle Branches",
+        "Views" => "Ansichten",
+        "show only stale branches" => "nur veraltete Branches zeigen",
+        "delete stale branches…" => "veraltete Branches löschen…",
+        "Stale branches" => "Veraltete Branches",
+        "Stale branches matching {query}" => "Veraltete Branches passend zu {query}",
+        "merged" => "gemergt",
+        "stale" => "veraltet",
+        "no stale branches" => "keine veralteten Branches",
+        "export the branch list…" => "Branch-Liste exportieren…",
+        "Export the branches to (.csv or .md, empty to copy)" => "Branches exportieren nach (.csv oder .md, leer zum Kopieren)",
+        "copied {count} branches as a Markdown table" => "{count} Branches als Markdown-Tabelle kopiert",
+        "exported {count} branches to {path}" => "{count} Branches nach {path} exportiert",
+        "No remotes yet. Name of the remote to add" => "Noch keine Remotes. Name des neuen Remotes",
+        "URL of {remote}" => "URL von {remote}",
+        "added remote {remote} at {url}" => "Remote {remote} mit {url} hinzugefügt",
+        "f or space r p to add one" => "f oder space r p, um eines hinzuzufügen",
+        "merge into the checked out branch…" => "in den ausgecheckten Branch mergen…",
+        "rebase the checked out branch onto this one…" => "den ausgecheckten Branch auf diesen rebasen…",
+        "{head} is based on {branch} already" => "{head} basiert bereits auf {branch}",
+        "{head} has every commit of {branch} already" => "{head} enthält bereits alle Commits von {branch}",
+        "Rebasing {head} onto {branch}" => "Rebase von {head} auf {branch}",
+        "Merging {branch} into {head}" => "Merge von {branch} in {head}",
+        "These files conflict, so nothing was changed:" => "Diese Dateien haben Konflikte, daher wurde nichts geändert:",
+        "conflicts" => "Konflikte",
+        "Fast-forward {head} by {count} commits to {branch}?" => "{head} per Fast-Forward um {count} Commits auf {branch} vorrücken?",
+        "Replay {count} commits of {head} onto {branch}? They get new ids." => "{count} Commits von {head} auf {branch} neu anwenden? Sie erhalten neue IDs.",
+        "Merge {count} commits of {branch} into {head} with a merge commit?" => "{count} Commits von {branch} mit einem Merge-Commit in {head} mergen?",
+        "Rebase" => "Rebase",
+        "Merge" => "Merge",
+        "merge {branch}" => "{branch} mergen",
+        "rebase onto {branch}" => "auf {branch} rebasen",
+        "conflicts in {paths}, nothing was changed" => "Konflikte in {paths}, nichts wurde geändert",
+        "actions on this branch…" => "Aktionen für diesen Branch…",
+        "tags" => "Tags",
+        "annotated" => "annotiert",
+        "{count} tags" => "{count} Tags",
+        "no tags" => "keine Tags",
+        "tagged by {name}" => "getaggt von {name}",
+        "{count} remotes" => "{count} Remotes",
+        "no remotes" => "keine Remotes",
+        "{count} branches" => "{count} Branches",
+        "esc: back" => "esc: zurück",
+        "enter: show  b: branch from tag  esc: back" => "enter: zeigen  b: Branch vom Tag  esc: zurück",
+        "branches tab" => "Tab Branches",
+        "tags tab" => "Tab Tags",
+        "remotes tab" => "Tab Remotes",
+        "next tab" => "nächster Tab",
+        "previous tab" => "vorheriger Tab",
+        "fetch" => "Abrufen",
+        "push" => "Pushen",
+        "offline" => "offline",
+        "back online" => "wieder online",
+        "offline, showing what was cached: {reason}" => "offline, zeige zwischengespeicherte Daten: {reason}",
+        "{branch} only points at another branch" => "{branch} zeigt nur auf einen anderen Branch",
+        "Delete {branch} on the server?" => "{branch} auf dem Server löschen?",
+        "Push {branch} to" => "{branch} pushen nach",
+        "The branch is deleted for everyone who fetches from the remote, unless it moved since the last fetch." => "Der Branch wird für alle gelöscht, die vom Remote abrufen, außer er hat sich seit dem letzten Abruf bewegt.",
+        "pull request not looked up, rate limited until {time}" => "Pull Request nicht abgefragt, Ratenlimit bis {time}",
+        "{host} rate limited until {time}" => "{host} Ratenlimit bis {time}",
+        "Log" => "Log",
+        "`: hide" => "`: ausblenden",
+        "nothing logged yet, -v logs more" => "noch nichts geloggt, -v loggt mehr",
+        "log pane" => "Log-Bereich",
+        "open pull request in browser" => "Pull Request im Browser öffnen",
+        "select a local branch to open its pull request" => "einen lokalen Branch wählen, um seinen Pull Request zu öffnen",
+        "draft" => "Entwurf",
+        "checks passed" => "Checks bestanden",
+        "checks failed" => "Checks fehlgeschlagen",
+        "checks running" => "Checks laufen",
+        "token from {program}" => "Token von {program}",
+        "the token comes from {program}, log out there to stop using it" => "das Token kommt von {program}, dort abmelden, um es nicht mehr zu verwenden",
+        "id" => "ID",
+        "id and summary" => "ID und Zusammenfassung",
+        "message" => "Nachricht",
+        "markdown link" => "Markdown-Link",
+        "Copy commit as" => "Commit kopieren als",
+        "safe mode" => "abgesicherter Modus",
+        "{dir} is not in a git repository" => "{dir} liegt in keinem Git-Repository",
+        "grit may not read the repository at {dir}" => "grit darf das Repository in {dir} nicht lesen",
+        "the repository at {dir} could not be read" => "das Repository in {dir} konnte nicht gelesen werden",
+        "Neither it nor any directory above it has a .git directory. Start a repository there, or pick one elsewhere." => "Weder es noch ein Verzeichnis darüber hat ein .git-Verzeichnis. Dort ein Repository anlegen oder woanders eines wählen.",
+        "Check who owns it and the permissions of its .git directory, or pick another repository." => "Besitzer und Rechte seines .git-Verzeichnisses prüfen oder ein anderes Repository wählen.",
+        "It may be damaged. `git fsck` in it tells more. Pick another repository meanwhile." => "Es ist vielleicht beschädigt. `git fsck` darin sagt mehr. Solange ein anderes Repository wählen.",
+        "run git init there" => "dort git init ausführen",
+        "open another directory" => "ein anderes Verzeichnis öffnen",
+        "Cannot open the repository" => "Repository kann nicht geöffnet werden",
+        "enter: open repository or go in  h: up  .: open this one  esc: back" => "enter: Repository öffnen oder hinein  h: hoch  .: dieses öffnen  esc: zurück",
+        "open another repository" => "ein anderes Repository öffnen",
+        "Opened lately" => "Zuletzt geöffnet",
+        "open the selected one" => "das ausgewählte öffnen",
+        "expected one of {choices}" => "erwartet wird eins von {choices}",
+        "a name cannot have < or > in it" => "ein Name darf kein < oder > enthalten",
+        "not an email address" => "keine E-Mail-Adresse",
+        "Git config" => "Git-Konfiguration",
+        "enter: set in this repository  d: unset here  esc: close" => "Enter: in diesem Repository setzen  d: hier entfernen  Esc: schließen",
+        "git config" => "Git-Konfiguration",
+        "{key} in this repository (empty: unset)" => "{key} in diesem Repository (leer: entfernen)",
+        "set {key} to {value}" => "{key} auf {value} gesetzt",
+        "unset {key} in this repository" => "{key} in diesem Repository entfernt",
+        "{key} is not set" => "{key} ist nicht gesetzt",
+        "{email} is not the address expected here, which matches {pattern}" => "{email} ist nicht die hier erwartete Adresse, die auf {pattern} passt",
+        "{key} to commit with in this repository" => "{key} für Commits in diesem Repository",
+        "{name} cancelled with its hooks" => "{name} samt Hooks abgebrochen",
+        "hooks of {name}: {hooks}" => "Hooks für {name}: {hooks}",
+        "a hook failed, so {name} was not done" => "ein Hook ist fehlgeschlagen, daher wurde {name} nicht ausgeführt",
+        "post-checkout of {name}" => "post-checkout von {name}",
+        "({keys}: load {count} more)" => "({keys}: {count} weitere laden)",
+        "({count} more not loaded)" => "({count} weitere nicht geladen)",
+        "every branch is loaded already" => "alle Branches sind schon geladen",
+        "load every branch" => "alle Branches laden",
+        "replace the local {name}" => "den lokalen {name} ersetzen",
+        "go ahead, tracking {upstream}" => "fortfahren und {upstream} folgen",
+        "go ahead, without tracking" => "fortfahren, ohne zu folgen",
+        "pick another name, such as {free}" => "einen anderen Namen wählen, etwa {free}",
+        "go to {branch}" => "zu {branch} gehen",
+        "{name} is taken by {branches}" => "{name} ist schon vergeben an {branches}",
+        "{name} is taken, {free} is not" => "{name} ist vergeben, {free} ist frei",
+        "{commit} is checked out at {path} until grit exits" => "{commit} ist bis zum Beenden von grit unter {path} ausgecheckt",
+        "todo" => "offen",
+        "ready" => "bereit",
+        "blocked" => "blockiert",
+        "clear" => "zurücksetzen",
+        "only local branches have a review state" => "nur lokale Branches haben einen Review-Status",
+        "Review state of {branch}" => "Review-Status von {branch}",
+        "Note on {branch}" => "Notiz zu {branch}",
+        "set review state…" => "Review-Status setzen…",
+        "edit branch note…" => "Branch-Notiz bearbeiten…",
+        "exported to the notifications and the log" => "in die Benachrichtigungen und das Log exportiert",
+        "{what}: {succeeded} succeeded, {failed} failed" => "{what}: {succeeded} erfolgreich, {failed} fehlgeschlagen",
+        "e: export to notifications and log  esc: close" => "e: in Benachrichtigungen und Log exportieren  esc: schließen",
+        "mark local branches to push first" => "markiere zuerst lokale Branches zum Pushen",
+        "push {count} branches" => "{count} Branches pushen",
+        "prune" => "aufräumen",
+        "prune all remotes" => "alle Remotes aufräumen",
+        "nothing to prune" => "nichts aufzuräumen",
+        "pruned {branch}" => "{branch} entfernt",
+        "{count} remote-tracking branches pruned" => "{count} Remote-Tracking-Branches entfernt",
+        "push marked branches" => "markierte Branches pushen",
+        "prune gone remote branches" => "verschwundene Remote-Branches aufräumen",
+        "Jobs still running" => "Jobs laufen noch",
+        "w: wait and quit  c: cancel them and quit  esc: stay" => "w: warten und beenden  c: abbrechen und beenden  Esc: bleiben",
+        "Quitting once the jobs are done" => "Beenden, sobald die Jobs fertig sind",
+        "c: cancel them  esc: stay" => "c: abbrechen  Esc: bleiben",
+        "follow branch, fetching it and marking new commits" => "Branch folgen, ihn abrufen und neue Commits markieren",
+        "following" => "gefolgt",
+        "{count} new" => "{count} neu",
+        "new" => "neu",
+        "following {branch}" => "{branch} wird gefolgt",
+        "stopped following {branch}" => "{branch} wird nicht mehr gefolgt",
+        "{count} new on {branch}" => "{count} neu auf {branch}",
+        "following {branch}, without fetching it in safe mode" => "{branch} wird gefolgt, im abgesicherten Modus ohne Abrufen",
+        "conflicts with {name}" => "kollidiert mit {name}",
+        "{path} has conflicts, resolve them in the file or with git mergetool rather than hunk by hunk" => "{path} hat Konflikte, löse sie in der Datei oder mit git mergetool statt Hunk für Hunk",
+        "{title} goes on in the background" => "{title} läuft im Hintergrund weiter",
+        "{title} failed" => "{title} fehlgeschlagen",
+        "j/k: scroll  s: stop  esc: hide" => "j/k: scrollen  s: stoppen  esc: ausblenden",
+        "refs were not read" => "die Refs wurden nicht gelesen",
+        "reading refs…" => "lese Refs…",
+        "author:me and owner:me need user.name to be set" => "author:me und owner:me brauchen ein gesetztes user.name",
+        "saved views" => "gespeicherte Ansichten",
+        "check out {commit}" => "{commit} auschecken",
+        "kept {path}, a worktree left behind with changes in it" => "{path} behalten, ein zurückgelassener Worktree mit Änderungen darin",
+        "{branch} has no upstream to follow" => "{branch} hat keinen Upstream zum Folgen",
+        "too large" => "zu groß",
+        _ => return None,
+    };
+    Some(val)
+}
@@ -0,0 +1,113 @@
+//! Message catalog for user facing strings.
+//!
+//! Strings are looked up by their english text, which doubles as the key, so an
+//! untranslated message falls back to english. Use the [`t!`](crate::t) macro rather
+//! than calling into this module directly.
+
+mod de;
+
+use std::{fmt::Display, sync::OnceLock};
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    /// Parses a locale from strings such as `de`, `de_DE` or `de_DE.UTF-8`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let lang = s
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "en" | "c" | "posix" => Some(Self::En),
+            "de" => Some(Self::De),
+            _ => None,
+        }
+    }
+
+    /// Picks the locale from an explicit setting (flag or config) and falls back to
+    /// the usual locale environment variables. As with `setlocale`, the first of
+    /// those that is set and not empty decides, so `LC_ALL=fr_FR` with `LANG=de_DE`
+    /// is english rather than german.
+    pub fn detect(explicit: Option<&str>) -> Self {
+        let env = ["GRIT_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|val| !val.is_empty());
+        explicit
+            .and_then(Self::parse)
+            .or_else(|| env.and_then(|val| Self::parse(&val)))
+            .unwrap_or_default()
+    }
+}
+
+/// Sets the process wide locale. Only the first call has any effect.
+pub fn init(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+pub fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+pub fn lookup(key: &'static str) -> &'static str {
+    match locale() {
+        Locale::En => key,
+        Locale::De => de::lookup(key).unwrap_or(key),
+    }
+}
+
+/// Replaces `{name}` placeholders in the template with the supplied arguments.
+/// The template is read once, so an argument that itself contains `{name}` is
+/// left the way it is. Placeholders without an argument are kept.
+pub fn format(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut res = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        res.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let arg = rest.find('}').and_then(|end| {
+            let name = &rest[1..end];
+            let (_, val) = args.iter().find(|(n, _)| *n == name)?;
+            Some((end, val))
+        });
+        match arg {
+            Some((end, val)) => {
+                res.push_str(&val.to_string());
+                rest = &rest[end + 1..];
+            }
+            None => {
+                res.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Translates a message into the current locale.
+///
+/// ```ignore
+/// t!("Branches");
+/// t!("sort ({sort})", sort = "name asc");
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::lookup($key)
+    };
+    ($key:literal, $($name:ident = $val:expr),+ $(,)?) => {
+        $crate::i18n::format(
+            $crate::i18n::lookup($key),
+            &[$((stringify!($name), &$val as &dyn std::fmt::Display)),+],
+        )
+    };
+}
@@ -1,5 +1,17 @@
 pub mod app;
+pub mod askpass;
 pub mod bootstrap;
-pub mod git;
+pub mod cli;
+pub mod config;
+pub mod crash;
+pub mod forge;
+pub mod fuzzy;
+pub use grit_core as git;
+pub mod i18n;
+pub mod logs;
 pub mod opts;
+pub mod paths;
+pub mod platform;
 pub mod prelude;
+pub mod redact;
+pub mod theme;
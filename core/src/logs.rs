@@ -0,0 +1,141 @@
+//! The latest events traced, kept in memory for the log pane of the app, since
+//! stderr is hidden behind the alternate screen while it runs.
+//!
+//! [`layer`] is installed next to the log file by
+//! [`bootstrap::init_logging`](crate::bootstrap::init_logging).
+
+use chrono::{DateTime, Local};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+use tracing::{field::Field, span, Event, Level, Subscriber};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// How many events are kept.
+const CAPACITY: usize = 500;
+
+static RECENT: Mutex<VecDeque<Entry>> = Mutex::new(VecDeque::new());
+/// Counts the events kept so far, to tell when the pane needs drawing again.
+static COUNT: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    /// The module the event came from, e.g. `grit_core::command`.
+    pub target: String,
+    /// The spans it happened in, outermost first, as the name followed by
+    /// the fields, e.g. `git{cmd=git fetch}`.
+    pub spans: Vec<String>,
+    /// The message followed by the other fields, as `name=value`.
+    pub message: String,
+}
+
+/// Keeps the events it is given, for [`recent`].
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    Recent
+}
+
+/// The events kept, oldest first.
+pub fn recent() -> Vec<Entry> {
+    RECENT
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// How many events were kept so far, which changes with every new one.
+pub fn count() -> u64 {
+    COUNT.load(Ordering::Relaxed)
+}
+
+struct Recent;
+
+impl<S> Layer<S> for Recent
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        let name = match fields.rest.trim_start() {
+            "" => span.name().to_string(),
+            rest => format!("{}{{{rest}}}", span.name()),
+        };
+        span.extensions_mut().insert(SpanName(name));
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| match span.extensions().get::<SpanName>() {
+                        Some(name) => name.0.clone(),
+                        None => span.name().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let meta = event.metadata();
+        let entry = Entry {
+            time: Local::now(),
+            level: *meta.level(),
+            target: meta.target().to_string(),
+            spans,
+            message: fields.message + &fields.rest,
+        };
+        if let Ok(mut recent) = RECENT.lock() {
+            if recent.len() == CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(entry);
+            COUNT.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The name of a span with its fields, worked out once when it is created.
+struct SpanName(String);
+
+#[derive(Default)]
+struct Fields {
+    message: String,
+    rest: String,
+}
+
+impl tracing::field::Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => {
+                let _ = write!(self.message, "{value:?}");
+            }
+            name => {
+                let _ = write!(self.rest, " {name}={value:?}");
+            }
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message.push_str(value),
+            name => {
+                let _ = write!(self.rest, " {name}={value}");
+            }
+        }
+    }
+}
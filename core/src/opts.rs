@@ -1,7 +1,221 @@
 use std::path::PathBuf;
 
+use crate::{
+    app::Sort,
+    cli::{self, Format},
+    git,
+};
+
 /// a TUI that lets you manage your github branches.
 #[derive(clap::Parser, Clone, Debug)]
+#[command(
+    after_help = "exit codes: 0 success or selection made, 1 aborted or nothing selected, \
+2 git error, 3 usage error, 4 config error, 5 i/o error"
+)]
 pub struct Opts {
     pub dir: Option<PathBuf>,
+
+    /// the branch to select on startup. same as --select.
+    pub branch: Option<String>,
+
+    /// the branch to select on startup. falls back to a fuzzy match.
+    #[arg(long)]
+    pub select: Option<String>,
+
+    /// path to the repository's git dir. defaults to $GIT_DIR, then discovery.
+    #[arg(long)]
+    pub git_dir: Option<PathBuf>,
+
+    /// path to the work tree. defaults to $GIT_WORK_TREE, then core.worktree, then the
+    /// repo's own, or with --git-dir the current directory.
+    #[arg(long)]
+    pub work_tree: Option<PathBuf>,
+
+    /// the locale for UI strings, e.g. `en` or `de`. defaults to $LANG.
+    #[arg(long)]
+    pub locale: Option<String>,
+
+    /// the config file. defaults to $GRIT_CONFIG, then config.toml in
+    /// $XDG_CONFIG_HOME/grit or the platform's config directory.
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// the color theme. overrides $GRIT_THEME and the config file.
+    #[arg(long)]
+    pub theme: Option<String>,
+
+    /// replace branch names, authors and emails with made up ones, for
+    /// screen-sharing private repos.
+    #[arg(long)]
+    pub demo: bool,
+
+    /// don't print what the session changed on exit.
+    #[arg(long)]
+    pub quiet: bool,
+
+    /// write the worktree picked to go to on exit to this file rather than
+    /// stdout, which has to be a terminal for the TUI, e.g. for a shell
+    /// function that changes into it afterwards. emptied if none is picked.
+    #[arg(long)]
+    pub exit_file: Option<PathBuf>,
+
+    /// read every branch afresh instead of taking tips and histories that did
+    /// not change from the cache, which is written anew.
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// reload when other programs change the repository, e.g. a fetch or a
+    /// rebase in another terminal.
+    #[arg(long)]
+    pub watch: bool,
+
+    /// start with every integration off and the default theme, to tell whether
+    /// one of them misbehaves: forge APIs, watching, hooks, user commands and
+    /// handoffs.
+    #[arg(long)]
+    pub safe: bool,
+
+    /// the initial sort: name, name-desc, date-asc, date or divergence. overrides $GRIT_SORT.
+    #[arg(long, value_parser = |s: &str| Sort::try_from(s.to_string()))]
+    pub sort: Option<Sort>,
+
+    /// how many commits to load per branch. overrides $GRIT_MAX_COMMITS.
+    #[arg(long, global = true)]
+    pub max_commits: Option<usize>,
+
+    /// the output format of the subcommands, and of the branch list printed
+    /// when stdout is not a terminal.
+    #[arg(long, value_enum, default_value_t, global = true)]
+    pub format: Format,
+
+    /// log more: -v for info, -vv for debug, -vvv for everything. $GRIT_LOG
+    /// takes precedence.
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// the file to log to. defaults to grit.log in the state directory, which
+    /// is only written to with -v or $GRIT_LOG.
+    #[arg(long, global = true)]
+    pub log_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum Command {
+    /// print the local branches, as when stdout is not a terminal.
+    List,
+    /// print the local branches without commits for a while, oldest first.
+    Stale(StaleArgs),
+    /// delete local branches, refusing unmerged ones unless forced and protected ones always.
+    Delete(DeleteArgs),
+    /// delete local branches that are merged, gone or old, and print a report.
+    Prune(PruneArgs),
+    /// print the stale local branches grouped by author, e.g. for a weekly mail.
+    Report(ReportArgs),
+    /// check out the branch that best matches a pattern, or pick one when several do.
+    Checkout(CheckoutArgs),
+    /// time each step of startup on this repository, to report or chase slowness.
+    Bench(BenchArgs),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct StaleArgs {
+    /// how many days without commits make a branch stale.
+    #[arg(long, default_value_t = 90)]
+    pub days: u32,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct ReportArgs {
+    /// how long without commits makes a branch stale, e.g. `90d` or `6m`.
+    /// branches merged into the default branch are stale regardless.
+    #[arg(long, default_value = "90d", value_parser = cli::parse_age)]
+    pub stale: i64,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct DeleteArgs {
+    /// the branches to delete.
+    #[arg(required = true)]
+    pub names: Vec<String>,
+
+    /// delete branches with commits that are in neither their upstream nor HEAD.
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct BenchArgs {
+    /// commits per branch to measure at, e.g. `10,100,1000`. defaults to --max-commits.
+    #[arg(long, value_delimiter = ',')]
+    pub at: Vec<usize>,
+
+    /// how often to repeat each measurement. the median is reported.
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub runs: u32,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct CheckoutArgs {
+    /// a fuzzy pattern such as `feat/log`. an exact branch name always wins.
+    pub pattern: String,
+
+    /// stash local changes that are in the way and reapply them afterwards
+    #[arg(long)]
+    pub stash: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+#[command(group(
+    clap::ArgGroup::new("criteria")
+        .required(true)
+        .multiple(true)
+        .args(["merged", "gone", "older_than"])
+))]
+pub struct PruneArgs {
+    /// branches fully merged into the default branch.
+    #[arg(long)]
+    pub merged: bool,
+
+    /// branches whose upstream no longer exists.
+    #[arg(long)]
+    pub gone: bool,
+
+    /// branches without commits for this long, e.g. `90d`, `12w` or `1y`. narrows
+    /// --merged and --gone when combined with them.
+    #[arg(long, value_parser = cli::parse_age)]
+    pub older_than: Option<i64>,
+
+    /// only report what would be deleted.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl Opts {
+    pub fn selection(&self) -> Option<&str> {
+        self.select.as_deref().or(self.branch.as_deref())
+    }
+
+    /// Points at the repository in or above `dir` instead, selecting `branch`
+    /// there. The git dir, work tree and branch given, on the command line or
+    /// in the environment, are left out.
+    pub fn retarget(&mut self, dir: PathBuf, branch: Option<String>) {
+        self.dir = Some(dir);
+        self.git_dir = None;
+        self.work_tree = None;
+        self.branch = None;
+        self.select = branch;
+        std::env::remove_var("GIT_DIR");
+        std::env::remove_var("GIT_WORK_TREE");
+    }
+
+    pub fn target(&self) -> git::Target {
+        git::Target {
+            dir: self.dir.clone(),
+            git_dir: self.git_dir.clone(),
+            work_tree: self.work_tree.clone(),
+        }
+    }
 }
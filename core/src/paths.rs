@@ -0,0 +1,135 @@
+//! Where grit keeps its files: the config, caches of what can be fetched again,
+//! state kept between runs such as crash reports, and the log.
+//!
+//! On Linux and the BSDs these follow the XDG base directories. macOS and
+//! Windows have places of their own, though the `XDG_*` variables are honored
+//! there too when set, as other command line tools do.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+const APP: &str = "grit";
+
+/// Where every file is kept instead of the places below, once set.
+static BASE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keeps the config, caches, state and log under `dir` rather than where the
+/// user's are, e.g. for tests, without changing the environment. Only the
+/// first call has an effect.
+pub fn set_base(dir: PathBuf) {
+    let _ = BASE.set(dir);
+}
+
+/// The config file: `--config`, then `$GRIT_CONFIG`, then `config.toml` in
+/// [`config_dir`].
+pub fn config_file(flag: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = flag {
+        return Some(path.to_path_buf());
+    }
+    if let Some(path) = std::env::var_os("GRIT_CONFIG").filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    if cfg!(target_os = "macos") && BASE.get().is_none() {
+        // where grit looked before it knew about macOS, kept while it exists
+        let legacy = home()?.join(".config").join(APP).join("config.toml");
+        if xdg("XDG_CONFIG_HOME").is_none() && legacy.exists() {
+            return Some(legacy);
+        }
+    }
+    Some(config_dir()?.join("config.toml"))
+}
+
+/// `$XDG_CONFIG_HOME/grit` or `~/.config/grit`; on macOS
+/// `~/Library/Application Support/grit`, on Windows `%APPDATA%\grit`.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(base) = BASE.get() {
+        return Some(base.join("config"));
+    }
+    let base = match xdg("XDG_CONFIG_HOME") {
+        Some(base) => base,
+        None if cfg!(windows) => known("APPDATA")?,
+        None if cfg!(target_os = "macos") => home()?.join("Library/Application Support"),
+        None => home()?.join(".config"),
+    };
+    Some(base.join(APP))
+}
+
+/// `$XDG_CACHE_HOME/grit` or `~/.cache/grit`; on macOS `~/Library/Caches/grit`,
+/// on Windows `%LOCALAPPDATA%\grit\cache`.
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(base) = BASE.get() {
+        return Some(base.join("cache"));
+    }
+    match xdg("XDG_CACHE_HOME") {
+        Some(base) => Some(base.join(APP)),
+        None if cfg!(windows) => Some(known("LOCALAPPDATA")?.join(APP).join("cache")),
+        None if cfg!(target_os = "macos") => Some(home()?.join("Library/Caches").join(APP)),
+        None => Some(home()?.join(".cache").join(APP)),
+    }
+}
+
+/// The cache file of one kind for the repository at `git_dir`, e.g.
+/// `forge/%home%me%project%.git.json`.
+pub fn repo_cache(kind: &str, git_dir: &Path) -> Option<PathBuf> {
+    let key = git_dir.to_string_lossy().replace(['/', '\\', ':'], "%");
+    Some(cache_dir()?.join(kind).join(format!("{key}.json")))
+}
+
+/// `$XDG_STATE_HOME/grit` or `~/.local/state/grit`; on macOS
+/// `~/Library/Application Support/grit/state`, on Windows
+/// `%LOCALAPPDATA%\grit\state`.
+pub fn state_dir() -> Option<PathBuf> {
+    if let Some(base) = BASE.get() {
+        return Some(base.join("state"));
+    }
+    match xdg("XDG_STATE_HOME") {
+        Some(base) => Some(base.join(APP)),
+        None if cfg!(windows) => Some(known("LOCALAPPDATA")?.join(APP).join("state")),
+        None if cfg!(target_os = "macos") => Some(
+            home()?
+                .join("Library/Application Support")
+                .join(APP)
+                .join("state"),
+        ),
+        None => Some(home()?.join(".local/state").join(APP)),
+    }
+}
+
+/// Where the log is written, when `-v` or `$GRIT_LOG` asks for one. XDG counts logs as
+/// state; macOS keeps them in `~/Library/Logs`.
+pub fn log_file() -> Option<PathBuf> {
+    let dir = match xdg("XDG_STATE_HOME") {
+        None if cfg!(target_os = "macos") && BASE.get().is_none() => {
+            home()?.join("Library/Logs").join(APP)
+        }
+        _ => state_dir()?,
+    };
+    Some(dir.join("grit.log"))
+}
+
+/// A base directory from the environment. Relative ones are to be ignored, the
+/// XDG spec says.
+fn xdg(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+}
+
+/// A folder Windows names in the environment, `%APPDATA%` or `%LOCALAPPDATA%`,
+/// or where it usually is if unset.
+fn known(var: &str) -> Option<PathBuf> {
+    let fallback = match var {
+        "APPDATA" => "AppData/Roaming",
+        _ => "AppData/Local",
+    };
+    xdg(var).or_else(|| Some(home()?.join(fallback)))
+}
+
+fn home() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+}
@@ -0,0 +1,204 @@
+//! What differs between the systems grit runs on, gathered in one place so that
+//! the rest of grit asks rather than checks `cfg`. Both descriptions are built
+//! on every system; only what the standard library has for one system alone
+//! is behind `cfg`.
+
+use std::{path::Path, process::Command};
+
+pub struct Platform {
+    /// Whether SIGTERM, SIGINT and SIGHUP exist to restore the terminal on.
+    /// Windows has none of them; ctrl-c arrives as a key in raw mode.
+    pub signals: bool,
+    /// Whether the terminal can be asked about the kitty keyboard protocol.
+    /// The Windows console answers nothing, so asking only waits.
+    pub keyboard_query: bool,
+    /// Whether a console without bracketed paste still works, pasting as typed
+    /// keys. The legacy Windows console refuses to turn it on.
+    pub paste_optional: bool,
+    /// Whether the terminal has a device name, as `tty` prints, for `GPG_TTY`.
+    pub tty: bool,
+    /// Whether `sh` can be counted on, rather than looked for.
+    pub sh: bool,
+    /// Added to the names of programs to find them on `$PATH`.
+    pub exe_suffix: &'static str,
+    /// Whether `kill -0` tells whether a process runs, rather than `tasklist`.
+    pub kill: bool,
+}
+
+pub const UNIX: Platform = Platform {
+    signals: true,
+    keyboard_query: true,
+    paste_optional: false,
+    tty: true,
+    sh: true,
+    exe_suffix: "",
+    kill: true,
+};
+
+pub const WINDOWS: Platform = Platform {
+    signals: false,
+    keyboard_query: false,
+    paste_optional: true,
+    tty: false,
+    sh: false,
+    exe_suffix: ".exe",
+    kill: false,
+};
+
+/// The system grit was built for.
+pub fn current() -> &'static Platform {
+    match cfg!(windows) {
+        true => &WINDOWS,
+        false => &UNIX,
+    }
+}
+
+impl Platform {
+    /// Whether an executable named `program` is in one of the `$PATH`
+    /// directories.
+    pub fn on_path(&self, program: &str) -> bool {
+        let file = format!("{program}{}", self.exe_suffix);
+        std::env::var_os("PATH")
+            .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(&file).is_file()))
+    }
+
+    /// Runs `script` in a shell. Scripts are written for `sh`, which git for
+    /// Windows brings along and puts on the path; `cmd` is what is left
+    /// without it.
+    pub fn shell(&self, script: &str) -> Command {
+        if self.has_sh() {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(script);
+            return cmd;
+        }
+        // `/S` has cmd drop only the outer quotes, keeping those around paths
+        // with spaces or `&` in the script
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/S", "/C"]);
+        raw_arg(&mut cmd, &format!("\"{script}\""));
+        cmd
+    }
+
+    /// Opens `path` in `editor`, as `GIT_EDITOR` names it, which may carry
+    /// arguments and so goes through the shell the way git runs it.
+    pub fn editor(&self, editor: &str, path: &Path) -> Command {
+        if !self.has_sh() {
+            return self.shell(&format!("{editor} \"{}\"", path.display()));
+        }
+        let mut cmd = self.shell(&format!("{editor} \"$@\""));
+        cmd.arg(editor).arg(path);
+        cmd
+    }
+
+    fn has_sh(&self) -> bool {
+        self.sh || self.on_path("sh")
+    }
+
+    /// Whether the process `pid` still runs. One that cannot be asked about
+    /// is taken to.
+    pub fn is_running(&self, pid: u32) -> bool {
+        let pid = pid.to_string();
+        if self.kill {
+            return Command::new("kill")
+                .args(["-0", &pid])
+                .stderr(std::process::Stdio::null())
+                .status()
+                .map_or(true, |status| status.success());
+        }
+        let filter = format!("PID eq {pid}");
+        Command::new("tasklist")
+            .args(["/FI", &filter, "/NH"])
+            .output()
+            .map_or(true, |out| {
+                String::from_utf8_lossy(&out.stdout).contains(&pid)
+            })
+    }
+
+    /// The terminal grit runs in, as `tty` names it.
+    pub fn tty(&self) -> Option<String> {
+        if !self.tty {
+            return None;
+        }
+        let out = Command::new("tty")
+            .stdin(std::process::Stdio::inherit())
+            .output()
+            .ok()?;
+        let tty = String::from_utf8(out.stdout).ok()?;
+        out.status.success().then(|| tty.trim().to_string())
+    }
+}
+
+/// Adds `arg` to `cmd` as is, rather than quoted the way Windows programs
+/// other than cmd parse their command line.
+#[cfg(windows)]
+fn raw_arg(cmd: &mut Command, arg: &str) {
+    use std::os::windows::process::CommandExt;
+    cmd.raw_arg(arg);
+}
+
+#[cfg(not(windows))]
+fn raw_arg(cmd: &mut Command, arg: &str) {
+    cmd.arg(arg);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Windows without `sh` on the path, which has to make do with `cmd`.
+    #[cfg(not(windows))]
+    const NO_SH: Platform = Platform {
+        exe_suffix: ".missing",
+        ..WINDOWS
+    };
+
+    #[cfg(not(windows))]
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sh_runs_the_script_as_written() {
+        let out = UNIX
+            .shell("echo \"$((1 + 2))\" && echo 'a b'")
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout), "3\na b\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sh_editor_gets_the_path_as_one_argument() {
+        let out = UNIX
+            .editor("printf '%s|'", Path::new("/tmp/a b & c.txt"))
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout), "/tmp/a b & c.txt|");
+    }
+
+    // raw arguments are left out of get_args on windows
+    #[cfg(not(windows))]
+    #[test]
+    fn cmd_gets_the_script_in_outer_quotes() {
+        let cmd = NO_SH.shell(r#""C:\a b\x.exe" --flag & echo done"#);
+        assert_eq!(cmd.get_program(), "cmd");
+        assert_eq!(
+            args(&cmd),
+            ["/S", "/C", r#"""C:\a b\x.exe" --flag & echo done""#]
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn cmd_editor_quotes_the_path() {
+        let cmd = NO_SH.editor("notepad", Path::new(r"C:\a b\msg & more.txt"));
+        assert_eq!(cmd.get_program(), "cmd");
+        assert_eq!(
+            args(&cmd),
+            ["/S", "/C", r#""notepad "C:\a b\msg & more.txt"""#]
+        );
+    }
+}
@@ -11,13 +11,13 @@ pub use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{
         palette::{
-            material::{BLUE, RED},
+            material::{AMBER, BLUE, GREEN, RED},
             tailwind::SLATE,
         },
         Color, Style,
     },
     symbols,
-    text::Line,
+    text::{Line, Span},
     widgets::{
         block::Title, Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
     },
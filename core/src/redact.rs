@@ -0,0 +1,195 @@
+//! Demo mode, which replaces branch names, authors and emails with made up ones
+//! wherever they are shown, so that grit can be screen-shared on private repos.
+//!
+//! Like [`i18n`](crate::i18n), it is switched on once at startup and consulted by
+//! the widgets as they render. Pseudonyms are derived from a hash of the real
+//! name, so they are the same across sessions, and every replacement is
+//! remembered so that [`text`] can scrub free text such as notifications.
+
+use regex::Regex;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Replacements made so far, and a pattern matching any of them.
+static SEEN: Mutex<Seen> = Mutex::new(Seen {
+    names: BTreeMap::new(),
+    pattern: None,
+    stale: false,
+});
+
+struct Seen {
+    names: BTreeMap<String, String>,
+    pattern: Option<Regex>,
+    /// Whether names were added since the pattern was built, which it is
+    /// again once text is next scrubbed rather than once per name.
+    stale: bool,
+}
+
+impl Seen {
+    /// The pattern matching any of the names, built again if they changed.
+    fn pattern(&mut self) -> Option<&Regex> {
+        if self.stale {
+            self.stale = false;
+            // longest first, so that `feat/a-b` wins over `feat/a`, and whole
+            // words only, so that an author called `al` leaves `also` alone
+            let mut keys: Vec<&String> = self.names.keys().collect();
+            keys.sort_by_key(|k| std::cmp::Reverse(k.len()));
+            let alternatives = keys
+                .iter()
+                .map(|k| regex::escape(k))
+                .collect::<Vec<_>>()
+                .join("|");
+            match Regex::new(&format!(r"\b(?:{alternatives})\b")) {
+                Ok(pattern) => self.pattern = Some(pattern),
+                // the names from before are still scrubbed
+                Err(err) => tracing::warn!("build the pattern of redacted names: {err}"),
+            }
+        }
+        self.pattern.as_ref()
+    }
+}
+
+/// Branch name segments that say nothing about a project.
+const KEEP: &[&str] = &[
+    "HEAD", "main", "master", "trunk", "develop", "dev", "origin", "upstream", "feature", "feat",
+    "fix", "bugfix", "hotfix", "release", "chore", "docs", "refactor", "test",
+];
+
+const ADJECTIVES: &[&str] = &[
+    "amber", "brisk", "calm", "dusty", "eager", "fuzzy", "gentle", "hollow", "icy", "jolly",
+    "keen", "lucky", "misty", "nimble", "olive", "quiet", "rapid", "silver", "tidy", "vivid",
+];
+
+const NOUNS: &[&str] = &[
+    "badger", "comet", "delta", "falcon", "garden", "harbor", "island", "lantern", "meadow",
+    "nebula", "orchid", "pebble", "quartz", "river", "summit", "thicket", "valley", "willow",
+];
+
+const FIRST: &[&str] = &[
+    "Alex", "Blake", "Casey", "Drew", "Emery", "Finley", "Harper", "Jordan", "Kai", "Logan",
+    "Morgan", "Noel", "Parker", "Quinn", "Riley", "Sage", "Taylor", "Avery",
+];
+
+const LAST: &[&str] = &[
+    "Archer", "Brooks", "Carter", "Dalton", "Ellis", "Fisher", "Grant", "Hayes", "Irving",
+    "Jensen", "Keller", "Lowe", "Monroe", "Nash", "Porter", "Reyes", "Sutton", "Wells",
+];
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A branch name, keeping its shape and conventional prefixes, e.g.
+/// `feature/amber-falcon`.
+pub fn branch(name: &str) -> Cow<'_, str> {
+    if !is_enabled() {
+        return Cow::Borrowed(name);
+    }
+    let fake = name
+        .split('/')
+        .map(|part| match KEEP.contains(&part) {
+            true => part.to_string(),
+            false => format!("{}-{}", pick(ADJECTIVES, part, 0), pick(NOUNS, part, 1)),
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    remember(name, &fake);
+    Cow::Owned(fake)
+}
+
+/// A person's name, e.g. `Casey Nash`.
+pub fn person(name: &str) -> Cow<'_, str> {
+    if !is_enabled() || name.is_empty() {
+        return Cow::Borrowed(name);
+    }
+    let fake = format!("{} {}", pick(FIRST, name, 0), pick(LAST, name, 1));
+    remember(name, &fake);
+    Cow::Owned(fake)
+}
+
+/// An email address at `example.com`.
+pub fn email(email: &str) -> Cow<'_, str> {
+    if !is_enabled() || email.is_empty() {
+        return Cow::Borrowed(email);
+    }
+    let fake = format!(
+        "{}.{}@example.com",
+        pick(FIRST, email, 0).to_lowercase(),
+        pick(LAST, email, 1).to_lowercase()
+    );
+    remember(email, &fake);
+    Cow::Owned(fake)
+}
+
+/// Free text with every name replaced so far, and any email, replaced.
+pub fn text(text: &str) -> Cow<'_, str> {
+    if !is_enabled() {
+        return Cow::Borrowed(text);
+    }
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    let email_re = EMAIL.get_or_init(|| Regex::new(r"[\w.+-]+@[\w-]+(\.[\w-]+)+").unwrap());
+    let text = email_re.replace_all(text, |caps: &regex::Captures| email(&caps[0]).into_owned());
+    let Ok(mut seen) = SEEN.lock() else {
+        return Cow::Owned(text.into_owned());
+    };
+    let seen = &mut *seen;
+    let Some(pattern) = seen.pattern().cloned() else {
+        return Cow::Owned(text.into_owned());
+    };
+    Cow::Owned(
+        pattern
+            .replace_all(&text, |caps: &regex::Captures| {
+                // a name the pattern was built before may be missing
+                seen.names
+                    .get(&caps[0])
+                    .cloned()
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned(),
+    )
+}
+
+/// Text with the user and password left out of every URL in it, which are
+/// never shown, demo mode or not, and otherwise as [`text`] makes it.
+pub fn url(url: &str) -> Cow<'_, str> {
+    static USERINFO: OnceLock<Regex> = OnceLock::new();
+    let userinfo =
+        USERINFO.get_or_init(|| Regex::new(r"([A-Za-z][A-Za-z0-9+.-]*://)[^/@\s]+@").unwrap());
+    match userinfo.replace_all(url, "$1") {
+        Cow::Borrowed(url) => text(url),
+        Cow::Owned(url) => Cow::Owned(text(&url).into_owned()),
+    }
+}
+
+fn remember(real: &str, fake: &str) {
+    let Ok(mut seen) = SEEN.lock() else {
+        return;
+    };
+    if seen.names.contains_key(real) {
+        return;
+    }
+    seen.names.insert(real.to_string(), fake.to_string());
+    seen.stale = true;
+}
+
+/// Picks a word for `key`, using a different part of its hash for each `salt`.
+fn pick<'a>(words: &[&'a str], key: &str, salt: u32) -> &'a str {
+    // FNV-1a, since std's hasher is not stable across releases
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    });
+    let hash = hash.rotate_right(salt * 17);
+    words[(hash % words.len() as u64) as usize]
+}
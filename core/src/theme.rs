@@ -0,0 +1,493 @@
+//! The colors of the branch list and the chrome around it: a named theme,
+//! `default` for dark terminals, `light` or `high-contrast`, with single
+//! colors replaced from the `[colors]` table of the config.
+//!
+//! Like [`i18n`](crate::i18n), it is set once at startup and consulted by the
+//! widgets as they render. Widgets draw in full color; once a frame is drawn,
+//! [`finish`] brings its colors down to what the terminal can show.
+
+use crate::{config::ColorsConfig, prelude::*};
+use color_eyre::eyre::eyre;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
+};
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+#[derive(Clone, Copy, Debug)]
+pub struct Theme {
+    /// The borders of the panes and the hints in the footer.
+    pub header: Style,
+    /// The selected row of the branch list.
+    pub selected: Style,
+    /// The background of the branch list and the details pane.
+    pub row_bg: Color,
+    pub local_branch: Color,
+    pub remote_branch: Color,
+    /// Whether every color drawn is mapped onto the 16 of the terminal's own
+    /// palette, see [`finish`].
+    pub ansi: bool,
+    pub depth: Depth,
+}
+
+/// How many colors the terminal shows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Depth {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl Depth {
+    /// The depth set in the config, or else the one the environment tells of:
+    /// `COLORTERM`, then the `colors` of the terminfo entry of `TERM`. Without
+    /// any `TERM`, as on Windows, the terminal is taken to show them all.
+    pub fn detect(explicit: Option<&str>) -> EResult<Self> {
+        match explicit {
+            Some("truecolor") => return Ok(Depth::TrueColor),
+            Some("256") => return Ok(Depth::Ansi256),
+            Some("16") => return Ok(Depth::Ansi16),
+            Some(other) => {
+                return Err(eyre!(
+                    "colors.depth: {other} is not one of truecolor, 256 or 16"
+                ))
+            }
+            None => {}
+        }
+        let var = |name| std::env::var(name).ok().filter(|val| !val.is_empty());
+        if var("COLORTERM").is_some_and(|val| val == "truecolor" || val == "24bit")
+            || var("WT_SESSION").is_some()
+        {
+            return Ok(Depth::TrueColor);
+        }
+        let Some(term) = var("TERM") else {
+            return Ok(Depth::TrueColor);
+        };
+        if term.ends_with("-direct") {
+            return Ok(Depth::TrueColor);
+        }
+        Ok(match terminfo_colors(&term) {
+            Some(colors) if colors >= 1 << 24 => Depth::TrueColor,
+            Some(colors) if colors >= 256 => Depth::Ansi256,
+            Some(_) => Depth::Ansi16,
+            None if term.contains("256color") => Depth::Ansi256,
+            None => Depth::Ansi16,
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            header: Style::new().fg(SLATE.c100).bg(BLUE.c800),
+            selected: Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD),
+            row_bg: SLATE.c950,
+            local_branch: SLATE.c200,
+            remote_branch: RED.c200,
+            ansi: false,
+            depth: Depth::TrueColor,
+        }
+    }
+}
+
+impl Theme {
+    fn light() -> Self {
+        Self {
+            header: Style::new().fg(SLATE.c900).bg(BLUE.c200),
+            selected: Style::new().bg(SLATE.c200).add_modifier(Modifier::BOLD),
+            row_bg: SLATE.c50,
+            local_branch: SLATE.c800,
+            remote_branch: RED.c700,
+            ansi: false,
+            depth: Depth::TrueColor,
+        }
+    }
+
+    /// The terminal's own colors and nothing dimmed, readable on light and
+    /// dark backgrounds alike and on terminals with only 16 colors.
+    fn high_contrast() -> Self {
+        Self {
+            header: Style::new()
+                .fg(Color::Black)
+                .bg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            selected: Style::new().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            row_bg: Color::Reset,
+            local_branch: Color::Reset,
+            remote_branch: Color::Red,
+            ansi: true,
+            depth: Depth::Ansi16,
+        }
+    }
+
+    /// The theme named `name`, or the default one, with the colors of
+    /// `colors` in place of its own, for a terminal of the detected depth.
+    pub fn resolve(name: Option<&str>, colors: &ColorsConfig) -> EResult<Self> {
+        let mut theme = match name.unwrap_or("default") {
+            "default" => Theme::default(),
+            "light" => Theme::light(),
+            "high-contrast" => Theme::high_contrast(),
+            name => {
+                return Err(eyre!(
+                    "unknown theme {name}, expected default, light or high-contrast"
+                ))
+            }
+        };
+        if !theme.ansi {
+            theme.depth = Depth::detect(colors.depth.as_deref())?;
+        }
+        match colors.selection.as_deref() {
+            // with 16 colors, the selected row's background could well be that
+            // of the others
+            None if theme.depth == Depth::Ansi16 => {
+                theme.selected = Style::new().add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            None | Some("color") => {}
+            Some("reverse") => {
+                theme.selected = Style::new().add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            Some("bold") => theme.selected = Style::new().add_modifier(Modifier::BOLD),
+            Some(other) => {
+                return Err(eyre!(
+                    "colors.selection: {other} is not one of color, reverse or bold"
+                ))
+            }
+        }
+        let color = |key: &str, value: &Option<String>| -> EResult<Option<Color>> {
+            value
+                .as_deref()
+                .map(|value| {
+                    Color::from_str(value)
+                        .map_err(|_| eyre!("colors.{key}: {value} is not a color"))
+                })
+                .transpose()
+        };
+        if let Some(fg) = color("header_fg", &colors.header_fg)? {
+            theme.header = theme.header.fg(fg);
+        }
+        if let Some(bg) = color("header_bg", &colors.header_bg)? {
+            theme.header = theme.header.bg(bg);
+        }
+        if let Some(bg) = color("selected_bg", &colors.selected_bg)? {
+            theme.selected = theme.selected.bg(bg);
+        }
+        if let Some(bg) = color("row_bg", &colors.row_bg)? {
+            theme.row_bg = bg;
+        }
+        if let Some(fg) = color("local_branch", &colors.local_branch)? {
+            theme.local_branch = fg;
+        }
+        if let Some(fg) = color("remote_branch", &colors.remote_branch)? {
+            theme.remote_branch = fg;
+        }
+        Ok(theme)
+    }
+}
+
+/// Sets the process wide theme. Only the first call has any effect.
+pub fn init(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+pub fn get() -> Theme {
+    THEME.get().copied().unwrap_or_default()
+}
+
+/// Brings the colors of a drawn frame down to the depth of the theme. The
+/// `high-contrast` theme also turns grays into the terminal's own foreground
+/// and background, and dims nothing.
+pub fn finish(buf: &mut Buffer) {
+    let theme = get();
+    let map: fn(Color) -> Color = match theme.depth {
+        _ if theme.ansi => ansi,
+        Depth::TrueColor => return,
+        Depth::Ansi256 => ansi256,
+        Depth::Ansi16 => ansi16,
+    };
+    for cell in &mut buf.content {
+        cell.bg = map(cell.bg);
+        cell.fg = distinct(map(cell.fg), cell.bg);
+        if theme.ansi {
+            cell.modifier.remove(Modifier::DIM);
+        }
+    }
+}
+
+/// `fg`, or if bringing the colors down made it the same as `bg`, such as a
+/// light blue on a dark blue, the one of black and white that shows on it.
+fn distinct(fg: Color, bg: Color) -> Color {
+    if fg != bg || fg == Color::Reset {
+        return fg;
+    }
+    match bg {
+        Color::White
+        | Color::Gray
+        | Color::LightRed
+        | Color::LightYellow
+        | Color::LightGreen
+        | Color::LightCyan
+        | Color::LightBlue
+        | Color::LightMagenta
+        | Color::Yellow
+        | Color::Cyan => Color::Black,
+        _ => Color::White,
+    }
+}
+
+/// The color as red, green and blue, if it is not one of the 16.
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(idx) if idx >= 16 => Some(indexed(idx)),
+        _ => None,
+    }
+}
+
+/// Whether a color is too close to a gray to have a hue worth keeping.
+fn is_gray((r, g, b): (u8, u8, u8)) -> bool {
+    r.max(g).max(b) - r.min(g).min(b) < 48
+}
+
+fn ansi(color: Color) -> Color {
+    match rgb(color) {
+        Some(rgb) if is_gray(rgb) => Color::Reset,
+        Some(rgb) => hue(rgb),
+        None => color,
+    }
+}
+
+fn ansi16(color: Color) -> Color {
+    let Some((r, g, b)) = rgb(color) else {
+        return color;
+    };
+    let lightness = (u16::from(r.max(g).max(b)) + u16::from(r.min(g).min(b))) / 2;
+    if is_gray((r, g, b)) {
+        return match lightness {
+            0..64 => Color::Black,
+            64..160 => Color::DarkGray,
+            160..224 => Color::Gray,
+            _ => Color::White,
+        };
+    }
+    match (hue((r, g, b)), lightness > 150) {
+        (Color::Red, true) => Color::LightRed,
+        (Color::Yellow, true) => Color::LightYellow,
+        (Color::Green, true) => Color::LightGreen,
+        (Color::Cyan, true) => Color::LightCyan,
+        (Color::Blue, true) => Color::LightBlue,
+        (Color::Magenta, true) => Color::LightMagenta,
+        (color, _) => color,
+    }
+}
+
+/// The nearest color of the 256 color palette, in its cube or among its grays.
+fn ansi256(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    let step = |v: u8| match v {
+        0..48 => 0,
+        48..115 => 1,
+        v => (v - 35) / 40,
+    };
+    let cube = 16 + 36 * step(r) + 6 * step(g) + step(b);
+    let avg = ((u16::from(r) + u16::from(g) + u16::from(b)) / 3) as u8;
+    let gray = 232 + (avg.saturating_sub(3) / 10).min(23);
+    let distance = |idx: u8| {
+        let (r2, g2, b2) = indexed(idx);
+        [(r, r2), (g, g2), (b, b2)]
+            .into_iter()
+            .map(|(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+            .sum::<i32>()
+    };
+    Color::Indexed(if distance(gray) < distance(cube) {
+        gray
+    } else {
+        cube
+    })
+}
+
+/// The nearest of red, yellow, green, cyan, blue and magenta.
+fn hue((r, g, b): (u8, u8, u8)) -> Color {
+    let max = r.max(g).max(b);
+    let delta = f32::from(max - r.min(g).min(b)).max(1.0);
+    let (rf, gf, bf) = (f32::from(r), f32::from(g), f32::from(b));
+    let hue = if max == r {
+        60.0 * ((gf - bf) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((bf - rf) / delta + 2.0)
+    } else {
+        60.0 * ((rf - gf) / delta + 4.0)
+    };
+    match hue as u16 {
+        30..90 => Color::Yellow,
+        90..150 => Color::Green,
+        150..210 => Color::Cyan,
+        210..270 => Color::Blue,
+        270..330 => Color::Magenta,
+        _ => Color::Red,
+    }
+}
+
+/// The color at an index of the 256 color palette past the first 16: a
+/// 6×6×6 cube, then 24 grays.
+fn indexed(idx: u8) -> (u8, u8, u8) {
+    if idx >= 232 {
+        let gray = 8 + (idx - 232) * 10;
+        return (gray, gray, gray);
+    }
+    let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+    let idx = idx - 16;
+    (level(idx / 36), level(idx / 6 % 6), level(idx % 6))
+}
+
+/// The `colors` of the compiled terminfo entry of `term`, looked for where
+/// ncurses looks.
+fn terminfo_colors(term: &str) -> Option<u32> {
+    let first = term.chars().next()?;
+    let var = |name| std::env::var_os(name).filter(|val| !val.is_empty());
+    let mut dirs: Vec<PathBuf> = vec![];
+    dirs.extend(var("TERMINFO").map(PathBuf::from));
+    dirs.extend(var("HOME").map(|home| Path::new(&home).join(".terminfo")));
+    if let Some(list) = var("TERMINFO_DIRS") {
+        dirs.extend(std::env::split_paths(&list).filter(|dir| !dir.as_os_str().is_empty()));
+    }
+    dirs.extend(
+        [
+            "/etc/terminfo",
+            "/lib/terminfo",
+            "/usr/share/terminfo",
+            "/usr/lib/terminfo",
+        ]
+        .map(PathBuf::from),
+    );
+    // macOS names the subdirectories by the hex code of the first letter
+    let subdirs = [first.to_string(), format!("{:x}", u32::from(first))];
+    dirs.iter()
+        .flat_map(|dir| subdirs.iter().map(move |sub| dir.join(sub).join(term)))
+        .find_map(|path| std::fs::read(path).ok())
+        .and_then(|entry| max_colors(&entry))
+}
+
+/// Reads the `colors` number out of a compiled terminfo entry, in either the
+/// legacy format or the one with 32 bit numbers.
+fn max_colors(entry: &[u8]) -> Option<u32> {
+    /// The index of `colors` among the numbers.
+    const COLORS: usize = 13;
+    let short = |at: usize| -> Option<usize> {
+        let bytes = entry.get(at..at + 2)?;
+        Some(usize::from(u16::from_le_bytes([bytes[0], bytes[1]])))
+    };
+    let width = match short(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let (names, bools, numbers) = (short(2)?, short(4)?, short(6)?);
+    if numbers <= COLORS {
+        return None;
+    }
+    // the numbers start on an even offset, after the header of six shorts
+    let start = (12 + names + bools).next_multiple_of(2) + COLORS * width;
+    let bytes = entry.get(start..start + width)?;
+    let colors = match width {
+        2 => i32::from(i16::from_le_bytes([bytes[0], bytes[1]])),
+        _ => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    };
+    u32::try_from(colors).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexed_colors() {
+        assert_eq!(indexed(16), (0, 0, 0));
+        assert_eq!(indexed(21), (0, 0, 255));
+        assert_eq!(indexed(196), (255, 0, 0));
+        assert_eq!(indexed(231), (255, 255, 255));
+        assert_eq!(indexed(232), (8, 8, 8));
+        assert_eq!(indexed(255), (238, 238, 238));
+    }
+
+    #[test]
+    fn ansi_keeps_hues_and_drops_grays() {
+        assert_eq!(ansi(Color::Rgb(220, 40, 40)), Color::Red);
+        assert_eq!(ansi(Color::Rgb(40, 200, 60)), Color::Green);
+        assert_eq!(ansi(Color::Rgb(40, 80, 220)), Color::Blue);
+        assert_eq!(ansi(Color::Rgb(230, 200, 40)), Color::Yellow);
+        assert_eq!(ansi(Color::Rgb(128, 130, 135)), Color::Reset);
+        assert_eq!(ansi(Color::Indexed(244)), Color::Reset);
+        assert_eq!(ansi(Color::Indexed(196)), Color::Red);
+        // the 16 are the terminal's own already
+        assert_eq!(ansi(Color::LightCyan), Color::LightCyan);
+    }
+
+    #[test]
+    fn same_hue_stays_readable() {
+        // the range's background and a blue text on it
+        let bg = ansi(Color::Rgb(30, 58, 138));
+        let fg = distinct(ansi(Color::Rgb(147, 197, 253)), bg);
+        assert_eq!(bg, Color::Blue);
+        assert_eq!(fg, Color::White);
+        assert_eq!(
+            distinct(Color::LightYellow, Color::LightYellow),
+            Color::Black
+        );
+        assert_eq!(distinct(Color::Red, Color::Blue), Color::Red);
+        assert_eq!(distinct(Color::Reset, Color::Reset), Color::Reset);
+    }
+
+    /// A compiled terminfo entry with `colors`, in the legacy format if
+    /// `width` is 2 or the one with 32 bit numbers if it is 4.
+    fn terminfo(width: usize, colors: i32) -> Vec<u8> {
+        let magic: u16 = if width == 2 { 0o432 } else { 0o1036 };
+        let names = b"xterm-test|test\0";
+        // an odd count, which pads the numbers onto an even offset
+        let bools = 3;
+        let mut entry = vec![];
+        for short in [magic, names.len() as u16, bools, 14, 0, 0] {
+            entry.extend(short.to_le_bytes());
+        }
+        entry.extend(names);
+        entry.extend(vec![1; usize::from(bools)]);
+        entry.resize(entry.len().next_multiple_of(2), 0);
+        for n in 0..14 {
+            let number = if n == 13 { colors } else { 80 };
+            match width {
+                2 => entry.extend((number as i16).to_le_bytes()),
+                _ => entry.extend(number.to_le_bytes()),
+            }
+        }
+        entry
+    }
+
+    #[test]
+    fn terminfo_colors_of_either_format() {
+        assert_eq!(max_colors(&terminfo(2, 256)), Some(256));
+        assert_eq!(max_colors(&terminfo(2, 8)), Some(8));
+        assert_eq!(max_colors(&terminfo(4, 0x100_0000)), Some(0x100_0000));
+        // absent
+        assert_eq!(max_colors(&terminfo(2, -1)), None);
+        assert_eq!(max_colors(b"not terminfo"), None);
+        let mut cut = terminfo(4, 256);
+        cut.truncate(cut.len() - 2);
+        assert_eq!(max_colors(&cut), None);
+    }
+
+    #[test]
+    fn ansi256_picks_cube_or_gray() {
+        assert_eq!(ansi256(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(ansi256(Color::Rgb(0, 0, 0)), Color::Indexed(16));
+        assert_eq!(ansi256(Color::Rgb(95, 135, 175)), Color::Indexed(67));
+        // grays are nearer among the 24 than in the cube
+        assert_eq!(ansi256(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        assert_eq!(ansi256(Color::Rgb(30, 30, 30)), Color::Indexed(234));
+        assert_eq!(ansi256(Color::Indexed(100)), Color::Indexed(100));
+        assert_eq!(ansi256(Color::Blue), Color::Blue);
+    }
+}
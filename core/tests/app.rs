@@ -0,0 +1,318 @@
+//! Drives the app headless: scripted keys against a repository made up for
+//! each test, checking the branch list as drawn on a `TestBackend`.
+
+use clap::Parser;
+use git2::{Repository, Signature, Time};
+use grit::{
+    app::{input::Script, App},
+    config::Config,
+    git,
+    opts::Opts,
+};
+use ratatui::{backend::TestBackend, Terminal};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Once,
+    },
+};
+
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 30;
+
+/// A repository under the temp dir, removed once the test is done with it.
+struct Fixture {
+    dir: PathBuf,
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Points the user's files at an empty dir, so that neither their config nor
+/// their git config changes what is drawn, nor the tests their caches. The
+/// environment is left alone, as the tests running alongside read it.
+fn isolate() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        let home = std::env::temp_dir().join(format!("grit-tests-{}", std::process::id()));
+        std::fs::create_dir_all(&home).unwrap();
+        grit::paths::set_base(home.clone());
+        for level in [
+            git2::ConfigLevel::System,
+            git2::ConfigLevel::XDG,
+            git2::ConfigLevel::Global,
+        ] {
+            // SAFETY: every test gets here before it opens a repository, and
+            // waits for the first to be done setting these
+            unsafe { git2::opts::set_search_path(level, &home).unwrap() };
+        }
+    });
+}
+
+/// Makes a repository with `main` and a branch of one commit on top of it for
+/// each of `branches`, committed that many hours ago.
+fn fixture(branches: &[(&str, i64)]) -> Fixture {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    isolate();
+    let dir = std::env::temp_dir().join(format!(
+        "grit-test-{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    let repo = Repository::init(&dir).unwrap();
+    repo.set_head("refs/heads/main").unwrap();
+    let now = chrono::Local::now().timestamp();
+    let sig = |hours: i64| {
+        Signature::new("Ada", "ada@example.com", &Time::new(now - hours * 3600, 0)).unwrap()
+    };
+    let commit = |parent: Option<git2::Oid>, summary: &str, hours: i64| {
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let parents: Vec<_> = parent
+            .map(|p| repo.find_commit(p).unwrap())
+            .into_iter()
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(None, &sig(hours), &sig(hours), summary, &tree, &parents)
+            .unwrap()
+    };
+    let base = commit(None, "initial commit", 1000);
+    repo.reference("refs/heads/main", base, true, "main")
+        .unwrap();
+    for (name, hours) in branches {
+        let tip = commit(Some(base), &format!("work on {name}"), *hours);
+        repo.branch(name, &repo.find_commit(tip).unwrap(), false)
+            .unwrap();
+    }
+    Fixture { dir }
+}
+
+/// Runs the app on `fixture` until `script` is done and all it started has
+/// finished, returning the screen it leaves behind.
+fn run(fixture: &Fixture, script: Script) -> Vec<String> {
+    run_at(&fixture.dir.join(".git"), Some(&fixture.dir), script)
+}
+
+/// Runs the app on the repository at `git_dir`, with the work tree it records
+/// unless `work_tree` is given.
+fn run_at(git_dir: &Path, work_tree: Option<&Path>, script: Script) -> Vec<String> {
+    let mut args = vec![Path::new("grit"), Path::new("--git-dir"), git_dir];
+    if let Some(work_tree) = work_tree {
+        args.extend([Path::new("--work-tree"), work_tree]);
+    }
+    let opts = Opts::parse_from(args);
+    let mut config = Config::default();
+    config.view.hints = Some(false);
+    let mut app = App::new(&opts, config).unwrap().with_input(script);
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    app.run(&mut terminal).unwrap();
+    app.draw_loaded(&mut terminal).unwrap();
+    let buf = terminal.backend().buffer();
+    (0..buf.area.height)
+        .map(|y| {
+            let row: String = (0..buf.area.width)
+                .map(|x| buf.get(x, y).symbol())
+                .collect();
+            row.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// The names in the branch list, top to bottom, with `>` before the selected one.
+fn branches(screen: &[String]) -> Vec<String> {
+    screen
+        .iter()
+        .filter_map(|row| {
+            let (selected, rest) = match row.strip_prefix('>') {
+                Some(rest) => (true, rest),
+                None => (false, row.as_str()),
+            };
+            let name = rest.split_whitespace().next()?;
+            KNOWN.contains(&name).then(|| match selected {
+                true => format!("> {name}"),
+                false => name.to_string(),
+            })
+        })
+        .collect()
+}
+
+const KNOWN: &[&str] = &["main", "alpha", "bravo", "charlie-fix", "delta-fix"];
+
+/// The row of `screen` that starts with `start`, split where two or more
+/// spaces keep its parts apart.
+fn row<'a>(screen: &'a [String], start: &str) -> Vec<&'a str> {
+    let row = screen
+        .iter()
+        .find(|row| row.starts_with(start))
+        .unwrap_or_else(|| panic!("no row starts with {start:?}: {screen:#?}"));
+    row.split("  ")
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// The history pane, one `author: summary` per commit, newest first.
+fn history(screen: &[String]) -> Vec<String> {
+    screen
+        .iter()
+        .skip_while(|row| !row.starts_with("authors:"))
+        .skip(1)
+        .take_while(|row| !row.is_empty())
+        .map(|row| {
+            // after the date, which has colons of its own
+            let (_, rest) = row.split_once(": ").unwrap();
+            rest.to_string()
+        })
+        .collect()
+}
+
+/// Adds a linked worktree with `branch` checked out to `fixture`, returning its
+/// git dir and work tree. It is removed along with the fixture.
+fn linked(fixture: &Fixture, branch: &str) -> (PathBuf, PathBuf) {
+    let repo = Repository::open(&fixture.dir).unwrap();
+    let path = fixture.dir.join("linked");
+    let reference = repo
+        .find_reference(&format!("refs/heads/{branch}"))
+        .unwrap();
+    let mut opts = git2::WorktreeAddOptions::new();
+    opts.reference(Some(&reference));
+    repo.worktree("linked", &path, Some(&opts)).unwrap();
+    (fixture.dir.join(".git/worktrees/linked"), path)
+}
+
+fn four() -> Fixture {
+    fixture(&[
+        ("bravo", 5),
+        ("alpha", 30),
+        ("delta-fix", 1),
+        ("charlie-fix", 12),
+    ])
+}
+
+#[test]
+fn lists_the_newest_branches_first() {
+    let screen = run(&four(), Script::new());
+    assert_eq!(
+        branches(&screen),
+        ["> delta-fix", "bravo", "charlie-fix", "alpha", "main"],
+        "{screen:#?}"
+    );
+}
+
+#[test]
+fn sorts_by_name_keeping_the_selection() {
+    // from newest first through farthest from upstream first
+    let screen = run(&four(), Script::new().keys("s s"));
+    assert_eq!(
+        branches(&screen),
+        ["alpha", "bravo", "charlie-fix", "> delta-fix", "main"],
+        "{screen:#?}"
+    );
+}
+
+#[test]
+fn sorts_by_name_descending_then_oldest_first() {
+    let fixture = four();
+    let descending = run(&fixture, Script::new().keys("s s s"));
+    assert_eq!(
+        branches(&descending),
+        ["main", "> delta-fix", "charlie-fix", "bravo", "alpha"],
+        "{descending:#?}"
+    );
+    let oldest = run(&fixture, Script::new().keys("s s s s"));
+    assert_eq!(
+        branches(&oldest),
+        ["main", "alpha", "charlie-fix", "bravo", "> delta-fix"],
+        "{oldest:#?}"
+    );
+}
+
+#[test]
+fn filters_by_name() {
+    let screen = run(&four(), Script::new().keys("/").text("fix").keys("enter"));
+    assert_eq!(
+        branches(&screen),
+        ["> delta-fix", "charlie-fix"],
+        "{screen:#?}"
+    );
+    assert_eq!(
+        row(&screen, "Branches"),
+        ["Branches matching fix", "1/2 branches (3 hidden)"],
+        "{screen:#?}"
+    );
+}
+
+#[test]
+fn moves_the_selection() {
+    let fixture = four();
+    let down = run(&fixture, Script::new().keys("j j"));
+    assert_eq!(
+        branches(&down),
+        ["delta-fix", "bravo", "> charlie-fix", "alpha", "main"],
+        "{down:#?}"
+    );
+    let last = run(&fixture, Script::new().keys("G"));
+    assert_eq!(
+        branches(&last),
+        ["delta-fix", "bravo", "charlie-fix", "alpha", "> main"],
+        "{last:#?}"
+    );
+    let back = run(&fixture, Script::new().keys("G k k g g"));
+    assert_eq!(
+        branches(&back),
+        ["> delta-fix", "bravo", "charlie-fix", "alpha", "main"],
+        "{back:#?}"
+    );
+}
+
+#[test]
+fn shows_the_history_of_the_selected_branch() {
+    let screen = run(&four(), Script::new().keys("j"));
+    assert_eq!(
+        history(&screen),
+        ["Ada: work on bravo", "Ada: initial commit"],
+        "{screen:#?}"
+    );
+}
+
+#[test]
+fn follows_the_head_and_status_of_a_linked_worktree() {
+    let fixture = four();
+    let (git_dir, work_tree) = linked(&fixture, "bravo");
+    let repo = git::Repository::open(&git::Target {
+        git_dir: Some(git_dir),
+        ..Default::default()
+    })
+    .unwrap();
+    let location = repo.location();
+    assert!(location.is_linked());
+    assert_eq!(location.common_dir(), fixture.dir.join(".git"));
+    assert_eq!(location.work_tree.as_deref(), Some(work_tree.as_path()));
+    assert_eq!(repo.head_branch().as_deref(), Some("bravo"));
+    std::fs::write(work_tree.join("notes"), "todo").unwrap();
+    assert_eq!(repo.status().unwrap().modified, 1);
+    std::fs::write(fixture.dir.join("other"), "todo").unwrap();
+    assert_eq!(repo.status().unwrap().modified, 1);
+    let worktrees = repo.worktrees().unwrap();
+    assert_eq!(worktrees.keys().collect::<Vec<_>>(), ["main"]);
+
+    repo.checkout("alpha", git::BranchType::Local).unwrap();
+    assert_eq!(repo.head_branch().as_deref(), Some("alpha"));
+    let main = Repository::open(&fixture.dir).unwrap();
+    assert_eq!(main.head().unwrap().shorthand(), Some("main"));
+}
+
+#[test]
+fn marks_the_branch_of_the_main_worktree_from_a_linked_one() {
+    let fixture = four();
+    let (git_dir, _) = linked(&fixture, "bravo");
+    let screen = run_at(&git_dir, None, Script::new());
+    let main = format!("main ⌂ {}", fixture.dir.display());
+    assert_eq!(row(&screen, "   main"), [main.as_str()], "{screen:#?}");
+    assert_eq!(row(&screen, "   bravo"), ["bravo"], "{screen:#?}");
+}
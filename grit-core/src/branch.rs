@@ -0,0 +1,621 @@
+use super::{
+    trailer::{self, Trailer},
+    Repository,
+};
+use crate::{
+    error::{Context, Error, Result},
+    replace::Replacements,
+};
+use chrono::{DateTime, Utc};
+use git2::BranchType;
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+};
+
+#[derive(Clone)]
+pub struct Branch {
+    repo: Repository,
+    pub name: String,
+    pub typ: BranchType,
+    pub commits: Vec<Commit>,
+    /// How a local branch relates to its upstream, once loaded.
+    pub tracking: Option<Tracking>,
+    /// The short name of the upstream of a local branch, e.g. `origin/main`,
+    /// once loaded.
+    pub upstream: Option<String>,
+    /// Whether `commits` only holds a tip commit known from before, rather than
+    /// the history, which [`Branch::load`] reads.
+    pub partial: bool,
+    /// Whether the history reaches a commit a shallow clone stops at, so that
+    /// older commits are missing rather than absent.
+    pub truncated: bool,
+    /// The branch a symbolic ref such as `origin/HEAD` points at, e.g.
+    /// `origin/main`. It has no commits if that branch is gone.
+    pub symbolic: Option<String>,
+}
+
+/// What tells stale local branches apart, as
+/// [`Repository::stale`](crate::Repository::stale) reads it.
+#[derive(Clone, Debug, Default)]
+pub struct Stale {
+    /// The local name of the default branch, which is never stale.
+    pub root: Option<String>,
+    /// The local branches the default branch contains.
+    pub merged: HashSet<String>,
+    /// Tips older than this, in Unix seconds, are old.
+    pub before: i64,
+}
+
+/// Reads what tells the stale local branches apart, with tips older than
+/// `before` old, of those whose full ref names are in `only` if it is given.
+/// The branches to read when there are too many to read them all: those
+/// committed to last, and those wanted whatever their tip.
+#[derive(Clone, Debug, Default)]
+pub struct Recent {
+    pub typ: Option<BranchType>,
+    /// How many of the branches committed to last.
+    pub count: usize,
+    /// The full ref names of branches read anyway.
+    pub keep: HashSet<String>,
+}
+
+impl Recent {
+    /// The full ref names of the branches picked, ranked by their tips alone.
+    pub(crate) fn pick(&self, repo: &git2::Repository) -> Result<HashSet<String>> {
+        let mut tips: Vec<(i64, String)> = vec![];
+        for br_res in repo.branches(self.typ).context("repo branches")? {
+            let (br, _) = br_res.context("branch")?;
+            let Some(refname) = br.get().name().map(ToOwned::to_owned) else {
+                continue;
+            };
+            let time = br.get().peel_to_commit().map_or(0, |c| c.time().seconds());
+            tips.push((time, refname));
+        }
+        tips.sort_unstable_by(|a, b| b.cmp(a));
+        let mut picked: HashSet<String> = tips
+            .into_iter()
+            .take(self.count)
+            .map(|(_, refname)| refname)
+            .collect();
+        picked.extend(self.keep.iter().cloned());
+        Ok(picked)
+    }
+}
+
+pub(crate) fn stale(
+    repo: &git2::Repository,
+    before: i64,
+    only: Option<&HashSet<String>>,
+) -> Result<Stale> {
+    let Some((name, base)) = super::stats::default_branch(repo) else {
+        return Ok(Stale {
+            before,
+            ..Stale::default()
+        });
+    };
+    let root = name.split_once('/').map_or(name.as_str(), |(_, n)| n);
+    let mut merged = HashSet::new();
+    for entry in repo
+        .branches(Some(BranchType::Local))
+        .context("list branches")?
+    {
+        let (branch, _) = entry.context("branch")?;
+        let (Some(name), Some(tip)) = (branch.name().ok().flatten(), branch.get().target()) else {
+            continue;
+        };
+        let refname = branch.get().name().unwrap_or_default();
+        if only.is_some_and(|only| !only.contains(refname)) {
+            continue;
+        }
+        if name != root && repo.merge_base(tip, base).ok() == Some(tip) {
+            merged.insert(name.to_string());
+        }
+    }
+    Ok(Stale {
+        root: Some(root.to_string()),
+        merged,
+        before,
+    })
+}
+
+/// Why a local branch is stale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Staleness {
+    /// The default branch contains it.
+    Merged,
+    /// Nothing was committed on it for a while.
+    Old,
+}
+
+impl Staleness {
+    pub fn label(self) -> &'static str {
+        match self {
+            Staleness::Merged => "merged",
+            Staleness::Old => "old",
+        }
+    }
+}
+
+/// The history of a branch, as [`Branch::stream_history`] reads it.
+#[derive(Clone)]
+pub struct History {
+    commits: Vec<Commit>,
+    truncated: bool,
+}
+
+impl History {
+    /// A history read before, e.g. from a cache.
+    pub fn new(commits: Vec<Commit>, truncated: bool) -> Self {
+        Self { commits, truncated }
+    }
+
+    /// The commit the branch pointed at when it was read.
+    pub fn tip(&self) -> Option<git2::Oid> {
+        self.commits.first().map(|c| c.id)
+    }
+}
+
+/// Looks up the history a branch had when its ref, by full name, pointed at
+/// the given tip, such as from a cache.
+pub type Cached = Box<dyn Fn(&str, git2::Oid) -> Option<History> + Send>;
+
+/// How a local branch relates to its upstream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tracking {
+    UpToDate,
+    Ahead(usize),
+    Behind(usize),
+    Diverged {
+        ahead: usize,
+        behind: usize,
+    },
+    /// No upstream is configured, or it no longer exists.
+    NoUpstream,
+}
+
+impl Tracking {
+    /// Reads the status of `branch` against its upstream.
+    pub(super) fn of(repo: &git2::Repository, branch: &git2::Branch) -> Tracking {
+        let Ok(upstream) = branch.upstream() else {
+            return Tracking::NoUpstream;
+        };
+        let ids = branch.get().target().zip(upstream.get().target());
+        let Some((ahead, behind)) = ids.and_then(|(l, u)| repo.graph_ahead_behind(l, u).ok())
+        else {
+            return Tracking::NoUpstream;
+        };
+        match (ahead, behind) {
+            (0, 0) => Tracking::UpToDate,
+            (ahead, 0) => Tracking::Ahead(ahead),
+            (0, behind) => Tracking::Behind(behind),
+            (ahead, behind) => Tracking::Diverged { ahead, behind },
+        }
+    }
+}
+
+impl Display for Branch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Branch {
+    pub fn new(repo: &Repository, name: impl AsRef<str>, typ: BranchType) -> Self {
+        let commits = Vec::default();
+        let name = name.as_ref().to_string();
+        Self {
+            repo: repo.clone(),
+            name,
+            typ,
+            commits,
+            tracking: None,
+            upstream: None,
+            partial: false,
+            truncated: false,
+            symbolic: None,
+        }
+    }
+
+    pub fn commits(&self) -> &[Commit] {
+        self.commits.as_ref()
+    }
+
+    /// The full name of the ref, e.g. `refs/heads/main` or
+    /// `refs/remotes/origin/main`.
+    pub fn refname(&self) -> String {
+        super::refname::full(&self.name, self.typ)
+    }
+
+    /// The commit the branch pointed at when it was read.
+    pub fn tip(&self) -> Option<git2::Oid> {
+        self.commits.first().map(|c| c.id)
+    }
+
+    /// Whether a local branch is stale, and why.
+    pub fn staleness(&self, stale: &Stale) -> Option<Staleness> {
+        if self.typ != BranchType::Local || stale.root.as_ref() == Some(&self.name) {
+            return None;
+        }
+        if stale.merged.contains(&self.name) {
+            return Some(Staleness::Merged);
+        }
+        let tip = self.commits.first()?;
+        (tip.timestamp.epoch() < stale.before).then_some(Staleness::Old)
+    }
+
+    /// The authors of the loaded commits with their commit counts, most active first.
+    pub fn contributors(&self) -> Vec<(&str, usize)> {
+        let mut counts: Vec<(&str, usize)> = vec![];
+        for commit in &self.commits {
+            let name = commit.author.name.as_deref().unwrap_or("?");
+            match counts.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((name, 1)),
+            }
+        }
+        // stable, so ties keep the order of most recent activity
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        counts
+    }
+
+    /// Loads the latest commits for this branch
+    pub fn load(&mut self) -> Result<()> {
+        let name = self.name.clone();
+        let typ = self.typ;
+        let (max, replaced) = (self.repo.max_commits(), self.repo.replace());
+        (self.commits, self.truncated) = self
+            .repo
+            .call(move |repo| walk(repo, &name, typ, max, replaced, &|| false))??;
+        self.partial = false;
+        Ok(())
+    }
+
+    /// Reads the history like [`Branch::load`], but without waiting for it. It
+    /// arrives on the returned channel, for [`Branch::set_history`], unless
+    /// `cancelled` returns true before it is read, e.g. because the selection
+    /// moved on, which ends it with [`Error::Cancelled`].
+    pub fn stream_history(
+        &self,
+        cancelled: impl Fn() -> bool + Send + 'static,
+    ) -> mpsc::Receiver<Result<History>> {
+        let (tx, rx) = mpsc::channel();
+        let (name, typ) = (self.name.clone(), self.typ);
+        let (max, replaced) = (self.repo.max_commits(), self.repo.replace());
+        let err_tx = tx.clone();
+        if let Err(err) = self.repo.submit(move |repo| {
+            let history = walk(repo, &name, typ, max, replaced, &cancelled)
+                .map(|(commits, truncated)| History { commits, truncated });
+            let _ = tx.send(history);
+        }) {
+            let _ = err_tx.send(Err(err));
+        }
+        rx
+    }
+
+    pub fn set_history(&mut self, history: History) {
+        (self.commits, self.truncated) = (history.commits, history.truncated);
+        self.partial = false;
+    }
+}
+
+/// The short name of the branch a symbolic ref points at.
+pub(super) fn symbolic_target(reference: &git2::Reference) -> Option<String> {
+    let target = reference.symbolic_target()?;
+    let short = ["refs/remotes/", "refs/heads/"]
+        .iter()
+        .find_map(|prefix| target.strip_prefix(prefix));
+    Some(short.unwrap_or(target).to_string())
+}
+
+/// Reads the latest commits reachable from the named branch, through rewritten
+/// history if `replaced`, and whether they reach the boundary of a shallow
+/// clone.
+pub(super) fn walk(
+    repo: &git2::Repository,
+    name: &str,
+    typ: BranchType,
+    max: usize,
+    replaced: bool,
+    cancelled: &dyn Fn() -> bool,
+) -> Result<(Vec<Commit>, bool)> {
+    // queued behind other reads, it may not be wanted anymore by the time
+    if cancelled() {
+        return Err(Error::Cancelled(format!("load {name}")));
+    }
+    let branch = find(repo, name, typ)?;
+    let head = branch.get();
+    // a symbolic ref to a branch that is gone, e.g. the old default branch
+    if head.symbolic_target().is_some() && head.resolve().is_err() {
+        return Ok((vec![], false));
+    }
+    let commit = head.peel_to_commit().context("get commit for ref")?;
+    let replacements = Replacements::read(repo)?;
+    let commits = match replacements.is_empty() {
+        true => revwalk(repo, commit.id(), max, cancelled)?,
+        false => crate::replace::walk(repo, commit.id(), &replacements, replaced, max)?,
+    };
+    let truncated = repo.is_shallow() && {
+        let boundary = crate::shallow::boundary(repo);
+        commits.iter().any(|c| boundary.contains(&c.id))
+    };
+    Ok((commits, truncated))
+}
+
+fn revwalk(
+    repo: &git2::Repository,
+    tip: git2::Oid,
+    max: usize,
+    cancelled: &dyn Fn() -> bool,
+) -> Result<Vec<Commit>> {
+    let mut revwalk = repo.revwalk().context("revwalk")?;
+    revwalk.push(tip).context("revwalk push commit")?;
+    revwalk
+        .take(max)
+        .map(|sha| -> Result<Commit> {
+            if cancelled() {
+                return Err(Error::Cancelled(format!("walk from {tip}")));
+            }
+            let sha = sha.context("revwalk sha")?;
+            let cmt = repo.find_commit(sha).context("find commit")?;
+            let mut commit: Commit = cmt.try_into().context("get commit")?;
+            commit.note = note(repo, sha);
+            Ok(commit)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .context("get commits")
+}
+
+/// Looks up a branch, reporting a missing one as [`Error::BranchNotFound`].
+pub(crate) fn find<'r>(
+    repo: &'r git2::Repository,
+    name: &str,
+    typ: BranchType,
+) -> Result<git2::Branch<'r>> {
+    repo.find_branch(name, typ).map_err(|err| match err.code() {
+        git2::ErrorCode::NotFound => Error::BranchNotFound(name.to_string()),
+        _ => err.into(),
+    })
+}
+
+/// How many hex digits [`short_id`] keeps, as set by [`use_abbrev`].
+static ABBREV: AtomicUsize = AtomicUsize::new(MIN_ABBREV);
+
+/// The fewest hex digits git abbreviates an id to by default.
+const MIN_ABBREV: usize = 7;
+
+/// Abbreviates `id` to the length [`use_abbrev`] found for the repository.
+/// Unlike git, an abbreviation that turns out to be ambiguous is not made
+/// longer, which would mean looking up every id.
+pub(crate) fn short_id(id: git2::Oid) -> String {
+    let mut short = id.to_string();
+    short.truncate(ABBREV.load(Ordering::Relaxed));
+    short
+}
+
+/// Finds the length ids of `repo` are abbreviated to once, as git does for
+/// `core.abbrev`: a number, `no` for whole ids, or by default long enough for
+/// the number of objects to be unlikely to share a prefix.
+pub(crate) fn use_abbrev(repo: &git2::Repository) {
+    let configured = repo
+        .config()
+        .and_then(|config| config.get_string("core.abbrev"))
+        .ok();
+    let len = match configured.as_deref() {
+        Some("no" | "false") => 40,
+        Some(value) => match value.parse::<usize>() {
+            Ok(len) => len.clamp(4, 40),
+            Err(_) => auto_abbrev(repo.path()),
+        },
+        None => auto_abbrev(repo.path()),
+    };
+    ABBREV.store(len, Ordering::Relaxed);
+}
+
+/// Two hex digits per doubling of the object count, as `core.abbrev=auto`.
+fn auto_abbrev(git_dir: &Path) -> usize {
+    let count = object_count(git_dir);
+    let bits = (usize::BITS - count.leading_zeros()) as usize;
+    bits.div_ceil(2).max(MIN_ABBREV)
+}
+
+/// Roughly how many objects the repository at `git_dir` holds: those in its
+/// packs, counted from their indexes, and the loose ones, guessed from one of
+/// the 256 directories they are spread over as git does.
+fn object_count(git_dir: &Path) -> usize {
+    let objects = crate::command::common_dir(git_dir).join("objects");
+    let packed: usize = std::fs::read_dir(objects.join("pack"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "idx"))
+        .filter_map(|path| pack_count(&path))
+        .sum();
+    let loose = std::fs::read_dir(objects.join("17")).map_or(0, |dir| dir.count());
+    packed + loose * 256
+}
+
+/// The number of objects in the pack index at `path`: the last entry of its
+/// fanout table, which follows a header in version 2.
+fn pack_count(path: &Path) -> Option<usize> {
+    let idx = std::fs::read(path).ok()?;
+    let fanout = if idx.starts_with(b"\xfftOc") { 8 } else { 0 };
+    let last = fanout + 255 * 4;
+    let bytes = idx.get(last..last + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?) as usize)
+}
+
+/// Fails with [`Error::BranchExists`] if there is a local branch `name`.
+pub(crate) fn expect_free(repo: &git2::Repository, name: &str) -> Result<()> {
+    match repo.find_branch(name, BranchType::Local) {
+        Ok(_) => Err(Error::BranchExists(name.to_string())),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Fails with [`Error::Moved`] unless `branch` still points at `expected`, so
+/// that a mutation does not act on what was shown if that is stale.
+pub(crate) fn expect_tip(branch: &git2::Branch, name: &str, expected: git2::Oid) -> Result<()> {
+    match branch.get().target() {
+        Some(tip) if tip == expected => Ok(()),
+        _ => Err(Error::Moved(name.to_string())),
+    }
+}
+
+/// Whether the commits of a local branch are all in its upstream, or in `HEAD`
+/// if it has none, as `git branch -d` decides before deleting.
+pub(crate) fn is_merged(repo: &git2::Repository, branch: &git2::Branch) -> Result<bool> {
+    let Some(tip) = branch.get().target() else {
+        return Ok(true);
+    };
+    let base = match branch.upstream() {
+        Ok(upstream) => upstream.get().target(),
+        Err(_) => repo.head().ok().and_then(|head| head.target()),
+    };
+    let Some(base) = base else {
+        return Ok(false);
+    };
+    Ok(tip == base || repo.graph_descendant_of(base, tip)?)
+}
+
+/// The note attached to a commit under the default notes ref.
+pub(super) fn note(repo: &git2::Repository, id: git2::Oid) -> Option<String> {
+    let note = repo.find_note(None, id).ok()?;
+    note.message().map(|m| m.trim_end().to_owned())
+}
+
+#[derive(Clone)]
+pub struct Commit {
+    pub id: git2::Oid,
+    /// `id` abbreviated to as many digits as `core.abbrev` asks for.
+    pub short: String,
+    pub summary: String,
+    pub message: String,
+    pub author: Author,
+    pub timestamp: Timestamp,
+    /// The note from `refs/notes/commits`, if any.
+    pub note: Option<String>,
+    pub trailers: Vec<Trailer>,
+    /// How the commit differs from what is in the object database, when shown
+    /// through rewritten history.
+    pub replacement: Option<Replacement>,
+}
+
+/// How rewritten history changes a commit.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Replacement {
+    /// It stands in for this commit, by a ref under `refs/replace/`, shown
+    /// abbreviated.
+    Object { id: git2::Oid, short: String },
+    /// It has other parents than it records, by a graft.
+    Graft,
+}
+
+impl Commit {
+    /// A commit as read before, e.g. from a cache, with its trailers parsed
+    /// from `message` again.
+    pub fn cached(
+        id: git2::Oid,
+        short: String,
+        summary: String,
+        message: String,
+        author: Author,
+        epoch: i64,
+        note: Option<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            id,
+            short,
+            summary,
+            trailers: trailer::parse(&message),
+            message,
+            author,
+            timestamp: git2::Time::new(epoch, 0).try_into()?,
+            note,
+            replacement: None,
+        })
+    }
+
+    /// The names of the co-authors credited in `Co-authored-by` trailers.
+    pub fn co_authors(&self) -> impl Iterator<Item = &str> {
+        self.trailers
+            .iter()
+            .filter(|t| t.is("Co-authored-by"))
+            .map(Trailer::name)
+    }
+}
+
+impl TryFrom<git2::Commit<'_>> for Commit {
+    type Error = Error;
+    fn try_from(commit: git2::Commit<'_>) -> Result<Self, Self::Error> {
+        let summary = commit.summary().map(ToOwned::to_owned).unwrap_or_default();
+        let message = commit.message().map(ToOwned::to_owned).unwrap_or_default();
+        let trailers = trailer::parse(&message);
+        let author = commit.author().into();
+        let timestamp = commit.time().try_into()?;
+        Ok(Self {
+            id: commit.id(),
+            short: short_id(commit.id()),
+            summary,
+            message,
+            author,
+            timestamp,
+            note: None,
+            trailers,
+            replacement: None,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct Timestamp {
+    epoch: i64,
+    dt: DateTime<Utc>,
+}
+
+impl Timestamp {
+    pub fn epoch(&self) -> i64 {
+        self.epoch
+    }
+    fn format(&self) -> impl Display {
+        self.dt.format("%m/%d/%Y %H:%M:%S")
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.format().fmt(f)
+    }
+}
+
+impl TryFrom<git2::Time> for Timestamp {
+    type Error = Error;
+    fn try_from(value: git2::Time) -> Result<Self, Self::Error> {
+        let epoch = value.seconds();
+        let dt = DateTime::from_timestamp(epoch, 0)
+            .with_context(|| format!("no timestamp available for epoch {epoch}"))?;
+        let ts = Self { epoch, dt };
+        Ok(ts)
+    }
+}
+
+#[derive(Clone)]
+pub struct Author {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+impl From<git2::Signature<'_>> for Author {
+    fn from(sig: git2::Signature<'_>) -> Self {
+        Self {
+            name: sig.name().map(ToOwned::to_owned),
+            email: sig.email().map(ToOwned::to_owned),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use crate::error::{self, Context, Error, Result};
+use std::{
+    ffi::OsString,
+    io::Read,
+    path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, Mutex, OnceLock},
+    thread,
+};
+
+/// Environment for the commands that talk to a remote, such as the program ssh
+/// asks for passphrases.
+static REMOTE_ENV: OnceLock<Vec<(String, OsString)>> = OnceLock::new();
+
+/// Environment for every command, for those that sign commits, such as the
+/// terminal gpg asks for the passphrase on.
+static SIGN_ENV: OnceLock<Vec<(String, OsString)>> = OnceLock::new();
+
+/// The locations needed to point the git CLI at a repository.
+#[derive(Clone, Debug)]
+pub struct Location {
+    pub git_dir: PathBuf,
+    pub work_tree: Option<PathBuf>,
+    /// Whether git is told to leave replace refs out, as history is shown.
+    pub no_replace: bool,
+}
+
+impl Location {
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new("git");
+        cmd.arg("--git-dir").arg(&self.git_dir);
+        if let Some(env) = SIGN_ENV.get() {
+            cmd.envs(env.iter().map(|(k, v)| (k, v)));
+        }
+        if let Some(work_tree) = &self.work_tree {
+            cmd.arg("--work-tree").arg(work_tree);
+        }
+        // rather than an option, so that the git the hooks run sees it too
+        if self.no_replace {
+            cmd.env("GIT_NO_REPLACE_OBJECTS", "1");
+        }
+        // rather than the working directory of grit, which it leaves alone
+        cmd.current_dir(self.dir());
+        cmd
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.work_tree.as_deref().unwrap_or(&self.git_dir)
+    }
+
+    /// The git dir that linked work trees share with the main one.
+    pub fn common_dir(&self) -> PathBuf {
+        common_dir(&self.git_dir)
+    }
+
+    /// Whether this is a linked work tree, whose HEAD, index and status are
+    /// its own while the refs and config are shared with the main one.
+    pub fn is_linked(&self) -> bool {
+        is_linked(&self.git_dir)
+    }
+}
+
+/// Whether `git_dir` is that of a linked work tree, `worktrees/<name>` in the
+/// main git dir.
+pub(crate) fn is_linked(git_dir: &Path) -> bool {
+    git_dir.join("commondir").is_file()
+}
+
+/// The git dir that the linked work tree of `git_dir` shares with the main
+/// one, or `git_dir` itself if it is the main one.
+pub(crate) fn common_dir(git_dir: &Path) -> PathBuf {
+    let Ok(dir) = std::fs::read_to_string(git_dir.join("commondir")) else {
+        return git_dir.to_path_buf();
+    };
+    // usually `../..`, resolved so that paths under it can be told apart
+    let mut common = PathBuf::new();
+    for component in git_dir.join(dir.trim()).components() {
+        match component {
+            Component::ParentDir => {
+                common.pop();
+            }
+            component => common.push(component),
+        }
+    }
+    common
+}
+
+/// The work tree of the linked work tree whose git dir is `git_dir`, which
+/// records where its `.git` file is.
+pub(crate) fn linked_work_tree(git_dir: &Path) -> Option<PathBuf> {
+    let dot_git = std::fs::read_to_string(git_dir.join("gitdir")).ok()?;
+    Path::new(dot_git.trim()).parent().map(Path::to_path_buf)
+}
+
+/// Runs a git subcommand, polling `cancelled` so that long running commands can be
+/// interrupted. Returns the combined output on success.
+pub fn run(loc: &Location, args: &[&str], cancelled: impl Fn() -> bool) -> Result<String> {
+    let mut cmd = loc.command();
+    cmd.args(args);
+    let what = format!("git {}", args.join(" "));
+    capture(cmd, &what, cancelled)
+}
+
+/// Sets the environment of the commands run with [`remote`]. Only the first
+/// call has an effect.
+pub fn set_remote_env(env: Vec<(String, OsString)>) {
+    let _ = REMOTE_ENV.set(env);
+}
+
+/// Sets the environment of the commands that may sign commits. Only the
+/// first call has an effect.
+pub fn set_sign_env(env: Vec<(String, OsString)>) {
+    let _ = SIGN_ENV.set(env);
+}
+
+/// Like [`run`], for commands that talk to a remote and may have to ask for a
+/// passphrase or about a host key along the way.
+pub fn remote(loc: &Location, args: &[&str], cancelled: impl Fn() -> bool) -> Result<String> {
+    remote_progress(loc, args, cancelled, |_| {})
+}
+
+/// Like [`remote`], passing `progress` each line of progress the command
+/// reports, such as `Receiving objects:  45% (45/100)`, as it comes. The
+/// command has to be told to report it, as with `--progress` for a fetch.
+pub fn remote_progress(
+    loc: &Location,
+    args: &[&str],
+    cancelled: impl Fn() -> bool,
+    progress: impl Fn(&str),
+) -> Result<String> {
+    let mut cmd = loc.command();
+    cmd.args(args);
+    if let Some(env) = REMOTE_ENV.get() {
+        cmd.envs(env.iter().map(|(k, v)| (k, v)));
+    }
+    let what = format!("git {}", args.join(" "));
+    capture_progress(cmd, &what, cancelled, progress)
+}
+
+/// Runs `cmd` with its output captured, killing it if `cancelled` returns true.
+/// `what` describes the command in errors.
+pub fn capture(cmd: Command, what: &str, cancelled: impl Fn() -> bool) -> Result<String> {
+    capture_progress(cmd, what, cancelled, |_| {})
+}
+
+fn capture_progress(
+    mut cmd: Command,
+    what: &str,
+    cancelled: impl Fn() -> bool,
+    progress: impl Fn(&str),
+) -> Result<String> {
+    let _span = tracing::debug_span!("git", cmd = what).entered();
+    let started = std::time::Instant::now();
+    tracing::debug!("started");
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("spawn {what}"))?;
+    let stdout = drain(child.stdout.take());
+    let latest = Arc::new(Mutex::new(String::new()));
+    let stderr = drain_progress(child.stderr.take(), latest.clone());
+    let mut reported = String::new();
+    let status = loop {
+        if let Ok(line) = latest.lock() {
+            if *line != reported {
+                reported.clone_from(&line);
+                progress(&reported);
+            }
+        }
+        if let Some(status) = child
+            .try_wait()
+            .with_context(|| format!("wait for {what}"))?
+        {
+            break status;
+        }
+        if cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            tracing::info!("cancelled");
+            return Err(Error::Cancelled(what.to_string()));
+        }
+        thread::sleep(std::time::Duration::from_millis(50));
+    };
+    let out = stdout.join().unwrap_or_default();
+    let err = stderr.join().unwrap_or_default();
+    let elapsed_ms = started.elapsed().as_millis();
+    if !status.success() {
+        // some commands, like a conflicting `stash pop`, explain themselves on stdout
+        let msg = if err.trim().is_empty() { &out } else { &err };
+        tracing::warn!(%status, elapsed_ms, output = msg.trim(), "failed");
+        return Err(error::classify(what, msg));
+    }
+    tracing::debug!(elapsed_ms, "finished");
+    Ok(format!("{out}{err}"))
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Like [`drain`], keeping `latest` at the last line written so far. Progress
+/// is redrawn in place, with lines ended by `\r` rather than `\n`, of which
+/// only the last one of each `\n` ended line is kept in the output, as a
+/// terminal would show it.
+fn drain_progress(
+    pipe: Option<impl Read + Send + 'static>,
+    latest: Arc<Mutex<String>>,
+) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut out = String::new();
+        let Some(mut pipe) = pipe else {
+            return out;
+        };
+        let mut line = vec![];
+        // the last of the lines redrawn in place since the last `\n`
+        let mut shown = vec![];
+        let mut chunk = [0; 4096];
+        while let Ok(n) = pipe.read(&mut chunk) {
+            if n == 0 {
+                break;
+            }
+            for &byte in &chunk[..n] {
+                match byte {
+                    b'\r' | b'\n' => {
+                        if !line.is_empty() {
+                            shown = std::mem::take(&mut line);
+                            let text = String::from_utf8_lossy(&shown);
+                            if let Ok(mut latest) = latest.lock() {
+                                *latest = text.trim().to_string();
+                            }
+                        }
+                        if byte == b'\n' {
+                            out.push_str(&String::from_utf8_lossy(&shown));
+                            out.push('\n');
+                            shown.clear();
+                        }
+                    }
+                    _ => line.push(byte),
+                }
+            }
+        }
+        let last = if line.is_empty() { &shown } else { &line };
+        out.push_str(&String::from_utf8_lossy(last));
+        out
+    })
+}
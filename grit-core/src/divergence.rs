@@ -0,0 +1,78 @@
+//! How a local branch and its upstream went apart: the commits only one of
+//! them has, back to the commit both share.
+
+use crate::{
+    branch::Commit,
+    error::{Context, Result},
+};
+
+/// How many commits of each side are read.
+const MAX_COMMITS: usize = 50;
+
+#[derive(Clone)]
+pub struct Divergence {
+    pub branch: String,
+    /// The short name of the upstream, e.g. `origin/main`.
+    pub upstream: String,
+    /// The commits only the branch has, newest first.
+    pub local: Vec<Commit>,
+    /// The commits only the upstream has, newest first.
+    pub remote: Vec<Commit>,
+    /// How many commits only the branch and only the upstream have, of which
+    /// `local` and `remote` hold the newest.
+    pub ahead: usize,
+    pub behind: usize,
+    /// The newest commit both have, if they share history at all.
+    pub base: Option<Commit>,
+}
+
+/// The divergence of the local branch `name` from its upstream, if it has one.
+pub(crate) fn of(repo: &git2::Repository, name: &str) -> Result<Option<Divergence>> {
+    let branch = super::branch::find(repo, name, git2::BranchType::Local)?;
+    let Ok(upstream) = branch.upstream() else {
+        return Ok(None);
+    };
+    let (Some(local), Some(remote)) = (branch.get().target(), upstream.get().target()) else {
+        return Ok(None);
+    };
+    let base = repo.merge_base(local, remote).ok();
+    let (ahead, behind) = repo
+        .graph_ahead_behind(local, remote)
+        .with_context(|| format!("compare {name} with its upstream"))?;
+    let base_commit = base
+        .map(|id| repo.find_commit(id).context("find the merge base"))
+        .transpose()?
+        .map(Commit::try_from)
+        .transpose()?;
+    Ok(Some(Divergence {
+        branch: name.to_string(),
+        upstream: upstream
+            .name()
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+            .to_string(),
+        local: only(repo, local, remote)?,
+        remote: only(repo, remote, local)?,
+        ahead,
+        behind,
+        base: base_commit,
+    }))
+}
+
+/// The newest commits reachable from `tip` but not from `other`.
+fn only(repo: &git2::Repository, tip: git2::Oid, other: git2::Oid) -> Result<Vec<Commit>> {
+    let mut walk = repo.revwalk().context("revwalk")?;
+    walk.push(tip).context("revwalk push")?;
+    walk.hide(other).context("revwalk hide")?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
+        .context("revwalk sort")?;
+    walk.take(MAX_COMMITS)
+        .map(|id| {
+            let commit = repo
+                .find_commit(id.context("revwalk")?)
+                .context("find commit")?;
+            Commit::try_from(commit)
+        })
+        .collect()
+}
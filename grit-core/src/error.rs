@@ -0,0 +1,159 @@
+//! The error type of grit-core.
+
+use std::{fmt::Display, path::PathBuf};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// libgit2 failed.
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The repository could not be watched for changes.
+    #[error(transparent)]
+    Watch(#[from] notify::Error),
+    #[error(transparent)]
+    InvalidName(#[from] crate::refname::Invalid),
+    /// No repository was found at or above the path.
+    #[error("not a git repository: {}", .0.display())]
+    NotARepo(PathBuf),
+    #[error("no branch named {0}")]
+    BranchNotFound(String),
+    /// A local branch of the name to create or rename to exists already.
+    #[error("a branch named {0} already exists")]
+    BranchExists(String),
+    #[error("{0} is not a valid remote name")]
+    InvalidRemoteName(String),
+    #[error("a remote named {0} already exists")]
+    RemoteExists(String),
+    /// The URL of a remote to add is neither a URL, an scp-like address such
+    /// as `git@host:repo`, nor the path of a directory.
+    #[error("{0} is not a URL or a path to a repository")]
+    InvalidRemoteUrl(String),
+    /// A branch no longer points where it did when it was read, e.g. because
+    /// another program moved it in the meantime.
+    #[error("{0} changed since it was loaded")]
+    Moved(String),
+    /// `HEAD` points at a commit rather than a branch.
+    #[error("HEAD is detached, check out a branch first")]
+    Detached,
+    /// The branch is checked out, here or in another worktree.
+    #[error("{0} is checked out")]
+    CheckedOut(String),
+    /// The branch has commits that are in neither its upstream nor `HEAD`.
+    #[error("{0} is not fully merged")]
+    Unmerged(String),
+    /// A file has conflicts of a merge left to resolve, which staging it hunk
+    /// by hunk would mark resolved.
+    #[error("{0} has unresolved conflicts")]
+    Conflicted(String),
+    /// Local changes would be overwritten, e.g. by a checkout.
+    #[error("{0}")]
+    DirtyWorktree(String),
+    /// A remote rejected the credentials, or none were available.
+    #[error("{0}")]
+    AuthFailed(String),
+    /// The host key of a remote was not accepted, being unknown or changed.
+    #[error("{0}")]
+    HostKey(String),
+    /// A remote could not be reached.
+    #[error("{0}")]
+    Network(String),
+    /// The git CLI exited unsuccessfully.
+    #[error("{command} failed: {stderr}")]
+    Command { command: String, stderr: String },
+    /// A git CLI command was interrupted by its caller.
+    #[error("{0} cancelled")]
+    Cancelled(String),
+    /// The worker thread that owns the repository is gone.
+    #[error("git worker has stopped")]
+    WorkerStopped,
+    #[error("{0}")]
+    Other(String),
+    /// Another error with a description of what was being done.
+    #[error("{context}: {inner}")]
+    Context { context: String, inner: Box<Error> },
+}
+
+impl Error {
+    /// The error underneath any [`Error::Context`] layers.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context { inner, .. } => inner.root(),
+            err => err,
+        }
+    }
+}
+
+/// Classifies the stderr of a failed git command, which is all there is to go on
+/// for errors from the git CLI.
+pub(crate) fn classify(command: &str, stderr: &str) -> Error {
+    let stderr = stderr.trim().to_string();
+    let lower = stderr.to_lowercase();
+    let any = |needles: &[&str]| needles.iter().any(|n| lower.contains(n));
+    if any(&[
+        "would be overwritten by",
+        "please commit your changes or stash them",
+    ]) {
+        Error::DirtyWorktree(stderr)
+    } else if any(&["host key verification failed"]) {
+        // before the rest, as git adds that it could not read from the remote
+        Error::HostKey(stderr)
+    } else if any(&[
+        "authentication failed",
+        // ssh's, as a file git cannot write is permission denied as well
+        "permission denied (publickey",
+        "permission denied, please try again",
+        "could not read username",
+        "invalid credentials",
+    ]) {
+        Error::AuthFailed(stderr)
+    } else if any(&[
+        "could not resolve host",
+        "connection refused",
+        "connection timed out",
+        "network is unreachable",
+        "unable to access",
+        "could not read from remote repository",
+    ]) {
+        Error::Network(stderr)
+    } else {
+        Error::Command {
+            command: command.to_string(),
+            stderr,
+        }
+    }
+}
+
+/// Describes what was being done when an error happened, in the manner of
+/// `eyre::WrapErr`.
+pub trait Context<T> {
+    fn context(self, context: impl Display) -> Result<T>;
+    fn with_context<D: Display>(self, f: impl FnOnce() -> D) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> Context<T> for std::result::Result<T, E> {
+    fn context(self, context: impl Display) -> Result<T> {
+        self.with_context(|| context)
+    }
+
+    fn with_context<D: Display>(self, f: impl FnOnce() -> D) -> Result<T> {
+        self.map_err(|err| Error::Context {
+            context: f().to_string(),
+            inner: Box::new(err.into()),
+        })
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context(self, context: impl Display) -> Result<T> {
+        self.with_context(|| context)
+    }
+
+    fn with_context<D: Display>(self, f: impl FnOnce() -> D) -> Result<T> {
+        self.ok_or_else(|| Error::Other(f().to_string()))
+    }
+}
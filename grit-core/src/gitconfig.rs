@@ -0,0 +1,89 @@
+//! The git config as git reads it, layer by layer, so that each value tells
+//! where it comes from and whether a later layer overrides it.
+
+use crate::error::{Context, Result};
+
+/// The file a config value comes from, from the lowest precedence up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigLevel {
+    ProgramData,
+    System,
+    Xdg,
+    Global,
+    Local,
+    Worktree,
+    App,
+}
+
+impl ConfigLevel {
+    fn of(level: git2::ConfigLevel) -> Self {
+        match level {
+            git2::ConfigLevel::ProgramData => ConfigLevel::ProgramData,
+            git2::ConfigLevel::System => ConfigLevel::System,
+            git2::ConfigLevel::XDG => ConfigLevel::Xdg,
+            git2::ConfigLevel::Global => ConfigLevel::Global,
+            git2::ConfigLevel::Local => ConfigLevel::Local,
+            git2::ConfigLevel::Worktree => ConfigLevel::Worktree,
+            git2::ConfigLevel::App | git2::ConfigLevel::Highest => ConfigLevel::App,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigEntry {
+    /// The key, lowercased where git ignores case, e.g. `user.name`.
+    pub name: String,
+    pub value: String,
+    pub level: ConfigLevel,
+    /// Whether a layer of higher precedence sets the key too, so that this
+    /// value is not the one in effect.
+    pub overridden: bool,
+}
+
+/// Every value of the config, from the lowest precedence up.
+pub(super) fn entries(repo: &git2::Repository) -> Result<Vec<ConfigEntry>> {
+    let cfg = repo.config().context("read config")?;
+    let mut entries = Vec::new();
+    let mut iter = cfg.entries(None).context("read config entries")?;
+    while let Some(entry) = iter.next() {
+        let entry = entry.context("read config entry")?;
+        let Some(name) = entry.name() else {
+            continue;
+        };
+        entries.push(ConfigEntry {
+            name: name.to_string(),
+            value: entry.value().unwrap_or_default().to_string(),
+            level: ConfigLevel::of(entry.level()),
+            overridden: false,
+        });
+    }
+    entries.sort_by_key(|e| e.level);
+    // values of the same key in the same file all count, as for remote fetch
+    // refspecs, while a file of higher precedence replaces them
+    for idx in 0..entries.len() {
+        let (name, level) = (&entries[idx].name, entries[idx].level);
+        let overridden = entries[idx + 1..]
+            .iter()
+            .any(|e| e.name == *name && e.level > level);
+        entries[idx].overridden = overridden;
+    }
+    Ok(entries)
+}
+
+/// Sets `name` in the config of the repository, or removes it there for
+/// `None`.
+pub(super) fn set_local(repo: &git2::Repository, name: &str, value: Option<&str>) -> Result<()> {
+    let cfg = repo.config().context("read config")?;
+    let mut local = cfg
+        .open_level(git2::ConfigLevel::Local)
+        .context("open the repository config")?;
+    match value {
+        Some(value) => local
+            .set_str(name, value)
+            .with_context(|| format!("set {name}")),
+        None => match local.remove(name) {
+            Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(()),
+            res => res.with_context(|| format!("unset {name}")),
+        },
+    }
+}
@@ -0,0 +1,48 @@
+//! The repository's own git hooks. libgit2 does not run them, so commits it
+//! writes skip them unless they are run first, as git would run them.
+
+use std::path::{Path, PathBuf};
+
+/// The hook `name`, if one is installed: an executable file in the directory
+/// `core.hooksPath` names, or else in the `hooks` directory of the repository.
+pub(super) fn find(repo: &git2::Repository, name: &str) -> Option<PathBuf> {
+    let top = repo.workdir().unwrap_or(repo.path());
+    let dir = repo
+        .config()
+        .and_then(|config| config.get_path("core.hooksPath"))
+        .map(|dir| top.join(dir))
+        .unwrap_or_else(|_| super::command::common_dir(repo.path()).join("hooks"));
+    let path = dir.join(name);
+    is_executable(&path).then_some(path)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// A shell script running `hooks`, each with its arguments, in turn until one
+/// fails, followed by the positional parameters it reads them from.
+pub(super) fn script(hooks: &[(PathBuf, Vec<String>)]) -> (String, Vec<String>) {
+    let mut args = vec![];
+    let calls: Vec<String> = hooks
+        .iter()
+        .map(|(path, hook_args)| {
+            let mut call = vec![];
+            for arg in std::iter::once(path.to_string_lossy().into_owned()).chain(hook_args.clone())
+            {
+                args.push(arg);
+                call.push(format!("\"${{{}}}\"", args.len()));
+            }
+            call.join(" ")
+        })
+        .collect();
+    (calls.join(" && "), args)
+}
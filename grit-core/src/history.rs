@@ -0,0 +1,107 @@
+//! The commits of a branch that changed a file, newest first, optionally
+//! following the file across renames the way `git log --follow` does.
+
+use crate::{
+    branch::{self, Commit},
+    error::{Context, Result},
+};
+use git2::{BranchType, Delta, DiffFindOptions, DiffOptions};
+
+/// A commit that changed the file.
+#[derive(Clone)]
+pub struct FileChange {
+    pub commit: Commit,
+    /// What the file was called after the commit.
+    pub path: String,
+    /// What the file was called before, if the commit renamed it.
+    pub renamed_from: Option<String>,
+}
+
+/// Walks the history of `name` for commits that changed `path`, up to `max` of
+/// them. Each commit is compared with its first parent. Without `follow` the
+/// history ends where the file was added; with it, an addition that rename
+/// detection pairs with a deletion continues under the old path.
+pub(crate) fn walk(
+    repo: &git2::Repository,
+    name: &str,
+    typ: BranchType,
+    path: &str,
+    follow: bool,
+    max: usize,
+) -> Result<Vec<FileChange>> {
+    let tip = branch::find(repo, name, typ)?
+        .get()
+        .peel_to_commit()
+        .context("get commit for ref")?;
+    let mut revwalk = repo.revwalk().context("revwalk")?;
+    revwalk.push(tip.id()).context("revwalk push commit")?;
+    revwalk
+        .simplify_first_parent()
+        .context("revwalk first parent")?;
+    let mut path = path.to_string();
+    let mut changes = vec![];
+    for sha in revwalk {
+        if changes.len() >= max {
+            break;
+        }
+        let cmt = repo
+            .find_commit(sha.context("revwalk sha")?)
+            .context("find commit")?;
+        let tree = cmt.tree().context("commit tree")?;
+        let parent = match cmt.parent(0) {
+            Ok(parent) => Some(parent.tree().context("parent tree")?),
+            Err(_) => None,
+        };
+        // a diff limited to the path is cheap, and only an addition needs the
+        // whole tree to look for where the file came from
+        let mut opts = DiffOptions::new();
+        opts.pathspec(&path).disable_pathspec_match(true);
+        let diff = repo
+            .diff_tree_to_tree(parent.as_ref(), Some(&tree), Some(&mut opts))
+            .context("diff commit")?;
+        let Some(status) = diff.deltas().next().map(|d| d.status()) else {
+            continue;
+        };
+        let mut renamed_from = None;
+        if status == Delta::Added && follow && parent.is_some() {
+            renamed_from = rename_source(repo, parent.as_ref(), &tree, &path)?;
+        }
+        let mut commit: Commit = cmt.try_into().context("get commit")?;
+        commit.note = branch::note(repo, commit.id);
+        changes.push(FileChange {
+            commit,
+            path: path.clone(),
+            renamed_from: renamed_from.clone(),
+        });
+        match renamed_from {
+            Some(old) => path = old,
+            None if status == Delta::Added => break,
+            None => {}
+        }
+    }
+    Ok(changes)
+}
+
+/// The path that `path` was renamed or copied from between the trees, if rename
+/// detection finds one.
+fn rename_source(
+    repo: &git2::Repository,
+    old: Option<&git2::Tree>,
+    new: &git2::Tree,
+    path: &str,
+) -> Result<Option<String>> {
+    let mut diff = repo
+        .diff_tree_to_tree(old, Some(new), None)
+        .context("diff commit")?;
+    let mut find = DiffFindOptions::new();
+    find.renames(true).copies(true);
+    diff.find_similar(Some(&mut find))
+        .context("detect renames")?;
+    let source = diff.deltas().find_map(|delta| {
+        let renamed = matches!(delta.status(), Delta::Renamed | Delta::Copied);
+        let new = delta.new_file().path()?.to_str()?;
+        let old = delta.old_file().path()?.to_str()?;
+        (renamed && new == path).then(|| old.to_string())
+    });
+    Ok(source)
+}
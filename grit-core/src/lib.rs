@@ -0,0 +1,52 @@
+//! The git layer of grit: reading branches and their commits, divergence from
+//! the default branch, and the handful of mutations the UI offers.
+//!
+//! Everything goes through a [`Repository`], a cheap to clone handle whose
+//! libgit2 state lives on a dedicated thread, so it can be shared freely with a
+//! UI or a server. Errors are reported as [`Error`].
+
+mod branch;
+pub mod command;
+mod divergence;
+mod error;
+mod gitconfig;
+mod githooks;
+mod history;
+mod ops;
+pub mod pickaxe;
+pub mod prune;
+pub mod refname;
+mod refs;
+mod remote;
+mod replace;
+mod repo;
+mod shallow;
+mod stack;
+mod stage;
+pub mod stats;
+mod status;
+mod tag;
+pub mod trailer;
+mod watch;
+mod worker;
+mod worktree;
+
+pub use branch::{
+    Author, Branch, Cached, Commit, History, Recent, Replacement, Stale, Staleness, Timestamp,
+    Tracking,
+};
+pub use divergence::Divergence;
+pub use error::{Context, Error, Result};
+pub use git2::{BranchType, Oid};
+pub use gitconfig::{ConfigEntry, ConfigLevel};
+pub use history::FileChange;
+pub use ops::Plan;
+pub use refs::Ref;
+pub use remote::Remote;
+pub use repo::{Repository, Squash, Target, DEFAULT_MAX_COMMITS};
+pub use shallow::Completeness;
+pub use stack::Link;
+pub use stage::Hunk;
+pub use status::{FileStatus, Status};
+pub use tag::Tag;
+pub use watch::Watch;
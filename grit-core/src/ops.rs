@@ -0,0 +1,182 @@
+//! Merging a branch into the checked out one, and rebasing the checked out one
+//! onto a branch. Both are tried in memory first, so that a conflict is
+//! reported with the files it is in rather than left behind as a merge or
+//! rebase stopped halfway.
+
+use crate::{
+    branch,
+    error::{Context, Error, Result},
+};
+use git2::BranchType;
+
+/// What a merge or rebase does, or would do.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Plan {
+    /// The checked out branch has every commit of the other one already.
+    UpToDate,
+    /// The checked out branch only moves forward, by this many commits.
+    FastForward(usize),
+    /// A merge commit brings in this many commits, or a rebase replays this
+    /// many commits of the checked out branch.
+    Clean(usize),
+    /// The paths the changes conflict in. Nothing was changed.
+    Conflicts(Vec<String>),
+}
+
+/// A merge worked out but not done yet.
+pub(crate) struct Merge {
+    /// Where the checked out branch was, to move it only if it still is.
+    pub head: git2::Oid,
+    pub other: git2::Oid,
+    pub plan: Plan,
+}
+
+/// The checked out branch and its tip.
+fn head(repo: &git2::Repository) -> Result<(String, git2::Commit<'_>)> {
+    let head = repo.head().context("read HEAD")?;
+    let name = match head.is_branch() {
+        true => head.shorthand().map(ToOwned::to_owned),
+        false => None,
+    }
+    .ok_or(Error::Detached)?;
+    let tip = head.peel_to_commit().context("read HEAD")?;
+    Ok((name, tip))
+}
+
+/// The tip of the branch to merge or rebase onto, which is not `head`.
+fn target<'r>(
+    repo: &'r git2::Repository,
+    name: &str,
+    typ: BranchType,
+    head: &str,
+) -> Result<git2::Commit<'r>> {
+    if typ == BranchType::Local && name == head {
+        return Err(Error::CheckedOut(name.to_string()));
+    }
+    branch::find(repo, name, typ)?
+        .get()
+        .peel_to_commit()
+        .with_context(|| format!("read {name}"))
+}
+
+/// The plan if `head` has all of `other` already, or lags behind it.
+fn relation(repo: &git2::Repository, head: git2::Oid, other: git2::Oid) -> Result<Option<Plan>> {
+    if head == other || repo.graph_descendant_of(head, other)? {
+        return Ok(Some(Plan::UpToDate));
+    }
+    if repo.graph_descendant_of(other, head)? {
+        let (ahead, _) = repo.graph_ahead_behind(other, head)?;
+        return Ok(Some(Plan::FastForward(ahead)));
+    }
+    Ok(None)
+}
+
+/// The conflicting paths of an index.
+fn conflicts(index: &git2::Index) -> Result<Vec<String>> {
+    let mut paths = vec![];
+    for conflict in index.conflicts().context("read conflicts")? {
+        let conflict = conflict.context("read conflicts")?;
+        if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            paths.push(String::from_utf8_lossy(&entry.path).into_owned());
+        }
+    }
+    paths.dedup();
+    Ok(paths)
+}
+
+/// What merging `name` into the checked out branch does.
+pub(crate) fn plan_merge(repo: &git2::Repository, name: &str, typ: BranchType) -> Result<Merge> {
+    let (head_name, head) = head(repo)?;
+    let other = target(repo, name, typ, &head_name)?;
+    let plan = match relation(repo, head.id(), other.id())? {
+        Some(plan) => plan,
+        None => {
+            let index = repo.merge_commits(&head, &other, None).context("merge")?;
+            match index.has_conflicts() {
+                true => Plan::Conflicts(conflicts(&index)?),
+                false => Plan::Clean(repo.graph_ahead_behind(other.id(), head.id())?.0),
+            }
+        }
+    };
+    Ok(Merge {
+        head: head.id(),
+        other: other.id(),
+        plan,
+    })
+}
+
+/// Writes the tree of merging `other` into `head`, which must not conflict.
+pub(crate) fn merge_tree(
+    repo: &git2::Repository,
+    head: git2::Oid,
+    other: git2::Oid,
+) -> Result<git2::Oid> {
+    let (head, other) = (repo.find_commit(head)?, repo.find_commit(other)?);
+    let mut index = repo.merge_commits(&head, &other, None).context("merge")?;
+    if index.has_conflicts() {
+        return Err(Error::Other(format!(
+            "conflicts in {}",
+            conflicts(&index)?.join(", ")
+        )));
+    }
+    index.write_tree_to(repo).context("write merged tree")
+}
+
+/// Moves the checked out branch from `from` to `to` and checks out its tree,
+/// which fails without changing anything if local changes are in the way.
+pub(crate) fn advance(
+    repo: &git2::Repository,
+    from: git2::Oid,
+    to: git2::Oid,
+    message: &str,
+) -> Result<()> {
+    let mut head = repo.head().context("read HEAD")?;
+    let name = head.shorthand().unwrap_or("HEAD").to_string();
+    if head.target() != Some(from) {
+        return Err(Error::Moved(name));
+    }
+    let commit = repo.find_commit(to).context("find commit")?;
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.safe();
+    repo.checkout_tree(commit.as_object(), Some(&mut checkout))
+        .map_err(|err| match err.code() {
+            git2::ErrorCode::Conflict => Error::DirtyWorktree(err.message().to_string()),
+            _ => err.into(),
+        })?;
+    head.set_target(to, message)
+        .with_context(|| format!("update {name}"))?;
+    Ok(())
+}
+
+/// What rebasing the checked out branch onto `onto` does, found by replaying
+/// its commits in memory.
+pub(crate) fn plan_rebase(repo: &git2::Repository, onto: &str, typ: BranchType) -> Result<Plan> {
+    let (head_name, head) = head(repo)?;
+    let onto = target(repo, onto, typ, &head_name)?;
+    if let Some(plan) = relation(repo, head.id(), onto.id())? {
+        return Ok(plan);
+    }
+    let branch = repo.find_annotated_commit(head.id())?;
+    let upstream = repo.find_annotated_commit(onto.id())?;
+    let mut opts = git2::RebaseOptions::new();
+    opts.inmemory(true);
+    let mut rebase = repo
+        .rebase(Some(&branch), Some(&upstream), None, Some(&mut opts))
+        .context("start rebase")?;
+    let sig = repo.signature().context("no user identity for commit")?;
+    let mut count = 0;
+    while let Some(op) = rebase.next() {
+        op.context("replay commit")?;
+        let index = rebase.inmemory_index().context("replay commit")?;
+        if index.has_conflicts() {
+            return Ok(Plan::Conflicts(conflicts(&index)?));
+        }
+        match rebase.commit(None, &sig, None) {
+            Ok(_) => count += 1,
+            // the change is in the branch rebased onto already
+            Err(err) if err.code() == git2::ErrorCode::Applied => {}
+            Err(err) => return Err(err).context("replay commit"),
+        }
+    }
+    Ok(Plan::Clean(count))
+}
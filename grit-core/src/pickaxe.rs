@@ -0,0 +1,151 @@
+//! Finds the commits of a branch whose diffs change how many times a string
+//! occurs in a file, the way `git log -S` does. Big histories take a while, so
+//! the walk runs on a thread of its own and reports as it goes.
+
+use crate::{
+    branch::{self, Commit},
+    command::Location,
+    error::{Context, Result},
+};
+use git2::{BranchType, Delta, DiffFindOptions, Oid};
+use std::sync::mpsc;
+
+/// How many commits are walked between progress reports.
+const REPORT_EVERY: usize = 100;
+
+/// A commit that added or removed the string.
+#[derive(Clone)]
+pub struct Hit {
+    pub commit: Commit,
+    /// The files in which it did.
+    pub paths: Vec<String>,
+}
+
+pub enum Progress {
+    Found(Box<Hit>),
+    /// How many commits have been looked at so far.
+    Walked(usize),
+}
+
+/// Starts searching the history of `name` for `needle`. The channel closes once
+/// the walk is done, and dropping the receiver stops it.
+pub(crate) fn search(
+    location: Location,
+    name: String,
+    typ: BranchType,
+    needle: String,
+) -> mpsc::Receiver<Result<Progress>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        if let Err(err) = walk(&location, &name, typ, needle.as_bytes(), &tx) {
+            let _ = tx.send(Err(err));
+        }
+    });
+    rx
+}
+
+fn walk(
+    location: &Location,
+    name: &str,
+    typ: BranchType,
+    needle: &[u8],
+    tx: &mpsc::Sender<Result<Progress>>,
+) -> Result<()> {
+    // a repository of its own, so that the worker is free for everything else
+    let repo = git2::Repository::open(&location.git_dir).context("open repo")?;
+    let tip = branch::find(&repo, name, typ)?
+        .get()
+        .peel_to_commit()
+        .context("get commit for ref")?;
+    let mut revwalk = repo.revwalk().context("revwalk")?;
+    revwalk.push(tip.id()).context("revwalk push commit")?;
+    for (walked, sha) in revwalk.enumerate() {
+        if walked % REPORT_EVERY == 0 && tx.send(Ok(Progress::Walked(walked))).is_err() {
+            return Ok(());
+        }
+        let cmt = repo
+            .find_commit(sha.context("revwalk sha")?)
+            .context("find commit")?;
+        // like git log, merges are not diffed
+        if cmt.parent_count() > 1 {
+            continue;
+        }
+        let paths = changed(&repo, &cmt, needle)?;
+        if paths.is_empty() {
+            continue;
+        }
+        let mut commit: Commit = cmt.try_into().context("get commit")?;
+        commit.note = branch::note(&repo, commit.id);
+        if tx
+            .send(Ok(Progress::Found(Box::new(Hit { commit, paths }))))
+            .is_err()
+        {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+/// The files in which `cmt` changed how often `needle` occurs.
+fn changed(repo: &git2::Repository, cmt: &git2::Commit, needle: &[u8]) -> Result<Vec<String>> {
+    let tree = cmt.tree().context("commit tree")?;
+    let parent = match cmt.parent(0) {
+        Ok(parent) => Some(parent.tree().context("parent tree")?),
+        Err(_) => None,
+    };
+    let mut diff = repo
+        .diff_tree_to_tree(parent.as_ref(), Some(&tree), None)
+        .context("diff commit")?;
+    let mut paths = counts_changed(repo, &diff, needle)?;
+    // a moved file is not an addition and a removal, which git pairs up first
+    let moved = |d: git2::DiffDelta| matches!(d.status(), Delta::Added | Delta::Deleted);
+    if !paths.is_empty() && diff.deltas().any(moved) {
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+            .context("detect renames")?;
+        paths = counts_changed(repo, &diff, needle)?;
+    }
+    Ok(paths)
+}
+
+/// The files in `diff` whose two sides hold `needle` a different number of times.
+fn counts_changed(
+    repo: &git2::Repository,
+    diff: &git2::Diff,
+    needle: &[u8],
+) -> Result<Vec<String>> {
+    let mut paths = vec![];
+    for delta in diff.deltas() {
+        let old = occurrences(repo, delta.old_file().id(), needle)?;
+        let new = occurrences(repo, delta.new_file().id(), needle)?;
+        if old != new {
+            let file = delta.new_file().path().or(delta.old_file().path());
+            if let Some(path) = file {
+                paths.push(path.to_string_lossy().into_owned());
+            }
+        }
+    }
+    Ok(paths)
+}
+
+/// How many times `needle` occurs in the blob, none for a missing or binary one.
+fn occurrences(repo: &git2::Repository, id: Oid, needle: &[u8]) -> Result<usize> {
+    if id.is_zero() || needle.is_empty() {
+        return Ok(0);
+    }
+    // submodules show up as commits, which are not in this repository
+    let Ok(blob) = repo.find_blob(id) else {
+        return Ok(0);
+    };
+    if blob.is_binary() {
+        return Ok(0);
+    }
+    let (content, mut count, mut at) = (blob.content(), 0, 0);
+    while let Some(pos) = content[at..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+    {
+        count += 1;
+        at += pos + needle.len();
+    }
+    Ok(count)
+}
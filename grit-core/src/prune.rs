@@ -0,0 +1,126 @@
+//! Finding local branches that are safe to clean up.
+
+use crate::error::{Context, Result};
+use git2::BranchType;
+use serde::Serialize;
+
+/// Which branches to prune. `merged` and `gone` each select branches on their
+/// own; `older_than` selects old branches by itself, and narrows the other two
+/// down to old branches when combined with them.
+#[derive(Clone, Debug, Default)]
+pub struct Criteria {
+    pub merged: bool,
+    pub gone: bool,
+    /// In seconds.
+    pub older_than: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Reason {
+    /// Fully merged into the default branch.
+    Merged,
+    /// Its upstream no longer exists.
+    Gone,
+    /// No commits within the `older_than` window.
+    Old,
+}
+
+impl Reason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Reason::Merged => "merged",
+            Reason::Gone => "gone",
+            Reason::Old => "old",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Candidate {
+    pub name: String,
+    pub reasons: Vec<Reason>,
+    /// The commit the branch pointed at, to delete it only if it still does.
+    #[serde(skip)]
+    pub tip: git2::Oid,
+}
+
+pub(super) fn candidates(
+    repo: &git2::Repository,
+    criteria: &Criteria,
+    now: i64,
+) -> Result<Vec<Candidate>> {
+    let default = super::stats::default_branch(repo);
+    let head = repo
+        .head()
+        .ok()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.shorthand().map(ToOwned::to_owned));
+    let mut res = vec![];
+    for item in repo
+        .branches(Some(BranchType::Local))
+        .context("list branches")?
+    {
+        let (branch, _) = item.context("branch")?;
+        let Some(name) = branch.name().ok().flatten().map(ToOwned::to_owned) else {
+            continue;
+        };
+        // never prune what is checked out or what everything is merged into
+        if Some(&name) == head.as_ref()
+            || default
+                .as_ref()
+                .is_some_and(|(d, _)| *d == name || d.ends_with(&format!("/{name}")))
+        {
+            continue;
+        }
+        let Ok(tip) = branch.get().peel_to_commit() else {
+            continue;
+        };
+        let mut reasons = vec![];
+        if criteria.merged {
+            if let Some((_, base)) = &default {
+                if tip.id() == *base || repo.graph_descendant_of(*base, tip.id()).unwrap_or(false) {
+                    reasons.push(Reason::Merged);
+                }
+            }
+        }
+        if criteria.gone && is_gone(repo, &name) {
+            reasons.push(Reason::Gone);
+        }
+        if let Some(age) = criteria.older_than {
+            let old = now - tip.time().seconds() > age;
+            let others = criteria.merged || criteria.gone;
+            match (old, others) {
+                (true, false) => reasons.push(Reason::Old),
+                (true, true) if !reasons.is_empty() => reasons.push(Reason::Old),
+                (false, true) => reasons.clear(),
+                _ => {}
+            }
+        }
+        if !reasons.is_empty() {
+            let tip = tip.id();
+            res.push(Candidate { name, reasons, tip });
+        }
+    }
+    Ok(res)
+}
+
+/// Whether a branch has an upstream configured that no longer exists, as after
+/// the remote branch was deleted and pruned.
+fn is_gone(repo: &git2::Repository, name: &str) -> bool {
+    let Ok(cfg) = repo.config() else {
+        return false;
+    };
+    let Ok(merge) = cfg.get_string(&format!("branch.{name}.merge")) else {
+        return false;
+    };
+    let remote = cfg
+        .get_string(&format!("branch.{name}.remote"))
+        .unwrap_or_default();
+    if remote == "." {
+        return repo.find_reference(&merge).is_err();
+    }
+    repo.find_branch(name, BranchType::Local)
+        .and_then(|b| b.upstream())
+        .is_err()
+}
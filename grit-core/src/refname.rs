@@ -0,0 +1,112 @@
+//! Validation and normalization of user entered branch and tag names, following
+//! the rules of `git check-ref-format`.
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum Invalid {
+    #[error("name is empty")]
+    Empty,
+    #[error("name cannot be '@'")]
+    At,
+    #[error("name cannot start with '-'")]
+    LeadingDash,
+    #[error("name cannot start or end with '/' or contain '//'")]
+    Slash,
+    #[error("name cannot end with '.'")]
+    TrailingDot,
+    #[error("name cannot contain '..'")]
+    DoubleDot,
+    #[error("name cannot contain '@{{'")]
+    AtBrace,
+    #[error("path components cannot start with '.'")]
+    DotComponent,
+    #[error("path components cannot end with '.lock'")]
+    LockComponent,
+    #[error("name cannot contain {0:?}")]
+    BadChar(char),
+}
+
+fn is_bad_char(c: char) -> bool {
+    c.is_ascii_control() || matches!(c, ' ' | '~' | '^' | ':' | '?' | '*' | '[' | '\\')
+}
+
+/// Checks that `name` is usable as a branch or tag name.
+pub fn validate(name: &str) -> Result<(), Invalid> {
+    if name.is_empty() {
+        return Err(Invalid::Empty);
+    }
+    if name == "@" {
+        return Err(Invalid::At);
+    }
+    if name.starts_with('-') {
+        return Err(Invalid::LeadingDash);
+    }
+    if let Some(c) = name.chars().find(|c| is_bad_char(*c)) {
+        return Err(Invalid::BadChar(c));
+    }
+    if name.starts_with('/') || name.ends_with('/') || name.contains("//") {
+        return Err(Invalid::Slash);
+    }
+    if name.ends_with('.') {
+        return Err(Invalid::TrailingDot);
+    }
+    if name.contains("..") {
+        return Err(Invalid::DoubleDot);
+    }
+    if name.contains("@{") {
+        return Err(Invalid::AtBrace);
+    }
+    for component in name.split('/') {
+        if component.starts_with('.') {
+            return Err(Invalid::DotComponent);
+        }
+        if component.ends_with(".lock") {
+            return Err(Invalid::LockComponent);
+        }
+    }
+    Ok(())
+}
+
+/// The full ref name of the branch `name`.
+pub fn full(name: &str, typ: git2::BranchType) -> String {
+    match typ {
+        git2::BranchType::Local => format!("refs/heads/{name}"),
+        git2::BranchType::Remote => format!("refs/remotes/{name}"),
+    }
+}
+
+/// Rewrites `name` into a valid ref name where possible: whitespace becomes `-`,
+/// forbidden characters and sequences are dropped. The result may still be invalid
+/// (for example if nothing is left), so validate it afterwards.
+pub fn normalize(name: &str) -> String {
+    let mut res = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if c.is_whitespace() {
+            if !res.ends_with('-') {
+                res.push('-');
+            }
+        } else if !is_bad_char(c) {
+            res.push(c);
+        }
+    }
+    while res.contains("..") {
+        res = res.replace("..", ".");
+    }
+    res = res.replace("@{", "@");
+    let components: Vec<String> = res
+        .split('/')
+        .map(|comp| {
+            let mut comp = comp.trim_start_matches('.');
+            while let Some(stripped) = comp.strip_suffix(".lock") {
+                comp = stripped;
+            }
+            comp.to_string()
+        })
+        .filter(|comp| !comp.is_empty())
+        .collect();
+    let res = components.join("/");
+    let res = res.trim_start_matches('-').trim_end_matches('.');
+    if res == "@" {
+        return String::new();
+    }
+    res.to_string()
+}
@@ -0,0 +1,76 @@
+//! Every reference under `refs/`, whatever namespace it lives in: branches and
+//! tags, but also notes, replace refs, the `pull/*` refs of forges and anything
+//! a tool made up for itself.
+
+use crate::{
+    branch::Commit,
+    error::{Context, Result},
+};
+
+#[derive(Clone)]
+pub struct Ref {
+    /// The full name, e.g. `refs/notes/commits`.
+    pub name: String,
+    /// The ref a symbolic ref points at, e.g. `refs/remotes/origin/main`.
+    pub symbolic: Option<String>,
+    /// The object the ref resolves to, following symbolic refs.
+    pub target: Option<git2::Oid>,
+    /// Its id abbreviated, empty if there is none.
+    pub short: String,
+    /// The type of that object, e.g. `tag` for an annotated tag.
+    pub kind: Option<git2::ObjectType>,
+    /// The commit the ref peels to, if it does.
+    pub commit: Option<Commit>,
+}
+
+/// The refs of `repo`, `HEAD` first and the rest sorted by name. A ref that
+/// cannot be read, e.g. one whose file is garbled, is left out.
+pub(crate) fn list(repo: &git2::Repository) -> Result<Vec<Ref>> {
+    let mut refs = vec![];
+    for reference in repo.references().context("list refs")? {
+        let read = reference
+            .context("read ref")
+            .and_then(|reference| read(repo, &reference));
+        match read {
+            Ok(r) => refs.extend(r),
+            Err(err) => tracing::warn!("skipped a ref: {err:#}"),
+        }
+    }
+    refs.sort_by(|a, b| a.name.cmp(&b.name));
+    // an unborn or broken HEAD leaves just the rest
+    if let Ok(head) = repo.find_reference("HEAD") {
+        match read(repo, &head) {
+            Ok(r) => {
+                refs.splice(0..0, r);
+            }
+            Err(err) => tracing::warn!("skipped HEAD: {err:#}"),
+        }
+    }
+    Ok(refs)
+}
+
+fn read(repo: &git2::Repository, reference: &git2::Reference) -> Result<Option<Ref>> {
+    let Some(name) = reference.name().map(ToOwned::to_owned) else {
+        return Ok(None);
+    };
+    // a dangling symbolic ref, e.g. the HEAD of a remote whose default branch
+    // was deleted, still shows
+    let resolved = reference.resolve().ok();
+    let target = resolved.as_ref().and_then(git2::Reference::target);
+    let object = target.and_then(|id| repo.find_object(id, None).ok());
+    let kind = object.as_ref().and_then(git2::Object::kind);
+    let short = target.map(crate::branch::short_id).unwrap_or_default();
+    let commit = resolved
+        .and_then(|r| r.peel_to_commit().ok())
+        .map(Commit::try_from)
+        .transpose()
+        .with_context(|| format!("read the commit of {name}"))?;
+    Ok(Some(Ref {
+        symbolic: reference.symbolic_target().map(ToOwned::to_owned),
+        name,
+        target,
+        short,
+        kind,
+        commit,
+    }))
+}
@@ -0,0 +1,200 @@
+use crate::error::{Context, Error, Result};
+use std::path::Path;
+
+/// A configured remote and how it is fetched.
+#[derive(Clone, Debug)]
+pub struct Remote {
+    pub name: String,
+    pub url: Option<String>,
+    pub fetch: Vec<String>,
+    /// `remote.<name>.prune`, if set.
+    pub prune: Option<bool>,
+    /// `remote.<name>.skipFetchAll`: whether `git fetch --all` leaves it out.
+    pub skip_fetch_all: bool,
+    /// Its remote-tracking branches, e.g. `origin/main`, sorted by name.
+    pub branches: Vec<String>,
+}
+
+pub(super) fn list(repo: &git2::Repository) -> Result<Vec<Remote>> {
+    let names = repo.remotes().context("list remotes")?;
+    let cfg = repo.config().context("read config")?;
+    let mut tracking = vec![];
+    for branch in repo
+        .branches(Some(git2::BranchType::Remote))
+        .context("list remote branches")?
+    {
+        let (branch, _) = branch.context("read remote branch")?;
+        if let Some(name) = branch.name().ok().flatten() {
+            if !name.ends_with("/HEAD") {
+                tracking.push(name.to_string());
+            }
+        }
+    }
+    tracking.sort();
+    let mut remotes = vec![];
+    for name in names.iter().flatten() {
+        let remote = repo
+            .find_remote(name)
+            .with_context(|| format!("find remote {name}"))?;
+        let fetch = remote
+            .fetch_refspecs()
+            .with_context(|| format!("read refspecs of {name}"))?
+            .iter()
+            .flatten()
+            .map(ToOwned::to_owned)
+            .collect();
+        remotes.push(Remote {
+            name: name.to_string(),
+            url: remote.url().map(ToOwned::to_owned),
+            fetch,
+            prune: cfg.get_bool(&format!("remote.{name}.prune")).ok(),
+            skip_fetch_all: cfg
+                .get_bool(&format!("remote.{name}.skipFetchAll"))
+                .unwrap_or(false),
+            branches: tracking
+                .iter()
+                .filter(|b| {
+                    b.strip_prefix(name)
+                        .is_some_and(|rest| rest.starts_with('/'))
+                })
+                .cloned()
+                .collect(),
+        });
+    }
+    Ok(remotes)
+}
+
+/// Adds a remote fetching all branches of `url`, after checking that the name
+/// is valid and free and that the URL looks like one. A path is taken from
+/// `dir`, where git runs, as git will.
+pub(super) fn add(repo: &git2::Repository, name: &str, url: &str, dir: &Path) -> Result<()> {
+    if !git2::Remote::is_valid_name(name) {
+        return Err(Error::InvalidRemoteName(name.to_string()));
+    }
+    if repo.find_remote(name).is_ok() {
+        return Err(Error::RemoteExists(name.to_string()));
+    }
+    let scp = url
+        .split_once(':')
+        .is_some_and(|(host, path)| !host.is_empty() && !host.contains('/') && !path.is_empty());
+    if !url.contains("://") && !scp && !dir.join(url).is_dir() {
+        return Err(Error::InvalidRemoteUrl(url.to_string()));
+    }
+    repo.remote(name, url)
+        .with_context(|| format!("add remote {name}"))?;
+    Ok(())
+}
+
+/// Replaces the fetch refspecs of a remote. If one of `specs` is refused, the
+/// ones before are put back rather than leaving some of each.
+pub(super) fn set_fetch(repo: &git2::Repository, name: &str, specs: &[String]) -> Result<()> {
+    let old: Vec<String> = repo
+        .find_remote(name)
+        .with_context(|| format!("find remote {name}"))?
+        .fetch_refspecs()
+        .context("read refspecs")?
+        .iter()
+        .flatten()
+        .map(ToOwned::to_owned)
+        .collect();
+    let res = write_fetch(repo, name, specs);
+    if res.is_err() {
+        if let Err(err) = write_fetch(repo, name, &old) {
+            tracing::warn!("put back the refspecs of {name}: {err}");
+        }
+    }
+    res
+}
+
+fn write_fetch(repo: &git2::Repository, name: &str, specs: &[String]) -> Result<()> {
+    let mut cfg = repo.config().context("read config")?;
+    let key = format!("remote.{name}.fetch");
+    // removing a multivar that is not set is an error we don't care about
+    let _ = cfg.remove_multivar(&key, ".*");
+    for spec in specs {
+        repo.remote_add_fetch(name, spec)
+            .with_context(|| format!("add refspec {spec}"))?;
+    }
+    Ok(())
+}
+
+/// Points `refs/remotes/<name>/HEAD`, which the default branch is taken from,
+/// at the remote's `branch`.
+pub(super) fn set_head(repo: &git2::Repository, name: &str, branch: &str) -> Result<()> {
+    let target = format!("refs/remotes/{name}/{branch}");
+    repo.find_reference(&target)
+        .with_context(|| format!("find {name}/{branch}"))?;
+    repo.reference_symbolic(
+        &format!("refs/remotes/{name}/HEAD"),
+        &target,
+        true,
+        "remote set-head",
+    )
+    .with_context(|| format!("set {name}/HEAD"))?;
+    Ok(())
+}
+
+pub(super) fn set_prune(repo: &git2::Repository, name: &str, prune: bool) -> Result<()> {
+    let mut cfg = repo.config().context("read config")?;
+    cfg.set_bool(&format!("remote.{name}.prune"), prune)
+        .with_context(|| format!("set remote.{name}.prune"))
+}
+
+/// Where pushing the local branch `name` goes, as `git push` arguments.
+pub(super) struct PushSpec {
+    pub remote: String,
+    pub refspec: String,
+    /// Whether the branch has no upstream yet, to be set by the push.
+    pub set_upstream: bool,
+}
+
+/// Pushes the local branch `name` to the branch it tracks, or to one of the
+/// same name on `remote` if it tracks none.
+pub(super) fn push_spec(repo: &git2::Repository, name: &str, remote: &str) -> Result<PushSpec> {
+    let refname = format!("refs/heads/{name}");
+    let cfg = repo.config().context("read config")?;
+    let upstream = repo.branch_upstream_remote(&refname).ok();
+    let merge = cfg.get_string(&format!("branch.{name}.merge")).ok();
+    if let (Some(upstream), Some(merge)) = (upstream.as_ref().and_then(|b| b.as_str()), merge) {
+        return Ok(PushSpec {
+            remote: upstream.to_string(),
+            refspec: format!("{refname}:{merge}"),
+            set_upstream: false,
+        });
+    }
+    Ok(PushSpec {
+        remote: remote.to_string(),
+        refspec: format!("{refname}:{refname}"),
+        set_upstream: true,
+    })
+}
+
+/// The remote a remote-tracking branch such as `origin/feat` belongs to, and
+/// the branch on it that it mirrors, such as `refs/heads/feat`.
+pub(super) fn tracked(repo: &git2::Repository, name: &str) -> Result<(String, String)> {
+    let refname = format!("refs/remotes/{name}");
+    let remote = repo
+        .branch_remote_name(&refname)
+        .with_context(|| format!("find the remote of {name}"))?;
+    let remote = remote
+        .as_str()
+        .ok_or_else(|| Error::Other(format!("the remote of {name} is not UTF-8")))?
+        .to_string();
+    let found = repo
+        .find_remote(&remote)
+        .with_context(|| format!("find remote {remote}"))?;
+    for spec in found.refspecs() {
+        if spec.direction() != git2::Direction::Fetch || !spec.dst_matches(&refname) {
+            continue;
+        }
+        let server = spec
+            .rtransform(&refname)
+            .with_context(|| format!("map {name} onto {remote}"))?;
+        if let Some(server) = server.as_str() {
+            return Ok((remote, server.to_string()));
+        }
+    }
+    Err(Error::Other(format!(
+        "{name} does not mirror a branch of {remote}"
+    )))
+}
@@ -0,0 +1,132 @@
+//! Rewritten history, as `git replace` and the grafts before it leave it:
+//! commits that stand in for others under `refs/replace/`, and commits given
+//! other parents in `info/grafts`. git shows history through them unless told
+//! not to. libgit2 knows nothing of replace refs and always applies grafts, so
+//! a history with either is walked here instead.
+
+use crate::{
+    branch::{Commit, Replacement},
+    error::{Context, Result},
+};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+#[derive(Default)]
+pub(crate) struct Replacements {
+    /// The commits standing in for others, by the ones they replace.
+    objects: HashMap<git2::Oid, git2::Oid>,
+    /// The parents grafted onto commits.
+    grafts: HashMap<git2::Oid, Vec<git2::Oid>>,
+}
+
+impl Replacements {
+    pub(crate) fn read(repo: &git2::Repository) -> Result<Self> {
+        let mut objects = HashMap::new();
+        let odb = repo.odb().context("open the object database")?;
+        for reference in repo
+            .references_glob("refs/replace/*")
+            .context("list replace refs")?
+            .flatten()
+        {
+            let replaced = reference
+                .name()
+                .and_then(|name| name.strip_prefix("refs/replace/"))
+                .and_then(|id| git2::Oid::from_str(id).ok());
+            // only commits stand in for commits in history
+            let by = reference.target().filter(|by| {
+                odb.read_header(*by)
+                    .is_ok_and(|(_, kind)| kind == git2::ObjectType::Commit)
+            });
+            if let (Some(replaced), Some(by)) = (replaced, by) {
+                objects.insert(replaced, by);
+            }
+        }
+        // shared by linked worktrees, like the rest of `info`
+        let common = crate::command::common_dir(repo.path());
+        let grafts = std::fs::read_to_string(common.join("info/grafts"))
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut ids = line
+                    .split_whitespace()
+                    .map(|id| git2::Oid::from_str(id).ok());
+                let commit = ids.next()??;
+                Some((commit, ids.collect::<Option<Vec<_>>>()?))
+            })
+            .collect();
+        Ok(Self { objects, grafts })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.objects.is_empty() && self.grafts.is_empty()
+    }
+
+    /// The commit shown for `id`, and how it differs from `id`.
+    fn resolve(&self, id: git2::Oid) -> (git2::Oid, Option<Replacement>) {
+        match self.objects.get(&id) {
+            Some(by) => {
+                let short = super::branch::short_id(id);
+                (*by, Some(Replacement::Object { id, short }))
+            }
+            None if self.grafts.contains_key(&id) => (id, Some(Replacement::Graft)),
+            None => (id, None),
+        }
+    }
+}
+
+/// Walks up to `max` commits from `tip`, newest first, through `replacements`
+/// if `replaced`, or as the commits themselves record their parents otherwise.
+pub(crate) fn walk(
+    repo: &git2::Repository,
+    tip: git2::Oid,
+    replacements: &Replacements,
+    replaced: bool,
+    max: usize,
+) -> Result<Vec<Commit>> {
+    let resolve = |id| match replaced {
+        true => replacements.resolve(id),
+        false => (id, None),
+    };
+    let mut queue = BinaryHeap::new();
+    let mut seen = HashSet::from([tip]);
+    let (id, replacement) = resolve(tip);
+    let commit = repo.find_commit(id).context("find commit")?;
+    queue.push((commit.time().seconds(), id, replacement));
+    let mut commits = vec![];
+    while let Some((_, id, replacement)) = queue.pop() {
+        if commits.len() == max {
+            break;
+        }
+        let cmt = repo.find_commit(id).context("find commit")?;
+        let parents = match replacements.grafts.get(&id).filter(|_| replaced) {
+            Some(parents) => parents.clone(),
+            None => recorded_parents(&cmt),
+        };
+        for parent in parents {
+            if !seen.insert(parent) {
+                continue;
+            }
+            let (id, replacement) = resolve(parent);
+            // missing below where a shallow clone stops
+            let Ok(parent) = repo.find_commit(id) else {
+                continue;
+            };
+            queue.push((parent.time().seconds(), id, replacement));
+        }
+        let mut commit: Commit = cmt.try_into().context("get commit")?;
+        commit.note = super::branch::note(repo, id);
+        commit.replacement = replacement;
+        commits.push(commit);
+    }
+    Ok(commits)
+}
+
+/// The parents as the commit records them, before any graft libgit2 applied.
+fn recorded_parents(commit: &git2::Commit) -> Vec<git2::Oid> {
+    let header = String::from_utf8_lossy(commit.raw_header_bytes()).into_owned();
+    header
+        .lines()
+        .filter_map(|line| line.strip_prefix("parent "))
+        .filter_map(|id| git2::Oid::from_str(id).ok())
+        .collect()
+}
@@ -0,0 +1,1566 @@
+use crate::error::{Context, Error, Result};
+use git2::BranchType;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use super::{
+    branch::{self, Branch, Cached, Commit, Recent, Stale},
+    command::Location,
+    divergence::{self, Divergence},
+    gitconfig::{self, ConfigEntry},
+    githooks,
+    history::{self, FileChange},
+    ops::{self, Plan},
+    pickaxe, prune,
+    refs::{self, Ref},
+    remote::{self, Remote},
+    replace::Replacements,
+    shallow::{self, Completeness},
+    stack::{self, Link},
+    stage::{self, Hunk},
+    stats,
+    status::{self, FileStatus, Status},
+    tag::{self, Tag},
+    watch::Watch,
+    worker, worktree,
+};
+use std::sync::mpsc;
+
+/// What squashing a branch would do: its commits since `base`, where it forked
+/// from `onto`, become one commit.
+#[derive(Clone, Debug)]
+pub struct Squash {
+    /// The commit the branch pointed at, to squash it only if it still does.
+    pub tip: git2::Oid,
+    pub base: git2::Oid,
+    pub onto: String,
+    pub count: usize,
+    /// The messages of the commits, oldest first, as a starting point for the
+    /// message of the squashed commit.
+    pub message: String,
+}
+
+/// Where to find a repository on disk.
+#[derive(Debug, Clone, Default)]
+pub struct Target {
+    pub dir: Option<PathBuf>,
+    pub git_dir: Option<PathBuf>,
+    pub work_tree: Option<PathBuf>,
+}
+
+/// A handle to a repository whose libgit2 state lives on a [`worker`](super::worker)
+/// thread. Cloning is cheap and every clone talks to the same worker.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    worker: worker::Handle,
+    location: Location,
+    max_commits: usize,
+    /// Whether history is shown through replace refs and grafts, as git does.
+    replace: bool,
+}
+
+/// How many commits are loaded per branch unless configured otherwise.
+pub const DEFAULT_MAX_COMMITS: usize = 100;
+
+impl Repository {
+    /// Opens the repository described by `target`.
+    ///
+    /// If `target.dir` is set, the repository is discovered from there and relative
+    /// paths resolve against it, while the process working directory stays as it is.
+    /// An explicit git dir (from the target or `$GIT_DIR`) is opened directly instead of
+    /// being discovered, and an explicit work tree (from the target or
+    /// `$GIT_WORK_TREE`) replaces the one recorded in the repo. As with git, an
+    /// explicit git dir without either takes its work tree from `core.worktree`,
+    /// and otherwise, unless it is bare, the directory grit was started in.
+    pub fn open(target: &Target) -> Result<Self> {
+        let within = |path: PathBuf| match &target.dir {
+            Some(dir) => dir.join(path),
+            None => path,
+        };
+        let git_dir = target
+            .git_dir
+            .clone()
+            .or_else(|| env_path("GIT_DIR"))
+            .map(within);
+        let work_tree = target
+            .work_tree
+            .clone()
+            .or_else(|| env_path("GIT_WORK_TREE"))
+            .map(within);
+        let explicit = git_dir.is_some();
+        let repo = match git_dir {
+            Some(git_dir) => {
+                git2::Repository::open_bare(&git_dir).map_err(|err| not_a_repo(err, &git_dir))?
+            }
+            None => Self::discover(target.dir.as_deref())?,
+        };
+        // libgit2 finds no work tree for the git dir of a linked work tree
+        // given as is, which would leave its HEAD checked out nowhere
+        let linked = super::command::is_linked(repo.path());
+        let work_tree = match work_tree {
+            Some(work_tree) => Some(work_tree),
+            None if explicit => Self::implied_work_tree(&repo, target.dir.as_deref())?,
+            None => repo.workdir().map(ToOwned::to_owned),
+        }
+        .or_else(|| {
+            linked
+                .then(|| super::command::linked_work_tree(repo.path()))
+                .flatten()
+        });
+        tracing::info!(git_dir = ?repo.path(), ?work_tree, linked, "opened repository");
+        if let Some(work_tree) = work_tree {
+            repo.set_workdir(&work_tree, false)
+                .with_context(|| format!("set work tree {}", work_tree.display()))?;
+        }
+        Self::spawn(repo)
+    }
+
+    /// The work tree git would use for `repo`, opened from a git dir given as
+    /// is: `core.worktree`, relative to the git dir, or `dir` or the working
+    /// directory. None for a bare repository, and for a linked work tree,
+    /// whose own is found from its git dir.
+    fn implied_work_tree(repo: &git2::Repository, dir: Option<&Path>) -> Result<Option<PathBuf>> {
+        let config = repo.config().context("read config")?;
+        if let Ok(work_tree) = config.get_string("core.worktree") {
+            return Ok(Some(repo.path().join(work_tree)));
+        }
+        if config.get_bool("core.bare").unwrap_or(false) || super::command::is_linked(repo.path()) {
+            return Ok(None);
+        }
+        let dir = match dir {
+            Some(dir) => dir.to_path_buf(),
+            None => std::env::current_dir().context("get current dir")?,
+        };
+        Ok(Some(dir))
+    }
+
+    pub fn current() -> Result<Self> {
+        Self::spawn(Self::discover(None)?)
+    }
+
+    /// Finds the repository `dir` is in, or else the working directory.
+    fn discover(dir: Option<&Path>) -> Result<git2::Repository> {
+        let start = match dir {
+            Some(dir) => dir.to_path_buf(),
+            None => std::env::current_dir().context("get current dir")?,
+        };
+        let flags = git2::RepositoryOpenFlags::FROM_ENV;
+        let ceiling = &[] as &[&std::ffi::OsStr];
+        git2::Repository::open_ext(&start, flags, ceiling).map_err(|err| not_a_repo(err, &start))
+    }
+
+    fn spawn(repo: git2::Repository) -> Result<Self> {
+        let location = Location {
+            git_dir: repo.path().to_path_buf(),
+            work_tree: repo.workdir().map(ToOwned::to_owned),
+            no_replace: false,
+        };
+        let worker = worker::spawn(repo)?;
+        Ok(Self {
+            worker,
+            location,
+            max_commits: DEFAULT_MAX_COMMITS,
+            replace: true,
+        })
+    }
+
+    /// Limits how many commits are loaded per branch.
+    pub fn with_max_commits(mut self, max: usize) -> Self {
+        self.max_commits = max;
+        self
+    }
+
+    pub fn max_commits(&self) -> usize {
+        self.max_commits
+    }
+
+    /// Shows history through replace refs and grafts, or as the commits record
+    /// it, which unlike `git --no-replace-objects` leaves out grafts too. The
+    /// git commands run are told to leave out replace refs likewise.
+    pub fn with_replace(mut self, replace: bool) -> Self {
+        self.replace = replace;
+        self.location.no_replace = !replace;
+        self
+    }
+
+    pub fn replace(&self) -> bool {
+        self.replace
+    }
+
+    /// Whether history was rewritten with replace refs or grafts.
+    pub fn has_replacements(&self) -> Result<bool> {
+        self.call(|repo| Ok(!Replacements::read(repo)?.is_empty()))?
+    }
+
+    /// Runs `f` against the repository on the git worker thread and waits for it.
+    pub fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&git2::Repository) -> T + Send + 'static,
+    {
+        self.worker.call(f)
+    }
+
+    /// Queues `f` on the git worker thread without waiting for it.
+    pub fn submit<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce(&git2::Repository) + Send + 'static,
+    {
+        self.worker.submit(f)
+    }
+
+    /// Runs `f` on the git worker thread without waiting for it, sending what
+    /// it returns on the returned channel.
+    fn later<T, F>(&self, f: F) -> mpsc::Receiver<Result<T>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&git2::Repository) -> Result<T> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let err_tx = tx.clone();
+        if let Err(err) = self.submit(move |repo| {
+            let _ = tx.send(f(repo));
+        }) {
+            let _ = err_tx.send(Err(err));
+        }
+        rx
+    }
+
+    /// Where the repository lives, for handing to the git CLI or other threads.
+    pub fn location(&self) -> Location {
+        self.location.clone()
+    }
+
+    pub fn branches(&self, typ: Option<BranchType>) -> Result<Vec<Branch>> {
+        let names = self.call(move |repo| -> Result<Vec<_>> {
+            repo.branches(typ)
+                .context("repo branches")?
+                .map(|br_res| {
+                    let (branch, typ) = br_res.context("branch")?;
+                    let name = branch.name().context("branch name")?;
+                    Ok(name.map(|name| (name.to_string(), typ)))
+                })
+                .collect()
+        })??;
+        Ok(names
+            .into_iter()
+            .flatten()
+            .map(|(name, typ)| Branch::new(self, name, typ))
+            .collect())
+    }
+
+    /// The configured `user.name`, if any.
+    pub fn user_name(&self) -> Result<Option<String>> {
+        self.call(|repo| {
+            repo.config()
+                .and_then(|cfg| cfg.get_string("user.name"))
+                .ok()
+        })
+    }
+
+    /// Every value of the git config, from the lowest precedence up, each with
+    /// the file it comes from.
+    pub fn config_entries(&self) -> Result<Vec<ConfigEntry>> {
+        self.call(gitconfig::entries)?
+    }
+
+    /// Sets `name` in the config of the repository, or removes it there for
+    /// `None`.
+    pub fn set_local_config(&self, name: &str, value: Option<String>) -> Result<()> {
+        let name = name.to_string();
+        self.call(move |repo| gitconfig::set_local(repo, &name, value.as_deref()))?
+    }
+
+    /// The branch checked out in the work tree, or `None` if HEAD is detached.
+    pub fn head_branch(&self) -> Option<String> {
+        self.call(|repo| {
+            let head = repo.head().ok()?;
+            head.is_branch()
+                .then(|| head.shorthand().map(ToOwned::to_owned))
+                .flatten()
+        })
+        .ok()
+        .flatten()
+    }
+
+    /// The local branches matching `criteria`.
+    pub fn prune_candidates(&self, criteria: prune::Criteria) -> Result<Vec<prune::Candidate>> {
+        let now = chrono::Utc::now().timestamp();
+        self.call(move |repo| prune::candidates(repo, &criteria, now))?
+    }
+
+    /// The branch others are merged into: the remote's default branch if known,
+    /// otherwise a local `main` or `master`.
+    pub fn default_branch(&self) -> Result<Option<String>> {
+        Ok(self.call(stats::default_branch)?.map(|(name, _)| name))
+    }
+
+    /// How many commits `rev` is ahead of and behind `base`.
+    pub fn ahead_behind(&self, rev: &str, base: &str) -> Result<(usize, usize)> {
+        let (rev, base) = (rev.to_string(), base.to_string());
+        self.call(move |repo| -> Result<(usize, usize)> {
+            let resolve = |rev: &str| {
+                repo.revparse_single(rev)
+                    .and_then(|obj| obj.peel_to_commit())
+                    .map(|c| c.id())
+                    .with_context(|| format!("resolve {rev}"))
+            };
+            let (local, upstream) = (resolve(&rev)?, resolve(&base)?);
+            repo.graph_ahead_behind(local, upstream)
+                .with_context(|| format!("compare {rev} with {base}"))
+        })?
+    }
+
+    /// Checks out a branch with the git CLI, so that the work tree, index and
+    /// hooks are handled exactly as git would. A remote branch without a local
+    /// counterpart is checked out as a new tracking branch.
+    pub fn checkout(&self, name: &str, typ: BranchType) -> Result<String> {
+        super::command::run(&self.location, &checkout_args(name, typ), || false)
+    }
+
+    /// Checks out the branch `name`, returning the local branch that ends up
+    /// checked out and whether it was created. A remote branch such as
+    /// `origin/feat` is checked out as the local `feat`, which is created to
+    /// track it unless it exists already.
+    pub fn switch_to(&self, name: &str, typ: BranchType) -> Result<(String, bool)> {
+        let (local, upstream) = self.switch_target(name, typ)?;
+        let args = switch_args(&local, upstream.as_deref());
+        super::command::run(&self.location, &args, || false)?;
+        Ok((local, upstream.is_some()))
+    }
+
+    /// The git command [`Repository::switch_to`] runs, for running it where
+    /// its output, such as that of the `post-checkout` hook, can be watched,
+    /// and the local branch it checks out.
+    pub fn switch_command(&self, name: &str, typ: BranchType) -> Result<(String, Command)> {
+        let (local, upstream) = self.switch_target(name, typ)?;
+        let mut cmd = self.location.command();
+        cmd.args(switch_args(&local, upstream.as_deref()));
+        Ok((local, cmd))
+    }
+
+    /// The local branch checking out `name` ends up on, and the remote branch
+    /// to create it from if there is none yet. The remote is the one whose
+    /// fetch refspec maps onto `name`, which may have a `/` in its name.
+    fn switch_target(&self, name: &str, typ: BranchType) -> Result<(String, Option<String>)> {
+        if typ == BranchType::Local {
+            return Ok((name.to_string(), None));
+        }
+        let name = name.to_string();
+        self.call(move |repo| {
+            let refname = format!("refs/remotes/{name}");
+            let remote = repo
+                .branch_remote_name(&refname)
+                .map_err(|_| Error::BranchNotFound(name.clone()))?;
+            let short = remote
+                .as_str()
+                .and_then(|remote| name.strip_prefix(remote)?.strip_prefix('/'))
+                .filter(|short| !short.is_empty())
+                .ok_or_else(|| Error::BranchNotFound(name.clone()))?
+                .to_string();
+            match repo.find_branch(&short, BranchType::Local) {
+                Ok(_) => Ok((short, None)),
+                Err(_) => Ok((short, Some(name))),
+            }
+        })?
+    }
+
+    /// Applies `ids` onto the checked out branch, oldest first. With `hooks`,
+    /// each is committed by `git commit`, which runs the `pre-commit` and
+    /// `commit-msg` hooks cherry-pick skips.
+    pub fn cherry_pick(&self, ids: &[git2::Oid], hooks: bool) -> Result<String> {
+        if hooks {
+            return self.commit_each("cherry-pick", ids);
+        }
+        let ids: Vec<String> = ids.iter().map(ToString::to_string).collect();
+        let mut args = vec!["cherry-pick"];
+        args.extend(ids.iter().map(String::as_str));
+        super::command::run(&self.location, &args, || false)
+    }
+
+    /// Reverts `ids` on the checked out branch with a commit each, in the order
+    /// given, which should be newest first. With `hooks`, each is committed
+    /// by `git commit`, as for [`Repository::cherry_pick`].
+    pub fn revert(&self, ids: &[git2::Oid], hooks: bool) -> Result<String> {
+        if hooks {
+            return self.commit_each("revert", ids);
+        }
+        let ids: Vec<String> = ids.iter().map(ToString::to_string).collect();
+        let mut args = vec!["revert", "--no-edit"];
+        args.extend(ids.iter().map(String::as_str));
+        super::command::run(&self.location, &args, || false)
+    }
+
+    /// Applies each of `ids` with `op`, cherry-pick or revert, without
+    /// committing, then commits it with the message `op` prepared, so that
+    /// git runs the commit hooks. A commit a hook stops is undone, keeping the
+    /// ones before it.
+    fn commit_each(&self, op: &str, ids: &[git2::Oid]) -> Result<String> {
+        let mut out = String::new();
+        for id in ids {
+            let id = id.to_string();
+            out += &super::command::run(&self.location, &[op, "--no-commit", &id], || false)?;
+            // a cherry-pick keeps the author, as git's own does
+            let mut args = vec!["commit", "--no-edit"];
+            if op == "cherry-pick" {
+                args.extend(["--reuse-message", &id]);
+            }
+            match super::command::run(&self.location, &args, || false) {
+                Ok(done) => out += &done,
+                Err(err) => {
+                    super::command::run(&self.location, &["reset", "--merge"], || false)?;
+                    return Err(err).with_context(|| format!("{op} {id}"));
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Whether commits are to be signed, as `commit.gpgSign` says. git signs
+    /// the ones it writes by itself, with the key `gpg.format` names, which may
+    /// ask for a passphrase on the terminal.
+    pub fn signs_commits(&self) -> bool {
+        self.call(|repo| {
+            repo.config()
+                .and_then(|config| config.get_bool("commit.gpgsign"))
+                .unwrap_or(false)
+        })
+        .unwrap_or(false)
+    }
+
+    /// Commits what is staged with `message`, from which lines starting with
+    /// `#` are stripped as in git's editor.
+    pub fn commit(&self, message: &str) -> Result<String> {
+        let args = ["commit", "--cleanup=strip", "--message", message];
+        super::command::run(&self.location, &args, || false)
+    }
+
+    /// The git command [`Repository::commit`] runs, for running it where its
+    /// output, such as that of the hooks, can be watched.
+    pub fn commit_command(&self, message: &str) -> Command {
+        let mut cmd = self.location.command();
+        cmd.args(["commit", "--cleanup=strip", "--message", message]);
+        cmd
+    }
+
+    /// A command running the installed ones of the hooks `hooks`, each with
+    /// its arguments, in turn until one fails, as git runs them: through the
+    /// shell, at the top of the work tree. `None` if none is installed.
+    pub fn hooks_command(&self, hooks: &[(&str, Vec<String>)]) -> Result<Option<Command>> {
+        let hooks: Vec<(String, Vec<String>)> = hooks
+            .iter()
+            .map(|(name, args)| (name.to_string(), args.clone()))
+            .collect();
+        let installed = self.call(move |repo| {
+            hooks
+                .into_iter()
+                .filter_map(|(name, args)| Some((githooks::find(repo, &name)?, args)))
+                .collect::<Vec<_>>()
+        })?;
+        if installed.is_empty() {
+            return Ok(None);
+        }
+        let (script, args) = githooks::script(&installed);
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(script).arg("sh").args(args);
+        cmd.current_dir(self.location.dir())
+            .env("GIT_DIR", &self.location.git_dir);
+        if let Some(work_tree) = &self.location.work_tree {
+            cmd.env("GIT_WORK_TREE", work_tree);
+        }
+        Ok(Some(cmd))
+    }
+
+    /// Whether the hook `name` is installed.
+    pub fn has_hook(&self, name: &str) -> bool {
+        let name = name.to_string();
+        self.call(move |repo| githooks::find(repo, &name).is_some())
+            .unwrap_or(false)
+    }
+
+    /// The file git keeps the message of the commit being made in, which the
+    /// `commit-msg` hook is given and may rewrite.
+    pub fn message_file(&self) -> PathBuf {
+        self.location.git_dir.join("COMMIT_EDITMSG")
+    }
+
+    /// The text to start commit messages from: the file `commit.template`
+    /// names, or else a `.gitmessage` at the top of the work tree.
+    pub fn commit_template(&self) -> Result<Option<String>> {
+        let (configured, workdir) = self.call(|repo| {
+            let configured = repo
+                .config()
+                .and_then(|config| config.get_path("commit.template"))
+                .ok();
+            (configured, repo.workdir().map(Path::to_path_buf))
+        })?;
+        let path = match (configured, workdir) {
+            (Some(path), Some(dir)) => Some(dir.join(path)),
+            (Some(path), None) => Some(path),
+            (None, Some(dir)) => Some(dir.join(".gitmessage")).filter(|p| p.exists()),
+            (None, None) => None,
+        };
+        let Some(path) = path else {
+            return Ok(None);
+        };
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("read commit template {}", path.display()))?;
+        Ok(Some(text))
+    }
+
+    /// Commits what is staged as a `fixup!` of `id`, for
+    /// [`Repository::autosquash`] to fold into it.
+    pub fn fixup(&self, id: git2::Oid) -> Result<String> {
+        let fixup = format!("--fixup={id}");
+        super::command::run(&self.location, &["commit", "--no-edit", &fixup], || false)
+    }
+
+    /// Rebases the checked out branch onto `base`, or from its root if `None`,
+    /// folding `fixup!` and `squash!` commits into their targets without asking
+    /// for the todo list. Uncommitted changes are stashed meanwhile.
+    pub fn autosquash(&self, base: Option<git2::Oid>) -> Result<String> {
+        let base = base.map_or("--root".to_string(), |id| id.to_string());
+        let args = [
+            "-c",
+            "sequence.editor=true",
+            "rebase",
+            "--interactive",
+            "--autosquash",
+            "--autostash",
+            &base,
+        ];
+        let out = super::command::run(&self.location, &args, || false)?;
+        // the progress is redrawn in place, of which only the end is of use
+        let lines: Vec<&str> = out
+            .lines()
+            .filter_map(|line| line.rsplit('\r').find(|l| !l.is_empty()))
+            .collect();
+        Ok(lines.join("\n"))
+    }
+
+    /// The local branches stacked on others, by name.
+    pub fn stacks(&self) -> Result<HashMap<String, Link>> {
+        self.call(stack::links)?
+    }
+
+    /// Like [`Repository::stacks`] without waiting, sending the result once
+    /// the worker gets to it.
+    pub fn stream_stacks(&self) -> mpsc::Receiver<Result<HashMap<String, Link>>> {
+        self.later(stack::links)
+    }
+
+    /// Rebases each child onto its parent, in order, taking the commits it has
+    /// on top of `base`, the tip of the parent it was built on, then checks
+    /// out what was checked out before. Uncommitted changes are stashed
+    /// meanwhile. A rebase that stops is aborted, leaving that child and the
+    /// ones after it as they were, and the error tells which it was.
+    pub fn restack(&self, links: &[(String, String, git2::Oid)]) -> Result<String> {
+        let head = match self.head_branch() {
+            Some(branch) => Some(branch),
+            None => self
+                .call(|repo| repo.head().ok()?.target())?
+                .map(|id| id.to_string()),
+        };
+        let mut out = String::new();
+        let mut failed = None;
+        for (parent, child, base) in links {
+            let base = base.to_string();
+            let args = ["rebase", "--autostash", "--onto", parent, &base, child];
+            match super::command::run(&self.location, &args, || false) {
+                Ok(done) => out += &done,
+                Err(err) => {
+                    if self.call(|repo| repo.state() != git2::RepositoryState::Clean)? {
+                        super::command::run(&self.location, &["rebase", "--abort"], || false)?;
+                    }
+                    failed =
+                        Some(Err(err).with_context(|| format!("restack {child} onto {parent}")));
+                    break;
+                }
+            }
+        }
+        if let Some(head) = head {
+            super::command::run(&self.location, &["checkout", "--quiet", &head], || false)?;
+        }
+        failed.unwrap_or(Ok(out))
+    }
+
+    /// Writes `ids` as numbered patch files into `dir`, which is relative to the
+    /// work tree. Returns the files written, one per line.
+    pub fn format_patch(&self, ids: &[git2::Oid], dir: &str) -> Result<String> {
+        let mut out = String::new();
+        for (n, id) in ids.iter().enumerate() {
+            let (n, id) = ((n + 1).to_string(), id.to_string());
+            let args = ["format-patch", "-o", dir, "--start-number", &n, "-1", &id];
+            out += &super::command::run(&self.location, &args, || false)?;
+        }
+        Ok(out)
+    }
+
+    /// The commits `new` has that `old` does not, newest first, as when a
+    /// branch moved from `old` to `new`.
+    pub fn commits_since(&self, old: git2::Oid, new: git2::Oid) -> Result<Vec<git2::Oid>> {
+        self.call(move |repo| -> Result<Vec<git2::Oid>> {
+            // gone after a force push that dropped it, when all that is known
+            // to have arrived is the new tip
+            if repo.find_commit(old).is_err() {
+                return Ok(vec![new]);
+            }
+            let mut walk = repo.revwalk().context("revwalk")?;
+            walk.push(new).context("revwalk push")?;
+            walk.hide(old).context("revwalk hide")?;
+            walk.set_sorting(git2::Sort::TOPOLOGICAL)
+                .context("revwalk sort")?;
+            walk.map(|id| id.context("revwalk")).collect()
+        })?
+    }
+
+    /// Plans squashing the local branch `name` down to one commit on top of its
+    /// merge base with the default branch.
+    pub fn plan_squash(&self, name: &str) -> Result<Squash> {
+        let name = name.to_string();
+        self.call(move |repo| -> Result<Squash> {
+            let (onto, default) =
+                stats::default_branch(repo).context("no default branch to squash onto")?;
+            let tip = branch::find(repo, &name, BranchType::Local)?
+                .get()
+                .peel_to_commit()
+                .with_context(|| format!("resolve {name}"))?;
+            let base = repo
+                .merge_base(tip.id(), default)
+                .with_context(|| format!("find where {name} forked from {onto}"))?;
+            let mut walk = repo.revwalk().context("revwalk")?;
+            walk.push(tip.id()).context("revwalk push")?;
+            walk.hide(base).context("revwalk hide")?;
+            walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+                .context("revwalk sort")?;
+            let mut messages = vec![];
+            for id in walk {
+                let commit = repo
+                    .find_commit(id.context("revwalk")?)
+                    .context("find commit")?;
+                messages.push(commit.message().unwrap_or_default().trim().to_string());
+            }
+            Ok(Squash {
+                tip: tip.id(),
+                base,
+                onto,
+                count: messages.len(),
+                message: messages.join("\n\n"),
+            })
+        })?
+    }
+
+    /// Replaces the commits of the local branch `name` since the base of `plan`
+    /// with a single commit of the same tree, like `git reset --soft` followed by
+    /// a commit, signed if [`Repository::signs_commits`]. The work tree and
+    /// index are left alone.
+    pub fn squash(&self, name: &str, plan: &Squash, message: &str) -> Result<git2::Oid> {
+        let (name, message) = (name.to_string(), message.to_string());
+        let (expected, base) = (plan.tip, plan.base);
+        let id = match self.signs_commits() {
+            // libgit2 cannot sign, so git writes the commit
+            true => {
+                let (tree, base) = (format!("{expected}^{{tree}}"), base.to_string());
+                let args = ["commit-tree", "-S", "-p", &base, "-m", &message, &tree];
+                let out = super::command::run(&self.location, &args, || false)?;
+                git2::Oid::from_str(out.trim()).context("read squashed commit")?
+            }
+            false => {
+                let message = message.clone();
+                self.call(move |repo| -> Result<git2::Oid> {
+                    let tip = repo.find_commit(expected).context("find tip")?;
+                    let parent = repo.find_commit(base).context("find merge base")?;
+                    let tree = tip.tree().context("read tree")?;
+                    let sig = repo.signature().context("no user identity for commit")?;
+                    repo.commit(None, &sig, &sig, &message, &tree, &[&parent])
+                        .context("write squashed commit")
+                })??
+            }
+        };
+        self.call(move |repo| -> Result<git2::Oid> {
+            let branch = branch::find(repo, &name, BranchType::Local)?;
+            branch::expect_tip(&branch, &name, expected)?;
+            branch
+                .into_reference()
+                .set_target(id, &format!("grit: squash {name}"))
+                .with_context(|| format!("update {name}"))?;
+            Ok(id)
+        })?
+    }
+
+    /// What merging `name` into the checked out branch would do.
+    pub fn plan_merge(&self, name: &str, typ: BranchType) -> Result<Plan> {
+        let name = name.to_string();
+        self.call(move |repo| ops::plan_merge(repo, &name, typ).map(|merge| merge.plan))?
+    }
+
+    /// The message of a commit merging `name`, worded as git words it.
+    pub fn merge_message(name: &str, typ: BranchType) -> String {
+        match typ {
+            BranchType::Local => format!("Merge branch '{name}'"),
+            BranchType::Remote => format!("Merge remote-tracking branch '{name}'"),
+        }
+    }
+
+    /// Merges `name` into the checked out branch, fast-forwarding it if it can
+    /// or else writing a merge commit with `message`, signed if commits are. A
+    /// merge that would conflict is not started, its plan listing the
+    /// conflicts instead.
+    pub fn merge(&self, name: &str, typ: BranchType, message: &str) -> Result<Plan> {
+        let merge = {
+            let name = name.to_string();
+            self.call(move |repo| ops::plan_merge(repo, &name, typ))??
+        };
+        let (head, other) = (merge.head, merge.other);
+        let to = match merge.plan {
+            Plan::UpToDate | Plan::Conflicts(_) => return Ok(merge.plan),
+            Plan::FastForward(_) => other,
+            Plan::Clean(_) => {
+                let tree = self.call(move |repo| ops::merge_tree(repo, head, other))??;
+                let message = message.to_string();
+                match self.signs_commits() {
+                    // libgit2 cannot sign, so git writes the commit
+                    true => {
+                        let (tree, head, other) =
+                            (tree.to_string(), head.to_string(), other.to_string());
+                        let args = [
+                            "commit-tree",
+                            "-S",
+                            "-p",
+                            &head,
+                            "-p",
+                            &other,
+                            "-m",
+                            &message,
+                            &tree,
+                        ];
+                        let out = super::command::run(&self.location, &args, || false)?;
+                        git2::Oid::from_str(out.trim()).context("read merge commit")?
+                    }
+                    false => self.call(move |repo| -> Result<git2::Oid> {
+                        let tree = repo.find_tree(tree).context("read merged tree")?;
+                        let parents = [&repo.find_commit(head)?, &repo.find_commit(other)?];
+                        let sig = repo.signature().context("no user identity for commit")?;
+                        repo.commit(None, &sig, &sig, &message, &tree, &parents)
+                            .context("write merge commit")
+                    })??,
+                }
+            }
+        };
+        let message = format!("grit: merge {name}");
+        self.call(move |repo| ops::advance(repo, head, to, &message))??;
+        Ok(merge.plan)
+    }
+
+    /// What rebasing the checked out branch onto `onto` would do.
+    pub fn plan_rebase(&self, onto: &str, typ: BranchType) -> Result<Plan> {
+        let onto = onto.to_string();
+        self.call(move |repo| ops::plan_rebase(repo, &onto, typ))?
+    }
+
+    /// Rebases the checked out branch onto `onto` with git, which signs the
+    /// commits as configured. A rebase that would conflict is not started,
+    /// and one that stops anyway is aborted.
+    pub fn rebase(&self, onto: &str, typ: BranchType) -> Result<Plan> {
+        let plan = self.plan_rebase(onto, typ)?;
+        if matches!(plan, Plan::UpToDate | Plan::Conflicts(_)) {
+            return Ok(plan);
+        }
+        let refname = match typ {
+            BranchType::Local => format!("refs/heads/{onto}"),
+            BranchType::Remote => format!("refs/remotes/{onto}"),
+        };
+        if let Err(err) = super::command::run(&self.location, &["rebase", &refname], || false) {
+            if self.call(|repo| repo.state() != git2::RepositoryState::Clean)? {
+                super::command::run(&self.location, &["rebase", "--abort"], || false)?;
+            }
+            return Err(err).with_context(|| format!("rebase onto {onto}"));
+        }
+        Ok(plan)
+    }
+
+    /// Stashes local changes, with untracked files if `untracked` is set.
+    pub fn stash(&self, message: &str, untracked: bool) -> Result<String> {
+        let mut args = vec!["stash", "push"];
+        if untracked {
+            args.push("--include-untracked");
+        }
+        if !message.is_empty() {
+            args.extend(["--message", message]);
+        }
+        super::command::run(&self.location, &args, || false)
+    }
+
+    /// The commits of a branch that changed `path`, newest first, following the
+    /// file across renames if `follow` is set.
+    pub fn file_history(
+        &self,
+        name: &str,
+        typ: BranchType,
+        path: &str,
+        follow: bool,
+    ) -> Result<Vec<FileChange>> {
+        let (name, path, max) = (name.to_string(), path.to_string(), self.max_commits);
+        self.call(move |repo| history::walk(repo, &name, typ, &path, follow, max))?
+    }
+
+    /// Searches the history of a branch for commits that add or remove `needle`,
+    /// reporting them as they are found.
+    pub fn pickaxe(
+        &self,
+        name: &str,
+        typ: BranchType,
+        needle: &str,
+    ) -> mpsc::Receiver<Result<pickaxe::Progress>> {
+        pickaxe::search(self.location(), name.to_string(), typ, needle.to_string())
+    }
+
+    /// Whether the clone is shallow or partial.
+    pub fn completeness(&self) -> Result<Completeness> {
+        self.call(shallow::of)
+    }
+
+    /// Fetches `by` more commits of history below where a shallow clone
+    /// stops, or all of it for `None`.
+    pub fn deepen(&self, by: Option<usize>, cancelled: impl Fn() -> bool) -> Result<String> {
+        let depth = by.map(|n| format!("--deepen={n}"));
+        let args = ["fetch", depth.as_deref().unwrap_or("--unshallow")];
+        let out = super::command::remote(&self.location, &args, cancelled)?;
+        // libgit2 reads where the clone stops only when opening it
+        self.worker.reopen()?;
+        Ok(out)
+    }
+
+    /// Every ref under `refs/`, with the commit each peels to.
+    pub fn refs(&self) -> Result<Vec<Ref>> {
+        self.call(refs::list)?
+    }
+
+    /// Like [`Repository::refs`] without waiting, sending the result once the
+    /// worker gets to it.
+    pub fn stream_refs(&self) -> mpsc::Receiver<Result<Vec<Ref>>> {
+        self.later(refs::list)
+    }
+
+    /// The tags, those on the newest commits first.
+    pub fn tags(&self) -> Result<Vec<Tag>> {
+        self.call(tag::list)?
+    }
+
+    /// The local branches checked out in other worktrees, with where those are.
+    pub fn worktrees(&self) -> Result<HashMap<String, PathBuf>> {
+        self.call(worktree::others)?
+    }
+
+    /// The directories of the linked worktrees.
+    pub fn worktree_paths(&self) -> Result<Vec<PathBuf>> {
+        self.call(worktree::paths)?
+    }
+
+    /// Adds a worktree at `path` with the commit `id` checked out on no
+    /// branch.
+    pub fn add_detached_worktree(&self, path: &Path, id: git2::Oid) -> Result<()> {
+        let (path, id) = (path.to_string_lossy(), id.to_string());
+        let args = ["worktree", "add", "--detach", &path, &id];
+        super::command::run(&self.location, &args, || false)?;
+        Ok(())
+    }
+
+    /// Removes the worktree at `path`, which git refuses if anything in it
+    /// was changed or added.
+    pub fn remove_worktree(&self, path: &Path) -> Result<()> {
+        let path = path.to_string_lossy();
+        let args = ["worktree", "remove", &path];
+        super::command::run(&self.location, &args, || false)?;
+        Ok(())
+    }
+
+    /// Starts watching the repository for changes made by other programs.
+    pub fn watch(&self) -> Result<Watch> {
+        let common = self.location.common_dir();
+        Watch::new(common, self.location.git_dir.join("index.lock"))
+    }
+
+    /// The files with uncommitted changes and the untracked ones, and the
+    /// ignored ones too if `ignored`.
+    pub fn status_files(&self, ignored: bool) -> Result<Vec<FileStatus>> {
+        self.call(move |repo| status::files(repo, ignored))?
+    }
+
+    /// The hunks of the unstaged changes to `path`, or of the staged ones if
+    /// `staged`.
+    pub fn hunks(&self, path: &str, staged: bool) -> Result<Vec<Hunk>> {
+        let path = path.to_string();
+        self.call(move |repo| stage::hunks(repo, &path, staged))?
+    }
+
+    /// Stages the hunk at `idx` of the unstaged changes to `path`, or unstages
+    /// it if `staged`, failing with [`Error::Moved`] if it is no longer the
+    /// hunk with `header`, and with [`Error::Conflicted`] if the file has
+    /// conflicts to resolve.
+    pub fn apply_hunk(&self, path: &str, staged: bool, idx: usize, header: &str) -> Result<()> {
+        let (path, header) = (path.to_string(), header.to_string());
+        self.call(move |repo| stage::apply(repo, &path, staged, idx, &header))?
+    }
+
+    /// Counts the uncommitted changes and stashes.
+    pub fn status(&self) -> Result<Status> {
+        self.call(status::read)?
+    }
+
+    /// Counts the uncommitted changes and stashes without waiting, sending the
+    /// result once the worker gets to it.
+    pub fn stream_status(&self) -> mpsc::Receiver<Result<Status>> {
+        let (tx, rx) = mpsc::channel();
+        let res = self.submit(move |repo| {
+            let _ = tx.send(status::read(repo));
+        });
+        if let Err(err) = res {
+            let (tx, rx) = mpsc::channel();
+            let _ = tx.send(Err(err));
+            return rx;
+        }
+        rx
+    }
+
+    /// Pushes the local branch `name` to the branch it tracks, or to one of the
+    /// same name on `remote`, `origin` if `None`, which it then tracks.
+    /// `progress` is passed each line of progress the push reports.
+    pub fn push(
+        &self,
+        name: &str,
+        remote: Option<&str>,
+        cancelled: impl Fn() -> bool,
+        progress: impl Fn(&str),
+    ) -> Result<String> {
+        let (name, remote) = (name.to_string(), remote.unwrap_or("origin").to_string());
+        let spec = self.call(move |repo| remote::push_spec(repo, &name, &remote))??;
+        let mut args = vec!["push", "--progress"];
+        if spec.set_upstream {
+            args.push("--set-upstream");
+        }
+        args.extend([spec.remote.as_str(), spec.refspec.as_str()]);
+        super::command::remote_progress(&self.location, &args, cancelled, progress)
+    }
+
+    /// The remote [`push`](Self::push) pushes the local branch `name` to: that
+    /// of its upstream, or else `remote`.
+    pub fn push_remote(&self, name: &str, remote: &str) -> Result<String> {
+        let (name, remote) = (name.to_string(), remote.to_string());
+        let spec = self.call(move |repo| remote::push_spec(repo, &name, &remote))??;
+        Ok(spec.remote)
+    }
+
+    /// Deletes the remote-tracking branches of `remote` whose branch on the
+    /// server is gone, returning their names, such as `origin/feat`.
+    pub fn prune_remote(&self, remote: &str, cancelled: impl Fn() -> bool) -> Result<Vec<String>> {
+        let out = super::command::remote(&self.location, &["remote", "prune", remote], cancelled)?;
+        let pruned = out
+            .lines()
+            .filter_map(|line| line.split_once("[pruned]"))
+            .map(|(_, name)| name.trim().to_string())
+            .collect();
+        Ok(pruned)
+    }
+
+    /// Deletes the branch on the server that the remote-tracking branch `name`,
+    /// such as `origin/feat`, mirrors, and with it `name`. Refuses if the
+    /// branch on the server moved away from `expected` since the last fetch.
+    pub fn delete_remote_branch(
+        &self,
+        name: &str,
+        expected: git2::Oid,
+        cancelled: impl Fn() -> bool,
+    ) -> Result<String> {
+        let tracking = name.to_string();
+        let (remote, server) = self.call(move |repo| remote::tracked(repo, &tracking))??;
+        let lease = format!("--force-with-lease={server}:{expected}");
+        let refspec = format!(":{server}");
+        let args = ["push", &lease, &remote, &refspec];
+        super::command::remote(&self.location, &args, cancelled)
+    }
+
+    /// The latest stash, if there is one, to tell whether a push made one.
+    pub fn latest_stash(&self) -> Result<Option<git2::Oid>> {
+        self.call(|repo| repo.refname_to_id("refs/stash").ok())
+    }
+
+    /// Reapplies and drops the latest stash.
+    pub fn stash_pop(&self) -> Result<String> {
+        super::command::run(&self.location, &["stash", "pop"], || false)
+    }
+
+    /// Puts `path` back the way it is at `HEAD`, in the index and the work
+    /// tree, deleting it if it is untracked. What is discarded is stashed first,
+    /// and the stash commit is returned for [`Repository::restore_discarded`].
+    pub fn discard(&self, path: &str) -> Result<git2::Oid> {
+        let before = self.latest_stash()?;
+        let message = format!("grit: discarded {path}");
+        // the path as it is, not a pattern that a name with `*` or `?`
+        // would turn it into
+        let pathspec = format!(":(literal){path}");
+        let args = [
+            "stash",
+            "push",
+            "--include-untracked",
+            "--message",
+            &message,
+            "--",
+            &pathspec,
+        ];
+        super::command::run(&self.location, &args, || false)?;
+        match self.latest_stash()? {
+            Some(after) if Some(after) != before => Ok(after),
+            _ => Err(Error::Other(format!("no changes to discard in {path}"))),
+        }
+    }
+
+    /// Applies the stash `id` made by [`Repository::discard`], staged changes
+    /// staged again, and drops it.
+    pub fn restore_discarded(&self, id: git2::Oid) -> Result<String> {
+        let at = self.call(move |repo| -> Result<Option<usize>> {
+            let reflog = repo.reflog("refs/stash").context("read stashes")?;
+            Ok(reflog.iter().position(|entry| entry.id_new() == id))
+        })??;
+        let Some(at) = at else {
+            let short = branch::short_id(id);
+            return Err(Error::Other(format!("the stash {short} is gone")));
+        };
+        let stash = format!("stash@{{{at}}}");
+        super::command::run(&self.location, &["stash", "pop", "--index", &stash], || {
+            false
+        })
+    }
+
+    /// Deletes a local branch, unless it no longer points at `expected`, is
+    /// checked out, or, without `force`, is not merged, as `git branch -d`
+    /// refuses.
+    pub fn delete_branch(&self, name: &str, expected: git2::Oid, force: bool) -> Result<()> {
+        let name = name.to_string();
+        self.call(move |repo| -> Result<()> {
+            let mut branch = branch::find(repo, &name, BranchType::Local)?;
+            branch::expect_tip(&branch, &name, expected)?;
+            if branch.is_head() || worktree::others(repo)?.contains_key(&name) {
+                return Err(Error::CheckedOut(name));
+            }
+            if !force && !branch::is_merged(repo, &branch)? {
+                return Err(Error::Unmerged(name));
+            }
+            branch.delete().with_context(|| format!("delete {name}"))
+        })?
+    }
+
+    /// Whether the local branch `name` is merged into its upstream, or `HEAD`
+    /// if it has none, so that deleting it loses no commits.
+    pub fn is_merged(&self, name: &str) -> Result<bool> {
+        let name = name.to_string();
+        self.call(move |repo| -> Result<bool> {
+            let branch = branch::find(repo, &name, BranchType::Local)?;
+            branch::is_merged(repo, &branch)
+        })?
+    }
+
+    /// The local branches merged as [`Repository::is_merged`] tells.
+    pub fn merged_branches(&self) -> Result<HashSet<String>> {
+        self.call(|repo| -> Result<HashSet<String>> {
+            let mut merged = HashSet::new();
+            for entry in repo
+                .branches(Some(BranchType::Local))
+                .context("list branches")?
+            {
+                let (branch, _) = entry.context("branch")?;
+                if let Some(name) = branch.name().ok().flatten() {
+                    if branch::is_merged(repo, &branch)? {
+                        merged.insert(name.to_string());
+                    }
+                }
+            }
+            Ok(merged)
+        })?
+    }
+
+    /// What tells the stale local branches apart: the default branch, and the
+    /// branches it contains, as their merge base with it is their own tip.
+    /// Tips older than `before`, in Unix seconds, are old.
+    pub fn stale(&self, before: i64) -> Result<Stale> {
+        self.call(move |repo| branch::stale(repo, before, None))?
+    }
+
+    /// Like [`Repository::stale`] without waiting, sending the result once the
+    /// worker gets to it. Only the branches `only` picks are looked at, if it
+    /// is given.
+    pub fn stream_stale(&self, before: i64, only: Option<Recent>) -> mpsc::Receiver<Result<Stale>> {
+        self.later(move |repo| {
+            let only = only.map(|only| only.pick(repo)).transpose()?;
+            branch::stale(repo, before, only.as_ref())
+        })
+    }
+
+    pub fn remotes(&self) -> Result<Vec<Remote>> {
+        self.call(remote::list)?
+    }
+
+    /// Replaces the fetch refspecs of remote `name` in the repo config.
+    pub fn set_fetch_refspecs(&self, name: &str, specs: Vec<String>) -> Result<()> {
+        let name = name.to_string();
+        self.call(move |repo| remote::set_fetch(repo, &name, &specs))?
+    }
+
+    pub fn add_remote(&self, name: &str, url: &str) -> Result<()> {
+        let (name, url) = (name.to_string(), url.to_string());
+        let dir = self.location.dir().to_path_buf();
+        self.call(move |repo| remote::add(repo, &name, &url, &dir))?
+    }
+
+    pub fn set_prune(&self, name: &str, prune: bool) -> Result<()> {
+        let name = name.to_string();
+        self.call(move |repo| remote::set_prune(repo, &name, prune))?
+    }
+
+    /// Points `<remote>/HEAD` at the remote's `branch`, as `git remote
+    /// set-head` does.
+    pub fn set_remote_head(&self, remote: &str, branch: &str) -> Result<()> {
+        let (remote, branch) = (remote.to_string(), branch.to_string());
+        self.call(move |repo| remote::set_head(repo, &remote, &branch))?
+    }
+
+    /// Sets the note on commit `id`, replacing any existing one. An empty note
+    /// removes it.
+    pub fn set_note(&self, id: git2::Oid, note: &str) -> Result<()> {
+        let note = note.trim_end().to_string();
+        self.call(move |repo| -> Result<()> {
+            let sig = repo.signature().context("no user identity for note")?;
+            if note.is_empty() {
+                if repo.find_note(None, id).is_ok() {
+                    repo.note_delete(id, None, &sig, &sig)
+                        .context("remove note")?;
+                }
+                return Ok(());
+            }
+            repo.note(&sig, &sig, None, id, &note, true)
+                .context("write note")?;
+            Ok(())
+        })?
+    }
+
+    /// The commit the ref `refname`, by full name, points at, if it exists.
+    pub fn ref_target(&self, refname: &str) -> Result<Option<git2::Oid>> {
+        let refname = refname.to_string();
+        self.call(move |repo| repo.refname_to_id(&refname).ok())
+    }
+
+    /// The commit the notes ref is at, which moves whenever a note changes.
+    pub fn notes_tip(&self) -> Result<Option<git2::Oid>> {
+        self.call(|repo| {
+            let name = repo.note_default_ref().ok()?;
+            repo.refname_to_id(&name).ok()
+        })
+    }
+
+    /// The short name of the upstream of a local branch, e.g. `origin/main`.
+    pub fn upstream(&self, name: &str) -> Option<String> {
+        let name = name.to_string();
+        self.call(move |repo| {
+            let branch = repo.find_branch(&name, BranchType::Local).ok()?;
+            let upstream = branch.upstream().ok()?;
+            upstream.name().ok().flatten().map(ToOwned::to_owned)
+        })
+        .ok()
+        .flatten()
+    }
+
+    /// How the local branch `name` relates to its upstream.
+    pub fn tracking(&self, name: &str) -> Result<branch::Tracking> {
+        let name = name.to_string();
+        self.call(move |repo| -> Result<branch::Tracking> {
+            let branch = branch::find(repo, &name, BranchType::Local)?;
+            Ok(branch::Tracking::of(repo, &branch))
+        })?
+    }
+
+    /// How the local branch `name` and its upstream went apart, if it has one.
+    pub fn divergence(&self, name: &str) -> Result<Option<Divergence>> {
+        let name = name.to_string();
+        self.call(move |repo| divergence::of(repo, &name))?
+    }
+
+    /// The summaries of the commits reachable from `rev` but not from `base`,
+    /// oldest first.
+    pub fn summaries(&self, rev: &str, base: &str) -> Result<Vec<String>> {
+        let range = format!("{base}..{rev}");
+        self.call(move |repo| -> Result<Vec<String>> {
+            let mut walk = repo.revwalk().context("revwalk")?;
+            walk.push_range(&range)
+                .with_context(|| format!("walk {range}"))?;
+            walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+                .context("revwalk sort")?;
+            walk.map(|id| {
+                let commit = repo
+                    .find_commit(id.context("revwalk")?)
+                    .context("find commit")?;
+                Ok(commit.summary().unwrap_or_default().to_string())
+            })
+            .collect()
+        })?
+    }
+
+    /// The names of the local branches.
+    pub fn local_names(&self) -> Result<Vec<String>> {
+        self.call(|repo| -> Result<Vec<String>> {
+            let mut names = vec![];
+            for res in repo
+                .branches(Some(BranchType::Local))
+                .context("list local branches")?
+            {
+                let (branch, _) = res.context("branch")?;
+                if let Some(name) = branch.name().ok().flatten() {
+                    names.push(name.to_string());
+                }
+            }
+            Ok(names)
+        })?
+    }
+
+    /// The tips of all remote-tracking branches by short name, e.g. `origin/main`.
+    /// Comparing two snapshots tells which refs a fetch updated.
+    pub fn remote_refs(&self) -> Result<BTreeMap<String, git2::Oid>> {
+        self.call(|repo| -> Result<BTreeMap<String, git2::Oid>> {
+            let mut refs = BTreeMap::new();
+            for res in repo
+                .branches(Some(BranchType::Remote))
+                .context("list remote branches")?
+            {
+                let (branch, _) = res.context("branch")?;
+                let reference = branch.get();
+                let (Some(name), Some(id)) = (reference.shorthand(), reference.target()) else {
+                    // symbolic refs like origin/HEAD have no target of their own
+                    continue;
+                };
+                refs.insert(name.to_string(), id);
+            }
+            Ok(refs)
+        })?
+    }
+
+    /// The full id of the commit that `rev` resolves to.
+    pub fn commit_id(&self, rev: &str) -> Option<String> {
+        let rev = rev.to_string();
+        self.call(move |repo| {
+            repo.revparse_single(&rev)
+                .and_then(|obj| obj.peel_to_commit())
+                .map(|c| c.id().to_string())
+                .ok()
+        })
+        .ok()
+        .flatten()
+    }
+
+    /// Creates a local branch pointing at the tip of `from`.
+    pub fn create_branch(&self, name: &str, from: &Branch, force: bool) -> Result<()> {
+        let name = name.to_string();
+        let (from, typ, expected) = (from.name.clone(), from.typ, from.tip());
+        self.call(move |repo| -> Result<()> {
+            super::refname::validate(&name)
+                .with_context(|| format!("'{name}' is not a valid branch name"))?;
+            if !force {
+                branch::expect_free(repo, &name)?;
+            }
+            let branch = branch::find(repo, &from, typ)?;
+            if let Some(expected) = expected {
+                branch::expect_tip(&branch, &from, expected)?;
+            }
+            let commit = branch
+                .get()
+                .peel_to_commit()
+                .with_context(|| format!("resolve {from}"))?;
+            repo.branch(&name, &commit, force)
+                .with_context(|| format!("create branch {name}"))?;
+            Ok(())
+        })?
+    }
+
+    /// Creates a local branch at the commit the ref `from` peels to,
+    /// replacing one of that name if `force`.
+    pub fn create_branch_at(&self, name: &str, from: &str, force: bool) -> Result<()> {
+        let (name, from) = (name.to_string(), from.to_string());
+        self.call(move |repo| -> Result<()> {
+            super::refname::validate(&name)
+                .with_context(|| format!("'{name}' is not a valid branch name"))?;
+            if !force {
+                branch::expect_free(repo, &name)?;
+            }
+            let commit = repo
+                .find_reference(&from)
+                .and_then(|r| r.peel_to_commit())
+                .with_context(|| format!("resolve {from}"))?;
+            repo.branch(&name, &commit, force)
+                .with_context(|| format!("create branch {name}"))?;
+            Ok(())
+        })?
+    }
+
+    /// Renames a local branch. Fails if `new` already exists, unless `force`
+    /// replaces it, or if `old` no longer points at `expected`.
+    pub fn rename_branch(
+        &self,
+        old: &str,
+        new: &str,
+        expected: Option<git2::Oid>,
+        force: bool,
+    ) -> Result<()> {
+        let (old, new) = (old.to_string(), new.to_string());
+        self.call(move |repo| -> Result<()> {
+            super::refname::validate(&new)
+                .with_context(|| format!("'{new}' is not a valid branch name"))?;
+            if !force {
+                branch::expect_free(repo, &new)?;
+            }
+            let mut branch = branch::find(repo, &old, BranchType::Local)?;
+            if let Some(expected) = expected {
+                branch::expect_tip(&branch, &old, expected)?;
+            }
+            branch
+                .rename(&new, force)
+                .with_context(|| format!("rename {old} to {new}"))?;
+            Ok(())
+        })?
+    }
+
+    /// The branches named `name`: the local one and those of each remote,
+    /// such as `origin/name`, that there are.
+    pub fn branches_named(&self, name: &str) -> Result<Vec<(String, BranchType)>> {
+        let name = name.to_string();
+        self.call(move |repo| -> Result<Vec<(String, BranchType)>> {
+            let mut found = vec![];
+            if repo.find_branch(&name, BranchType::Local).is_ok() {
+                found.push((name.clone(), BranchType::Local));
+            }
+            let remotes = repo.remotes().context("list remotes")?;
+            for remote in remotes.iter().flatten() {
+                let remote_branch = format!("{remote}/{name}");
+                if repo.find_branch(&remote_branch, BranchType::Remote).is_ok() {
+                    found.push((remote_branch, BranchType::Remote));
+                }
+            }
+            Ok(found)
+        })?
+    }
+
+    /// Makes the local branch `name` track the remote branch `upstream`, such
+    /// as `origin/name`.
+    pub fn set_upstream(&self, name: &str, upstream: &str) -> Result<()> {
+        let (name, upstream) = (name.to_string(), upstream.to_string());
+        self.call(move |repo| -> Result<()> {
+            let mut branch = branch::find(repo, &name, BranchType::Local)?;
+            branch
+                .set_upstream(Some(&upstream))
+                .with_context(|| format!("set the upstream of {name} to {upstream}"))
+        })?
+    }
+
+    pub fn branch_count(&self, typ: Option<BranchType>) -> Result<usize> {
+        self.call(move |repo| -> Result<usize> {
+            Ok(repo.branches(typ).context("repo branches")?.count())
+        })?
+    }
+
+    /// Streams branches with their commits loaded as each one is read, so callers
+    /// can show the first results without waiting for the whole set. The channel is
+    /// closed once every branch has been sent; an error ends the stream early.
+    pub fn stream_branches(&self, typ: Option<BranchType>) -> mpsc::Receiver<Result<Branch>> {
+        self.stream_branches_with(typ, HashMap::new())
+    }
+
+    /// Like [`Repository::stream_branches`], but a branch whose tip is in `known`
+    /// gets that commit alone instead of its history, and is marked partial.
+    pub fn stream_branches_with(
+        &self,
+        typ: Option<BranchType>,
+        known: HashMap<git2::Oid, Commit>,
+    ) -> mpsc::Receiver<Result<Branch>> {
+        self.stream(typ, known, Box::new(|_, _| None), true, None)
+    }
+
+    /// Like [`Repository::stream_branches`], but a branch whose ref points at
+    /// a tip `cached` has the history of gets that history rather than walking
+    /// it again. Only the branches `only` picks are read, if it is given.
+    pub fn stream_branches_cached(
+        &self,
+        typ: Option<BranchType>,
+        cached: Cached,
+        only: Option<Recent>,
+    ) -> mpsc::Receiver<Result<Branch>> {
+        self.stream(typ, HashMap::new(), cached, true, only)
+    }
+
+    /// Streams every branch with its tip commit alone, marked partial, which is
+    /// quick to read however long the histories are. The tips in `known` are
+    /// not even read, and a branch whose ref points at a tip `cached` has the
+    /// history of gets that history in full. Only the branches `only` picks
+    /// are read, if it is given.
+    pub fn stream_branch_tips(
+        &self,
+        typ: Option<BranchType>,
+        known: HashMap<git2::Oid, Commit>,
+        cached: Cached,
+        only: Option<Recent>,
+    ) -> mpsc::Receiver<Result<Branch>> {
+        self.stream(typ, known, cached, false, only)
+    }
+
+    /// Streams branches, taking the history of those `cached` has at their
+    /// tip, and reading the history of the others whose tip is not
+    /// in `known` if `history`, and their tip alone otherwise.
+    fn stream(
+        &self,
+        typ: Option<BranchType>,
+        known: HashMap<git2::Oid, Commit>,
+        cached: Cached,
+        history: bool,
+        only: Option<Recent>,
+    ) -> mpsc::Receiver<Result<Branch>> {
+        let (tx, rx) = mpsc::channel();
+        let handle = self.clone();
+        let (max, replaced) = (self.max_commits, self.replace);
+        let res = self.submit(move |repo| {
+            let send = |branch: Result<Branch>| tx.send(branch).is_ok();
+            // ranked here rather than by the caller, as it peels every tip
+            let only = match only.map(|only| only.pick(repo)).transpose() {
+                Ok(only) => only,
+                Err(err) => {
+                    send(Err(err));
+                    return;
+                }
+            };
+            // a tip known from before may be one that is replaced now
+            let (known, lookup) = match Replacements::read(repo) {
+                Ok(replacements) if replacements.is_empty() => (known, Some(cached)),
+                _ => (HashMap::new(), None),
+            };
+            let (mut cached, mut walked) = (0, 0);
+            let iter = match repo.branches(typ).context("repo branches") {
+                Ok(iter) => iter,
+                Err(err) => {
+                    send(Err(err));
+                    return;
+                }
+            };
+            for br_res in iter {
+                let res = br_res.context("branch").and_then(|(br, typ)| {
+                    let name = br.name().context("branch name")?.map(ToOwned::to_owned);
+                    let Some(name) = name else {
+                        return Ok(None);
+                    };
+                    let refname = br.get().name().unwrap_or_default();
+                    if only.as_ref().is_some_and(|only| !only.contains(refname)) {
+                        return Ok(None);
+                    }
+                    let mut branch = Branch::new(&handle, &name, typ);
+                    branch.symbolic = branch::symbolic_target(br.get());
+                    let target = br.get().target();
+                    let unmoved = target
+                        .zip(lookup.as_ref())
+                        .and_then(|(tip, lookup)| lookup(refname, tip))
+                        .filter(|h| h.tip() == target);
+                    match (unmoved, target.and_then(|id| known.get(&id).cloned())) {
+                        (Some(history), _) => {
+                            branch.set_history(history);
+                            cached += 1;
+                        }
+                        (None, Some(tip)) => {
+                            branch.commits = vec![tip];
+                            branch.partial = true;
+                        }
+                        (None, None) if history => {
+                            (branch.commits, branch.truncated) =
+                                branch::walk(repo, &name, typ, max, replaced, &|| false)
+                                    .with_context(|| format!("load {name}"))?;
+                            walked += 1;
+                        }
+                        (None, None) => {
+                            (branch.commits, _) =
+                                branch::walk(repo, &name, typ, 1, replaced, &|| false)
+                                    .with_context(|| format!("load {name}"))?;
+                            branch.partial = true;
+                        }
+                    }
+                    if typ == BranchType::Local {
+                        branch.tracking = Some(branch::Tracking::of(repo, &br));
+                        branch.upstream = br
+                            .upstream()
+                            .ok()
+                            .and_then(|u| u.name().ok().flatten().map(ToOwned::to_owned));
+                    }
+                    Ok(Some(branch))
+                });
+                match res {
+                    Ok(Some(branch)) => {
+                        if !send(Ok(branch)) {
+                            return;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        send(Err(err));
+                        return;
+                    }
+                }
+            }
+            tracing::debug!(cached, walked, "branches read");
+        });
+        if let Err(err) = res {
+            let (tx, rx) = mpsc::channel();
+            let _ = tx.send(Err(err));
+            return rx;
+        }
+        rx
+    }
+
+    /// Computes repository statistics on the worker, delivering them on the
+    /// returned channel when done.
+    pub fn stats(&self) -> mpsc::Receiver<Result<stats::Stats>> {
+        let (tx, rx) = mpsc::channel();
+        let now = chrono::Utc::now().timestamp();
+        let err_tx = tx.clone();
+        if let Err(err) = self.submit(move |repo| {
+            let _ = tx.send(stats::compute(repo, now));
+        }) {
+            let _ = err_tx.send(Err(err));
+        }
+        rx
+    }
+}
+
+fn not_a_repo(err: git2::Error, path: &std::path::Path) -> Error {
+    match err.code() {
+        git2::ErrorCode::NotFound => Error::NotARepo(path.to_path_buf()),
+        _ => err.into(),
+    }
+}
+
+fn env_path(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .filter(|val| !val.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The arguments of `git checkout` for checking out `name`, tracking it as a
+/// new local branch if it is a remote one.
+/// The git arguments that check out `local`, creating it to track `upstream`
+/// if it is given.
+fn switch_args<'a>(local: &'a str, upstream: Option<&'a str>) -> Vec<&'a str> {
+    match upstream {
+        Some(upstream) => vec!["checkout", "-b", local, "--track", upstream],
+        None => vec!["checkout", local],
+    }
+}
+
+fn checkout_args(name: &str, typ: BranchType) -> Vec<&str> {
+    match typ {
+        BranchType::Local => vec!["checkout", name],
+        BranchType::Remote => vec!["checkout", "--track", name],
+    }
+}
@@ -0,0 +1,56 @@
+//! Clones that lack part of the history: shallow ones, which stop below some
+//! commits, and partial ones, which fetch the objects they left out when they
+//! are needed.
+
+use std::collections::HashSet;
+
+/// How complete a clone is, as [`crate::Repository::completeness`] finds it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Completeness {
+    /// Whether the history stops below some commits, whose parents are not in
+    /// the clone.
+    pub shallow: bool,
+    /// The filter of a partial clone, e.g. `blob:none`, empty for a promisor
+    /// remote without one.
+    pub partial: Option<String>,
+}
+
+pub(crate) fn of(repo: &git2::Repository) -> Completeness {
+    Completeness {
+        shallow: repo.is_shallow(),
+        partial: partial(repo),
+    }
+}
+
+/// The commits a shallow clone stops at, listed in `shallow` of the git dir.
+pub(crate) fn boundary(repo: &git2::Repository) -> HashSet<git2::Oid> {
+    // kept in the main git dir, which linked worktrees share
+    let path = crate::command::common_dir(repo.path()).join("shallow");
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| git2::Oid::from_str(line.trim()).ok())
+        .collect()
+}
+
+fn partial(repo: &git2::Repository) -> Option<String> {
+    let config = repo.config().ok()?;
+    let remotes = repo.remotes().ok()?;
+    let filter = remotes.iter().flatten().find_map(|name| {
+        let filter = config.get_string(&format!("remote.{name}.partialclonefilter"));
+        let promisor = config.get_bool(&format!("remote.{name}.promisor"));
+        match (filter, promisor) {
+            (Ok(filter), _) => Some(filter),
+            (_, Ok(true)) => Some(String::new()),
+            _ => None,
+        }
+    });
+    // git before 2.26 recorded the filter of the one promisor remote here
+    filter.or_else(|| {
+        config.get_string("extensions.partialclone").ok().map(|_| {
+            config
+                .get_string("core.partialclonefilter")
+                .unwrap_or_default()
+        })
+    })
+}
@@ -0,0 +1,143 @@
+//! Stacked branches: local branches built on other local branches rather than
+//! on the default branch, as with pull requests that depend on each other. A
+//! branch counts as stacked on another if it contains a commit that other
+//! branch has had as its tip, as its reflog remembers, and the default branch
+//! does not contain. When that is not the current tip, the other branch has
+//! moved on since, e.g. by an amend or a rebase, and the stacked one has to be
+//! rebased onto it again.
+
+use crate::error::{Context, Result};
+use std::collections::HashMap;
+
+/// Where a stacked branch sits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Link {
+    /// The local branch it is built on.
+    pub parent: String,
+    /// The tip the parent had when it was built on it, which is the current
+    /// one unless the parent moved on.
+    pub base: git2::Oid,
+    /// Whether the parent has moved on since, leaving it on an old tip.
+    pub stale: bool,
+}
+
+/// How many of its past tips a branch is recognized by.
+const PAST_TIPS: usize = 20;
+/// How far back the history of a branch is looked through for a parent.
+const MAX_DEPTH: usize = 1000;
+
+struct Tips {
+    name: String,
+    current: git2::Oid,
+}
+
+/// The links of the stacked local branches, by name.
+pub(crate) fn links(repo: &git2::Repository) -> Result<HashMap<String, Link>> {
+    let base = super::stats::default_branch(repo);
+    // the default branch is the bottom of every stack, under its local name
+    let root = base
+        .as_ref()
+        .map(|(name, _)| name.split_once('/').map_or(name.as_str(), |(_, n)| n));
+    let descends = |id: git2::Oid, of: git2::Oid| repo.graph_descendant_of(id, of).unwrap_or(false);
+    let mut branches = vec![];
+    // which branches had each tip, by index into `branches`
+    let mut had: HashMap<git2::Oid, Vec<usize>> = HashMap::new();
+    for entry in repo
+        .branches(Some(git2::BranchType::Local))
+        .context("list branches")?
+    {
+        let (branch, _) = entry.context("branch")?;
+        let (Some(name), Some(current)) = (branch.name().ok().flatten(), branch.get().target())
+        else {
+            continue;
+        };
+        if Some(name) == root {
+            continue;
+        }
+        // the tips it had, leaving out those it was created or reset at, which
+        // are commits of other branches
+        let mut own = vec![current];
+        if let Ok(log) = repo.reflog(&format!("refs/heads/{name}")) {
+            own.extend(
+                log.iter()
+                    .filter(|entry| {
+                        let message = entry.message().unwrap_or_default();
+                        !message.starts_with("branch: Created") && !message.starts_with("reset:")
+                    })
+                    .map(|entry| entry.id_new())
+                    .take(PAST_TIPS),
+            );
+        }
+        own.sort();
+        own.dedup();
+        for tip in own {
+            had.entry(tip).or_default().push(branches.len());
+        }
+        branches.push(Tips {
+            name: name.to_string(),
+            current,
+        });
+    }
+    let mut links = HashMap::new();
+    for child in &branches {
+        // the nearest commit of its own history, short of the default branch,
+        // that another branch has had as its tip
+        let mut walk = repo.revwalk().context("walk")?;
+        walk.set_sorting(git2::Sort::TOPOLOGICAL).context("sort")?;
+        walk.push(child.current).context("walk from the tip")?;
+        if let Some((_, base)) = &base {
+            walk.hide(*base).context("hide the default branch")?;
+        }
+        let mut found = None;
+        for id in walk.skip(1).take(MAX_DEPTH) {
+            let id = id.context("walk")?;
+            let Some(owners) = had.get(&id) else {
+                continue;
+            };
+            // a branch ahead of this one is stacked on it, not under it
+            let parents: Vec<_> = owners
+                .iter()
+                .map(|&i| &branches[i])
+                .filter(|parent| {
+                    parent.name != child.name
+                        && parent.current != child.current
+                        && !descends(parent.current, child.current)
+                })
+                .collect();
+            let current = parents.iter().find(|parent| parent.current == id);
+            if let Some(parent) = current.or(parents.first()) {
+                found = Some((id, *parent));
+                break;
+            }
+        }
+        if let Some((tip, parent)) = found {
+            let stale = tip != parent.current && !descends(child.current, parent.current);
+            let link = Link {
+                parent: parent.name.clone(),
+                base: tip,
+                stale,
+            };
+            links.insert(child.name.clone(), link);
+        }
+    }
+    // two branches each built on a past tip of the other stack on neither
+    let cyclic: Vec<String> = links
+        .keys()
+        .filter(|name| {
+            let mut at = links.get(*name);
+            for _ in 0..links.len() {
+                match at {
+                    Some(link) if link.parent == **name => return true,
+                    Some(link) => at = links.get(&link.parent),
+                    None => return false,
+                }
+            }
+            false
+        })
+        .cloned()
+        .collect();
+    for name in cyclic {
+        links.remove(&name);
+    }
+    Ok(links)
+}
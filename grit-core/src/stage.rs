@@ -0,0 +1,103 @@
+//! Staging and unstaging a file hunk by hunk, the way `git add -p` and
+//! `git reset -p` do, by applying one hunk of a diff to the index.
+
+use crate::error::{Context, Error, Result};
+use git2::{ApplyLocation, ApplyOptions, Diff, DiffOptions, Patch};
+use std::path::Path;
+
+/// A hunk of the changes to a file, staged or not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The `@@ -a,b +c,d @@` line, with the function git found, if any.
+    pub header: String,
+    /// Each line with its origin: `+`, `-` or a space for context.
+    pub lines: Vec<(char, String)>,
+}
+
+/// The hunks of the changes to `path` in the work tree that are not staged, or
+/// of those that are if `staged`.
+pub(crate) fn hunks(repo: &git2::Repository, path: &str, staged: bool) -> Result<Vec<Hunk>> {
+    let diff = diff(repo, path, staged, false)?;
+    let mut hunks = vec![];
+    for idx in 0..diff.deltas().len() {
+        let Some(patch) = Patch::from_diff(&diff, idx).context("read patch")? else {
+            // binary
+            continue;
+        };
+        for h in 0..patch.num_hunks() {
+            let (hunk, count) = patch.hunk(h).context("read hunk")?;
+            let mut lines = Vec::with_capacity(count);
+            for l in 0..count {
+                let line = patch.line_in_hunk(h, l).context("read hunk line")?;
+                let text = String::from_utf8_lossy(line.content());
+                lines.push((line.origin(), text.trim_end_matches('\n').to_string()));
+            }
+            let header = String::from_utf8_lossy(hunk.header())
+                .trim_end()
+                .to_string();
+            hunks.push(Hunk { header, lines });
+        }
+    }
+    Ok(hunks)
+}
+
+/// Stages the hunk at `idx` of the unstaged changes to `path`, or unstages it
+/// from the staged ones if `staged`. `header` is that of the hunk as it was
+/// read, so that one that changed since is not applied in its place.
+pub(crate) fn apply(
+    repo: &git2::Repository,
+    path: &str,
+    staged: bool,
+    idx: usize,
+    header: &str,
+) -> Result<()> {
+    let mut index = repo.index().context("read index")?;
+    // the stages of a conflict: the base, ours and theirs
+    if (1..=3).any(|stage| index.get_path(Path::new(path), stage).is_some()) {
+        return Err(Error::Conflicted(path.to_string()));
+    }
+    let current = hunks(repo, path, staged)?;
+    if current.get(idx).map(|h| h.header.as_str()) != Some(header) {
+        return Err(Error::Moved(path.to_string()));
+    }
+    if !staged && index.get_path(Path::new(path), 0).is_none() {
+        // an untracked file is all one hunk, which git cannot apply to an
+        // index without the file, so it is added whole
+        index.add_path(Path::new(path)).context("add to index")?;
+        return index.write().context("write index");
+    }
+    // unstaging applies the staged changes backwards, which git lists in the
+    // same order
+    let diff = diff(repo, path, staged, staged)?;
+    let mut at = 0;
+    let mut opts = ApplyOptions::new();
+    opts.hunk_callback(|_| {
+        at += 1;
+        at - 1 == idx
+    });
+    repo.apply(&diff, ApplyLocation::Index, Some(&mut opts))
+        .with_context(|| format!("apply hunk to {path}"))
+}
+
+fn diff<'r>(
+    repo: &'r git2::Repository,
+    path: &str,
+    staged: bool,
+    reverse: bool,
+) -> Result<Diff<'r>> {
+    let mut opts = DiffOptions::new();
+    opts.pathspec(path)
+        .disable_pathspec_match(true)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true)
+        .reverse(reverse);
+    let diff = match staged {
+        true => {
+            let head = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+            repo.diff_tree_to_index(head.as_ref(), None, Some(&mut opts))
+        }
+        false => repo.diff_index_to_workdir(None, Some(&mut opts)),
+    };
+    diff.with_context(|| format!("diff {path}"))
+}
@@ -0,0 +1,113 @@
+//! Repository-wide statistics for the dashboard.
+
+use crate::error::{Context, Result};
+use git2::BranchType;
+use std::collections::HashSet;
+
+/// Branches without a commit for this long count as stale.
+pub const STALE_AFTER_DAYS: i64 = 90;
+/// How many weeks of commit volume are collected.
+pub const WEEKS: usize = 26;
+/// Caps the commits walked for authors and volume in large repositories.
+const MAX_COMMITS: usize = 20_000;
+
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    pub local: usize,
+    pub remote: usize,
+    pub stale_local: usize,
+    pub stale_remote: usize,
+    /// Authors by commit count across local branches, most active first.
+    pub authors: Vec<(String, u64)>,
+    /// Commits per week across local branches, oldest week first.
+    pub weekly: Vec<u64>,
+    /// The branch that divergence is measured against.
+    pub default_branch: Option<String>,
+    /// Local branches with the commits they are ahead and behind the default
+    /// branch, most diverged first.
+    pub divergence: Vec<(String, usize, usize)>,
+}
+
+pub(super) fn compute(repo: &git2::Repository, now: i64) -> Result<Stats> {
+    let mut stats = Stats::default();
+    let stale_before = now - STALE_AFTER_DAYS * 24 * 60 * 60;
+    let mut tips = vec![];
+    for res in repo.branches(None).context("list branches")? {
+        let (branch, typ) = res.context("branch")?;
+        let Ok(commit) = branch.get().peel_to_commit() else {
+            continue;
+        };
+        let stale = commit.time().seconds() < stale_before;
+        match typ {
+            BranchType::Local => {
+                stats.local += 1;
+                stats.stale_local += usize::from(stale);
+                if let Some(name) = branch.name().ok().flatten() {
+                    tips.push((name.to_string(), commit.id()));
+                }
+            }
+            BranchType::Remote => {
+                stats.remote += 1;
+                stats.stale_remote += usize::from(stale);
+            }
+        }
+    }
+
+    let mut walk = repo.revwalk().context("revwalk")?;
+    for (_, id) in &tips {
+        walk.push(*id).context("revwalk push")?;
+    }
+    let mut authors: Vec<(String, u64)> = vec![];
+    let mut weekly = vec![0u64; WEEKS];
+    for id in walk.take(MAX_COMMITS).flatten() {
+        let Ok(commit) = repo.find_commit(id) else {
+            continue;
+        };
+        let name = commit.author().name().unwrap_or("?").to_string();
+        match authors.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, count)) => *count += 1,
+            None => authors.push((name, 1)),
+        }
+        let age = (now - commit.time().seconds()).max(0) / (7 * 24 * 60 * 60);
+        if let Some(week) = usize::try_from(age)
+            .ok()
+            .and_then(|a| WEEKS.checked_sub(a + 1))
+        {
+            weekly[week] += 1;
+        }
+    }
+    authors.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    stats.authors = authors;
+    stats.weekly = weekly;
+
+    if let Some((name, base)) = default_branch(repo) {
+        let mut seen = HashSet::new();
+        for (branch, id) in &tips {
+            if !seen.insert(*id) || *id == base {
+                continue;
+            }
+            if let Ok((ahead, behind)) = repo.graph_ahead_behind(*id, base) {
+                stats.divergence.push((branch.clone(), ahead, behind));
+            }
+        }
+        stats
+            .divergence
+            .sort_by_key(|(_, ahead, behind)| std::cmp::Reverse(ahead + behind));
+        stats.default_branch = Some(name);
+    }
+    Ok(stats)
+}
+
+/// The remote's default branch if known, otherwise a local `main` or `master`.
+pub(crate) fn default_branch(repo: &git2::Repository) -> Option<(String, git2::Oid)> {
+    let candidates = [
+        "refs/remotes/origin/HEAD",
+        "refs/heads/main",
+        "refs/heads/master",
+    ];
+    candidates.iter().find_map(|name| {
+        let reference = repo.find_reference(name).ok()?.resolve().ok()?;
+        let id = reference.peel_to_commit().ok()?.id();
+        Some((reference.shorthand()?.to_string(), id))
+    })
+}
@@ -0,0 +1,148 @@
+//! How much is uncommitted in the work tree and index, and how many stashes
+//! there are, as counts for a glance, and the files themselves for a closer
+//! look. Ignored files are those of `.gitignore`, `.git/info/exclude` and
+//! `core.excludesFile`, as git has them.
+
+use crate::error::{Context, Result};
+use git2::Status as Flags;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Status {
+    /// Files changed in the work tree but not staged, untracked ones included.
+    pub modified: usize,
+    /// Files with staged changes.
+    pub staged: usize,
+    pub stashes: usize,
+}
+
+impl Status {
+    pub fn is_dirty(&self) -> bool {
+        self.modified > 0 || self.staged > 0
+    }
+}
+
+/// A file with uncommitted changes, or untracked or ignored, the way
+/// `git status --short --ignored` lists it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStatus {
+    /// Relative to the top of the work tree. A directory, with a trailing
+    /// slash, stands for all the untracked or ignored files in it.
+    pub path: String,
+    /// The state in the index, e.g. `M` or `A`, a space if unchanged.
+    pub index: char,
+    /// The state in the work tree, likewise.
+    pub worktree: char,
+}
+
+impl FileStatus {
+    pub fn is_untracked(&self) -> bool {
+        self.index == '?'
+    }
+
+    pub fn is_ignored(&self) -> bool {
+        self.index == '!'
+    }
+
+    /// Whether a merge left conflicts in it to resolve.
+    pub fn is_conflicted(&self) -> bool {
+        self.index == 'U'
+    }
+}
+
+/// The files `git status` would list, ignored ones too if `ignored`.
+pub(crate) fn files(repo: &git2::Repository, ignored: bool) -> Result<Vec<FileStatus>> {
+    if repo.is_bare() {
+        return Ok(vec![]);
+    }
+    let mut opts = git2::StatusOptions::new();
+    // like git, untracked and ignored directories are listed as one entry
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .include_ignored(ignored)
+        .recurse_ignored_dirs(false)
+        .renames_head_to_index(true);
+    let statuses = repo.statuses(Some(&mut opts)).context("read status")?;
+    let files = statuses
+        .iter()
+        .filter_map(|entry| {
+            let (index, worktree) = codes(entry.status())?;
+            // a staged rename is listed under its new name
+            let renamed = entry
+                .head_to_index()
+                .filter(|_| index == 'R')
+                .and_then(|delta| {
+                    delta
+                        .new_file()
+                        .path()
+                        .map(|p| p.to_string_lossy().into_owned())
+                });
+            let path = renamed.or_else(|| entry.path().map(ToOwned::to_owned))?;
+            Some(FileStatus {
+                path,
+                index,
+                worktree,
+            })
+        })
+        .collect();
+    Ok(files)
+}
+
+/// The two letters of `git status --short` for `flags`.
+fn codes(flags: Flags) -> Option<(char, char)> {
+    if flags.is_conflicted() {
+        return Some(('U', 'U'));
+    }
+    if flags.is_ignored() {
+        return Some(('!', '!'));
+    }
+    if flags.is_wt_new() {
+        return Some(('?', '?'));
+    }
+    let index = match flags {
+        f if f.is_index_new() => 'A',
+        f if f.is_index_modified() => 'M',
+        f if f.is_index_deleted() => 'D',
+        f if f.is_index_renamed() => 'R',
+        f if f.is_index_typechange() => 'T',
+        _ => ' ',
+    };
+    let worktree = match flags {
+        f if f.is_wt_modified() => 'M',
+        f if f.is_wt_deleted() => 'D',
+        f if f.is_wt_renamed() => 'R',
+        f if f.is_wt_typechange() => 'T',
+        _ => ' ',
+    };
+    (index != ' ' || worktree != ' ').then_some((index, worktree))
+}
+
+pub(crate) fn read(repo: &git2::Repository) -> Result<Status> {
+    let mut status = Status::default();
+    if !repo.is_bare() {
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(true).include_ignored(false);
+        let statuses = repo.statuses(Some(&mut opts)).context("read status")?;
+        let worktree = Flags::WT_NEW
+            | Flags::WT_MODIFIED
+            | Flags::WT_DELETED
+            | Flags::WT_RENAMED
+            | Flags::WT_TYPECHANGE
+            | Flags::CONFLICTED;
+        let index = Flags::INDEX_NEW
+            | Flags::INDEX_MODIFIED
+            | Flags::INDEX_DELETED
+            | Flags::INDEX_RENAMED
+            | Flags::INDEX_TYPECHANGE;
+        for entry in statuses.iter() {
+            let flags = entry.status();
+            status.modified += usize::from(flags.intersects(worktree));
+            status.staged += usize::from(flags.intersects(index));
+        }
+    }
+    // each stash is an entry in the reflog of refs/stash
+    status.stashes = match repo.find_reference("refs/stash") {
+        Ok(_) => repo.reflog("refs/stash").context("read stashes")?.len(),
+        Err(_) => 0,
+    };
+    Ok(status)
+}
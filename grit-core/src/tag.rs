@@ -0,0 +1,52 @@
+//! The tags of the repository, with the commits they were put on.
+
+use crate::{
+    branch::{Author, Commit},
+    error::{Context, Result},
+};
+
+#[derive(Clone)]
+pub struct Tag {
+    /// The short name, e.g. `v1.2.0`.
+    pub name: String,
+    /// The commit the tag peels to, if it does.
+    pub commit: Option<Commit>,
+    /// The message of an annotated tag, `None` for a lightweight one.
+    pub message: Option<String>,
+    /// Who made an annotated tag, if it says.
+    pub tagger: Option<Author>,
+}
+
+/// The tags of `repo`, those on the newest commits first.
+pub(crate) fn list(repo: &git2::Repository) -> Result<Vec<Tag>> {
+    let names = repo.tag_names(None).context("list tags")?;
+    let mut tags = vec![];
+    for name in names.iter().flatten() {
+        let reference = repo
+            .find_reference(&format!("refs/tags/{name}"))
+            .with_context(|| format!("find tag {name}"))?;
+        let annotated = reference.peel_to_tag().ok();
+        let commit = reference
+            .peel_to_commit()
+            .ok()
+            .map(Commit::try_from)
+            .transpose()
+            .with_context(|| format!("read the commit of {name}"))?;
+        tags.push(Tag {
+            name: name.to_string(),
+            commit,
+            message: annotated
+                .as_ref()
+                .map(|tag| tag.message().unwrap_or_default().trim_end().to_string()),
+            tagger: annotated
+                .as_ref()
+                .and_then(git2::Tag::tagger)
+                .map(Author::from),
+        });
+    }
+    tags.sort_by(|a, b| {
+        let epoch = |t: &Tag| t.commit.as_ref().map(|c| c.timestamp.epoch());
+        epoch(b).cmp(&epoch(a)).then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(tags)
+}
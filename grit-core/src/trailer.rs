@@ -0,0 +1,54 @@
+//! Trailers such as `Signed-off-by:` at the end of commit messages.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trailer {
+    pub key: String,
+    pub value: String,
+}
+
+impl Trailer {
+    /// The name of a `Name <email>` value, or the whole value otherwise.
+    pub fn name(&self) -> &str {
+        match self.value.split_once('<') {
+            Some((name, _)) if !name.trim().is_empty() => name.trim(),
+            _ => self.value.as_str(),
+        }
+    }
+
+    pub fn is(&self, key: &str) -> bool {
+        self.key.eq_ignore_ascii_case(key)
+    }
+}
+
+/// Parses the trailers in the last paragraph of a message. As with git, the
+/// paragraph only counts as trailers if every line is one, allowing for indented
+/// continuation lines. The subject line never holds trailers.
+pub fn parse(message: &str) -> Vec<Trailer> {
+    let message = message.trim_end();
+    let Some((_, body)) = message.split_once('\n') else {
+        return vec![];
+    };
+    let last = body.trim_start().rsplit("\n\n").next().unwrap_or_default();
+    let mut trailers: Vec<Trailer> = vec![];
+    for line in last.lines() {
+        if line.starts_with([' ', '\t']) {
+            let Some(prev) = trailers.last_mut() else {
+                return vec![];
+            };
+            prev.value.push(' ');
+            prev.value.push_str(line.trim());
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            return vec![];
+        };
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return vec![];
+        }
+        trailers.push(Trailer {
+            key: key.to_string(),
+            value: value.trim().to_string(),
+        });
+    }
+    trailers
+}
@@ -0,0 +1,101 @@
+//! Notices changes to a repository made by other programs, so that what is
+//! shown can be read again. A fetch or a rebase touches the git dir many times
+//! over, so changes are taken in batches: at most one per [`MIN_INTERVAL`], once
+//! the changes have paused for [`QUIET`] and nothing holds the index lock.
+
+use crate::error::{Context, Result};
+use notify::{RecursiveMode, Watcher};
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+/// The least time between two batches.
+const MIN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long the changes have to pause before a batch is taken in.
+const QUIET: Duration = Duration::from_millis(100);
+
+/// How often to look again while the index is locked.
+const LOCK_POLL: Duration = Duration::from_millis(100);
+
+pub struct Watch {
+    /// Kept for as long as the watch is, which stops when it is dropped.
+    _watcher: notify::RecommendedWatcher,
+    rx: mpsc::Receiver<()>,
+    /// The index lock of the worktree, held while git writes the index.
+    lock: PathBuf,
+    /// When the latest change not yet taken in happened.
+    changed: Option<Instant>,
+    /// When the last batch was.
+    taken: Option<Instant>,
+}
+
+impl Watch {
+    /// Watches `git_dir`, the common one for linked worktrees, which holds the
+    /// refs of all of them. `lock` is the index lock of this worktree.
+    pub(crate) fn new(git_dir: PathBuf, lock: PathBuf) -> Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let root = git_dir.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if event.paths.iter().any(|path| relevant(&root, path)) {
+                    let _ = tx.send(());
+                }
+            })
+            .context("start watching")?;
+        watcher
+            .watch(&git_dir, RecursiveMode::Recursive)
+            .with_context(|| format!("watch {}", git_dir.display()))?;
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            lock,
+            changed: None,
+            taken: None,
+        })
+    }
+
+    /// Takes in the changes since the last call, returning whether the
+    /// repository should be read again now.
+    pub fn poll(&mut self) -> bool {
+        while self.rx.try_recv().is_ok() {
+            self.changed = Some(Instant::now());
+        }
+        if !self.wait().is_some_and(|wait| wait.is_zero()) {
+            return false;
+        }
+        self.changed = None;
+        self.taken = Some(Instant::now());
+        true
+    }
+
+    /// How long until the next batch may be taken in, if there are changes.
+    pub fn wait(&self) -> Option<Duration> {
+        let quiet = QUIET.saturating_sub(self.changed?.elapsed());
+        let interval = self.taken.map_or(Duration::ZERO, |at| {
+            MIN_INTERVAL.saturating_sub(at.elapsed())
+        });
+        let wait = quiet.max(interval);
+        // what is read while git writes the index would be half done; the
+        // lock going away is not watched for, only looked at
+        if wait.is_zero() && self.lock.exists() {
+            return Some(LOCK_POLL);
+        }
+        Some(wait)
+    }
+}
+
+/// Whether a change to `path` can change what is shown. Objects are written in
+/// bulk by fetches and only matter once a ref points at them.
+fn relevant(git_dir: &Path, path: &Path) -> bool {
+    let Ok(rel) = path.strip_prefix(git_dir) else {
+        return true;
+    };
+    !rel.components()
+        .any(|c| matches!(c, Component::Normal(name) if name == "objects" || name == "logs"))
+}
@@ -0,0 +1,100 @@
+//! A dedicated thread that owns the `git2::Repository`.
+//!
+//! libgit2 repositories are `Send` but not `Sync`, so instead of sharing one
+//! between threads every operation is shipped to the worker as a closure and the
+//! result is sent back over a channel. Requests run one at a time in the order they
+//! were sent.
+
+use crate::error::{Context, Error, Result};
+use std::{sync::mpsc, thread};
+
+enum Request {
+    Run(Box<dyn FnOnce(&git2::Repository) + Send>),
+    /// Opens the repository again, for what libgit2 only reads when opening it.
+    Reopen(mpsc::SyncSender<Result<()>>),
+}
+
+#[derive(Clone)]
+pub struct Handle {
+    tx: mpsc::Sender<Request>,
+}
+
+impl std::fmt::Debug for Handle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<<git worker>>")
+    }
+}
+
+/// Moves the repository onto a new worker thread. The thread exits once every
+/// handle has been dropped.
+pub fn spawn(mut repo: git2::Repository) -> Result<Handle> {
+    crate::branch::use_abbrev(&repo);
+    let (tx, rx) = mpsc::channel::<Request>();
+    thread::Builder::new()
+        .name("git".into())
+        .spawn(move || {
+            for req in rx {
+                match req {
+                    Request::Run(f) => f(&repo),
+                    Request::Reopen(done) => {
+                        let res = reopen(&repo).map(|reopened| {
+                            crate::branch::use_abbrev(&reopened);
+                            repo = reopened;
+                        });
+                        let _ = done.send(res);
+                    }
+                }
+            }
+        })
+        .context("spawn git worker")?;
+    Ok(Handle { tx })
+}
+
+/// Opens `repo` again with the same git dir and work tree.
+fn reopen(repo: &git2::Repository) -> Result<git2::Repository> {
+    // not `open_bare`, which misses the commits a shallow clone stops at
+    let reopened = git2::Repository::open(repo.path()).context("reopen repo")?;
+    if let Some(work_tree) = repo.workdir() {
+        reopened
+            .set_workdir(work_tree, false)
+            .context("set work tree")?;
+    }
+    Ok(reopened)
+}
+
+impl Handle {
+    /// Runs `f` on the worker and waits for its result.
+    ///
+    /// Must not be called from within another request, which would deadlock.
+    pub fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&git2::Repository) -> T + Send + 'static,
+    {
+        let (tx, rx) = mpsc::sync_channel(1);
+        self.submit(move |repo| {
+            let _ = tx.send(f(repo));
+        })?;
+        rx.recv().map_err(|_| Error::WorkerStopped)
+    }
+
+    /// Queues `f` on the worker without waiting for it.
+    pub fn submit<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce(&git2::Repository) + Send + 'static,
+    {
+        self.tx
+            .send(Request::Run(Box::new(f)))
+            .map_err(|_| Error::WorkerStopped)
+    }
+
+    /// Opens the repository again once the requests sent before are done, and
+    /// waits for it.
+    pub fn reopen(&self) -> Result<()> {
+        let (tx, rx) = mpsc::sync_channel(1);
+        self.tx
+            .send(Request::Reopen(tx))
+            .map_err(|_| Error::WorkerStopped)?;
+        rx.recv().map_err(|_| Error::WorkerStopped)?
+    }
+}
@@ -0,0 +1,58 @@
+//! The other worktrees of a repository, for the branches checked out in them,
+//! which git refuses to check out a second time.
+
+use crate::error::{Context, Result};
+use std::{collections::HashMap, path::PathBuf};
+
+/// The local branches checked out in worktrees other than this one, with the
+/// path of each worktree.
+pub(crate) fn others(repo: &git2::Repository) -> Result<HashMap<String, PathBuf>> {
+    let here = repo.workdir().and_then(|dir| dir.canonicalize().ok());
+    let mut branches = HashMap::new();
+    let mut add = |wt: &git2::Repository| {
+        let Some(dir) = wt.workdir() else {
+            return;
+        };
+        if dir.canonicalize().ok() == here {
+            return;
+        }
+        let Ok(head) = wt.head() else {
+            return;
+        };
+        if let (true, Some(name)) = (head.is_branch(), head.shorthand()) {
+            // without the trailing slash libgit2 gives it
+            branches.insert(name.to_string(), dir.components().collect());
+        }
+    };
+    if crate::command::is_linked(repo.path()) {
+        let common = crate::command::common_dir(repo.path());
+        let main = git2::Repository::open(common).context("open main worktree")?;
+        add(&main);
+    }
+    for name in repo.worktrees().context("list worktrees")?.iter().flatten() {
+        let wt = repo
+            .find_worktree(name)
+            .with_context(|| format!("find worktree {name}"))?;
+        // one whose directory is gone until it is pruned
+        if wt.validate().is_err() {
+            continue;
+        }
+        if let Ok(wt) = git2::Repository::open_from_worktree(&wt) {
+            add(&wt);
+        }
+    }
+    Ok(branches)
+}
+
+/// The directories of the linked worktrees of `repo`, those whose directory
+/// is gone included.
+pub(crate) fn paths(repo: &git2::Repository) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for name in repo.worktrees().context("list worktrees")?.iter().flatten() {
+        let wt = repo
+            .find_worktree(name)
+            .with_context(|| format!("find worktree {name}"))?;
+        paths.push(wt.path().components().collect());
+    }
+    Ok(paths)
+}